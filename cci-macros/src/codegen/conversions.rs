@@ -1,34 +1,53 @@
-use crate::preset::PresetFieldOpts;
+use super::ron_type::config_name;
+use crate::preset::{dependency_chain, PresetFieldOpts};
 use proc_macro2::TokenStream;
-use quote::{format_ident, quote};
+use quote::quote;
 
 pub fn generate_conversions(
     preset_ident: &syn::Ident,
     preset_id: &str,
+    group: bool,
     fields: &[PresetFieldOpts],
 ) -> TokenStream {
-    let config_name = format_ident!(
-        "{}Config",
-        preset_ident
-            .to_string()
-            .strip_suffix("Preset")
-            .unwrap_or(&preset_ident.to_string())
-    );
+    let config_name = config_name(preset_ident);
 
     // Generate from_config method (PresetConfig -> Preset instance)
     let from_config_impl = generate_from_config(preset_ident, fields);
 
-    // Generate ron_to_preset_config (RON -> PresetConfig)
-    let ron_to_preset_config = generate_ron_to_preset_config(preset_id, &config_name, fields);
+    // Generate ron_to_preset_config (RON -> PresetConfig); a group's options
+    // only go into the config of the preset flattening it
+    let ron_to_preset_config =
+        generate_ron_to_preset_config((!group).then_some(preset_id), &config_name, fields);
 
     // Generate preset_config_to_ron (PresetConfig -> RON)
     let preset_config_to_ron = generate_preset_config_to_ron(&config_name, fields);
 
+    // A group's values as its RON config, for the defaults presets give it
+    let to_ron = group.then(|| {
+        let ron_fields = fields.iter().map(|field| {
+            let field_ident = field.ident.as_ref().unwrap();
+            if field.flatten {
+                quote! { #field_ident: self.#field_ident.to_ron() }
+            } else {
+                quote! { #field_ident: self.#field_ident.clone() }
+            }
+        });
+        quote! {
+            #[allow(clippy::clone_on_copy)]
+            pub fn to_ron(&self) -> #config_name {
+                #config_name {
+                    #(#ron_fields),*
+                }
+            }
+        }
+    });
+
     quote! {
         impl #preset_ident {
             #from_config_impl
             #ron_to_preset_config
             #preset_config_to_ron
+            #to_ron
         }
     }
 }
@@ -41,8 +60,12 @@ fn generate_from_config(_preset_ident: &syn::Ident, fields: &[PresetFieldOpts])
         // Use field name as option ID
         let option_id = field_ident.to_string();
 
-        // Check if this is the version field (hidden=true)
-        if field.hidden {
+        if field.flatten {
+            quote! {
+                #field_ident: #field_ty::from_config(config, version)
+            }
+        } else if field.hidden {
+            // Check if this is the version field (hidden=true)
             // Version comes from the version parameter
             quote! {
                 #field_ident: version.to_string()
@@ -76,12 +99,24 @@ fn generate_from_config(_preset_ident: &syn::Ident, fields: &[PresetFieldOpts])
                             }
                         }
                     } else if type_str == "String" {
+                        let default_val = field.default.as_ref()
+                            .map(|s| s.parse::<TokenStream>().unwrap())
+                            .unwrap_or_else(|| quote! { String::new() });
                         quote! {
-                            #field_ident: config.get_string(#option_id).unwrap_or_else(|| "".to_string())
+                            #field_ident: config.get_string(#option_id).unwrap_or_else(|| #default_val)
+                        }
+                    } else if field.is_int() {
+                        let value = int_from_config(field);
+                        quote! {
+                            #field_ident: #value
                         }
                     } else if type_str == "bool" {
+                        // Off whenever an option it depends on is off
+                        let parents = dependency_chain(field, fields)
+                            .into_iter()
+                            .map(|parent| parent.ident.as_ref().unwrap().to_string());
                         quote! {
-                            #field_ident: config.get_bool(#option_id)
+                            #field_ident: config.get_bool(#option_id) #(&& config.get_bool(#parents))*
                         }
                     } else {
                         // Assume it's an enum with from_str method
@@ -111,7 +146,7 @@ fn generate_from_config(_preset_ident: &syn::Ident, fields: &[PresetFieldOpts])
 }
 
 fn generate_ron_to_preset_config(
-    preset_id: &str,
+    preset_id: Option<&str>,
     config_name: &syn::Ident,
     fields: &[PresetFieldOpts],
 ) -> TokenStream {
@@ -128,6 +163,12 @@ fn generate_ron_to_preset_config(
             return None;
         }
 
+        if field.flatten {
+            return Some(quote! {
+                #field_ty::ron_into_preset_config(&ron.#ron_field_name, config);
+            });
+        }
+
         match field_ty {
             syn::Type::Path(type_path) => {
                 let type_str = quote!(#type_path).to_string().replace(" ", "");
@@ -143,28 +184,32 @@ fn generate_ron_to_preset_config(
                     let inner_type_str = &type_str[inner_type_start..inner_type_end];
                     let inner_type = syn::parse_str::<syn::Type>(inner_type_str).unwrap();
 
+                    // `none` is a variant either way, so it can be chosen again
                     Some(quote! {
-                        if let Some(ref value) = ron.#ron_field_name {
-                            config.set(#option_id.to_string(), crate::editor::config::OptionValue::Enum {
-                                selected: value.as_str().to_string(),
-                                variants: #inner_type::all_variants().iter().map(|s| s.to_string()).collect(),
-                            });
-                        } else {
-                            // Set with empty selection for None
-                            config.set(#option_id.to_string(), crate::editor::config::OptionValue::Enum {
-                                selected: "none".to_string(),
-                                variants: {
-                                    let mut v = vec!["none".to_string()];
-                                    v.extend(#inner_type::all_variants().iter().map(|s| s.to_string()));
-                                    v
-                                },
-                            });
-                        }
+                        config.set(#option_id.to_string(), crate::editor::config::OptionValue::Enum {
+                            selected: ron
+                                .#ron_field_name
+                                .as_ref()
+                                .map_or("none", |value| value.as_str())
+                                .to_string(),
+                            variants: {
+                                let mut v = vec!["none".to_string()];
+                                v.extend(#inner_type::all_variants().iter().map(|s| s.to_string()));
+                                v
+                            },
+                        });
                     })
                 } else if type_str == "String" {
                     Some(quote! {
                         config.set(#option_id.to_string(), crate::editor::config::OptionValue::String(ron.#ron_field_name.clone()));
                     })
+                } else if field.is_int() {
+                    // Values past i32's range are kept as its maximum
+                    Some(quote! {
+                        config.set(#option_id.to_string(), crate::editor::config::OptionValue::Int(
+                            i32::try_from(ron.#ron_field_name).unwrap_or(i32::MAX),
+                        ));
+                    })
                 } else if type_str == "bool" {
                     Some(quote! {
                         config.set(#option_id.to_string(), crate::editor::config::OptionValue::Bool(ron.#ron_field_name));
@@ -183,12 +228,26 @@ fn generate_ron_to_preset_config(
         }
     });
 
+    let ron_to_preset_config = preset_id.map(|preset_id| {
+        quote! {
+            pub fn ron_to_preset_config(ron: #config_name) -> crate::editor::config::PresetConfig {
+                let mut config = crate::editor::config::PresetConfig::new(#preset_id.to_string());
+                Self::ron_into_preset_config(&ron, &mut config);
+                config
+            }
+        }
+    });
+
     quote! {
-        pub fn ron_to_preset_config(ron: #config_name) -> crate::editor::config::PresetConfig {
-            let mut config = crate::editor::config::PresetConfig::new(#preset_id.to_string());
+        /// Set the options `ron` has in `config`
+        pub fn ron_into_preset_config(
+            ron: &#config_name,
+            config: &mut crate::editor::config::PresetConfig,
+        ) {
             #(#set_statements)*
-            config
         }
+
+        #ron_to_preset_config
     }
 }
 
@@ -209,7 +268,11 @@ fn generate_preset_config_to_ron(
             .map(|s| s.parse::<TokenStream>().unwrap())
             .unwrap_or_else(|| quote! { Default::default() });
 
-        if field.hidden {
+        if field.flatten {
+            quote! {
+                #ron_field_name: #field_ty::preset_config_to_ron(config)
+            }
+        } else if field.hidden {
             // Version field - use default or from config
             match field_ty {
                 syn::Type::Path(type_path) => {
@@ -254,6 +317,11 @@ fn generate_preset_config_to_ron(
                         quote! {
                             #ron_field_name: config.get_string(#option_id).unwrap_or_else(|| #default_val)
                         }
+                    } else if field.is_int() {
+                        let value = int_from_config(field);
+                        quote! {
+                            #ron_field_name: #value
+                        }
                     } else if type_str == "bool" {
                         quote! {
                             #ron_field_name: config.get_bool(#option_id)
@@ -280,3 +348,21 @@ fn generate_preset_config_to_ron(
         }
     }
 }
+
+/// An integer field's value in `config`, or its default when it's unset or
+/// doesn't fit the field's type
+fn int_from_config(field: &PresetFieldOpts) -> TokenStream {
+    let field_ty = &field.ty;
+    let option_id = field.ident.as_ref().unwrap().to_string();
+    let default_val = field
+        .default
+        .as_ref()
+        .map(|s| s.parse::<TokenStream>().unwrap())
+        .unwrap_or_else(|| quote! { Default::default() });
+    quote! {
+        config
+            .get_int(#option_id)
+            .and_then(|value| #field_ty::try_from(value).ok())
+            .unwrap_or_else(|| #default_val)
+    }
+}
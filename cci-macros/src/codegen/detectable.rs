@@ -0,0 +1,60 @@
+use crate::preset::PresetOpts;
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// `Detectable` matching the `detect_uses` actions and `detect_run` commands;
+/// nothing when the preset has neither, so it keeps its own impl
+pub fn generate_detectable_impl(opts: &PresetOpts) -> TokenStream {
+    if opts.detect_uses.is_empty() && opts.detect_run.is_empty() {
+        return quote! {};
+    }
+    let preset_ident = &opts.ident;
+    let uses = &opts.detect_uses;
+    let run = &opts.detect_run;
+
+    // Without commands to look for, only the Actions workflows can match
+    let other_platforms = if run.is_empty() {
+        quote! {
+            fn matches_gitlab(&self, _config: &crate::platforms::gitlab::models::GitLabCI) -> bool {
+                false
+            }
+
+            fn matches_circleci(&self, _config: &crate::platforms::circleci::models::CircleCIConfig) -> bool {
+                false
+            }
+
+            fn matches_jenkins(&self, _pipeline: &crate::platforms::jenkins::models::JenkinsConfig) -> bool {
+                false
+            }
+        }
+    } else {
+        quote! {
+            fn matches_gitlab(&self, config: &crate::platforms::gitlab::models::GitLabCI) -> bool {
+                crate::detection::gitlab_runs(config, &[#(#run),*])
+            }
+
+            fn matches_circleci(&self, config: &crate::platforms::circleci::models::CircleCIConfig) -> bool {
+                crate::detection::circleci_runs(config, &[#(#run),*])
+            }
+
+            fn matches_jenkins(&self, pipeline: &crate::platforms::jenkins::models::JenkinsConfig) -> bool {
+                crate::detection::jenkins_runs(pipeline, &[#(#run),*])
+            }
+        }
+    };
+
+    quote! {
+        impl crate::traits::Detectable for #preset_ident {
+            fn matches_github(&self, workflow: &crate::platforms::github::models::GitHubWorkflow) -> bool {
+                crate::detection::github_matches(workflow, &[#(#uses),*], &[#(#run),*])
+            }
+
+            fn matches_gitea(&self, workflow: &crate::platforms::gitea::models::GiteaWorkflow) -> bool {
+                // Gitea Actions uses the same workflow format as GitHub Actions
+                crate::detection::github_matches(workflow, &[#(#uses),*], &[#(#run),*])
+            }
+
+            #other_platforms
+        }
+    }
+}
@@ -1,19 +1,27 @@
-use crate::preset::{PresetFieldOpts, PresetOpts};
+use crate::preset::{PresetFieldOpts, PresetOpts, INT_TYPES};
 use proc_macro2::TokenStream;
 use quote::quote;
-use std::collections::HashMap;
 
 pub fn generate_editor_preset_impl(opts: &PresetOpts, fields: &[PresetFieldOpts]) -> TokenStream {
     let preset_ident = &opts.ident;
+    let field_methods = generate_field_methods(preset_ident, opts.group, fields);
+    if opts.group {
+        return field_methods;
+    }
     let preset_id = &opts.id;
     let preset_name = &opts.name;
     let preset_description = &opts.description;
 
     let features_impl = generate_features_method(fields);
-    let default_config_impl = generate_default_config_method(preset_id, fields);
+    let default_config_impl = generate_default_config_method(preset_id);
     let matches_project_impl = generate_matches_project_method(&opts.matches);
+    let extract_config_impl = generate_extract_config_method();
+    let is_addon = opts.addon || opts.decorator;
+    let is_decorator = opts.decorator;
 
     quote! {
+        #field_methods
+
         impl crate::editor::config::EditorPreset for #preset_ident {
             fn preset_id(&self) -> &'static str {
                 #preset_id
@@ -31,8 +39,20 @@ pub fn generate_editor_preset_impl(opts: &PresetOpts, fields: &[PresetFieldOpts]
 
             #default_config_impl
 
+            fn config_schema(&self) -> serde_json::Value {
+                Self::schema()
+            }
+
             #matches_project_impl
 
+            fn is_addon(&self) -> bool {
+                #is_addon
+            }
+
+            fn is_decorator(&self) -> bool {
+                #is_decorator
+            }
+
             fn generate(
                 &self,
                 config: &crate::editor::config::PresetConfig,
@@ -42,13 +62,132 @@ pub fn generate_editor_preset_impl(opts: &PresetOpts, fields: &[PresetFieldOpts]
                 let preset = Self::from_config(config, language_version);
                 crate::platforms::helpers::generate_for_platform(&preset, platform)
             }
+
+            fn build(
+                &self,
+                config: &crate::editor::config::PresetConfig,
+                platform: crate::editor::state::Platform,
+                language_version: &str,
+            ) -> crate::error::Result<crate::platforms::helpers::PlatformConfig> {
+                let preset = Self::from_config(config, language_version);
+                crate::platforms::helpers::build_for_platform(&preset, platform)
+            }
+
+            fn decorate(
+                &self,
+                config: &crate::editor::config::PresetConfig,
+                language_version: &str,
+                pipeline: &mut crate::platforms::helpers::PlatformConfig,
+            ) -> crate::error::Result<()> {
+                let preset = Self::from_config(config, language_version);
+                crate::traits::DecoratesPipelines::decorate(&preset, pipeline)
+            }
+
+            fn required_secrets(
+                &self,
+                config: &crate::editor::config::PresetConfig,
+                platform: crate::editor::state::Platform,
+                language_version: &str,
+            ) -> Vec<crate::platforms::secrets::Secret> {
+                let preset = Self::from_config(config, language_version);
+                crate::traits::RequiresSecrets::required_secrets(&preset, platform)
+            }
+
+            fn support_files(
+                &self,
+                config: &crate::editor::config::PresetConfig,
+                platform: crate::editor::state::Platform,
+                language_version: &str,
+            ) -> crate::error::Result<Vec<(std::path::PathBuf, String)>> {
+                let preset = Self::from_config(config, language_version);
+                crate::traits::SupportFiles::support_files(&preset, platform)
+            }
+
+            fn local_checks(
+                &self,
+                config: &crate::editor::config::PresetConfig,
+                language_version: &str,
+            ) -> Vec<crate::platforms::checks::LocalCheck> {
+                let preset = Self::from_config(config, language_version);
+                crate::traits::LocalChecks::local_checks(&preset)
+            }
+
+            fn matches_github(
+                &self,
+                workflow: &crate::platforms::github::models::GitHubWorkflow,
+            ) -> bool {
+                crate::traits::Detectable::matches_github(self, workflow)
+            }
+
+            #extract_config_impl
+
+            fn matches_detection(
+                &self,
+                detection: &crate::detection::DetectionResult,
+                working_dir: &std::path::Path,
+            ) -> bool {
+                crate::editor::config::EditorPreset::matches_project(
+                    self,
+                    &detection.project_type,
+                    working_dir,
+                ) || crate::traits::DetectedDefaults::matches_metadata(self, &detection.metadata)
+            }
+
+            fn detected_config(
+                &self,
+                detection: &crate::detection::DetectionResult,
+            ) -> crate::editor::config::PresetConfig {
+                let mut config = crate::editor::config::EditorPreset::default_config(self, true);
+                crate::traits::DetectedDefaults::tune_defaults(self, &detection.metadata, &mut config);
+                config
+            }
+        }
+    }
+}
+
+/// Methods on the struct that the `EditorPreset` impl, and presets flattening
+/// a group, build on: setting the options' defaults, reading back GitHub
+/// steps and, for a group, listing its options
+fn generate_field_methods(
+    preset_ident: &syn::Ident,
+    group: bool,
+    fields: &[PresetFieldOpts],
+) -> TokenStream {
+    let set_defaults = generate_set_defaults_method(fields);
+    let set_values = group.then(|| generate_set_values_method(fields));
+    let read_github_steps = generate_read_github_steps_method(fields);
+    let options = group.then(|| {
+        let push_options = fields.iter().filter(|field| !field.hidden).map(|field| {
+            if field.flatten {
+                let field_ty = &field.ty;
+                quote! { options.extend(#field_ty::options()); }
+            } else {
+                let option_meta = option_meta(field);
+                quote! { options.push(#option_meta); }
+            }
+        });
+        quote! {
+            pub fn options() -> Vec<crate::editor::config::OptionMeta> {
+                let mut options = Vec::new();
+                #(#push_options)*
+                options
+            }
+        }
+    });
+
+    quote! {
+        impl #preset_ident {
+            #set_defaults
+            #set_values
+            #read_github_steps
+            #options
         }
     }
 }
 
 fn generate_features_method(fields: &[PresetFieldOpts]) -> TokenStream {
-    // Group fields by feature
-    let mut features_map: HashMap<String, Vec<&PresetFieldOpts>> = HashMap::new();
+    // Group fields by feature, in the order the features are first declared
+    let mut features: Vec<(&String, Vec<&PresetFieldOpts>)> = Vec::new();
 
     for field in fields {
         // Skip hidden fields
@@ -57,95 +196,31 @@ fn generate_features_method(fields: &[PresetFieldOpts]) -> TokenStream {
         }
 
         if let Some(ref feature_id) = field.feature {
-            features_map
-                .entry(feature_id.clone())
-                .or_insert_with(Vec::new)
-                .push(field);
+            match features.iter_mut().find(|(id, _)| *id == feature_id) {
+                Some((_, feature_fields)) => feature_fields.push(field),
+                None => features.push((feature_id, vec![field])),
+            }
         }
     }
 
     // Generate FeatureMeta for each feature group
-    let feature_metas = features_map.iter().map(|(feature_id, feature_fields)| {
+    let feature_metas = features.iter().map(|(feature_id, feature_fields)| {
         // Get feature metadata from the first field in the group
         let first_field = feature_fields.first().unwrap();
-        let feature_display = first_field.feature_display.as_ref()
-            .map(|s| s.as_str())
+        let feature_display = first_field
+            .feature_display
+            .as_deref()
             .unwrap_or(feature_id.as_str());
 
-        // Generate OptionMeta for each field in the feature
-        let option_metas = feature_fields.iter().map(|field| {
-            let field_ident = field.ident.as_ref().unwrap();
-            let field_ty = &field.ty;
-
-            let option_id = field_ident.to_string();
-
-            let display_name = field.display.as_ref()
-                .map(|s| s.as_str())
-                .unwrap_or(&option_id);
-
-            let description = field.description.as_ref()
-                .map(|s| s.as_str())
-                .unwrap_or("");
-
-            // Determine default value based on type
-            let default_value = if let Some(ref default_str) = field.default {
-                let default_expr: TokenStream = default_str.parse().unwrap();
-                match field_ty {
-                    syn::Type::Path(type_path) => {
-                        let type_str = quote!(#type_path).to_string().replace(" ", "");
-                        if type_str.starts_with("Option<") {
-                            // Extract inner type from Option<T>
-                            let inner_type_start = type_str.find('<').unwrap() + 1;
-                            let inner_type_end = type_str.rfind('>').unwrap();
-                            let inner_type_str = &type_str[inner_type_start..inner_type_end];
-                            let inner_type = syn::parse_str::<syn::Type>(inner_type_str).unwrap();
-
-                            // Option<EnumType> - include "none" variant
-                            quote! {
-                                crate::editor::config::OptionValue::Enum {
-                                    selected: {
-                                        let opt_val: #field_ty = #default_expr;
-                                        opt_val.as_ref().map(|v| v.as_str().to_string()).unwrap_or_else(|| "none".to_string())
-                                    },
-                                    variants: {
-                                        let mut v = vec!["none".to_string()];
-                                        v.extend(#inner_type::all_variants().iter().map(|s| s.to_string()));
-                                        v
-                                    },
-                                }
-                            }
-                        } else if type_str.contains("String") {
-                            quote! {
-                                crate::editor::config::OptionValue::String(#default_expr)
-                            }
-                        } else if type_str.contains("bool") {
-                            quote! {
-                                crate::editor::config::OptionValue::Bool(#default_expr)
-                            }
-                        } else {
-                            // Enum
-                            quote! {
-                                crate::editor::config::OptionValue::Enum {
-                                    selected: (#default_expr).as_str().to_string(),
-                                    variants: #field_ty::all_variants().iter().map(|s| s.to_string()).collect(),
-                                }
-                            }
-                        }
-                    }
-                    _ => quote! { crate::editor::config::OptionValue::Bool(false) },
-                }
+        // Generate OptionMeta for each field in the feature, and take a
+        // flattened group's from the group
+        let push_options = feature_fields.iter().map(|field| {
+            if field.flatten {
+                let field_ty = &field.ty;
+                quote! { options.extend(#field_ty::options()); }
             } else {
-                quote! { crate::editor::config::OptionValue::Bool(false) }
-            };
-
-            quote! {
-                crate::editor::config::OptionMeta {
-                    id: #option_id.to_string(),
-                    display_name: #display_name.to_string(),
-                    description: #description.to_string(),
-                    default_value: #default_value,
-                    depends_on: None,
-                }
+                let option_meta = option_meta(field);
+                quote! { options.push(#option_meta); }
             }
         });
 
@@ -154,9 +229,11 @@ fn generate_features_method(fields: &[PresetFieldOpts]) -> TokenStream {
                 id: #feature_id.to_string(),
                 display_name: #feature_display.to_string(),
                 description: String::new(),
-                options: vec![
-                    #(#option_metas),*
-                ],
+                options: {
+                    let mut options = Vec::new();
+                    #(#push_options)*
+                    options
+                },
             }
         }
     });
@@ -170,17 +247,246 @@ fn generate_features_method(fields: &[PresetFieldOpts]) -> TokenStream {
     }
 }
 
-fn generate_default_config_method(preset_id: &str, fields: &[PresetFieldOpts]) -> TokenStream {
+/// The `OptionMeta` the editor shows a field with
+fn option_meta(field: &PresetFieldOpts) -> TokenStream {
+    let field_ident = field.ident.as_ref().unwrap();
+    let field_ty = &field.ty;
+
+    let option_id = field_ident.to_string();
+
+    let display_name = field.display.as_deref().unwrap_or(&option_id);
+
+    let description = field.description.as_deref().unwrap_or("");
+
+    // Determine default value based on type
+    let default_value = if field.is_int() {
+        int_default_value(field)
+    } else if let Some(ref default_str) = field.default {
+        let default_expr: TokenStream = default_str.parse().unwrap();
+        match field_ty {
+            syn::Type::Path(type_path) => {
+                let type_str = quote!(#type_path).to_string().replace(" ", "");
+                if type_str.starts_with("Option<") {
+                    // Extract inner type from Option<T>
+                    let inner_type_start = type_str.find('<').unwrap() + 1;
+                    let inner_type_end = type_str.rfind('>').unwrap();
+                    let inner_type_str = &type_str[inner_type_start..inner_type_end];
+                    let inner_type = syn::parse_str::<syn::Type>(inner_type_str).unwrap();
+
+                    // Option<EnumType> - include "none" variant
+                    quote! {
+                        crate::editor::config::OptionValue::Enum {
+                            selected: {
+                                let opt_val: #field_ty = #default_expr;
+                                opt_val.as_ref().map(|v| v.as_str().to_string()).unwrap_or_else(|| "none".to_string())
+                            },
+                            variants: {
+                                let mut v = vec!["none".to_string()];
+                                v.extend(#inner_type::all_variants().iter().map(|s| s.to_string()));
+                                v
+                            },
+                        }
+                    }
+                } else if type_str.contains("String") {
+                    quote! {
+                        crate::editor::config::OptionValue::String(#default_expr)
+                    }
+                } else if type_str.contains("bool") {
+                    quote! {
+                        crate::editor::config::OptionValue::Bool(#default_expr)
+                    }
+                } else {
+                    // Enum
+                    quote! {
+                        crate::editor::config::OptionValue::Enum {
+                            selected: (#default_expr).as_str().to_string(),
+                            variants: #field_ty::all_variants().iter().map(|s| s.to_string()).collect(),
+                        }
+                    }
+                }
+            }
+            _ => quote! { crate::editor::config::OptionValue::Bool(false) },
+        }
+    } else {
+        quote! { crate::editor::config::OptionValue::Bool(false) }
+    };
+
+    let variants = generate_variant_metas(field_ty);
+    let depends_on = match field.parent() {
+        Some(parent) => quote! { Some(#parent.to_string()) },
+        None => quote! { None },
+    };
+    let validation = generate_validation(field);
+    let placeholder = match &field.placeholder {
+        Some(placeholder) => quote! { Some(#placeholder.to_string()) },
+        None => quote! { None },
+    };
+
+    quote! {
+        crate::editor::config::OptionMeta {
+            id: #option_id.to_string(),
+            display_name: #display_name.to_string(),
+            description: #description.to_string(),
+            default_value: #default_value,
+            variants: #variants,
+            depends_on: #depends_on,
+            validation: #validation,
+            placeholder: #placeholder,
+        }
+    }
+}
+
+/// An integer field's default as an `OptionValue::Int`, which holds an `i32`
+fn int_default_value(field: &PresetFieldOpts) -> TokenStream {
+    let field_ty = &field.ty;
+    let default_expr = field
+        .default
+        .as_ref()
+        .map(|s| s.parse::<TokenStream>().unwrap())
+        .unwrap_or_else(|| quote! { Default::default() });
+    quote! {
+        crate::editor::config::OptionValue::Int({
+            let value: #field_ty = #default_expr;
+            i32::try_from(value).unwrap_or(i32::MAX)
+        })
+    }
+}
+
+/// The `validation` expression, or the `Validation` the `min`, `max` and
+/// `pattern` shorthands make, with the `requires` and `conflicts` rules;
+/// integer fields also keep to their type's range so every value converts back
+fn generate_validation(field: &PresetFieldOpts) -> TokenStream {
+    let optional_string = |value: &Option<String>| match value {
+        Some(value) => quote! { Some(#value.to_string()) },
+        None => quote! { None },
+    };
+    let requires = optional_string(&field.requires);
+    let conflicts = &field.conflicts;
+    if let Some(expr) = &field.validation {
+        let expr: TokenStream = expr.parse().unwrap();
+        return quote! {
+            {
+                use crate::editor::config::Validation;
+                Validation {
+                    requires: #requires,
+                    conflicts: vec![#(#conflicts.to_string()),*],
+                    ..#expr
+                }
+            }
+        };
+    }
+    let field_ty = &field.ty;
+    let (min, max) = if field.is_int() {
+        let min = field
+            .min
+            .map(|min| quote! { #min })
+            .unwrap_or_else(|| quote! { i32::try_from(#field_ty::MIN).unwrap_or(i32::MIN) });
+        let max = field
+            .max
+            .map(|max| quote! { #max })
+            .unwrap_or_else(|| quote! { i32::try_from(#field_ty::MAX).unwrap_or(i32::MAX) });
+        (quote! { Some(#min) }, quote! { Some(#max) })
+    } else {
+        (quote! { None }, quote! { None })
+    };
+    let pattern = optional_string(&field.pattern);
+    let expected = optional_string(&field.expected);
+    quote! {
+        crate::editor::config::Validation {
+            pattern: #pattern,
+            expected: #expected,
+            min: #min,
+            max: #max,
+            exclusive: None,
+            requires: #requires,
+            conflicts: vec![#(#conflicts.to_string()),*],
+        }
+    }
+}
+
+/// The `VariantMeta`s of an enum option; other options have none
+fn generate_variant_metas(field_ty: &syn::Type) -> TokenStream {
+    let syn::Type::Path(type_path) = field_ty else {
+        return quote! { Vec::new() };
+    };
+    let type_str = quote!(#type_path).to_string().replace(" ", "");
+    let to_meta = quote! {
+        |(id, display_name, description): (&str, &str, &str)| crate::editor::config::VariantMeta {
+            id: id.to_string(),
+            display_name: display_name.to_string(),
+            description: description.to_string(),
+        }
+    };
+
+    if type_str.starts_with("Option<") {
+        let inner_type_start = type_str.find('<').unwrap() + 1;
+        let inner_type_end = type_str.rfind('>').unwrap();
+        let inner_type =
+            syn::parse_str::<syn::Type>(&type_str[inner_type_start..inner_type_end]).unwrap();
+        quote! {
+            {
+                let mut v = vec![crate::editor::config::VariantMeta {
+                    id: "none".to_string(),
+                    display_name: "None".to_string(),
+                    description: String::new(),
+                }];
+                v.extend(#inner_type::variant_info().into_iter().map(#to_meta));
+                v
+            }
+        }
+    } else if type_str.contains("String")
+        || type_str.contains("bool")
+        || INT_TYPES.contains(&type_str.as_str())
+    {
+        quote! { Vec::new() }
+    } else {
+        quote! { #field_ty::variant_info().into_iter().map(#to_meta).collect() }
+    }
+}
+
+fn generate_default_config_method(preset_id: &str) -> TokenStream {
+    quote! {
+        fn default_config(&self, detected: bool) -> crate::editor::config::PresetConfig {
+            let mut config = crate::editor::config::PresetConfig::new(#preset_id.to_string());
+            Self::set_defaults(&mut config, detected);
+            config
+        }
+    }
+}
+
+fn generate_set_defaults_method(fields: &[PresetFieldOpts]) -> TokenStream {
     let set_statements = fields.iter().filter_map(|field| {
         // Skip hidden fields
         if field.hidden {
             return None;
         }
 
+        if field.flatten {
+            let field_ty = &field.ty;
+            let feature_id = field.feature.as_deref().unwrap_or("");
+            let set = match &field.default {
+                Some(group) => {
+                    let group: TokenStream = group.parse().unwrap();
+                    quote! { (#group).set_values(config, detected) }
+                }
+                None => quote! { #field_ty::set_defaults(config, detected) },
+            };
+            // Workflow settings keep their defaults even for undetected presets
+            return Some(quote! {
+                {
+                    let detected = detected
+                        || crate::editor::config::WORKFLOW_SETTING_FEATURES.contains(&#feature_id);
+                    #set;
+                }
+            });
+        }
+
         let field_ty = &field.ty;
         let option_id = field.ident.as_ref().unwrap().to_string();
 
-        let default_value = if let Some(ref default_str) = field.default {
+        let default_value = if field.is_int() {
+            int_default_value(field)
+        } else if let Some(ref default_str) = field.default {
             let default_expr: TokenStream = default_str.parse().unwrap();
             match field_ty {
                 syn::Type::Path(type_path) => {
@@ -213,8 +519,16 @@ fn generate_default_config_method(preset_id: &str, fields: &[PresetFieldOpts]) -
                             crate::editor::config::OptionValue::String(#default_expr)
                         }
                     } else if type_str.contains("bool") {
+                        // Workflow settings keep their defaults even for undetected presets
+                        let feature_id = field.feature.as_deref().unwrap_or("");
                         quote! {
-                            crate::editor::config::OptionValue::Bool(if detected { #default_expr } else { false })
+                            crate::editor::config::OptionValue::Bool(
+                                if detected || crate::editor::config::WORKFLOW_SETTING_FEATURES.contains(&#feature_id) {
+                                    #default_expr
+                                } else {
+                                    false
+                                }
+                            )
                         }
                     } else {
                         // Enum
@@ -238,20 +552,100 @@ fn generate_default_config_method(preset_id: &str, fields: &[PresetFieldOpts]) -
     });
 
     quote! {
-        fn default_config(&self, detected: bool) -> crate::editor::config::PresetConfig {
-            let mut config = crate::editor::config::PresetConfig::new(#preset_id.to_string());
+        /// Set each option to its default, with toggles off unless `detected`
+        #[allow(unused_variables)]
+        pub fn set_defaults(config: &mut crate::editor::config::PresetConfig, detected: bool) {
             #(#set_statements)*
+        }
+    }
+}
+
+/// A group's `set_values`, for presets that give the group defaults of their own
+fn generate_set_values_method(fields: &[PresetFieldOpts]) -> TokenStream {
+    let toggles_off = fields
+        .iter()
+        .filter(|field| !field.hidden)
+        .filter_map(|field| {
+            let field_ident = field.ident.as_ref().unwrap();
+            if field.flatten {
+                return Some(quote! { self.#field_ident.set_values(config, false); });
+            }
+            let option_id = field_ident.to_string();
+            field.is_bool().then(|| {
+                quote! {
+                    config.set(#option_id.to_string(), crate::editor::config::OptionValue::Bool(false));
+                }
+            })
+        });
+
+    quote! {
+        /// Set each option to this group's value, with toggles off unless `detected`
+        pub fn set_values(&self, config: &mut crate::editor::config::PresetConfig, detected: bool) {
+            Self::ron_into_preset_config(&self.to_ron(), config);
+            if !detected {
+                #(#toggles_off)*
+            }
+        }
+    }
+}
+
+/// Start from the detected defaults, read back the shared workflow settings
+/// and every `github_step` toggle, then let the preset read the rest
+fn generate_extract_config_method() -> TokenStream {
+    quote! {
+        fn extract_config(
+            &self,
+            workflow: &crate::platforms::github::models::GitHubWorkflow,
+        ) -> crate::editor::config::PresetConfig {
+            let mut config = crate::editor::config::EditorPreset::default_config(self, true);
+            crate::detection::read_workflow_settings(workflow, &mut config);
+            Self::read_github_steps(workflow, &mut config);
+            crate::traits::DetectedDefaults::extract_github(self, workflow, &mut config);
             config
         }
     }
 }
 
-fn generate_matches_project_method(matches_pattern: &Option<String>) -> TokenStream {
+fn generate_read_github_steps_method(fields: &[PresetFieldOpts]) -> TokenStream {
+    let read_toggles = fields.iter().filter_map(|field| {
+        if field.flatten {
+            let field_ty = &field.ty;
+            return Some(quote! {
+                #field_ty::read_github_steps(workflow, config);
+            });
+        }
+        let marker = field.github_step.as_ref()?;
+        let option_id = field.ident.as_ref().unwrap().to_string();
+        Some(quote! {
+            config.set(
+                #option_id.to_string(),
+                crate::editor::config::OptionValue::Bool(
+                    crate::detection::has_step(workflow, #marker),
+                ),
+            );
+        })
+    });
+
+    quote! {
+        /// Read each `github_step` toggle back from whether `workflow` has its step
+        #[allow(unused_variables)]
+        pub fn read_github_steps(
+            workflow: &crate::platforms::github::models::GitHubWorkflow,
+            config: &mut crate::editor::config::PresetConfig,
+        ) {
+            #(#read_toggles)*
+        }
+    }
+}
+
+fn generate_matches_project_method(matches_pattern: &Option<syn::LitStr>) -> TokenStream {
     if let Some(pattern) = matches_pattern {
-        // Parse the pattern (e.g., "RustBinary | RustLibrary | RustWorkspace")
-        let variants: Vec<_> = pattern.split('|').map(|s| s.trim()).collect();
+        // Parse the pattern (e.g., "RustBinary | RustLibrary | RustWorkspace"),
+        // with each variant spanned at the attribute
+        let value = pattern.value();
+        let variants: Vec<_> = value.split('|').map(|s| s.trim()).collect();
         let match_arms = variants.iter().map(|v| {
-            let variant_ident: TokenStream = v.parse().unwrap();
+            let variant_ident = syn::Ident::new(v, pattern.span());
             quote! { crate::detection::ProjectType::#variant_ident }
         });
 
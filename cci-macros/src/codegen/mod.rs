@@ -1,7 +1,13 @@
 mod conversions;
+mod detectable;
 mod editor;
+mod option_ids;
 mod ron_type;
+mod schema;
 
 pub use conversions::generate_conversions;
+pub use detectable::generate_detectable_impl;
 pub use editor::generate_editor_preset_impl;
+pub use option_ids::generate_option_ids;
 pub use ron_type::generate_ron_type;
+pub use schema::generate_schema_method;
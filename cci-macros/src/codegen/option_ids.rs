@@ -0,0 +1,168 @@
+use crate::preset::PresetFieldOpts;
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+
+/// `OPTION_IDS`, the option IDs the struct puts in a `PresetConfig`, its
+/// flattened groups' included; with groups, `const` assertions that no two
+/// fields end up with the same ID, spanned at the field flattening the group
+pub fn generate_option_ids(preset_ident: &syn::Ident, fields: &[PresetFieldOpts]) -> TokenStream {
+    let own_ids: Vec<String> = fields
+        .iter()
+        .filter(|field| !field.hidden && !field.flatten)
+        .map(|field| field.ident.as_ref().unwrap().to_string())
+        .collect();
+    let groups: Vec<&PresetFieldOpts> = fields.iter().filter(|field| field.flatten).collect();
+    let group_tys = groups.iter().map(|field| &field.ty);
+    let group_tys_again = group_tys.clone();
+    let own_count = own_ids.len();
+
+    let checks = groups.iter().enumerate().map(|(index, group)| {
+        let group_ty = &group.ty;
+        let group_name = quote!(#group_ty).to_string().replace(" ", "");
+        let span = group.ident.as_ref().unwrap().span();
+        let own_checks = own_ids.iter().map(|id| {
+            let message = format!(
+                "option `{}` of {} is also an option of the flattened {}",
+                id, preset_ident, group_name
+            );
+            quote_spanned! {span=>
+                assert!(!contains(#group_ty::OPTION_IDS, #id), #message);
+            }
+        });
+        let group_checks = groups[index + 1..].iter().map(|other| {
+            let other_ty = &other.ty;
+            let message = format!(
+                "{} and {} are both flattened into {} but share an option ID",
+                group_name,
+                quote!(#other_ty).to_string().replace(" ", ""),
+                preset_ident
+            );
+            quote_spanned! {span=>
+                assert!(!overlaps(#group_ty::OPTION_IDS, #other_ty::OPTION_IDS), #message);
+            }
+        });
+        quote! {
+            #(#own_checks)*
+            #(#group_checks)*
+        }
+    });
+    let checks = (!groups.is_empty()).then(|| {
+        quote! {
+            const _: () = {
+                const fn same(a: &str, b: &str) -> bool {
+                    let (a, b) = (a.as_bytes(), b.as_bytes());
+                    if a.len() != b.len() {
+                        return false;
+                    }
+                    let mut i = 0;
+                    while i < a.len() {
+                        if a[i] != b[i] {
+                            return false;
+                        }
+                        i += 1;
+                    }
+                    true
+                }
+                const fn contains(ids: &[&str], id: &str) -> bool {
+                    let mut i = 0;
+                    while i < ids.len() {
+                        if same(ids[i], id) {
+                            return true;
+                        }
+                        i += 1;
+                    }
+                    false
+                }
+                const fn overlaps(a: &[&str], b: &[&str]) -> bool {
+                    let mut i = 0;
+                    while i < a.len() {
+                        if contains(b, a[i]) {
+                            return true;
+                        }
+                        i += 1;
+                    }
+                    false
+                }
+                #(#checks)*
+            };
+        }
+    });
+
+    quote! {
+        impl #preset_ident {
+            /// IDs of the options this sets in a `PresetConfig`, its
+            /// flattened groups' included
+            pub const OPTION_IDS: &'static [&'static str] = &{
+                let mut ids = [""; #own_count #(+ #group_tys::OPTION_IDS.len())*];
+                let own: [&str; #own_count] = [#(#own_ids),*];
+                let mut at = 0;
+                while at < own.len() {
+                    ids[at] = own[at];
+                    at += 1;
+                }
+                #(
+                    let group = #group_tys_again::OPTION_IDS;
+                    let mut i = 0;
+                    while i < group.len() {
+                        ids[at] = group[i];
+                        at += 1;
+                        i += 1;
+                    }
+                )*
+                ids
+            };
+        }
+
+        #checks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use darling::FromField;
+
+    fn fields(item: syn::ItemStruct) -> Vec<PresetFieldOpts> {
+        item.fields
+            .iter()
+            .map(|field| PresetFieldOpts::from_field(field).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_flattened_groups_are_checked_against_every_other_id() {
+        let fields = fields(syn::parse_quote! {
+            struct Demo {
+                #[preset_field(default = "0")]
+                timeout_minutes: u32,
+                #[preset_field(flatten, feature = "job_control")]
+                job_control: JobControl,
+                #[preset_field(flatten, feature = "multi_arch")]
+                multi_arch: MultiArch,
+            }
+        });
+        let code = generate_option_ids(&syn::parse_quote!(Demo), &fields).to_string();
+
+        assert!(code.contains("contains (JobControl :: OPTION_IDS , \"timeout_minutes\")"));
+        assert!(code.contains(
+            "\"option `timeout_minutes` of Demo is also an option of the flattened JobControl\""
+        ));
+        assert!(code.contains("overlaps (JobControl :: OPTION_IDS , MultiArch :: OPTION_IDS)"));
+        assert!(code.contains(
+            "\"JobControl and MultiArch are both flattened into Demo but share an option ID\""
+        ));
+    }
+
+    #[test]
+    fn test_no_checks_without_groups() {
+        let fields = fields(syn::parse_quote! {
+            struct Demo {
+                #[preset_field(default = "0")]
+                timeout_minutes: u32,
+            }
+        });
+        let code = generate_option_ids(&syn::parse_quote!(Demo), &fields).to_string();
+        assert!(code.contains("\"timeout_minutes\""));
+        assert!(!code.contains("assert"));
+    }
+}
@@ -2,19 +2,34 @@ use crate::preset::PresetFieldOpts;
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 
-pub fn generate_ron_type(
-    preset_ident: &syn::Ident,
-    _preset_id: &str,
-    fields: &[PresetFieldOpts],
-) -> TokenStream {
-    // Generate RON config struct name (e.g., RustPreset -> RustConfig)
-    let config_name = format_ident!(
+/// RON config struct name (e.g., RustPreset -> RustConfig, MultiArch -> MultiArchConfig)
+pub fn config_name(preset_ident: &syn::Ident) -> syn::Ident {
+    format_ident!(
         "{}Config",
         preset_ident
             .to_string()
             .strip_suffix("Preset")
             .unwrap_or(&preset_ident.to_string())
-    );
+    )
+}
+
+/// The RON config struct of a flattened group's type, at the same path
+fn flattened_ron_type(field_ty: &syn::Type) -> syn::Type {
+    let mut ron_ty = field_ty.clone();
+    if let syn::Type::Path(type_path) = &mut ron_ty {
+        if let Some(last) = type_path.path.segments.last_mut() {
+            last.ident = config_name(&last.ident);
+        }
+    }
+    ron_ty
+}
+
+pub fn generate_ron_type(
+    preset_ident: &syn::Ident,
+    group: bool,
+    fields: &[PresetFieldOpts],
+) -> TokenStream {
+    let config_name = config_name(preset_ident);
 
     // Generate fields for the RON struct
     let ron_fields = fields.iter().map(|field| {
@@ -24,18 +39,30 @@ pub fn generate_ron_type(
         // Use the field name directly
         let ron_field_name = field_ident.clone();
 
-        // Add #[serde(default)] for non-String types
-        let serde_default = match field_ty {
-            syn::Type::Path(type_path) => {
-                let type_str = quote!(#type_path).to_string().replace(" ", "");
-                // Don't add default for basic String types, but do for Vec and other types
-                if type_str == "String" {
-                    quote! {}
-                } else {
-                    quote! { #[serde(default)] }
-                }
-            }
-            _ => quote! { #[serde(default)] },
+        // A group's settings nest, filled in with the preset's defaults for
+        // the group when left out
+        if field.flatten {
+            let ron_ty = flattened_ron_type(field_ty);
+            let default_fn = format!("{}::default_{}", config_name, field_ident);
+            let deserialize_fn = format!("{}::deserialize_{}", config_name, field_ident);
+            return quote! {
+                #[serde(default = #default_fn, deserialize_with = #deserialize_fn)]
+                pub #ron_field_name: #ron_ty
+            };
+        }
+
+        // Options left out take the default they declare, so options added
+        // later don't break existing config files; Strings without one are
+        // required and other types fall back to their `Default`
+        let is_string = matches!(field_ty, syn::Type::Path(type_path)
+            if quote!(#type_path).to_string().replace(" ", "") == "String");
+        let serde_default = if field.default.is_some() {
+            let default_fn = format!("{}::default_{}", config_name, field_ident);
+            quote! { #[serde(default = #default_fn)] }
+        } else if is_string {
+            quote! {}
+        } else {
+            quote! { #[serde(default)] }
         };
 
         quote! {
@@ -44,11 +71,127 @@ pub fn generate_ron_type(
         }
     });
 
+    // Default value functions referenced by #[serde(default = "...")]
+    let default_fns = fields.iter().filter_map(|field| {
+        let field_ident = field.ident.as_ref().unwrap();
+        let field_ty = &field.ty;
+        if field.flatten {
+            let ron_ty = flattened_ron_type(field_ty);
+            let group = field
+                .default
+                .as_ref()
+                .map(|s| s.parse::<TokenStream>().unwrap())
+                .unwrap_or_else(|| quote! { #field_ty::default() });
+            let fn_name = format_ident!("default_{}", field_ident);
+            let deserialize_fn = format_ident!("deserialize_{}", field_ident);
+            return Some(quote! {
+                fn #fn_name() -> #ron_ty {
+                    (#group).to_ron()
+                }
+
+                fn #deserialize_fn<'de, D: serde::Deserializer<'de>>(
+                    deserializer: D,
+                ) -> Result<#ron_ty, D::Error> {
+                    #ron_ty::deserialize_over(deserializer, Self::#fn_name())
+                }
+            });
+        }
+        let default_expr: TokenStream = field.default.as_ref()?.parse().unwrap();
+        let fn_name = format_ident!("default_{}", field_ident);
+        Some(quote! {
+            fn #fn_name() -> #field_ty {
+                #default_expr
+            }
+        })
+    });
+
+    // Groups nest in presets' configs, which fall back to their defaults
+    let default_impl = group.then(|| {
+        let default_fields = fields.iter().map(|field| {
+            let field_ident = field.ident.as_ref().unwrap();
+            let default_expr = field
+                .default
+                .as_ref()
+                .map(|s| s.parse::<TokenStream>().unwrap())
+                .unwrap_or_else(|| quote! { Default::default() });
+            quote! { #field_ident: #default_expr }
+        });
+        quote! {
+            impl Default for #config_name {
+                fn default() -> Self {
+                    Self {
+                        #(#default_fields),*
+                    }
+                }
+            }
+        }
+    });
+
+    // Groups are read over the defaults of the preset flattening them, so
+    // the settings left out keep those rather than the group's own
+    let deserialize_over = group.then(|| {
+        let patch_name = format_ident!("{}Patch", config_name);
+        let patch_fields = fields.iter().map(|field| {
+            let field_ident = field.ident.as_ref().unwrap();
+            let ron_ty = if field.flatten {
+                flattened_ron_type(&field.ty)
+            } else {
+                field.ty.clone()
+            };
+            let present_fn = format!("{}::present", patch_name);
+            quote! {
+                #[serde(default, deserialize_with = #present_fn)]
+                #field_ident: Option<#ron_ty>
+            }
+        });
+        let merged_fields = fields.iter().map(|field| {
+            let field_ident = field.ident.as_ref().unwrap();
+            quote! { #field_ident: patch.#field_ident.unwrap_or(base.#field_ident) }
+        });
+        quote! {
+            /// A group's settings as a config gives them, each one optional
+            #[doc(hidden)]
+            #[derive(serde::Deserialize)]
+            #[serde(deny_unknown_fields)]
+            pub struct #patch_name {
+                #(#patch_fields),*
+            }
+
+            impl #patch_name {
+                fn present<'de, D: serde::Deserializer<'de>, T: serde::Deserialize<'de>>(
+                    deserializer: D,
+                ) -> Result<Option<T>, D::Error> {
+                    T::deserialize(deserializer).map(Some)
+                }
+            }
+
+            impl #config_name {
+                /// Read the group's settings, taking those left out from `base`
+                pub fn deserialize_over<'de, D: serde::Deserializer<'de>>(
+                    deserializer: D,
+                    base: Self,
+                ) -> Result<Self, D::Error> {
+                    let patch = <#patch_name as serde::Deserialize>::deserialize(deserializer)?;
+                    Ok(Self {
+                        #(#merged_fields),*
+                    })
+                }
+            }
+        }
+    });
+
     quote! {
         #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
         #[serde(deny_unknown_fields)]
         pub struct #config_name {
             #(#ron_fields),*
         }
+
+        impl #config_name {
+            #(#default_fns)*
+        }
+
+        #default_impl
+        #deserialize_over
     }
 }
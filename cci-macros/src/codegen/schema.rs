@@ -0,0 +1,149 @@
+use crate::preset::{PresetFieldOpts, PresetOpts};
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// A `schema()` returning the JSON Schema (draft-07) of the RON config
+/// struct, as serde reads it; a group's is nested in the presets flattening it
+pub fn generate_schema_method(opts: &PresetOpts, fields: &[PresetFieldOpts]) -> TokenStream {
+    let preset_ident = &opts.ident;
+    let insert_properties = fields.iter().map(|field| {
+        let option_id = field.ident.as_ref().unwrap().to_string();
+        let field_schema = field_schema(field);
+        quote! {
+            properties.insert(#option_id.to_string(), #field_schema);
+        }
+    });
+    // Matches the serde defaults generate_ron_type gives the fields
+    let required = fields
+        .iter()
+        .filter(|field| field.is_string() && (field.default.is_none() || field.hidden))
+        .map(|field| field.ident.as_ref().unwrap().to_string());
+    let about = (!opts.group).then(|| {
+        let name = &opts.name;
+        let description = &opts.description;
+        quote! {
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "title": #name,
+            "description": #description,
+        }
+    });
+
+    quote! {
+        impl #preset_ident {
+            pub fn schema() -> serde_json::Value {
+                let mut properties = serde_json::Map::new();
+                #(#insert_properties)*
+                serde_json::json!({
+                    #about
+                    "type": "object",
+                    "properties": properties,
+                    "required": [#(#required),*],
+                    "additionalProperties": false,
+                })
+            }
+        }
+    }
+}
+
+/// The schema of one field's value, with its display name, description and
+/// default
+fn field_schema(field: &PresetFieldOpts) -> TokenStream {
+    let field_ty = &field.ty;
+    if field.flatten {
+        let Some(group) = &field.default else {
+            return quote! { #field_ty::schema() };
+        };
+        // Defaults the preset gives the group replace the group's own
+        let group: TokenStream = group.parse().unwrap();
+        return quote! {
+            {
+                let mut schema = #field_ty::schema();
+                if let (Some(properties), Ok(serde_json::Value::Object(defaults))) = (
+                    schema["properties"].as_object_mut(),
+                    serde_json::to_value((#group).to_ron()),
+                ) {
+                    for (id, default) in defaults {
+                        if let Some(property) = properties.get_mut(&id).and_then(|p| p.as_object_mut()) {
+                            if property.contains_key("default") {
+                                property.insert("default".to_string(), default);
+                            }
+                        }
+                    }
+                }
+                schema
+            }
+        };
+    }
+
+    let type_str = quote!(#field_ty).to_string().replace(" ", "");
+    // Enums are listed by the names serde gives their variants
+    let serde_names = |enum_ty: &syn::Type| {
+        quote! {
+            #enum_ty::all_variants()
+                .into_iter()
+                .filter_map(#enum_ty::from_str)
+                .filter_map(|variant| serde_json::to_value(variant).ok())
+        }
+    };
+    let mut keys = vec![if field.is_bool() {
+        quote! { ("type", serde_json::json!("boolean")) }
+    } else if field.is_string() {
+        quote! { ("type", serde_json::json!("string")) }
+    } else if field.is_int() {
+        quote! { ("type", serde_json::json!("integer")) }
+    } else if type_str.starts_with("Vec<") {
+        quote! { ("type", serde_json::json!("array")) }
+    } else if type_str.starts_with("Option<") {
+        let inner_type_start = type_str.find('<').unwrap() + 1;
+        let inner_type_end = type_str.rfind('>').unwrap();
+        let inner_type =
+            syn::parse_str::<syn::Type>(&type_str[inner_type_start..inner_type_end]).unwrap();
+        let names = serde_names(&inner_type);
+        quote! {
+            ("enum", serde_json::Value::Array(#names.chain([serde_json::Value::Null]).collect()))
+        }
+    } else {
+        let names = serde_names(field_ty);
+        quote! { ("enum", serde_json::Value::Array(#names.collect())) }
+    }];
+
+    if field.is_int() {
+        let min = field
+            .min
+            .map(|min| quote! { #min })
+            .unwrap_or_else(|| quote! { #field_ty::MIN });
+        let max = field
+            .max
+            .map(|max| quote! { #max })
+            .unwrap_or_else(|| quote! { #field_ty::MAX });
+        keys.push(quote! { ("minimum", serde_json::json!(#min)) });
+        keys.push(quote! { ("maximum", serde_json::json!(#max)) });
+    }
+    if let Some(pattern) = &field.pattern {
+        keys.push(quote! { ("pattern", serde_json::json!(#pattern)) });
+    }
+    if let Some(display) = &field.display {
+        keys.push(quote! { ("title", serde_json::json!(#display)) });
+    }
+    if let Some(description) = &field.description {
+        keys.push(quote! { ("description", serde_json::json!(#description)) });
+    }
+    if let Some(default) = &field.default {
+        let default_expr: TokenStream = default.parse().unwrap();
+        keys.push(quote! {
+            ("default", {
+                let value: #field_ty = #default_expr;
+                serde_json::to_value(value).unwrap_or_default()
+            })
+        });
+    }
+
+    quote! {
+        serde_json::Value::Object(
+            [#(#keys),*]
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+}
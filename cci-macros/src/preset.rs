@@ -3,7 +3,10 @@ use proc_macro::TokenStream;
 use quote::quote;
 use syn::{parse_macro_input, DeriveInput};
 
-use crate::codegen::{generate_conversions, generate_editor_preset_impl, generate_ron_type};
+use crate::codegen::{
+    generate_conversions, generate_detectable_impl, generate_editor_preset_impl,
+    generate_option_ids, generate_ron_type, generate_schema_method,
+};
 
 /// Struct-level attributes for #[preset(...)]
 #[derive(Debug, FromDeriveInput)]
@@ -13,17 +16,46 @@ pub struct PresetOpts {
     pub data: darling::ast::Data<(), PresetFieldOpts>,
 
     /// Preset ID (e.g., "rust", "python-app")
+    #[darling(default)]
     pub id: String,
 
     /// Display name (e.g., "Rust", "Python App")
+    #[darling(default)]
     pub name: String,
 
     /// User-facing description
+    #[darling(default)]
     pub description: String,
 
+    /// Only a group of fields for presets to `flatten`, so none of the
+    /// preset-level attributes apply and no `EditorPreset` is generated
+    #[darling(default)]
+    pub group: bool,
+
     /// ProjectType pattern for matches_project() (e.g., "RustBinary | RustLibrary")
     #[darling(default)]
-    pub matches: Option<String>,
+    pub matches: Option<syn::LitStr>,
+
+    /// Addon presets layer extra jobs on top of a base preset
+    #[darling(default)]
+    pub addon: bool,
+
+    /// Decorator addons extend the pipelines of other presets instead of
+    /// generating their own
+    #[darling(default)]
+    pub decorator: bool,
+
+    /// Part of a `uses` action the preset's GitHub and Gitea workflows have
+    /// (e.g., "dtolnay/rust-toolchain"); repeat for alternatives. With this
+    /// or `detect_run`, `Detectable` is derived instead of hand-written
+    #[darling(multiple)]
+    pub detect_uses: Vec<String>,
+
+    /// Part of a command the preset's pipelines run on every platform
+    /// (e.g., "cargo test"); repeat for alternatives. Workflows must match
+    /// both this and `detect_uses` when both are given
+    #[darling(multiple)]
+    pub detect_run: Vec<String>,
 }
 
 /// Field-level attributes for #[preset_field(...)]
@@ -33,7 +65,8 @@ pub struct PresetFieldOpts {
     pub ident: Option<syn::Ident>,
     pub ty: syn::Type,
 
-    /// Default value expression
+    /// Default value expression; on a `flatten` field, the group with the
+    /// preset's own defaults (e.g., "Triggers { tags: ..., ..Triggers::default() }")
     #[darling(default)]
     pub default: Option<String>,
 
@@ -56,6 +89,329 @@ pub struct PresetFieldOpts {
     /// Feature group display name
     #[darling(default)]
     pub feature_display: Option<String>,
+
+    /// Boolean field this option only applies with (e.g., "enable_coverage")
+    #[darling(default)]
+    pub depends_on: Option<String>,
+
+    /// For a bool field, part of a `run` command or `uses` action that only
+    /// the steps it turns on contain (e.g., "cargo clippy"), so importing a
+    /// GitHub workflow reads it back
+    #[darling(default)]
+    pub github_step: Option<String>,
+
+    /// `Validation` expression for the rules the value keeps to
+    /// (e.g., "Validation::cron()")
+    #[darling(default)]
+    pub validation: Option<String>,
+
+    /// Example shown in the TUI while a String or integer field is empty
+    #[darling(default)]
+    pub placeholder: Option<String>,
+
+    /// Bounds of an integer field
+    #[darling(default)]
+    pub min: Option<i32>,
+    #[darling(default)]
+    pub max: Option<i32>,
+
+    /// Regex a String field's value must match, with `expected` saying
+    /// what it accepts for errors
+    #[darling(default)]
+    pub pattern: Option<String>,
+    #[darling(default)]
+    pub expected: Option<String>,
+
+    /// Boolean field that must be on while this one is; like `depends_on`,
+    /// but a config with this on and it off is invalid rather than ignored
+    #[darling(default)]
+    pub requires: Option<String>,
+
+    /// Bool or `Option<Enum>` fields that can't be on while this one is;
+    /// repeat for several
+    #[darling(multiple)]
+    pub conflicts: Vec<String>,
+
+    /// The field's type is a `#[preset(group)]` whose options go under this
+    /// field's `feature`, with its settings nested in the RON config; the
+    /// group's `...Config` struct must be in scope too
+    #[darling(default)]
+    pub flatten: bool,
+}
+
+/// Integer types an option can have, edited as `OptionValue::Int`
+pub const INT_TYPES: &[&str] = &[
+    "i8", "i16", "i32", "i64", "isize", "u8", "u16", "u32", "u64", "usize",
+];
+
+impl PresetFieldOpts {
+    fn type_str(&self) -> String {
+        let ty = &self.ty;
+        quote!(#ty).to_string().replace(" ", "")
+    }
+
+    pub fn is_bool(&self) -> bool {
+        self.type_str() == "bool"
+    }
+
+    pub fn is_string(&self) -> bool {
+        self.type_str() == "String"
+    }
+
+    pub fn is_int(&self) -> bool {
+        INT_TYPES.contains(&self.type_str().as_str())
+    }
+
+    /// Whether the field is on or off, as a bool or an `Option<Enum>` is
+    pub fn is_toggle(&self) -> bool {
+        self.is_bool() || self.type_str().starts_with("Option<")
+    }
+
+    /// The field this one depends on, through `depends_on` or `requires`
+    pub fn parent(&self) -> Option<&str> {
+        self.depends_on.as_deref().or(self.requires.as_deref())
+    }
+}
+
+/// The fields `field` depends on, its parent first
+pub fn dependency_chain<'a>(
+    field: &'a PresetFieldOpts,
+    fields: &'a [PresetFieldOpts],
+) -> Vec<&'a PresetFieldOpts> {
+    let mut chain: Vec<&PresetFieldOpts> = Vec::new();
+    let mut parent = field.parent();
+    while let Some(name) = parent {
+        let Some(next) = fields
+            .iter()
+            .find(|f| f.ident.as_ref().is_some_and(|ident| ident == name))
+        else {
+            break;
+        };
+        // A cycle is reported by check_dependencies
+        if chain.iter().any(|known| known.ident == next.ident) {
+            break;
+        }
+        chain.push(next);
+        parent = next.parent();
+    }
+    chain
+}
+
+/// `depends_on` and `requires` must name a boolean field of the same preset,
+/// without going in circles, and `conflicts` other fields that turn on and off
+fn check_dependencies(fields: &[PresetFieldOpts]) -> syn::Result<()> {
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        if (field.requires.is_some() || !field.conflicts.is_empty()) && !field.is_toggle() {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "requires and conflicts only apply to bool and Option<Enum> fields",
+            ));
+        }
+        for other in &field.conflicts {
+            let found = fields
+                .iter()
+                .find(|f| f.ident.as_ref().is_some_and(|ident| ident == other));
+            match found {
+                Some(other_field)
+                    if other_field.is_toggle()
+                        && !other_field.hidden
+                        && other_field.ident != field.ident => {}
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        ident,
+                        format!(
+                        "conflicts must name another visible bool or Option<Enum> field, not `{}`",
+                        other
+                    ),
+                    ))
+                }
+            }
+        }
+        if field.depends_on.is_some() && field.requires.is_some() {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "use either depends_on or requires",
+            ));
+        }
+        let Some(parent) = field.parent() else {
+            continue;
+        };
+        let found = fields
+            .iter()
+            .find(|f| f.ident.as_ref().is_some_and(|ident| ident == parent));
+        match found {
+            None => {
+                return Err(syn::Error::new_spanned(
+                    ident,
+                    format!("no field `{}` to depend on", parent),
+                ))
+            }
+            Some(parent_field) if !parent_field.is_bool() || parent_field.hidden => {
+                return Err(syn::Error::new_spanned(
+                    ident,
+                    format!(
+                        "depends_on and requires must name a visible bool field, not `{}`",
+                        parent
+                    ),
+                ))
+            }
+            Some(_) => {}
+        }
+        if dependency_chain(field, fields)
+            .iter()
+            .any(|ancestor| ancestor.parent() == Some(&ident.to_string()))
+        {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "depends_on chain leads back to this field",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// `github_step` only reads back toggles
+fn check_github_steps(fields: &[PresetFieldOpts]) -> syn::Result<()> {
+    for field in fields {
+        if field.github_step.is_some() && (!field.is_bool() || field.hidden) {
+            return Err(syn::Error::new_spanned(
+                field.ident.as_ref().unwrap(),
+                "github_step only applies to visible bool fields",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Groups have only fields, which go under the feature of the field
+/// flattening them; presets need what the editor shows them with
+fn check_preset(opts: &PresetOpts, fields: &[PresetFieldOpts]) -> syn::Result<()> {
+    let ident = &opts.ident;
+    if !opts.group {
+        if opts.id.is_empty() || opts.name.is_empty() || opts.description.is_empty() {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "presets need an id, name and description",
+            ));
+        }
+        return Ok(());
+    }
+    if !opts.id.is_empty()
+        || !opts.name.is_empty()
+        || !opts.description.is_empty()
+        || opts.matches.is_some()
+        || opts.addon
+        || opts.decorator
+        || !opts.detect_uses.is_empty()
+        || !opts.detect_run.is_empty()
+    {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "a group only has fields; the preset flattening it has the rest",
+        ));
+    }
+    if let Some(field) = fields
+        .iter()
+        .find(|f| f.feature.is_some() || f.feature_display.is_some())
+    {
+        return Err(syn::Error::new_spanned(
+            field.ident.as_ref().unwrap(),
+            "fields of a group go under the feature of the field flattening it",
+        ));
+    }
+    Ok(())
+}
+
+/// `matches` is ProjectType variants separated by `|`; whether they exist
+/// isn't checked here, the generated `matches_project` names each one
+fn check_matches(opts: &PresetOpts) -> syn::Result<()> {
+    let Some(matches) = &opts.matches else {
+        return Ok(());
+    };
+    for variant in matches.value().split('|').map(str::trim) {
+        if syn::parse_str::<syn::Ident>(variant).is_err() {
+            return Err(syn::Error::new_spanned(
+                matches,
+                format!(
+                    "matches is ProjectType variants separated by `|`, not `{}`",
+                    variant
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// A flattened field only says which feature the group's options go under,
+/// and optionally the preset's own defaults for them
+fn check_flatten(fields: &[PresetFieldOpts]) -> syn::Result<()> {
+    for field in fields.iter().filter(|f| f.flatten) {
+        let ident = field.ident.as_ref().unwrap();
+        let configured = field.hidden
+            || field.display.is_some()
+            || field.description.is_some()
+            || field.depends_on.is_some()
+            || field.github_step.is_some()
+            || field.validation.is_some()
+            || field.placeholder.is_some()
+            || field.min.is_some()
+            || field.max.is_some()
+            || field.pattern.is_some()
+            || field.expected.is_some()
+            || field.requires.is_some()
+            || !field.conflicts.is_empty();
+        if configured {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "flatten only takes feature, feature_display and default; the group's fields have the rest",
+            ));
+        }
+        if field.feature.is_none() {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "flatten needs a feature to put the group's options under",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Editing attributes must suit the field's type, and the `min`, `max` and
+/// `pattern` shorthands can't be mixed with a `validation` expression
+fn check_editing(fields: &[PresetFieldOpts]) -> syn::Result<()> {
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let error = |message: &str| Err(syn::Error::new_spanned(ident, message));
+        let ranged = field.min.is_some() || field.max.is_some();
+        let patterned = field.pattern.is_some() || field.expected.is_some();
+        if field.hidden
+            && (field.validation.is_some() || field.placeholder.is_some() || ranged || patterned)
+        {
+            return error("validation and placeholders only apply to visible fields");
+        }
+        if field.placeholder.is_some() && !field.is_string() && !field.is_int() {
+            return error("placeholder only applies to String and integer fields");
+        }
+        if ranged && !field.is_int() {
+            return error("min and max only apply to integer fields");
+        }
+        if patterned && !field.is_string() {
+            return error("pattern only applies to String fields");
+        }
+        if field.expected.is_some() && field.pattern.is_none() {
+            return error("expected describes a pattern, so needs one");
+        }
+        if field.validation.is_some() && (ranged || patterned) {
+            return error("use either validation or min, max and pattern");
+        }
+        if let (Some(min), Some(max)) = (field.min, field.max) {
+            if min > max {
+                return error("min is above max");
+            }
+        }
+    }
+    Ok(())
 }
 
 pub fn derive_preset_impl(input: TokenStream) -> TokenStream {
@@ -68,25 +424,47 @@ pub fn derive_preset_impl(input: TokenStream) -> TokenStream {
 
     // Extract fields before consuming opts
     let fields: Vec<_> = opts.data.clone().take_struct().unwrap().fields;
+    if let Err(e) = check_preset(&opts, &fields)
+        .and_then(|_| check_matches(&opts))
+        .and_then(|_| check_flatten(&fields))
+        .and_then(|_| check_dependencies(&fields))
+        .and_then(|_| check_github_steps(&fields))
+        .and_then(|_| check_editing(&fields))
+    {
+        return e.to_compile_error().into();
+    }
     let preset_ident = &opts.ident;
 
     // Generate the RON config struct
-    let ron_type = generate_ron_type(&opts.ident, &opts.id, &fields);
+    let ron_type = generate_ron_type(&opts.ident, opts.group, &fields);
 
     // Generate conversion methods
-    let conversions = generate_conversions(&opts.ident, &opts.id, &fields);
+    let conversions = generate_conversions(&opts.ident, &opts.id, opts.group, &fields);
 
-    // Generate EditorPreset trait implementation
+    // Generate EditorPreset trait implementation, or the option metadata of a group
     let editor_preset = generate_editor_preset_impl(&opts, &fields);
 
+    // Generate the option IDs, checking flattened groups don't repeat any
+    let option_ids = generate_option_ids(preset_ident, &fields);
+
+    // Generate the JSON Schema of the RON config struct
+    let schema = generate_schema_method(&opts, &fields);
+
+    // Generate Detectable from the detect_* markers, if there are any
+    let detectable = generate_detectable_impl(&opts);
+
     // Generate default() method using field defaults
     let default_fields = fields.iter().map(|field| {
         let field_ident = field.ident.as_ref().unwrap();
+        let field_ty = &field.ty;
         let default_expr = field
             .default
             .as_ref()
             .map(|s| s.parse::<proc_macro2::TokenStream>().unwrap())
-            .unwrap_or_else(|| quote::quote! { Default::default() });
+            .unwrap_or_else(|| match field.flatten {
+                true => quote::quote! { #field_ty::default() },
+                false => quote::quote! { Default::default() },
+            });
         quote::quote! {
             #field_ident: #default_expr
         }
@@ -105,9 +483,40 @@ pub fn derive_preset_impl(input: TokenStream) -> TokenStream {
     let expanded = quote! {
         #ron_type
         #conversions
+        #option_ids
+        #schema
         #editor_preset
+        #detectable
         #default_impl
     };
 
     TokenStream::from(expanded)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opts(input: DeriveInput) -> PresetOpts {
+        PresetOpts::from_derive_input(&input).unwrap()
+    }
+
+    #[test]
+    fn test_matches_must_be_variant_names() {
+        let malformed = opts(syn::parse_quote! {
+            #[preset(id = "demo", name = "Demo", description = "Demo", matches = "RustBinary, RustLibrary")]
+            struct Demo {}
+        });
+        let error = check_matches(&malformed).unwrap_err().to_string();
+        assert_eq!(
+            error,
+            "matches is ProjectType variants separated by `|`, not `RustBinary, RustLibrary`"
+        );
+
+        let valid = opts(syn::parse_quote! {
+            #[preset(id = "demo", name = "Demo", description = "Demo", matches = "RustBinary | RustLibrary")]
+            struct Demo {}
+        });
+        assert!(check_matches(&valid).is_ok());
+    }
+}
@@ -17,18 +17,43 @@ struct PresetEnumOpts {
 
 /// Variant-level attributes for #[preset_variant(...)]
 #[derive(Debug, FromVariant)]
-#[darling(attributes(preset_variant))]
+#[darling(attributes(preset_variant), forward_attrs(doc))]
 struct PresetVariantOpts {
     ident: syn::Ident,
 
+    /// Doc comments, shown as the variant's description
+    attrs: Vec<syn::Attribute>,
+
     /// String identifier
     id: String,
 
     /// Display name
-    #[allow(dead_code)]
     display: String,
 }
 
+impl PresetVariantOpts {
+    /// The doc comment's lines joined into one
+    fn description(&self) -> String {
+        let lines: Vec<String> = self
+            .attrs
+            .iter()
+            .filter_map(|attr| match &attr.meta {
+                syn::Meta::NameValue(syn::MetaNameValue {
+                    value:
+                        syn::Expr::Lit(syn::ExprLit {
+                            lit: syn::Lit::Str(doc),
+                            ..
+                        }),
+                    ..
+                }) => Some(doc.value().trim().to_string()),
+                _ => None,
+            })
+            .filter(|line| !line.is_empty())
+            .collect();
+        lines.join(" ")
+    }
+}
+
 pub fn derive_preset_enum_impl(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
@@ -61,11 +86,21 @@ pub fn derive_preset_enum_impl(input: TokenStream) -> TokenStream {
     // Generate all_variants method
     let all_variant_ids = variants.iter().map(|v| &v.id);
 
+    // Generate variant_info method
+    let variant_infos = variants.iter().map(|v| {
+        let id = &v.id;
+        let display = &v.display;
+        let description = v.description();
+        quote! {
+            (#id, #display, #description)
+        }
+    });
+
     // Generate Default implementation
     let default_impl = if let Some(ref default_name) = opts.default {
         let default_variant = variants
             .iter()
-            .find(|v| v.ident.to_string() == *default_name)
+            .find(|v| v.ident == default_name)
             .expect("Default variant not found");
         let default_ident = &default_variant.ident;
         quote! {
@@ -97,6 +132,11 @@ pub fn derive_preset_enum_impl(input: TokenStream) -> TokenStream {
             pub fn all_variants() -> Vec<&'static str> {
                 vec![#(#all_variant_ids),*]
             }
+
+            /// Each variant's ID, display name and description, for the editor
+            pub fn variant_info() -> Vec<(&'static str, &'static str, &'static str)> {
+                vec![#(#variant_infos),*]
+            }
         }
 
         #default_impl
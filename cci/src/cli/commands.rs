@@ -1,170 +1,2051 @@
-use crate::config::{preset_choice_to_config, CciConfig};
+use crate::cli::json::print_json;
+use crate::cli::OutputFormat;
+use crate::config::migrate::CURRENT_VERSION;
+use crate::config::{locate_config, preset_choice_to_config, CciConfig, ConfigFormat};
+use crate::detection::DetectionOptions;
 use crate::editor::registry::build_registry;
-use crate::error::Result;
+use crate::error::{config_error, unsupported_platform_error, Result};
 use crate::generator::MultiPresetGenerator;
 use anyhow::{bail, Context};
 use colored::Colorize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-/// Handle the generate command
-pub fn handle_generate(config_path: &str, platform_arg: Option<String>, force: bool) -> Result<()> {
-    use crate::detection::DetectorRegistry;
+/// Parse platform identifiers from the command line, defaulting to GitHub
+///
+/// Duplicates are dropped while preserving the order given by the user.
+pub fn parse_platforms(platform_args: &[String]) -> Result<Vec<crate::editor::state::Platform>> {
     use crate::editor::state::Platform;
 
-    // 1. Load and parse RON
-    println!("{} {}", "Loading".cyan().bold(), config_path);
-    let ron_str = std::fs::read_to_string(config_path)
-        .with_context(|| format!("Failed to read config file: {}", config_path))?;
+    if platform_args.is_empty() {
+        return Ok(vec![Platform::GitHub]);
+    }
+
+    let mut platforms = Vec::new();
+    for arg in platform_args {
+        if arg.eq_ignore_ascii_case("all") {
+            for platform in Platform::all() {
+                if !platforms.contains(&platform) {
+                    platforms.push(platform);
+                }
+            }
+            continue;
+        }
+
+        let platform = Platform::from_id(arg).ok_or_else(|| unsupported_platform_error(arg))?;
+        if !platforms.contains(&platform) {
+            platforms.push(platform);
+        }
+    }
+
+    Ok(platforms)
+}
 
-    // 2. Parse RON
-    println!("{}", "Parsing RON configuration...".cyan().bold());
-    let config: CciConfig = ron::Options::default()
-        .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
-        .from_str(&ron_str)
-        .with_context(|| "Failed to parse RON config. Check syntax and structure.")?;
+/// Read a config file, or stdin when the path is `-`
+fn read_config_source(config_path: &str) -> Result<String> {
+    if config_path == "-" {
+        let mut source = String::new();
+        std::io::Read::read_to_string(&mut std::io::stdin(), &mut source)
+            .context("Failed to read config from stdin")?;
+        return Ok(source);
+    }
+    std::fs::read_to_string(config_path)
+        .with_context(|| format!("Failed to read config file: {}", config_path))
+}
 
-    if config.is_empty() {
+/// The config file `config_path` names, which is a cci.yaml or cci.toml
+/// when that's what the project has instead of the default cci.ron
+fn config_file(config_path: &str) -> String {
+    if config_path == "-" {
+        return config_path.to_string();
+    }
+    locate_config(Path::new(config_path))
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Read and parse a config file in the format its extension names (RON
+/// from stdin), rejecting configs without presets
+fn load_config(config_path: &str) -> Result<CciConfig> {
+    let config_path = config_file(config_path);
+    let config_path = config_path.as_str();
+    let source = read_config_source(config_path)?;
+
+    let format = ConfigFormat::from_path(Path::new(config_path));
+    let migrated = format.migrate(&source).with_context(|| {
+        format!(
+            "Failed to parse {} config. Check syntax and structure.",
+            format.name()
+        )
+    })?;
+    if migrated.from_version < CURRENT_VERSION {
+        eprintln!(
+            "{} {} is config version {}; run `cci config migrate` to upgrade it",
+            "note:".yellow().bold(),
+            if config_path == "-" {
+                "stdin"
+            } else {
+                config_path
+            },
+            migrated.from_version
+        );
+    }
+
+    let mut config = migrated.config;
+    if config.presets.is_empty() {
         bail!("No presets defined in configuration file");
     }
+    config.resolve_extends(config_source_dir(config_path))?;
+    config.check_values()?;
+    Ok(config)
+}
+
+/// The directory `Extends` entries' `from` paths are relative to
+fn config_source_dir(config_path: &str) -> &Path {
+    match Path::new(config_path).parent() {
+        Some(dir) if config_path != "-" && !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    }
+}
+
+/// Generate every output file for `platforms`, in order
+///
+/// Support files shared between platforms are only listed once, under the
+/// first platform that produces them. `file_naming` is the config's.
+fn generate_outputs(
+    preset_configs: &[(String, crate::editor::config::PresetConfig)],
+    file_naming: crate::editor::state::FileNaming,
+    platforms: &[crate::editor::state::Platform],
+    language_version: &str,
+) -> Result<Vec<(crate::editor::state::Platform, PathBuf, String)>> {
+    let registry = Arc::new(build_registry());
+    let mut outputs: Vec<(_, PathBuf, String)> = Vec::new();
+    for platform in platforms {
+        let generator = MultiPresetGenerator::new(
+            preset_configs.to_vec(),
+            registry.clone(),
+            *platform,
+            language_version.to_string(),
+        )
+        .with_file_naming(file_naming);
+
+        let platform_outputs = generator.generate_all().with_context(|| {
+            format!(
+                "Failed to generate CI configurations for {}",
+                platform.name()
+            )
+        })?;
+
+        for (filename, content) in platform_outputs {
+            // Support files can be shared between platforms
+            if !outputs.iter().any(|(_, known, _)| *known == filename) {
+                outputs.push((*platform, filename, content));
+            }
+        }
+    }
+    Ok(outputs)
+}
+
+/// Handle the generate command
+///
+/// With `stdout`, the generated files are printed instead of written and
+/// nothing else is printed, so the output can be piped. With `dry_run`, the
+/// files that would change are listed with diffs and nothing is written.
+pub fn handle_generate(
+    config_path: &str,
+    platform_args: Vec<String>,
+    force: bool,
+    stdout: bool,
+    dry_run: bool,
+    recursive: bool,
+    lint: bool,
+) -> Result<()> {
+    if stdout {
+        return print_generated(config_path, &platform_args);
+    }
+    if recursive {
+        return generate_recursive(&platform_args, force, dry_run, lint);
+    }
+    generate_files(config_path, &platform_args, force, dry_run, lint).map(|_| ())
+}
+
+/// The presets used and the files generated from them
+type Generated = (
+    Vec<String>,
+    Vec<(crate::editor::state::Platform, PathBuf, String)>,
+);
+
+/// Generate the pipelines `config_path` describes and write them, unless `dry_run`
+fn generate_files(
+    config_path: &str,
+    platform_args: &[String],
+    force: bool,
+    dry_run: bool,
+    lint: bool,
+) -> Result<Generated> {
+    use crate::detection::DetectorRegistry;
+
+    // 1. Load and parse RON
+    println!("{} {}", "Loading".cyan().bold(), config_path);
+    let config = load_config(config_path)?;
 
     // 3. Detect project and determine platform
     let working_dir = std::path::PathBuf::from(".");
     let detector_registry = DetectorRegistry::new();
-    let detection = detector_registry.detect(&working_dir)?;
+    let detection = detector_registry.detect(&working_dir)?.remove(0);
+
+    let platforms = parse_platforms(platform_args)?;
+    let platform_names: Vec<String> = platforms
+        .iter()
+        .map(|p| format!("{:?}", p).yellow().to_string())
+        .collect();
 
-    let platform = if let Some(p) = platform_arg {
-        match p.to_lowercase().as_str() {
-            "github" => Platform::GitHub,
-            "gitea" => Platform::Gitea,
-            "gitlab" => Platform::GitLab,
-            "circleci" => Platform::CircleCI,
-            "jenkins" => Platform::Jenkins,
-            _ => Platform::GitHub,
+    println!(
+        "{} {} preset(s) for platform(s) {}",
+        "Found".green().bold(),
+        config.presets.len(),
+        platform_names.join(", ")
+    );
+
+    // 4. Convert to preset configs
+    let mut preset_configs = Vec::new();
+
+    for preset_choice in config.presets {
+        let (preset_id, preset_config) = preset_choice_to_config(&preset_choice);
+        println!("  {} {}", "•".blue(), preset_id);
+        preset_configs.push((preset_id, preset_config));
+    }
+    let preset_ids: Vec<String> = preset_configs.iter().map(|(id, _)| id.clone()).collect();
+
+    // 5. Generate outputs for every requested platform
+    println!("\n{}", "Generating CI configurations...".cyan().bold());
+    let language_version = detection
+        .language_version
+        .unwrap_or_else(|| "stable".to_string());
+
+    let outputs = generate_outputs(
+        &preset_configs,
+        config.file_naming,
+        &platforms,
+        &language_version,
+    )?;
+
+    // 6. Check for conflicts before touching the filesystem
+    let base_path = PathBuf::from(".");
+    if dry_run {
+        print_plan(&outputs, &base_path, force)?;
+        if lint {
+            report_findings(&outputs);
         }
+        return Ok((preset_ids, outputs));
+    }
+
+    write_outputs(&outputs, &base_path, force)?;
+    if lint {
+        report_findings(&outputs);
+    }
+
+    // 8. Summarize per platform
+    println!("\n{}", "Summary:".cyan().bold());
+    for platform in &platforms {
+        let count = outputs.iter().filter(|(p, _, _)| p == platform).count();
+        println!("  {} {}: {} file(s)", "•".blue(), platform.name(), count);
+    }
+
+    println!("\n{}", "Done!".green().bold());
+    Ok((preset_ids, outputs))
+}
+
+/// `cci generate --commit`: generate, then commit the files to `branch` and optionally open a pull request
+///
+/// Everything that could fail before the files are written (the repository,
+/// the branch, the remote and the token) is checked first.
+pub fn handle_generate_commit(
+    config_path: &str,
+    platform_args: Vec<String>,
+    force: bool,
+    lint: bool,
+    branch: &str,
+    pull_request: bool,
+) -> Result<()> {
+    use crate::git::{Publish, Repo};
+
+    let repo = Repo::open(&PathBuf::from("."))?;
+    let publish = Publish::prepare(
+        &repo,
+        branch,
+        pull_request,
+        &parse_platforms(&platform_args)?,
+    )?;
+
+    let (preset_ids, outputs) = generate_files(config_path, &platform_args, force, false, lint)?;
+    let mut paths: Vec<PathBuf> = outputs.iter().map(|(_, path, _)| path.clone()).collect();
+    if config_path != "-" && Path::new(config_path).exists() {
+        paths.push(PathBuf::from(config_path));
+    }
+    let Some(published) = publish.publish(&repo, config_path, &preset_ids, &paths)? else {
+        println!(
+            "\n{} the generated files are already committed",
+            "Nothing to commit:".yellow().bold()
+        );
+        return Ok(());
+    };
+    println!(
+        "{} {} file(s) to {}",
+        "Committed".green().bold(),
+        published.changes.len(),
+        branch.yellow()
+    );
+    if let (Some(url), Some(name)) = (published.url, publish.request_name()) {
+        println!("{} {} {}", "Opened".green().bold(), name, url);
+    }
+    Ok(())
+}
+
+/// Write generated files, refusing to replace existing ones unless `force` is set
+fn write_outputs(
+    outputs: &[(crate::editor::state::Platform, PathBuf, String)],
+    base_path: &std::path::Path,
+    force: bool,
+) -> Result<()> {
+    use crate::generator::merge::merge_into_existing;
+
+    if !force {
+        for (_, filename, _) in outputs {
+            let output_path = base_path.join(filename);
+            if output_path.exists() {
+                bail!(
+                    "File exists: {}. Use --force to overwrite",
+                    output_path.display()
+                );
+            }
+        }
+    }
+
+    // Keep jobs and steps added to existing pipelines by hand
+    for (platform, filename, content) in outputs {
+        // Use the full path (includes subdirectories like .github/workflows)
+        let output_path = base_path.join(filename);
+
+        // Create parent directories if needed
+        if let Some(parent) = output_path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+
+        let content = merge_into_existing(*platform, content, &output_path)?;
+        std::fs::write(&output_path, content)
+            .with_context(|| format!("Failed to write file: {}", output_path.display()))?;
+
+        println!(
+            "  {} {}",
+            "✓".green().bold(),
+            output_path.display().to_string().yellow()
+        );
+    }
+    Ok(())
+}
+
+/// Print what cci's lint rules, and actionlint if it's installed, find in generated files
+///
+/// These are warnings: the files are written either way.
+fn report_findings(outputs: &[(crate::editor::state::Platform, PathBuf, String)]) {
+    use crate::lint::{check_generated, platform_for_path};
+
+    let mut printed = false;
+    for (platform, filename, content) in outputs {
+        if platform_for_path(filename) != Some(*platform) {
+            continue;
+        }
+        let issues = check_generated(*platform, filename, content);
+        if issues.is_empty() {
+            continue;
+        }
+        if !printed {
+            println!("\n{}", "Warnings:".yellow().bold());
+            printed = true;
+        }
+        println!("  {}", filename.display().to_string().yellow());
+        for issue in issues {
+            println!("    {} {}", "warning".yellow().bold(), issue);
+        }
+    }
+}
+
+/// `cci generate --recursive`: generate scoped pipelines for every project under the current directory
+fn generate_recursive(
+    platform_args: &[String],
+    force: bool,
+    dry_run: bool,
+    lint: bool,
+) -> Result<()> {
+    use crate::generator::monorepo::{default_presets, find_projects, generate_projects, Project};
+
+    let base_path = PathBuf::from(".");
+    let platforms = parse_platforms(platform_args)?;
+    let registry = Arc::new(build_registry());
+
+    println!("{}", "Searching for projects...".cyan().bold());
+    let mut projects = Vec::new();
+    let options = DetectionOptions::load(&base_path);
+    for (dir, detection) in find_projects(&base_path, &options) {
+        let config_path = locate_config(&dir.join("cci.ron"));
+        let preset_configs: Vec<(String, crate::editor::config::PresetConfig)> =
+            if config_path.exists() {
+                load_config(&config_path.to_string_lossy())?
+                    .presets
+                    .iter()
+                    .map(preset_choice_to_config)
+                    .collect()
+            } else {
+                default_presets(&registry, &detection, &dir)
+            };
+        if preset_configs.is_empty() {
+            println!(
+                "  {} {} ({}): no preset supports this project type, skipping",
+                "!".yellow().bold(),
+                dir.display(),
+                detection.project_type
+            );
+            continue;
+        }
+        println!(
+            "  {} {} ({})",
+            "•".blue(),
+            dir.display(),
+            detection.project_type
+        );
+        projects.push(Project {
+            dir,
+            detection,
+            preset_configs,
+        });
+    }
+    if projects.is_empty() {
+        bail!("No projects found under the current directory");
+    }
+
+    println!("\n{}", "Generating CI configurations...".cyan().bold());
+    let mut outputs = Vec::new();
+    let mut files_per_project = vec![0; projects.len()];
+    for platform in &platforms {
+        for (project, path, content) in generate_projects(&projects, registry.clone(), *platform)? {
+            if let Some(index) = project {
+                files_per_project[index] += 1;
+            }
+            outputs.push((*platform, path, content));
+        }
+    }
+
+    if dry_run {
+        print_plan(&outputs, &base_path, force)?;
+    } else {
+        write_outputs(&outputs, &base_path, force)?;
+    }
+    if lint {
+        report_findings(&outputs);
+    }
+
+    println!("\n{}", "Summary:".cyan().bold());
+    let rows: Vec<[String; 4]> = projects
+        .iter()
+        .zip(&files_per_project)
+        .map(|(project, files)| {
+            let presets: Vec<&str> = project
+                .preset_configs
+                .iter()
+                .map(|(id, _)| id.as_str())
+                .collect();
+            [
+                project.dir.display().to_string(),
+                project.detection.project_type.to_string(),
+                presets.join(", "),
+                files.to_string(),
+            ]
+        })
+        .collect();
+    let header = ["Project", "Type", "Presets", "Files"];
+    let mut widths = header.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: [&str; 4]| {
+        format!(
+            "  {:w0$}  {:w1$}  {:w2$}  {:>w3$}",
+            cells[0],
+            cells[1],
+            cells[2],
+            cells[3],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+            w3 = widths[3]
+        )
+    };
+    println!("{}", line(header).bold());
+    for row in &rows {
+        println!("{}", line([&row[0], &row[1], &row[2], &row[3]]));
+    }
+    let parents = outputs.len() - files_per_project.iter().sum::<usize>();
+    if parents > 0 {
+        println!(
+            "  plus {} parent pipeline(s) triggering the projects",
+            parents
+        );
+    }
+
+    if !dry_run {
+        println!("\n{}", "Done!".green().bold());
+    }
+    Ok(())
+}
+
+/// List what `cci generate` would write, with a diff for each existing file it changes
+fn print_plan(
+    outputs: &[(crate::editor::state::Platform, PathBuf, String)],
+    base_path: &std::path::Path,
+    force: bool,
+) -> Result<()> {
+    use crate::generator::merge::merge_into_existing;
+
+    println!("\n{}", "Dry run, nothing is written:".cyan().bold());
+    let (mut created, mut overwritten) = (0, 0);
+    for (platform, filename, content) in outputs {
+        let output_path = base_path.join(filename);
+        let display = output_path.display().to_string();
+        match std::fs::read_to_string(&output_path) {
+            Err(_) => {
+                created += 1;
+                println!("  {} {} (create)", "+".green().bold(), display.yellow());
+            }
+            Ok(existing) => {
+                let content = merge_into_existing(*platform, content, &output_path)?;
+                if existing == content {
+                    println!("  {} {} (unchanged)", "=".dimmed(), display.dimmed());
+                } else {
+                    overwritten += 1;
+                    println!("  {} {} (overwrite)", "~".yellow().bold(), display.yellow());
+                    crate::diff::print_colored_diff(&existing, &content);
+                }
+            }
+        }
+    }
+
+    println!(
+        "\n{} file(s) would be created, {} overwritten",
+        created, overwritten
+    );
+    let existing = outputs
+        .iter()
+        .filter(|(_, filename, _)| base_path.join(filename).exists())
+        .count();
+    if existing > 0 && !force {
+        println!(
+            "{} {} file(s) already exist, so generating needs {}",
+            "ℹ".blue(),
+            existing,
+            "--force".yellow()
+        );
+    }
+    Ok(())
+}
+
+/// Print every generated file to stdout without touching the filesystem
+///
+/// A single file is printed as is; several are each preceded by a
+/// `==> path <==` line. Support files are included.
+fn print_generated(config_path: &str, platform_args: &[String]) -> Result<()> {
+    use crate::detection::DetectorRegistry;
+
+    let config = load_config(config_path)?;
+    let platforms = parse_platforms(platform_args)?;
+    // Piped configs may not come from a project directory
+    let language_version = DetectorRegistry::new()
+        .detect(&PathBuf::from("."))
+        .ok()
+        .and_then(|mut detections| detections.remove(0).language_version)
+        .unwrap_or_else(|| "stable".to_string());
+    let preset_configs: Vec<_> = config.presets.iter().map(preset_choice_to_config).collect();
+    let outputs = generate_outputs(
+        &preset_configs,
+        config.file_naming,
+        &platforms,
+        &language_version,
+    )?;
+
+    if let [(_, _, content)] = outputs.as_slice() {
+        print!("{}", content);
+        return Ok(());
+    }
+    for (index, (_, filename, content)) in outputs.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        println!("==> {} <==", filename.display());
+        print!("{}", content);
+    }
+    Ok(())
+}
+
+/// Handle the check command
+///
+/// Regenerates every file in memory and compares it with the one on disk, so
+/// CI can fail when a generated config was edited or cci.ron changed without
+/// regenerating. Jobs and steps added by hand don't count as drift.
+pub fn handle_check(
+    config_path: &str,
+    platform_args: Vec<String>,
+    format: OutputFormat,
+) -> Result<()> {
+    use crate::detection::DetectorRegistry;
+    use crate::generator::merge::merge_into_existing;
+
+    #[derive(serde::Serialize)]
+    struct FileStatus {
+        path: PathBuf,
+        platform: &'static str,
+        /// `ok`, `stale` or `missing`
+        status: &'static str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        diff: Option<String>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct CheckReport {
+        up_to_date: bool,
+        files: Vec<FileStatus>,
+    }
+
+    let config = load_config(config_path)?;
+    let platforms = parse_platforms(&platform_args)?;
+    let language_version = DetectorRegistry::new()
+        .detect(&PathBuf::from("."))
+        .ok()
+        .and_then(|mut detections| detections.remove(0).language_version)
+        .unwrap_or_else(|| "stable".to_string());
+    let preset_configs: Vec<_> = config.presets.iter().map(preset_choice_to_config).collect();
+    let outputs = generate_outputs(
+        &preset_configs,
+        config.file_naming,
+        &platforms,
+        &language_version,
+    )?;
+
+    let mut files = Vec::new();
+    for (platform, filename, content) in &outputs {
+        let expected = merge_into_existing(*platform, content, filename)?;
+        let (status, diff) = match std::fs::read_to_string(filename) {
+            Ok(actual) if actual == expected => ("ok", None),
+            Ok(actual) => ("stale", Some((actual, expected))),
+            Err(_) => ("missing", None),
+        };
+
+        if format == OutputFormat::Text {
+            match (status, &diff) {
+                ("ok", _) => println!("  {} {}", "✓".green().bold(), filename.display()),
+                (_, Some((actual, expected))) => {
+                    println!(
+                        "  {} {} is out of date",
+                        "✗".red().bold(),
+                        filename.display().to_string().yellow()
+                    );
+                    crate::diff::print_colored_diff(actual, expected);
+                }
+                _ => println!(
+                    "  {} {} is missing",
+                    "✗".red().bold(),
+                    filename.display().to_string().yellow()
+                ),
+            }
+        }
+        files.push(FileStatus {
+            path: filename.clone(),
+            platform: platform.id(),
+            status,
+            diff: diff.map(|(actual, expected)| {
+                crate::diff::unified_diff(
+                    &actual,
+                    &expected,
+                    &filename.display().to_string(),
+                    "generated",
+                )
+            }),
+        });
+    }
+
+    let stale = files.iter().filter(|file| file.status != "ok").count();
+    match format {
+        OutputFormat::Json => print_json(&CheckReport {
+            up_to_date: stale == 0,
+            files,
+        })?,
+        OutputFormat::Text if stale == 0 => {
+            println!("\n{}", "All generated files are up to date".green().bold())
+        }
+        OutputFormat::Text => {}
+    }
+    if stale > 0 {
+        bail!(
+            "{} generated file(s) out of date; run `cci generate --force` to update them",
+            stale
+        );
+    }
+    Ok(())
+}
+
+/// Handle the diff command
+pub fn handle_diff(old_path: &str, new_path: &str, platform_args: Vec<String>) -> Result<()> {
+    use crate::detection::DetectorRegistry;
+    use crate::generator::merge::strip_markers;
+
+    let platforms = parse_platforms(&platform_args)?;
+    let language_version = DetectorRegistry::new()
+        .detect(&PathBuf::from("."))
+        .ok()
+        .and_then(|mut detections| detections.remove(0).language_version)
+        .unwrap_or_else(|| "stable".to_string());
+    let generate = |config_path: &str| -> Result<Vec<(PathBuf, String)>> {
+        let config = load_config(config_path)?;
+        let preset_configs: Vec<_> = config.presets.iter().map(preset_choice_to_config).collect();
+        Ok(generate_outputs(
+            &preset_configs,
+            config.file_naming,
+            &platforms,
+            &language_version,
+        )?
+        .into_iter()
+        .map(|(_, filename, content)| (filename, strip_markers(&content)))
+        .collect())
+    };
+    let old = generate(old_path)?;
+    let new = generate(new_path)?;
+
+    // Files in the order the old config generates them, then files it doesn't
+    let mut filenames: Vec<&PathBuf> = old.iter().map(|(filename, _)| filename).collect();
+    for (filename, _) in &new {
+        if !filenames.contains(&filename) {
+            filenames.push(filename);
+        }
+    }
+    let content = |outputs: &[(PathBuf, String)], filename: &PathBuf| {
+        outputs
+            .iter()
+            .find(|(known, _)| known == filename)
+            .map(|(_, content)| content.clone())
+    };
+
+    let mut changed = 0;
+    for filename in filenames {
+        let (before, after) = (content(&old, filename), content(&new, filename));
+        if before == after {
+            continue;
+        }
+        changed += 1;
+        let status = match (&before, &after) {
+            (None, _) => "added".green(),
+            (_, None) => "removed".red(),
+            _ => "changed".yellow(),
+        };
+        println!(
+            "{} {} ({})",
+            "•".blue(),
+            filename.display().to_string().bold(),
+            status
+        );
+        crate::diff::print_colored_diff(&before.unwrap_or_default(), &after.unwrap_or_default());
+        println!();
+    }
+
+    if changed == 0 {
+        println!("{}", "Both configs generate the same files".green().bold());
     } else {
-        Platform::GitHub // Default platform
+        println!("{} file(s) differ", changed);
+    }
+    Ok(())
+}
+
+/// Handle the explain command
+pub fn handle_explain(target: &str, config_path: &str, platform_args: Vec<String>) -> Result<()> {
+    use crate::detection::DetectorRegistry;
+    use crate::generator::explain::{explain, Origin};
+
+    let config = load_config(config_path)?;
+    let platforms = parse_platforms(&platform_args)?;
+    let registry = Arc::new(build_registry());
+    let language_version = DetectorRegistry::new()
+        .detect(&PathBuf::from("."))
+        .ok()
+        .and_then(|mut detections| detections.remove(0).language_version)
+        .unwrap_or_else(|| "stable".to_string());
+    let preset_configs: Vec<_> = config.presets.iter().map(preset_choice_to_config).collect();
+
+    let mut found = 0;
+    for platform in platforms {
+        let explanations = explain(
+            &preset_configs,
+            config.file_naming,
+            registry.clone(),
+            platform,
+            &language_version,
+        )
+        .with_context(|| {
+            format!(
+                "Failed to generate CI configurations for {}",
+                platform.name()
+            )
+        })?;
+
+        for explanation in explanations.iter().filter(|e| e.matches(target)) {
+            found += 1;
+            let item = &explanation.item;
+            match &item.step {
+                None => println!(
+                    "{} {} in {}",
+                    "Job".cyan().bold(),
+                    item.job.yellow(),
+                    item.file.display()
+                ),
+                Some(step) => println!(
+                    "{} {} of job {} in {}",
+                    "Step".cyan().bold(),
+                    step.yellow(),
+                    item.job.yellow(),
+                    item.file.display()
+                ),
+            }
+            for origin in &explanation.origins {
+                let preset_name = |id: &str| {
+                    registry
+                        .get(id)
+                        .map_or_else(|| id.to_string(), |p| p.preset_name().to_string())
+                };
+                match origin {
+                    Origin::Preset { preset } => {
+                        println!(
+                            "  {} always generated by {}",
+                            "•".blue(),
+                            preset_name(preset)
+                        )
+                    }
+                    Origin::Decorator { preset } => {
+                        println!(
+                            "  {} added by the {} addon",
+                            "•".blue(),
+                            preset_name(preset)
+                        )
+                    }
+                    Origin::Option {
+                        preset,
+                        feature,
+                        option,
+                        value,
+                    } => println!(
+                        "  {} {} › {} › {} = {}",
+                        "•".blue(),
+                        preset_name(preset),
+                        feature,
+                        option,
+                        value.green()
+                    ),
+                }
+            }
+        }
+    }
+
+    if found == 0 {
+        bail!("No generated job or step matches `{}`", target);
+    }
+    Ok(())
+}
+
+/// Handle the secrets command
+pub fn handle_secrets(config_path: &str, platform_args: Vec<String>) -> Result<()> {
+    use crate::platforms::secrets::{setup_command, setup_location};
+
+    let config = load_config(config_path)?;
+    let platforms = parse_platforms(&platform_args)?;
+    let registry = Arc::new(build_registry());
+    let preset_configs: Vec<_> = config.presets.iter().map(preset_choice_to_config).collect();
+
+    for platform in platforms {
+        // Secrets don't depend on the toolchain version
+        let generator = MultiPresetGenerator::new(
+            preset_configs.clone(),
+            registry.clone(),
+            platform,
+            "stable".to_string(),
+        );
+        let secrets = generator.required_secrets();
+
+        println!("{}", platform.name().cyan().bold());
+        if secrets.is_empty() {
+            println!("  {} No secrets required", "✓".green());
+            println!();
+            continue;
+        }
+
+        println!(
+            "  {} {}",
+            "Configure in:".dimmed(),
+            setup_location(platform)
+        );
+        for (secret, presets) in &secrets {
+            println!(
+                "  {} {} {}",
+                "•".blue(),
+                secret.name.yellow().bold(),
+                format!("({})", presets.join(", ")).dimmed()
+            );
+            println!("    {}", secret.description);
+            if let Some(command) = setup_command(platform, secret) {
+                println!("    {} {}", "$".dimmed(), command);
+            }
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+/// Handle the validate command
+pub fn handle_deps(dir: &str, tool: &str, schedule: &str, force: bool) -> Result<()> {
+    use crate::generator::deps::{
+        detect_ecosystems, DependencyTool, DependencyUpdates, UpdateSchedule,
     };
 
-    println!(
-        "{} {} preset(s) for platform {}",
-        "Found".green().bold(),
-        config.len(),
-        format!("{:?}", platform).yellow()
-    );
+    let tool = DependencyTool::from_id(tool).ok_or_else(|| {
+        config_error(format!(
+            "Unknown update tool '{}' (expected dependabot or renovate)",
+            tool
+        ))
+    })?;
+    let schedule = UpdateSchedule::from_id(schedule).ok_or_else(|| {
+        config_error(format!(
+            "Unknown schedule '{}' (expected daily, weekly or monthly)",
+            schedule
+        ))
+    })?;
+
+    let working_dir = PathBuf::from(dir);
+    let ecosystems = detect_ecosystems(&working_dir)?;
+
+    println!("{}", "Detected ecosystems:".cyan().bold());
+    for ecosystem in &ecosystems {
+        println!("  {} {}", "•".blue(), ecosystem.dependabot_id());
+    }
+
+    let updates = DependencyUpdates {
+        tool,
+        schedule,
+        ecosystems,
+    };
+    let content = updates.render()?;
+
+    let output_path = working_dir.join(tool.output_path());
+    if output_path.exists() && !force {
+        bail!(
+            "File exists: {}. Use --force to overwrite",
+            output_path.display()
+        );
+    }
+    if let Some(parent) = output_path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    std::fs::write(&output_path, content)
+        .with_context(|| format!("Failed to write file: {}", output_path.display()))?;
+
+    println!(
+        "\n{} {} {}",
+        "✓".green().bold(),
+        tool.name(),
+        output_path.display().to_string().yellow()
+    );
+    Ok(())
+}
+
+pub fn handle_hooks(config_path: &str, tool: &str, force: bool) -> Result<()> {
+    use crate::generator::hooks::{GitHooks, HookTool};
+    use crate::platforms::checks::collect_checks;
+
+    let tool = HookTool::from_id(tool).ok_or_else(|| {
+        config_error(format!(
+            "Unknown hook tool '{}' (expected pre-commit or lefthook)",
+            tool
+        ))
+    })?;
+
+    let config = load_config(config_path)?;
+    let registry = build_registry();
+    let preset_configs: Vec<_> = config.presets.iter().map(preset_choice_to_config).collect();
+    // Local checks use whatever toolchain is installed
+    let checks = collect_checks(&preset_configs, &registry, "stable");
+    if checks.is_empty() {
+        bail!("None of the configured presets run checks that can run locally");
+    }
+
+    println!("{}", "Checks:".cyan().bold());
+    for check in &checks {
+        println!(
+            "  {} {} {}",
+            "•".blue(),
+            check.id,
+            format!("({})", check.command).dimmed()
+        );
+    }
+
+    let content = GitHooks { tool, checks }.render()?;
+    let output_path = tool.output_path();
+    if output_path.exists() && !force {
+        bail!(
+            "File exists: {}. Use --force to overwrite",
+            output_path.display()
+        );
+    }
+    std::fs::write(&output_path, content)
+        .with_context(|| format!("Failed to write file: {}", output_path.display()))?;
+
+    println!(
+        "\n{} {} {}",
+        "✓".green().bold(),
+        tool.name(),
+        output_path.display().to_string().yellow()
+    );
+    println!("  {} {}", "$".dimmed(), tool.install_command());
+    Ok(())
+}
+
+pub fn handle_tasks(config_path: &str, tool: &str, force: bool) -> Result<()> {
+    use crate::generator::tasks::{TaskFile, TaskTool};
+    use crate::platforms::checks::collect_checks;
+
+    let tool = TaskTool::from_id(tool).ok_or_else(|| {
+        config_error(format!(
+            "Unknown task runner '{}' (expected just or make)",
+            tool
+        ))
+    })?;
+
+    let config = load_config(config_path)?;
+    let registry = build_registry();
+    let preset_configs: Vec<_> = config.presets.iter().map(preset_choice_to_config).collect();
+    let checks = collect_checks(&preset_configs, &registry, "stable");
+    if checks.is_empty() {
+        bail!("None of the configured presets run checks that can run locally");
+    }
+
+    let content = TaskFile { tool, checks }.render()?;
+    let output_path = tool.output_path();
+    if output_path.exists() && !force {
+        bail!(
+            "File exists: {}. Use --force to overwrite",
+            output_path.display()
+        );
+    }
+    std::fs::write(&output_path, content)
+        .with_context(|| format!("Failed to write file: {}", output_path.display()))?;
+
+    println!(
+        "{} {}",
+        "✓".green().bold(),
+        output_path.display().to_string().yellow()
+    );
+    println!("  {} {} ci", "$".dimmed(), tool.id());
+    Ok(())
+}
+
+pub fn handle_import(dir: &str, output: &str, force: bool) -> Result<()> {
+    use crate::config::preset_config_to_choice;
+    use crate::detection::{
+        import_github_workflows, unreadable_github_workflows, DetectorRegistry,
+    };
+
+    let working_dir = PathBuf::from(dir);
+    let language_version = DetectorRegistry::new()
+        .detect(&working_dir)
+        .ok()
+        .and_then(|mut detections| detections.remove(0).language_version)
+        .unwrap_or_else(|| "stable".to_string());
+
+    let registry = build_registry();
+    let imported = import_github_workflows(&working_dir, &registry, &language_version)?;
+    let unreadable = unreadable_github_workflows(&working_dir)?;
+    for (path, error) in &unreadable {
+        eprintln!("{} {}: {}", "⚠".yellow().bold(), path.display(), error);
+    }
+    if imported.is_empty() {
+        if let [(path, error)] = unreadable.as_slice() {
+            bail!("Failed to parse {}: {}", path.display(), error);
+        }
+        if !unreadable.is_empty() {
+            bail!(
+                "No workflow in {} matches a preset; {} couldn't be parsed",
+                working_dir.join(".github/workflows").display(),
+                unreadable.len()
+            );
+        }
+        bail!(
+            "No workflow in {} matches a preset",
+            working_dir.join(".github/workflows").display()
+        );
+    }
+
+    let mut config = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    for workflow in &imported {
+        let preset_ids: Vec<&str> = workflow
+            .configs
+            .iter()
+            .map(|config| config.preset_id.as_str())
+            .collect();
+        let fit = if workflow.distance == 0 {
+            "exact match".green().to_string()
+        } else {
+            format!("{} difference(s)", workflow.distance)
+                .yellow()
+                .to_string()
+        };
+        println!(
+            "{} {} → {} ({})",
+            "✓".green().bold(),
+            workflow.path.display(),
+            preset_ids.join(", ").cyan(),
+            fit
+        );
+
+        for preset_config in &workflow.configs {
+            if seen.insert(preset_config.preset_id.clone()) {
+                config.push(preset_config_to_choice(
+                    &preset_config.preset_id,
+                    preset_config,
+                ));
+            }
+        }
+    }
+
+    let output_path = PathBuf::from(output);
+    if output_path.exists() && !force {
+        bail!(
+            "File exists: {}. Use --force to overwrite",
+            output_path.display()
+        );
+    }
+    let text = ConfigFormat::from_path(&output_path).write(&CciConfig::new(config))?;
+    std::fs::write(&output_path, text)
+        .with_context(|| format!("Failed to write file: {}", output_path.display()))?;
+
+    println!(
+        "{} {}",
+        "Wrote".green().bold(),
+        output_path.display().to_string().yellow()
+    );
+    Ok(())
+}
+
+pub fn handle_migrate(
+    from: &str,
+    to: &str,
+    input: Option<&str>,
+    output: Option<&str>,
+    force: bool,
+) -> Result<()> {
+    use crate::editor::state::Platform;
+    use crate::models::adapters::migrate;
+
+    let from = Platform::from_id(from).ok_or_else(|| unsupported_platform_error(from))?;
+    let to = Platform::from_id(to).ok_or_else(|| unsupported_platform_error(to))?;
+    let input_path = input.map_or_else(|| from.output_path(), PathBuf::from);
+    let output_path = output.map_or_else(|| to.output_path(), PathBuf::from);
+
+    let content = std::fs::read_to_string(&input_path)
+        .with_context(|| format!("Failed to read file: {}", input_path.display()))?;
+    let migration = migrate(&content, from, to)
+        .with_context(|| format!("Failed to translate {}", input_path.display()))?;
+
+    if output_path.exists() && !force {
+        bail!(
+            "File exists: {}. Use --force to overwrite",
+            output_path.display()
+        );
+    }
+    if let Some(parent) = output_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&output_path, &migration.output)
+        .with_context(|| format!("Failed to write file: {}", output_path.display()))?;
+
+    println!(
+        "{} {} ({}) → {} ({})",
+        "Migrated".green().bold(),
+        input_path.display(),
+        from.name(),
+        output_path.display().to_string().yellow(),
+        to.name()
+    );
+    if !migration.notes.is_empty() {
+        println!(
+            "\n{} {} construct(s) could not be translated:",
+            "⚠".yellow().bold(),
+            migration.notes.len()
+        );
+        for note in &migration.notes {
+            println!("  - {}", note);
+        }
+    }
+    Ok(())
+}
+
+/// `cci run`: generate the pipeline in memory and run its jobs with act or gitlab-runner
+pub fn handle_run(
+    job: Option<&str>,
+    config_path: &str,
+    platform_arg: &str,
+    list: bool,
+    dry_run: bool,
+    extra_args: &[String],
+) -> Result<()> {
+    use crate::detection::DetectorRegistry;
+    use crate::editor::state::Platform;
+    use crate::generator::merge::merge_into_existing;
+    use crate::lint::platform_for_path;
+    use crate::local::{execute, invocations, jobs, select};
+
+    let platform =
+        Platform::from_id(platform_arg).ok_or_else(|| unsupported_platform_error(platform_arg))?;
+    let config = load_config(config_path)?;
+    let language_version = DetectorRegistry::new()
+        .detect(&PathBuf::from("."))
+        .ok()
+        .and_then(|mut detections| detections.remove(0).language_version)
+        .unwrap_or_else(|| "stable".to_string());
+    let preset_configs: Vec<_> = config.presets.iter().map(preset_choice_to_config).collect();
+    let outputs: Vec<(PathBuf, String)> = generate_outputs(
+        &preset_configs,
+        config.file_naming,
+        &[platform],
+        &language_version,
+    )?
+    .into_iter()
+    .map(|(_, filename, content)| (filename, content))
+    .collect();
+    let pipeline_jobs = jobs(platform, &outputs)?;
+
+    if list {
+        for job in &pipeline_jobs {
+            match &job.stage {
+                Some(stage) => println!("{} ({})", job.id, stage.dimmed()),
+                None => println!("{} ({})", job.id, job.file.display().to_string().dimmed()),
+            }
+        }
+        return Ok(());
+    }
+    let selected = select(&pipeline_jobs, job)?;
+
+    // act reads the workflows straight from a scratch directory, but
+    // gitlab-runner only reads .gitlab-ci.yml from the project
+    let workflow_dir = std::env::temp_dir().join(format!("cci-run-{}", std::process::id()));
+    let pipelines = outputs
+        .iter()
+        .filter(|(filename, _)| platform_for_path(filename) == Some(platform));
+    if platform == Platform::GitLab {
+        for (filename, content) in pipelines {
+            let expected = merge_into_existing(platform, content, filename)?;
+            match std::fs::read_to_string(filename) {
+                Ok(actual) if actual == expected => {}
+                Ok(_) => bail!(
+                    "{} is out of date and gitlab-runner reads it from disk; run `cci generate -p gitlab` first",
+                    filename.display()
+                ),
+                Err(_) => bail!(
+                    "{} is missing and gitlab-runner reads it from disk; run `cci generate -p gitlab` first",
+                    filename.display()
+                ),
+            }
+        }
+    } else if !dry_run {
+        std::fs::create_dir_all(&workflow_dir)
+            .with_context(|| format!("Failed to create directory: {}", workflow_dir.display()))?;
+        for (filename, content) in pipelines {
+            let path = workflow_dir.join(filename.file_name().unwrap_or_default());
+            std::fs::write(&path, content)
+                .with_context(|| format!("Failed to write file: {}", path.display()))?;
+        }
+    }
+
+    let runs = invocations(
+        platform,
+        &selected,
+        job.is_none(),
+        &workflow_dir,
+        extra_args,
+    )?;
+    if dry_run {
+        for run in &runs {
+            println!("{}", run);
+        }
+        return Ok(());
+    }
+
+    let mut results = Vec::new();
+    for run in &runs {
+        println!("\n{} {}", "Running".cyan().bold(), run.label.yellow());
+        println!("  {} {}\n", "$".dimmed(), run);
+        match execute(run, &PathBuf::from(".")) {
+            Ok(passed) => results.push((run, passed)),
+            Err(e) => {
+                let _ = std::fs::remove_dir_all(&workflow_dir);
+                return Err(e);
+            }
+        }
+    }
+    let _ = std::fs::remove_dir_all(&workflow_dir);
+
+    println!("\n{}", "Summary:".cyan().bold());
+    for (run, passed) in &results {
+        if *passed {
+            println!("  {} {}", "✓".green().bold(), run.label);
+        } else {
+            println!("  {} {}", "✗".red().bold(), run.label.yellow());
+        }
+    }
+    let failed = results.iter().filter(|(_, passed)| !passed).count();
+    if failed > 0 {
+        bail!("{} of {} run(s) failed", failed, results.len());
+    }
+    Ok(())
+}
+
+/// `cci run --native`: run the GitLab pipeline's commands on this machine or in containers
+pub fn handle_run_native(
+    job: Option<&str>,
+    config_path: &str,
+    docker: bool,
+    host: bool,
+    all_steps: bool,
+    list: bool,
+    dry_run: bool,
+) -> Result<()> {
+    use crate::detection::DetectorRegistry;
+    use crate::editor::state::Platform;
+    use crate::local::native::{local_pipeline, run, step_hazard, Options, Outcome};
+    use crate::local::{select, Job};
+    use crate::models::Step;
+    use crate::platforms::helpers::PlatformConfig;
+
+    let config = load_config(config_path)?;
+    let language_version = DetectorRegistry::new()
+        .detect(&PathBuf::from("."))
+        .ok()
+        .and_then(|mut detections| detections.remove(0).language_version)
+        .unwrap_or_else(|| "stable".to_string());
+    let preset_configs: Vec<_> = config.presets.iter().map(preset_choice_to_config).collect();
+    let generator = MultiPresetGenerator::new(
+        preset_configs,
+        Arc::new(build_registry()),
+        Platform::GitLab,
+        language_version,
+    );
+    let configs: Vec<_> = generator
+        .build_pipelines()?
+        .into_iter()
+        .filter_map(|(_, _, pipeline)| match pipeline {
+            PlatformConfig::GitLab(ci) => Some(ci),
+            _ => None,
+        })
+        .collect();
+
+    // Any job can be named, including ones that only run for some events
+    let every_id: Vec<String> = configs
+        .iter()
+        .flat_map(|ci| ci.jobs.keys().cloned())
+        .collect();
+    let all_jobs: Vec<Job> = local_pipeline(&configs, &every_id)
+        .0
+        .jobs
+        .into_iter()
+        .map(|job| Job {
+            id: job.id,
+            file: PathBuf::from(".gitlab-ci.yml"),
+            stage: job.stage,
+        })
+        .collect();
+    let targets: Vec<String> = match job {
+        Some(_) => select(&all_jobs, job)?
+            .into_iter()
+            .map(|job| job.id.clone())
+            .collect(),
+        None => Vec::new(),
+    };
+    let (pipeline, skipped) = local_pipeline(&configs, &targets);
+
+    if list {
+        for job in &pipeline.jobs {
+            println!("{}", job.id);
+        }
+        for report in &skipped {
+            println!("{} {}", report.id, "(only runs for some events)".dimmed());
+        }
+        return Ok(());
+    }
+    let selected = pipeline
+        .jobs
+        .iter()
+        .filter(|job| targets.is_empty() || targets.contains(&job.id));
+    if dry_run {
+        for job in selected {
+            match (&job.image, docker) {
+                (Some(image), true) => println!("{} (in {})", job.id.yellow(), image),
+                _ => println!("{}", job.id.yellow()),
+            }
+            let on_host = job.image.is_none() || !docker;
+            for step in &job.steps {
+                match step {
+                    Step::Run { command, .. }
+                        if !all_steps && step_hazard(command, on_host).is_some() =>
+                    {
+                        println!("  {} {}", "skip".dimmed(), step.label())
+                    }
+                    Step::Run { command, .. } => {
+                        for line in command.lines() {
+                            println!("  {} {}", "$".dimmed(), line);
+                        }
+                    }
+                    Step::Action { uses, .. } => println!("  {} {}", "skip".dimmed(), uses),
+                    Step::Checkout => {}
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    if !docker && !host {
+        bail!(
+            "--native runs the jobs' commands on this machine; pass --host to allow that, \
+             or --docker to run them in containers"
+        );
+    }
+    let options = Options {
+        dir: PathBuf::from("."),
+        docker,
+        host,
+        all_steps,
+        quiet: false,
+    };
+    let mut reports = run(&pipeline, &targets, &options)?;
+    if targets.is_empty() {
+        reports.extend(skipped);
+    }
+
+    println!("\n{}", "Summary:".cyan().bold());
+    for report in &reports {
+        let seconds = format!("{:.1}s", report.duration.as_secs_f64());
+        match &report.outcome {
+            Outcome::Passed => println!(
+                "  {} {} {}",
+                "✓".green().bold(),
+                report.id,
+                seconds.dimmed()
+            ),
+            Outcome::Failed { step } => println!(
+                "  {} {} failed at `{}`",
+                "✗".red().bold(),
+                report.id.yellow(),
+                step
+            ),
+            Outcome::Skipped { reason } => {
+                println!("  {} {} skipped: {}", "-".dimmed(), report.id, reason)
+            }
+        }
+    }
+    let failed = reports
+        .iter()
+        .filter(|report| matches!(report.outcome, Outcome::Failed { .. }))
+        .count();
+    if failed > 0 {
+        bail!("{} of {} job(s) failed", failed, reports.len());
+    }
+    Ok(())
+}
+
+/// `cci fleet`: regenerate the pipelines of many repositories
+///
+/// Repositories are the directories in `repos` and the `from` file, or every
+/// repository of `org`, cloned into its directory. With `template`, its
+/// contents become each repository's cci.ron. Without `commit`, the files that
+/// would change are only listed; with it, they're written and committed to
+/// the given branch, and a pull request is opened if asked for. A repository
+/// that fails doesn't stop the others.
+pub fn handle_fleet(
+    mut repos: Vec<String>,
+    from: Option<&str>,
+    org: Option<(&str, &str)>,
+    template: Option<&str>,
+    platform_args: Vec<String>,
+    commit: Option<(&str, bool)>,
+) -> Result<()> {
+    let platforms = parse_platforms(&platform_args)?;
+    if let Some(from) = from {
+        let list = std::fs::read_to_string(from)
+            .with_context(|| format!("Failed to read repository list: {}", from))?;
+        repos.extend(
+            list.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(str::to_string),
+        );
+    }
+    let mut dirs: Vec<PathBuf> = repos.iter().map(PathBuf::from).collect();
+    if let Some((org, clone_dir)) = org {
+        dirs = clone_org(org, Path::new(clone_dir), &platforms)?;
+    }
+    if dirs.is_empty() {
+        bail!("No repositories given; list their directories, or use --from or --org");
+    }
+    let template = template
+        .map(|path| {
+            std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read template: {}", path))
+        })
+        .transpose()?;
+
+    println!(
+        "{} {} repositories",
+        if commit.is_some() {
+            "Updating"
+        } else {
+            "Checking"
+        }
+        .cyan()
+        .bold(),
+        dirs.len()
+    );
+    let mut rows = Vec::new();
+    let mut failed = 0;
+    for dir in &dirs {
+        let row = match fleet_repo(dir, template.as_deref(), &platforms, commit) {
+            Ok(repo) => {
+                println!(
+                    "  {} {}: {}",
+                    "✓".green().bold(),
+                    dir.display(),
+                    repo.result
+                );
+                for file in &repo.files {
+                    println!("      {}", file.display().to_string().yellow());
+                }
+                [
+                    dir.display().to_string(),
+                    repo.project_type,
+                    repo.presets.join(", "),
+                    repo.result,
+                ]
+            }
+            Err(e) => {
+                failed += 1;
+                println!("  {} {}: {:#}", "✗".red().bold(), dir.display(), e);
+                [
+                    dir.display().to_string(),
+                    String::new(),
+                    String::new(),
+                    "failed".to_string(),
+                ]
+            }
+        };
+        rows.push(row);
+    }
+
+    println!("\n{}", "Summary:".cyan().bold());
+    let header = ["Repository", "Type", "Presets", "Result"];
+    let mut widths = header.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: [&str; 4]| {
+        format!(
+            "  {:w0$}  {:w1$}  {:w2$}  {}",
+            cells[0],
+            cells[1],
+            cells[2],
+            cells[3],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+        )
+    };
+    println!("{}", line(header).bold());
+    for row in &rows {
+        println!("{}", line([&row[0], &row[1], &row[2], &row[3]]));
+    }
+
+    if failed > 0 {
+        bail!("{} of {} repositories failed", failed, dirs.len());
+    }
+    Ok(())
+}
 
-    // 4. Convert to preset configs
-    let registry = Arc::new(build_registry());
-    let mut preset_configs = Vec::new();
+/// What happened to one repository in `cci fleet`
+struct FleetRepo {
+    project_type: String,
+    presets: Vec<String>,
+    /// Files that changed, or would change
+    files: Vec<PathBuf>,
+    result: String,
+}
 
-    for preset_choice in config {
-        let (preset_id, preset_config) = preset_choice_to_config(&preset_choice);
-        println!("  {} {}", "•".blue(), preset_id);
-        preset_configs.push((preset_id, preset_config));
+fn fleet_repo(
+    dir: &Path,
+    template: Option<&str>,
+    platforms: &[crate::editor::state::Platform],
+    commit: Option<(&str, bool)>,
+) -> Result<FleetRepo> {
+    use crate::detection::DetectorRegistry;
+    use crate::generator::merge::merge_into_existing;
+    use crate::git::{Publish, Repo};
+
+    let config_path = locate_config(&dir.join("cci.ron"));
+    let format = match template {
+        Some(_) => ConfigFormat::Ron,
+        None => ConfigFormat::from_path(&config_path),
+    };
+    let source = match template {
+        Some(template) => template.to_string(),
+        None if config_path.exists() => std::fs::read_to_string(&config_path)
+            .with_context(|| format!("Failed to read {}", config_path.display()))?,
+        None => bail!("No cci.ron; pass --template to give it one"),
+    };
+    let mut config = format.parse(&source).with_context(|| {
+        format!(
+            "Failed to parse {} config. Check syntax and structure.",
+            format.name()
+        )
+    })?;
+    if config.presets.is_empty() {
+        bail!("No presets defined in configuration file");
     }
+    config.resolve_extends(dir)?;
+    config.check_values()?;
+    // Check the branch and token before anything is written
+    let publish = match commit {
+        Some((branch, pull_request)) => {
+            let repo = Repo::open(dir)?;
+            let publish = Publish::prepare(&repo, branch, pull_request, platforms)?;
+            Some((repo, publish))
+        }
+        None => None,
+    };
 
-    // 5. Generate outputs
-    println!("\n{}", "Generating CI configurations...".cyan().bold());
+    let detection = DetectorRegistry::new().detect(dir)?.remove(0);
     let language_version = detection
         .language_version
         .unwrap_or_else(|| "stable".to_string());
+    let preset_configs: Vec<_> = config.presets.iter().map(preset_choice_to_config).collect();
+    let presets: Vec<String> = preset_configs.iter().map(|(id, _)| id.clone()).collect();
+    let mut files = Vec::new();
+    for (platform, path, content) in generate_outputs(
+        &preset_configs,
+        config.file_naming,
+        platforms,
+        &language_version,
+    )? {
+        let content = merge_into_existing(platform, &content, &dir.join(&path))?;
+        files.push((path, content));
+    }
+    if template.is_some() {
+        files.push((PathBuf::from("cci.ron"), source));
+    }
+    let changed: Vec<&(PathBuf, String)> = files
+        .iter()
+        .filter(|(path, content)| {
+            std::fs::read_to_string(dir.join(path)).ok().as_deref() != Some(content.as_str())
+        })
+        .collect();
+    let mut repo = FleetRepo {
+        project_type: detection.project_type.to_string(),
+        presets,
+        files: changed.iter().map(|(path, _)| path.clone()).collect(),
+        result: String::new(),
+    };
 
-    let generator = MultiPresetGenerator::new(preset_configs, registry, platform, language_version);
-
-    let outputs = generator
-        .generate_all()
-        .with_context(|| "Failed to generate CI configurations")?;
+    let Some((git, publish)) = publish else {
+        repo.result = match changed.len() {
+            0 => "up to date".to_string(),
+            count => format!("{} file(s) to update", count),
+        };
+        return Ok(repo);
+    };
+    for (path, content) in &changed {
+        let output_path = dir.join(path);
+        if let Some(parent) = output_path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+        std::fs::write(&output_path, content)
+            .with_context(|| format!("Failed to write file: {}", output_path.display()))?;
+    }
+    let mut paths: Vec<PathBuf> = files.into_iter().map(|(path, _)| path).collect();
+    let config_name = PathBuf::from(config_path.file_name().unwrap_or_default());
+    if !paths.contains(&config_name) && config_path.exists() {
+        paths.push(config_name);
+    }
+    repo.result = match publish.publish(&git, "cci.ron", &repo.presets, &paths)? {
+        None => "up to date".to_string(),
+        Some(published) => match published.url {
+            Some(url) => url,
+            None => format!(
+                "committed {} file(s) to {}",
+                published.changes.len(),
+                publish.branch
+            ),
+        },
+    };
+    Ok(repo)
+}
 
-    // 6. Write files
-    let base_path = PathBuf::from(".");
+/// Clone every repository of `org` (`host/name`) into `clone_dir`, returning their directories
+///
+/// Repositories that are already there are reused as they are.
+fn clone_org(
+    org: &str,
+    clone_dir: &Path,
+    platforms: &[crate::editor::state::Platform],
+) -> Result<Vec<PathBuf>> {
+    use crate::git::forge::{list_repos, Forge, Remote};
 
-    for (filename, content) in outputs {
-        // Use the full path (includes subdirectories like .github/workflows)
-        let output_path = base_path.join(&filename);
+    let org = org.trim_start_matches("https://").trim_end_matches('/');
+    let Some((host, path)) = org.split_once('/') else {
+        bail!("Give the org with its host, like github.com/{}", org);
+    };
+    let org = Remote {
+        host: host.to_string(),
+        path: path.to_string(),
+    };
+    let forge = Forge::detect(&org, platforms)?;
+    let repos = list_repos(forge, &org, forge.token().ok().as_deref())?;
+    if repos.is_empty() {
+        bail!("{} has no repositories", org.path);
+    }
 
-        // Check if file exists and force flag
-        if output_path.exists() && !force {
-            bail!(
-                "File exists: {}. Use --force to overwrite",
-                output_path.display()
-            );
+    println!(
+        "{} {} repositories from {}",
+        "Cloning".cyan().bold(),
+        repos.len(),
+        org.path
+    );
+    std::fs::create_dir_all(clone_dir)
+        .with_context(|| format!("Failed to create directory: {}", clone_dir.display()))?;
+    let mut dirs = Vec::new();
+    for repo in repos {
+        let dir = clone_dir.join(&repo.name);
+        if !dir.exists() {
+            crate::git::clone(&repo.clone_url, &dir)?;
+            println!("  {} {}", "✓".green().bold(), repo.name);
         }
+        dirs.push(dir);
+    }
+    Ok(dirs)
+}
 
-        // Create parent directories if needed
-        if let Some(parent) = output_path.parent() {
-            std::fs::create_dir_all(parent)
-                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+pub fn handle_lint(file: Option<&str>, platform: Option<&str>) -> Result<()> {
+    use crate::editor::state::Platform;
+    use crate::lint::{find_ci_files, lint, platform_for_path, Severity};
+
+    let platform = platform
+        .map(|id| Platform::from_id(id).ok_or_else(|| unsupported_platform_error(id)))
+        .transpose()?;
+    let files = match file {
+        Some(file) => {
+            let path = PathBuf::from(file);
+            let platform = platform
+                .or_else(|| platform_for_path(&path))
+                .ok_or_else(|| {
+                    config_error(format!(
+                        "Can't tell which platform {} is for; pass --platform",
+                        path.display()
+                    ))
+                })?;
+            vec![(path, platform)]
         }
+        // Shown relative to the repository root, e.g. `.gitlab-ci.yml`
+        None => find_ci_files(Path::new("."))
+            .into_iter()
+            .map(|(path, platform)| {
+                let relative = path.strip_prefix(".").unwrap_or(&path).to_path_buf();
+                (relative, platform)
+            })
+            .collect(),
+    };
+    if files.is_empty() {
+        bail!("No CI configs found");
+    }
 
-        // Write file
-        std::fs::write(&output_path, content)
-            .with_context(|| format!("Failed to write file: {}", output_path.display()))?;
+    let mut errors = 0;
+    for (path, platform) in files {
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read file: {}", path.display()))?;
+        let issues = lint(platform, &content);
+        if issues.is_empty() {
+            println!("{} {}", "✓".green().bold(), path.display());
+            continue;
+        }
 
         println!(
-            "  {} {}",
-            "✓".green().bold(),
-            output_path.display().to_string().yellow()
+            "{} {}",
+            "✗".red().bold(),
+            path.display().to_string().yellow()
         );
+        for issue in issues {
+            match issue.severity {
+                Severity::Error => {
+                    errors += 1;
+                    println!("  {} {}", "error".red().bold(), issue);
+                }
+                Severity::Warning => println!("  {} {}", "warning".yellow().bold(), issue),
+            }
+        }
     }
 
-    println!("\n{}", "Done!".green().bold());
+    if errors > 0 {
+        bail!("Found {} error(s)", errors);
+    }
     Ok(())
 }
 
-/// Handle the validate command
-pub fn handle_validate(config_path: &str) -> Result<()> {
-    println!("{} {}", "Validating".cyan().bold(), config_path);
-
-    // Load file
-    let ron_str = std::fs::read_to_string(config_path)
-        .with_context(|| format!("Failed to read config file: {}", config_path))?;
+pub fn handle_validate(
+    config_path: &str,
+    format: OutputFormat,
+    schema: bool,
+    platform_args: Vec<String>,
+) -> Result<()> {
+    #[derive(serde::Serialize)]
+    struct ValidateReport {
+        valid: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+        presets: Vec<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        schema: Option<Vec<SchemaReport>>,
+    }
+    #[derive(serde::Serialize)]
+    struct SchemaReport {
+        file: String,
+        platform: String,
+        /// False when cci has no schema for the platform
+        checked: bool,
+        issues: Vec<String>,
+    }
 
-    // Parse RON
-    let config: CciConfig = ron::Options::default()
-        .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
-        .from_str(&ron_str)
-        .with_context(|| {
-            "Failed to parse RON config. Check syntax and structure:\n\
-             - Ensure all fields are properly formatted\n\
-             - Check for missing commas\n\
-             - Verify enum variants match expected values"
-        })?;
+    let config_path = config_file(config_path);
+    let config_path = config_path.as_str();
+    if format == OutputFormat::Text {
+        println!("{} {}", "Validating".cyan().bold(), config_path);
+    }
 
-    // Basic validation
-    if config.is_empty() {
-        bail!("Validation failed: No presets defined in configuration");
+    let parsed = parse_for_validation(config_path);
+    let checks = match (&parsed, schema) {
+        (Ok(config), true) => Some(check_schemas(config, &platform_args)?),
+        _ => None,
+    };
+    let errors = checks
+        .iter()
+        .flatten()
+        .map(|check| check.errors())
+        .sum::<usize>();
+    if format == OutputFormat::Json {
+        let report = match &parsed {
+            Ok(config) => ValidateReport {
+                valid: errors == 0,
+                error: None,
+                presets: config
+                    .presets
+                    .iter()
+                    .map(|preset| preset_choice_to_config(preset).0)
+                    .collect(),
+                schema: checks.as_ref().map(|checks| {
+                    checks
+                        .iter()
+                        .map(|check| SchemaReport {
+                            file: check.filename.display().to_string(),
+                            platform: check.platform.id().to_string(),
+                            checked: check.issues.is_some(),
+                            issues: check
+                                .issues
+                                .iter()
+                                .flatten()
+                                .map(|issue| issue.to_string())
+                                .collect(),
+                        })
+                        .collect()
+                }),
+            },
+            Err(e) => ValidateReport {
+                valid: false,
+                error: Some(format!("{:#}", e)),
+                presets: Vec::new(),
+                schema: None,
+            },
+        };
+        print_json(&report)?;
+    }
+    let config = parsed?;
+    if format == OutputFormat::Json {
+        if errors > 0 {
+            bail!("Found {} schema error(s)", errors);
+        }
+        return Ok(());
     }
 
     println!("\n{}", "Configuration is valid!".green().bold());
-    println!("  Presets: {}", config.len());
+    println!("  Presets: {}", config.presets.len());
 
-    for (idx, preset) in config.iter().enumerate() {
+    for (idx, preset) in config.presets.iter().enumerate() {
         let preset_name = match preset {
             crate::config::PresetChoice::PythonApp(_) => "Python",
             crate::config::PresetChoice::Rust(_) => "Rust",
             crate::config::PresetChoice::GoApp(_) => "Go App",
             crate::config::PresetChoice::Docker(_) => "Docker",
+            crate::config::PresetChoice::Release(_) => "Release",
+            crate::config::PresetChoice::ReleaseAutomation(_) => "Release Automation",
+            crate::config::PresetChoice::CodeScanning(_) => "Code Scanning",
+            crate::config::PresetChoice::Notifications(_) => "Notifications",
+            crate::config::PresetChoice::Nightly(_) => "Nightly Builds",
+            crate::config::PresetChoice::Deploy(_) => "Deploy",
+            crate::config::PresetChoice::Custom(config) => &config.preset,
+            crate::config::PresetChoice::Extends(extends) => &extends.preset,
         };
         println!("    {}. {}", idx + 1, preset_name);
     }
 
+    let Some(checks) = checks else {
+        return Ok(());
+    };
+    println!("\n{}", "Schema check".cyan().bold());
+    for check in &checks {
+        let filename = check.filename.display();
+        match &check.issues {
+            None => println!(
+                "  {} {} (no schema for {})",
+                "-".dimmed(),
+                filename,
+                check.platform.name()
+            ),
+            Some(issues) if issues.is_empty() => println!("  {} {}", "✓".green().bold(), filename),
+            Some(issues) => {
+                println!("  {} {}", "✗".red().bold(), filename.to_string().yellow());
+                for issue in issues {
+                    println!("    {} {}", "error".red().bold(), issue);
+                }
+            }
+        }
+    }
+    if errors > 0 {
+        bail!(
+            "Found {} schema error(s); this is a bug in cci, please report it",
+            errors
+        );
+    }
     Ok(())
 }
 
-/// Handle the detect command
-pub fn handle_detect(dir: &str) -> Result<()> {
+/// One generated file checked against its platform's schema
+struct SchemaCheck {
+    platform: crate::editor::state::Platform,
+    filename: PathBuf,
+    /// `None` when cci has no schema for the platform
+    issues: Option<Vec<crate::lint::Issue>>,
+}
+
+impl SchemaCheck {
+    fn errors(&self) -> usize {
+        self.issues.as_ref().map_or(0, Vec::len)
+    }
+}
+
+/// Generate `config` for each platform in memory and check each workflow file
+///
+/// Support files that aren't CI configs, like `dependabot.yml`, are left out.
+fn check_schemas(config: &CciConfig, platform_args: &[String]) -> Result<Vec<SchemaCheck>> {
     use crate::detection::DetectorRegistry;
+    use crate::lint::{platform_for_path, schema};
+
+    let platforms = parse_platforms(platform_args)?;
+    let language_version = DetectorRegistry::new()
+        .detect(&PathBuf::from("."))
+        .ok()
+        .and_then(|mut detections| detections.remove(0).language_version)
+        .unwrap_or_else(|| "stable".to_string());
+    let preset_configs: Vec<_> = config.presets.iter().map(preset_choice_to_config).collect();
+    Ok(generate_outputs(
+        &preset_configs,
+        config.file_naming,
+        &platforms,
+        &language_version,
+    )?
+    .into_iter()
+    .filter(|(platform, filename, _)| platform_for_path(filename) == Some(*platform))
+    .map(|(platform, filename, content)| SchemaCheck {
+        platform,
+        issues: schema::validate(platform, &content),
+        filename,
+    })
+    .collect())
+}
+
+/// Read and parse a config file with detailed hints for fixing syntax errors
+fn parse_for_validation(config_path: &str) -> Result<CciConfig> {
+    // Load file
+    let config_path = config_file(config_path);
+    let config_path = config_path.as_str();
+    let source = read_config_source(config_path)?;
+
+    // Parse it in the format its extension names
+    let format = ConfigFormat::from_path(Path::new(config_path));
+    let mut config = format.parse(&source).with_context(|| {
+        format!(
+            "Failed to parse {} config. Check syntax and structure:\n\
+             - Ensure all fields are properly formatted\n\
+             - Check for missing commas\n\
+             - Verify enum variants match expected values",
+            format.name()
+        )
+    })?;
+
+    // Basic validation
+    if config.presets.is_empty() {
+        bail!("Validation failed: No presets defined in configuration");
+    }
+    config.resolve_extends(config_source_dir(config_path))?;
+    config
+        .check_values()
+        .context("Validation failed: fix these values in cci.ron")?;
+    Ok(config)
+}
+
+/// Handle the detect command, searching no more of the repository than `options` allow
+pub fn handle_detect(dir: &str, options: &DetectionOptions, format: OutputFormat) -> Result<()> {
+    use crate::detection::{
+        detect_facets, existing_platforms, find_existing_ci, import_github_workflows,
+        DetectorRegistry, MemberSource, WorkspaceDetector, WorkspaceMember,
+    };
     use crate::editor::registry::build_registry;
+    use crate::editor::state::Platform;
+    use crate::generator::explain::describe_value;
     use std::path::PathBuf;
 
     let working_dir = PathBuf::from(dir);
+    if format == OutputFormat::Json {
+        return print_detection_json(&working_dir, options);
+    }
 
     println!("{}", "Detecting project type...".cyan().bold());
     println!();
 
     // 1. Detect project type
     let detector_registry = DetectorRegistry::new();
-    let detection = match detector_registry.detect(&working_dir) {
+    let mut detections = match detector_registry.detect(&working_dir) {
         Ok(d) => d,
         Err(_) => {
             println!("{}", "✗ No project type detected".red().bold());
@@ -179,12 +2060,22 @@ pub fn handle_detect(dir: &str) -> Result<()> {
         }
     };
 
-    // 2. Display project type
+    // 2. Display project type, then anything else found alongside it
+    let detection = detections.remove(0);
     println!(
-        "{} {}",
+        "{} {} {}",
         "✓ Project Type:".green().bold(),
-        detection.project_type
+        detection.project_type,
+        format!("({}% confidence)", detection.confidence).dimmed()
     );
+    for other in &detections {
+        println!(
+            "  {} {} {}",
+            "Also:".dimmed(),
+            other.project_type,
+            format!("({}% confidence)", other.confidence).dimmed()
+        );
+    }
 
     if let Some(version) = &detection.language_version {
         println!("  {} {}", "Language Version:".dimmed(), version);
@@ -199,44 +2090,150 @@ pub fn handle_detect(dir: &str) -> Result<()> {
         }
     }
 
-    // 4. Check for existing CI files
+    // 4. Display the other projects in the repository, if it holds several
+    let members: Vec<WorkspaceMember> = WorkspaceDetector::with_options(options.clone())
+        .detect(&working_dir)
+        .into_iter()
+        .filter(|member| member.source != MemberSource::Root)
+        .collect();
+    if !members.is_empty() {
+        println!();
+        println!("{}", "Workspace members:".cyan().bold());
+        for member in &members {
+            println!(
+                "  {} {} {}",
+                member.dir.display(),
+                member.detection.project_type,
+                format!("({})", member.source.name()).dimmed()
+            );
+        }
+    }
+
+    // 5. Display containers and infrastructure, and the addon each calls for
+    let registry = build_registry();
+    let facets = detect_facets(&working_dir);
+    if !facets.is_empty() {
+        println!();
+        println!("{}", "Containers and infrastructure:".cyan().bold());
+        for found in &facets {
+            let preset_id = found.facet.preset_id();
+            let preset = registry
+                .get(preset_id)
+                .map(|preset| preset.preset_name())
+                .unwrap_or(preset_id);
+            println!(
+                "  {} {} {}",
+                found.path.display(),
+                found.facet.description(),
+                format!("(suggests the {} preset)", preset).dimmed()
+            );
+        }
+    }
+
+    // 6. Check for existing CI files
     println!();
     println!(
         "{}",
         "Checking for existing CI configurations...".cyan().bold()
     );
 
-    let ci_files = vec![
-        (".github/workflows", "GitHub Actions"),
-        (".gitea/workflows", "Gitea Actions"),
-        (".gitlab-ci.yml", "GitLab CI"),
-        (".circleci/config.yml", "CircleCI"),
-        ("Jenkinsfile", "Jenkins"),
-    ];
-
-    let mut found_ci = false;
-    for (path, platform) in ci_files {
-        let full_path = working_dir.join(path);
-        if full_path.exists() {
-            println!("  {} {}", "✓".green(), platform);
-            found_ci = true;
-        }
+    let existing = find_existing_ci(&working_dir);
+    for ci in &existing {
+        let note = if ci.parsed || ci.platform == Platform::Jenkins {
+            String::new()
+        } else {
+            format!(" {}", "(not a valid config)".yellow())
+        };
+        println!(
+            "  {} {} {}{}",
+            "✓".green(),
+            ci.platform.name(),
+            ci.path.display().to_string().dimmed(),
+            note
+        );
     }
 
-    if !found_ci {
+    if existing.is_empty() {
         println!("  {} No existing CI configurations found", "ℹ".blue());
+    } else if let Some(platform) = existing_platforms(&existing).first() {
+        println!(
+            "  {} The editor will start on {}",
+            "ℹ".blue(),
+            platform.name()
+        );
+    }
+
+    // 7. Show the presets that come closest to the existing workflows
+    let language_version = detection.language_version.as_deref().unwrap_or("stable");
+    let imported = import_github_workflows(&working_dir, &registry, language_version)?;
+    if !imported.is_empty() {
+        println!();
+        println!(
+            "{}",
+            "Presets that reproduce the existing workflows:"
+                .cyan()
+                .bold()
+        );
+    }
+    for workflow in &imported {
+        let preset_ids: Vec<&str> = workflow
+            .configs
+            .iter()
+            .map(|config| config.preset_id.as_str())
+            .collect();
+        let fit = if workflow.distance == 0 {
+            "exact match".green().to_string()
+        } else {
+            format!("{} difference(s)", workflow.distance)
+                .yellow()
+                .to_string()
+        };
+        println!(
+            "  {} → {} ({})",
+            workflow
+                .path
+                .strip_prefix(&working_dir)
+                .unwrap_or(&workflow.path)
+                .display(),
+            preset_ids.join(", ").cyan(),
+            fit
+        );
+        let changed: Vec<String> = workflow
+            .changed_options(&registry)
+            .into_iter()
+            .map(|(preset_id, option_id, value)| {
+                format!("{}.{} = {}", preset_id, option_id, describe_value(&value))
+            })
+            .collect();
+        if !changed.is_empty() {
+            println!("    {} {}", "Options:".dimmed(), changed.join(", "));
+        }
+        for missing in &workflow.missing {
+            println!("    {} {}", "-".red(), missing);
+        }
+        for extra in &workflow.extra {
+            println!("    {} {}", "+".green(), extra);
+        }
+    }
+    if imported.iter().any(|workflow| workflow.distance > 0) {
+        println!(
+            "  {} - is only in the workflow, + only in what the presets generate",
+            "ℹ".blue()
+        );
     }
 
-    // 5. Show matching presets
+    // 8. Show matching presets
     println!();
     println!("{}", "Matching presets for this project:".cyan().bold());
 
-    let registry = build_registry();
     let mut matching_presets = Vec::new();
     let mut available_presets = Vec::new();
 
     for preset in registry.all() {
-        if preset.matches_project(&detection.project_type, &working_dir) {
+        if std::iter::once(&detection)
+            .chain(&detections)
+            .any(|detected| preset.matches_detection(detected, &working_dir))
+        {
             matching_presets.push(preset);
         } else {
             available_presets.push(preset);
@@ -252,7 +2249,7 @@ pub fn handle_detect(dir: &str) -> Result<()> {
         }
     }
 
-    // 6. Show other available presets
+    // 9. Show other available presets
     if !available_presets.is_empty() {
         println!();
         println!("{}", "Other available presets:".dimmed());
@@ -262,7 +2259,7 @@ pub fn handle_detect(dir: &str) -> Result<()> {
         }
     }
 
-    // 7. Suggest next steps
+    // 10. Suggest next steps
     println!();
     println!("{}", "Next steps:".cyan().bold());
     if matching_presets.is_empty() {
@@ -284,3 +2281,476 @@ pub fn handle_detect(dir: &str) -> Result<()> {
 
     Ok(())
 }
+
+/// Print what `cci detect` reports as JSON
+fn print_detection_json(working_dir: &std::path::Path, options: &DetectionOptions) -> Result<()> {
+    use crate::detection::{
+        detect_facets, existing_platforms, find_existing_ci, import_github_workflows,
+        DetectorRegistry, MemberSource, WorkspaceDetector,
+    };
+    use crate::generator::explain::describe_value;
+    use std::collections::BTreeMap;
+
+    #[derive(serde::Serialize)]
+    struct Member {
+        dir: String,
+        project_type: String,
+        source: &'static str,
+    }
+
+    #[derive(serde::Serialize)]
+    struct OtherType {
+        project_type: String,
+        confidence: u8,
+    }
+
+    #[derive(serde::Serialize)]
+    struct WorkflowFit {
+        path: String,
+        /// The base preset, then the decorators applied to it
+        presets: Vec<String>,
+        /// Options set differently from the presets' defaults, e.g.
+        /// `rust.enable_coverage: off`
+        options: BTreeMap<String, String>,
+        distance: usize,
+        /// What the workflow does that the presets don't generate
+        missing: Vec<String>,
+        /// What the presets generate that the workflow doesn't do
+        extra: Vec<String>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct FacetReport {
+        kind: &'static str,
+        path: String,
+        suggested_preset: &'static str,
+    }
+
+    #[derive(serde::Serialize)]
+    struct DetectReport {
+        /// `None` when no supported project was found
+        project_type: Option<String>,
+        /// How sure detection is of `project_type`, from 0 to 100
+        confidence: Option<u8>,
+        /// Further project types found alongside `project_type`, surest first
+        other_project_types: Vec<OtherType>,
+        language_version: Option<String>,
+        metadata: BTreeMap<String, String>,
+        /// The other projects in the repository, for monorepos
+        members: Vec<Member>,
+        /// Containers and infrastructure found, with the addon each calls for
+        facets: Vec<FacetReport>,
+        /// Platforms the repository already has configs for, the one the
+        /// editor starts on first
+        existing_ci: Vec<&'static str>,
+        /// The presets closest to each existing GitHub workflow
+        workflows: Vec<WorkflowFit>,
+        matching_presets: Vec<&'static str>,
+        other_presets: Vec<&'static str>,
+    }
+
+    let mut detections = DetectorRegistry::new().detect_all(working_dir)?;
+    let registry = build_registry();
+    let language_version = detections
+        .first()
+        .and_then(|detection| detection.language_version.clone())
+        .unwrap_or_else(|| "stable".to_string());
+    let workflows = import_github_workflows(working_dir, &registry, &language_version)?
+        .into_iter()
+        .map(|workflow| WorkflowFit {
+            path: workflow
+                .path
+                .strip_prefix(working_dir)
+                .unwrap_or(&workflow.path)
+                .to_string_lossy()
+                .replace('\\', "/"),
+            presets: workflow
+                .configs
+                .iter()
+                .map(|config| config.preset_id.clone())
+                .collect(),
+            options: workflow
+                .changed_options(&registry)
+                .into_iter()
+                .map(|(preset_id, option_id, value)| {
+                    (
+                        format!("{}.{}", preset_id, option_id),
+                        describe_value(&value),
+                    )
+                })
+                .collect(),
+            distance: workflow.distance,
+            missing: workflow.missing,
+            extra: workflow.extra,
+        })
+        .collect();
+    let (matching_presets, other_presets) = registry
+        .all()
+        .into_iter()
+        .map(|preset| {
+            let matches = detections
+                .iter()
+                .any(|detected| preset.matches_detection(detected, working_dir));
+            (preset.preset_id(), matches)
+        })
+        .partition::<Vec<_>, _>(|(_, matches)| *matches);
+    let detection = (!detections.is_empty()).then(|| detections.remove(0));
+    let report = DetectReport {
+        project_type: detection
+            .as_ref()
+            .map(|detection| detection.project_type.to_string()),
+        confidence: detection.as_ref().map(|detection| detection.confidence),
+        other_project_types: detections
+            .iter()
+            .map(|detected| OtherType {
+                project_type: detected.project_type.to_string(),
+                confidence: detected.confidence,
+            })
+            .collect(),
+        language_version: detection
+            .as_ref()
+            .and_then(|detection| detection.language_version.clone()),
+        metadata: detection
+            .map(|detection| detection.metadata.into_iter().collect())
+            .unwrap_or_default(),
+        members: WorkspaceDetector::with_options(options.clone())
+            .detect(working_dir)
+            .into_iter()
+            .filter(|member| member.source != MemberSource::Root)
+            .map(|member| Member {
+                dir: member.dir.to_string_lossy().replace('\\', "/"),
+                project_type: member.detection.project_type.to_string(),
+                source: member.source.name(),
+            })
+            .collect(),
+        facets: detect_facets(working_dir)
+            .into_iter()
+            .map(|found| FacetReport {
+                kind: found.facet.id(),
+                path: found.path.to_string_lossy().replace('\\', "/"),
+                suggested_preset: found.facet.preset_id(),
+            })
+            .collect(),
+        existing_ci: existing_platforms(&find_existing_ci(working_dir))
+            .into_iter()
+            .map(|platform| platform.name())
+            .collect(),
+        workflows,
+        matching_presets: matching_presets.into_iter().map(|(id, _)| id).collect(),
+        other_presets: other_presets.into_iter().map(|(id, _)| id).collect(),
+    };
+    print_json(&report)
+}
+
+/// Handle the presets list command
+pub fn handle_presets_list(format: OutputFormat) -> Result<()> {
+    use crate::editor::catalog::describe;
+
+    let registry = build_registry();
+    let presets: Vec<_> = registry
+        .all()
+        .into_iter()
+        .map(|preset| describe(preset.as_ref()))
+        .collect();
+    if format == OutputFormat::Json {
+        return print_json(&presets);
+    }
+
+    let width = presets.iter().map(|info| info.id.len()).max().unwrap_or(0);
+    for info in &presets {
+        let kind = match info.kind.as_str() {
+            "preset" => String::new(),
+            kind => format!(" ({})", kind).dimmed().to_string(),
+        };
+        println!(
+            "  {:width$}  {}{}",
+            info.id.yellow(),
+            info.description,
+            kind,
+            width = width
+        );
+    }
+    println!(
+        "\nRun {} for a preset's options",
+        "cci presets show <id>".cyan()
+    );
+    Ok(())
+}
+
+/// Handle the presets show command
+/// Handle `presets show --schema`: one preset's schema, or cci.ron's
+pub fn handle_presets_schema(id: Option<&str>) -> Result<()> {
+    use crate::config::schema::config_schema;
+    use crate::error::preset_not_found_error;
+
+    let schema = match id {
+        Some(id) => build_registry()
+            .get(id)
+            .ok_or_else(|| preset_not_found_error(id))?
+            .config_schema(),
+        None => config_schema(),
+    };
+    print_json(&schema)
+}
+
+pub fn handle_presets_show(id: &str, format: OutputFormat) -> Result<()> {
+    use crate::editor::catalog::describe;
+    use crate::error::preset_not_found_error;
+
+    let registry = build_registry();
+    let preset = registry.get(id).ok_or_else(|| preset_not_found_error(id))?;
+    let info = describe(preset.as_ref());
+    if format == OutputFormat::Json {
+        return print_json(&info);
+    }
+
+    println!("{} ({})", info.name.cyan().bold(), info.id.yellow());
+    println!("{}", info.description);
+    println!();
+    println!("  {} {}", "Kind:".dimmed(), info.kind);
+    if !info.project_types.is_empty() {
+        println!(
+            "  {} {}",
+            "Suggested for:".dimmed(),
+            info.project_types.join(", ")
+        );
+    }
+
+    for feature in &info.features {
+        println!();
+        println!("{} ({})", feature.name.green().bold(), feature.id);
+        if !feature.description.is_empty() {
+            println!("  {}", feature.description.dimmed());
+        }
+        for option in &feature.options {
+            let default = match &option.default {
+                serde_yaml::Value::String(value) if value.is_empty() => "\"\"".to_string(),
+                serde_yaml::Value::String(value) => value.clone(),
+                value => serde_yaml::to_string(value)?.trim().to_string(),
+            };
+            println!(
+                "  {} {} [{}: {}]",
+                "•".blue(),
+                option.id.yellow(),
+                option.kind,
+                default
+            );
+            println!("      {}", option.name);
+            if !option.description.is_empty() {
+                println!("      {}", option.description.dimmed());
+            }
+            if !option.variants.is_empty() {
+                println!(
+                    "      {} {}",
+                    "One of:".dimmed(),
+                    option.variants.join(", ")
+                );
+            }
+            if let Some(parent) = &option.depends_on {
+                println!("      {} {}", "Requires:".dimmed(), parent);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Rewrite `config_path` in the current cci.ron format
+pub fn handle_config_migrate(config_path: &str, dry_run: bool) -> Result<()> {
+    let config_path = config_file(config_path);
+    let config_path = config_path.as_str();
+    let format = ConfigFormat::from_path(Path::new(config_path));
+    let original = std::fs::read_to_string(config_path)
+        .with_context(|| format!("Failed to read config file: {}", config_path))?;
+    let migrated = format
+        .migrate(&original)
+        .with_context(|| format!("Failed to parse {} config: {}", format.name(), config_path))?;
+    if migrated.from_version == CURRENT_VERSION {
+        println!(
+            "{} {} is already config version {}",
+            "✓".green().bold(),
+            config_path,
+            CURRENT_VERSION
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{} {} from version {} to {}",
+        if dry_run {
+            "Would migrate"
+        } else {
+            "Migrating"
+        }
+        .cyan()
+        .bold(),
+        config_path,
+        migrated.from_version,
+        CURRENT_VERSION
+    );
+    for change in &migrated.changes {
+        println!("  {} {}", "•".blue(), change);
+    }
+
+    let upgraded = format.write(&migrated.config)?;
+    if dry_run {
+        crate::diff::print_colored_diff(&original, &upgraded);
+        return Ok(());
+    }
+    std::fs::write(config_path, upgraded)
+        .with_context(|| format!("Failed to write file: {}", config_path))?;
+    println!("{} {}", "Wrote".green().bold(), config_path.yellow());
+    Ok(())
+}
+
+/// Rewrite the config at `input` in the format `output`'s extension names
+///
+/// Comments aren't carried over, since each format parses them away.
+pub fn handle_config_convert(input: &str, output: &str, force: bool) -> Result<()> {
+    let input = config_file(input);
+    let (from, to) = (
+        ConfigFormat::from_path(Path::new(&input)),
+        ConfigFormat::from_path(Path::new(output)),
+    );
+    if Path::new(output).exists() && !force {
+        bail!("File exists: {}. Use --force to overwrite", output);
+    }
+    let source = read_config_source(&input)?;
+    let migrated = from
+        .migrate(&source)
+        .with_context(|| format!("Failed to parse {} config: {}", from.name(), input))?;
+    let converted = to.write(&migrated.config)?;
+    // Check it reads back the same before writing anything
+    if format!("{:?}", to.parse(&converted)?) != format!("{:?}", migrated.config) {
+        bail!("{} can't hold everything in {}", to.name(), input);
+    }
+    std::fs::write(output, converted)
+        .with_context(|| format!("Failed to write file: {}", output))?;
+    println!(
+        "{} {} ({}) to {} ({})",
+        "Converted".green().bold(),
+        input,
+        from.name(),
+        output.yellow(),
+        to.name()
+    );
+    Ok(())
+}
+
+/// `cci wizard`: ask for the config on stdin, write it, and offer to generate from it
+pub fn handle_wizard(dir: &str, output: &str, yes: bool, force: bool) -> Result<()> {
+    use crate::cli::wizard::{run, Prompter};
+    use crate::config::preset_config_to_choice;
+    use crate::detection::DetectorRegistry;
+    use std::io::BufRead;
+
+    let detection = DetectorRegistry::new()
+        .detect(&PathBuf::from(dir))
+        .ok()
+        .map(|mut detections| detections.remove(0));
+    match &detection {
+        Some(detection) => println!(
+            "{} {}\n",
+            "Detected".green().bold(),
+            detection.project_type.to_string().yellow()
+        ),
+        None => println!("{}\n", "No known project type detected".yellow()),
+    }
+
+    let stdin = std::io::stdin();
+    let input: Box<dyn BufRead> = if yes {
+        Box::new(std::io::empty())
+    } else {
+        Box::new(stdin.lock())
+    };
+    let mut prompter = Prompter::new(input, std::io::stdout());
+    let answers = run(&mut prompter, &build_registry(), detection.as_ref())?;
+
+    let output_path = PathBuf::from(output);
+    if output_path.exists()
+        && !force
+        && !prompter.confirm(&format!("\n{} exists. Overwrite it?", output), false)?
+    {
+        bail!(
+            "File exists: {}. Use --force to overwrite",
+            output_path.display()
+        );
+    }
+    let config = CciConfig::new(
+        answers
+            .presets
+            .iter()
+            .map(|(id, config)| preset_config_to_choice(id, config))
+            .collect(),
+    );
+    std::fs::write(
+        &output_path,
+        ConfigFormat::from_path(&output_path).write(&config)?,
+    )
+    .with_context(|| format!("Failed to write file: {}", output_path.display()))?;
+    println!(
+        "\n{} {}",
+        "Wrote".green().bold(),
+        output_path.display().to_string().yellow()
+    );
+
+    if !prompter.confirm("Generate the CI files now?", true)? {
+        let platforms: Vec<&str> = answers.platforms.iter().map(|p| p.id()).collect();
+        println!(
+            "  {} cci generate {} -p {}",
+            "$".dimmed(),
+            output,
+            platforms.join(",")
+        );
+        return Ok(());
+    }
+    println!();
+    handle_generate(
+        output,
+        answers
+            .platforms
+            .iter()
+            .map(|platform| platform.id().to_string())
+            .collect(),
+        force,
+        false,
+        false,
+        false,
+        true,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::state::Platform;
+
+    #[test]
+    fn test_parse_platforms_default() {
+        assert_eq!(parse_platforms(&[]).unwrap(), vec![Platform::GitHub]);
+    }
+
+    #[test]
+    fn test_parse_platforms_multiple() {
+        let args = vec![
+            "github".to_string(),
+            "GitLab".to_string(),
+            "github".to_string(),
+        ];
+        assert_eq!(
+            parse_platforms(&args).unwrap(),
+            vec![Platform::GitHub, Platform::GitLab]
+        );
+    }
+
+    #[test]
+    fn test_parse_platforms_all() {
+        let args = vec!["all".to_string()];
+        assert_eq!(parse_platforms(&args).unwrap(), Platform::all());
+    }
+
+    #[test]
+    fn test_parse_platforms_unknown() {
+        let args = vec!["travis".to_string()];
+        assert!(parse_platforms(&args).is_err());
+    }
+}
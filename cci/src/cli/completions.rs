@@ -0,0 +1,490 @@
+//! Shell completion scripts and the man page, generated from the clap definition
+//!
+//! Walking the [`clap::Command`] tree keeps both in sync with the real CLI.
+//! Platform and preset arguments complete to the values cci knows about
+//! rather than to file names.
+
+use clap::{Arg, Command, ValueEnum};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// What an argument's value completes to
+enum Hint {
+    Values(Vec<String>),
+    Files,
+    Directories,
+    Nothing,
+}
+
+fn hint(command: &Command, arg: &Arg) -> Hint {
+    use crate::editor::registry::build_registry;
+    use crate::editor::state::Platform;
+
+    let possible: Vec<String> = arg
+        .get_possible_values()
+        .iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| value.get_name().to_string())
+        .collect();
+    if !possible.is_empty() {
+        return Hint::Values(possible);
+    }
+
+    let platforms = || Platform::all().iter().map(|p| p.id().to_string()).collect();
+    match (command.get_name(), arg.get_id().as_str()) {
+        ("lint" | "run", "platform") => Hint::Values(platforms()),
+        (_, "platform") => {
+            let mut values: Vec<String> = platforms();
+            values.push("all".to_string());
+            Hint::Values(values)
+        }
+        ("migrate", "from" | "to") => Hint::Values(platforms()),
+        ("show", "id") => Hint::Values(
+            build_registry()
+                .all()
+                .iter()
+                .map(|preset| preset.preset_id().to_string())
+                .collect(),
+        ),
+        (_, "dir") | ("fleet", "repos" | "clone_dir") => Hint::Directories,
+        ("fleet", "from" | "template") => Hint::Files,
+        (_, "config" | "file" | "input" | "output") => Hint::Files,
+        _ => Hint::Nothing,
+    }
+}
+
+/// Generate the completion script for `shell`
+pub fn completions(shell: Shell, command: &mut Command) -> String {
+    command.build();
+    let mut out = String::new();
+    match shell {
+        Shell::Bash => bash(command, &mut out),
+        Shell::Zsh => zsh(command, &mut out),
+        Shell::Fish => fish(command, &[], &mut out),
+    }
+    out
+}
+
+fn visible_args(command: &Command) -> impl Iterator<Item = &Arg> {
+    command.get_arguments().filter(|arg| !arg.is_hide_set())
+}
+
+fn visible_subcommands(command: &Command) -> impl Iterator<Item = &Command> {
+    command
+        .get_subcommands()
+        .filter(|subcommand| !subcommand.is_hide_set() && subcommand.get_name() != "help")
+}
+
+fn flags(arg: &Arg) -> Vec<String> {
+    let mut flags = Vec::new();
+    if let Some(short) = arg.get_short() {
+        flags.push(format!("-{}", short));
+    }
+    if let Some(long) = arg.get_long() {
+        flags.push(format!("--{}", long));
+    }
+    flags
+}
+
+fn takes_value(arg: &Arg) -> bool {
+    arg.get_action().takes_values()
+}
+
+fn help(arg: &Arg) -> String {
+    arg.get_help()
+        .map(|help| help.to_string())
+        .unwrap_or_default()
+}
+
+fn about(command: &Command) -> String {
+    command
+        .get_about()
+        .map(|about| about.to_string())
+        .unwrap_or_default()
+}
+
+fn bash(root: &Command, out: &mut String) {
+    fn paths<'a>(command: &'a Command, path: String, all: &mut Vec<(String, &'a Command)>) {
+        all.push((path.clone(), command));
+        for subcommand in visible_subcommands(command) {
+            paths(
+                subcommand,
+                format!("{}__{}", path, subcommand.get_name()),
+                all,
+            );
+        }
+    }
+    let name = root.get_name();
+    let mut all = Vec::new();
+    paths(root, name.to_string(), &mut all);
+
+    let _ = writeln!(out, "_{}() {{", name);
+    out.push_str("    local cur prev cmd i\n");
+    out.push_str("    cur=\"${COMP_WORDS[COMP_CWORD]}\"\n");
+    out.push_str("    prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n");
+    let _ = writeln!(out, "    cmd=\"{}\"", name);
+    out.push_str("    for ((i = 1; i < COMP_CWORD; i++)); do\n");
+    out.push_str("        case \"${cmd}__${COMP_WORDS[i]}\" in\n");
+    for (path, _) in all.iter().skip(1) {
+        let _ = writeln!(out, "            {}) cmd=\"{}\" ;;", path, path);
+    }
+    out.push_str("        esac\n    done\n\n    case \"${cmd}\" in\n");
+
+    for (path, command) in &all {
+        let mut words: Vec<String> = visible_args(command).flat_map(flags).collect();
+        words.extend(visible_subcommands(command).map(|sub| sub.get_name().to_string()));
+        let _ = writeln!(out, "        {})", path);
+
+        let options: Vec<&Arg> = visible_args(command)
+            .filter(|arg| !arg.is_positional() && takes_value(arg))
+            .collect();
+        if !options.is_empty() {
+            out.push_str("            case \"${prev}\" in\n");
+            for arg in options {
+                let _ = writeln!(out, "                {})", flags(arg).join("|"));
+                let _ = writeln!(out, "                    {}", bash_reply(command, arg));
+                out.push_str("                    return ;;\n");
+            }
+            out.push_str("            esac\n");
+        }
+        let positional = visible_args(command)
+            .find(|arg| arg.is_positional())
+            .filter(|arg| !matches!(hint(command, arg), Hint::Nothing));
+        if let Some(arg) = positional {
+            out.push_str("            if [[ \"${cur}\" != -* ]]; then\n");
+            let _ = writeln!(out, "                {}", bash_reply(command, arg));
+            out.push_str("                return\n            fi\n");
+        }
+        let _ = writeln!(
+            out,
+            "            COMPREPLY=($(compgen -W \"{}\" -- \"${{cur}}\")) ;;",
+            words.join(" ")
+        );
+    }
+    out.push_str("    esac\n}\n\n");
+    let _ = writeln!(
+        out,
+        "complete -F _{} -o bashdefault -o default {}",
+        name, name
+    );
+}
+
+fn bash_reply(command: &Command, arg: &Arg) -> String {
+    match hint(command, arg) {
+        Hint::Values(values) => format!(
+            "COMPREPLY=($(compgen -W \"{}\" -- \"${{cur}}\"))",
+            values.join(" ")
+        ),
+        Hint::Files => "COMPREPLY=($(compgen -f -- \"${cur}\"))".to_string(),
+        Hint::Directories => "COMPREPLY=($(compgen -d -- \"${cur}\"))".to_string(),
+        Hint::Nothing => "COMPREPLY=()".to_string(),
+    }
+}
+
+fn zsh(root: &Command, out: &mut String) {
+    let name = root.get_name();
+    let _ = writeln!(out, "#compdef {}\n", name);
+    zsh_function(root, &format!("_{}", name), out);
+    let _ = writeln!(out, "_{} \"$@\"", name);
+}
+
+fn zsh_function(command: &Command, function: &str, out: &mut String) {
+    let subcommands: Vec<&Command> = visible_subcommands(command).collect();
+    let _ = writeln!(out, "{}() {{", function);
+    if !subcommands.is_empty() {
+        out.push_str("    local line state\n");
+    }
+    out.push_str("    _arguments -C -s \\\n");
+    for arg in visible_args(command) {
+        let _ = writeln!(out, "        {} \\", zsh_spec(command, arg));
+    }
+    if subcommands.is_empty() {
+        out.push_str("        && return 0\n}\n\n");
+        return;
+    }
+    out.push_str("        '1: :->commands' \\\n        '*::arg:->args'\n\n");
+    out.push_str("    case $state in\n        commands)\n");
+    out.push_str("            local commands=(\n");
+    for subcommand in &subcommands {
+        let _ = writeln!(
+            out,
+            "                '{}:{}'",
+            subcommand.get_name(),
+            zsh_escape(&about(subcommand))
+        );
+    }
+    out.push_str("            )\n            _describe 'command' commands ;;\n");
+    out.push_str("        args)\n            case $line[1] in\n");
+    for subcommand in &subcommands {
+        let _ = writeln!(
+            out,
+            "                {}) {}__{} ;;",
+            subcommand.get_name(),
+            function,
+            subcommand.get_name()
+        );
+    }
+    out.push_str("            esac ;;\n    esac\n}\n\n");
+    for subcommand in subcommands {
+        zsh_function(
+            subcommand,
+            &format!("{}__{}", function, subcommand.get_name()),
+            out,
+        );
+    }
+}
+
+fn zsh_spec(command: &Command, arg: &Arg) -> String {
+    let action = match hint(command, arg) {
+        Hint::Values(values) => format!("({})", values.join(" ")),
+        Hint::Files => "_files".to_string(),
+        Hint::Directories => "_files -/".to_string(),
+        Hint::Nothing => " ".to_string(),
+    };
+    let help = zsh_escape(&help(arg));
+    let value_name = arg.get_id().as_str();
+    if arg.is_positional() {
+        return format!("'::{}:{}'", value_name, action);
+    }
+    let flags = flags(arg);
+    let value = if takes_value(arg) {
+        format!(":{}:{}", value_name, action)
+    } else {
+        String::new()
+    };
+    if flags.len() == 1 {
+        format!("'{}[{}]{}'", flags[0], help, value)
+    } else {
+        format!(
+            "'({})'{{{}}}'[{}]{}'",
+            flags.join(" "),
+            flags.join(","),
+            help,
+            value
+        )
+    }
+}
+
+/// Escape text for a single-quoted zsh `_arguments` spec
+fn zsh_escape(text: &str) -> String {
+    text.replace('\'', "'\\''")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+        .replace(':', "\\:")
+}
+
+fn fish(command: &Command, parents: &[&str], out: &mut String) {
+    let root = parents.first().copied().unwrap_or(command.get_name());
+    let subcommands: Vec<&Command> = visible_subcommands(command).collect();
+    let names: Vec<&str> = subcommands.iter().map(|sub| sub.get_name()).collect();
+
+    // Options apply once the command's own path has been typed
+    let condition = match parents.len() {
+        0 => "__fish_use_subcommand".to_string(),
+        _ => format!("__fish_seen_subcommand_from {}", command.get_name()),
+    };
+    for arg in visible_args(command).filter(|arg| !arg.is_positional()) {
+        let mut line = format!("complete -c {} -n '{}'", root, condition);
+        if let Some(short) = arg.get_short() {
+            let _ = write!(line, " -s {}", short);
+        }
+        if let Some(long) = arg.get_long() {
+            let _ = write!(line, " -l {}", long);
+        }
+        if takes_value(arg) {
+            match hint(command, arg) {
+                Hint::Values(values) => {
+                    let _ = write!(line, " -xa '{}'", values.join(" "));
+                }
+                Hint::Files => line.push_str(" -rF"),
+                Hint::Directories => line.push_str(" -xa '(__fish_complete_directories)'"),
+                Hint::Nothing => line.push_str(" -x"),
+            }
+        }
+        let _ = write!(line, " -d '{}'", fish_escape(&help(arg)));
+        let _ = writeln!(out, "{}", line);
+    }
+    if let Some(Hint::Values(values)) = visible_args(command)
+        .find(|arg| arg.is_positional())
+        .map(|arg| hint(command, arg))
+    {
+        let _ = writeln!(
+            out,
+            "complete -c {} -n '{}' -xa '{}'",
+            root,
+            condition,
+            values.join(" ")
+        );
+    }
+
+    let mut path = parents.to_vec();
+    path.push(command.get_name());
+    for subcommand in &subcommands {
+        // Offer subcommands until one of them has been typed
+        let condition = match parents.len() {
+            0 => "__fish_use_subcommand".to_string(),
+            _ => format!(
+                "__fish_seen_subcommand_from {}; and not __fish_seen_subcommand_from {}",
+                command.get_name(),
+                names.join(" ")
+            ),
+        };
+        let _ = writeln!(
+            out,
+            "complete -c {} -f -n '{}' -a {} -d '{}'",
+            root,
+            condition,
+            subcommand.get_name(),
+            fish_escape(&about(subcommand))
+        );
+    }
+    for subcommand in subcommands {
+        fish(subcommand, &path, out);
+    }
+}
+
+fn fish_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+/// Generate a man page in roff covering every subcommand
+pub fn man_page(command: &mut Command) -> String {
+    command.build();
+    let name = command.get_name().to_string();
+    let version = command.get_version().unwrap_or_default().to_string();
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        ".TH {} 1 \"\" \"{} {}\" \"User Commands\"",
+        name.to_uppercase(),
+        name,
+        version
+    );
+    let _ = writeln!(out, ".SH NAME\n{} \\- {}", name, roff(&about(command)));
+    let _ = writeln!(
+        out,
+        ".SH SYNOPSIS\n\\fB{}\\fR [\\fIOPTIONS\\fR] [\\fICOMMAND\\fR]",
+        name
+    );
+    out.push_str(".SH OPTIONS\n");
+    man_args(command, &mut out);
+    out.push_str(".SH COMMANDS\n");
+    man_commands(command, &name, &mut out);
+    out
+}
+
+fn man_commands(command: &Command, path: &str, out: &mut String) {
+    for subcommand in visible_subcommands(command) {
+        let path = format!("{} {}", path, subcommand.get_name());
+        let positionals: Vec<String> = visible_args(subcommand)
+            .filter(|arg| arg.is_positional())
+            .map(|arg| {
+                let name = arg.get_id().as_str().to_uppercase();
+                if arg.is_required_set() {
+                    format!("<{}>", name)
+                } else {
+                    format!("[{}]", name)
+                }
+            })
+            .collect();
+        let _ = writeln!(
+            out,
+            ".TP\n\\fB{}\\fR [\\fIOPTIONS\\fR] {}\n{}",
+            roff(&path),
+            roff(&positionals.join(" ")),
+            roff(&about(subcommand))
+        );
+        // Global options and help are already described at the top
+        if visible_args(subcommand).any(|arg| !is_inherited(arg)) {
+            out.push_str(".RS\n");
+            man_args(subcommand, out);
+            out.push_str(".RE\n");
+        }
+        man_commands(subcommand, &path, out);
+    }
+}
+
+fn is_inherited(arg: &Arg) -> bool {
+    arg.is_global_set() || arg.get_id() == "help"
+}
+
+fn man_args(command: &Command, out: &mut String) {
+    let top_level = command
+        .get_bin_name()
+        .is_none_or(|name| !name.contains(' '));
+    for arg in visible_args(command).filter(|arg| top_level || !is_inherited(arg)) {
+        let mut term = if arg.is_positional() {
+            format!("\\fI{}\\fR", arg.get_id().as_str().to_uppercase())
+        } else {
+            flags(arg)
+                .iter()
+                .map(|flag| format!("\\fB{}\\fR", roff(flag)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        if !arg.is_positional() && takes_value(arg) {
+            let _ = write!(term, " <{}>", arg.get_id().as_str().to_uppercase());
+        }
+        let mut description = help(arg);
+        if let Hint::Values(values) = hint(command, arg) {
+            let _ = write!(description, " [values: {}]", values.join(", "));
+        }
+        if let Some(default) = arg
+            .get_default_values()
+            .first()
+            .filter(|_| takes_value(arg))
+        {
+            let _ = write!(description, " [default: {}]", default.to_string_lossy());
+        }
+        let _ = writeln!(out, ".TP\n{}\n{}", term, roff(description.trim()));
+    }
+}
+
+/// Escape text for roff, keeping lines from starting with a control character
+fn roff(text: &str) -> String {
+    let text = text.replace('\\', "\\e").replace('-', "\\-");
+    text.lines()
+        .map(|line| {
+            if line.starts_with('.') || line.starts_with('\'') {
+                format!("\\&{}", line)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Cli;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_completions_cover_subcommands_and_values() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let script = completions(shell, &mut Cli::command());
+            assert!(script.contains("generate"), "{:?}", shell);
+            assert!(script.contains("github gitea gitlab circleci jenkins"));
+            assert!(script.contains("text json"));
+        }
+        let bash = completions(Shell::Bash, &mut Cli::command());
+        assert!(bash.contains("cci__presets__show)"));
+        assert!(bash.contains("rust"));
+    }
+
+    #[test]
+    fn test_man_page() {
+        let page = man_page(&mut Cli::command());
+        assert!(page.starts_with(".TH CCI 1"));
+        assert!(page.contains("\\fBcci presets show\\fR"));
+        assert!(page.contains("\\fB\\-\\-platform\\fR"));
+    }
+}
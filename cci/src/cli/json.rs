@@ -0,0 +1,110 @@
+//! JSON output for scripts consuming cci's commands
+//!
+//! Values go through `serde_yaml`'s data model, which JSON is a subset of, so
+//! no separate serializer is needed.
+
+use crate::error::Result;
+use serde::Serialize;
+use serde_yaml::Value;
+use std::fmt::Write;
+
+/// Serialize `value` as indented JSON
+pub fn to_json<T: Serialize>(value: &T) -> Result<String> {
+    let mut out = String::new();
+    write_value(&serde_yaml::to_value(value)?, 0, &mut out);
+    Ok(out)
+}
+
+/// Print `value` as indented JSON on stdout
+pub fn print_json<T: Serialize>(value: &T) -> Result<()> {
+    println!("{}", to_json(value)?);
+    Ok(())
+}
+
+fn write_value(value: &Value, indent: usize, out: &mut String) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(value) => out.push_str(if *value { "true" } else { "false" }),
+        Value::Number(number) if number.is_f64() && !number.as_f64().unwrap().is_finite() => {
+            out.push_str("null")
+        }
+        Value::Number(number) => {
+            let _ = write!(out, "{}", number);
+        }
+        Value::String(value) => write_string(value, out),
+        Value::Sequence(values) if values.is_empty() => out.push_str("[]"),
+        Value::Sequence(values) => {
+            out.push('[');
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                newline(indent + 1, out);
+                write_value(value, indent + 1, out);
+            }
+            newline(indent, out);
+            out.push(']');
+        }
+        Value::Mapping(map) if map.is_empty() => out.push_str("{}"),
+        Value::Mapping(map) => {
+            out.push('{');
+            for (index, (key, value)) in map.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                newline(indent + 1, out);
+                match key {
+                    Value::String(key) => write_string(key, out),
+                    key => write_string(&serde_yaml::to_string(key).unwrap_or_default(), out),
+                }
+                out.push_str(": ");
+                write_value(value, indent + 1, out);
+            }
+            newline(indent, out);
+            out.push('}');
+        }
+        Value::Tagged(tagged) => write_value(&tagged.value, indent, out),
+    }
+}
+
+fn newline(indent: usize, out: &mut String) {
+    out.push('\n');
+    out.push_str(&"  ".repeat(indent));
+}
+
+fn write_string(value: &str, out: &mut String) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_to_json() {
+        let mut map = BTreeMap::new();
+        map.insert("name", serde_yaml::to_value("say \"hi\"\n").unwrap());
+        map.insert("count", serde_yaml::to_value(2).unwrap());
+        map.insert("tags", serde_yaml::to_value(Vec::<String>::new()).unwrap());
+        map.insert("on", serde_yaml::to_value([true, false]).unwrap());
+        assert_eq!(
+            to_json(&map).unwrap(),
+            "{\n  \"count\": 2,\n  \"name\": \"say \\\"hi\\\"\\n\",\n  \"on\": [\n    true,\n    false\n  ],\n  \"tags\": []\n}"
+        );
+    }
+}
@@ -1,6 +1,9 @@
 pub mod commands;
+pub mod completions;
+pub mod json;
+pub mod wizard;
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 
 #[derive(Parser)]
 #[command(name = "cci")]
@@ -11,28 +14,320 @@ pub struct Cli {
     pub command: Option<Commands>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Colored text for people
+    Text,
+    /// Structured JSON for other tools
+    Json,
+}
+
 #[derive(Subcommand)]
 pub enum Commands {
     /// Generate CI config from RON file
     Generate {
+        /// Path to cci.ron config file, or `-` to read it from stdin
+        #[arg(default_value = "cci.ron")]
+        config: String,
+
+        /// Target platform(s), comma-separated (e.g. github,gitlab)
+        #[arg(short, long, value_delimiter = ',')]
+        platform: Vec<String>,
+
+        /// Force overwrite existing files
+        #[arg(short, long)]
+        force: bool,
+
+        /// Print the generated files instead of writing them
+        #[arg(long, conflicts_with = "force")]
+        stdout: bool,
+
+        /// List the files that would be written, with diffs, without writing them
+        #[arg(long, conflicts_with = "stdout")]
+        dry_run: bool,
+
+        /// Generate pipelines for every project found under the current directory,
+        /// each scoped to its own directory. Projects may have their own cci.ron
+        #[arg(short, long, conflicts_with = "stdout")]
+        recursive: bool,
+
+        /// Resolve GitHub Actions pins that aren't in the bundled table over the network
+        #[arg(long)]
+        online: bool,
+
+        /// Don't check the generated files with cci's lint rules and actionlint
+        #[arg(long)]
+        no_lint: bool,
+
+        /// Commit the generated files and cci.ron to a new branch
+        #[arg(long, conflicts_with_all = ["stdout", "dry_run", "recursive"])]
+        commit: bool,
+
+        /// Branch to commit to
+        #[arg(long, requires = "commit", default_value = crate::git::DEFAULT_BRANCH)]
+        branch: String,
+
+        /// Push the branch and open a pull request, with the token in GITHUB_TOKEN,
+        /// GITEA_TOKEN or GITLAB_TOKEN
+        #[arg(long, requires = "commit")]
+        pr: bool,
+    },
+
+    /// Check that generated files on disk match cci.ron
+    Check {
+        /// Path to cci.ron config file
+        #[arg(default_value = "cci.ron")]
+        config: String,
+
+        /// Target platform(s), comma-separated (e.g. github,gitlab)
+        #[arg(short, long, value_delimiter = ',')]
+        platform: Vec<String>,
+
+        /// Resolve GitHub Actions pins that aren't in the bundled table over the network
+        #[arg(long)]
+        online: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+
+    /// Show which preset, feature and option produced a generated job or step
+    Explain {
+        /// Job ID, or (part of) a step's name or command
+        target: String,
+
+        /// Path to cci.ron config file
+        #[arg(short, long, default_value = "cci.ron")]
+        config: String,
+
+        /// Target platform(s), comma-separated (e.g. github,gitlab)
+        #[arg(short, long, value_delimiter = ',')]
+        platform: Vec<String>,
+    },
+
+    /// Show how the generated files change between two configs
+    Diff {
+        /// Config to compare from
+        old: String,
+
+        /// Config to compare to
+        new: String,
+
+        /// Target platform(s), comma-separated (e.g. github,gitlab)
+        #[arg(short, long, value_delimiter = ',')]
+        platform: Vec<String>,
+    },
+
+    /// List the secrets generated configs need and how to set them up
+    Secrets {
         /// Path to cci.ron config file
         #[arg(default_value = "cci.ron")]
         config: String,
 
-        /// Target platform
+        /// Target platform(s), comma-separated (e.g. github,gitlab)
+        #[arg(short, long, value_delimiter = ',')]
+        platform: Vec<String>,
+    },
+
+    /// Generate a Dependabot or Renovate config for the detected ecosystems
+    Deps {
+        /// Project directory
+        #[arg(short, long, default_value = ".")]
+        dir: String,
+
+        /// Update tool (dependabot, renovate)
+        #[arg(short, long, default_value = "dependabot")]
+        tool: String,
+
+        /// How often to check for updates (daily, weekly, monthly)
+        #[arg(short, long, default_value = "weekly")]
+        schedule: String,
+
+        /// Force overwrite existing files
         #[arg(short, long)]
-        platform: Option<String>,
+        force: bool,
+    },
+
+    /// Generate git hooks that run the same checks as the CI config
+    Hooks {
+        /// Path to cci.ron config file
+        #[arg(default_value = "cci.ron")]
+        config: String,
+
+        /// Hook manager (pre-commit, lefthook)
+        #[arg(short, long, default_value = "pre-commit")]
+        tool: String,
+
+        /// Force overwrite existing files
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Generate a justfile or Makefile that runs the same checks as the CI config
+    Tasks {
+        /// Path to cci.ron config file
+        #[arg(default_value = "cci.ron")]
+        config: String,
+
+        /// Task runner (just, make)
+        #[arg(short, long, default_value = "just")]
+        tool: String,
+
+        /// Force overwrite existing files
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Reconstruct cci.ron from the existing GitHub workflows
+    Import {
+        /// Project directory
+        #[arg(short, long, default_value = ".")]
+        dir: String,
+
+        /// Path of the config file to write; a .yaml or .toml extension
+        /// writes it in that format
+        #[arg(short, long, default_value = "cci.ron")]
+        output: String,
+
+        /// Force overwrite existing files
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Translate an existing CI config to another platform
+    Migrate {
+        /// Platform the existing config is written for
+        #[arg(long)]
+        from: String,
+
+        /// Platform to translate it to
+        #[arg(long)]
+        to: String,
+
+        /// Existing config (defaults to the source platform's usual path)
+        #[arg(short, long)]
+        input: Option<String>,
+
+        /// Where to write the result (defaults to the target platform's usual path)
+        #[arg(short, long)]
+        output: Option<String>,
 
         /// Force overwrite existing files
         #[arg(short, long)]
         force: bool,
     },
 
+    /// Run the generated pipeline locally with act (GitHub, Gitea) or gitlab-runner (GitLab)
+    Run {
+        /// Job to run, e.g. rust/test (defaults to every job)
+        job: Option<String>,
+
+        /// Path to cci.ron config file
+        #[arg(short, long, default_value = "cci.ron")]
+        config: String,
+
+        /// Platform whose pipeline to run
+        #[arg(short, long, default_value = "github")]
+        platform: String,
+
+        /// Run the jobs' commands directly instead of through act or gitlab-runner
+        #[arg(long, conflicts_with_all = ["platform", "args"])]
+        native: bool,
+
+        /// With --native, run each job in a container from its image
+        #[arg(long, requires = "native")]
+        docker: bool,
+
+        /// With --native, let jobs run on this machine, all of them without
+        /// --docker and the ones without an image with it
+        #[arg(long, requires = "native")]
+        host: bool,
+
+        /// With --native, also run steps that publish or upload something, or
+        /// install software for the whole machine
+        #[arg(long, requires = "native")]
+        all_steps: bool,
+
+        /// List the jobs that can be run
+        #[arg(long)]
+        list: bool,
+
+        /// Print the commands instead of running them
+        #[arg(long, conflicts_with = "list")]
+        dry_run: bool,
+
+        /// Extra arguments for act or gitlab-runner, after `--`
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+
+    /// Regenerate the pipelines of many repositories, optionally from a shared cci.ron
+    Fleet {
+        /// Repository directories
+        repos: Vec<String>,
+
+        /// File listing repository directories, one per line
+        #[arg(long)]
+        from: Option<String>,
+
+        /// Clone every repository of a GitHub or Gitea org, or GitLab group, e.g. github.com/fossable
+        #[arg(long, conflicts_with_all = ["repos", "from"])]
+        org: Option<String>,
+
+        /// Directory --org repositories are cloned into
+        #[arg(long, requires = "org", default_value = "fleet")]
+        clone_dir: String,
+
+        /// cci.ron copied into every repository, instead of using each one's own
+        #[arg(short, long)]
+        template: Option<String>,
+
+        /// Target platform(s), comma-separated (e.g. github,gitlab)
+        #[arg(short, long, value_delimiter = ',')]
+        platform: Vec<String>,
+
+        /// Write the files and commit them to a new branch in each repository
+        #[arg(long)]
+        commit: bool,
+
+        /// Branch to commit to
+        #[arg(long, requires = "commit", default_value = crate::git::DEFAULT_BRANCH)]
+        branch: String,
+
+        /// Push each branch and open a pull request, with the token in GITHUB_TOKEN,
+        /// GITEA_TOKEN or GITLAB_TOKEN
+        #[arg(long, requires = "commit")]
+        pr: bool,
+    },
+
+    /// Check CI configs for invalid keys and broken job references
+    Lint {
+        /// Config to check (defaults to every CI config in the current directory)
+        file: Option<String>,
+
+        /// Platform the file is written for, when its path doesn't tell
+        #[arg(short, long)]
+        platform: Option<String>,
+    },
+
     /// Validate RON config syntax
     Validate {
         /// Path to cci.ron config file
         #[arg(default_value = "cci.ron")]
         config: String,
+
+        /// Also check the generated files against the bundled GitHub Actions and GitLab CI schemas
+        #[arg(long)]
+        schema: bool,
+
+        /// Platform(s) to generate for the schema check, comma-separated (e.g. github,gitlab)
+        #[arg(short, long, value_delimiter = ',', requires = "schema")]
+        platform: Vec<String>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
     },
 
     /// Run interactive editor (default)
@@ -42,10 +337,121 @@ pub enum Commands {
         dir: String,
     },
 
+    /// Set up cci.ron by answering questions, without the full-screen editor
+    Wizard {
+        /// Project directory to detect presets for
+        #[arg(short, long, default_value = ".")]
+        dir: String,
+
+        /// Where to write the config; a .yaml or .toml extension writes it
+        /// in that format
+        #[arg(short, long, default_value = "cci.ron")]
+        output: String,
+
+        /// Take every default without asking
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Overwrite an existing config without asking
+        #[arg(short, long)]
+        force: bool,
+    },
+
     /// Detect project type and matching presets
     Detect {
         /// Project directory
         #[arg(short, long, default_value = ".")]
         dir: String,
+
+        /// How many directories deep to search for projects (overrides cci.ron)
+        #[arg(long)]
+        max_depth: Option<usize>,
+
+        /// Also search directories that .gitignore ignores
+        #[arg(long)]
+        no_gitignore: bool,
+
+        /// Directory glob never searched, e.g. 'third_party/**' (repeatable)
+        #[arg(long)]
+        exclude: Vec<String>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+
+    /// Print a shell completion script
+    Completions {
+        /// Shell to complete for
+        #[arg(value_enum)]
+        shell: completions::Shell,
+    },
+
+    /// Print the man page in roff
+    Man,
+
+    /// Browse the available presets and their options
+    Presets {
+        #[command(subcommand)]
+        command: PresetsCommand,
+    },
+
+    /// Manage cci.ron itself
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PresetsCommand {
+    /// List every preset and addon
+    List {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+
+    /// Show a preset's features, options and defaults
+    Show {
+        /// Preset ID (e.g. rust)
+        #[arg(required_unless_present = "schema")]
+        id: Option<String>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// Print the JSON Schema of the preset's cci.ron entry, or of all of
+        /// cci.ron without an ID
+        #[arg(long)]
+        schema: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Upgrade a cci.ron written for an older release to the current format
+    Migrate {
+        /// Path to cci.ron config file
+        #[arg(default_value = "cci.ron")]
+        config: String,
+
+        /// Show the upgraded file as a diff without writing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Rewrite a config in another format, e.g. cci.ron as cci.yaml or
+    /// cci.toml; formats follow the file extensions
+    Convert {
+        /// Path to the config to convert, or `-` to read RON from stdin
+        input: String,
+
+        /// Path to write, ending in .ron, .yaml, .yml or .toml
+        output: String,
+
+        /// Overwrite the output file if it exists
+        #[arg(long)]
+        force: bool,
     },
 }
@@ -0,0 +1,404 @@
+//! `cci wizard`: question-and-answer setup for terminals the editor can't use
+//!
+//! Every prompt is a single line, so answers can be piped in from a script.
+//! Choices can be picked by number or by ID, and an empty answer or the end
+//! of input takes the default.
+
+use crate::detection::DetectionResult;
+use crate::editor::config::{settle_rules, EditorPreset, OptionMeta, OptionValue, PresetConfig};
+use crate::editor::registry::PresetRegistry;
+use crate::editor::state::Platform;
+use crate::error::Result;
+use anyhow::bail;
+use std::io::{BufRead, Write};
+use std::sync::Arc;
+
+/// Asks questions on `output` and reads the answers from `input`
+pub struct Prompter<R, W> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> Prompter<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Self { input, output }
+    }
+
+    /// Print a line that isn't a question
+    pub fn say(&mut self, text: &str) -> Result<()> {
+        writeln!(self.output, "{}", text)?;
+        Ok(())
+    }
+
+    /// Ask `prompt` and read a trimmed answer; `None` when it's empty or input ended
+    fn answer(&mut self, prompt: &str) -> Result<Option<String>> {
+        write!(self.output, "{} ", prompt)?;
+        self.output.flush()?;
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            writeln!(self.output)?;
+            return Ok(None);
+        }
+        let line = line.trim();
+        Ok((!line.is_empty()).then(|| line.to_string()))
+    }
+
+    pub fn confirm(&mut self, question: &str, default: bool) -> Result<bool> {
+        let hint = if default { "[Y/n]" } else { "[y/N]" };
+        loop {
+            match self.answer(&format!("{} {}", question, hint))? {
+                None => return Ok(default),
+                Some(answer) => match answer.to_lowercase().as_str() {
+                    "y" | "yes" => return Ok(true),
+                    "n" | "no" => return Ok(false),
+                    _ => self.say("  Answer y or n")?,
+                },
+            }
+        }
+    }
+
+    pub fn input(&mut self, question: &str, default: &str) -> Result<String> {
+        let prompt = if default.is_empty() {
+            format!("{}:", question)
+        } else {
+            format!("{} [{}]:", question, default)
+        };
+        Ok(self.answer(&prompt)?.unwrap_or_else(|| default.to_string()))
+    }
+
+    /// Pick one of `choices`, given as (ID, label) pairs
+    pub fn select(
+        &mut self,
+        question: &str,
+        choices: &[(String, String)],
+        default: usize,
+    ) -> Result<usize> {
+        self.list(question, choices, |index| index == default)?;
+        loop {
+            let Some(answer) = self.answer(&format!("Choice [{}]:", default + 1))? else {
+                return Ok(default);
+            };
+            match find_choice(choices, &answer) {
+                Some(index) => return Ok(index),
+                None => self.say(&format!("  Unknown choice: {}", answer))?,
+            }
+        }
+    }
+
+    /// Pick any of `choices`, separated by commas or spaces; `none` picks nothing
+    pub fn multi_select(
+        &mut self,
+        question: &str,
+        choices: &[(String, String)],
+        defaults: &[usize],
+    ) -> Result<Vec<usize>> {
+        self.list(question, choices, |index| defaults.contains(&index))?;
+        let default_hint = if defaults.is_empty() {
+            "none".to_string()
+        } else {
+            defaults
+                .iter()
+                .map(|index| (index + 1).to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        'ask: loop {
+            let Some(answer) = self.answer(&format!("Choices [{}]:", default_hint))? else {
+                return Ok(defaults.to_vec());
+            };
+            if answer.eq_ignore_ascii_case("none") {
+                return Ok(Vec::new());
+            }
+            let mut picked = Vec::new();
+            for token in answer.split([',', ' ']).filter(|token| !token.is_empty()) {
+                match find_choice(choices, token) {
+                    Some(index) if !picked.contains(&index) => picked.push(index),
+                    Some(_) => {}
+                    None => {
+                        self.say(&format!("  Unknown choice: {}", token))?;
+                        continue 'ask;
+                    }
+                }
+            }
+            picked.sort_unstable();
+            return Ok(picked);
+        }
+    }
+
+    fn list(
+        &mut self,
+        question: &str,
+        choices: &[(String, String)],
+        marked: impl Fn(usize) -> bool,
+    ) -> Result<()> {
+        self.say(question)?;
+        for (index, (_, label)) in choices.iter().enumerate() {
+            let mark = if marked(index) { "*" } else { " " };
+            self.say(&format!("  {} {}) {}", mark, index + 1, label))?;
+        }
+        Ok(())
+    }
+}
+
+/// A 1-based number or a case-insensitive ID
+fn find_choice(choices: &[(String, String)], answer: &str) -> Option<usize> {
+    match answer.parse::<usize>() {
+        Ok(number) => (1..=choices.len()).contains(&number).then(|| number - 1),
+        Err(_) => choices
+            .iter()
+            .position(|(id, _)| id.eq_ignore_ascii_case(answer)),
+    }
+}
+
+/// Everything the wizard asked for
+#[derive(Debug)]
+pub struct Answers {
+    pub platforms: Vec<Platform>,
+    pub presets: Vec<(String, PresetConfig)>,
+}
+
+/// Ask for platforms, presets, their options and addons
+///
+/// Presets suggested for the detected project are chosen by default.
+pub fn run<R: BufRead, W: Write>(
+    prompter: &mut Prompter<R, W>,
+    registry: &PresetRegistry,
+    detection: Option<&DetectionResult>,
+) -> Result<Answers> {
+    let platform_choices: Vec<(String, String)> = Platform::all()
+        .iter()
+        .map(|platform| (platform.id().to_string(), platform.name().to_string()))
+        .collect();
+    let mut platforms: Vec<Platform> = prompter
+        .multi_select("Which CI platforms?", &platform_choices, &[0])?
+        .into_iter()
+        .map(|index| Platform::all()[index])
+        .collect();
+    if platforms.is_empty() {
+        prompter.say("No platform chosen, using GitHub Actions")?;
+        platforms.push(Platform::GitHub);
+    }
+
+    let (addons, bases): (Vec<_>, Vec<_>) = registry
+        .all()
+        .into_iter()
+        .partition(|preset| preset.is_addon());
+    let suggested = |preset: &&Arc<dyn EditorPreset>| {
+        detection
+            .is_some_and(|detection| preset.matches_detection(detection, std::path::Path::new(".")))
+    };
+
+    let mut chosen = Vec::new();
+    let defaults: Vec<usize> = (0..bases.len())
+        .filter(|index| suggested(&bases[*index]))
+        .collect();
+    for index in prompter.multi_select("\nWhich presets?", &choices(&bases), &defaults)? {
+        chosen.push(bases[index]);
+    }
+    if chosen.is_empty() {
+        bail!("No presets chosen");
+    }
+    for index in prompter.multi_select("\nWhich addons?", &choices(&addons), &[])? {
+        chosen.push(addons[index]);
+    }
+
+    let mut presets = Vec::new();
+    for preset in chosen {
+        let mut config = match detection {
+            Some(detection) if suggested(&preset) => preset.detected_config(detection),
+            _ => preset.default_config(true),
+        };
+        if prompter.confirm(
+            &format!("\nCustomize {}'s options?", preset.preset_name()),
+            false,
+        )? {
+            customize(prompter, preset.as_ref(), &mut config)?;
+        }
+        presets.push((preset.preset_id().to_string(), config));
+    }
+    Ok(Answers { platforms, presets })
+}
+
+fn choices(presets: &[&Arc<dyn EditorPreset>]) -> Vec<(String, String)> {
+    presets
+        .iter()
+        .map(|preset| {
+            (
+                preset.preset_id().to_string(),
+                format!("{} - {}", preset.preset_name(), preset.preset_description()),
+            )
+        })
+        .collect()
+}
+
+/// Ask for every option of `preset`, skipping ones whose parent option is off
+///
+/// Workflow settings (triggers, runner, ...) come last, behind a question of their own.
+fn customize<R: BufRead, W: Write>(
+    prompter: &mut Prompter<R, W>,
+    preset: &dyn EditorPreset,
+    config: &mut PresetConfig,
+) -> Result<()> {
+    let (settings, features): (Vec<_>, Vec<_>) = preset
+        .features()
+        .into_iter()
+        .partition(|feature| feature.is_workflow_setting());
+    let mut ask_settings = false;
+    for (index, feature) in features.iter().chain(&settings).enumerate() {
+        if index == features.len() {
+            ask_settings = prompter.confirm("\nChange workflow settings too?", false)?;
+        }
+        if index >= features.len() && !ask_settings {
+            break;
+        }
+
+        if feature.description.is_empty() {
+            prompter.say(&format!("\n{}", feature.display_name))?;
+        } else {
+            prompter.say(&format!(
+                "\n{}: {}",
+                feature.display_name, feature.description
+            ))?;
+        }
+        for option in &feature.options {
+            let enabled = option
+                .depends_on
+                .as_ref()
+                .is_none_or(|parent| !matches!(config.get(parent), Some(OptionValue::Bool(false))));
+            if !enabled {
+                continue;
+            }
+            let current = config
+                .get(&option.id)
+                .cloned()
+                .unwrap_or_else(|| option.default_value.clone());
+            let value = ask_option(prompter, option, &current)?;
+            if value != current {
+                config.set(option.id.clone(), value);
+                // The latest answer wins over earlier ones it rules out
+                settle_rules(preset, config, Some(&option.id));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn ask_option<R: BufRead, W: Write>(
+    prompter: &mut Prompter<R, W>,
+    option: &OptionMeta,
+    current: &OptionValue,
+) -> Result<OptionValue> {
+    let question = match (&option.placeholder, current) {
+        (Some(placeholder), OptionValue::String(value)) if value.is_empty() => {
+            format!("  {} (e.g. {})", option.display_name, placeholder)
+        }
+        _ => format!("  {}", option.display_name),
+    };
+    Ok(match current {
+        OptionValue::Bool(value) => OptionValue::Bool(prompter.confirm(&question, *value)?),
+        OptionValue::Enum { selected, variants } => {
+            let choices: Vec<(String, String)> = variants
+                .iter()
+                .map(|variant| (variant.clone(), variant.clone()))
+                .collect();
+            let default = variants
+                .iter()
+                .position(|variant| variant == selected)
+                .unwrap_or(0);
+            let index = prompter.select(&question, &choices, default)?;
+            OptionValue::Enum {
+                selected: variants[index].clone(),
+                variants: variants.clone(),
+            }
+        }
+        OptionValue::String(value) => loop {
+            let answer = OptionValue::String(prompter.input(&question, value)?);
+            // Keeping the value it has is always allowed, or ended input would ask forever
+            match option.validation.check(&answer) {
+                Ok(()) => break answer,
+                Err(_) if answer == *current => break answer,
+                Err(e) => prompter.say(&format!("  {}", e))?,
+            }
+        },
+        OptionValue::Int(value) => loop {
+            let answer = prompter.input(&question, &value.to_string())?;
+            match answer.parse().map(OptionValue::Int) {
+                Ok(number) => match option.validation.check(&number) {
+                    Ok(()) => break number,
+                    Err(_) if number == *current => break number,
+                    Err(e) => prompter.say(&format!("  {}", e))?,
+                },
+                Err(_) => prompter.say("  Enter a whole number")?,
+            }
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detection::ProjectType;
+    use crate::editor::registry::build_registry;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_wizard_from_piped_answers() {
+        let detection = DetectionResult {
+            project_type: ProjectType::RustBinary,
+            confidence: 100,
+            language_version: None,
+            metadata: HashMap::new(),
+        };
+        // GitLab, the suggested preset, the release addon by ID, no customizing
+        let input = "gitlab\n\nbogus\nrelease\nn\n";
+        let mut output = Vec::new();
+        let mut prompter = Prompter::new(input.as_bytes(), &mut output);
+        let answers = run(&mut prompter, &build_registry(), Some(&detection)).unwrap();
+
+        assert_eq!(answers.platforms, vec![Platform::GitLab]);
+        let ids: Vec<&str> = answers.presets.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["rust", "release"]);
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .contains("Unknown choice: bogus"));
+    }
+
+    #[test]
+    fn test_customize_applies_answers() {
+        let registry = build_registry();
+        let rust = registry.get("rust").unwrap();
+        let mut config = rust.default_config(true);
+        // Turn every toggle off and keep everything else
+        let mut input = String::new();
+        for feature in rust.features() {
+            if !feature.is_workflow_setting() {
+                // Options under a toggle that's now off aren't asked about
+                for option in feature.options.iter().filter(|o| o.depends_on.is_none()) {
+                    match option.default_value {
+                        OptionValue::Bool(_) => input.push_str("n\n"),
+                        _ => input.push('\n'),
+                    }
+                }
+            }
+        }
+        input.push_str("n\n");
+        let mut prompter = Prompter::new(input.as_bytes(), Vec::new());
+        customize(&mut prompter, rust.as_ref(), &mut config).unwrap();
+        // Workflow settings are skipped by the "n" to changing them
+        for feature in rust.features() {
+            if feature.is_workflow_setting() {
+                assert!(config.get_bool("read_only_permissions"));
+                continue;
+            }
+            for option in &feature.options {
+                if let OptionValue::Bool(_) = option.default_value {
+                    assert!(!config.get_bool(&option.id), "{} is still on", option.id);
+                }
+            }
+        }
+        assert_eq!(
+            config.get_enum("coverage_provider").as_deref(),
+            Some("codecov")
+        );
+    }
+}
@@ -0,0 +1,306 @@
+//! Presets in cci.ron that start from another config's settings
+//!
+//! ```ron
+//! Extends((
+//!     preset: "rust",
+//!     from: "team",
+//!     options: {"enable_coverage": false},
+//! ))
+//! ```
+//!
+//! takes the `rust` settings of the profile `team`, or of another config
+//! when `from` is a path ending in `.ron`, `.yaml` or `.toml` (relative to
+//! the file it's in), and changes only the options listed. Without `from`
+//! the preset's defaults are the starting point. Entries are resolved when
+//! cci.ron is loaded, so the rest of cci only sees the settings they come to.
+
+use super::{preset_config_to_choice, CciConfig, ConfigFormat, PresetChoice};
+use crate::editor::config::{OptionValue, PresetConfig};
+use crate::editor::profiles::profiles_dir;
+use crate::editor::registry::build_registry;
+use crate::error::{config_error, Result};
+use crate::presets::custom::CustomValue;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// A preset's settings taken from elsewhere, with some options changed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtendsConfig {
+    /// The preset's ID, e.g. "rust"
+    pub preset: String,
+    /// A profile's name, or a path to a config ending in `.ron`, `.yaml` or `.toml`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub options: BTreeMap<String, CustomValue>,
+}
+
+/// Where an `Extends` entry's settings start from, kept so they can be
+/// written back as only the options that differ
+#[derive(Debug, Clone)]
+pub struct ExtendsBase {
+    pub from: Option<String>,
+    pub config: PresetConfig,
+}
+
+impl ExtendsBase {
+    /// The entry that turns these settings into `config`
+    pub fn extends(&self, config: &PresetConfig) -> ExtendsConfig {
+        ExtendsConfig {
+            preset: config.preset_id.clone(),
+            from: self.from.clone(),
+            options: config
+                .values
+                .iter()
+                .filter(|(id, value)| self.config.get(id) != Some(*value))
+                .map(|(id, value)| (id.clone(), CustomValue::from_option_value(value)))
+                .collect(),
+        }
+    }
+}
+
+impl ExtendsConfig {
+    /// The settings the entry starts from, reading `from` relative to `dir`
+    pub fn base(&self, dir: &Path) -> Result<ExtendsBase> {
+        self.base_in(dir, &[])
+    }
+
+    /// `chain` is the files already being read, so one that extends itself
+    /// is reported instead of read forever
+    fn base_in(&self, dir: &Path, chain: &[PathBuf]) -> Result<ExtendsBase> {
+        let Some(from) = &self.from else {
+            let config = build_registry()
+                .get(&self.preset)
+                .map(|preset| preset.default_config(true))
+                .ok_or_else(|| config_error(format!("No preset `{}`", self.preset)))?;
+            return Ok(ExtendsBase { from: None, config });
+        };
+
+        let is_path = [".ron", ".yaml", ".yml", ".toml"]
+            .iter()
+            .any(|ext| from.ends_with(ext));
+        let path = if is_path || from.contains(['/', '\\']) {
+            dir.join(from)
+        } else {
+            profiles_dir()
+                .ok_or_else(|| config_error("No home directory to find profiles in"))?
+                .join(format!("{}.ron", from))
+        };
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+        if chain.contains(&canonical) {
+            return Err(config_error(format!(
+                "{} extends itself through `from`",
+                path.display()
+            )));
+        }
+        let text = std::fs::read_to_string(&path).with_context(|| {
+            format!(
+                "Failed to read {}, which `{}` extends",
+                path.display(),
+                self.preset
+            )
+        })?;
+        let mut config = ConfigFormat::from_path(&path)
+            .parse(&text)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        let chain = [chain, &[canonical]].concat();
+        config.resolve_extends_in(path.parent().unwrap_or(Path::new(".")), &chain)?;
+
+        config
+            .presets
+            .iter()
+            .map(PresetChoice::to_preset_config)
+            .find(|(id, _)| *id == self.preset)
+            .map(|(_, config)| ExtendsBase {
+                from: Some(from.clone()),
+                config,
+            })
+            .ok_or_else(|| {
+                config_error(format!(
+                    "{} has no `{}` preset",
+                    path.display(),
+                    self.preset
+                ))
+            })
+    }
+
+    /// `base` with the entry's options changed, each typed like the option
+    /// it replaces
+    pub fn apply(&self, base: &PresetConfig) -> Result<PresetConfig> {
+        let mut config = base.clone();
+        for (id, value) in &self.options {
+            let value = match (base.get(id), value) {
+                (Some(OptionValue::Bool(_)), CustomValue::Bool(enabled)) => {
+                    OptionValue::Bool(*enabled)
+                }
+                (Some(OptionValue::Enum { variants, .. }), CustomValue::String(selected))
+                    if variants.contains(selected) =>
+                {
+                    OptionValue::Enum {
+                        selected: selected.clone(),
+                        variants: variants.clone(),
+                    }
+                }
+                (Some(OptionValue::Enum { variants, .. }), _) => {
+                    return Err(config_error(format!(
+                        "`{}` of `{}` is one of: {}",
+                        id,
+                        self.preset,
+                        variants.join(", ")
+                    )))
+                }
+                (Some(OptionValue::String(_)), CustomValue::String(value)) => {
+                    OptionValue::String(value.clone())
+                }
+                (Some(OptionValue::Int(_)), CustomValue::String(value)) => {
+                    OptionValue::Int(value.parse().map_err(|_| {
+                        config_error(format!("`{}` of `{}` is a number", id, self.preset))
+                    })?)
+                }
+                (Some(_), _) => {
+                    return Err(config_error(format!(
+                        "`{}` of `{}` isn't a {}",
+                        id,
+                        self.preset,
+                        match value {
+                            CustomValue::Bool(_) => "toggle",
+                            CustomValue::String(_) => "string",
+                        }
+                    )))
+                }
+                (None, _) => {
+                    return Err(config_error(format!(
+                        "`{}` has no option `{}`",
+                        self.preset, id
+                    )))
+                }
+            };
+            config.set(id.clone(), value);
+        }
+        Ok(config)
+    }
+}
+
+impl CciConfig {
+    /// Replace each `Extends` entry with the settings it comes to, reading
+    /// `from` paths relative to `dir`
+    pub fn resolve_extends(&mut self, dir: &Path) -> Result<()> {
+        self.resolve_extends_in(dir, &[])
+    }
+
+    fn resolve_extends_in(&mut self, dir: &Path, chain: &[PathBuf]) -> Result<()> {
+        for choice in &mut self.presets {
+            if let PresetChoice::Extends(extends) = choice {
+                let config = extends
+                    .base_in(dir, chain)
+                    .and_then(|base| extends.apply(&base.config))
+                    .with_context(|| format!("Failed to extend `{}`", extends.preset))?;
+                *choice = preset_config_to_choice(&extends.preset, &config);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::migrate::parse_config;
+    use crate::config::preset_choice_to_config;
+    use std::fs;
+
+    #[test]
+    fn test_extends_overrides_another_config() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("base.ron"),
+            "(version: 3, presets: [Rust((rust_version: \"stable\", enable_coverage: false, enable_linter: true, toolchain_matrix: \"stable, beta\"))])",
+        )
+        .unwrap();
+        fs::create_dir(dir.path().join("service")).unwrap();
+        let text = r#"(version: 3, presets: [
+            Extends((preset: "rust", from: "../base.ron", options: {"enable_linter": false})),
+        ])"#;
+        let mut config = parse_config(text).unwrap();
+        config.resolve_extends(&dir.path().join("service")).unwrap();
+
+        let (id, rust) = preset_choice_to_config(&config.presets[0]);
+        assert_eq!(id, "rust");
+        assert!(!rust.get_bool("enable_coverage"));
+        assert!(!rust.get_bool("enable_linter"));
+        assert_eq!(rust.get_string("toolchain_matrix").unwrap(), "stable, beta");
+
+        let base = ExtendsConfig {
+            preset: "rust".to_string(),
+            from: Some("../base.ron".to_string()),
+            options: BTreeMap::new(),
+        }
+        .base(&dir.path().join("service"))
+        .unwrap();
+        assert_eq!(
+            base.extends(&rust).options,
+            BTreeMap::from([("enable_linter".to_string(), CustomValue::Bool(false))])
+        );
+    }
+
+    #[test]
+    fn test_extends_reads_other_formats() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("base.toml"),
+            "version = 3\n\n[[presets]]\n[presets.Rust]\nrust_version = \"stable\"\nenable_linter = true\n",
+        )
+        .unwrap();
+        let text = r#"
+version: 3
+presets:
+- Extends:
+    preset: rust
+    from: base.toml
+    options:
+      enable_coverage: false
+"#;
+        let mut config = ConfigFormat::Yaml.parse(text).unwrap();
+        config.resolve_extends(dir.path()).unwrap();
+
+        let (_, rust) = preset_choice_to_config(&config.presets[0]);
+        assert!(rust.get_bool("enable_linter"));
+        assert!(!rust.get_bool("enable_coverage"));
+    }
+
+    #[test]
+    fn test_extends_is_checked() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("loop.ron"),
+            "(version: 3, presets: [Extends((preset: \"rust\", from: \"loop.ron\"))])",
+        )
+        .unwrap();
+        let invalid = [
+            r#"Extends((preset: "rust", from: "loop.ron"))"#,
+            r#"Extends((preset: "rust", from: "missing.ron"))"#,
+            r#"Extends((preset: "cobol"))"#,
+            r#"Extends((preset: "rust", options: {"enable_lasers": true}))"#,
+            r#"Extends((preset: "rust", options: {"enable_linter": "yes"}))"#,
+            r#"Extends((preset: "rust", options: {"runner": "mainframe"}))"#,
+        ];
+        for entry in invalid {
+            let mut config = parse_config(&format!("(version: 3, presets: [{}])", entry)).unwrap();
+            assert!(
+                config.resolve_extends(dir.path()).is_err(),
+                "accepted {}",
+                entry
+            );
+        }
+
+        let mut config = parse_config(
+            r#"(version: 3, presets: [Extends((preset: "rust", options: {"runner": "macos"}))])"#,
+        )
+        .unwrap();
+        config.resolve_extends(dir.path()).unwrap();
+        let (_, rust) = preset_choice_to_config(&config.presets[0]);
+        assert_eq!(rust.get_enum("runner").unwrap(), "macos");
+    }
+}
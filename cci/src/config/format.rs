@@ -0,0 +1,259 @@
+//! cci.ron's alternatives, cci.yaml and cci.toml
+//!
+//! All three hold the same [`CciConfig`], and which one a file is written in
+//! follows from its extension. Preset entries keep the shape they have in
+//! RON, a single key naming the preset:
+//!
+//! ```yaml
+//! version: 3
+//! presets:
+//! - Rust:
+//!     rust_version: stable
+//! ```
+//!
+//! The other formats came with version 2, so RON files are the only ones
+//! older than that; version 2 YAML and TOML files are upgraded through the
+//! same value tree they're read with.
+
+use super::migrate::{migrate, nest_workflow_settings_value, Migrated, CURRENT_VERSION};
+use super::{to_ron_string, CciConfig};
+use crate::error::{config_error, Result};
+use std::path::{Path, PathBuf};
+
+/// Names a config file can have in a project, in the order they're looked for
+pub const CONFIG_FILE_NAMES: &[&str] = &["cci.ron", "cci.yaml", "cci.yml", "cci.toml"];
+
+/// The syntax a config file is written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Ron,
+    Yaml,
+    Toml,
+}
+
+impl ConfigFormat {
+    /// The format of `path` from its extension, RON unless it says otherwise
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml" | "yml") => ConfigFormat::Yaml,
+            Some("toml") => ConfigFormat::Toml,
+            _ => ConfigFormat::Ron,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ConfigFormat::Ron => "RON",
+            ConfigFormat::Yaml => "YAML",
+            ConfigFormat::Toml => "TOML",
+        }
+    }
+
+    /// Parse a config written in this format, upgrading it if it's older
+    pub fn migrate(self, text: &str) -> Result<Migrated> {
+        if self == ConfigFormat::Ron {
+            return migrate(text);
+        }
+        let invalid = |e: &dyn std::fmt::Display| {
+            config_error(format!("Invalid {} config: {}", self.name(), e))
+        };
+        let mut value: serde_json::Value = match self {
+            ConfigFormat::Yaml => serde_yaml::from_str(text).map_err(|e| invalid(&e))?,
+            _ => toml::from_str(text).map_err(|e| invalid(&e))?,
+        };
+        let from_version = value["version"].as_u64().unwrap_or(0);
+        if from_version == u64::from(CURRENT_VERSION) {
+            // Read the text itself, so errors point into it
+            let config: CciConfig = match self {
+                ConfigFormat::Yaml => serde_yaml::with::singleton_map_recursive::deserialize(
+                    serde_yaml::Deserializer::from_str(text),
+                )
+                .map_err(|e| invalid(&e))?,
+                _ => toml::from_str(text).map_err(|e| invalid(&e))?,
+            };
+            return Ok(Migrated {
+                from_version: config.version,
+                config,
+                changes: Vec::new(),
+            });
+        }
+        if from_version != 2 {
+            return Err(config_error(format!(
+                "{} config is version {}, but this release of cci reads versions 2 to {}",
+                self.name(),
+                from_version,
+                CURRENT_VERSION
+            )));
+        }
+
+        let mut changes = Vec::new();
+        nest_workflow_settings_value(&mut value, &mut changes);
+        value["version"] = CURRENT_VERSION.into();
+        let config: CciConfig = serde_yaml::with::singleton_map_recursive::deserialize(value)
+            .map_err(|e| invalid(&e))?;
+        Ok(Migrated {
+            config,
+            from_version: 2,
+            changes,
+        })
+    }
+
+    /// Parse a config written in this format
+    pub fn parse(self, text: &str) -> Result<CciConfig> {
+        Ok(self.migrate(text)?.config)
+    }
+
+    /// `config` written out in this format
+    pub fn write(self, config: &CciConfig) -> Result<String> {
+        match self {
+            ConfigFormat::Ron => to_ron_string(config),
+            ConfigFormat::Yaml => {
+                let mut out = Vec::new();
+                serde_yaml::with::singleton_map_recursive::serialize(
+                    config,
+                    &mut serde_yaml::Serializer::new(&mut out),
+                )
+                .map_err(|e| anyhow::anyhow!("Failed to serialize to YAML: {}", e))?;
+                String::from_utf8(out).map_err(|e| anyhow::anyhow!("Invalid YAML: {}", e))
+            }
+            ConfigFormat::Toml => toml::to_string_pretty(config)
+                .map_err(|e| anyhow::anyhow!("Failed to serialize to TOML: {}", e)),
+        }
+    }
+}
+
+/// `path`, or when it's a cci.ron that doesn't exist, the cci.yaml or
+/// cci.toml beside it that does
+pub fn locate_config(path: &Path) -> PathBuf {
+    if path.exists() || path.file_name().and_then(|name| name.to_str()) != Some("cci.ron") {
+        return path.to_path_buf();
+    }
+    let dir = path.parent().unwrap_or(Path::new(""));
+    CONFIG_FILE_NAMES
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|candidate| candidate.exists())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::preset_config_to_choice;
+    use crate::editor::registry::build_registry;
+    use tempfile::tempdir;
+
+    fn every_preset() -> CciConfig {
+        let registry = build_registry();
+        CciConfig::new(
+            registry
+                .all()
+                .iter()
+                .map(|preset| {
+                    preset_config_to_choice(preset.preset_id(), &preset.default_config(true))
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_formats_round_trip_every_preset() {
+        let config = every_preset();
+        for format in [ConfigFormat::Ron, ConfigFormat::Yaml, ConfigFormat::Toml] {
+            let text = format.write(&config).unwrap();
+            let parsed = format.parse(&text).unwrap();
+            assert_eq!(
+                format!("{:?}", parsed),
+                format!("{:?}", config),
+                "{} round trip",
+                format.name()
+            );
+        }
+    }
+
+    #[test]
+    fn test_yaml_and_toml_entries_are_keyed_by_preset() {
+        let yaml = ConfigFormat::Yaml.parse(
+            r#"
+version: 3
+presets:
+- Rust:
+    rust_version: stable
+    enable_coverage: false
+"#,
+        );
+        let toml = ConfigFormat::Toml.parse(
+            r#"
+version = 3
+
+[[presets]]
+[presets.Rust]
+rust_version = "stable"
+enable_coverage = false
+"#,
+        );
+        for config in [yaml.unwrap(), toml.unwrap()] {
+            let (preset_id, preset) = config.presets[0].to_preset_config();
+            assert_eq!(preset_id, "rust");
+            assert!(!preset.get_bool("enable_coverage"));
+        }
+    }
+
+    #[test]
+    fn test_yaml_and_toml_from_version_2_are_upgraded() {
+        let yaml = ConfigFormat::Yaml
+            .migrate("version: 2\npresets:\n- Docker:\n    image_name: app\n    tags: release-*\n");
+        let toml = ConfigFormat::Toml.migrate(
+            "version = 2\n\n[[presets]]\n[presets.Docker]\nimage_name = \"app\"\ntags = \"release-*\"\n",
+        );
+        for migrated in [yaml.unwrap(), toml.unwrap()] {
+            assert_eq!(migrated.from_version, 2);
+            assert_eq!(migrated.config.version, CURRENT_VERSION);
+            assert_eq!(migrated.changes, ["Docker: tags → triggers.tags"]);
+            let (_, docker) = migrated.config.presets[0].to_preset_config();
+            assert_eq!(docker.get_string("tags").unwrap(), "release-*");
+            assert_eq!(
+                docker.get_string("workflow_name").unwrap(),
+                "Docker Build and Push"
+            );
+        }
+    }
+
+    #[test]
+    fn test_yaml_and_toml_reject_other_versions() {
+        assert!(ConfigFormat::Yaml
+            .parse("version: 1\npresets: []\n")
+            .is_err());
+        assert!(ConfigFormat::Toml
+            .parse("version = 4\npresets = []\n")
+            .is_err());
+    }
+
+    #[test]
+    fn test_format_follows_extension() {
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("cci.yml")),
+            ConfigFormat::Yaml
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("a/cci.toml")),
+            ConfigFormat::Toml
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("cci.ron")),
+            ConfigFormat::Ron
+        );
+        assert_eq!(ConfigFormat::from_path(Path::new("-")), ConfigFormat::Ron);
+    }
+
+    #[test]
+    fn test_missing_cci_ron_falls_back_to_other_formats() {
+        let dir = tempdir().unwrap();
+        let cci_ron = dir.path().join("cci.ron");
+        assert_eq!(locate_config(&cci_ron), cci_ron);
+        std::fs::write(dir.path().join("cci.toml"), "").unwrap();
+        assert_eq!(locate_config(&cci_ron), dir.path().join("cci.toml"));
+        std::fs::write(&cci_ron, "").unwrap();
+        assert_eq!(locate_config(&cci_ron), cci_ron);
+    }
+}
@@ -0,0 +1,788 @@
+//! Upgrading cci.ron files written for older releases
+//!
+//! Files carry the format version they were written in; files without one
+//! predate versioning and count as version 1. Older files are parsed into a
+//! loose syntax tree that keeps every name, each migration between their
+//! version and [`CURRENT_VERSION`] rewrites it, and the result is parsed as a
+//! [`CciConfig`] like any current file.
+
+use super::{CciConfig, PresetChoice};
+use crate::error::{config_error, Result};
+use anyhow::Context;
+
+/// The version of cci.ron this release reads and writes
+pub const CURRENT_VERSION: u32 = 3;
+
+/// A step from one version to the next, rewriting every preset entry
+struct Migration {
+    to: u32,
+    apply: fn(&mut [Node], &mut Vec<String>),
+}
+
+/// Migrations in order; each one's `to` is one more than the last
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        to: 2,
+        apply: rename_legacy_options,
+    },
+    Migration {
+        to: 3,
+        apply: nest_workflow_settings,
+    },
+];
+
+/// An upgraded config and what upgrading it changed
+#[derive(Debug, Clone)]
+pub struct Migrated {
+    pub config: CciConfig,
+    /// The version the file was written in
+    pub from_version: u32,
+    /// One line per rewritten option, e.g. `Rust: version → rust_version`
+    pub changes: Vec<String>,
+}
+
+fn options() -> ron::Options {
+    ron::Options::default().with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+}
+
+/// Parse a cci.ron file of any version, upgrading it to the current one
+pub fn parse_config(text: &str) -> Result<CciConfig> {
+    Ok(migrate(text)?.config)
+}
+
+/// Parse a cci.ron file of any version, reporting how it was upgraded
+pub fn migrate(text: &str) -> Result<Migrated> {
+    let document = match Document::parse(text) {
+        Ok(document) => document,
+        // Let the real parser report what's wrong
+        Err(_) => return parse_current(text).map(unchanged),
+    };
+
+    let from_version = document.version()?;
+    if from_version > CURRENT_VERSION {
+        return Err(config_error(format!(
+            "cci.ron is version {}, but this release of cci only reads up to version {}; \
+             upgrade cci to use it",
+            from_version, CURRENT_VERSION
+        )));
+    }
+    if from_version == CURRENT_VERSION {
+        return parse_current(text).map(unchanged);
+    }
+
+    // Settings besides the presets, like `detection`, are kept as they are
+    let mut fields = document.into_fields();
+    fields.retain(|(id, _)| id != "version");
+    if !fields.iter().any(|(id, _)| id == "presets") {
+        fields.push(("presets".to_string(), Node::List(Vec::new())));
+    }
+    let mut changes = Vec::new();
+    for (_, presets) in fields.iter_mut().filter(|(id, _)| id == "presets") {
+        if let Node::List(presets) = presets {
+            for migration in MIGRATIONS.iter().filter(|m| m.to > from_version) {
+                (migration.apply)(presets, &mut changes);
+            }
+        }
+    }
+    fields.insert(
+        0,
+        (
+            "version".to_string(),
+            Node::Atom(CURRENT_VERSION.to_string()),
+        ),
+    );
+
+    let upgraded = Node::Struct(None, fields).to_string();
+    let config = parse_current(&upgraded).with_context(|| {
+        format!(
+            "cci.ron is version {}, and upgrading it to version {} left options this \
+             release doesn't know",
+            from_version, CURRENT_VERSION
+        )
+    })?;
+    Ok(Migrated {
+        config,
+        from_version,
+        changes,
+    })
+}
+
+fn unchanged(config: CciConfig) -> Migrated {
+    Migrated {
+        from_version: config.version,
+        config,
+        changes: Vec::new(),
+    }
+}
+
+/// Parse a file in the current format, or a bare preset list from before versioning
+fn parse_current(text: &str) -> Result<CciConfig> {
+    let mut parser = Parser::new(text);
+    let _ = parser.skip_attributes();
+    if parser.peek() == Some(b'[') {
+        let presets: Vec<PresetChoice> = options().from_str(text)?;
+        return Ok(CciConfig {
+            version: 1,
+            ..CciConfig::new(presets)
+        });
+    }
+    Ok(options().from_str(text)?)
+}
+
+/// Version 1 files used the option names from before presets were generated
+/// by `#[preset]`, e.g. `version` instead of `rust_version`
+fn rename_legacy_options(presets: &mut [Node], changes: &mut Vec<String>) {
+    const RENAMES: &[(&str, &str, &str)] = &[
+        ("Rust", "version", "rust_version"),
+        ("Rust", "coverage", "enable_coverage"),
+        ("Rust", "linter", "enable_linter"),
+        ("Rust", "security", "enable_security_scan"),
+        ("Rust", "formatter", "enable_format_check"),
+        ("Python", "version", "python_version"),
+        ("Python", "type_check", "enable_type_check"),
+        ("GoApp", "version", "go_version"),
+        ("GoApp", "linter", "enable_linter"),
+        ("GoApp", "security", "enable_security_scan"),
+        ("Docker", "registry_type", "registry"),
+        ("Docker", "cache", "enable_cache"),
+        ("Docker", "tags_only", "push_on_tags_only"),
+    ];
+    const VALUES: &[(&str, &str, &str, &str)] = &[
+        ("Docker", "registry", "docker_hub", "dockerhub"),
+        ("Docker", "registry", "github_registry", "github"),
+    ];
+
+    for preset in presets.iter_mut() {
+        let Some((name, fields)) = preset.preset_fields() else {
+            continue;
+        };
+        for (_, from, to) in RENAMES.iter().filter(|(preset, ..)| *preset == name) {
+            rename_field(&name, fields, from, to, changes);
+        }
+        for (_, field, from, to) in VALUES.iter().filter(|(preset, ..)| *preset == name) {
+            if let Some((_, value)) = fields.iter_mut().find(|(id, _)| id == field) {
+                if *value == Node::Atom(from.to_string()) {
+                    *value = Node::Atom(to.to_string());
+                    changes.push(format!("{}: {}: {} → {}", name, field, from, to));
+                }
+            }
+        }
+        // Python's linter and formatter were toggles with the tool chosen separately
+        if name == "Python" {
+            merge_tool_toggle(&name, fields, "linter", "linter_tool", changes);
+            merge_tool_toggle(&name, fields, "formatter", "formatter_tool", changes);
+        }
+    }
+}
+
+/// Presets whose workflow settings moved into the groups of
+/// [`crate::presets::workflow`] in version 3
+const WORKFLOW_PRESETS: &[&str] = &["Rust", "Python", "GoApp", "Docker"];
+
+/// Each group of those settings, by the field it's nested under, and its options
+const WORKFLOW_GROUPS: &[(&str, &[&str])] = &[
+    (
+        "github",
+        &["read_only_permissions", "cancel_in_progress", "pin_actions"],
+    ),
+    ("runner", &["runner", "runner_label"]),
+    (
+        "triggers",
+        &[
+            "workflow_name",
+            "push_branches",
+            "pr_branches",
+            "tags",
+            "schedule_cron",
+            "manual_dispatch",
+            "paths",
+        ],
+    ),
+];
+
+/// Version 2 files had the triggers, runner and GitHub settings of the
+/// language presets among their other options, e.g. `push_branches` instead
+/// of `triggers: (push_branches: ...)`
+fn nest_workflow_settings(presets: &mut [Node], changes: &mut Vec<String>) {
+    for preset in presets.iter_mut() {
+        let Some((name, fields)) = preset.preset_fields() else {
+            continue;
+        };
+        if !WORKFLOW_PRESETS.contains(&name.as_str()) {
+            continue;
+        }
+        for (group, options) in WORKFLOW_GROUPS {
+            let (nested, rest): (Vec<_>, Vec<_>) = std::mem::take(fields)
+                .into_iter()
+                .partition(|(id, _)| options.contains(&id.as_str()));
+            *fields = rest;
+            if nested.is_empty() {
+                continue;
+            }
+            for (id, _) in &nested {
+                changes.push(format!("{}: {} → {}.{}", name, id, group, id));
+            }
+            fields.push((group.to_string(), Node::Struct(None, nested)));
+        }
+    }
+}
+
+/// [`nest_workflow_settings`] for a version 2 YAML or TOML file, read as a
+/// value tree with each preset entry keyed by its name
+pub(super) fn nest_workflow_settings_value(
+    config: &mut serde_json::Value,
+    changes: &mut Vec<String>,
+) {
+    let Some(presets) = config["presets"].as_array_mut() else {
+        return;
+    };
+    for entry in presets.iter_mut().filter_map(|entry| entry.as_object_mut()) {
+        for (name, fields) in entry.iter_mut() {
+            let Some(fields) = fields.as_object_mut() else {
+                continue;
+            };
+            if !WORKFLOW_PRESETS.contains(&name.as_str()) {
+                continue;
+            }
+            for (group, options) in WORKFLOW_GROUPS {
+                let nested: serde_json::Map<String, serde_json::Value> = options
+                    .iter()
+                    .filter_map(|id| Some((id.to_string(), fields.remove(*id)?)))
+                    .collect();
+                if nested.is_empty() {
+                    continue;
+                }
+                for id in nested.keys() {
+                    changes.push(format!("{}: {} → {}.{}", name, id, group, id));
+                }
+                fields.insert(group.to_string(), serde_json::Value::Object(nested));
+            }
+        }
+    }
+}
+
+/// Rename `from` to `to`, dropping `from` if the file already set `to`
+fn rename_field(
+    preset: &str,
+    fields: &mut Vec<(String, Node)>,
+    from: &str,
+    to: &str,
+    changes: &mut Vec<String>,
+) {
+    let Some(index) = fields.iter().position(|(id, _)| id == from) else {
+        return;
+    };
+    if fields.iter().any(|(id, _)| id == to) {
+        fields.remove(index);
+        changes.push(format!(
+            "{}: removed {}, {} is already set",
+            preset, from, to
+        ));
+    } else {
+        fields[index].0 = to.to_string();
+        changes.push(format!("{}: {} → {}", preset, from, to));
+    }
+}
+
+/// Replace `toggle: true, tool_field: tool` with `toggle: tool`
+fn merge_tool_toggle(
+    preset: &str,
+    fields: &mut Vec<(String, Node)>,
+    toggle: &str,
+    tool_field: &str,
+    changes: &mut Vec<String>,
+) {
+    let tool = fields
+        .iter()
+        .position(|(id, _)| id == tool_field)
+        .map(|index| fields.remove(index).1);
+    let Some(index) = fields.iter().position(|(id, _)| id == toggle) else {
+        if tool.is_some() {
+            changes.push(format!("{}: removed {}", preset, tool_field));
+        }
+        return;
+    };
+    let enabled = match &fields[index].1 {
+        Node::Atom(value) if value == "true" => true,
+        Node::Atom(value) if value == "false" => false,
+        _ => return,
+    };
+    match tool {
+        Some(tool) if enabled => {
+            changes.push(format!(
+                "{}: {}: true, {}: {} → {}: {}",
+                preset, toggle, tool_field, tool, toggle, tool
+            ));
+            fields[index].1 = tool;
+        }
+        _ => {
+            // The tool can no longer be turned off, so either way the default is used
+            fields.remove(index);
+            changes.push(format!(
+                "{}: removed {}, using the default tool",
+                preset, toggle
+            ));
+        }
+    }
+}
+
+/// A parsed RON value that keeps the names serde's untyped values drop
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    /// Numbers, strings, characters and bare identifiers, as written
+    Atom(String),
+    /// `Name(a, b)` or `(a, b)`
+    Tuple(Option<String>, Vec<Node>),
+    /// `Name(a: 1)` or `(a: 1)`
+    Struct(Option<String>, Vec<(String, Node)>),
+    List(Vec<Node>),
+    Map(Vec<(Node, Node)>),
+}
+
+impl Node {
+    /// The preset name and options of an entry like `Rust((rust_version: "stable"))`
+    fn preset_fields(&mut self) -> Option<(String, &mut Vec<(String, Node)>)> {
+        match self {
+            Node::Tuple(Some(name), items) => match items.as_mut_slice() {
+                [Node::Struct(None, fields)] => Some((name.clone(), fields)),
+                _ => None,
+            },
+            Node::Struct(Some(name), fields) => Some((name.clone(), fields)),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Node {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn join<T>(
+            f: &mut std::fmt::Formatter<'_>,
+            items: &[T],
+            mut item: impl FnMut(&mut std::fmt::Formatter<'_>, &T) -> std::fmt::Result,
+        ) -> std::fmt::Result {
+            for (index, value) in items.iter().enumerate() {
+                if index > 0 {
+                    write!(f, ", ")?;
+                }
+                item(f, value)?;
+            }
+            Ok(())
+        }
+
+        match self {
+            Node::Atom(value) => write!(f, "{}", value),
+            Node::Tuple(name, items) => {
+                write!(f, "{}(", name.as_deref().unwrap_or(""))?;
+                join(f, items, |f, item| write!(f, "{}", item))?;
+                write!(f, ")")
+            }
+            Node::Struct(name, fields) => {
+                write!(f, "{}(", name.as_deref().unwrap_or(""))?;
+                join(f, fields, |f, (id, value)| write!(f, "{}: {}", id, value))?;
+                write!(f, ")")
+            }
+            Node::List(items) => {
+                write!(f, "[")?;
+                join(f, items, |f, item| write!(f, "{}", item))?;
+                write!(f, "]")
+            }
+            Node::Map(entries) => {
+                write!(f, "{{")?;
+                join(f, entries, |f, (key, value)| {
+                    write!(f, "{}: {}", key, value)
+                })?;
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// A whole cci.ron file
+struct Document {
+    root: Node,
+}
+
+impl Document {
+    fn parse(text: &str) -> std::result::Result<Self, String> {
+        let mut parser = Parser::new(text);
+        parser.skip_attributes()?;
+        let root = parser.value()?;
+        parser.skip_ws()?;
+        match parser.peek() {
+            None => Ok(Self { root }),
+            Some(_) => Err(parser.error("expected the end of the file")),
+        }
+    }
+
+    fn version(&self) -> Result<u32> {
+        match &self.root {
+            Node::List(_) => Ok(1),
+            Node::Struct(_, fields) => match fields.iter().find(|(id, _)| id == "version") {
+                Some((_, Node::Atom(version))) => version
+                    .parse()
+                    .map_err(|_| config_error(format!("Invalid cci.ron version: {}", version))),
+                Some((_, other)) => {
+                    Err(config_error(format!("Invalid cci.ron version: {}", other)))
+                }
+                None => Ok(1),
+            },
+            _ => Ok(1),
+        }
+    }
+
+    /// The top-level settings, with a bare list from before versioning as
+    /// the `presets`
+    fn into_fields(self) -> Vec<(String, Node)> {
+        match self.root {
+            Node::List(presets) => vec![("presets".to_string(), Node::List(presets))],
+            Node::Struct(_, fields) => fields,
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Reads RON's syntax without knowing the types it describes
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+type ParseResult<T> = std::result::Result<T, String>;
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn error(&self, message: &str) -> String {
+        let line = self.text[..self.pos].matches('\n').count() + 1;
+        format!("line {}: {}", line, message)
+    }
+
+    fn expect(&mut self, byte: u8) -> ParseResult<()> {
+        self.skip_ws()?;
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", byte as char)))
+        }
+    }
+
+    /// Skip whitespace and comments; block comments nest
+    fn skip_ws(&mut self) -> ParseResult<()> {
+        loop {
+            let rest = &self.text[self.pos..];
+            if let Some(c) = rest.chars().next().filter(|c| c.is_whitespace()) {
+                self.pos += c.len_utf8();
+            } else if rest.starts_with("//") {
+                self.pos += rest.find('\n').unwrap_or(rest.len());
+            } else if rest.starts_with("/*") {
+                let mut depth = 0;
+                loop {
+                    let rest = &self.text[self.pos..];
+                    if rest.starts_with("/*") {
+                        depth += 1;
+                        self.pos += 2;
+                    } else if rest.starts_with("*/") {
+                        depth -= 1;
+                        self.pos += 2;
+                        if depth == 0 {
+                            break;
+                        }
+                    } else if let Some(c) = rest.chars().next() {
+                        self.pos += c.len_utf8();
+                    } else {
+                        return Err(self.error("unterminated comment"));
+                    }
+                }
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Skip `#![enable(...)]` extension attributes
+    fn skip_attributes(&mut self) -> ParseResult<()> {
+        loop {
+            self.skip_ws()?;
+            if !self.text[self.pos..].starts_with("#!") {
+                return Ok(());
+            }
+            match self.text[self.pos..].find(']') {
+                Some(end) => self.pos += end + 1,
+                None => return Err(self.error("unterminated attribute")),
+            }
+        }
+    }
+
+    fn ident(&mut self) -> Option<&'a str> {
+        let start = self.pos;
+        let bytes = self.text.as_bytes();
+        if !bytes
+            .get(self.pos)
+            .is_some_and(|b| b.is_ascii_alphabetic() || *b == b'_')
+        {
+            return None;
+        }
+        while bytes
+            .get(self.pos)
+            .is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'_')
+        {
+            self.pos += 1;
+        }
+        Some(&self.text[start..self.pos])
+    }
+
+    fn value(&mut self) -> ParseResult<Node> {
+        self.skip_ws()?;
+        let start = self.pos;
+        match self.peek() {
+            None => Err(self.error("unexpected end of file")),
+            Some(b'"') => {
+                self.quoted(b'"')?;
+                Ok(Node::Atom(self.text[start..self.pos].to_string()))
+            }
+            Some(b'\'') => {
+                self.quoted(b'\'')?;
+                Ok(Node::Atom(self.text[start..self.pos].to_string()))
+            }
+            Some(b'r') if matches!(self.text.as_bytes().get(self.pos + 1), Some(b'"' | b'#')) => {
+                self.raw_string()?;
+                Ok(Node::Atom(self.text[start..self.pos].to_string()))
+            }
+            Some(b'(') => self.group(None),
+            Some(b'[') => {
+                self.pos += 1;
+                let items = self.separated(b']', |parser| parser.value())?;
+                Ok(Node::List(items))
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                let entries = self.separated(b'}', |parser| {
+                    let key = parser.value()?;
+                    parser.expect(b':')?;
+                    Ok((key, parser.value()?))
+                })?;
+                Ok(Node::Map(entries))
+            }
+            Some(b) if b.is_ascii_alphabetic() || b == b'_' => {
+                let name = self.ident().unwrap_or_default().to_string();
+                let before = self.pos;
+                self.skip_ws()?;
+                if self.peek() == Some(b'(') {
+                    self.group(Some(name))
+                } else {
+                    self.pos = before;
+                    Ok(Node::Atom(name))
+                }
+            }
+            Some(_) => {
+                while self
+                    .peek()
+                    .is_some_and(|b| b.is_ascii_alphanumeric() || b"_.+-".contains(&b))
+                {
+                    self.pos += 1;
+                }
+                if self.pos == start {
+                    return Err(self.error("expected a value"));
+                }
+                Ok(Node::Atom(self.text[start..self.pos].to_string()))
+            }
+        }
+    }
+
+    /// A parenthesized struct or tuple, after its optional name
+    fn group(&mut self, name: Option<String>) -> ParseResult<Node> {
+        self.pos += 1;
+        self.skip_ws()?;
+        let start = self.pos;
+        let is_struct = self.ident().is_some() && {
+            self.skip_ws()?;
+            let rest = &self.text[self.pos..];
+            rest.starts_with(':') && !rest.starts_with("::")
+        };
+        self.pos = start;
+
+        if is_struct {
+            let fields = self.separated(b')', |parser| {
+                parser.skip_ws()?;
+                let id = parser.ident().unwrap_or_default().to_string();
+                parser.expect(b':')?;
+                Ok((id, parser.value()?))
+            })?;
+            Ok(Node::Struct(name, fields))
+        } else {
+            let items = self.separated(b')', |parser| parser.value())?;
+            Ok(Node::Tuple(name, items))
+        }
+    }
+
+    /// Comma-separated items up to `close`, allowing a trailing comma
+    fn separated<T>(
+        &mut self,
+        close: u8,
+        mut item: impl FnMut(&mut Self) -> ParseResult<T>,
+    ) -> ParseResult<Vec<T>> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_ws()?;
+            if self.peek() == Some(close) {
+                self.pos += 1;
+                return Ok(items);
+            }
+            items.push(item(self)?);
+            self.skip_ws()?;
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b) if b == close => {}
+                _ => return Err(self.error(&format!("expected `,` or `{}`", close as char))),
+            }
+        }
+    }
+
+    fn quoted(&mut self, quote: u8) -> ParseResult<()> {
+        self.pos += 1;
+        loop {
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some(b'\\') => self.pos += 2,
+                Some(b) if b == quote => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some(_) => self.pos += 1,
+            }
+        }
+    }
+
+    fn raw_string(&mut self) -> ParseResult<()> {
+        self.pos += 1;
+        let hashes = self.text[self.pos..]
+            .bytes()
+            .take_while(|b| *b == b'#')
+            .count();
+        self.pos += hashes;
+        self.expect(b'"')?;
+        let end = format!("\"{}", "#".repeat(hashes));
+        match self.text[self.pos..].find(&end) {
+            Some(offset) => {
+                self.pos += offset + end.len();
+                Ok(())
+            }
+            None => Err(self.error("unterminated raw string")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_version_1() {
+        let text = r#"// Written before versioning
+[
+    Rust((
+        version: "stable",
+        coverage: true,
+        linter: true,
+        build_release: false,
+    )),
+    Python((
+        version: "3.11",
+        linter: true,
+        linter_tool: ruff,
+        formatter: false,
+    )),
+    Docker((
+        image_name: "myorg/myapp",
+        registry_type: docker_hub,
+        dockerfile_path: "./Dockerfile",
+        build_context: ".",
+        tags_only: false,
+    )),
+]
+"#;
+        let migrated = migrate(text).unwrap();
+        assert_eq!(migrated.from_version, 1);
+        assert_eq!(migrated.config.version, CURRENT_VERSION);
+        assert_eq!(migrated.config.presets.len(), 3);
+        assert!(migrated
+            .changes
+            .contains(&"Rust: version → rust_version".to_string()));
+        assert!(migrated
+            .changes
+            .contains(&"Docker: registry_type → registry".to_string()));
+
+        let rendered = crate::config::to_ron_string(&migrated.config).unwrap();
+        assert!(rendered.contains("version: 3"));
+        assert!(rendered.contains("linter: Some(ruff)"));
+        assert!(rendered.contains("registry: dockerhub"));
+        let reparsed = migrate(&rendered).unwrap();
+        assert_eq!(reparsed.from_version, CURRENT_VERSION);
+        assert!(reparsed.changes.is_empty());
+    }
+
+    #[test]
+    fn test_migrate_version_2_nests_workflow_settings() {
+        let text = r#"(
+    version: 2,
+    presets: [
+        Rust((rust_version: "stable", push_branches: "main", pin_actions: true, runner: macos)),
+        Release((workflow_name: "Ship")),
+    ],
+    detection: (max_depth: 2),
+)"#;
+        let migrated = migrate(text).unwrap();
+        assert_eq!(migrated.from_version, 2);
+        assert_eq!(
+            migrated.changes,
+            [
+                "Rust: pin_actions → github.pin_actions",
+                "Rust: runner → runner.runner",
+                "Rust: push_branches → triggers.push_branches",
+            ]
+        );
+        assert_eq!(migrated.config.detection.max_depth, 2);
+
+        let (_, rust) = migrated.config.presets[0].to_preset_config();
+        assert_eq!(rust.get_string("push_branches").unwrap(), "main");
+        assert_eq!(rust.get_string("pr_branches").unwrap(), "main, master");
+        assert!(rust.get_bool("pin_actions"));
+        assert_eq!(rust.get_enum("runner").as_deref(), Some("macos"));
+        // Addons keep the trigger options they have of their own
+        let (_, release) = migrated.config.presets[1].to_preset_config();
+        assert_eq!(release.get_string("workflow_name").unwrap(), "Ship");
+    }
+
+    #[test]
+    fn test_current_bare_list_and_newer_versions() {
+        let config =
+            parse_config("#![enable(implicit_some)]\n[Rust((rust_version: \"stable\"))]").unwrap();
+        assert_eq!(config.presets.len(), 1);
+
+        let error = parse_config("(version: 99, presets: [])").unwrap_err();
+        assert!(error.to_string().contains("version 99"));
+    }
+
+    #[test]
+    fn test_examples_are_current() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let migrated = migrate(&std::fs::read_to_string(&path).unwrap()).unwrap();
+            assert_eq!(
+                migrated.from_version,
+                CURRENT_VERSION,
+                "{} is out of date",
+                path.display()
+            );
+            assert!(migrated.changes.is_empty());
+        }
+    }
+}
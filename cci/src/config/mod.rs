@@ -1,4 +1,10 @@
 // converter.rs deleted - conversion methods now auto-generated by #[derive(Preset)] macro
+pub mod extends;
+pub mod format;
+pub mod migrate;
 pub mod ron_types;
+pub mod schema;
 
+pub use extends::{ExtendsBase, ExtendsConfig};
+pub use format::{locate_config, ConfigFormat, CONFIG_FILE_NAMES};
 pub use ron_types::*;
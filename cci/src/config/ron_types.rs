@@ -1,10 +1,63 @@
+use crate::detection::DetectionOptions;
+use crate::editor::state::FileNaming;
 use serde::{Deserialize, Serialize};
 
 // Re-export the generated config types from presets
-pub use crate::presets::{DockerConfig, GoAppConfig, PythonAppConfig, RustConfig};
+pub use crate::presets::{
+    CodeScanningConfig, CustomConfig, DeployConfig, DockerConfig, GoAppConfig, NightlyConfig,
+    NotificationsConfig, PythonAppConfig, ReleaseAutomationConfig, ReleaseConfig, RustConfig,
+};
 
-/// Top-level CCI configuration - just an array of presets
-pub type CciConfig = Vec<PresetChoice>;
+/// Top-level CCI configuration: the format version, the chosen presets, how
+/// projects are detected and how the pipelines are split into files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CciConfig {
+    /// See [`crate::config::migrate`] for how older versions are upgraded
+    pub version: u32,
+    pub presets: Vec<PresetChoice>,
+    #[serde(default, skip_serializing_if = "DetectionOptions::is_default")]
+    pub detection: DetectionOptions,
+    #[serde(default, skip_serializing_if = "FileNaming::is_default")]
+    pub file_naming: FileNaming,
+}
+
+impl CciConfig {
+    /// A config in the current format
+    pub fn new(presets: Vec<PresetChoice>) -> Self {
+        Self {
+            version: crate::config::migrate::CURRENT_VERSION,
+            presets,
+            detection: DetectionOptions::default(),
+            file_naming: FileNaming::default(),
+        }
+    }
+
+    /// Every option value the presets' rules don't allow, one message each
+    pub fn invalid_values(&self) -> Vec<String> {
+        let registry = crate::editor::registry::build_registry();
+        self.presets
+            .iter()
+            .map(preset_choice_to_config)
+            .flat_map(|(preset_id, config)| match registry.get(&preset_id) {
+                Some(preset) => crate::editor::config::validate_config(preset.as_ref(), &config),
+                None => Vec::new(),
+            })
+            .collect()
+    }
+
+    /// An error listing every value [`CciConfig::invalid_values`] finds
+    pub fn check_values(&self) -> crate::error::Result<()> {
+        let problems = self.invalid_values();
+        if problems.is_empty() {
+            return Ok(());
+        }
+        Err(crate::error::config_error(format!(
+            "{} invalid option value(s):\n  {}",
+            problems.len(),
+            problems.join("\n  ")
+        )))
+    }
+}
 
 /// Preset choice enum - supports all available presets
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,12 +67,26 @@ pub enum PresetChoice {
     Rust(RustConfig),
     GoApp(GoAppConfig),
     Docker(DockerConfig),
+    Release(ReleaseConfig),
+    ReleaseAutomation(ReleaseAutomationConfig),
+    CodeScanning(CodeScanningConfig),
+    Notifications(NotificationsConfig),
+    Nightly(NightlyConfig),
+    Deploy(DeployConfig),
+    /// A preset from `~/.config/cci/presets/`
+    Custom(CustomConfig),
+    /// Another config's settings for a preset, see [`crate::config::extends`]
+    Extends(super::ExtendsConfig),
 }
 
 impl PresetChoice {
     /// Convert a PresetChoice to a PresetConfig using the generated conversion methods
     pub fn to_preset_config(&self) -> (String, crate::editor::config::PresetConfig) {
-        use crate::presets::{DockerPreset, GoAppPreset, PythonAppPreset, RustPreset};
+        use crate::presets::{
+            CodeScanningPreset, DeployPreset, DockerPreset, GoAppPreset, NightlyPreset,
+            NotificationsPreset, PythonAppPreset, ReleaseAutomationPreset, ReleasePreset,
+            RustPreset,
+        };
 
         match self {
             PresetChoice::Rust(config) => (
@@ -38,10 +105,57 @@ impl PresetChoice {
                 "docker".to_string(),
                 DockerPreset::ron_to_preset_config(config.clone()),
             ),
+            PresetChoice::Release(config) => (
+                "release".to_string(),
+                ReleasePreset::ron_to_preset_config(config.clone()),
+            ),
+            PresetChoice::ReleaseAutomation(config) => (
+                "release-automation".to_string(),
+                ReleaseAutomationPreset::ron_to_preset_config(config.clone()),
+            ),
+            PresetChoice::CodeScanning(config) => (
+                "code-scanning".to_string(),
+                CodeScanningPreset::ron_to_preset_config(config.clone()),
+            ),
+            PresetChoice::Notifications(config) => (
+                "notifications".to_string(),
+                NotificationsPreset::ron_to_preset_config(config.clone()),
+            ),
+            PresetChoice::Nightly(config) => (
+                "nightly".to_string(),
+                NightlyPreset::ron_to_preset_config(config.clone()),
+            ),
+            PresetChoice::Deploy(config) => (
+                "deploy".to_string(),
+                DeployPreset::ron_to_preset_config(config.clone()),
+            ),
+            PresetChoice::Custom(config) => (config.preset.clone(), config.to_preset_config()),
+            // Entries are resolved when cci.ron is loaded; this is for one that wasn't
+            PresetChoice::Extends(extends) => {
+                let config = extends
+                    .base(std::path::Path::new("."))
+                    .and_then(|base| extends.apply(&base.config))
+                    .unwrap_or_else(|e| {
+                        eprintln!("Warning: {:#}", e);
+                        crate::editor::config::PresetConfig::new(extends.preset.clone())
+                    });
+                (extends.preset.clone(), config)
+            }
         }
     }
 }
 
+/// Serialize a config the way cci.ron files are written
+pub fn to_ron_string(config: &CciConfig) -> crate::error::Result<String> {
+    let pretty_config = ron::ser::PrettyConfig::new()
+        .depth_limit(5)
+        .separate_tuple_members(true)
+        .enumerate_arrays(false);
+
+    ron::ser::to_string_pretty(config, pretty_config)
+        .map_err(|e| anyhow::anyhow!("Failed to serialize to RON: {}", e))
+}
+
 /// Convert a PresetChoice to a (preset_id, PresetConfig) tuple
 pub fn preset_choice_to_config(
     choice: &PresetChoice,
@@ -54,13 +168,63 @@ pub fn preset_config_to_choice(
     preset_id: &str,
     config: &crate::editor::config::PresetConfig,
 ) -> PresetChoice {
-    use crate::presets::{DockerPreset, GoAppPreset, PythonAppPreset, RustPreset};
+    use crate::presets::{
+        CodeScanningPreset, DeployPreset, DockerPreset, GoAppPreset, NightlyPreset,
+        NotificationsPreset, PythonAppPreset, ReleaseAutomationPreset, ReleasePreset, RustPreset,
+    };
 
     match preset_id {
         "rust" => PresetChoice::Rust(RustPreset::preset_config_to_ron(config)),
         "python-app" => PresetChoice::PythonApp(PythonAppPreset::preset_config_to_ron(config)),
         "go-app" => PresetChoice::GoApp(GoAppPreset::preset_config_to_ron(config)),
         "docker" => PresetChoice::Docker(DockerPreset::preset_config_to_ron(config)),
-        _ => panic!("Unknown preset ID: {}", preset_id),
+        "release" => PresetChoice::Release(ReleasePreset::preset_config_to_ron(config)),
+        "release-automation" => {
+            PresetChoice::ReleaseAutomation(ReleaseAutomationPreset::preset_config_to_ron(config))
+        }
+        "code-scanning" => {
+            PresetChoice::CodeScanning(CodeScanningPreset::preset_config_to_ron(config))
+        }
+        "notifications" => {
+            PresetChoice::Notifications(NotificationsPreset::preset_config_to_ron(config))
+        }
+        "nightly" => PresetChoice::Nightly(NightlyPreset::preset_config_to_ron(config)),
+        "deploy" => PresetChoice::Deploy(DeployPreset::preset_config_to_ron(config)),
+        _ => PresetChoice::Custom(CustomConfig::from_preset_config(preset_id, config)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::registry::build_registry;
+    use crate::presets::RustPreset;
+
+    #[test]
+    fn test_options_left_out_take_their_declared_defaults() {
+        let registry = build_registry();
+        for entry in [
+            "Rust(())",
+            "Python(())",
+            "GoApp(())",
+            "Docker(())",
+            "Release(())",
+            "ReleaseAutomation(())",
+            "CodeScanning(())",
+            "Notifications(())",
+            "Nightly(())",
+            "Deploy(())",
+        ] {
+            let choice: PresetChoice = ron::from_str(entry).unwrap();
+            let (preset_id, config) = choice.to_preset_config();
+            let defaults = registry.get(&preset_id).unwrap().default_config(true);
+            assert_eq!(config.values, defaults.values, "{}", entry);
+        }
+
+        let config = RustPreset::ron_to_preset_config(ron::from_str("()").unwrap());
+        assert_eq!(
+            format!("{:?}", RustPreset::from_config(&config, "stable")),
+            format!("{:?}", RustPreset::default())
+        );
     }
 }
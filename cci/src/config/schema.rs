@@ -0,0 +1,125 @@
+//! JSON Schema of cci.ron, for editors to complete and check it with
+//!
+//! RON isn't JSON, but it goes through serde's data model, which the schema
+//! describes: each entry of `presets` is an object with a single key naming
+//! the preset (`Rust`, `Python`, `Custom`, ...) that holds its settings, each
+//! preset's coming from [`EditorPreset::config_schema`].
+
+use super::migrate::CURRENT_VERSION;
+use super::preset_config_to_choice;
+use crate::editor::config::EditorPreset;
+use crate::editor::registry::build_registry;
+use serde_json::{json, Value};
+
+/// The schema of a whole cci.ron, with every preset cci knows about
+pub fn config_schema() -> Value {
+    let registry = build_registry();
+    let mut entries: Vec<Value> = registry
+        .all()
+        .iter()
+        .map(|preset| entry_schema(preset.as_ref()))
+        .collect();
+    entries.push(json!({
+        "type": "object",
+        "properties": {
+            "Extends": {
+                "type": "object",
+                "description": "Another config's settings for a preset, with some options changed",
+                "properties": {
+                    "preset": { "type": "string" },
+                    "from": { "type": "string" },
+                    "options": { "type": "object" },
+                },
+                "required": ["preset"],
+            },
+        },
+        "required": ["Extends"],
+        "additionalProperties": false,
+    }));
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "cci.ron",
+        "type": "object",
+        "properties": {
+            "version": { "type": "integer", "minimum": 1, "maximum": CURRENT_VERSION },
+            "presets": { "type": "array", "items": { "oneOf": entries } },
+            "detection": {
+                "type": "object",
+                "properties": {
+                    "max_depth": { "type": "integer", "minimum": 0 },
+                    "respect_gitignore": { "type": "boolean" },
+                    "exclude": { "type": "array", "items": { "type": "string" } },
+                },
+                "additionalProperties": false,
+            },
+        },
+        "required": ["presets"],
+    })
+}
+
+/// A `presets` entry for `preset`, under the name cci.ron gives it
+fn entry_schema(preset: &dyn EditorPreset) -> Value {
+    let choice = preset_config_to_choice(preset.preset_id(), &preset.default_config(true));
+    let name = match serde_json::to_value(choice) {
+        Ok(Value::Object(entry)) => entry.keys().next().cloned().unwrap_or_default(),
+        _ => String::new(),
+    };
+    let mut settings = preset.config_schema();
+    if let Some(settings) = settings.as_object_mut() {
+        settings.remove("$schema");
+    }
+    json!({
+        "type": "object",
+        "properties": { name.clone(): settings },
+        "required": [name],
+        "additionalProperties": false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CciConfig;
+    use crate::lint::schema::Schema;
+
+    fn schema() -> Schema {
+        Schema::parse(&serde_json::to_string(&config_schema()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_default_configs_match_schema() {
+        let registry = build_registry();
+        let config = CciConfig::new(
+            registry
+                .all()
+                .iter()
+                .map(|preset| {
+                    preset_config_to_choice(preset.preset_id(), &preset.default_config(true))
+                })
+                .collect(),
+        );
+        // serde_yaml writes enums as tags, so go through JSON for the data model
+        let instance = serde_yaml::to_value(serde_json::to_value(&config).unwrap()).unwrap();
+        let issues = schema().validate(&instance);
+        assert!(issues.is_empty(), "{:?}", issues);
+    }
+
+    #[test]
+    fn test_schema_rejects_unknown_and_mistyped_options() {
+        let schema = schema();
+        let check = |entry: &str| {
+            let text = format!(
+                r#"{{"version": {}, "presets": [{}]}}"#,
+                CURRENT_VERSION, entry
+            );
+            schema.validate(&serde_yaml::from_str(&text).unwrap())
+        };
+        assert!(check(r#"{"Rust": {"rust_version": "stable"}}"#).is_empty());
+        assert!(
+            !check(r#"{"Rust": {"rust_version": "stable", "enable_lasers": true}}"#).is_empty()
+        );
+        assert!(!check(r#"{"Docker": {"registry": "azure"}}"#).is_empty());
+        assert!(!check(r#"{"Cobol": {}}"#).is_empty());
+    }
+}
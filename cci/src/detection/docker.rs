@@ -4,24 +4,31 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
+/// The Dockerfiles looked for, the first found being the main one, then the
+/// Compose files
+const MARKERS: &[&str] = &[
+    "Dockerfile",
+    "dockerfile",
+    "Dockerfile.dev",
+    "Dockerfile.prod",
+    "Dockerfile.build",
+    "docker-compose.yml",
+    "docker-compose.yaml",
+    "compose.yml",
+    "compose.yaml",
+];
+const DOCKERFILES: &[&str] = MARKERS.split_at(5).0;
+const COMPOSE_FILES: &[&str] = MARKERS.split_at(5).1;
+
 pub struct DockerDetector;
 
 impl ProjectDetector for DockerDetector {
     fn detect(&self, path: &Path) -> Result<Option<DetectionResult>> {
-        // Look for Dockerfile or common variations
-        let dockerfile_patterns = vec![
-            "Dockerfile",
-            "dockerfile",
-            "Dockerfile.dev",
-            "Dockerfile.prod",
-            "Dockerfile.build",
-        ];
-
         let mut found_dockerfiles = Vec::new();
         let mut metadata = HashMap::new();
 
         // Check for standard Dockerfiles in the root
-        for pattern in &dockerfile_patterns {
+        for pattern in DOCKERFILES {
             let dockerfile_path = path.join(pattern);
             if dockerfile_path.exists() {
                 found_dockerfiles.push(pattern.to_string());
@@ -29,14 +36,8 @@ impl ProjectDetector for DockerDetector {
         }
 
         // Also check for docker-compose files
-        let compose_files = vec![
-            "docker-compose.yml",
-            "docker-compose.yaml",
-            "compose.yml",
-            "compose.yaml",
-        ];
         let mut has_compose = false;
-        for compose_file in &compose_files {
+        for compose_file in COMPOSE_FILES {
             let compose_path = path.join(compose_file);
             if compose_path.exists() {
                 has_compose = true;
@@ -74,8 +75,13 @@ impl ProjectDetector for DockerDetector {
             metadata.insert("has_compose".to_string(), "yes".to_string());
         }
 
+        // Images are usually built from a project another detector finds,
+        // and a Compose file alone may only run its services
+        let confidence = if found_dockerfiles.is_empty() { 40 } else { 60 };
+
         Ok(Some(DetectionResult {
             project_type: ProjectType::DockerImage,
+            confidence,
             language_version: None,
             metadata,
         }))
@@ -84,6 +90,10 @@ impl ProjectDetector for DockerDetector {
     fn name(&self) -> &str {
         "Docker"
     }
+
+    fn markers(&self) -> &[&str] {
+        MARKERS
+    }
 }
 
 /// Extract the base image from a Dockerfile
@@ -0,0 +1,129 @@
+//! Finding the CI configs a repository already has, and so which platforms
+//! it already uses
+
+use crate::editor::state::Platform;
+use crate::lint::find_ci_files;
+use crate::platforms::circleci::models::CircleCIConfig;
+use crate::platforms::github::models::GitHubWorkflow;
+use crate::platforms::gitlab::models::GitLabCI;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A CI config found in the repository
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExistingCi {
+    pub platform: Platform,
+    /// Relative to the directory searched
+    pub path: PathBuf,
+    /// Whether the platform's models read the file; Jenkinsfiles are Groovy,
+    /// so they're recognized by name alone and never parsed
+    pub parsed: bool,
+}
+
+/// Every CI config in `dir`, sorted by path
+pub fn find_existing_ci(dir: &Path) -> Vec<ExistingCi> {
+    let mut found: Vec<ExistingCi> = find_ci_files(dir)
+        .into_iter()
+        .map(|(path, platform)| {
+            let parsed =
+                fs::read_to_string(&path).is_ok_and(|content| parses_as(platform, &content));
+            ExistingCi {
+                platform,
+                path: path.strip_prefix(dir).unwrap_or(&path).to_path_buf(),
+                parsed,
+            }
+        })
+        .collect();
+    if dir.join("Jenkinsfile").is_file() {
+        found.push(ExistingCi {
+            platform: Platform::Jenkins,
+            path: PathBuf::from("Jenkinsfile"),
+            parsed: false,
+        });
+    }
+    found.sort_by(|a, b| a.path.cmp(&b.path));
+    found
+}
+
+/// The platforms `found` uses, those with a config the models read first,
+/// then in [`Platform::all`] order
+pub fn existing_platforms(found: &[ExistingCi]) -> Vec<Platform> {
+    let mut platforms: Vec<Platform> = Platform::all()
+        .into_iter()
+        .filter(|platform| found.iter().any(|ci| ci.platform == *platform))
+        .collect();
+    platforms.sort_by_key(|platform| {
+        !found
+            .iter()
+            .any(|ci| ci.platform == *platform && (ci.parsed || ci.platform == Platform::Jenkins))
+    });
+    platforms
+}
+
+fn parses_as(platform: Platform, content: &str) -> bool {
+    match platform {
+        Platform::GitHub | Platform::Gitea => {
+            serde_yaml::from_str::<GitHubWorkflow>(content).is_ok()
+        }
+        Platform::GitLab => serde_yaml::from_str::<GitLabCI>(content).is_ok(),
+        Platform::CircleCI => serde_yaml::from_str::<CircleCIConfig>(content).is_ok(),
+        Platform::Jenkins => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_find_existing_ci() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join(".github/workflows")).unwrap();
+        fs::write(
+            root.join(".github/workflows/ci.yml"),
+            "name: CI\non: [push]\njobs:\n  test:\n    runs-on: ubuntu-latest\n    steps:\n      - run: make test\n",
+        )
+        .unwrap();
+        fs::write(root.join(".gitlab-ci.yml"), "- not a pipeline\n").unwrap();
+        fs::write(root.join("Jenkinsfile"), "pipeline {}\n").unwrap();
+
+        let found = find_existing_ci(root);
+        let summary: Vec<(&str, Platform, bool)> = found
+            .iter()
+            .map(|ci| (ci.path.to_str().unwrap(), ci.platform, ci.parsed))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (".github/workflows/ci.yml", Platform::GitHub, true),
+                (".gitlab-ci.yml", Platform::GitLab, false),
+                ("Jenkinsfile", Platform::Jenkins, false),
+            ]
+        );
+
+        // A config that doesn't parse may be stale, so it ranks last
+        assert_eq!(
+            existing_platforms(&found),
+            vec![Platform::GitHub, Platform::Jenkins, Platform::GitLab]
+        );
+        assert!(find_existing_ci(tempdir().unwrap().path()).is_empty());
+    }
+
+    #[test]
+    fn test_scalar_triggers_and_needs_parse() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join(".github/workflows")).unwrap();
+        fs::write(
+            root.join(".github/workflows/ci.yml"),
+            "name: CI\non: push\njobs:\n  build:\n    runs-on: ubuntu-latest\n    steps:\n      - run: make\n  test:\n    runs-on: ubuntu-latest\n    needs: build\n    steps:\n      - run: make test\n",
+        )
+        .unwrap();
+
+        let found = find_existing_ci(root);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].parsed);
+    }
+}
@@ -0,0 +1,282 @@
+//! Containers and infrastructure-as-code living next to a project
+//!
+//! These aren't projects of their own, but each calls for an addon preset:
+//! a Dockerfile for the Docker preset, and Helm charts, Terraform or
+//! Kubernetes manifests for the Deploy preset.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directories infrastructure is usually kept in, besides the root
+const INFRA_DIRS: &[&str] = &[
+    "deploy",
+    "infra",
+    "infrastructure",
+    "terraform",
+    "k8s",
+    "kubernetes",
+    "manifests",
+    "helm",
+    "charts",
+];
+
+/// Something besides the code that the pipeline could build or deploy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Facet {
+    Dockerfile,
+    DockerCompose,
+    Helm,
+    Terraform,
+    Kubernetes,
+}
+
+impl Facet {
+    /// Identifier used in JSON output
+    pub fn id(&self) -> &'static str {
+        match self {
+            Facet::Dockerfile => "dockerfile",
+            Facet::DockerCompose => "compose",
+            Facet::Helm => "helm",
+            Facet::Terraform => "terraform",
+            Facet::Kubernetes => "kubernetes",
+        }
+    }
+
+    /// What was noticed, e.g. "a Dockerfile"
+    pub fn description(&self) -> &'static str {
+        match self {
+            Facet::Dockerfile => "a Dockerfile",
+            Facet::DockerCompose => "a Compose file",
+            Facet::Helm => "a Helm chart",
+            Facet::Terraform => "Terraform configuration",
+            Facet::Kubernetes => "Kubernetes manifests",
+        }
+    }
+
+    /// The addon preset that builds or deploys it
+    pub fn preset_id(&self) -> &'static str {
+        match self {
+            Facet::Dockerfile | Facet::DockerCompose => "docker",
+            Facet::Helm | Facet::Terraform | Facet::Kubernetes => "deploy",
+        }
+    }
+}
+
+/// A facet and where it was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectedFacet {
+    pub facet: Facet,
+    /// The file, or for Terraform and Kubernetes the directory, relative to
+    /// the project; `.` for the project itself
+    pub path: PathBuf,
+}
+
+impl DetectedFacet {
+    /// The command that deploys it, for the facets the Deploy preset handles
+    pub fn deploy_command(&self) -> Option<String> {
+        let path = self.path.to_string_lossy().replace('\\', "/");
+        match self.facet {
+            Facet::Helm => {
+                let chart = self.path.parent().unwrap_or(Path::new("."));
+                let chart = chart.to_string_lossy().replace('\\', "/");
+                let chart = if chart.is_empty() {
+                    ".".to_string()
+                } else {
+                    chart
+                };
+                let release = Path::new(&chart)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "app".to_string());
+                Some(format!("helm upgrade --install {} {}", release, chart))
+            }
+            Facet::Terraform if path == "." => Some("terraform apply -auto-approve".to_string()),
+            Facet::Terraform => Some(format!("terraform -chdir={} apply -auto-approve", path)),
+            Facet::Kubernetes => Some(format!("kubectl apply -f {}", path)),
+            Facet::Dockerfile | Facet::DockerCompose => None,
+        }
+    }
+}
+
+/// Every facet of the project in `dir`, at most one of each kind, in the
+/// order of [`Facet`]
+pub fn detect_facets(dir: &Path) -> Vec<DetectedFacet> {
+    let mut candidates = vec![PathBuf::from(".")];
+    candidates.extend(INFRA_DIRS.iter().map(PathBuf::from));
+    let candidates: Vec<PathBuf> = candidates
+        .into_iter()
+        .filter(|candidate| dir.join(candidate).is_dir())
+        .collect();
+
+    let mut found = Vec::new();
+    let mut add = |facet: Facet, path: Option<PathBuf>| {
+        if let Some(path) = path {
+            found.push(DetectedFacet { facet, path });
+        }
+    };
+
+    add(
+        Facet::Dockerfile,
+        first_file(dir, &["Dockerfile", "dockerfile", "Containerfile"]),
+    );
+    add(
+        Facet::DockerCompose,
+        first_file(
+            dir,
+            &[
+                "docker-compose.yml",
+                "docker-compose.yaml",
+                "compose.yml",
+                "compose.yaml",
+            ],
+        ),
+    );
+    // Charts sit in their own directory, e.g. charts/api/Chart.yaml
+    add(
+        Facet::Helm,
+        candidates.iter().find_map(|candidate| {
+            let chart = candidate.join("Chart.yaml");
+            if dir.join(&chart).is_file() {
+                return Some(normalize(chart));
+            }
+            subdirs(&dir.join(candidate))
+                .into_iter()
+                .map(|subdir| candidate.join(subdir).join("Chart.yaml"))
+                .find(|chart| dir.join(chart).is_file())
+                .map(normalize)
+        }),
+    );
+    add(
+        Facet::Terraform,
+        candidates
+            .iter()
+            .find(|candidate| has_file(&dir.join(candidate), |name| name.ends_with(".tf")))
+            .cloned(),
+    );
+    add(
+        Facet::Kubernetes,
+        candidates
+            .iter()
+            .filter(|candidate| candidate.as_os_str() != ".")
+            .find(|candidate| {
+                let candidate = dir.join(candidate);
+                has_file(&candidate, |name| name.starts_with("kustomization."))
+                    || yaml_files(&candidate)
+                        .iter()
+                        .any(|path| is_kubernetes_manifest(path))
+            })
+            .cloned(),
+    );
+    found
+}
+
+fn first_file(dir: &Path, names: &[&str]) -> Option<PathBuf> {
+    names
+        .iter()
+        .find(|name| dir.join(name).is_file())
+        .map(PathBuf::from)
+}
+
+/// `./Chart.yaml` as `Chart.yaml`
+fn normalize(path: PathBuf) -> PathBuf {
+    path.strip_prefix(".")
+        .map(Path::to_path_buf)
+        .unwrap_or(path)
+}
+
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut subdirs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()))
+        .map(|entry| PathBuf::from(entry.file_name()))
+        .collect();
+    subdirs.sort();
+    subdirs
+}
+
+fn has_file(dir: &Path, matches: impl Fn(&str) -> bool) -> bool {
+    fs::read_dir(dir).is_ok_and(|entries| {
+        entries
+            .filter_map(|entry| entry.ok())
+            .any(|entry| matches(&entry.file_name().to_string_lossy()))
+    })
+}
+
+fn yaml_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            matches!(
+                path.extension().and_then(|ext| ext.to_str()),
+                Some("yml" | "yaml")
+            )
+        })
+        .collect()
+}
+
+/// Whether the file reads as Kubernetes objects: an `apiVersion` and a `kind`
+fn is_kubernetes_manifest(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|content| {
+        content.lines().any(|line| line.starts_with("apiVersion:"))
+            && content.lines().any(|line| line.starts_with("kind:"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_detect_facets() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        write(root, "Dockerfile", "FROM alpine\n");
+        write(root, "charts/api/Chart.yaml", "name: api\n");
+        write(root, "infra/main.tf", "terraform {}\n");
+        write(
+            root,
+            "k8s/deployment.yaml",
+            "apiVersion: apps/v1\nkind: Deployment\n",
+        );
+        // Not a manifest
+        write(root, "deploy/values.yaml", "replicas: 2\n");
+
+        let found = detect_facets(root);
+        let summary: Vec<(Facet, &str)> = found
+            .iter()
+            .map(|found| (found.facet, found.path.to_str().unwrap()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Facet::Dockerfile, "Dockerfile"),
+                (Facet::Helm, "charts/api/Chart.yaml"),
+                (Facet::Terraform, "infra"),
+                (Facet::Kubernetes, "k8s"),
+            ]
+        );
+        assert_eq!(
+            found[1].deploy_command().as_deref(),
+            Some("helm upgrade --install api charts/api")
+        );
+        assert_eq!(
+            found[2].deploy_command().as_deref(),
+            Some("terraform -chdir=infra apply -auto-approve")
+        );
+        assert_eq!(found[0].deploy_command(), None);
+        assert!(detect_facets(tempdir().unwrap().path()).is_empty());
+    }
+}
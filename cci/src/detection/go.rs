@@ -1,9 +1,41 @@
-use super::{DetectionResult, ProjectDetector, ProjectType};
+use super::{
+    insert_known_dependencies, DetectionResult, KnownDependencies, ProjectDetector, ProjectType,
+};
 use crate::error::Result;
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
+/// Module path prefixes that say what kind of project this is
+const KNOWN_DEPENDENCIES: KnownDependencies = &[
+    (
+        "framework",
+        &[
+            ("github.com/gin-gonic/gin", "gin"),
+            ("github.com/labstack/echo", "echo"),
+            ("github.com/gofiber/fiber", "fiber"),
+            ("github.com/go-chi/chi", "chi"),
+        ],
+    ),
+    (
+        "cli",
+        &[
+            ("github.com/spf13/cobra", "cobra"),
+            ("github.com/urfave/cli", "urfave-cli"),
+        ],
+    ),
+    (
+        "database",
+        &[
+            ("github.com/lib/pq", "postgres"),
+            ("github.com/jackc/pgx", "postgres"),
+            ("github.com/go-sql-driver/mysql", "mysql"),
+            ("github.com/redis/go-redis", "redis"),
+            ("github.com/go-redis/redis", "redis"),
+        ],
+    ),
+];
+
 pub struct GoDetector;
 
 impl ProjectDetector for GoDetector {
@@ -16,12 +48,18 @@ impl ProjectDetector for GoDetector {
 
         let mut metadata = HashMap::new();
 
-        // Try to parse go.mod for version
+        // The go directive names the version the module is written for
+        let mut go_version = None;
         if let Ok(contents) = fs::read_to_string(&go_mod) {
             if let Some(line) = contents.lines().find(|l| l.starts_with("go ")) {
                 let version = line.strip_prefix("go ").unwrap_or("1.21").trim();
                 metadata.insert("go_version".to_string(), version.to_string());
+                go_version = Some(version.to_string());
             }
+            let required = required_modules(&contents);
+            insert_known_dependencies(&mut metadata, KNOWN_DEPENDENCIES, |prefix| {
+                required.iter().any(|module| module.starts_with(prefix))
+            });
         }
 
         // Simple heuristic: apps have main.go in root or cmd/
@@ -33,9 +71,14 @@ impl ProjectDetector for GoDetector {
             ProjectType::GoLibrary
         };
 
+        // A module without any Go files of its own may only vendor tooling
+        let has_sources = is_app || has_go_files(path);
+        let confidence = if has_sources { 95 } else { 85 };
+
         Ok(Some(DetectionResult {
             project_type,
-            language_version: Some("1.21".to_string()),
+            confidence,
+            language_version: Some(go_version.unwrap_or_else(|| "1.21".to_string())),
             metadata,
         }))
     }
@@ -43,4 +86,82 @@ impl ProjectDetector for GoDetector {
     fn name(&self) -> &str {
         "Go"
     }
+
+    fn markers(&self) -> &[&str] {
+        &["go.mod"]
+    }
+}
+
+fn has_go_files(path: &Path) -> bool {
+    fs::read_dir(path).is_ok_and(|entries| {
+        entries
+            .filter_map(|entry| entry.ok())
+            .any(|entry| entry.path().extension().is_some_and(|ext| ext == "go"))
+    })
+}
+
+/// The module paths in go.mod's `require` directives
+fn required_modules(go_mod: &str) -> Vec<&str> {
+    let mut modules = Vec::new();
+    let mut in_block = false;
+    for line in go_mod.lines() {
+        let line = line.trim();
+        let module = if in_block {
+            if line == ")" {
+                in_block = false;
+                continue;
+            }
+            line
+        } else if let Some(rest) = line.strip_prefix("require ") {
+            if rest.trim() == "(" {
+                in_block = true;
+                continue;
+            }
+            rest.trim()
+        } else {
+            continue;
+        };
+        if let Some(path) = module.split_whitespace().next() {
+            modules.push(path);
+        }
+    }
+    modules
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_version_from_go_directive() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("go.mod"),
+            "module example.com/app\n\ngo 1.22.3\n\ntoolchain go1.23.0\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("main.go"), "package main\n").unwrap();
+
+        let result = GoDetector.detect(dir.path()).unwrap().unwrap();
+
+        assert_eq!(result.project_type, ProjectType::GoApp);
+        assert_eq!(result.language_version.as_deref(), Some("1.22.3"));
+    }
+
+    #[test]
+    fn test_framework_metadata() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("go.mod"),
+            "module example.com/tool\n\ngo 1.22\n\nrequire github.com/spf13/cobra v1.8.0\n\nrequire (\n\tgithub.com/jackc/pgx/v5 v5.5.0\n\tgithub.com/spf13/pflag v1.0.5 // indirect\n)\n",
+        )
+        .unwrap();
+
+        let result = GoDetector.detect(dir.path()).unwrap().unwrap();
+
+        assert_eq!(result.metadata["cli"], "cobra");
+        assert_eq!(result.metadata["database"], "postgres");
+        assert!(!result.metadata.contains_key("framework"));
+    }
 }
@@ -0,0 +1,564 @@
+//! Reconstruct preset configs from existing GitHub workflows
+//!
+//! `Detectable` only says which preset a workflow looks like. Each preset's
+//! `extract_config` reads what options it can straight from the workflow;
+//! from there each alternative option value is generated in turn and kept
+//! when the output gets closer to the existing workflow.
+
+use crate::editor::config::{EditorPreset, OptionValue, PresetConfig};
+use crate::editor::registry::PresetRegistry;
+use crate::editor::state::Platform;
+use crate::error::Result;
+use crate::platforms::github::models::{job_id, GitHubRunsOn, GitHubWorkflow};
+use crate::platforms::github::pinning::is_sha;
+use crate::platforms::helpers::PlatformConfig;
+use crate::platforms::triggers::TriggerConfig;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Presets reconstructed from one workflow file
+#[derive(Debug, Clone)]
+pub struct ImportedWorkflow {
+    pub path: PathBuf,
+    /// The base preset followed by the decorators applied to it
+    pub configs: Vec<PresetConfig>,
+    /// Signatures (steps, jobs, settings) the configs don't reproduce; 0 is exact
+    pub distance: usize,
+    /// What the workflow does that the configs don't generate, described
+    pub missing: Vec<String>,
+    /// What the configs generate that the workflow doesn't do, described
+    pub extra: Vec<String>,
+}
+
+impl ImportedWorkflow {
+    /// The options the configs set differently from a new preset's defaults,
+    /// as preset ID, option ID and value
+    pub fn changed_options(&self, registry: &PresetRegistry) -> Vec<(String, String, OptionValue)> {
+        let mut changed = Vec::new();
+        for config in &self.configs {
+            let Some(preset) = registry.get(&config.preset_id) else {
+                continue;
+            };
+            let defaults = preset.default_config(true);
+            for feature in preset.features() {
+                for option in feature.options {
+                    if let Some(value) = config.get(&option.id) {
+                        if defaults.get(&option.id) != Some(value) {
+                            changed.push((config.preset_id.clone(), option.id, value.clone()));
+                        }
+                    }
+                }
+            }
+        }
+        changed
+    }
+}
+
+/// Import every workflow under `.github/workflows` that matches a preset
+///
+/// Workflows the models can't parse, or that no preset matches, are skipped;
+/// [`unreadable_github_workflows`] says why the former were.
+pub fn import_github_workflows(
+    working_dir: &Path,
+    registry: &PresetRegistry,
+    language_version: &str,
+) -> Result<Vec<ImportedWorkflow>> {
+    let paths = workflow_paths(working_dir)?;
+    let mut imported = Vec::new();
+    for path in paths {
+        let content = fs::read_to_string(&path)?;
+        let Ok(workflow) = serde_yaml::from_str::<GitHubWorkflow>(&content) else {
+            continue;
+        };
+        if let Some((configs, distance)) =
+            import_github_workflow(&workflow, registry, language_version)
+        {
+            let target = signatures(&workflow);
+            let generated = render(&configs, registry, language_version)
+                .map(|generated| signatures(&generated))
+                .unwrap_or_default();
+            imported.push(ImportedWorkflow {
+                path,
+                configs,
+                distance,
+                missing: target.difference(&generated).map(|s| describe(s)).collect(),
+                extra: generated.difference(&target).map(|s| describe(s)).collect(),
+            });
+        }
+    }
+    Ok(imported)
+}
+
+/// Workflows under `.github/workflows` the models can't parse, with the
+/// parse error
+pub fn unreadable_github_workflows(working_dir: &Path) -> Result<Vec<(PathBuf, String)>> {
+    let mut unreadable = Vec::new();
+    for path in workflow_paths(working_dir)? {
+        let content = fs::read_to_string(&path)?;
+        if let Err(e) = serde_yaml::from_str::<GitHubWorkflow>(&content) {
+            unreadable.push((path, e.to_string()));
+        }
+    }
+    Ok(unreadable)
+}
+
+/// The YAML files under `.github/workflows`, sorted
+fn workflow_paths(working_dir: &Path) -> Result<Vec<PathBuf>> {
+    let dir = working_dir.join(".github/workflows");
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            matches!(
+                path.extension().and_then(|ext| ext.to_str()),
+                Some("yml" | "yaml")
+            )
+        })
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Reconstruct the preset (and decorators) that best reproduce `workflow`
+///
+/// Returns the configs with the remaining distance, or `None` when no preset
+/// matches the workflow.
+pub fn import_github_workflow(
+    workflow: &GitHubWorkflow,
+    registry: &PresetRegistry,
+    language_version: &str,
+) -> Option<(Vec<PresetConfig>, usize)> {
+    let (base, base_config, mut distance) = registry
+        .all()
+        .into_iter()
+        .filter(|preset| !preset.is_decorator() && preset.matches_github(workflow))
+        .filter_map(|preset| {
+            let (config, distance) = fit(preset.as_ref(), workflow, |config| {
+                github_workflow(
+                    preset
+                        .build(config, Platform::GitHub, language_version)
+                        .ok()?,
+                )
+            })?;
+            Some((preset, config, distance))
+        })
+        .min_by_key(|(_, _, distance)| *distance)?;
+
+    let mut configs = vec![base_config];
+    for decorator in registry
+        .all()
+        .into_iter()
+        .filter(|preset| preset.is_decorator() && preset.matches_github(workflow))
+    {
+        let decorated = fit(decorator.as_ref(), workflow, |config| {
+            let mut pipeline = base
+                .build(&configs[0], Platform::GitHub, language_version)
+                .ok()?;
+            decorator
+                .decorate(config, language_version, &mut pipeline)
+                .ok()?;
+            github_workflow(pipeline)
+        });
+        if let Some((config, decorated_distance)) = decorated {
+            if decorated_distance < distance {
+                distance = decorated_distance;
+                configs.push(config);
+            }
+        }
+    }
+    Some((configs, distance))
+}
+
+/// The workflow a base preset's config and its decorators' generate together
+fn render(
+    configs: &[PresetConfig],
+    registry: &PresetRegistry,
+    language_version: &str,
+) -> Option<GitHubWorkflow> {
+    let (base, decorators) = configs.split_first()?;
+    let mut pipeline = registry
+        .get(&base.preset_id)?
+        .build(base, Platform::GitHub, language_version)
+        .ok()?;
+    for config in decorators {
+        registry
+            .get(&config.preset_id)?
+            .decorate(config, language_version, &mut pipeline)
+            .ok()?;
+    }
+    github_workflow(pipeline)
+}
+
+fn github_workflow(pipeline: PlatformConfig) -> Option<GitHubWorkflow> {
+    match pipeline {
+        PlatformConfig::GitHub(workflow) => Some(workflow),
+        _ => None,
+    }
+}
+
+/// Adjust `preset`'s options until `render` reproduces `workflow` as closely as it can
+fn fit(
+    preset: &dyn EditorPreset,
+    workflow: &GitHubWorkflow,
+    render: impl Fn(&PresetConfig) -> Option<GitHubWorkflow>,
+) -> Option<(PresetConfig, usize)> {
+    let target = signatures(workflow);
+    let distance = |config: &PresetConfig| {
+        let generated = signatures(&render(config)?);
+        Some(generated.symmetric_difference(&target).count())
+    };
+
+    let mut config = preset.extract_config(workflow);
+    let mut best = distance(&config)?;
+
+    let options: Vec<String> = preset
+        .features()
+        .into_iter()
+        .flat_map(|feature| feature.options.into_iter().map(|option| option.id))
+        .collect();
+
+    // Take the single change that helps most each round, so a toggle isn't
+    // switched off when picking another of its choices would match better
+    while best > 0 {
+        let Some((trial_distance, trial)) = options
+            .iter()
+            .flat_map(|option| {
+                alternatives(config.get(option))
+                    .into_iter()
+                    .map(|candidate| {
+                        let mut trial = config.clone();
+                        trial.set(option.clone(), candidate);
+                        trial
+                    })
+            })
+            .filter_map(|trial| Some((distance(&trial)?, trial)))
+            .min_by_key(|(trial_distance, _)| *trial_distance)
+        else {
+            break;
+        };
+        if trial_distance >= best {
+            break;
+        }
+        best = trial_distance;
+        config = trial;
+    }
+    Some((config, best))
+}
+
+/// Other values a toggle or choice could take; free-form values are left alone
+fn alternatives(value: Option<&OptionValue>) -> Vec<OptionValue> {
+    match value {
+        Some(OptionValue::Bool(enabled)) => vec![OptionValue::Bool(!enabled)],
+        Some(OptionValue::Enum { selected, variants }) => variants
+            .iter()
+            .filter(|variant| *variant != selected)
+            .map(|variant| OptionValue::Enum {
+                selected: variant.clone(),
+                variants: variants.clone(),
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Copy the workflow's name, triggers and settings into the options every
+/// preset shares
+///
+/// These translate back directly, so they're read instead of searched for.
+/// Pinned actions in particular can't be searched for, as signatures leave
+/// out action versions.
+pub(crate) fn read_workflow_settings(workflow: &GitHubWorkflow, config: &mut PresetConfig) {
+    let actions: Vec<&str> = workflow
+        .jobs
+        .values()
+        .flat_map(|job| &job.steps)
+        .filter_map(|step| step.uses.as_deref())
+        .filter(|uses| !uses.starts_with("./") && !uses.starts_with("docker://"))
+        .collect();
+    let pinned = !actions.is_empty()
+        && actions.iter().all(|uses| {
+            uses.split_once('@')
+                .is_some_and(|(_, reference)| is_sha(reference))
+        });
+    let triggers = TriggerConfig::from_github(workflow);
+    let values = [
+        ("workflow_name", OptionValue::String(triggers.name)),
+        (
+            "push_branches",
+            OptionValue::String(triggers.push_branches.join(", ")),
+        ),
+        (
+            "pr_branches",
+            OptionValue::String(triggers.pr_branches.join(", ")),
+        ),
+        ("tags", OptionValue::String(triggers.tags.join(", "))),
+        ("paths", OptionValue::String(triggers.paths.join(", "))),
+        (
+            "schedule_cron",
+            OptionValue::String(triggers.schedule_cron.unwrap_or_default()),
+        ),
+        (
+            "manual_dispatch",
+            OptionValue::Bool(triggers.manual_dispatch),
+        ),
+        (
+            "read_only_permissions",
+            OptionValue::Bool(workflow.permissions.is_some()),
+        ),
+        (
+            "cancel_in_progress",
+            OptionValue::Bool(workflow.concurrency.is_some()),
+        ),
+        ("pin_actions", OptionValue::Bool(pinned)),
+    ];
+    for (id, value) in values {
+        if config.get(id).is_some() {
+            config.set(id.to_string(), value);
+        }
+    }
+}
+
+/// Whether a step of `workflow` runs a command or uses an action containing
+/// `marker`
+pub(crate) fn has_step(workflow: &GitHubWorkflow, marker: &str) -> bool {
+    workflow
+        .jobs
+        .values()
+        .flat_map(|job| &job.steps)
+        .any(|step| {
+            step.run.as_deref().is_some_and(|run| run.contains(marker))
+                || step
+                    .uses
+                    .as_deref()
+                    .is_some_and(|uses| uses.contains(marker))
+        })
+}
+
+/// What a workflow does, as comparable strings
+///
+/// Action versions are dropped so pinned or upgraded actions still match.
+fn signatures(workflow: &GitHubWorkflow) -> BTreeSet<String> {
+    let mut signatures = BTreeSet::new();
+    if workflow.permissions.is_some() {
+        signatures.insert("permissions".to_string());
+    }
+    if workflow.concurrency.is_some() {
+        signatures.insert("concurrency".to_string());
+    }
+
+    for (id, job) in &workflow.jobs {
+        // Built workflows still use `preset/job` IDs; files have them rewritten
+        let id = &job_id(id);
+        signatures.insert(format!("job:{}", id));
+        let runs_on = match &job.runs_on {
+            GitHubRunsOn::Label(label) => label.clone(),
+            GitHubRunsOn::Labels(labels) => labels.join(","),
+        };
+        signatures.insert(format!("runs-on:{}", runs_on));
+        if job.if_condition.is_some() {
+            signatures.insert(format!("if:{}", id));
+        }
+
+        for step in &job.steps {
+            if let Some(uses) = &step.uses {
+                let action = uses.split('@').next().unwrap_or(uses);
+                signatures.insert(format!("uses:{}", action));
+                for (key, value) in step.with.iter().flatten() {
+                    signatures.insert(format!("with:{}:{}={:?}", action, key, value));
+                }
+            }
+            if let Some(run) = &step.run {
+                signatures.insert(format!("run:{}", run.trim()));
+            }
+        }
+    }
+    signatures
+}
+
+/// A signature in words, e.g. `runs \`cargo test\`` for `run:cargo test`
+fn describe(signature: &str) -> String {
+    let Some((kind, detail)) = signature.split_once(':') else {
+        return format!("{} settings", signature);
+    };
+    match kind {
+        "job" => format!("job {}", detail),
+        "runs-on" => format!("a job on {}", detail),
+        "if" => format!("a condition on job {}", detail),
+        "uses" => format!("action {}", detail),
+        "with" => format!("input {}", detail),
+        _ => {
+            let command = detail.lines().next().unwrap_or_default();
+            let more = if detail.lines().nth(1).is_some() {
+                " …"
+            } else {
+                ""
+            };
+            format!("runs `{}{}`", command, more)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::registry::build_registry;
+
+    fn render(preset_id: &str, config: &PresetConfig) -> GitHubWorkflow {
+        let registry = build_registry();
+        let preset = registry.get(preset_id).unwrap();
+        github_workflow(preset.build(config, Platform::GitHub, "stable").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_import_restores_options() {
+        let registry = build_registry();
+        let mut config = registry.get("rust").unwrap().default_config(true);
+        config.set("enable_coverage".to_string(), OptionValue::Bool(false));
+        config.set("enable_security_scan".to_string(), OptionValue::Bool(false));
+        config.set(
+            "workflow_name".to_string(),
+            OptionValue::String("Build".to_string()),
+        );
+        config.set(
+            "push_branches".to_string(),
+            OptionValue::String("develop".to_string()),
+        );
+        let workflow = render("rust", &config);
+
+        let (configs, distance) = import_github_workflow(&workflow, &registry, "stable").unwrap();
+        assert_eq!(distance, 0);
+        assert_eq!(configs.len(), 1);
+        let imported = &configs[0];
+        assert_eq!(imported.preset_id, "rust");
+        assert!(!imported.get_bool("enable_coverage"));
+        assert!(!imported.get_bool("enable_security_scan"));
+        assert!(imported.get_bool("enable_linter"));
+        assert_eq!(imported.get_string("workflow_name").unwrap(), "Build");
+        assert_eq!(imported.get_string("push_branches").unwrap(), "develop");
+        assert_eq!(render("rust", imported), workflow);
+    }
+
+    #[test]
+    fn test_extract_config_reads_options_back() {
+        let registry = build_registry();
+        let rust = registry.get("rust").unwrap();
+        let mut config = rust.default_config(true);
+        config.set("enable_coverage".to_string(), OptionValue::Bool(false));
+        config.set("enable_linter".to_string(), OptionValue::Bool(false));
+        config.set("cancel_in_progress".to_string(), OptionValue::Bool(false));
+        config.set(
+            "toolchain_matrix".to_string(),
+            OptionValue::String("stable, beta".to_string()),
+        );
+        let workflow = render("rust", &config);
+
+        // Read straight from the workflow, with no candidate configs rendered
+        let extracted = rust.extract_config(&workflow);
+        assert!(!extracted.get_bool("enable_coverage"));
+        assert!(!extracted.get_bool("enable_linter"));
+        assert!(extracted.get_bool("enable_security_scan"));
+        assert!(extracted.get_bool("read_only_permissions"));
+        assert!(!extracted.get_bool("cancel_in_progress"));
+        assert!(!extracted.get_bool("pin_actions"));
+        assert_eq!(
+            extracted.get_string("toolchain_matrix").unwrap(),
+            "stable, beta"
+        );
+        assert_eq!(render("rust", &extracted), workflow);
+    }
+
+    #[test]
+    fn test_import_reports_what_differs() {
+        let registry = build_registry();
+        let mut config = registry.get("rust").unwrap().default_config(true);
+        config.set("enable_coverage".to_string(), OptionValue::Bool(false));
+        let mut workflow = render("rust", &config);
+        let job = workflow.jobs.values_mut().next().unwrap();
+        let mut step = job.steps[0].clone();
+        step.uses = None;
+        step.with = None;
+        step.name = Some("Lint docs".to_string());
+        step.run = Some("make lint-docs".to_string());
+        job.steps.push(step);
+
+        let dir = tempfile::tempdir().unwrap();
+        let workflows = dir.path().join(".github/workflows");
+        fs::create_dir_all(&workflows).unwrap();
+        fs::write(
+            workflows.join("ci.yml"),
+            serde_yaml::to_string(&workflow).unwrap(),
+        )
+        .unwrap();
+
+        let imported = import_github_workflows(dir.path(), &registry, "stable").unwrap();
+        assert_eq!(imported.len(), 1);
+        assert_eq!(
+            imported[0].missing,
+            vec!["runs `make lint-docs`".to_string()]
+        );
+        assert!(imported[0].extra.is_empty());
+        assert_eq!(imported[0].distance, 1);
+        assert!(imported[0].changed_options(&registry).contains(&(
+            "rust".to_string(),
+            "enable_coverage".to_string(),
+            OptionValue::Bool(false)
+        )));
+    }
+
+    #[test]
+    fn test_import_skips_unknown_workflows() {
+        let dir = tempfile::tempdir().unwrap();
+        let workflows = dir.path().join(".github/workflows");
+        fs::create_dir_all(&workflows).unwrap();
+        fs::write(workflows.join("broken.yml"), "not: [a workflow").unwrap();
+        fs::write(
+            workflows.join("other.yml"),
+            "name: Other\non: [push]\njobs:\n  hello:\n    runs-on: ubuntu-latest\n    steps:\n      - run: echo hi\n",
+        )
+        .unwrap();
+
+        let imported = import_github_workflows(dir.path(), &build_registry(), "stable").unwrap();
+        assert!(imported.is_empty());
+        let unreadable = unreadable_github_workflows(dir.path()).unwrap();
+        assert_eq!(unreadable.len(), 1);
+        assert!(unreadable[0].0.ends_with("broken.yml"));
+    }
+
+    #[test]
+    fn test_import_reads_scalar_triggers_and_needs() {
+        let dir = tempfile::tempdir().unwrap();
+        let workflows = dir.path().join(".github/workflows");
+        fs::create_dir_all(&workflows).unwrap();
+        fs::write(
+            workflows.join("ci.yml"),
+            r#"name: CI
+on: push
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+      - uses: dtolnay/rust-toolchain@v1
+        with:
+          toolchain: stable
+      - run: cargo test --all-features
+  lint:
+    runs-on: ubuntu-latest
+    needs: test
+    steps:
+      - uses: actions/checkout@v4
+      - run: cargo clippy --all-features -- -D warnings
+"#,
+        )
+        .unwrap();
+
+        let imported = import_github_workflows(dir.path(), &build_registry(), "stable").unwrap();
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0].configs[0].preset_id, "rust");
+        assert!(unreadable_github_workflows(dir.path()).unwrap().is_empty());
+    }
+}
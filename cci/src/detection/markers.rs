@@ -0,0 +1,66 @@
+//! Recognizing a preset's pipelines by the actions and commands they run,
+//! for the `Detectable` impls `#[preset(detect_uses, detect_run)]` generates
+
+use crate::platforms::circleci::models::{CircleCIConfig, CircleCIRun, CircleCIStep};
+use crate::platforms::github::models::GitHubWorkflow;
+use crate::platforms::gitlab::models::GitLabCI;
+use crate::platforms::jenkins::models::JenkinsConfig;
+
+fn contains_any(text: &str, markers: &[&str]) -> bool {
+    markers.iter().any(|marker| text.contains(marker))
+}
+
+/// Whether a step uses one of `uses` and a step runs one of `run`; an empty
+/// list holds for any workflow
+pub(crate) fn github_matches(workflow: &GitHubWorkflow, uses: &[&str], run: &[&str]) -> bool {
+    let steps = || workflow.jobs.values().flat_map(|job| &job.steps);
+    let uses_any = uses.is_empty()
+        || steps().any(|step| {
+            step.uses
+                .as_deref()
+                .is_some_and(|action| contains_any(action, uses))
+        });
+    let runs_any = run.is_empty()
+        || steps().any(|step| {
+            step.run
+                .as_deref()
+                .is_some_and(|command| contains_any(command, run))
+        });
+    uses_any && runs_any
+}
+
+/// Whether a job's scripts run one of `run`
+pub(crate) fn gitlab_runs(config: &GitLabCI, run: &[&str]) -> bool {
+    config.jobs.values().any(|job| {
+        job.before_script
+            .iter()
+            .flatten()
+            .chain(&job.script)
+            .chain(job.after_script.iter().flatten())
+            .any(|line| contains_any(line, run))
+    })
+}
+
+/// Whether a job's `run` steps run one of `run`
+pub(crate) fn circleci_runs(config: &CircleCIConfig, run: &[&str]) -> bool {
+    config.jobs.values().flat_map(|job| &job.steps).any(|step| {
+        let CircleCIStep::Command { run: command } = step else {
+            return false;
+        };
+        match command {
+            CircleCIRun::Simple(command)
+            | CircleCIRun::Detailed { command, .. }
+            | CircleCIRun::Timed { command, .. }
+            | CircleCIRun::Conditional { command, .. } => contains_any(command, run),
+        }
+    })
+}
+
+/// Whether a stage's steps run one of `run`
+pub(crate) fn jenkins_runs(config: &JenkinsConfig, run: &[&str]) -> bool {
+    config
+        .stages
+        .iter()
+        .flat_map(|stage| &stage.steps)
+        .any(|step| contains_any(step, run))
+}
@@ -1,9 +1,16 @@
 mod docker;
+mod existing_ci;
+mod facets;
 mod go;
+mod import;
+mod markers;
+mod node;
+mod options;
 mod preset_detector;
 mod python;
 mod registry;
 mod rust;
+mod workspace;
 
 use crate::error::Result;
 use std::collections::HashMap;
@@ -13,6 +20,9 @@ use std::path::Path;
 #[derive(Debug, Clone, PartialEq)]
 pub struct DetectionResult {
     pub project_type: ProjectType,
+    /// How sure the detector is, from 0 to 100, going by how much of the
+    /// project it recognized; see [`DetectorRegistry::detect`] for ties
+    pub confidence: u8,
     pub language_version: Option<String>,
     pub metadata: HashMap<String, String>,
 }
@@ -27,9 +37,28 @@ pub enum ProjectType {
     PythonLibrary,
     GoApp,
     GoLibrary,
+    NodeApp,
+    NodeLibrary,
     DockerImage,
 }
 
+impl ProjectType {
+    pub fn all() -> Vec<ProjectType> {
+        vec![
+            ProjectType::RustLibrary,
+            ProjectType::RustBinary,
+            ProjectType::RustWorkspace,
+            ProjectType::PythonApp,
+            ProjectType::PythonLibrary,
+            ProjectType::GoApp,
+            ProjectType::GoLibrary,
+            ProjectType::NodeApp,
+            ProjectType::NodeLibrary,
+            ProjectType::DockerImage,
+        ]
+    }
+}
+
 impl std::fmt::Display for ProjectType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -40,11 +69,32 @@ impl std::fmt::Display for ProjectType {
             ProjectType::PythonLibrary => write!(f, "Python Library"),
             ProjectType::GoApp => write!(f, "Go Application"),
             ProjectType::GoLibrary => write!(f, "Go Library"),
+            ProjectType::NodeApp => write!(f, "Node.js Application"),
+            ProjectType::NodeLibrary => write!(f, "Node.js Library"),
             ProjectType::DockerImage => write!(f, "Docker Image"),
         }
     }
 }
 
+/// Dependencies worth knowing about, as a metadata key and, for each value,
+/// the dependency that implies it
+type KnownDependencies<'a> = &'a [(&'a str, &'a [(&'a str, &'a str)])];
+
+/// Record what a project's dependencies say about it, e.g. `framework:
+/// django`, taking the first match for each key; `depends_on` is asked about
+/// each dependency name in the tables
+fn insert_known_dependencies(
+    metadata: &mut HashMap<String, String>,
+    known: KnownDependencies,
+    depends_on: impl Fn(&str) -> bool,
+) {
+    for (key, dependencies) in known {
+        if let Some((_, value)) = dependencies.iter().find(|(name, _)| depends_on(name)) {
+            metadata.insert(key.to_string(), value.to_string());
+        }
+    }
+}
+
 /// Trait for detecting project types
 pub trait ProjectDetector: Send + Sync {
     /// Detect if this detector matches the current project
@@ -52,11 +102,28 @@ pub trait ProjectDetector: Send + Sync {
 
     /// Get the name of this detector
     fn name(&self) -> &str;
+
+    /// Files whose presence means this detector may match a directory, so
+    /// directories without any are skipped; empty to be asked about every
+    /// directory
+    fn markers(&self) -> &[&str] {
+        &[]
+    }
 }
 
 pub use docker::DockerDetector;
+pub use existing_ci::{existing_platforms, find_existing_ci, ExistingCi};
+pub use facets::{detect_facets, DetectedFacet, Facet};
 pub use go::GoDetector;
+pub(crate) use import::{has_step, read_workflow_settings};
+pub use import::{
+    import_github_workflow, import_github_workflows, unreadable_github_workflows, ImportedWorkflow,
+};
+pub(crate) use markers::{circleci_runs, github_matches, gitlab_runs, jenkins_runs};
+pub use node::NodeDetector;
+pub use options::DetectionOptions;
 pub use preset_detector::PresetDetector;
 pub use python::PythonDetector;
 pub use registry::DetectorRegistry;
 pub use rust::RustDetector;
+pub use workspace::{MemberSource, WorkspaceDetector, WorkspaceMember};
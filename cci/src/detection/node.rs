@@ -0,0 +1,232 @@
+use super::{DetectionResult, ProjectDetector, ProjectType};
+use crate::error::Result;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Lockfiles and the package manager that writes each, most specific first
+const LOCKFILES: &[(&str, &str)] = &[
+    ("pnpm-lock.yaml", "pnpm"),
+    ("yarn.lock", "yarn"),
+    ("bun.lockb", "bun"),
+    ("package-lock.json", "npm"),
+    ("npm-shrinkwrap.json", "npm"),
+];
+
+/// Frameworks recognized by a dependency on their package, checked in order
+/// so a Next.js app built with Vite plugins is still Next.js
+const FRAMEWORKS: &[(&str, &str)] = &[
+    ("next", "next"),
+    ("nuxt", "nuxt"),
+    ("@sveltejs/kit", "sveltekit"),
+    ("@remix-run/react", "remix"),
+    ("astro", "astro"),
+    ("vite", "vite"),
+];
+
+pub struct NodeDetector;
+
+impl ProjectDetector for NodeDetector {
+    fn detect(&self, path: &Path) -> Result<Option<DetectionResult>> {
+        let package_json = path.join("package.json");
+
+        if !package_json.exists() {
+            return Ok(None);
+        }
+
+        // A package.json that doesn't parse still makes this a Node project
+        let manifest: Value = fs::read_to_string(&package_json)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or(Value::Null);
+
+        let mut metadata = HashMap::new();
+
+        if let Some(name) = manifest.get("name").and_then(Value::as_str) {
+            metadata.insert("name".to_string(), name.to_string());
+        }
+        metadata.insert(
+            "package_manager".to_string(),
+            package_manager(path, &manifest).to_string(),
+        );
+        let typescript =
+            path.join("tsconfig.json").exists() || has_dependency(&manifest, "typescript");
+        metadata.insert(
+            "language".to_string(),
+            if typescript {
+                "typescript"
+            } else {
+                "javascript"
+            }
+            .to_string(),
+        );
+
+        let framework = FRAMEWORKS
+            .iter()
+            .find(|(package, _)| has_dependency(&manifest, package))
+            .map(|(_, framework)| *framework);
+        if let Some(framework) = framework {
+            metadata.insert("framework".to_string(), framework.to_string());
+        }
+
+        // Published packages say where their entry point is; apps are private
+        // or are built with a framework
+        let is_library = framework.is_none()
+            && manifest.get("private").and_then(Value::as_bool) != Some(true)
+            && ["main", "exports", "module", "types"]
+                .iter()
+                .any(|key| manifest.get(key).is_some());
+
+        let project_type = if is_library {
+            ProjectType::NodeLibrary
+        } else {
+            ProjectType::NodeApp
+        };
+
+        // A package.json with only dev tooling, e.g. commit hooks in a Rust
+        // repository, isn't much of a Node project
+        let confidence = if manifest.is_null() {
+            50
+        } else if framework.is_some()
+            || is_library
+            || manifest.get("dependencies").is_some()
+            || manifest.get("bin").is_some()
+        {
+            90
+        } else {
+            45
+        };
+
+        Ok(Some(DetectionResult {
+            project_type,
+            confidence,
+            language_version: Some(node_version(path, &manifest)),
+            metadata,
+        }))
+    }
+
+    fn name(&self) -> &str {
+        "Node"
+    }
+
+    fn markers(&self) -> &[&str] {
+        &["package.json"]
+    }
+}
+
+/// The package manager named by `packageManager`, or the one whose lockfile is present
+fn package_manager(path: &Path, manifest: &Value) -> &'static str {
+    if let Some(declared) = manifest.get("packageManager").and_then(Value::as_str) {
+        // e.g. "pnpm@9.1.0"
+        let name = declared.split('@').next().unwrap_or_default();
+        if let Some((_, manager)) = LOCKFILES.iter().find(|(_, manager)| *manager == name) {
+            return manager;
+        }
+    }
+    LOCKFILES
+        .iter()
+        .find(|(lockfile, _)| path.join(lockfile).exists())
+        .map(|(_, manager)| *manager)
+        .unwrap_or("npm")
+}
+
+fn has_dependency(manifest: &Value, package: &str) -> bool {
+    ["dependencies", "devDependencies", "peerDependencies"]
+        .iter()
+        .any(|section| {
+            manifest
+                .get(section)
+                .and_then(|deps| deps.get(package))
+                .is_some()
+        })
+}
+
+/// The Node version from `.nvmrc` or `.node-version`, then `engines.node`, or
+/// the current LTS release
+fn node_version(path: &Path, manifest: &Value) -> String {
+    for file in [".nvmrc", ".node-version"] {
+        if let Ok(contents) = fs::read_to_string(path.join(file)) {
+            let version = contents.trim().trim_start_matches('v');
+            if !version.is_empty() {
+                return version.to_string();
+            }
+        }
+    }
+    manifest
+        .get("engines")
+        .and_then(|engines| engines.get("node"))
+        .and_then(Value::as_str)
+        .and_then(engines_version)
+        .unwrap_or_else(|| "lts/*".to_string())
+}
+
+/// The lowest major version an `engines.node` range allows, e.g. `20` for `>=20.10 <23`
+fn engines_version(range: &str) -> Option<String> {
+    let first = range.split("||").next()?.split_whitespace().next()?;
+    let version = first.trim_start_matches(['>', '=', '^', '~', 'v']);
+    let major = version.split('.').next()?;
+    (!major.is_empty() && major.chars().all(|c| c.is_ascii_digit())).then(|| major.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_detect_next_app() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{
+                "name": "web",
+                "private": true,
+                "dependencies": { "next": "14.2.0", "react": "18.3.0" },
+                "devDependencies": { "typescript": "5.4.0" }
+            }"#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("pnpm-lock.yaml"), "").unwrap();
+        fs::write(dir.path().join(".nvmrc"), "v20.11.1\n").unwrap();
+
+        let result = NodeDetector.detect(dir.path()).unwrap().unwrap();
+
+        assert_eq!(result.project_type, ProjectType::NodeApp);
+        assert_eq!(result.language_version.as_deref(), Some("20.11.1"));
+        assert_eq!(result.metadata["framework"], "next");
+        assert_eq!(result.metadata["package_manager"], "pnpm");
+        assert_eq!(result.metadata["language"], "typescript");
+    }
+
+    #[test]
+    fn test_detect_node_library() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{
+                "name": "left-pad",
+                "main": "index.js",
+                "packageManager": "yarn@4.1.0",
+                "engines": { "node": ">=18.0.0 <23" }
+            }"#,
+        )
+        .unwrap();
+
+        let result = NodeDetector.detect(dir.path()).unwrap().unwrap();
+
+        assert_eq!(result.project_type, ProjectType::NodeLibrary);
+        assert_eq!(result.language_version.as_deref(), Some("18"));
+        assert_eq!(result.metadata["package_manager"], "yarn");
+        assert_eq!(result.metadata["language"], "javascript");
+        assert!(!result.metadata.contains_key("framework"));
+    }
+
+    #[test]
+    fn test_no_package_json() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("tsconfig.json"), "{}").unwrap();
+
+        assert!(NodeDetector.detect(dir.path()).unwrap().is_none());
+    }
+}
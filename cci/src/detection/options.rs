@@ -0,0 +1,330 @@
+//! How much of a repository detection searches
+//!
+//! Searching for the projects of a monorepo can otherwise wander into
+//! vendored code and build output, so the depth is capped, whatever
+//! `.gitignore` files ignore is left out, and more directories can be
+//! excluded in cci.ron's `detection` settings or with `cci detect` flags.
+
+use crate::config::{locate_config, ConfigFormat};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+/// Directories that never hold projects of their own
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules", "vendor", "venv", "dist", "build"];
+
+/// Limits on the directories searched for projects
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DetectionOptions {
+    /// How many directories below the root projects are searched for
+    pub max_depth: usize,
+    /// Leave out directories the repository's .gitignore files ignore
+    pub respect_gitignore: bool,
+    /// Directories never searched, as globs relative to the root, e.g.
+    /// `third_party` or `**/fixtures`
+    pub exclude: Vec<String>,
+}
+
+impl Default for DetectionOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 4,
+            respect_gitignore: true,
+            exclude: Vec::new(),
+        }
+    }
+}
+
+impl DetectionOptions {
+    /// The settings in `dir`'s cci.ron (or cci.yaml/cci.toml), or the
+    /// defaults when it has none
+    pub fn load(dir: &Path) -> Self {
+        let path = locate_config(&dir.join("cci.ron"));
+        fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| ConfigFormat::from_path(&path).parse(&text).ok())
+            .map(|config| config.detection)
+            .unwrap_or_default()
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// What a directory holds, from one read of it
+pub(crate) struct Listing {
+    pub files: HashSet<String>,
+    /// The subdirectories that are searched, relative to the root, sorted
+    pub subdirs: Vec<PathBuf>,
+}
+
+/// Decides which directories of one repository are searched; shared by the
+/// threads searching it
+pub(crate) struct DirFilter {
+    root: PathBuf,
+    options: DetectionOptions,
+    /// The rules of each directory's .gitignore, by directory, read as needed
+    gitignores: Mutex<HashMap<PathBuf, Vec<IgnoreRule>>>,
+}
+
+impl DirFilter {
+    pub fn new(root: &Path, options: &DetectionOptions) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            options: options.clone(),
+            gitignores: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The files of `dir`, relative to the root, and its subdirectories
+    /// that are searched
+    pub fn list(&self, dir: &Path) -> Listing {
+        let mut listing = Listing {
+            files: HashSet::new(),
+            subdirs: Vec::new(),
+        };
+        let Ok(entries) = fs::read_dir(self.root.join(dir)) else {
+            return listing;
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            if entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+                let subdir = dir.join(entry.file_name());
+                if self.allows(&subdir) {
+                    listing.subdirs.push(subdir);
+                }
+            } else {
+                listing
+                    .files
+                    .insert(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+        listing.subdirs.sort();
+        listing
+    }
+
+    /// The subdirectories of `dir`, relative to the root, that are searched,
+    /// sorted
+    pub fn subdirs(&self, dir: &Path) -> Vec<PathBuf> {
+        self.list(dir).subdirs
+    }
+
+    /// Whether `dir`, relative to the root, is searched; its parents are
+    /// taken to be
+    fn allows(&self, dir: &Path) -> bool {
+        let name = dir.file_name().unwrap_or_default().to_string_lossy();
+        if name.starts_with('.')
+            || SKIPPED_DIRS.contains(&name.as_ref())
+            || components(dir).len() > self.options.max_depth
+        {
+            return false;
+        }
+        !self.excluded(dir) && (!self.options.respect_gitignore || !self.ignored(dir))
+    }
+
+    /// Whether one of the `exclude` globs matches `dir`
+    pub fn excluded(&self, dir: &Path) -> bool {
+        let dir = components(dir);
+        self.options.exclude.iter().any(|pattern| {
+            let pattern = components(Path::new(pattern.trim().trim_end_matches('/')));
+            // `third_party/**` leaves out the directory as well as what's in it
+            let within = match pattern.split_last() {
+                Some((&"**", parent)) => glob_match(parent, &dir),
+                _ => false,
+            };
+            within || glob_match(&pattern, &dir)
+        })
+    }
+
+    /// Whether the .gitignore files of `dir`'s parents ignore it, the last
+    /// rule matching deciding as git does
+    fn ignored(&self, dir: &Path) -> bool {
+        let dir = components(dir);
+        let mut ignored = false;
+        for depth in 0..dir.len() {
+            let base: PathBuf = dir[..depth].iter().collect();
+            let mut gitignores = self.gitignores.lock().unwrap_or_else(|e| e.into_inner());
+            let rules = gitignores
+                .entry(base.clone())
+                .or_insert_with(|| read_gitignore(&self.root.join(&base)));
+            for rule in rules.iter() {
+                if rule.matches(&dir[depth..]) {
+                    ignored = !rule.negated;
+                }
+            }
+        }
+        ignored
+    }
+}
+
+/// One line of a .gitignore file
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct IgnoreRule {
+    /// Patterns starting with `!` take a path back out
+    negated: bool,
+    /// Patterns with a slash before their end are relative to the
+    /// .gitignore's directory; others match a name at any depth
+    anchored: bool,
+    pattern: Vec<String>,
+}
+
+impl IgnoreRule {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let line = line.trim_end_matches('/');
+        let anchored = line.contains('/');
+        let pattern: Vec<String> = line
+            .trim_start_matches('/')
+            .split('/')
+            .filter(|component| !component.is_empty())
+            .map(str::to_string)
+            .collect();
+        (!pattern.is_empty()).then_some(Self {
+            negated,
+            anchored,
+            pattern,
+        })
+    }
+
+    /// Whether the rule matches `path`, relative to the .gitignore's directory
+    fn matches(&self, path: &[&str]) -> bool {
+        let pattern: Vec<&str> = self.pattern.iter().map(String::as_str).collect();
+        if self.anchored {
+            glob_match(&pattern, path)
+        } else {
+            path.last()
+                .is_some_and(|name| wildcard_match(pattern[0], name))
+        }
+    }
+}
+
+fn read_gitignore(dir: &Path) -> Vec<IgnoreRule> {
+    fs::read_to_string(dir.join(".gitignore"))
+        .map(|contents| contents.lines().filter_map(IgnoreRule::parse).collect())
+        .unwrap_or_default()
+}
+
+fn components(path: &Path) -> Vec<&str> {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => name.to_str(),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether the path components match the pattern's, where a `**` component
+/// stands for any number of directories
+fn glob_match(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| glob_match(rest, &path[skip..])),
+        Some((first, rest)) => path
+            .split_first()
+            .is_some_and(|(name, path)| wildcard_match(first, name) && glob_match(rest, path)),
+    }
+}
+
+/// Whether `name` matches `pattern`, where `*` stands for any run of characters
+pub(crate) fn wildcard_match(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, rest)) => {
+            let Some(name) = name.strip_prefix(prefix) else {
+                return false;
+            };
+            (0..=name.len())
+                .filter(|&i| name.is_char_boundary(i))
+                .any(|i| wildcard_match(rest, &name[i..]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_dir_filter() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        for path in [
+            "a/b/c/d/e",
+            "third_party/lib",
+            "generated",
+            "keep",
+            "x/fixtures",
+        ] {
+            fs::create_dir_all(root.join(path)).unwrap();
+        }
+        fs::write(root.join(".gitignore"), "/generated\n*.log\n").unwrap();
+        fs::write(root.join("x/.gitignore"), "fixtures/\n").unwrap();
+
+        let options = DetectionOptions {
+            max_depth: 3,
+            exclude: vec!["third_party/**".to_string()],
+            ..DetectionOptions::default()
+        };
+        let filter = DirFilter::new(root, &options);
+        assert_eq!(
+            filter.subdirs(Path::new("")),
+            vec![
+                PathBuf::from("a"),
+                PathBuf::from("keep"),
+                PathBuf::from("x")
+            ]
+        );
+        assert!(filter.subdirs(Path::new("x")).is_empty());
+        assert_eq!(
+            filter.subdirs(Path::new("a/b")),
+            vec![PathBuf::from("a/b/c")]
+        );
+        assert!(filter.subdirs(Path::new("a/b/c")).is_empty());
+
+        let options = DetectionOptions {
+            respect_gitignore: false,
+            ..DetectionOptions::default()
+        };
+        let filter = DirFilter::new(root, &options);
+        assert!(filter
+            .subdirs(Path::new(""))
+            .contains(&PathBuf::from("generated")));
+    }
+
+    #[test]
+    fn test_load_from_cci_ron() {
+        let dir = tempdir().unwrap();
+        assert!(DetectionOptions::load(dir.path()).is_default());
+
+        fs::write(
+            dir.path().join("cci.ron"),
+            "(version: 3, presets: [], detection: (max_depth: 2, exclude: [\"fixtures\"]))",
+        )
+        .unwrap();
+        let options = DetectionOptions::load(dir.path());
+        assert_eq!(options.max_depth, 2);
+        assert!(options.respect_gitignore);
+        assert_eq!(options.exclude, vec!["fixtures".to_string()]);
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match(&["**", "fixtures"], &["x", "y", "fixtures"]));
+        assert!(glob_match(&["**", "fixtures"], &["fixtures"]));
+        assert!(!glob_match(&["src", "*"], &["src"]));
+        assert!(wildcard_match("cci-*", "cci-macros"));
+        assert!(!wildcard_match("cci-*", "cci"));
+        assert!(wildcard_match("*-service", "api-service"));
+    }
+}
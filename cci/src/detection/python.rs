@@ -1,8 +1,36 @@
-use super::{DetectionResult, ProjectDetector, ProjectType};
+use super::{
+    insert_known_dependencies, DetectionResult, KnownDependencies, ProjectDetector, ProjectType,
+};
 use crate::error::Result;
 use std::collections::HashMap;
+use std::fs;
 use std::path::Path;
 
+/// Distributions that say what kind of project this is
+const KNOWN_DEPENDENCIES: KnownDependencies = &[
+    (
+        "framework",
+        &[
+            ("django", "django"),
+            ("flask", "flask"),
+            ("fastapi", "fastapi"),
+        ],
+    ),
+    ("cli", &[("click", "click"), ("typer", "typer")]),
+    (
+        "database",
+        &[
+            ("psycopg", "postgres"),
+            ("psycopg2", "postgres"),
+            ("psycopg2-binary", "postgres"),
+            ("asyncpg", "postgres"),
+            ("mysqlclient", "mysql"),
+            ("pymysql", "mysql"),
+            ("redis", "redis"),
+        ],
+    ),
+];
+
 pub struct PythonDetector;
 
 impl ProjectDetector for PythonDetector {
@@ -23,8 +51,29 @@ impl ProjectDetector for PythonDetector {
             metadata.insert("config".to_string(), "setup.py".to_string());
         }
 
-        // Simple heuristic: apps have main.py or __main__.py
-        let is_app = path.join("main.py").exists() || path.join("__main__.py").exists();
+        let pyproject = fs::read_to_string(path.join("pyproject.toml"))
+            .ok()
+            .and_then(|contents| contents.parse::<toml::Table>().ok());
+        let build_backend = pyproject
+            .as_ref()
+            .and_then(|pyproject| pyproject.get("build-system"))
+            .and_then(|build| build.get("build-backend"))
+            .and_then(|backend| backend.as_str())
+            .map(|backend| backend.split('.').next().unwrap_or(backend).to_string());
+        if let Some(backend) = &build_backend {
+            metadata.insert("build_backend".to_string(), backend.clone());
+        }
+
+        let dependencies = dependencies(path);
+        insert_known_dependencies(&mut metadata, KNOWN_DEPENDENCIES, |name| {
+            dependencies.iter().any(|dependency| dependency == name)
+        });
+
+        // Simple heuristic: apps have main.py or __main__.py, or are built
+        // on a web framework
+        let is_app = path.join("main.py").exists()
+            || path.join("__main__.py").exists()
+            || metadata.contains_key("framework");
 
         let project_type = if is_app {
             ProjectType::PythonApp
@@ -32,9 +81,26 @@ impl ProjectDetector for PythonDetector {
             ProjectType::PythonLibrary
         };
 
+        // A pyproject.toml that only configures tools, or a requirements.txt
+        // on its own, is often there for scripts beside another project; one
+        // built by maturin packages the crate next to it
+        let packaged = pyproject.as_ref().is_some_and(|pyproject| {
+            pyproject.contains_key("project")
+                || pyproject
+                    .get("tool")
+                    .is_some_and(|tool| tool.get("poetry").is_some())
+        });
+        let confidence = match build_backend.as_deref() {
+            Some("maturin") => 80,
+            _ if packaged => 90,
+            _ if has_setup => 85,
+            _ => 50,
+        };
+
         Ok(Some(DetectionResult {
             project_type,
-            language_version: Some("3.11".to_string()),
+            confidence,
+            language_version: Some(python_version(path).unwrap_or_else(|| "3.11".to_string())),
             metadata,
         }))
     }
@@ -42,4 +108,125 @@ impl ProjectDetector for PythonDetector {
     fn name(&self) -> &str {
         "Python"
     }
+
+    fn markers(&self) -> &[&str] {
+        &["pyproject.toml", "setup.py", "requirements.txt"]
+    }
+}
+
+/// The names of the distributions the project depends on, lowercased, from
+/// pyproject.toml (PEP 621 or Poetry) and requirements.txt
+fn dependencies(path: &Path) -> Vec<String> {
+    let mut requirements: Vec<String> = Vec::new();
+    if let Some(pyproject) = fs::read_to_string(path.join("pyproject.toml"))
+        .ok()
+        .and_then(|contents| contents.parse::<toml::Table>().ok())
+    {
+        if let Some(list) = pyproject
+            .get("project")
+            .and_then(|project| project.get("dependencies"))
+            .and_then(|dependencies| dependencies.as_array())
+        {
+            requirements.extend(list.iter().filter_map(|r| r.as_str()).map(str::to_string));
+        }
+        if let Some(table) = pyproject
+            .get("tool")
+            .and_then(|tool| tool.get("poetry"))
+            .and_then(|poetry| poetry.get("dependencies"))
+            .and_then(|dependencies| dependencies.as_table())
+        {
+            requirements.extend(table.keys().cloned());
+        }
+    }
+    if let Ok(contents) = fs::read_to_string(path.join("requirements.txt")) {
+        requirements.extend(
+            contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with(['#', '-']))
+                .map(str::to_string),
+        );
+    }
+    requirements
+        .iter()
+        .filter_map(|requirement| {
+            // e.g. "Django>=4.2", "uvicorn[standard]", "redis ; python_version > '3.8'"
+            let name = requirement
+                .split(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_' || c == '.'))
+                .next()?;
+            (!name.is_empty()).then(|| name.to_lowercase().replace('_', "-"))
+        })
+        .collect()
+}
+
+/// The version pinned by `.python-version`, or the lowest that
+/// pyproject.toml's `requires-python` allows
+fn python_version(path: &Path) -> Option<String> {
+    if let Ok(contents) = fs::read_to_string(path.join(".python-version")) {
+        // pyenv allows several versions, one per line; the first is preferred
+        if let Some(version) = contents.lines().map(str::trim).find(|l| !l.is_empty()) {
+            return Some(version.to_string());
+        }
+    }
+    let pyproject = fs::read_to_string(path.join("pyproject.toml")).ok()?;
+    let pyproject = pyproject.parse::<toml::Table>().ok()?;
+    let requires = pyproject.get("project")?.get("requires-python")?.as_str()?;
+    requires_python_version(requires)
+}
+
+/// The lower bound of a version specifier, e.g. `3.10` for `>=3.10,<4`
+fn requires_python_version(specifier: &str) -> Option<String> {
+    specifier.split(',').map(str::trim).find_map(|clause| {
+        let version = ["~=", ">=", "==", "^"]
+            .iter()
+            .find_map(|operator| clause.strip_prefix(operator))?
+            .trim()
+            .trim_end_matches(".*");
+        (!version.is_empty()).then(|| version.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_version_from_python_version_or_pyproject() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("pyproject.toml"),
+            "[project]\nname = \"app\"\nrequires-python = \">=3.10, <4\"\n",
+        )
+        .unwrap();
+
+        let detect = || PythonDetector.detect(dir.path()).unwrap().unwrap();
+        assert_eq!(detect().language_version.as_deref(), Some("3.10"));
+
+        fs::write(dir.path().join(".python-version"), "3.12.2\n3.11\n").unwrap();
+        assert_eq!(detect().language_version.as_deref(), Some("3.12.2"));
+    }
+
+    #[test]
+    fn test_framework_metadata() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("requirements.txt"),
+            "# web\nDjango>=4.2\npsycopg2-binary==2.9.9\n-r dev.txt\n",
+        )
+        .unwrap();
+
+        let result = PythonDetector.detect(dir.path()).unwrap().unwrap();
+
+        assert_eq!(result.project_type, ProjectType::PythonApp);
+        assert_eq!(result.metadata["framework"], "django");
+        assert_eq!(result.metadata["database"], "postgres");
+    }
+
+    #[test]
+    fn test_requires_python_version() {
+        assert_eq!(requires_python_version("~=3.9").as_deref(), Some("3.9"));
+        assert_eq!(requires_python_version("==3.11.*").as_deref(), Some("3.11"));
+        assert_eq!(requires_python_version("<3.13"), None);
+    }
 }
@@ -1,10 +1,15 @@
 use super::docker::DockerDetector;
 use super::go::GoDetector;
+use super::node::NodeDetector;
 use super::python::PythonDetector;
 use super::rust::RustDetector;
 use super::{DetectionResult, ProjectDetector};
 use crate::error::{detection_failed_error, Result};
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::fs;
 use std::path::Path;
+use std::thread;
 
 /// Registry for all project detectors
 pub struct DetectorRegistry {
@@ -23,6 +28,7 @@ impl DetectorRegistry {
         registry.register(Box::new(RustDetector));
         registry.register(Box::new(PythonDetector));
         registry.register(Box::new(GoDetector));
+        registry.register(Box::new(NodeDetector));
         registry.register(Box::new(DockerDetector));
 
         registry
@@ -33,16 +39,49 @@ impl DetectorRegistry {
         self.detectors.push(detector);
     }
 
-    /// Detect project type in the given path
-    /// Returns the first detection result found
-    pub fn detect(&self, path: &Path) -> Result<DetectionResult> {
+    /// Detect every project type in the given path, ranked by confidence, so
+    /// a Rust crate with a Dockerfile is a Rust crate before it's a Docker
+    /// image
+    ///
+    /// Equally sure matches keep registration order, most specific first:
+    /// a Cargo.toml without sources beside a pyproject.toml built by maturin
+    /// is a Rust crate with Python bindings, not the other way around.
+    ///
+    /// Fails when no detector recognizes the path.
+    pub fn detect(&self, path: &Path) -> Result<Vec<DetectionResult>> {
+        let results = self.detect_all(path)?;
+        if results.is_empty() {
+            return Err(detection_failed_error());
+        }
+        Ok(results)
+    }
+
+    /// Like [`Self::detect`], but an unrecognized path is an empty list
+    pub fn detect_all(&self, path: &Path) -> Result<Vec<DetectionResult>> {
+        self.detect_listed(path, &file_names(path))
+    }
+
+    /// Like [`Self::detect_all`], for a directory whose file names are known
+    /// already; only the detectors with a marker among them are asked
+    pub(crate) fn detect_listed(
+        &self,
+        path: &Path,
+        files: &HashSet<String>,
+    ) -> Result<Vec<DetectionResult>> {
+        let mut results = Vec::new();
         for detector in &self.detectors {
+            let markers = detector.markers();
+            if !markers.is_empty() && !markers.iter().any(|marker| files.contains(*marker)) {
+                continue;
+            }
             if let Some(result) = detector.detect(path)? {
-                return Ok(result);
+                results.push(result);
             }
         }
+        // Stable, so ties stay in registration order
+        results.sort_by_key(|result| Reverse(result.confidence));
 
-        Err(detection_failed_error())
+        Ok(results)
     }
 
     /// Get all registered detector names
@@ -51,6 +90,40 @@ impl DetectorRegistry {
     }
 }
 
+/// The names of the files in `dir`
+pub(crate) fn file_names(dir: &Path) -> HashSet<String> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_type().is_ok_and(|kind| !kind.is_dir()))
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// `f` applied to each item, spread over the available cores, in order
+pub(crate) fn par_map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let threads = thread::available_parallelism()
+        .map_or(1, |threads| threads.get())
+        .min(items.len());
+    if threads <= 1 {
+        return items.iter().map(f).collect();
+    }
+    let f = &f;
+    thread::scope(|scope| {
+        let workers: Vec<_> = items
+            .chunks(items.len().div_ceil(threads))
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<R>>()))
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("detection thread panicked"))
+            .collect()
+    })
+}
+
 impl Default for DetectorRegistry {
     fn default() -> Self {
         Self::new()
@@ -60,7 +133,9 @@ impl Default for DetectorRegistry {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs;
+    use crate::detection::ProjectType;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
     use tempfile::tempdir;
 
     #[test]
@@ -85,6 +160,106 @@ edition = "2021"
         let _result = registry.detect(dir.path()).unwrap();
     }
 
+    #[test]
+    fn test_registry_ranks_every_project_type() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("Dockerfile"), "FROM rust:1").unwrap();
+
+        let results = DetectorRegistry::new().detect(dir.path()).unwrap();
+        let types: Vec<_> = results.iter().map(|r| r.project_type.clone()).collect();
+
+        assert_eq!(types.len(), 2);
+        assert_eq!(types[1], ProjectType::DockerImage);
+        assert!(matches!(
+            types[0],
+            ProjectType::RustBinary | ProjectType::RustLibrary
+        ));
+    }
+
+    #[test]
+    fn test_registry_breaks_ties_in_registration_order() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"bindings\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("pyproject.toml"),
+            "[project]\nname = \"bindings\"\n\n[build-system]\nbuild-backend = \"maturin\"\n",
+        )
+        .unwrap();
+
+        let results = DetectorRegistry::new().detect(dir.path()).unwrap();
+        assert_eq!(results[0].confidence, results[1].confidence);
+        assert_eq!(results[0].project_type, ProjectType::RustBinary);
+        assert_eq!(results[1].project_type, ProjectType::PythonLibrary);
+        assert_eq!(results[1].metadata["build_backend"], "maturin");
+
+        // A Python package with a real Cargo crate's sources goes second,
+        // and one without a build backend of its own outranks a stub crate
+        fs::create_dir(dir.path().join("src")).unwrap();
+        let results = DetectorRegistry::new().detect(dir.path()).unwrap();
+        assert!(results[0].confidence > results[1].confidence);
+        fs::remove_dir(dir.path().join("src")).unwrap();
+        fs::write(
+            dir.path().join("pyproject.toml"),
+            "[project]\nname = \"tool\"\n",
+        )
+        .unwrap();
+        let results = DetectorRegistry::new().detect(dir.path()).unwrap();
+        assert_eq!(results[0].project_type, ProjectType::PythonLibrary);
+        assert_eq!(results[1].project_type, ProjectType::RustBinary);
+    }
+
+    /// Counts how often it's asked, and never matches
+    struct CountingDetector(Arc<AtomicUsize>);
+
+    impl ProjectDetector for CountingDetector {
+        fn detect(&self, _path: &Path) -> Result<Option<DetectionResult>> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(None)
+        }
+
+        fn name(&self) -> &str {
+            "Counting"
+        }
+
+        fn markers(&self) -> &[&str] {
+            &["counted.txt"]
+        }
+    }
+
+    #[test]
+    fn test_detectors_are_only_asked_with_a_marker() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("README.md"), "").unwrap();
+        let asked = Arc::new(AtomicUsize::new(0));
+        let mut registry = DetectorRegistry::new();
+        registry.register(Box::new(CountingDetector(asked.clone())));
+
+        assert!(registry.detect_all(dir.path()).unwrap().is_empty());
+        assert_eq!(asked.load(Ordering::SeqCst), 0);
+        fs::write(dir.path().join("counted.txt"), "").unwrap();
+        registry.detect_all(dir.path()).unwrap();
+        assert_eq!(asked.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_par_map_keeps_order() {
+        let items: Vec<usize> = (0..100).collect();
+        assert_eq!(
+            par_map(&items, |n| n * 2),
+            (0..100).map(|n| n * 2).collect::<Vec<_>>()
+        );
+        assert!(par_map(&Vec::<usize>::new(), |n| *n).is_empty());
+    }
+
     #[test]
     fn test_registry_no_match() {
         let dir = tempdir().unwrap();
@@ -93,4 +268,26 @@ edition = "2021"
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_registry_detect_all() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("go.mod"),
+            "module example.com/app\n\ngo 1.22\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("Dockerfile"), "FROM golang:1.22").unwrap();
+
+        let registry = DetectorRegistry::new();
+        let results = registry.detect_all(dir.path()).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].project_type, ProjectType::GoLibrary);
+        assert_eq!(results[1].project_type, ProjectType::DockerImage);
+        assert!(registry
+            .detect_all(tempdir().unwrap().path())
+            .unwrap()
+            .is_empty());
+    }
 }
@@ -1,10 +1,38 @@
-use super::{DetectionResult, ProjectDetector, ProjectType};
+use super::{
+    insert_known_dependencies, DetectionResult, KnownDependencies, ProjectDetector, ProjectType,
+};
 use crate::error::{cargo_toml_error, Result};
 use cargo_toml::Manifest;
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
+/// Crates that say what kind of project this is
+const KNOWN_DEPENDENCIES: KnownDependencies = &[
+    (
+        "framework",
+        &[
+            ("actix-web", "actix"),
+            ("axum", "axum"),
+            ("rocket", "rocket"),
+            ("warp", "warp"),
+            ("poem", "poem"),
+        ],
+    ),
+    ("cli", &[("clap", "clap"), ("argh", "argh")]),
+    (
+        "database",
+        &[
+            ("tokio-postgres", "postgres"),
+            ("postgres", "postgres"),
+            ("deadpool-postgres", "postgres"),
+            ("mysql", "mysql"),
+            ("mysql_async", "mysql"),
+            ("redis", "redis"),
+        ],
+    ),
+];
+
 pub struct RustDetector;
 
 impl ProjectDetector for RustDetector {
@@ -21,6 +49,13 @@ impl ProjectDetector for RustDetector {
             Manifest::from_str(&contents).map_err(|e| cargo_toml_error(e.to_string()))?;
 
         let mut metadata = HashMap::new();
+        insert_known_dependencies(&mut metadata, KNOWN_DEPENDENCIES, |name| {
+            manifest.dependencies.contains_key(name)
+                || manifest
+                    .workspace
+                    .as_ref()
+                    .is_some_and(|workspace| workspace.dependencies.contains_key(name))
+        });
 
         // Check if it's a workspace
         if let Some(workspace) = &manifest.workspace {
@@ -32,7 +67,8 @@ impl ProjectDetector for RustDetector {
 
             return Ok(Some(DetectionResult {
                 project_type: ProjectType::RustWorkspace,
-                language_version: extract_rust_version(&manifest),
+                confidence: 95,
+                language_version: extract_rust_version(path),
                 metadata,
             }));
         }
@@ -56,9 +92,13 @@ impl ProjectDetector for RustDetector {
             // For simplicity, skip edition extraction due to Inheritable complexity
         }
 
+        // A manifest without sources may only be there for tooling
+        let confidence = if path.join("src").is_dir() { 95 } else { 80 };
+
         Ok(Some(DetectionResult {
             project_type,
-            language_version: extract_rust_version(&manifest),
+            confidence,
+            language_version: extract_rust_version(path),
             metadata,
         }))
     }
@@ -66,6 +106,10 @@ impl ProjectDetector for RustDetector {
     fn name(&self) -> &str {
         "Rust"
     }
+
+    fn markers(&self) -> &[&str] {
+        &["Cargo.toml"]
+    }
 }
 
 fn has_library(manifest: &Manifest) -> bool {
@@ -81,9 +125,32 @@ fn has_binary(manifest: &Manifest) -> bool {
             .is_some()
 }
 
-fn extract_rust_version(_manifest: &Manifest) -> Option<String> {
-    // Default to stable - rust_version extraction is complex with Inheritable
-    Some("stable".to_string())
+/// The toolchain channel pinned by `rust-toolchain.toml`, or the older
+/// `rust-toolchain`, falling back to stable
+fn extract_rust_version(path: &Path) -> Option<String> {
+    let channel = fs::read_to_string(path.join("rust-toolchain.toml"))
+        .ok()
+        .and_then(|contents| toolchain_channel(&contents))
+        .or_else(|| {
+            let contents = fs::read_to_string(path.join("rust-toolchain")).ok()?;
+            let contents = contents.trim();
+            // `rust-toolchain` is either TOML or the bare channel name
+            if contents.starts_with('[') {
+                toolchain_channel(contents)
+            } else {
+                (!contents.is_empty()).then(|| contents.to_string())
+            }
+        });
+    Some(channel.unwrap_or_else(|| "stable".to_string()))
+}
+
+fn toolchain_channel(contents: &str) -> Option<String> {
+    let toolchain = contents.parse::<toml::Table>().ok()?;
+    toolchain
+        .get("toolchain")?
+        .get("channel")?
+        .as_str()
+        .map(str::to_string)
 }
 
 #[cfg(test)]
@@ -164,6 +231,57 @@ members = ["crate1", "crate2"]
         assert_eq!(result.metadata.get("member_count").unwrap(), "2");
     }
 
+    #[test]
+    fn test_version_from_toolchain_file() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"pinned\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+
+        let detect = || RustDetector.detect(dir.path()).unwrap().unwrap();
+        assert_eq!(detect().language_version.as_deref(), Some("stable"));
+
+        fs::write(dir.path().join("rust-toolchain"), "nightly-2024-05-01\n").unwrap();
+        assert_eq!(
+            detect().language_version.as_deref(),
+            Some("nightly-2024-05-01")
+        );
+
+        fs::write(
+            dir.path().join("rust-toolchain.toml"),
+            "[toolchain]\nchannel = \"1.78.0\"\ncomponents = [\"clippy\"]\n",
+        )
+        .unwrap();
+        assert_eq!(detect().language_version.as_deref(), Some("1.78.0"));
+    }
+
+    #[test]
+    fn test_framework_metadata() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            r#"
+[package]
+name = "api"
+version = "0.1.0"
+
+[dependencies]
+axum = "0.7"
+clap = { version = "4", features = ["derive"] }
+tokio-postgres = "0.7"
+        "#,
+        )
+        .unwrap();
+
+        let result = RustDetector.detect(dir.path()).unwrap().unwrap();
+
+        assert_eq!(result.metadata["framework"], "axum");
+        assert_eq!(result.metadata["cli"], "clap");
+        assert_eq!(result.metadata["database"], "postgres");
+    }
+
     #[test]
     fn test_no_cargo_toml() {
         let dir = tempdir().unwrap();
@@ -0,0 +1,492 @@
+//! Finding the projects in a repository that holds more than one
+//!
+//! Workspaces that list their members are taken at their word: Cargo
+//! workspaces, pnpm workspaces, npm and yarn `workspaces` in package.json,
+//! and go.work files. Repositories that don't list them, e.g. a `services/`
+//! directory of unrelated projects, are searched instead, within the limits
+//! of [`DetectionOptions`].
+
+use super::options::{wildcard_match, DirFilter};
+use super::registry::par_map;
+use super::{DetectionOptions, DetectionResult, DetectorRegistry};
+use cargo_toml::Manifest;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where a project was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemberSource {
+    /// The repository root itself
+    Root,
+    /// A member of the root's Cargo workspace
+    Cargo,
+    /// A package listed in pnpm-workspace.yaml
+    Pnpm,
+    /// A package listed in the root package.json's `workspaces`
+    Npm,
+    /// A module in go.work's `use` directives
+    GoWork,
+    /// A directory found by searching, with no workspace listing it
+    Nested,
+}
+
+impl MemberSource {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MemberSource::Root => "root",
+            MemberSource::Cargo => "Cargo workspace",
+            MemberSource::Pnpm => "pnpm workspace",
+            MemberSource::Npm => "npm workspace",
+            MemberSource::GoWork => "go.work",
+            MemberSource::Nested => "nested",
+        }
+    }
+}
+
+/// A project found in the repository
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkspaceMember {
+    /// Relative to the repository root; `.` for the root itself
+    pub dir: PathBuf,
+    pub detection: DetectionResult,
+    pub source: MemberSource,
+}
+
+/// Finds every project root in a repository
+pub struct WorkspaceDetector {
+    registry: DetectorRegistry,
+    options: DetectionOptions,
+    search_undeclared: bool,
+}
+
+impl WorkspaceDetector {
+    pub fn new() -> Self {
+        Self::with_options(DetectionOptions::default())
+    }
+
+    /// Search no more of the repository than `options` allow
+    pub fn with_options(options: DetectionOptions) -> Self {
+        Self {
+            registry: DetectorRegistry::new(),
+            options,
+            search_undeclared: false,
+        }
+    }
+
+    /// Also search the directories a workspace manifest doesn't list, e.g. a
+    /// Go service next to the members of a Cargo workspace
+    pub fn search_undeclared(mut self) -> Self {
+        self.search_undeclared = true;
+        self
+    }
+
+    /// Every project under `root`, sorted by path, the root first when it's
+    /// a project itself
+    ///
+    /// Members are taken from the root's workspace manifests when it has
+    /// any; otherwise, or with [`Self::search_undeclared`], subdirectories
+    /// are searched, without descending into the projects found, so a
+    /// project's own subdirectories aren't mistaken for more projects.
+    /// Members matching an `exclude` glob are left out either way.
+    pub fn detect(&self, root: &Path) -> Vec<WorkspaceMember> {
+        let filter = DirFilter::new(root, &self.options);
+        let mut members = Vec::new();
+        if let Some(detection) = self.primary(root) {
+            members.push(WorkspaceMember {
+                dir: PathBuf::from("."),
+                detection,
+                source: MemberSource::Root,
+            });
+        }
+
+        let declared = [
+            (MemberSource::Cargo, cargo_members(root)),
+            (MemberSource::Pnpm, pnpm_members(root)),
+            (MemberSource::Npm, npm_members(root)),
+            (MemberSource::GoWork, go_work_members(root)),
+        ];
+        let has_declared = declared.iter().any(|(_, patterns)| patterns.is_some());
+        let mut candidates: Vec<(PathBuf, MemberSource)> = Vec::new();
+        // Members and the directories workspaces exclude, which a search skips
+        let mut declared_dirs: Vec<PathBuf> = Vec::new();
+        for (source, patterns) in declared {
+            let Some((include, exclude)) = patterns else {
+                continue;
+            };
+            let excluded: Vec<PathBuf> = exclude
+                .iter()
+                .flat_map(|pattern| expand(root, &filter, pattern))
+                .collect();
+            declared_dirs.extend(excluded.iter().cloned());
+            for dir in include
+                .iter()
+                .flat_map(|pattern| expand(root, &filter, pattern))
+            {
+                if !excluded.contains(&dir)
+                    && !filter.excluded(&dir)
+                    && !candidates.iter().any(|(candidate, _)| *candidate == dir)
+                {
+                    candidates.push((dir, source));
+                }
+            }
+        }
+        declared_dirs.extend(candidates.iter().map(|(dir, _)| dir.clone()));
+        let detections = par_map(&candidates, |(dir, _)| self.primary(&root.join(dir)));
+        for ((dir, source), detection) in candidates.into_iter().zip(detections) {
+            if let Some(detection) = detection {
+                members.push(WorkspaceMember {
+                    dir,
+                    detection,
+                    source,
+                });
+            }
+        }
+
+        if !has_declared || self.search_undeclared {
+            self.search(root, &filter, &declared_dirs, &mut members);
+        }
+        members.sort_by(|a, b| a.dir.cmp(&b.dir));
+        members
+    }
+
+    /// The most specific project type detected in `dir`
+    fn primary(&self, dir: &Path) -> Option<DetectionResult> {
+        self.registry
+            .detect(dir)
+            .ok()
+            .map(|mut detections| detections.remove(0))
+    }
+
+    /// Search one level of directories at a time, each read once and the
+    /// level's spread over the available cores, going no further into the
+    /// projects found or the `declared` directories
+    fn search(
+        &self,
+        root: &Path,
+        filter: &DirFilter,
+        declared: &[PathBuf],
+        found: &mut Vec<WorkspaceMember>,
+    ) {
+        let mut level = filter.subdirs(Path::new(""));
+        level.retain(|dir| !declared.contains(dir));
+        while !level.is_empty() {
+            let scanned = par_map(&level, |dir| {
+                let listing = filter.list(dir);
+                let detection = self
+                    .registry
+                    .detect_listed(&root.join(dir), &listing.files)
+                    .ok()
+                    .filter(|detections| !detections.is_empty())
+                    .map(|mut detections| detections.remove(0));
+                (detection, listing.subdirs)
+            });
+            let mut next = Vec::new();
+            for (dir, (detection, subdirs)) in level.into_iter().zip(scanned) {
+                match detection {
+                    Some(detection) => found.push(WorkspaceMember {
+                        dir,
+                        detection,
+                        source: MemberSource::Nested,
+                    }),
+                    None => next.extend(subdirs.into_iter().filter(|dir| !declared.contains(dir))),
+                }
+            }
+            level = next;
+        }
+    }
+}
+
+impl Default for WorkspaceDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Member patterns and exclusions, for a workspace declared in a manifest
+type Patterns = Option<(Vec<String>, Vec<String>)>;
+
+fn cargo_members(root: &Path) -> Patterns {
+    let contents = fs::read_to_string(root.join("Cargo.toml")).ok()?;
+    let workspace = Manifest::from_str(&contents).ok()?.workspace?;
+    Some((workspace.members, workspace.exclude))
+}
+
+fn pnpm_members(root: &Path) -> Patterns {
+    let contents = fs::read_to_string(root.join("pnpm-workspace.yaml")).ok()?;
+    let manifest: serde_yaml::Value = serde_yaml::from_str(&contents).ok()?;
+    let packages: Vec<String> = manifest
+        .get("packages")?
+        .as_sequence()?
+        .iter()
+        .filter_map(|pattern| pattern.as_str().map(str::to_string))
+        .collect();
+    Some(split_negated(packages))
+}
+
+fn npm_members(root: &Path) -> Patterns {
+    let contents = fs::read_to_string(root.join("package.json")).ok()?;
+    let manifest: Value = serde_json::from_str(&contents).ok()?;
+    // Either a list, or yarn's `{ "packages": [...] }`
+    let workspaces = manifest.get("workspaces")?;
+    let packages = workspaces
+        .as_array()
+        .or_else(|| workspaces.get("packages")?.as_array())?;
+    Some(split_negated(
+        packages
+            .iter()
+            .filter_map(|pattern| pattern.as_str().map(str::to_string))
+            .collect(),
+    ))
+}
+
+fn go_work_members(root: &Path) -> Patterns {
+    let contents = fs::read_to_string(root.join("go.work")).ok()?;
+    let mut modules = Vec::new();
+    let mut in_block = false;
+    for line in contents.lines() {
+        let line = line.split("//").next().unwrap_or_default().trim();
+        if in_block {
+            if line == ")" {
+                in_block = false;
+            } else if !line.is_empty() {
+                modules.push(line.to_string());
+            }
+        } else if let Some(rest) = line.strip_prefix("use") {
+            match rest.trim() {
+                "(" => in_block = true,
+                module if !module.is_empty() => modules.push(module.to_string()),
+                _ => {}
+            }
+        }
+    }
+    Some((modules, Vec::new()))
+}
+
+/// pnpm and npm exclude packages with `!` patterns among the others
+fn split_negated(patterns: Vec<String>) -> (Vec<String>, Vec<String>) {
+    let (exclude, include): (Vec<String>, Vec<String>) = patterns
+        .into_iter()
+        .partition(|pattern| pattern.starts_with('!'));
+    let exclude = exclude
+        .into_iter()
+        .map(|pattern| pattern[1..].to_string())
+        .collect();
+    (include, exclude)
+}
+
+/// The directories under `root` matching `pattern`, relative to `root`
+///
+/// Patterns are paths whose components may hold `*` wildcards, or be `**`
+/// for any number of directories, e.g. `crates/*` or `packages/**`.
+fn expand(root: &Path, filter: &DirFilter, pattern: &str) -> Vec<PathBuf> {
+    let pattern = pattern
+        .trim()
+        .trim_start_matches("./")
+        .trim_end_matches('/');
+    let components: Vec<&str> = pattern
+        .split('/')
+        .filter(|component| !component.is_empty() && *component != ".")
+        .collect();
+    let mut found = Vec::new();
+    expand_from(root, filter, PathBuf::new(), &components, &mut found);
+    found.sort();
+    found.dedup();
+    found
+}
+
+fn expand_from(
+    root: &Path,
+    filter: &DirFilter,
+    dir: PathBuf,
+    components: &[&str],
+    found: &mut Vec<PathBuf>,
+) {
+    let Some((first, rest)) = components.split_first() else {
+        if !dir.as_os_str().is_empty() && root.join(&dir).is_dir() {
+            found.push(dir);
+        }
+        return;
+    };
+    if *first == "**" {
+        expand_from(root, filter, dir.clone(), rest, found);
+        for subdir in filter.subdirs(&dir) {
+            expand_from(root, filter, subdir, components, found);
+        }
+    } else if first.contains('*') {
+        for subdir in filter.subdirs(&dir) {
+            let name = subdir.file_name().unwrap_or_default().to_string_lossy();
+            if wildcard_match(first, &name) {
+                expand_from(root, filter, subdir.clone(), rest, found);
+            }
+        }
+    } else {
+        expand_from(root, filter, dir.join(first), rest, found);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detection::ProjectType;
+    use tempfile::tempdir;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn dirs(members: &[WorkspaceMember]) -> Vec<(&str, MemberSource)> {
+        members
+            .iter()
+            .map(|member| (member.dir.to_str().unwrap(), member.source))
+            .collect()
+    }
+
+    const CRATE: &str = "[package]\nname = \"x\"\nversion = \"0.1.0\"\n";
+
+    #[test]
+    fn test_cargo_workspace_members() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\", \"cli\"]\nexclude = [\"crates/scratch\"]\n",
+        );
+        write(root, "crates/core/Cargo.toml", CRATE);
+        write(root, "crates/scratch/Cargo.toml", CRATE);
+        write(root, "cli/Cargo.toml", CRATE);
+        // Not a member, so not searched for
+        write(root, "examples/demo/go.mod", "module demo\n");
+
+        let members = WorkspaceDetector::new().detect(root);
+        assert_eq!(
+            dirs(&members),
+            vec![
+                (".", MemberSource::Root),
+                ("cli", MemberSource::Cargo),
+                ("crates/core", MemberSource::Cargo),
+            ]
+        );
+        assert_eq!(
+            members[0].detection.project_type,
+            ProjectType::RustWorkspace
+        );
+    }
+
+    #[test]
+    fn test_undeclared_projects_next_to_a_workspace() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/scratch\"]\n",
+        );
+        write(root, "crates/core/Cargo.toml", CRATE);
+        write(root, "crates/core/fixtures/go.mod", "module fixture\n");
+        write(root, "crates/scratch/Cargo.toml", CRATE);
+        write(root, "svc/go.mod", "module svc\n");
+        write(root, "web/app/package.json", "{}");
+
+        let members = WorkspaceDetector::new().search_undeclared().detect(root);
+        assert_eq!(
+            dirs(&members),
+            vec![
+                (".", MemberSource::Root),
+                ("crates/core", MemberSource::Cargo),
+                ("svc", MemberSource::Nested),
+                ("web/app", MemberSource::Nested),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_js_and_go_workspaces() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "pnpm-workspace.yaml",
+            "packages:\n  - 'apps/*'\n  - '!apps/legacy'\n",
+        );
+        write(
+            root,
+            "package.json",
+            r#"{ "workspaces": { "packages": ["libs/**"] } }"#,
+        );
+        write(root, "apps/web/package.json", "{}");
+        write(root, "apps/legacy/package.json", "{}");
+        write(root, "libs/ui/button/package.json", "{}");
+        write(
+            root,
+            "go.work",
+            "go 1.22\n\nuse (\n\t./tools/gen // codegen\n)\n",
+        );
+        write(root, "tools/gen/go.mod", "module gen\n");
+
+        let members = WorkspaceDetector::new().detect(root);
+        assert_eq!(
+            dirs(&members),
+            vec![
+                (".", MemberSource::Root),
+                ("apps/web", MemberSource::Pnpm),
+                ("libs/ui/button", MemberSource::Npm),
+                ("tools/gen", MemberSource::GoWork),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nested_projects_are_searched_for() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        write(root, "services/api/Cargo.toml", CRATE);
+        write(root, "services/api/fixtures/Cargo.toml", CRATE);
+        write(root, "services/web/package.json", "{}");
+        write(root, "node_modules/left-pad/package.json", "{}");
+
+        let members = WorkspaceDetector::new().detect(root);
+        assert_eq!(
+            dirs(&members),
+            vec![
+                ("services/api", MemberSource::Nested),
+                ("services/web", MemberSource::Nested),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_search_limits() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        write(root, "services/api/Cargo.toml", CRATE);
+        write(root, "services/web/package.json", "{}");
+        write(root, "third_party/lib/go.mod", "module lib\n");
+        write(root, "out/gen/package.json", "{}");
+        write(root, ".gitignore", "out/\n");
+
+        let options = DetectionOptions {
+            exclude: vec!["services/web".to_string()],
+            ..DetectionOptions::default()
+        };
+        let members = WorkspaceDetector::with_options(options).detect(root);
+        assert_eq!(
+            dirs(&members),
+            vec![
+                ("services/api", MemberSource::Nested),
+                ("third_party/lib", MemberSource::Nested),
+            ]
+        );
+
+        let options = DetectionOptions {
+            max_depth: 1,
+            ..DetectionOptions::default()
+        };
+        assert!(WorkspaceDetector::with_options(options)
+            .detect(root)
+            .is_empty());
+    }
+}
@@ -0,0 +1,136 @@
+//! Line diffs between two versions of a generated file
+//!
+//! Shared by the editor's preview, `cci check`, and `cci diff`. Lines are
+//! matched with Myers' algorithm, so reordered or widely separated edits
+//! still line up.
+
+use colored::Colorize;
+use similar::{ChangeTag, DiffOp, TextDiff};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffType {
+    Unchanged,
+    Added,
+    Removed,
+}
+
+/// Every line of `old` and `new` in order, tagged with how it changed
+pub fn compute_diff(old: &str, new: &str) -> Vec<(String, DiffType)> {
+    TextDiff::from_lines(old, new)
+        .iter_all_changes()
+        .map(|change| {
+            let tag = match change.tag() {
+                ChangeTag::Equal => DiffType::Unchanged,
+                ChangeTag::Insert => DiffType::Added,
+                ChangeTag::Delete => DiffType::Removed,
+            };
+            (
+                change.value().trim_end_matches(['\n', '\r']).to_string(),
+                tag,
+            )
+        })
+        .collect()
+}
+
+/// Rows of a side-by-side diff, as line indexes into `old` and `new`
+///
+/// Unchanged lines share a row. Removed lines are paired with the lines that
+/// replaced them, and a side is `None` where the other has lines it lacks.
+pub fn side_by_side(old: &str, new: &str) -> Vec<(Option<usize>, Option<usize>)> {
+    let mut rows = Vec::new();
+    for op in TextDiff::from_lines(old, new).ops() {
+        let (old_index, old_len, new_index, new_len) = match *op {
+            DiffOp::Equal {
+                old_index,
+                new_index,
+                len,
+            } => (old_index, len, new_index, len),
+            DiffOp::Delete {
+                old_index,
+                old_len,
+                new_index,
+            } => (old_index, old_len, new_index, 0),
+            DiffOp::Insert {
+                old_index,
+                new_index,
+                new_len,
+            } => (old_index, 0, new_index, new_len),
+            DiffOp::Replace {
+                old_index,
+                old_len,
+                new_index,
+                new_len,
+            } => (old_index, old_len, new_index, new_len),
+        };
+        for row in 0..old_len.max(new_len) {
+            rows.push((
+                (row < old_len).then_some(old_index + row),
+                (row < new_len).then_some(new_index + row),
+            ));
+        }
+    }
+    rows
+}
+
+/// A plain unified diff with 3 lines of context, headed by both names
+pub fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(3)
+        .header(old_name, new_name)
+        .to_string()
+}
+
+/// Print a colored unified diff from `old` to `new`, indented under a heading
+pub fn print_colored_diff(old: &str, new: &str) {
+    let diff = TextDiff::from_lines(old, new);
+    for hunk in diff.unified_diff().context_radius(3).iter_hunks() {
+        println!("    {}", hunk.header().to_string().cyan());
+        for change in hunk.iter_changes() {
+            let line = change.value().trim_end_matches(['\n', '\r']);
+            match change.tag() {
+                ChangeTag::Delete => println!("    {}", format!("-{}", line).red()),
+                ChangeTag::Insert => println!("    {}", format!("+{}", line).green()),
+                ChangeTag::Equal => println!("    {}", format!(" {}", line).dimmed()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_diff_matches_moved_blocks() {
+        let old = "a\nb\nc\nd\ne\nf\ng\n";
+        let new = "a\nx\nb\nc\nd\ne\nf\ng\n";
+        let diff = compute_diff(old, new);
+        assert_eq!(diff.len(), 8);
+        assert_eq!(diff[1], ("x".to_string(), DiffType::Added));
+        assert_eq!(
+            diff.iter()
+                .filter(|(_, tag)| *tag == DiffType::Unchanged)
+                .count(),
+            7
+        );
+    }
+
+    #[test]
+    fn test_side_by_side_pairs_replaced_lines() {
+        let old = "a\nb\nc\nd\n";
+        let new = "a\nB\nc\nx\ny\nd\n";
+        assert_eq!(
+            side_by_side(old, new),
+            vec![
+                (Some(0), Some(0)),
+                (Some(1), Some(1)),
+                (Some(2), Some(2)),
+                (None, Some(3)),
+                (None, Some(4)),
+                (Some(3), Some(5)),
+            ]
+        );
+        assert_eq!(side_by_side("a\nb\n", "")[1], (Some(1), None));
+    }
+}
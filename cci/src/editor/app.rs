@@ -1,51 +1,112 @@
-use crate::detection::DetectionResult;
-use crate::editor::events::handle_key_event;
+use crate::config::locate_config;
+use crate::detection::{import_github_workflows, DetectionResult};
+use crate::editor::events::{handle_key_event, handle_mouse_event};
+use crate::editor::session;
+use crate::editor::settings::Settings;
 use crate::editor::state::EditorState;
 use crate::editor::ui::render_ui;
 use crate::error::Result;
+use crate::generator::monorepo::find_projects;
 use crossterm::{
-    event::{self, Event},
+    clipboard::CopyToClipboard,
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use ratatui::{backend::CrosstermBackend, Terminal};
+use ratatui::{backend::CrosstermBackend, layout::Rect, Terminal};
 use std::io;
-use std::path::PathBuf;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long typing has to pause before the preview is generated again
+const DEBOUNCE: Duration = Duration::from_millis(75);
+
+/// How often the spinner moves while the preview is being generated
+const SPINNER_TICK: Duration = Duration::from_millis(80);
 
 pub struct EditorApp {
     state: EditorState,
+    /// The preview being generated in the background, if any
+    generation: Option<Receiver<EditorState>>,
+    /// When the pending preview should start generating
+    generate_at: Option<Instant>,
+    /// The session as last saved, so it's only written when something changed
+    saved_session: Option<String>,
 }
 
 impl EditorApp {
-    pub fn new(detection: DetectionResult, platform: Option<String>) -> Result<Self> {
+    pub fn new(detections: Vec<DetectionResult>, platform: Option<String>) -> Result<Self> {
         let working_dir = PathBuf::from(".");
 
-        // Check if cci.ron exists, if so, load from it
-        let cci_ron_path = working_dir.join("cci.ron");
-        let state = if cci_ron_path.exists() {
-            EditorState::from_ron_file(&cci_ron_path)?
+        // Check if cci.ron (or cci.yaml/cci.toml) exists, if so, load from it
+        let config_path = locate_config(&working_dir.join("cci.ron"));
+        let mut state = if config_path.exists() {
+            EditorState::from_config_file(&config_path)?
         } else {
-            EditorState::from_detection(detection, platform, working_dir)?
+            // Pick up an existing GitHub workflow before falling back to defaults
+            let mut state = EditorState::from_detections(detections, platform, working_dir)?;
+            let imported = import_github_workflows(
+                &state.working_dir,
+                &state.registry,
+                &state.language_version,
+            )?;
+            state.apply_imported(&imported);
+            state
         };
 
-        Ok(Self { state })
+        // A repository holding several projects gets a panel to switch between them
+        state.attach_projects(find_projects(&state.working_dir, &state.detection_options));
+
+        // Colors and keys are the user's, whichever repository this is
+        let settings = Settings::load()?;
+        state.theme = settings.theme;
+        state.keymap = settings.keymap;
+
+        // The session isn't saved over until it's been restored or thrown away
+        let saved_session = state.session().to_ron().ok();
+        match session::load(&state.working_dir) {
+            Ok(Some(last)) => state.offer_session(last),
+            Ok(None) => {}
+            // Choices from a different version of cci aren't worth stopping for
+            Err(_) => session::clear(&state.working_dir),
+        }
+
+        // Keys return straight away; the preview catches up in the background
+        state.defer_generation = true;
+
+        Ok(Self {
+            state,
+            generation: None,
+            generate_at: None,
+            saved_session,
+        })
     }
 
     pub fn run(mut self) -> Result<()> {
         // Setup terminal
         enable_raw_mode()?;
         let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen)?;
+        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
         let backend = CrosstermBackend::new(stdout);
         let mut terminal = Terminal::new(backend)?;
 
         // Run the event loop
         let result = self.event_loop(&mut terminal);
+        // Quitting means there's nothing to restore next time
+        if result.is_ok() {
+            session::clear(&self.state.working_dir);
+        }
 
         // Cleanup
         disable_raw_mode()?;
-        execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+        execute!(
+            terminal.backend_mut(),
+            LeaveAlternateScreen,
+            DisableMouseCapture
+        )?;
         terminal.show_cursor()?;
 
         result
@@ -59,12 +120,31 @@ impl EditorApp {
             // Render
             terminal.draw(|f| render_ui(f, &self.state))?;
 
+            // Keep the spinner moving while the preview catches up
+            let waiting = self.state.preview_pending || self.state.preview_busy;
+            let timeout = if waiting {
+                SPINNER_TICK
+            } else {
+                Duration::from_millis(100)
+            };
+
             // Handle events
-            if event::poll(Duration::from_millis(100))? {
-                if let Event::Key(key) = event::read()? {
-                    handle_key_event(&mut self.state, key);
+            if event::poll(timeout)? {
+                let event = event::read()?;
+                // Where things are on screen decides what clicks hit and where jumps land
+                let size = terminal.size()?;
+                let screen = Rect::new(0, 0, size.width, size.height);
+                match event {
+                    Event::Key(key) => handle_key_event(&mut self.state, key, screen),
+                    Event::Mouse(mouse) => handle_mouse_event(&mut self.state, mouse, screen),
+                    _ => {}
+                }
+                if self.state.preview_pending {
+                    self.generate_at = Some(Instant::now() + DEBOUNCE);
                 }
+                self.save_session();
             }
+            self.update_generation();
 
             // Write CI config if requested
             if self.state.should_write {
@@ -72,6 +152,23 @@ impl EditorApp {
                 self.state.should_write = false; // Reset the flag so we don't keep writing
             }
 
+            // Terminals that don't take clipboard escape sequences ignore this
+            if self.state.should_copy {
+                self.state.should_copy = false;
+                self.settle_generation();
+                execute!(
+                    io::stdout(),
+                    CopyToClipboard::to_clipboard_from(self.state.yaml_preview.as_bytes())
+                )?;
+                self.state.preview_copied = true;
+            }
+
+            if self.state.should_edit {
+                self.state.should_edit = false;
+                self.settle_generation();
+                self.edit_preview(terminal)?;
+            }
+
             // Check for exit
             if self.state.should_quit {
                 break;
@@ -81,24 +178,164 @@ impl EditorApp {
         Ok(())
     }
 
-    fn write_config(&self) -> Result<()> {
-        use std::fs;
+    /// Keep the choices made so far in case the editor doesn't get to quit
+    fn save_session(&mut self) {
+        if self.state.session_restore.is_some() {
+            return;
+        }
+        let Ok(text) = self.state.session().to_ron() else {
+            return;
+        };
+        if self.saved_session.as_ref() != Some(&text)
+            && session::save(&self.state.working_dir, &text).is_ok()
+        {
+            self.saved_session = Some(text);
+        }
+    }
+
+    /// Pick up a finished preview, and start generating a pending one once
+    /// the keys have paused for a moment
+    fn update_generation(&mut self) {
+        if let Some(receiver) = &self.generation {
+            match receiver.try_recv() {
+                Ok(done) => {
+                    self.state.finish_generation(done);
+                    self.finish_worker();
+                }
+                Err(TryRecvError::Disconnected) => self.finish_worker(),
+                Err(TryRecvError::Empty) => {}
+            }
+        }
 
-        let output_path = self
+        let due = self.generate_at.is_none_or(|at| at <= Instant::now());
+        if self.generation.is_none() && due {
+            if let Some(mut job) = self.state.start_generation() {
+                let (sender, receiver) = mpsc::channel();
+                thread::spawn(move || {
+                    job.generate_preview();
+                    let _ = sender.send(job);
+                });
+                self.generation = Some(receiver);
+                self.generate_at = None;
+                self.state.preview_busy = true;
+            }
+        }
+
+        if self.state.preview_pending || self.state.preview_busy {
+            self.state.spinner_frame = self.state.spinner_frame.wrapping_add(1);
+        }
+    }
+
+    /// Bring the preview up to date before something needs what it shows
+    fn settle_generation(&mut self) {
+        if let Some(receiver) = self.generation.take() {
+            if let Ok(done) = receiver.recv() {
+                self.state.finish_generation(done);
+            }
+            self.finish_worker();
+        }
+        if self.state.preview_pending {
+            self.state.preview_pending = false;
+            self.state.generate_preview();
+        }
+    }
+
+    fn finish_worker(&mut self) {
+        self.generation = None;
+        self.state.preview_busy = false;
+    }
+
+    /// Hand the previewed file to `$VISUAL` or `$EDITOR`, and offer to keep
+    /// what changed once it exits
+    fn edit_preview<B: ratatui::backend::Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+    ) -> Result<()> {
+        let before = match self.state.editable_preview() {
+            Ok(content) => content,
+            Err(e) => {
+                self.state.generation_error = Some(e.to_string());
+                return Ok(());
+            }
+        };
+        // Named like the real file so the editor picks the right syntax
+        let name = self
             .state
-            .working_dir
-            .join(self.state.target_platform.output_path());
+            .preview_path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        let path = std::env::temp_dir().join(format!("cci-{}-{}", std::process::id(), name));
+        std::fs::write(&path, &before)?;
 
-        // Create parent directories
-        if let Some(parent) = output_path.parent() {
-            fs::create_dir_all(parent)?;
+        disable_raw_mode()?;
+        execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
+        let status = editor_command(&path).status();
+        enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+        terminal.clear()?;
+
+        let after = std::fs::read_to_string(&path);
+        let _ = std::fs::remove_file(&path);
+        match status {
+            Ok(status) if status.success() => self.state.review_hand_edit(before, after?),
+            Ok(status) => {
+                self.state.current_item_description =
+                    format!("The editor exited with {}; nothing was changed", status)
+            }
+            Err(e) => {
+                self.state.current_item_description = format!("Couldn't start the editor: {}", e)
+            }
         }
+        Ok(())
+    }
+
+    /// Write the files the user confirmed, backing up the ones replaced if asked
+    fn write_config(&mut self) -> Result<()> {
+        use std::fs;
+
+        let Some(confirm) = self.state.write_confirm.take() else {
+            return Ok(());
+        };
+        for file in confirm.files.iter().filter(|file| !file.is_unchanged()) {
+            let output_path = self.state.write_root().join(&file.path);
 
-        // Write file
-        fs::write(&output_path, &self.state.yaml_preview)?;
+            // Create parent directories
+            if let Some(parent) = output_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            if confirm.backup && file.existing.is_some() {
+                let backup_path = backup_path(&output_path);
+                fs::copy(&output_path, &backup_path)?;
+                println!("💾 Backed up: {}", backup_path.display());
+            }
+            fs::write(&output_path, &file.content)?;
 
-        println!("✨ Generated: {}", output_path.display());
+            println!("✨ Generated: {}", output_path.display());
+        }
 
         Ok(())
     }
 }
+
+/// `$VISUAL`, then `$EDITOR`, then `vi`, opening `path`; the variables may
+/// carry arguments, like `code --wait`
+fn editor_command(path: &Path) -> Command {
+    let editor = ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    let mut words = editor.split_whitespace();
+    let mut command = Command::new(words.next().unwrap_or("vi"));
+    command.args(words).arg(path);
+    command
+}
+
+/// `ci.yml` backs up to `ci.yml.bak`
+fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
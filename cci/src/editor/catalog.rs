@@ -0,0 +1,119 @@
+//! Plain descriptions of the registered presets, for listing them outside the editor
+
+use super::config::{EditorPreset, OptionMeta, OptionValue};
+use crate::detection::ProjectType;
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PresetInfo {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// `preset`, `addon`, or `decorator` for addons that extend other presets' files
+    pub kind: String,
+    /// Detected project types the preset is suggested for
+    pub project_types: Vec<String>,
+    pub features: Vec<FeatureInfo>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureInfo {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub options: Vec<OptionInfo>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OptionInfo {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// `bool`, `enum`, `string` or `int`
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub default: serde_yaml::Value,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depends_on: Option<String>,
+}
+
+/// Describe a preset with its features, options and defaults
+pub fn describe(preset: &dyn EditorPreset) -> PresetInfo {
+    let kind = if preset.is_decorator() {
+        "decorator"
+    } else if preset.is_addon() {
+        "addon"
+    } else {
+        "preset"
+    };
+    PresetInfo {
+        id: preset.preset_id().to_string(),
+        name: preset.preset_name().to_string(),
+        description: preset.preset_description().to_string(),
+        kind: kind.to_string(),
+        project_types: ProjectType::all()
+            .iter()
+            .filter(|project_type| preset.matches_project(project_type, Path::new(".")))
+            .map(|project_type| project_type.to_string())
+            .collect(),
+        features: preset
+            .features()
+            .into_iter()
+            .map(|feature| FeatureInfo {
+                id: feature.id,
+                name: feature.display_name,
+                description: feature.description,
+                options: feature.options.iter().map(describe_option).collect(),
+            })
+            .collect(),
+    }
+}
+
+fn describe_option(option: &OptionMeta) -> OptionInfo {
+    let (kind, default, variants) = match &option.default_value {
+        OptionValue::Bool(value) => ("bool", serde_yaml::Value::from(*value), Vec::new()),
+        OptionValue::Enum { selected, variants } => (
+            "enum",
+            serde_yaml::Value::from(selected.clone()),
+            variants.clone(),
+        ),
+        OptionValue::String(value) => {
+            ("string", serde_yaml::Value::from(value.clone()), Vec::new())
+        }
+        OptionValue::Int(value) => ("int", serde_yaml::Value::from(*value), Vec::new()),
+    };
+    OptionInfo {
+        id: option.id.clone(),
+        name: option.display_name.clone(),
+        description: option.description.clone(),
+        kind: kind.to_string(),
+        default,
+        variants,
+        depends_on: option.depends_on.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::registry::build_registry;
+
+    #[test]
+    fn test_describe_rust() {
+        let registry = build_registry();
+        let info = describe(registry.get("rust").unwrap().as_ref());
+        assert_eq!(info.kind, "preset");
+        assert!(info.project_types.contains(&"Rust Binary".to_string()));
+        let linter = info
+            .features
+            .iter()
+            .flat_map(|feature| &feature.options)
+            .find(|option| option.id == "enable_linter")
+            .unwrap();
+        assert_eq!(linter.kind, "bool");
+        assert_eq!(linter.default, serde_yaml::Value::from(true));
+    }
+}
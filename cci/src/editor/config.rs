@@ -1,7 +1,8 @@
-use crate::detection::ProjectType;
+use crate::detection::{DetectionResult, ProjectType};
 use crate::editor::state::Platform;
 use crate::error::Result;
-use std::collections::HashMap;
+use crate::platforms::secrets::Secret;
+use std::collections::{BTreeMap, HashMap};
 
 /// Represents a configurable option value
 #[derive(Debug, Clone, PartialEq)]
@@ -20,6 +21,15 @@ impl OptionValue {
         matches!(self, OptionValue::Bool(true))
     }
 
+    /// Whether a toggle is on, or an optional choice is something other than "none"
+    pub fn is_on(&self) -> bool {
+        match self {
+            OptionValue::Bool(b) => *b,
+            OptionValue::Enum { selected, .. } => selected != "none",
+            OptionValue::String(_) | OptionValue::Int(_) => false,
+        }
+    }
+
     pub fn toggle_bool(&mut self) {
         if let OptionValue::Bool(v) = self {
             *v = !*v;
@@ -35,6 +45,14 @@ impl OptionValue {
     }
 }
 
+/// One choice of an enum option
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariantMeta {
+    pub id: String,
+    pub display_name: String,
+    pub description: String,
+}
+
 /// Metadata for a single option within a feature
 #[derive(Debug, Clone)]
 pub struct OptionMeta {
@@ -42,7 +60,247 @@ pub struct OptionMeta {
     pub display_name: String,
     pub description: String,
     pub default_value: OptionValue,
+    /// Choices of an enum option, in order; empty for other options
+    pub variants: Vec<VariantMeta>,
     pub depends_on: Option<String>, // ID of parent option this depends on
+    pub validation: Validation,
+    /// Example shown in place of a string or integer option's empty value
+    pub placeholder: Option<String>,
+}
+
+/// Rules an option's value keeps to beyond its type, checked by the
+/// editor's input overlay, by `cci validate` and when cci.ron is loaded
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Validation {
+    /// Regex a string value must match as a whole; empty values always do,
+    /// since they leave the option unset
+    pub pattern: Option<String>,
+    /// What `pattern` accepts, for the error, e.g. "five cron fields, like `0 3 * * 1`"
+    pub expected: Option<String>,
+    /// Bounds of an integer value
+    pub min: Option<i32>,
+    pub max: Option<i32>,
+    /// Of a preset's bool options that share a group, only one can be on
+    pub exclusive: Option<String>,
+    /// Bool option that must be on while this one is
+    pub requires: Option<String>,
+    /// Options that can't be on while this one is
+    pub conflicts: Vec<String>,
+}
+
+impl Validation {
+    pub fn pattern(pattern: &str, expected: &str) -> Self {
+        Self {
+            pattern: Some(pattern.to_string()),
+            expected: Some(expected.to_string()),
+            ..Self::default()
+        }
+    }
+
+    pub fn range(min: i32, max: i32) -> Self {
+        Self {
+            min: Some(min),
+            max: Some(max),
+            ..Self::default()
+        }
+    }
+
+    pub fn exclusive(group: &str) -> Self {
+        Self {
+            exclusive: Some(group.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// A cron schedule
+    pub fn cron() -> Self {
+        Self::pattern(r"\S+(\s+\S+){4}", "five cron fields, like `0 3 * * 1`")
+    }
+
+    /// A comma-separated list of versions or toolchains
+    pub fn version_list(example: &str) -> Self {
+        Self::pattern(
+            r"[\w.+-]+(\s*,\s*[\w.+-]+)*",
+            &format!("a comma-separated list, like `{}`", example),
+        )
+    }
+
+    /// A comma-separated list of operating systems
+    pub fn os_list() -> Self {
+        Self::pattern(
+            r"(linux|macos|windows)(\s*,\s*(linux|macos|windows))*",
+            "a comma-separated list of `linux`, `macos` and `windows`",
+        )
+    }
+
+    /// Why `value` breaks the rules, if it does
+    pub fn check(&self, value: &OptionValue) -> std::result::Result<(), String> {
+        match value {
+            OptionValue::String(text) if !text.is_empty() => {
+                let Some(pattern) = &self.pattern else {
+                    return Ok(());
+                };
+                let regex = regex::Regex::new(&format!("^(?:{})$", pattern))
+                    .map_err(|e| format!("Invalid pattern `{}`: {}", pattern, e))?;
+                if regex.is_match(text.trim()) {
+                    return Ok(());
+                }
+                Err(match &self.expected {
+                    Some(expected) => format!("`{}` isn't {}", text, expected),
+                    None => format!("`{}` doesn't match `{}`", text, pattern),
+                })
+            }
+            OptionValue::Int(number) => match (self.min, self.max) {
+                (Some(min), _) if *number < min => {
+                    Err(format!("{} is below the minimum of {}", number, min))
+                }
+                (_, Some(max)) if *number > max => {
+                    Err(format!("{} is above the maximum of {}", number, max))
+                }
+                _ => Ok(()),
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+fn display_name<'a>(options: &'a [OptionMeta], id: &'a str) -> &'a str {
+    options
+        .iter()
+        .find(|option| option.id == id)
+        .map_or(id, |option| option.display_name.as_str())
+}
+
+/// Every way `config` breaks the rules of `preset`'s options, one message each
+pub fn validate_config(preset: &dyn EditorPreset, config: &PresetConfig) -> Vec<String> {
+    let options: Vec<OptionMeta> = preset
+        .features()
+        .into_iter()
+        .flat_map(|feature| feature.options)
+        .collect();
+    let mut problems = Vec::new();
+    let mut groups: BTreeMap<&str, Vec<&OptionMeta>> = BTreeMap::new();
+    // Each pair once, whichever of the two declares the conflict
+    let mut conflicts: Vec<(&str, &str)> = Vec::new();
+    for option in &options {
+        let Some(value) = config.get(&option.id) else {
+            continue;
+        };
+        if let Err(e) = option.validation.check(value) {
+            problems.push(format!(
+                "{} (`{}.{}`): {}",
+                option.display_name,
+                preset.preset_id(),
+                option.id,
+                e
+            ));
+        }
+        if let (Some(required), true) = (&option.validation.requires, value.is_on()) {
+            if !config.get_bool(required) {
+                problems.push(format!(
+                    "{} (`{}`) of `{}` requires {} (`{}`); turn it on or this off",
+                    option.display_name,
+                    option.id,
+                    preset.preset_id(),
+                    display_name(&options, required),
+                    required
+                ));
+            }
+        }
+        let applies = option
+            .depends_on
+            .as_ref()
+            .is_none_or(|parent| config.get_bool(parent));
+        if let (Some(group), true, true) = (&option.validation.exclusive, value.as_bool(), applies)
+        {
+            groups.entry(group).or_default().push(option);
+        }
+        if value.is_on() && applies {
+            for other in &option.validation.conflicts {
+                let known = conflicts.iter().any(|&(a, b)| a == other && b == option.id);
+                if config.get(other).is_some_and(OptionValue::is_on) && !known {
+                    conflicts.push((&option.id, other));
+                }
+            }
+        }
+    }
+    for (first, second) in conflicts {
+        problems.push(format!(
+            "{} (`{}`) and {} (`{}`) of `{}` can't be on together; turn one off",
+            display_name(&options, first),
+            first,
+            display_name(&options, second),
+            second,
+            preset.preset_id()
+        ));
+    }
+    for on in groups.values().filter(|on| on.len() > 1) {
+        problems.push(format!(
+            "{} of `{}` can't be on together; turn all but one off",
+            on.iter()
+                .map(|option| format!("{} (`{}`)", option.display_name, option.id))
+                .collect::<Vec<_>>()
+                .join(", "),
+            preset.preset_id()
+        ));
+    }
+    problems
+}
+
+/// Turn options of `config` off until `preset`'s rules hold: at most one
+/// option of each exclusive group on, `keep` if it's one of them and
+/// otherwise the first; no two conflicting options on, again favoring `keep`;
+/// and none on without the option it requires
+pub fn settle_rules(preset: &dyn EditorPreset, config: &mut PresetConfig, keep: Option<&str>) {
+    let mut options: Vec<OptionMeta> = preset
+        .features()
+        .into_iter()
+        .flat_map(|feature| feature.options)
+        .collect();
+    let is_on = |config: &PresetConfig, id: &str| config.get(id).is_some_and(OptionValue::is_on);
+
+    let mut groups: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for option in &options {
+        if let (Some(group), true) = (&option.validation.exclusive, config.get_bool(&option.id)) {
+            groups.entry(group).or_default().push(&option.id);
+        }
+    }
+    for on in groups.into_values().filter(|on| on.len() > 1) {
+        let kept = keep.filter(|keep| on.contains(keep)).unwrap_or(on[0]);
+        for id in on.into_iter().filter(|id| *id != kept) {
+            config.turn_off(id);
+        }
+    }
+
+    // `keep` goes first, so it's the one left on
+    if let Some(at) = keep.and_then(|keep| options.iter().position(|o| o.id == keep)) {
+        let kept = options.remove(at);
+        options.insert(0, kept);
+    }
+    for option in &options {
+        if !is_on(config, &option.id) {
+            continue;
+        }
+        for other in &options {
+            let conflicting = option.validation.conflicts.contains(&other.id)
+                || other.validation.conflicts.contains(&option.id);
+            if conflicting && is_on(config, &other.id) {
+                config.turn_off(&other.id);
+            }
+        }
+    }
+
+    // Turning one off can leave another without what it requires
+    while let Some(option) = options.iter().find(|option| {
+        is_on(config, &option.id)
+            && option
+                .validation
+                .requires
+                .as_ref()
+                .is_some_and(|required| !is_on(config, required))
+    }) {
+        config.turn_off(&option.id);
+    }
 }
 
 /// A feature groups related options
@@ -54,6 +312,18 @@ pub struct FeatureMeta {
     pub options: Vec<OptionMeta>,
 }
 
+/// Feature groups that configure the workflow itself rather than adding CI jobs
+///
+/// Options in these groups don't count towards a preset being enabled and
+/// aren't flipped when the whole preset is toggled.
+pub const WORKFLOW_SETTING_FEATURES: &[&str] = &["triggers", "runner", "github"];
+
+impl FeatureMeta {
+    pub fn is_workflow_setting(&self) -> bool {
+        WORKFLOW_SETTING_FEATURES.contains(&self.id.as_str())
+    }
+}
+
 /// Runtime configuration state for a preset
 #[derive(Debug, Clone)]
 pub struct PresetConfig {
@@ -95,6 +365,16 @@ impl PresetConfig {
         })
     }
 
+    pub fn get_int(&self, option_id: &str) -> Option<i32> {
+        self.values.get(option_id).and_then(|v| {
+            if let OptionValue::Int(n) = v {
+                Some(*n)
+            } else {
+                None
+            }
+        })
+    }
+
     pub fn get_enum(&self, option_id: &str) -> Option<String> {
         self.values.get(option_id).and_then(|v| {
             if let OptionValue::Enum { selected, .. } = v {
@@ -110,6 +390,21 @@ impl PresetConfig {
         self.values.insert(option_id, value);
     }
 
+    /// Switch a toggle off, or an optional choice to "none"
+    pub fn turn_off(&mut self, option_id: &str) {
+        let value = match self.values.get(option_id) {
+            Some(OptionValue::Bool(_)) => OptionValue::Bool(false),
+            Some(OptionValue::Enum { variants, .. }) if variants.iter().any(|v| v == "none") => {
+                OptionValue::Enum {
+                    selected: "none".to_string(),
+                    variants: variants.clone(),
+                }
+            }
+            _ => return,
+        };
+        self.set(option_id.to_string(), value);
+    }
+
     pub fn toggle(&mut self, option_id: &str) {
         if let Some(value) = self.values.get_mut(option_id) {
             self.explicitly_set.insert(option_id.to_string());
@@ -148,10 +443,76 @@ pub trait EditorPreset: Send + Sync {
         language_version: &str,
     ) -> Result<String>;
 
+    /// Build the platform config without rendering it, so decorators can extend it
+    fn build(
+        &self,
+        config: &PresetConfig,
+        platform: Platform,
+        language_version: &str,
+    ) -> Result<crate::platforms::helpers::PlatformConfig>;
+
+    /// Extend another preset's platform config (no-op unless this is a decorator)
+    fn decorate(
+        &self,
+        config: &PresetConfig,
+        language_version: &str,
+        pipeline: &mut crate::platforms::helpers::PlatformConfig,
+    ) -> Result<()>;
+
+    /// Secrets the generated output for `platform` expects to be configured
+    fn required_secrets(
+        &self,
+        config: &PresetConfig,
+        platform: Platform,
+        language_version: &str,
+    ) -> Vec<Secret>;
+
+    /// Extra files (tool configs) the generated output for `platform` relies on
+    fn support_files(
+        &self,
+        config: &PresetConfig,
+        platform: Platform,
+        language_version: &str,
+    ) -> Result<Vec<(std::path::PathBuf, String)>>;
+
+    /// Commands behind the generated jobs, for git hooks and task runners
+    fn local_checks(
+        &self,
+        config: &PresetConfig,
+        language_version: &str,
+    ) -> Vec<crate::platforms::checks::LocalCheck>;
+
+    /// Whether an existing GitHub workflow looks like this preset's output
+    fn matches_github(&self, workflow: &crate::platforms::github::models::GitHubWorkflow) -> bool;
+
+    /// The config an existing GitHub workflow was most likely generated
+    /// with, read from its jobs and steps
+    fn extract_config(
+        &self,
+        workflow: &crate::platforms::github::models::GitHubWorkflow,
+    ) -> PresetConfig;
+
     /// Check if this preset matches the detected project type
     /// This is used for UI coloring and sorting, not for enabling/disabling presets
     fn matches_project(&self, project_type: &ProjectType, working_dir: &std::path::Path) -> bool;
 
+    /// Whether this preset adds jobs on top of a base preset instead of being one
+    fn is_addon(&self) -> bool;
+
+    /// Whether this addon extends other presets' pipelines instead of generating its own
+    fn is_decorator(&self) -> bool;
+
     /// Get default configuration based on project detection
     fn default_config(&self, detected: bool) -> PresetConfig;
+
+    /// JSON Schema of the preset's settings as cci.ron holds them
+    fn config_schema(&self) -> serde_json::Value;
+
+    /// Whether this preset suits what detection found: a matching project
+    /// type, or metadata calling for it
+    fn matches_detection(&self, detection: &DetectionResult, working_dir: &std::path::Path)
+        -> bool;
+
+    /// The detected defaults, tuned to the project's metadata
+    fn detected_config(&self, detection: &DetectionResult) -> PresetConfig;
 }
@@ -1,17 +1,365 @@
+use crate::editor::config::OptionValue;
+use crate::editor::keymap::Action;
 use crate::editor::state::{EditorState, Platform, TreeItem};
-use crossterm::event::{KeyCode, KeyEvent};
+use crate::editor::ui::{
+    areas, platform_menu_area, preview_page, preview_row, preview_scroll_limit, project_at,
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Position, Rect};
+
+/// Lines the preview moves for each notch of the scroll wheel
+const SCROLL_LINES: u16 = 3;
+
+/// Whether a popup has the keys, rather than the tree
+fn overlay_open(state: &EditorState) -> bool {
+    state.text_input.is_some()
+        || state.enum_menu.is_some()
+        || state.platform_menu_open
+        || state.write_confirm.is_some()
+        || state.edit_confirm.is_some()
+        || state.session_restore.is_some()
+        || !state.suggestions.is_empty()
+        || state.profile_menu.is_some()
+        || state.help_scroll.is_some()
+        || state.search.as_ref().is_some_and(|search| search.typing)
+}
+
+/// Handle a key press on a terminal of size `screen`
+pub fn handle_key_event(state: &mut EditorState, key: KeyEvent, screen: Rect) {
+    state.preview_copied = false;
+    if !overlay_open(state) {
+        match state.keymap.action(&key) {
+            Some(Action::Undo) => {
+                state.undo();
+                return;
+            }
+            Some(Action::Redo) => {
+                state.redo();
+                return;
+            }
+            // Each project keeps its own history, so switching isn't an edit
+            Some(Action::NextProject) => {
+                state.cycle_project(true);
+                return;
+            }
+            Some(Action::PreviousProject) => {
+                state.cycle_project(false);
+                return;
+            }
+            _ => {}
+        }
+    }
+
+    // Whatever a key changes can be undone
+    let before = state.snapshot();
+    handle_key(state, key, screen);
+    state.record_edit(before);
+    clamp_preview_scroll(state, screen);
+}
+
+/// Keep the preview from scrolling past its last row
+fn clamp_preview_scroll(state: &mut EditorState, screen: Rect) {
+    // Working out the preview's height isn't free, and nothing's scrolled at the top
+    if state.preview_scroll > 0 {
+        state.preview_scroll = state
+            .preview_scroll
+            .min(preview_scroll_limit(state, screen));
+    }
+}
+
+/// Handle a click or scroll on a terminal of size `screen`
+pub fn handle_mouse_event(state: &mut EditorState, mouse: MouseEvent, screen: Rect) {
+    // Picking a project in the project panel isn't an edit either
+    let projects = areas(screen, state).projects;
+    let position = Position::new(mouse.column, mouse.row);
+    if mouse.kind == MouseEventKind::Down(MouseButton::Left)
+        && !overlay_open(state)
+        && projects.contains(position)
+    {
+        if let Some(index) = project_at(state, screen, position.y) {
+            state.switch_project(index);
+        }
+        return;
+    }
+
+    let before = state.snapshot();
+    handle_mouse(state, mouse, screen);
+    state.record_edit(before);
+    clamp_preview_scroll(state, screen);
+}
+
+fn handle_mouse(state: &mut EditorState, mouse: MouseEvent, screen: Rect) {
+    let position = Position::new(mouse.column, mouse.row);
+    let areas = areas(screen, state);
+
+    match mouse.kind {
+        MouseEventKind::ScrollDown | MouseEventKind::ScrollUp => {
+            let down = mouse.kind == MouseEventKind::ScrollDown;
+            let step = |scroll: &mut u16| {
+                *scroll = if down {
+                    scroll.saturating_add(SCROLL_LINES)
+                } else {
+                    scroll.saturating_sub(SCROLL_LINES)
+                }
+            };
+            if let Some(scroll) = &mut state.help_scroll {
+                step(scroll);
+            } else if let Some(confirm) = &mut state.write_confirm {
+                step(&mut confirm.scroll);
+            } else if let Some(edit) = &mut state.edit_confirm {
+                step(&mut edit.scroll);
+            } else if let Some(restore) = &mut state.session_restore {
+                step(&mut restore.scroll);
+            } else if !overlay_open(state) && areas.preview.contains(position) {
+                step(&mut state.preview_scroll);
+            } else if !overlay_open(state) && areas.tree.contains(position) {
+                state.tree_cursor = if down {
+                    (state.tree_cursor + 1).min(state.tree_items.len().saturating_sub(1))
+                } else {
+                    state.tree_cursor.saturating_sub(1)
+                };
+                state.update_current_item_description();
+            }
+        }
+
+        MouseEventKind::ScrollLeft | MouseEventKind::ScrollRight
+            if !overlay_open(state) && areas.preview.contains(position) =>
+        {
+            if mouse.kind == MouseEventKind::ScrollLeft {
+                state.scroll_preview_left();
+            } else {
+                state.scroll_preview_right();
+            }
+        }
+
+        MouseEventKind::Down(MouseButton::Left) => {
+            // A click outside the platform menu closes it
+            if state.platform_menu_open {
+                let menu = platform_menu_area(screen);
+                let row = position.y.saturating_sub(menu.y + 1) as usize;
+                if menu.contains(position) && position.y > menu.y && row < Platform::all().len() {
+                    state.platform_menu_cursor = row;
+                    state.select_platform_from_menu();
+                } else if !menu.contains(position) {
+                    state.close_platform_menu();
+                }
+            } else if !overlay_open(state) && areas.warnings.contains(position) {
+                state.warnings_collapsed = !state.warnings_collapsed;
+            } else if !overlay_open(state) && areas.tree.contains(position) {
+                // Rows start inside the border, from the first one scrolled to
+                let row =
+                    position.y.saturating_sub(areas.tree.y + 1) as usize + state.tree_offset.get();
+                let column = position.x.saturating_sub(areas.tree.x + 1);
+                let inside = position.y > areas.tree.y && position.y + 1 < areas.tree.bottom();
+                if inside && row < state.tree_items.len() {
+                    click_tree_item(state, row, column);
+                }
+            }
+        }
+
+        _ => {}
+    }
+}
+
+/// Select the tree item on `row`, toggling it if `column` is on its arrow, circle or checkbox
+fn click_tree_item(state: &mut EditorState, row: usize, column: u16) {
+    state.tree_cursor = row;
+    match state.tree_items[row].clone() {
+        // "▼ ● Name"
+        TreeItem::Preset(preset_id) => match column {
+            0 => state.toggle_preset_expand(&preset_id),
+            2 => state.toggle_preset(&preset_id),
+            _ => {}
+        },
+        // "  ▼ [✓] Name"
+        TreeItem::Feature(preset_id, feature_id) => match column {
+            2 => state.toggle_feature_expand(&preset_id, &feature_id),
+            4..=6 if state.feature_enabled_count(&preset_id, &feature_id).1 > 0 => {
+                state.toggle_feature(&preset_id, &feature_id)
+            }
+            _ => {}
+        },
+        // "      [✓] Name"
+        TreeItem::Option(preset_id, _feature_id, option_id) => {
+            let is_bool = matches!(
+                state.get_option_value(&preset_id, &option_id),
+                Some(OptionValue::Bool(_))
+            );
+            if is_bool && (6..9).contains(&column) {
+                state.toggle_option(&preset_id, &option_id);
+            }
+        }
+    }
+    state.update_current_item_description();
+}
+
+fn handle_key(state: &mut EditorState, key: KeyEvent, screen: Rect) {
+    // Nothing is written until the diff has been confirmed
+    if let Some(confirm) = &mut state.write_confirm {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => state.should_write = true,
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc | KeyCode::Char('q') => {
+                state.cancel_write()
+            }
+            KeyCode::Char('b') => confirm.backup = !confirm.backup,
+            KeyCode::Down | KeyCode::Char('j') => confirm.scroll = confirm.scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => confirm.scroll = confirm.scroll.saturating_sub(1),
+            KeyCode::PageDown => confirm.scroll = confirm.scroll.saturating_add(10),
+            KeyCode::PageUp => confirm.scroll = confirm.scroll.saturating_sub(10),
+            _ => {}
+        }
+        return;
+    }
+
+    // The last session comes back only if asked for
+    if let Some(restore) = &mut state.session_restore {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => state.restore_session(),
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc | KeyCode::Char('q') => {
+                state.discard_session()
+            }
+            KeyCode::Down | KeyCode::Char('j') => restore.scroll = restore.scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => restore.scroll = restore.scroll.saturating_sub(1),
+            KeyCode::PageDown => restore.scroll = restore.scroll.saturating_add(10),
+            KeyCode::PageUp => restore.scroll = restore.scroll.saturating_sub(10),
+            _ => {}
+        }
+        return;
+    }
+
+    // Addon presets are only enabled for what was noticed if asked for
+    if !state.suggestions.is_empty() {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => state.accept_suggestion(),
+            KeyCode::Char('n') | KeyCode::Char('N') => state.dismiss_suggestion(false),
+            KeyCode::Esc | KeyCode::Char('q') => state.dismiss_suggestion(true),
+            _ => {}
+        }
+        return;
+    }
+
+    // Edits back from $EDITOR are only kept once they've been looked over
+    if let Some(edit) = &mut state.edit_confirm {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => state.keep_hand_edit(),
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc | KeyCode::Char('q') => {
+                state.discard_hand_edit()
+            }
+            KeyCode::Down | KeyCode::Char('j') => edit.scroll = edit.scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => edit.scroll = edit.scroll.saturating_sub(1),
+            KeyCode::PageDown => edit.scroll = edit.scroll.saturating_add(10),
+            KeyCode::PageUp => edit.scroll = edit.scroll.saturating_sub(10),
+            _ => {}
+        }
+        return;
+    }
+
+    // The help overlay scrolls until it's closed
+    if let Some(scroll) = &mut state.help_scroll {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('?') => state.help_scroll = None,
+            KeyCode::Down | KeyCode::Char('j') => *scroll = scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => *scroll = scroll.saturating_sub(1),
+            KeyCode::PageDown => *scroll = scroll.saturating_add(10),
+            KeyCode::PageUp => *scroll = scroll.saturating_sub(10),
+            _ => {}
+        }
+        return;
+    }
+
+    // Saved profiles: Enter loads one, 's' names the current presets to save them
+    if let Some(menu) = &mut state.profile_menu {
+        if let Some(name) = &mut menu.new_name {
+            match key.code {
+                KeyCode::Esc => menu.new_name = None,
+                KeyCode::Enter => state.save_profile_from_menu(),
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => name.push(c),
+                _ => {}
+            }
+            return;
+        }
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => state.close_profile_menu(),
+            KeyCode::Up | KeyCode::Char('k') => menu.cursor = menu.cursor.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') if menu.cursor + 1 < menu.names.len() => {
+                menu.cursor += 1;
+            }
+            KeyCode::Enter => state.load_profile_from_menu(),
+            KeyCode::Char('s') => {
+                menu.new_name = Some(String::new());
+                menu.error = None;
+            }
+            _ => {}
+        }
+        return;
+    }
+
+    // If a value is being typed, keys edit it
+    if let Some(input) = &mut state.text_input {
+        match key.code {
+            KeyCode::Esc => state.cancel_text_input(),
+            KeyCode::Enter => state.submit_text_input(),
+            KeyCode::Left => input.move_left(),
+            KeyCode::Right => input.move_right(),
+            KeyCode::Home => input.move_home(),
+            KeyCode::End => input.move_end(),
+            KeyCode::Backspace => input.backspace(),
+            KeyCode::Delete => input.delete(),
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => input.insert(c),
+            _ => {}
+        }
+        return;
+    }
+
+    // While a search is being typed, keys narrow the tree
+    if state.search.as_ref().is_some_and(|search| search.typing) {
+        match key.code {
+            KeyCode::Esc => state.clear_search(),
+            KeyCode::Enter => state.confirm_search(),
+            KeyCode::Backspace => state.search_backspace(),
+            KeyCode::Up if state.tree_cursor > 0 => {
+                state.tree_cursor -= 1;
+                state.update_current_item_description();
+            }
+            KeyCode::Down if state.tree_cursor < state.tree_items.len().saturating_sub(1) => {
+                state.tree_cursor += 1;
+                state.update_current_item_description();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                state.search_insert(c)
+            }
+            _ => {}
+        }
+        return;
+    }
+
+    // If an enum option's choices are shown, pick one of them
+    if let Some(menu) = &mut state.enum_menu {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => state.close_enum_menu(),
+            KeyCode::Up | KeyCode::Char('k') => {
+                menu.cursor = menu.cursor.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') if menu.cursor + 1 < menu.variants.len() => {
+                menu.cursor += 1;
+            }
+            KeyCode::Enter => state.select_from_enum_menu(),
+            _ => {}
+        }
+        return;
+    }
 
-pub fn handle_key_event(state: &mut EditorState, key: KeyEvent) {
     // If platform menu is open, handle menu navigation
     if state.platform_menu_open {
         match key.code {
             KeyCode::Esc | KeyCode::Char('q') => {
                 state.close_platform_menu();
             }
-            KeyCode::Up | KeyCode::Char('k') => {
-                if state.platform_menu_cursor > 0 {
-                    state.platform_menu_cursor -= 1;
-                }
+            KeyCode::Up | KeyCode::Char('k') if state.platform_menu_cursor > 0 => {
+                state.platform_menu_cursor -= 1;
             }
             KeyCode::Down | KeyCode::Char('j') => {
                 let platforms = Platform::all();
@@ -22,37 +370,110 @@ pub fn handle_key_event(state: &mut EditorState, key: KeyEvent) {
             KeyCode::Enter => {
                 state.select_platform_from_menu();
             }
+            // Mark additional platforms to write alongside the selected one
+            KeyCode::Char(' ') => {
+                state.toggle_extra_platform_from_menu();
+            }
             _ => {}
         }
         return;
     }
 
-    // Normal tree navigation
-    match key.code {
+    // Esc drops the search filter before it quits
+    if key.code == KeyCode::Esc && state.search.is_some() {
+        state.clear_search();
+        return;
+    }
+
+    // Normal tree navigation, with the keys from the user's keymap
+    let Some(action) = state.keymap.action(&key) else {
+        return;
+    };
+    match action {
         // Quit
-        KeyCode::Char('q') | KeyCode::Esc => {
+        Action::Quit => {
             state.should_quit = true;
         }
 
-        // Write CI pipeline YAML
-        KeyCode::Char('w') | KeyCode::Char('W') => {
-            state.should_write = true;
+        Action::Help => {
+            state.help_scroll = Some(0);
+        }
+
+        // Review the diff of everything that would be written
+        Action::Write => {
+            state.open_write_confirm();
+        }
+
+        // Cycle the dependency update config written with 'd'
+        Action::DependencyTool => {
+            state.cycle_dependency_tool();
+        }
+
+        // Save the presets as a profile, or load one saved in another repository
+        Action::Profiles => {
+            state.open_profile_menu();
+        }
+
+        // Undo brings back whatever a reset threw away
+        Action::Reset => {
+            if let Some(item) = state.current_item().cloned() {
+                state.reset_item(&item);
+                state.update_current_item_description();
+            }
+        }
+
+        Action::ResetAll => {
+            state.reset_all();
+            state.update_current_item_description();
+        }
+
+        // A project that no longer detects keeps what was found before
+        Action::Redetect => {
+            if let Err(e) = state.redetect() {
+                state.current_item_description = format!("Detection failed: {}", e);
+            }
+        }
+
+        // Show the diff against the file on disk inline or in two columns
+        Action::SideBySide => {
+            state.side_by_side = !state.side_by_side;
+        }
+
+        // Cycle between a shared workflow file and one per preset
+        Action::FileNaming => {
+            state.cycle_file_naming();
+        }
+
+        // Search the tree with '/', and jump between matches with n/N
+        Action::Search => {
+            state.open_search();
+        }
+
+        Action::NextMatch => {
+            state.jump_to_match(true);
+        }
+
+        Action::PreviousMatch => {
+            state.jump_to_match(false);
         }
 
         // Open platform menu with 'p'
-        KeyCode::Char('p') => {
+        Action::PlatformMenu => {
             state.open_platform_menu();
         }
 
+        // Type a new value for string and integer options, or pick an enum's from a list
+        Action::Edit if state.open_text_input() || state.open_enum_menu() => {}
+
         // Toggle preset/feature/option with Enter or Space
-        KeyCode::Enter | KeyCode::Char(' ') => {
+        Action::Edit | Action::Toggle => {
             if let Some(item) = state.current_item().cloned() {
                 match item {
                     TreeItem::Preset(preset_id) => {
                         state.toggle_preset(&preset_id);
                     }
-                    TreeItem::Feature(_preset_id, _feature_id) => {
-                        // Features don't have a toggle action, just expand/collapse with arrow keys
+                    TreeItem::Feature(preset_id, feature_id) => {
+                        state.toggle_feature(&preset_id, &feature_id);
                     }
                     TreeItem::Option(preset_id, _feature_id, option_id) => {
                         state.toggle_option(&preset_id, &option_id);
@@ -62,7 +483,7 @@ pub fn handle_key_event(state: &mut EditorState, key: KeyEvent) {
         }
 
         // Left - collapse preset/feature
-        KeyCode::Left | KeyCode::Char('h') => {
+        Action::Collapse => {
             if let Some(item) = state.current_item().cloned() {
                 match item {
                     TreeItem::Preset(preset_id) => {
@@ -109,7 +530,7 @@ pub fn handle_key_event(state: &mut EditorState, key: KeyEvent) {
         }
 
         // Right - expand preset/feature
-        KeyCode::Right | KeyCode::Char('l') => {
+        Action::Expand => {
             if let Some(item) = state.current_item().cloned() {
                 match item {
                     TreeItem::Preset(preset_id) => {
@@ -133,34 +554,395 @@ pub fn handle_key_event(state: &mut EditorState, key: KeyEvent) {
         }
 
         // Navigation - J/K for preview scroll when Shift is held
-        KeyCode::Char('K') => {
+        Action::ScrollPreviewUp => {
             state.scroll_preview_up();
         }
 
-        KeyCode::Char('J') => {
+        Action::ScrollPreviewDown => {
             state.scroll_preview_down();
         }
 
-        // Navigation - regular up/down and lowercase j/k for tree navigation
-        KeyCode::Up | KeyCode::Char('k') => {
-            if state.tree_cursor > 0 {
-                state.tree_cursor -= 1;
-                state.update_current_item_description();
-            }
+        Action::PreviewPageUp => {
+            state.preview_scroll = state
+                .preview_scroll
+                .saturating_sub(preview_page(state, screen));
         }
 
-        KeyCode::Down | KeyCode::Char('j') => {
-            if state.tree_cursor < state.tree_items.len().saturating_sub(1) {
-                state.tree_cursor += 1;
-                state.update_current_item_description();
+        Action::PreviewPageDown => {
+            state.preview_scroll = state
+                .preview_scroll
+                .saturating_add(preview_page(state, screen));
+        }
+
+        Action::PreviewTop => {
+            state.preview_scroll = 0;
+        }
+
+        Action::PreviewBottom => {
+            state.preview_scroll = preview_scroll_limit(state, screen);
+        }
+
+        Action::ScrollPreviewLeft => {
+            state.scroll_preview_left();
+        }
+
+        Action::ScrollPreviewRight => {
+            state.scroll_preview_right();
+        }
+
+        // Wrap long lines, or cut them off and scroll sideways with H/L
+        Action::Wrap => {
+            state.toggle_preview_wrap();
+        }
+
+        // The app copies the file once the key is handled
+        Action::CopyPreview => {
+            state.should_copy = true;
+        }
+
+        // The app hands the file to $EDITOR once the key is handled
+        Action::OpenInEditor => {
+            state.should_edit = true;
+        }
+
+        Action::Warnings => {
+            state.warnings_collapsed = !state.warnings_collapsed;
+        }
+
+        // Scroll the preview to the job the item under the cursor produces
+        Action::JumpToJob => {
+            let location = state
+                .current_item()
+                .and_then(|item| state.job_location(item));
+            if let Some(row) =
+                location.and_then(|(file, line)| preview_row(state, screen, file, line))
+            {
+                state.preview_scroll = row;
             }
         }
 
-        // Tab to cycle platform (alternative to 'p' menu)
-        KeyCode::Tab => {
+        // Navigation - regular up/down and lowercase j/k for tree navigation
+        Action::Up if state.tree_cursor > 0 => {
+            state.tree_cursor -= 1;
+            state.update_current_item_description();
+        }
+
+        Action::Down if state.tree_cursor < state.tree_items.len().saturating_sub(1) => {
+            state.tree_cursor += 1;
+            state.update_current_item_description();
+        }
+
+        // Tab and Shift+Tab to move between the preview tabs (alternative to 'p' menu)
+        Action::NextPlatform => {
             state.cycle_platform();
         }
 
+        Action::PreviousPlatform => {
+            state.cycle_platform_back();
+        }
+
+        // Undo, redo and switching projects are handled before the snapshot is taken
         _ => {}
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detection::{DetectionResult, ProjectType};
+    use crossterm::event::MouseEvent;
+    use std::collections::HashMap;
+
+    fn click(kind: MouseEventKind, column: u16, row: u16) -> MouseEvent {
+        MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    #[test]
+    fn test_mouse_selects_toggles_and_scrolls() {
+        let dir = tempfile::tempdir().unwrap();
+        let detection = DetectionResult {
+            project_type: ProjectType::RustLibrary,
+            confidence: 100,
+            language_version: Some("stable".to_string()),
+            metadata: HashMap::new(),
+        };
+        let mut state =
+            EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        let screen = Rect::new(0, 0, 120, 60);
+        let tree = areas(screen, &state).tree;
+        let left = MouseEventKind::Down(MouseButton::Left);
+
+        // Expand the linter feature by its arrow, then click the option's checkbox
+        let feature_id = state
+            .registry
+            .get("rust")
+            .unwrap()
+            .features()
+            .into_iter()
+            .find(|feature| {
+                feature
+                    .options
+                    .iter()
+                    .any(|option| option.id == "enable_linter")
+            })
+            .unwrap()
+            .id;
+        let key = ("rust".to_string(), feature_id.clone());
+        state.expanded_features.remove(&key);
+        state.rebuild_tree();
+        let feature = state
+            .tree_items
+            .iter()
+            .position(|item| *item == TreeItem::Feature(key.0.clone(), key.1.clone()))
+            .unwrap();
+        handle_mouse_event(
+            &mut state,
+            click(left, tree.x + 3, tree.y + 1 + feature as u16),
+            screen,
+        );
+        assert_eq!(state.tree_cursor, feature);
+        let option = state
+            .tree_items
+            .iter()
+            .position(|item| matches!(item, TreeItem::Option(_, _, id) if id == "enable_linter"))
+            .unwrap();
+        let value = |state: &EditorState| state.get_option_value("rust", "enable_linter").cloned();
+        let before = value(&state);
+        let row = tree.y + 1 + option as u16;
+        handle_mouse_event(&mut state, click(left, tree.x + 20, row), screen);
+        assert_eq!(state.tree_cursor, option);
+        assert_eq!(value(&state), before);
+        handle_mouse_event(&mut state, click(left, tree.x + 8, row), screen);
+        assert_ne!(value(&state), before);
+        assert!(state.undo());
+        assert_eq!(value(&state), before);
+
+        let preview = areas(screen, &state).preview;
+        handle_mouse_event(
+            &mut state,
+            click(MouseEventKind::ScrollDown, preview.x + 5, preview.y + 5),
+            screen,
+        );
+        assert_eq!(state.preview_scroll, SCROLL_LINES);
+
+        // Platforms are listed in the menu in order, one per row
+        state.open_platform_menu();
+        let menu = platform_menu_area(screen);
+        handle_mouse_event(&mut state, click(left, menu.x + 5, menu.y + 3), screen);
+        assert!(!state.platform_menu_open);
+        assert_eq!(state.target_platform, Platform::all()[2]);
+    }
+
+    #[test]
+    fn test_clicks_land_on_the_scrolled_tree() {
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let dir = tempfile::tempdir().unwrap();
+        let detection = DetectionResult {
+            project_type: ProjectType::RustLibrary,
+            confidence: 100,
+            language_version: Some("stable".to_string()),
+            metadata: HashMap::new(),
+        };
+        let mut state =
+            EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        let screen = Rect::new(0, 0, 120, 30);
+        let tree = areas(screen, &state).tree;
+        assert!(state.tree_items.len() > tree.height as usize);
+
+        // The tree scrolls to keep the cursor in view
+        state.tree_cursor = state.tree_items.len() - 1;
+        let mut terminal = Terminal::new(TestBackend::new(screen.width, screen.height)).unwrap();
+        terminal
+            .draw(|f| crate::editor::ui::render_ui(f, &state))
+            .unwrap();
+        let offset = state.tree_offset.get();
+        assert!(offset > 0);
+
+        let left = MouseEventKind::Down(MouseButton::Left);
+        handle_mouse_event(&mut state, click(left, tree.x + 20, tree.y + 1), screen);
+        assert_eq!(state.tree_cursor, offset);
+    }
+
+    #[test]
+    fn test_preview_scrolls_by_page() {
+        let dir = tempfile::tempdir().unwrap();
+        let detection = DetectionResult {
+            project_type: ProjectType::RustLibrary,
+            confidence: 100,
+            language_version: Some("stable".to_string()),
+            metadata: HashMap::new(),
+        };
+        let mut state =
+            EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        let screen = Rect::new(0, 0, 120, 30);
+        let press = |state: &mut EditorState, code: KeyCode, modifiers: KeyModifiers| {
+            handle_key_event(state, KeyEvent::new(code, modifiers), screen)
+        };
+        let page = preview_page(&state, screen);
+        let limit = preview_scroll_limit(&state, screen);
+        assert!(limit > page, "{} rows past a page of {}", limit, page);
+
+        press(&mut state, KeyCode::PageDown, KeyModifiers::NONE);
+        assert_eq!(state.preview_scroll, page);
+        press(&mut state, KeyCode::Char('d'), KeyModifiers::CONTROL);
+        assert_eq!(state.preview_scroll, page * 2);
+        press(&mut state, KeyCode::Char('u'), KeyModifiers::CONTROL);
+        assert_eq!(state.preview_scroll, page);
+
+        // Past the last row is as far as it goes
+        press(&mut state, KeyCode::Char('G'), KeyModifiers::SHIFT);
+        assert_eq!(state.preview_scroll, limit);
+        press(&mut state, KeyCode::Char('J'), KeyModifiers::SHIFT);
+        press(&mut state, KeyCode::PageDown, KeyModifiers::NONE);
+        assert_eq!(state.preview_scroll, limit);
+        let preview = areas(screen, &state).preview;
+        handle_mouse_event(
+            &mut state,
+            MouseEvent {
+                kind: MouseEventKind::ScrollDown,
+                column: preview.x + 5,
+                row: preview.y + 5,
+                modifiers: KeyModifiers::NONE,
+            },
+            screen,
+        );
+        assert_eq!(state.preview_scroll, limit);
+
+        press(&mut state, KeyCode::Char('g'), KeyModifiers::NONE);
+        assert_eq!(state.preview_scroll, 0);
+    }
+
+    #[test]
+    fn test_jump_to_job() {
+        let dir = tempfile::tempdir().unwrap();
+        let detection = DetectionResult {
+            project_type: ProjectType::RustLibrary,
+            confidence: 100,
+            language_version: Some("stable".to_string()),
+            metadata: HashMap::new(),
+        };
+        let mut state =
+            EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        let screen = Rect::new(0, 0, 120, 60);
+        let jump = KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE);
+
+        let job_at = |state: &EditorState, item: TreeItem| {
+            let (file, line) = state.job_location(&item).unwrap();
+            assert_eq!(file, 0);
+            state
+                .yaml_preview
+                .lines()
+                .nth(line - 1)
+                .unwrap()
+                .trim()
+                .to_string()
+        };
+        let rust = |feature: &str| TreeItem::Feature("rust".to_string(), feature.to_string());
+        let linting = job_at(&state, rust("linting"));
+        let formatting = job_at(&state, rust("formatting"));
+        assert!(
+            linting.contains("lint") || linting.contains("clippy"),
+            "{}",
+            linting
+        );
+        assert!(
+            formatting.contains("fmt") || formatting.contains("format"),
+            "{}",
+            formatting
+        );
+        assert_ne!(linting, formatting);
+
+        state.tree_cursor = state
+            .tree_items
+            .iter()
+            .position(|item| *item == rust("linting"))
+            .unwrap();
+        handle_key_event(&mut state, jump, screen);
+        let (_, line) = state.job_location(&rust("linting")).unwrap();
+        // Nothing wraps on a wide screen, so the job is as far down as its
+        // line, unless that's past the last page
+        assert_eq!(
+            state.preview_scroll as usize,
+            (line - 1).min(preview_scroll_limit(&state, screen) as usize)
+        );
+        assert!(state.preview_scroll > 0);
+    }
+
+    #[test]
+    fn test_wrap_toggle_and_sideways_scroll() {
+        let dir = tempfile::tempdir().unwrap();
+        let detection = DetectionResult {
+            project_type: ProjectType::RustLibrary,
+            confidence: 100,
+            language_version: Some("stable".to_string()),
+            metadata: HashMap::new(),
+        };
+        let mut state =
+            EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        let screen = Rect::new(0, 0, 120, 60);
+        let press = |state: &mut EditorState, c: char| {
+            handle_key_event(
+                state,
+                KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE),
+                screen,
+            )
+        };
+
+        // Wrapped lines have nothing to scroll sideways to
+        assert!(state.preview_wrap);
+        press(&mut state, 'L');
+        assert_eq!(state.preview_hscroll, 0);
+
+        press(&mut state, 'r');
+        assert!(!state.preview_wrap);
+        press(&mut state, 'L');
+        press(&mut state, 'L');
+        press(&mut state, 'H');
+        assert_eq!(state.preview_hscroll, 4);
+        press(&mut state, 'r');
+        assert_eq!(state.preview_hscroll, 0);
+
+        press(&mut state, 'y');
+        assert!(state.should_copy);
+    }
+
+    #[test]
+    fn test_warnings_panel_folds() {
+        let dir = tempfile::tempdir().unwrap();
+        let detection = DetectionResult {
+            project_type: ProjectType::RustLibrary,
+            confidence: 100,
+            language_version: Some("stable".to_string()),
+            metadata: HashMap::new(),
+        };
+        let mut state =
+            EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        let screen = Rect::new(0, 0, 120, 60);
+        assert!(!state.warnings.is_empty());
+        let expanded = areas(screen, &state).warnings;
+        assert_eq!(expanded.height, state.warnings.len() as u16 + 2);
+
+        handle_key_event(
+            &mut state,
+            KeyEvent::new(KeyCode::Char('!'), KeyModifiers::NONE),
+            screen,
+        );
+        assert!(state.warnings_collapsed);
+        assert_eq!(areas(screen, &state).warnings.height, 1);
+
+        // Clicking the folded title opens it again
+        let title = areas(screen, &state).warnings;
+        handle_mouse_event(
+            &mut state,
+            click(MouseEventKind::Down(MouseButton::Left), 5, title.y),
+            screen,
+        );
+        assert!(!state.warnings_collapsed);
+    }
+}
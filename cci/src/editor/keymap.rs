@@ -0,0 +1,429 @@
+//! Keys for the editor's main view, overridable under `[keys]` in
+//! `~/.config/cci/config.toml`
+//!
+//! Popups (menus, text input, the write confirmation) keep their own keys.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Something a key does in the main view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Up,
+    Down,
+    Collapse,
+    Expand,
+    Toggle,
+    Edit,
+    ScrollPreviewUp,
+    ScrollPreviewDown,
+    ScrollPreviewLeft,
+    ScrollPreviewRight,
+    PreviewPageUp,
+    PreviewPageDown,
+    PreviewTop,
+    PreviewBottom,
+    Wrap,
+    CopyPreview,
+    OpenInEditor,
+    SideBySide,
+    JumpToJob,
+    Warnings,
+    NextPlatform,
+    PreviousPlatform,
+    NextProject,
+    PreviousProject,
+    PlatformMenu,
+    Search,
+    NextMatch,
+    PreviousMatch,
+    FileNaming,
+    DependencyTool,
+    Profiles,
+    Reset,
+    ResetAll,
+    Redetect,
+    Undo,
+    Redo,
+    Write,
+    Help,
+    Quit,
+}
+
+impl Action {
+    /// Every action, in the order keys are looked up
+    pub fn all() -> [Action; 39] {
+        use Action::*;
+        [
+            Up,
+            Down,
+            Collapse,
+            Expand,
+            Toggle,
+            Edit,
+            ScrollPreviewUp,
+            ScrollPreviewDown,
+            ScrollPreviewLeft,
+            ScrollPreviewRight,
+            PreviewPageUp,
+            PreviewPageDown,
+            PreviewTop,
+            PreviewBottom,
+            Wrap,
+            CopyPreview,
+            OpenInEditor,
+            SideBySide,
+            JumpToJob,
+            Warnings,
+            NextPlatform,
+            PreviousPlatform,
+            NextProject,
+            PreviousProject,
+            PlatformMenu,
+            Search,
+            NextMatch,
+            PreviousMatch,
+            FileNaming,
+            DependencyTool,
+            Profiles,
+            Reset,
+            ResetAll,
+            Redetect,
+            Undo,
+            Redo,
+            Write,
+            Help,
+            Quit,
+        ]
+    }
+
+    /// The action's name under `[keys]`
+    pub fn id(self) -> &'static str {
+        match self {
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Collapse => "collapse",
+            Action::Expand => "expand",
+            Action::Toggle => "toggle",
+            Action::Edit => "edit",
+            Action::ScrollPreviewUp => "scroll_preview_up",
+            Action::ScrollPreviewDown => "scroll_preview_down",
+            Action::ScrollPreviewLeft => "scroll_preview_left",
+            Action::ScrollPreviewRight => "scroll_preview_right",
+            Action::PreviewPageUp => "preview_page_up",
+            Action::PreviewPageDown => "preview_page_down",
+            Action::PreviewTop => "preview_top",
+            Action::PreviewBottom => "preview_bottom",
+            Action::Wrap => "wrap",
+            Action::CopyPreview => "copy_preview",
+            Action::OpenInEditor => "open_in_editor",
+            Action::SideBySide => "side_by_side",
+            Action::JumpToJob => "jump_to_job",
+            Action::Warnings => "warnings",
+            Action::NextPlatform => "next_platform",
+            Action::PreviousPlatform => "previous_platform",
+            Action::NextProject => "next_project",
+            Action::PreviousProject => "previous_project",
+            Action::PlatformMenu => "platform_menu",
+            Action::Search => "search",
+            Action::NextMatch => "next_match",
+            Action::PreviousMatch => "previous_match",
+            Action::FileNaming => "file_naming",
+            Action::DependencyTool => "dependency_tool",
+            Action::Profiles => "profiles",
+            Action::Reset => "reset",
+            Action::ResetAll => "reset_all",
+            Action::Redetect => "redetect",
+            Action::Undo => "undo",
+            Action::Redo => "redo",
+            Action::Write => "write",
+            Action::Help => "help",
+            Action::Quit => "quit",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::all().into_iter().find(|action| action.id() == id)
+    }
+
+    fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            Action::Up => &["up", "k"],
+            Action::Down => &["down", "j"],
+            Action::Collapse => &["left", "h"],
+            Action::Expand => &["right", "l"],
+            Action::Toggle => &["space"],
+            Action::Edit => &["enter"],
+            Action::ScrollPreviewUp => &["K"],
+            Action::ScrollPreviewDown => &["J"],
+            Action::ScrollPreviewLeft => &["H"],
+            Action::ScrollPreviewRight => &["L"],
+            Action::PreviewPageUp => &["pageup", "ctrl+u"],
+            Action::PreviewPageDown => &["pagedown", "ctrl+d"],
+            Action::PreviewTop => &["g"],
+            Action::PreviewBottom => &["G"],
+            Action::Wrap => &["r"],
+            Action::CopyPreview => &["y"],
+            Action::OpenInEditor => &["e"],
+            Action::SideBySide => &["s"],
+            Action::JumpToJob => &["o"],
+            Action::Warnings => &["!"],
+            Action::NextPlatform => &["tab"],
+            Action::PreviousPlatform => &["shift+tab"],
+            Action::NextProject => &["]"],
+            Action::PreviousProject => &["["],
+            Action::PlatformMenu => &["p"],
+            Action::Search => &["/"],
+            Action::NextMatch => &["n"],
+            Action::PreviousMatch => &["N"],
+            Action::FileNaming => &["f"],
+            Action::DependencyTool => &["d"],
+            Action::Profiles => &["P"],
+            Action::Reset => &["x"],
+            Action::ResetAll => &["X"],
+            Action::Redetect => &["R"],
+            Action::Undo => &["ctrl+z"],
+            Action::Redo => &["ctrl+y"],
+            Action::Write => &["W", "w"],
+            Action::Help => &["?"],
+            Action::Quit => &["q", "esc"],
+        }
+    }
+}
+
+/// A key, with or without Ctrl
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    code: KeyCode,
+    control: bool,
+}
+
+impl Key {
+    /// Parse a key like `q`, `W`, `ctrl+s`, `shift+tab`, `enter` or `f2`
+    ///
+    /// Letters are case-sensitive: `N` is Shift+n.
+    pub fn parse(text: &str) -> std::result::Result<Self, String> {
+        let (control, name) = match text.split_once('+') {
+            Some((modifier, name)) if !name.is_empty() => match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => (true, name),
+                "shift" if name.eq_ignore_ascii_case("tab") => (false, "backtab"),
+                "shift" if name.chars().count() == 1 => {
+                    return Self::parse(&name.to_uppercase());
+                }
+                _ => return Err(format!("Unknown modifier in `{}`", text)),
+            },
+            _ => (false, text),
+        };
+
+        let mut chars = name.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) if control => KeyCode::Char(c.to_ascii_lowercase()),
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match name.to_lowercase().as_str() {
+                "space" => KeyCode::Char(' '),
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                lower => match lower.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n) if (1..=12).contains(&n) => KeyCode::F(n),
+                    _ => return Err(format!("Unknown key `{}`", text)),
+                },
+            },
+        };
+        Ok(Self { code, control })
+    }
+
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        self.code == event.code && self.control == event.modifiers.contains(KeyModifiers::CONTROL)
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.control {
+            write!(f, "^")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) if self.control => write!(f, "{}", c.to_ascii_uppercase()),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Enter => write!(f, "Enter"),
+            KeyCode::Esc => write!(f, "Esc"),
+            KeyCode::Tab => write!(f, "Tab"),
+            KeyCode::BackTab => write!(f, "Shift+Tab"),
+            KeyCode::Backspace => write!(f, "Backspace"),
+            KeyCode::Delete => write!(f, "Del"),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::Home => write!(f, "Home"),
+            KeyCode::End => write!(f, "End"),
+            KeyCode::PageUp => write!(f, "PgUp"),
+            KeyCode::PageDown => write!(f, "PgDn"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// The keys bound to each main-view action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: HashMap<Action, Vec<Key>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = Action::all()
+            .into_iter()
+            .map(|action| {
+                let keys = action
+                    .default_keys()
+                    .iter()
+                    .map(|key| Key::parse(key).expect("default keys parse"))
+                    .collect();
+                (action, keys)
+            })
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// What `event` does in the main view, if anything
+    pub fn action(&self, event: &KeyEvent) -> Option<Action> {
+        Action::all()
+            .into_iter()
+            .find(|action| self.keys(*action).iter().any(|key| key.matches(event)))
+    }
+
+    pub fn keys(&self, action: Action) -> &[Key] {
+        self.bindings
+            .get(&action)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Bind `keys` to `action` alone, taking them from any other action they were bound to
+    pub fn bind(&mut self, action: Action, keys: Vec<Key>) {
+        for bound in self.bindings.values_mut() {
+            bound.retain(|key| !keys.contains(key));
+        }
+        self.bindings.insert(action, keys);
+    }
+
+    /// The action's keys as the footer and help show them, e.g. `W/w`
+    pub fn label(&self, action: Action) -> String {
+        let keys: Vec<String> = self.keys(action).iter().map(Key::to_string).collect();
+        keys.join("/")
+    }
+
+    /// The action's first key, where there's only room for one
+    pub fn first_label(&self, action: Action) -> String {
+        self.keys(action)
+            .first()
+            .map(Key::to_string)
+            .unwrap_or_default()
+    }
+
+    /// Two opposite actions' keys side by side, e.g. `↑↓/kj`
+    pub fn pair_label(&self, first: Action, second: Action) -> String {
+        let keys: Vec<String> = self
+            .keys(first)
+            .iter()
+            .zip(self.keys(second))
+            .map(|(a, b)| {
+                let (a, b) = (a.to_string(), b.to_string());
+                // Only single characters read clearly run together
+                if a.chars().count() == 1 && b.chars().count() == 1 {
+                    format!("{}{}", a, b)
+                } else {
+                    format!("{}/{}", a, b)
+                }
+            })
+            .collect();
+        if keys.is_empty() {
+            format!("{}/{}", self.label(first), self.label(second))
+        } else {
+            keys.join("/")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_parse_and_label_keys() {
+        for (text, label) in [
+            ("q", "q"),
+            ("N", "N"),
+            ("shift+n", "N"),
+            ("ctrl+s", "^S"),
+            ("Ctrl+S", "^S"),
+            ("shift+tab", "Shift+Tab"),
+            ("space", "Space"),
+            ("PageDown", "PgDn"),
+            ("f5", "F5"),
+            ("/", "/"),
+        ] {
+            assert_eq!(Key::parse(text).unwrap().to_string(), label, "{}", text);
+        }
+        assert!(Key::parse("hyper+x").is_err());
+        assert!(Key::parse("f13").is_err());
+        assert!(Key::parse("banana").is_err());
+    }
+
+    #[test]
+    fn test_bindings_move_between_actions() {
+        let mut keymap = Keymap::default();
+        assert_eq!(
+            keymap.action(&event(KeyCode::Char('w'), KeyModifiers::NONE)),
+            Some(Action::Write)
+        );
+        assert_eq!(
+            keymap.action(&event(KeyCode::Char('z'), KeyModifiers::CONTROL)),
+            Some(Action::Undo)
+        );
+        // Ctrl isn't ignored: ^W isn't W
+        assert_eq!(
+            keymap.action(&event(KeyCode::Char('w'), KeyModifiers::CONTROL)),
+            None
+        );
+        assert_eq!(keymap.pair_label(Action::Up, Action::Down), "↑↓/kj");
+        assert_eq!(keymap.pair_label(Action::Undo, Action::Redo), "^Z/^Y");
+
+        keymap.bind(
+            Action::Write,
+            vec![Key::parse("ctrl+s").unwrap(), Key::parse("j").unwrap()],
+        );
+        assert_eq!(keymap.label(Action::Write), "^S/j");
+        assert_eq!(
+            keymap.action(&event(KeyCode::Char('j'), KeyModifiers::NONE)),
+            Some(Action::Write)
+        );
+        assert_eq!(keymap.label(Action::Down), "↓");
+        assert_eq!(
+            keymap.action(&event(KeyCode::Char('w'), KeyModifiers::NONE)),
+            None
+        );
+    }
+}
@@ -1,9 +1,16 @@
 pub mod app;
+pub mod catalog;
 pub mod config;
 pub mod events;
+pub mod keymap;
+pub mod profiles;
 pub mod registry;
+pub mod session;
+pub mod settings;
 pub mod state;
+pub mod theme;
 pub mod ui;
+pub mod warnings;
 
 use crate::detection::DetectorRegistry;
 use crate::error::Result;
@@ -19,8 +26,8 @@ pub fn run_with_args(dir: &str, platform: Option<String>) -> Result<()> {
     // Auto-detect project
     let working_dir = PathBuf::from(dir);
     let registry = DetectorRegistry::new();
-    let detection = registry.detect(&working_dir)?;
+    let detections = registry.detect(&working_dir)?;
 
     // Launch editor
-    app::EditorApp::new(detection, platform)?.run()
+    app::EditorApp::new(detections, platform)?.run()
 }
@@ -0,0 +1,94 @@
+//! Named preset configurations shared between repositories
+//!
+//! A profile is a `cci.ron` saved under `~/.config/cci/profiles/<name>.ron`
+//! (or `$XDG_CONFIG_HOME/cci/profiles/`), so a team's standard pipeline can
+//! be loaded into any repository's editor.
+
+use crate::config::migrate::parse_config;
+use crate::config::{preset_choice_to_config, CciConfig};
+use crate::editor::config::PresetConfig;
+use crate::editor::settings::config_dir;
+use crate::error::{config_error, Result};
+use anyhow::Context;
+use std::path::{Path, PathBuf};
+
+/// Where profiles are kept, if there's a home directory to keep them in
+pub fn profiles_dir() -> Option<PathBuf> {
+    Some(config_dir()?.join("profiles"))
+}
+
+/// The names of the profiles in `dir`, sorted
+pub fn list(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "ron" {
+                return None;
+            }
+            Some(path.file_stem()?.to_string_lossy().to_string())
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+/// Why `name` can't name a profile, if it can't
+pub fn check_name(name: &str) -> std::result::Result<(), String> {
+    if name.is_empty() {
+        return Err("Enter a name".to_string());
+    }
+    if name.starts_with('.') || name.contains(['/', '\\']) {
+        return Err("Names can't contain slashes or start with a dot".to_string());
+    }
+    Ok(())
+}
+
+fn path(dir: &Path, name: &str) -> Result<PathBuf> {
+    check_name(name).map_err(config_error)?;
+    Ok(dir.join(format!("{}.ron", name)))
+}
+
+/// Save `config` as the profile `name`, replacing any profile of that name
+pub fn save(dir: &Path, name: &str, config: &str) -> Result<()> {
+    let path = path(dir, name)?;
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    std::fs::write(&path, config)
+        .with_context(|| format!("Failed to write profile: {}", path.display()))?;
+    Ok(())
+}
+
+/// The preset configs saved as the profile `name`
+pub fn load(dir: &Path, name: &str) -> Result<Vec<(String, PresetConfig)>> {
+    let path = path(dir, name)?;
+    let text = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read profile: {}", path.display()))?;
+    let mut config: CciConfig = parse_config(&text)
+        .with_context(|| format!("Failed to parse profile: {}", path.display()))?;
+    config.resolve_extends(dir)?;
+    Ok(config.presets.iter().map(preset_choice_to_config).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_and_check_names() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(list(&dir.path().join("missing")).is_empty());
+
+        save(dir.path(), "team", "(version: 3, presets: [])").unwrap();
+        save(dir.path(), "oss", "(version: 3, presets: [])").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "").unwrap();
+        assert_eq!(list(dir.path()), vec!["oss", "team"]);
+
+        assert!(check_name("").is_err());
+        assert!(check_name("../escape").is_err());
+        assert!(check_name(".hidden").is_err());
+        assert!(save(dir.path(), "a/b", "").is_err());
+        assert!(load(dir.path(), "nope").is_err());
+    }
+}
@@ -29,15 +29,40 @@ impl PresetRegistry {
     }
 }
 
-/// Build the global preset registry
+impl Default for PresetRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Build the global preset registry: the built-in presets, then the custom
+/// ones installed in `~/.config/cci/presets/`
 pub fn build_registry() -> PresetRegistry {
+    let mut registry = builtin_registry();
+    for preset in crate::presets::custom::installed() {
+        registry.register(preset.clone());
+    }
+    registry
+}
+
+/// The presets that ship with cci
+pub fn builtin_registry() -> PresetRegistry {
     let mut registry = PresetRegistry::new();
 
     // Register all editor preset implementations
     registry.register(Arc::new(crate::presets::RustPreset::default()));
     registry.register(Arc::new(crate::presets::PythonAppPreset::default()));
     registry.register(Arc::new(crate::presets::GoAppPreset::default()));
-    registry.register(Arc::new(crate::presets::DockerPreset::DEFAULT));
+    registry.register(Arc::new(crate::presets::DockerPreset::default()));
+
+    // Addons
+    registry.register(Arc::new(crate::presets::ReleasePreset::DEFAULT));
+    registry.register(Arc::new(crate::presets::ReleaseAutomationPreset::DEFAULT));
+    registry.register(Arc::new(crate::presets::CodeScanningPreset::DEFAULT));
+    // Decorators apply in this order; notifications go last to see every job
+    registry.register(Arc::new(crate::presets::NightlyPreset::DEFAULT));
+    registry.register(Arc::new(crate::presets::DeployPreset::DEFAULT));
+    registry.register(Arc::new(crate::presets::NotificationsPreset::DEFAULT));
 
     registry
 }
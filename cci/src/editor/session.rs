@@ -0,0 +1,166 @@
+//! The editor's in-progress choices, kept in `.cci/session.ron`
+//!
+//! The file is written after every change and removed when the editor quits,
+//! so one left behind means the last session ended without quitting (a crash,
+//! or a closed terminal) and can be offered back on the next launch.
+
+use crate::config::{preset_choice_to_config, preset_config_to_choice, PresetChoice};
+use crate::editor::config::PresetConfig;
+use crate::editor::state::{FileNaming, Platform};
+use crate::error::{config_error, Result};
+use crate::generator::deps::DependencyTool;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Everything the user can change in the editor
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub presets: Vec<(String, PresetConfig)>,
+    pub platform: Platform,
+    pub extra_platforms: Vec<Platform>,
+    pub dependency_tool: Option<DependencyTool>,
+    pub file_naming: FileNaming,
+    /// Hand-edited files, keyed by the path they're written to
+    pub hand_edits: BTreeMap<PathBuf, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SessionFile {
+    platform: String,
+    #[serde(default)]
+    extra_platforms: Vec<String>,
+    #[serde(default)]
+    dependency_tool: Option<String>,
+    #[serde(default)]
+    file_naming: Option<String>,
+    presets: Vec<PresetChoice>,
+    #[serde(default)]
+    hand_edits: BTreeMap<PathBuf, String>,
+}
+
+impl Session {
+    pub fn to_ron(&self) -> Result<String> {
+        let mut presets: Vec<&(String, PresetConfig)> = self.presets.iter().collect();
+        presets.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let mut extra_platforms: Vec<String> = self
+            .extra_platforms
+            .iter()
+            .map(|platform| platform.id().to_string())
+            .collect();
+        extra_platforms.sort();
+        let file = SessionFile {
+            platform: self.platform.id().to_string(),
+            extra_platforms,
+            dependency_tool: self.dependency_tool.map(|tool| tool.id().to_string()),
+            file_naming: Some(self.file_naming.id().to_string()),
+            presets: presets
+                .into_iter()
+                .map(|(id, config)| preset_config_to_choice(id, config))
+                .collect(),
+            hand_edits: self.hand_edits.clone(),
+        };
+        ron::ser::to_string_pretty(&file, ron::ser::PrettyConfig::new())
+            .map_err(|e| anyhow::anyhow!("Failed to serialize the session: {}", e))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let file: SessionFile = ron::from_str(text).map_err(|e| config_error(e.to_string()))?;
+        let platform = |id: &str| {
+            Platform::from_id(id).ok_or_else(|| config_error(format!("Unknown platform: {}", id)))
+        };
+        Ok(Self {
+            presets: file.presets.iter().map(preset_choice_to_config).collect(),
+            platform: platform(&file.platform)?,
+            extra_platforms: file
+                .extra_platforms
+                .iter()
+                .map(|id| platform(id))
+                .collect::<Result<_>>()?,
+            dependency_tool: file
+                .dependency_tool
+                .map(|id| {
+                    DependencyTool::from_id(&id)
+                        .ok_or_else(|| config_error(format!("Unknown dependency tool: {}", id)))
+                })
+                .transpose()?,
+            file_naming: match file.file_naming {
+                Some(id) => FileNaming::from_id(&id)
+                    .ok_or_else(|| config_error(format!("Unknown file naming: {}", id)))?,
+                None => FileNaming::default(),
+            },
+            hand_edits: file.hand_edits,
+        })
+    }
+}
+
+/// Where the session for the repository at `working_dir` is kept
+pub fn path(working_dir: &Path) -> PathBuf {
+    working_dir.join(".cci").join("session.ron")
+}
+
+/// Save a session written out by [`Session::to_ron`]
+pub fn save(working_dir: &Path, text: &str) -> Result<()> {
+    let path = path(working_dir);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    std::fs::write(&path, text)
+        .with_context(|| format!("Failed to write session: {}", path.display()))?;
+    Ok(())
+}
+
+/// The session left behind in `working_dir`, if there is one
+pub fn load(working_dir: &Path) -> Result<Option<Session>> {
+    let path = path(working_dir);
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read session: {}", path.display()));
+        }
+    };
+    Session::parse(&text)
+        .map(Some)
+        .with_context(|| format!("Failed to parse session: {}", path.display()))
+}
+
+/// Forget the session in `working_dir`, once it's been dealt with
+pub fn clear(working_dir: &Path) {
+    let _ = std::fs::remove_file(path(working_dir));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_load_and_clear() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(load(dir.path()).unwrap().is_none());
+
+        let session = Session {
+            presets: Vec::new(),
+            platform: Platform::GitLab,
+            extra_platforms: vec![Platform::GitHub],
+            dependency_tool: Some(DependencyTool::Renovate),
+            file_naming: FileNaming::Prefixed,
+            hand_edits: BTreeMap::from([(PathBuf::from(".gitlab-ci.yml"), "jobs".to_string())]),
+        };
+        save(dir.path(), &session.to_ron().unwrap()).unwrap();
+        let loaded = load(dir.path()).unwrap().unwrap();
+        assert_eq!(loaded.platform, Platform::GitLab);
+        assert_eq!(loaded.extra_platforms, vec![Platform::GitHub]);
+        assert_eq!(loaded.dependency_tool, Some(DependencyTool::Renovate));
+        assert_eq!(loaded.file_naming, FileNaming::Prefixed);
+        assert_eq!(loaded.hand_edits, session.hand_edits);
+
+        std::fs::write(path(dir.path()), "(platform: \"svn\", presets: [])").unwrap();
+        assert!(load(dir.path()).is_err());
+
+        clear(dir.path());
+        assert!(load(dir.path()).unwrap().is_none());
+    }
+}
@@ -0,0 +1,167 @@
+//! The editor's own settings, from `~/.config/cci/config.toml`
+//!
+//! ```toml
+//! theme = "colorblind"  # or "default", "no-color"
+//!
+//! [colors]
+//! accent = "magenta"
+//! selected = "#ffaa00"
+//!
+//! [keys]
+//! write = "ctrl+s"
+//! quit = ["q", "ctrl+c"]
+//! ```
+//!
+//! Without a `theme`, `NO_COLOR` picks the no-color theme.
+
+use crate::editor::keymap::{Action, Key, Keymap};
+use crate::editor::theme::Theme;
+use crate::error::{config_error, Result};
+use anyhow::Context;
+use ratatui::style::Color;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// `$XDG_CONFIG_HOME/cci`, or `~/.config/cci`
+pub fn config_dir() -> Option<PathBuf> {
+    let config = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(config.join("cci"))
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SettingsFile {
+    theme: Option<String>,
+    colors: BTreeMap<String, String>,
+    keys: BTreeMap<String, Keys>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Keys {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// The theme and keymap the editor runs with
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Settings {
+    pub theme: Theme,
+    pub keymap: Keymap,
+}
+
+impl Settings {
+    /// Settings from `config.toml` in [`config_dir`], or the defaults without one
+    pub fn load() -> Result<Self> {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        match config_dir() {
+            Some(dir) => Self::load_from(&dir.join("config.toml"), no_color),
+            None => Self::parse("", no_color),
+        }
+    }
+
+    pub fn load_from(path: &Path, no_color: bool) -> Result<Self> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {}", path.display()));
+            }
+        };
+        Self::parse(&text, no_color).with_context(|| format!("Invalid {}", path.display()))
+    }
+
+    pub fn parse(text: &str, no_color: bool) -> Result<Self> {
+        let file: SettingsFile = toml::from_str(text).map_err(|e| config_error(e.to_string()))?;
+
+        let mut theme = match file.theme.as_deref() {
+            Some(name) => Theme::named(name).ok_or_else(|| {
+                config_error(format!(
+                    "Unknown theme `{}`; use default, colorblind or no-color",
+                    name
+                ))
+            })?,
+            None if no_color => Theme::no_color(),
+            None => Theme::default(),
+        };
+        for (role, color) in &file.colors {
+            let slot = theme
+                .role_mut(role)
+                .ok_or_else(|| config_error(format!("Unknown color `{}`", role)))?;
+            *slot = color
+                .parse::<Color>()
+                .map_err(|_| config_error(format!("`{}` isn't a color", color)))?;
+        }
+
+        let mut keymap = Keymap::default();
+        for (id, keys) in &file.keys {
+            let action = Action::from_id(id)
+                .ok_or_else(|| config_error(format!("Unknown action `{}`", id)))?;
+            let keys = match keys {
+                Keys::One(key) => vec![key.clone()],
+                Keys::Many(keys) => keys.clone(),
+            };
+            let keys = keys
+                .iter()
+                .map(|key| Key::parse(key).map_err(config_error))
+                .collect::<Result<Vec<Key>>>()?;
+            keymap.bind(action, keys);
+        }
+
+        Ok(Self { theme, keymap })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_settings() {
+        assert_eq!(Settings::parse("", false).unwrap(), Settings::default());
+        assert_eq!(Settings::parse("", true).unwrap().theme, Theme::no_color());
+
+        let settings = Settings::parse(
+            r##"
+theme = "colorblind"
+
+[colors]
+accent = "magenta"
+selected = "#ffaa00"
+
+[keys]
+write = "ctrl+s"
+quit = ["q", "ctrl+c"]
+"##,
+            true,
+        )
+        .unwrap();
+        assert_eq!(settings.theme.positive, Theme::colorblind().positive);
+        assert_eq!(settings.theme.accent, Color::Magenta);
+        assert_eq!(settings.theme.selected, Color::Rgb(0xff, 0xaa, 0x00));
+        assert_eq!(settings.keymap.label(Action::Write), "^S");
+        assert_eq!(settings.keymap.label(Action::Quit), "q/^C");
+
+        for text in [
+            "theme = \"neon\"",
+            "[colors]\nsparkle = \"red\"",
+            "[colors]\naccent = \"not a color\"",
+            "[keys]\nfly = \"x\"",
+            "[keys]\nwrite = \"hyper+x\"",
+            "unknown = 1",
+        ] {
+            assert!(Settings::parse(text, false).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_missing_file_is_the_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = Settings::load_from(&dir.path().join("config.toml"), false).unwrap();
+        assert_eq!(settings, Settings::default());
+    }
+}
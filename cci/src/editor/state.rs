@@ -1,12 +1,37 @@
-use crate::detection::{DetectionResult, ProjectType};
-use crate::editor::config::{OptionValue, PresetConfig};
+use crate::config::{locate_config, ExtendsBase};
+use crate::detection::{
+    detect_facets, existing_platforms, find_existing_ci, DetectedFacet, DetectionOptions,
+    DetectionResult, DetectorRegistry, ImportedWorkflow, ProjectType,
+};
+use crate::editor::config::{
+    settle_rules, validate_config, EditorPreset, OptionMeta, OptionValue, PresetConfig, VariantMeta,
+};
+use crate::editor::keymap::Keymap;
+use crate::editor::profiles;
 use crate::editor::registry::{build_registry, PresetRegistry};
-use crate::error::Result;
-use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use crate::editor::session::{self, Session};
+use crate::editor::theme::Theme;
+use crate::editor::warnings::{formatter_conflicts, Warning, WarningKind};
+use crate::error::{config_error, detection_failed_error, Result};
+use crate::generator::combine::place;
+use crate::generator::deps::{
+    detect_ecosystems, DependencyTool, DependencyUpdates, Ecosystem, UpdateSchedule,
+};
+use crate::generator::merge::{
+    is_managed, mark_managed, merge_into_existing, merge_with_existing, strip_markers,
+};
+use crate::generator::monorepo::{generate_projects, Project};
+use crate::lint::{check_generated, platform_for_path, Issue};
+use crate::platforms::helpers::job_line;
+use crate::platforms::secrets::Secret;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::SystemTime;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Platform {
     GitHub,
     Gitea,
@@ -26,6 +51,29 @@ impl Platform {
         ]
     }
 
+    /// Parse a platform from its command-line identifier (e.g. "github")
+    pub fn from_id(id: &str) -> Option<Platform> {
+        match id.trim().to_lowercase().as_str() {
+            "github" => Some(Platform::GitHub),
+            "gitea" => Some(Platform::Gitea),
+            "gitlab" => Some(Platform::GitLab),
+            "circleci" => Some(Platform::CircleCI),
+            "jenkins" => Some(Platform::Jenkins),
+            _ => None,
+        }
+    }
+
+    /// Command-line identifier for this platform
+    pub fn id(&self) -> &'static str {
+        match self {
+            Platform::GitHub => "github",
+            Platform::Gitea => "gitea",
+            Platform::GitLab => "gitlab",
+            Platform::CircleCI => "circleci",
+            Platform::Jenkins => "jenkins",
+        }
+    }
+
     pub fn name(&self) -> &'static str {
         match self {
             Platform::GitHub => "GitHub Actions",
@@ -36,6 +84,17 @@ impl Platform {
         }
     }
 
+    /// Whether steps can use GitHub Actions
+    pub fn runs_actions(&self) -> bool {
+        matches!(self, Platform::GitHub | Platform::Gitea)
+    }
+
+    /// Whether jobs run in the container image they name rather than on
+    /// the runner itself
+    pub fn runs_jobs_in_images(&self) -> bool {
+        matches!(self, Platform::GitLab | Platform::CircleCI)
+    }
+
     pub fn output_path(&self) -> PathBuf {
         match self {
             Platform::GitHub => PathBuf::from(".github/workflows/ci.yml"),
@@ -54,10 +113,378 @@ pub enum TreeItem {
     Option(String, String, String), // preset_id, feature_id, option_id
 }
 
+/// How the presets' pipelines are split into workflow files
+///
+/// Only GitHub and Gitea read more than one workflow file, so the other
+/// platforms always combine the presets into their one file. Saved in cci.ron
+/// as `file_naming`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileNaming {
+    /// Every preset in the platform's usual file, e.g. `ci.yml`
+    #[default]
+    Combined,
+    /// A file per preset named after it, e.g. `rust.yml`
+    PerPreset,
+    /// A file per preset with a `ci-` prefix, e.g. `ci-rust.yml`
+    Prefixed,
+}
+
+impl FileNaming {
+    pub fn next(self) -> Self {
+        match self {
+            FileNaming::Combined => FileNaming::PerPreset,
+            FileNaming::PerPreset => FileNaming::Prefixed,
+            FileNaming::Prefixed => FileNaming::Combined,
+        }
+    }
+
+    /// Identifier used for this in files, e.g. "per_preset"
+    pub fn id(self) -> &'static str {
+        match self {
+            FileNaming::Combined => "combined",
+            FileNaming::PerPreset => "per_preset",
+            FileNaming::Prefixed => "prefixed",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<FileNaming> {
+        [
+            FileNaming::Combined,
+            FileNaming::PerPreset,
+            FileNaming::Prefixed,
+        ]
+        .into_iter()
+        .find(|naming| naming.id() == id)
+    }
+
+    /// The file names this produces, as shown in the footer
+    pub fn name(self) -> &'static str {
+        match self {
+            FileNaming::Combined => "ci.yml",
+            FileNaming::PerPreset => "<preset>.yml",
+            FileNaming::Prefixed => "ci-<preset>.yml",
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == FileNaming::default()
+    }
+
+    /// Where the `index`th file written for `platform` goes, when the first
+    /// pipeline in it is `preset_id`'s
+    ///
+    /// The first file of combined presets is the platform's usual one. Only
+    /// GitHub and Gitea read more than one file, so the other platforms have
+    /// no path for a second.
+    pub fn path(self, platform: Platform, preset_id: &str, index: usize) -> Option<PathBuf> {
+        let dir = match platform {
+            Platform::GitHub => ".github/workflows",
+            Platform::Gitea => ".gitea/workflows",
+            Platform::GitLab | Platform::CircleCI | Platform::Jenkins => {
+                return (index == 0).then(|| platform.output_path());
+            }
+        };
+        Some(match self {
+            FileNaming::Combined if index == 0 => platform.output_path(),
+            FileNaming::Combined | FileNaming::PerPreset => {
+                PathBuf::from(format!("{}/{}.yml", dir, preset_id))
+            }
+            FileNaming::Prefixed => PathBuf::from(format!("{}/ci-{}.yml", dir, preset_id)),
+        })
+    }
+
+    /// Where `preset_id`'s pipeline goes on `platform` when it gets a file of its own
+    pub fn preset_path(self, platform: Platform, preset_id: &str) -> Option<PathBuf> {
+        match (self, platform) {
+            (FileNaming::Combined, _)
+            | (_, Platform::GitLab | Platform::CircleCI | Platform::Jenkins) => None,
+            _ => self.path(platform, preset_id, 0),
+        }
+    }
+}
+
+/// The input overlay for typing a string or integer option's value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextInput {
+    pub preset_id: String,
+    pub option_id: String,
+    /// Option name shown in the overlay's title
+    pub label: String,
+    pub text: String,
+    /// Cursor position, in characters
+    pub cursor: usize,
+    /// Whether only whole numbers are accepted
+    pub integer: bool,
+    /// Example shown while the text is empty
+    pub placeholder: Option<String>,
+    /// Why the text was rejected the last time it was submitted
+    pub error: Option<String>,
+}
+
+impl TextInput {
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.text.len(), |(index, _)| index)
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let index = self.byte_index(self.cursor);
+        self.text.insert(index, c);
+        self.cursor += 1;
+        self.error = None;
+    }
+
+    /// Delete the character before the cursor
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            let index = self.byte_index(self.cursor);
+            self.text.remove(index);
+            self.error = None;
+        }
+    }
+
+    /// Delete the character under the cursor
+    pub fn delete(&mut self) {
+        if self.cursor < self.text.chars().count() {
+            let index = self.byte_index(self.cursor);
+            self.text.remove(index);
+            self.error = None;
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.text.chars().count());
+    }
+
+    pub fn move_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.text.chars().count();
+    }
+
+    /// The value the text stands for, or why it isn't valid
+    pub fn value(&self) -> std::result::Result<OptionValue, String> {
+        if !self.integer {
+            return Ok(OptionValue::String(self.text.clone()));
+        }
+        self.text
+            .trim()
+            .parse()
+            .map(OptionValue::Int)
+            .map_err(|_| "Enter a whole number".to_string())
+    }
+}
+
+/// The popup listing every choice of an enum option
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumMenu {
+    pub preset_id: String,
+    pub option_id: String,
+    /// Option name shown in the popup's title
+    pub label: String,
+    pub variants: Vec<VariantMeta>,
+    pub cursor: usize,
+}
+
+/// A file writing would create or replace, with what's there now
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingWrite {
+    /// Relative to the working directory
+    pub path: PathBuf,
+    pub content: String,
+    pub existing: Option<String>,
+}
+
+impl PendingWrite {
+    pub fn is_unchanged(&self) -> bool {
+        self.existing.as_deref() == Some(self.content.as_str())
+    }
+
+    /// Whether this replaces a pipeline cci didn't write
+    pub fn is_foreign(&self) -> bool {
+        let Some(existing) = &self.existing else {
+            return false;
+        };
+        !self.is_unchanged()
+            && platform_for_path(&self.path).and_then(|platform| is_managed(platform, existing))
+                == Some(false)
+    }
+}
+
+/// The modal listing what 'W' would write, waiting for y/n
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteConfirm {
+    pub files: Vec<PendingWrite>,
+    /// Lines scrolled past in the diff
+    pub scroll: u16,
+    /// Whether replaced files are first copied to `<file>.bak`, toggled with 'b'
+    pub backup: bool,
+}
+
+/// Edits made to the preview in `$EDITOR`, waiting to be kept or thrown away
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandEdit {
+    pub path: PathBuf,
+    /// What was handed to the editor
+    pub before: String,
+    /// The file as the editor left it
+    pub after: String,
+    /// Lines scrolled past in the diff
+    pub scroll: u16,
+}
+
+/// A session left behind by an editor that didn't quit, offered back at launch
+#[derive(Debug, Clone)]
+pub struct SessionRestore {
+    pub session: Session,
+    /// The preview as it is now
+    pub before: String,
+    /// The preview with the session restored
+    pub after: String,
+    /// Lines scrolled past in the diff
+    pub scroll: u16,
+}
+
+/// An addon preset offered for something found next to the project, e.g. the
+/// Docker preset for a Dockerfile
+#[derive(Debug, Clone)]
+pub struct Suggestion {
+    pub preset_id: String,
+    pub facet: DetectedFacet,
+}
+
+/// The projects of a repository holding several, with the one being edited
+#[derive(Clone)]
+pub struct Workspace {
+    /// The repository root, which the projects' directories are relative to
+    pub root: PathBuf,
+    pub projects: Vec<WorkspaceProject>,
+    pub active: usize,
+}
+
+/// A project in the project panel
+#[derive(Clone)]
+pub struct WorkspaceProject {
+    /// Relative to the repository root; `.` for the root itself
+    pub dir: PathBuf,
+    pub detection: DetectionResult,
+    /// The project's settings while another project is being edited; `None`
+    /// for the active project and for those not opened yet
+    pub state: Option<Box<EditorState>>,
+}
+
+/// The popup listing saved profiles, opened with 'P'
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProfileMenu {
+    pub names: Vec<String>,
+    pub cursor: usize,
+    /// The name being typed to save the current presets as, if one is
+    pub new_name: Option<String>,
+    /// Why the last save or load failed
+    pub error: Option<String>,
+}
+
+/// A search narrowing the preset tree to the presets, features and options whose names match
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Search {
+    pub query: String,
+    /// Whether keys still go to the query, rather than to the filtered tree
+    pub typing: bool,
+}
+
+/// Whether `name` contains the characters of `query` in order, ignoring case
+pub fn fuzzy_match(query: &str, name: &str) -> bool {
+    let mut name = name.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter(|c| !c.is_whitespace())
+        .all(|c| name.any(|n| n == c))
+}
+
+/// Whether [`flipped`] can change the option: booleans and enums
+fn is_flippable(option: &OptionMeta) -> bool {
+    matches!(
+        option.default_value,
+        OptionValue::Bool(_) | OptionValue::Enum { .. }
+    )
+}
+
+/// `config` with each of `options` toggled, or moved to its next variant
+fn flipped(config: &PresetConfig, options: &[&OptionMeta]) -> PresetConfig {
+    let mut flipped = config.clone();
+    for option in options {
+        let mut value = config
+            .get(&option.id)
+            .cloned()
+            .unwrap_or_else(|| option.default_value.clone());
+        match &mut value {
+            OptionValue::Bool(enabled) => *enabled = !*enabled,
+            value => value.cycle_enum(),
+        }
+        flipped.set(option.id.clone(), value);
+    }
+    flipped
+}
+
+/// How many edits undo can step back through
+const HISTORY_LIMIT: usize = 100;
+
+/// Columns the preview moves for each press of H or L
+const HSCROLL_COLUMNS: u16 = 4;
+
+/// Target platform and the extra platforms written with it
+type Platforms = (Platform, HashSet<Platform>);
+
+/// The settings the user can change, taken before each key so [`EditorState::record_edit`]
+/// can tell what it changed
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    preset_configs: HashMap<String, PresetConfig>,
+    platforms: Platforms,
+    dependency_tool: Option<DependencyTool>,
+    file_naming: FileNaming,
+}
+
+/// What one edit changed, as before and after pairs of only the parts it touched
+#[derive(Debug, Clone)]
+struct Edit {
+    presets: Vec<(PresetConfig, PresetConfig)>,
+    platforms: Option<(Platforms, Platforms)>,
+    dependency_tool: Option<(Option<DependencyTool>, Option<DependencyTool>)>,
+    file_naming: Option<(FileNaming, FileNaming)>,
+}
+
+/// Edits that can be undone, oldest first, and those undone that can be redone
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    undo: VecDeque<Edit>,
+    redo: Vec<Edit>,
+}
+
+#[derive(Clone)]
 pub struct EditorState {
     // Project context
     pub project_type: ProjectType,
     pub language_version: String,
+    /// What detection found, shown in the info bar; `None` when the presets
+    /// came from cci.ron and nothing was detected
+    pub detection: Option<DetectionResult>,
+    /// The other project types detected alongside `detection`, e.g. a
+    /// Dockerfile next to a Cargo.toml, whose presets are enabled too
+    pub other_detections: Vec<DetectionResult>,
+    /// How far the repository is searched for projects, kept in cci.ron
+    pub detection_options: DetectionOptions,
     pub working_dir: PathBuf,
 
     // User selections
@@ -66,24 +493,87 @@ pub struct EditorState {
     // Dynamic preset configuration (REPLACES all hardcoded fields!)
     pub registry: Arc<PresetRegistry>,
     pub preset_configs: HashMap<String, PresetConfig>,
+    /// What the presets cci.ron `Extends` start from, by preset ID, so saving
+    /// writes back only the options changed on top
+    pub extends: HashMap<String, ExtendsBase>,
 
     // UI state - tree structure
     pub expanded_presets: HashSet<String>, // preset IDs
     pub expanded_features: HashSet<(String, String)>, // (preset_id, feature_id)
     pub tree_items: Vec<TreeItem>,
     pub tree_cursor: usize,
+    /// First tree row on screen, which the tree moves as it follows the cursor
+    pub tree_offset: Cell<usize>,
     pub platform_menu_open: bool,
     pub platform_menu_cursor: usize,
+    /// Value being typed for a string or integer option, if the overlay is open
+    pub text_input: Option<TextInput>,
+    /// Choices for an enum option, if the popup is open
+    pub enum_menu: Option<EnumMenu>,
+    /// Filter on the preset tree, opened with `/`
+    pub search: Option<Search>,
+    /// Files waiting to be written, if the confirmation is open
+    pub write_confirm: Option<WriteConfirm>,
+    /// Colors to draw with and keys for the main view, from the user's settings
+    pub theme: Theme,
+    pub keymap: Keymap,
+    /// Lines scrolled past in the help overlay, if it's open
+    pub help_scroll: Option<u16>,
+    /// Saved profiles to load from, if the popup is open
+    pub profile_menu: Option<ProfileMenu>,
+    /// Where profiles are saved; `None` without a home directory
+    pub profiles_dir: Option<PathBuf>,
+    /// Option, preset and platform changes that Ctrl+Z and Ctrl+Y step through
+    pub history: History,
+    /// Additional platforms written alongside the target platform
+    pub extra_platforms: HashSet<Platform>,
+    /// Dependency update tool whose config is written alongside the pipeline
+    pub dependency_tool: Option<DependencyTool>,
+    /// Whether the presets share a workflow file or get one each, cycled with 'f'
+    pub file_naming: FileNaming,
 
     // Preview scroll state
     pub preview_scroll: u16,
+    /// Whether the preview shows the file on disk and the generated one in two columns
+    pub side_by_side: bool,
+    /// Whether long preview lines wrap; otherwise they're scrolled sideways
+    pub preview_wrap: bool,
+    /// Columns the preview is scrolled right by, when lines don't wrap
+    pub preview_hscroll: u16,
 
     // Generated output
     pub yaml_preview: String,
+    /// Where the previewed file is written
+    pub preview_path: PathBuf,
+    /// Files for presets whose pipelines can't share the previewed one's file
+    pub extra_previews: Vec<(PathBuf, String)>,
     pub generation_error: Option<String>,
+    /// Why generating failed for each platform it failed for, marked on the preview tabs
+    pub platform_errors: HashMap<Platform, String>,
+    /// Secrets the previewed config needs on the target platform
+    pub required_secrets: Vec<Secret>,
+    /// Problems lint rules and actionlint find in the preview, shown inline
+    pub lint_issues: Vec<Issue>,
+    /// Everything the warnings panel lists, worked out on every regenerate
+    pub warnings: Vec<Warning>,
+    /// Whether the warnings panel is folded down to its title, toggled with '!'
+    pub warnings_collapsed: bool,
+    /// Files edited by hand in `$EDITOR`, by path, kept over what's generated
+    /// the way edits to the files on disk are
+    pub hand_edits: HashMap<PathBuf, String>,
+    /// Edits back from `$EDITOR` that haven't been kept or thrown away yet
+    pub edit_confirm: Option<HandEdit>,
+    /// The last session's choices, until they're restored or thrown away
+    pub session_restore: Option<SessionRestore>,
+    /// Addon presets still to offer, the first one shown until it's answered
+    pub suggestions: Vec<Suggestion>,
+    /// Every project in the repository, when there's more than one to pick from
+    pub workspace: Option<Workspace>,
 
     // Existing YAML for diff
     pub existing_yaml: Option<String>,
+    /// When the previewed file on disk was last changed, if it exists
+    pub existing_modified: Option<SystemTime>,
 
     // UI info
     pub current_item_description: String,
@@ -91,6 +581,29 @@ pub struct EditorState {
     // Exit flags
     pub should_quit: bool,
     pub should_write: bool,
+    /// Copy the previewed file to the terminal's clipboard
+    pub should_copy: bool,
+    /// Set by the open-in-editor key; the app runs `$EDITOR` once the key is handled
+    pub should_edit: bool,
+    /// Whether regenerating leaves the work to the app's background thread,
+    /// rather than doing it before the key returns
+    pub defer_generation: bool,
+    /// A change the preview hasn't been generated for yet
+    pub preview_pending: bool,
+    /// Set by the app while a background thread generates the preview
+    pub preview_busy: bool,
+    /// Advanced by the app while the preview is pending, to animate the spinner
+    pub spinner_frame: usize,
+    /// Whether the preview was copied, until the next key
+    pub preview_copied: bool,
+}
+
+/// The platform the repository already uses, or GitHub for one without CI
+fn existing_platform(working_dir: &Path) -> Platform {
+    existing_platforms(&find_existing_ci(working_dir))
+        .first()
+        .copied()
+        .unwrap_or(Platform::GitHub)
 }
 
 impl EditorState {
@@ -99,24 +612,32 @@ impl EditorState {
         platform: Option<String>,
         working_dir: PathBuf,
     ) -> Result<Self> {
+        Self::from_detections(vec![detection], platform, working_dir)
+    }
+
+    /// Start from everything detected, ranked as by
+    /// [`DetectorRegistry::detect`]: the first result sets the project type and
+    /// language version, and every result's presets are enabled
+    pub fn from_detections(
+        mut detections: Vec<DetectionResult>,
+        platform: Option<String>,
+        working_dir: PathBuf,
+    ) -> Result<Self> {
+        if detections.is_empty() {
+            return Err(detection_failed_error());
+        }
+        let detection = detections.remove(0);
+        let other_detections = detections;
         let project_type = detection.project_type.clone();
         let language_version = detection
             .language_version
             .clone()
             .unwrap_or_else(|| "stable".to_string());
 
-        let target_platform = if let Some(p) = platform {
-            match p.to_lowercase().as_str() {
-                "github" => Platform::GitHub,
-                "gitea" => Platform::Gitea,
-                "gitlab" => Platform::GitLab,
-                "circleci" => Platform::CircleCI,
-                "jenkins" => Platform::Jenkins,
-                _ => Platform::GitHub,
-            }
-        } else {
-            Platform::GitHub
-        };
+        let target_platform = platform
+            .as_deref()
+            .and_then(Platform::from_id)
+            .unwrap_or_else(|| existing_platform(&working_dir));
 
         // Build the preset registry
         let registry = Arc::new(build_registry());
@@ -127,10 +648,16 @@ impl EditorState {
 
         for preset in registry.all() {
             let preset_id = preset.preset_id();
-            let matches = preset.matches_project(&project_type, &working_dir);
+            let detected = std::iter::once(&detection)
+                .chain(&other_detections)
+                .find(|detected| preset.matches_detection(detected, &working_dir));
+            let matches = detected.is_some();
 
             // Create default config based on whether it matches
-            let config = preset.default_config(matches);
+            let config = match detected {
+                Some(detected) => preset.detected_config(detected),
+                None => preset.default_config(false),
+            };
             preset_configs.insert(preset_id.to_string(), config);
 
             // Expand matching presets by default
@@ -146,28 +673,70 @@ impl EditorState {
         let mut state = Self {
             project_type,
             language_version,
+            detection: Some(detection),
+            other_detections,
+            detection_options: DetectionOptions::default(),
             working_dir,
             target_platform,
             registry,
             preset_configs,
+            extends: HashMap::new(),
             expanded_presets,
             expanded_features: HashSet::new(),
             tree_items: Vec::new(),
             tree_cursor: 0,
+            tree_offset: Cell::new(0),
             platform_menu_open: false,
             platform_menu_cursor: Platform::all()
                 .iter()
                 .position(|&p| p == target_platform)
                 .unwrap_or(0),
+            text_input: None,
+            enum_menu: None,
+            search: None,
+            write_confirm: None,
+            theme: Theme::default(),
+            keymap: Keymap::default(),
+            help_scroll: None,
+            profile_menu: None,
+            profiles_dir: profiles::profiles_dir(),
+            history: History::default(),
+            extra_platforms: HashSet::new(),
+            dependency_tool: None,
+            file_naming: FileNaming::default(),
             preview_scroll: 0,
+            side_by_side: false,
+            preview_wrap: true,
+            preview_hscroll: 0,
             yaml_preview: String::new(),
+            preview_path: target_platform.output_path(),
+            extra_previews: Vec::new(),
             generation_error: None,
+            platform_errors: HashMap::new(),
+            required_secrets: Vec::new(),
+            lint_issues: Vec::new(),
+            warnings: Vec::new(),
+            warnings_collapsed: false,
+            hand_edits: HashMap::new(),
+            edit_confirm: None,
+            session_restore: None,
+            suggestions: Vec::new(),
+            workspace: None,
             existing_yaml,
+            existing_modified: None,
             current_item_description: String::new(),
             should_quit: false,
             should_write: false,
+            should_copy: false,
+            should_edit: false,
+            defer_generation: false,
+            preview_pending: false,
+            preview_busy: false,
+            spinner_frame: 0,
+            preview_copied: false,
         };
 
+        state.suggestions = state.facet_suggestions();
         state.rebuild_tree();
         state.regenerate_yaml();
         state.update_current_item_description();
@@ -175,40 +744,213 @@ impl EditorState {
     }
 
     pub fn rebuild_tree(&mut self) {
-        self.tree_items.clear();
-
-        // Get all presets and sort: matching ones first, then others
+        // Get all presets and sort: matching ones first, the surest detection's
+        // leading, then others, then addons
         let mut all_presets: Vec<_> = self.registry.all().into_iter().collect();
-        all_presets
-            .sort_by_key(|preset| !preset.matches_project(&self.project_type, &self.working_dir));
+        all_presets.sort_by_key(|preset| {
+            (
+                preset.is_addon(),
+                !self.preset_matches(preset.as_ref()),
+                std::cmp::Reverse(
+                    self.matching_detection(preset.as_ref())
+                        .map(|detection| detection.confidence),
+                ),
+            )
+        });
+
+        // While searching, items are shown if they match, contain a match, or
+        // are expanded under a match
+        let searching = self.search_query().is_some();
 
         // Build three-level tree: Preset → Feature → Option
+        let mut items = Vec::new();
         for preset in all_presets {
             let preset_id = preset.preset_id().to_string();
-            self.tree_items.push(TreeItem::Preset(preset_id.clone()));
-
-            // Add features if preset is expanded
-            if self.expanded_presets.contains(&preset_id) {
-                for feature in preset.features() {
-                    let feature_id = feature.id.clone();
-                    self.tree_items
-                        .push(TreeItem::Feature(preset_id.clone(), feature_id.clone()));
-
-                    // Add options if feature is expanded
-                    if self
+            let preset_hit = self.is_search_hit(preset.preset_name());
+            let preset_expanded = self.expanded_presets.contains(&preset_id);
+
+            let mut children = Vec::new();
+            for feature in preset.features() {
+                let feature_id = feature.id.clone();
+                let feature_hit = self.is_search_hit(&feature.display_name);
+                let feature_expanded = preset_expanded
+                    && self
                         .expanded_features
-                        .contains(&(preset_id.clone(), feature_id.clone()))
-                    {
-                        for option in &feature.options {
-                            self.tree_items.push(TreeItem::Option(
-                                preset_id.clone(),
-                                feature_id.clone(),
-                                option.id.clone(),
-                            ));
-                        }
-                    }
+                        .contains(&(preset_id.clone(), feature_id.clone()));
+                let options: Vec<TreeItem> = feature
+                    .options
+                    .iter()
+                    .filter(|option| {
+                        (feature_expanded && (!searching || preset_hit || feature_hit))
+                            || self.is_search_hit(&option.display_name)
+                    })
+                    .map(|option| {
+                        TreeItem::Option(preset_id.clone(), feature_id.clone(), option.id.clone())
+                    })
+                    .collect();
+
+                if (preset_expanded && (!searching || preset_hit))
+                    || feature_hit
+                    || !options.is_empty()
+                {
+                    children.push(TreeItem::Feature(preset_id.clone(), feature_id));
+                    children.extend(options);
                 }
             }
+
+            if !searching || preset_hit || !children.is_empty() {
+                items.push(TreeItem::Preset(preset_id));
+                items.extend(children);
+            }
+        }
+        self.tree_items = items;
+    }
+
+    /// The search query, if one is narrowing the tree
+    fn search_query(&self) -> Option<&str> {
+        self.search
+            .as_ref()
+            .map(|search| search.query.as_str())
+            .filter(|query| !query.trim().is_empty())
+    }
+
+    fn is_search_hit(&self, name: &str) -> bool {
+        self.search_query()
+            .is_some_and(|query| fuzzy_match(query, name))
+    }
+
+    /// The name the tree shows for `item`
+    fn item_name(&self, item: &TreeItem) -> Option<String> {
+        let (preset_id, feature_id, option_id) = match item {
+            TreeItem::Preset(preset_id) => (preset_id, None, None),
+            TreeItem::Feature(preset_id, feature_id) => (preset_id, Some(feature_id), None),
+            TreeItem::Option(preset_id, feature_id, option_id) => {
+                (preset_id, Some(feature_id), Some(option_id))
+            }
+        };
+        let preset = self.registry.get(preset_id)?;
+        let Some(feature_id) = feature_id else {
+            return Some(preset.preset_name().to_string());
+        };
+        let feature = preset
+            .features()
+            .into_iter()
+            .find(|feature| &feature.id == feature_id)?;
+        match option_id {
+            Some(option_id) => feature
+                .options
+                .into_iter()
+                .find(|option| &option.id == option_id)
+                .map(|option| option.display_name),
+            None => Some(feature.display_name),
+        }
+    }
+
+    /// Positions in the tree of the items whose names match the search
+    pub fn search_matches(&self) -> Vec<usize> {
+        self.tree_items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| {
+                self.item_name(item)
+                    .is_some_and(|name| self.is_search_hit(&name))
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Rebuild the tree, keeping the cursor on the same item if it's still shown
+    fn refresh_tree(&mut self) {
+        let current = self.current_item().cloned();
+        self.rebuild_tree();
+        self.tree_cursor = current
+            .and_then(|current| self.tree_items.iter().position(|item| *item == current))
+            .unwrap_or_else(|| {
+                self.tree_cursor
+                    .min(self.tree_items.len().saturating_sub(1))
+            });
+        self.update_current_item_description();
+    }
+
+    /// Start typing a search, continuing the current one if there is one
+    pub fn open_search(&mut self) {
+        let search = self.search.get_or_insert_with(Search::default);
+        search.typing = true;
+    }
+
+    pub fn search_insert(&mut self, c: char) {
+        if let Some(search) = &mut self.search {
+            search.query.push(c);
+            self.follow_search();
+        }
+    }
+
+    pub fn search_backspace(&mut self) {
+        if let Some(search) = &mut self.search {
+            search.query.pop();
+            self.follow_search();
+        }
+    }
+
+    /// Refilter the tree and move the cursor to a match, unless it's already on one
+    fn follow_search(&mut self) {
+        if self.search_query().is_none() {
+            self.reveal_current_item();
+        }
+        self.refresh_tree();
+        if !self.search_matches().contains(&self.tree_cursor) {
+            self.jump_to_match(true);
+        }
+    }
+
+    /// Stop typing and keep the tree filtered, so n/N can move between matches
+    pub fn confirm_search(&mut self) {
+        if self.search_query().is_none() {
+            self.clear_search();
+        } else if let Some(search) = &mut self.search {
+            search.typing = false;
+        }
+    }
+
+    /// Drop the filter, keeping the item under the cursor in view
+    pub fn clear_search(&mut self) {
+        self.search = None;
+        self.reveal_current_item();
+        self.refresh_tree();
+    }
+
+    /// Expand the preset and feature the item under the cursor is in
+    fn reveal_current_item(&mut self) {
+        match self.current_item().cloned() {
+            Some(TreeItem::Option(preset_id, feature_id, _)) => {
+                self.expanded_presets.insert(preset_id.clone());
+                self.expanded_features.insert((preset_id, feature_id));
+            }
+            Some(TreeItem::Feature(preset_id, _)) => {
+                self.expanded_presets.insert(preset_id);
+            }
+            _ => {}
+        }
+    }
+
+    /// Move the cursor to the next match after it, or the previous one before it, wrapping around
+    pub fn jump_to_match(&mut self, forward: bool) {
+        let matches = self.search_matches();
+        let target = if forward {
+            matches
+                .iter()
+                .find(|&&i| i > self.tree_cursor)
+                .or(matches.first())
+        } else {
+            matches
+                .iter()
+                .rev()
+                .find(|&&i| i < self.tree_cursor)
+                .or(matches.last())
+        };
+        if let Some(&target) = target {
+            self.tree_cursor = target;
+            self.update_current_item_description();
         }
     }
 
@@ -240,652 +982,3434 @@ impl EditorState {
     pub fn regenerate_yaml(&mut self) {
         // Reset scroll position when regenerating
         self.preview_scroll = 0;
+        self.preview_hscroll = 0;
+        if self.defer_generation {
+            self.preview_pending = true;
+        } else {
+            self.generate_preview();
+        }
+    }
 
-        // Find the first preset that has any options enabled
-        let active_preset = self.registry.all().into_iter().find(|preset| {
-            if let Some(config) = self.preset_configs.get(preset.preset_id()) {
-                self.has_any_options_enabled(config)
-            } else {
-                false
-            }
-        });
+    /// A copy of the state to generate the pending preview in off the UI
+    /// thread, if there's a change waiting for one
+    pub fn start_generation(&mut self) -> Option<EditorState> {
+        if !self.preview_pending {
+            return None;
+        }
+        self.preview_pending = false;
+        let mut job = self.clone();
+        job.history = History::default();
+        job.defer_generation = false;
+        Some(job)
+    }
 
-        let Some(preset) = active_preset else {
-            self.yaml_preview = "# No preset options enabled\n# Enable at least one option to generate configuration".to_string();
-            self.generation_error = None;
-            return;
-        };
+    /// Show what a copy from [`Self::start_generation`] generated
+    pub fn finish_generation(&mut self, done: EditorState) {
+        self.yaml_preview = done.yaml_preview;
+        self.preview_path = done.preview_path;
+        self.extra_previews = done.extra_previews;
+        self.existing_yaml = done.existing_yaml;
+        self.existing_modified = done.existing_modified;
+        self.generation_error = done.generation_error;
+        self.platform_errors = done.platform_errors;
+        self.required_secrets = done.required_secrets;
+        self.lint_issues = done.lint_issues;
+        self.warnings = done.warnings;
+    }
 
-        let preset_id = preset.preset_id();
-        let config = match self.preset_configs.get(preset_id) {
-            Some(c) => c,
-            None => {
-                self.generation_error = Some(format!("Config not found for preset: {}", preset_id));
-                return;
+    /// Generate the preview and everything shown with it, right away
+    pub fn generate_preview(&mut self) {
+        let mut required_secrets = Vec::new();
+        for (preset, config) in self.active_presets_and_decorators() {
+            for secret in
+                preset.required_secrets(config, self.target_platform, &self.language_version)
+            {
+                if !required_secrets.contains(&secret) {
+                    required_secrets.push(secret);
+                }
             }
-        };
-
-        let result = preset.generate(config, self.target_platform, &self.language_version);
+        }
+        self.required_secrets = required_secrets;
 
-        match result {
-            Ok(yaml) => {
-                self.yaml_preview = yaml;
+        self.lint_issues = Vec::new();
+        self.extra_previews = Vec::new();
+        match self.generate_for_platform(self.target_platform) {
+            None => {
+                self.yaml_preview = "# No preset options enabled\n# Enable at least one option to generate configuration".to_string();
+                self.preview_path = self.target_platform.output_path();
                 self.generation_error = None;
             }
-            Err(e) => {
+            Some(Ok(files)) => match self.with_hand_edits(self.target_platform, files) {
+                Ok(files) => self.show_files(files),
+                Err(e) => self.generation_error = Some(e.to_string()),
+            },
+            Some(Err(e)) => {
                 self.generation_error = Some(e.to_string());
             }
         }
+
+        // The other tabs only show whether their platform's output generates
+        self.platform_errors = Platform::all()
+            .into_iter()
+            .filter(|&platform| platform != self.target_platform)
+            .filter_map(|platform| match self.generate_for_platform(platform) {
+                Some(Err(e)) => Some((platform, e.to_string())),
+                _ => None,
+            })
+            .collect();
+        if let Some(error) = &self.generation_error {
+            self.platform_errors
+                .insert(self.target_platform, error.clone());
+        }
+        self.warnings = self.collect_warnings();
     }
 
-    fn has_any_options_enabled(&self, config: &PresetConfig) -> bool {
-        config.values.values().any(|v| match v {
-            OptionValue::Bool(b) => *b,
-            _ => true, // Consider non-bool options as "enabled" if they have a value
-        })
+    /// Preview the target platform's files, the first in full and the rest as extras
+    fn show_files(&mut self, mut files: Vec<(PathBuf, String)>) {
+        let (path, yaml) = files.remove(0);
+        self.lint_issues = check_generated(self.target_platform, &path, &yaml);
+        // The first file isn't the platform's usual one when each preset gets its own
+        let existing = self.working_dir.join(&path);
+        self.existing_yaml = std::fs::read_to_string(&existing).ok();
+        self.existing_modified = std::fs::metadata(&existing)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        self.preview_path = path;
+        self.yaml_preview = yaml;
+        self.extra_previews = files;
+        self.generation_error = None;
     }
 
-    pub fn get_option_value(&self, preset_id: &str, option_id: &str) -> Option<&OptionValue> {
-        self.preset_configs
-            .get(preset_id)
-            .and_then(|config| config.get(option_id))
+    /// Generated files with the jobs and steps added in `$EDITOR` merged back in
+    fn with_hand_edits(
+        &self,
+        platform: Platform,
+        files: Vec<(PathBuf, String)>,
+    ) -> Result<Vec<(PathBuf, String)>> {
+        files
+            .into_iter()
+            .map(|(path, content)| {
+                let content = match self.hand_edits.get(&path) {
+                    Some(edit) => strip_markers(&merge_with_existing(
+                        platform,
+                        &mark_managed(platform, &content)?,
+                        edit,
+                    )?),
+                    None => content,
+                };
+                Ok((path, content))
+            })
+            .collect()
     }
 
-    pub fn set_option_value(&mut self, preset_id: &str, option_id: &str, value: OptionValue) {
-        if let Some(config) = self.preset_configs.get_mut(preset_id) {
-            config.set(option_id.to_string(), value);
+    /// The previewed file as it's handed to `$EDITOR`, with generated jobs
+    /// marked so edits to them can be told from what's generated
+    pub fn editable_preview(&self) -> Result<String> {
+        let platform = self.target_platform;
+        let generated = match self.generate_for_platform(platform) {
+            Some(files) => files?
+                .into_iter()
+                .find(|(path, _)| *path == self.preview_path)
+                .map(|(_, content)| content)
+                .unwrap_or_else(|| self.yaml_preview.clone()),
+            None => self.yaml_preview.clone(),
+        };
+        let marked = mark_managed(platform, &generated)?;
+        match self.hand_edits.get(&self.preview_path) {
+            Some(edit) => merge_with_existing(platform, &marked, edit),
+            None => Ok(marked),
         }
     }
 
-    pub fn toggle_option(&mut self, preset_id: &str, option_id: &str) {
-        if let Some(config) = self.preset_configs.get_mut(preset_id) {
-            if let Some(value) = config.get(option_id) {
-                let new_value = match value {
-                    OptionValue::Bool(b) => OptionValue::Bool(!b),
-                    OptionValue::Enum { selected, variants } => {
-                        // Cycle to next variant
-                        let current_index =
-                            variants.iter().position(|v| v == selected).unwrap_or(0);
-                        let next_index = (current_index + 1) % variants.len();
-                        OptionValue::Enum {
-                            selected: variants[next_index].clone(),
-                            variants: variants.clone(),
-                        }
-                    }
-                    other => other.clone(),
-                };
-                config.set(option_id.to_string(), new_value);
-            }
+    /// Ask whether to keep what came back from `$EDITOR`, unless nothing changed
+    pub fn review_hand_edit(&mut self, before: String, after: String) {
+        if before == after {
+            return;
         }
-        self.regenerate_yaml();
-        self.auto_save_ron();
+        // Jenkinsfiles are written whole, so there's nothing to merge edits into
+        if self.target_platform == Platform::Jenkins {
+            self.current_item_description =
+                "Jenkinsfiles are always regenerated whole, so hand edits can't be kept"
+                    .to_string();
+            return;
+        }
+        self.edit_confirm = Some(HandEdit {
+            path: self.preview_path.clone(),
+            before,
+            after,
+            scroll: 0,
+        });
     }
 
-    pub fn cycle_platform(&mut self) {
-        let platforms = Platform::all();
-        let current_index = platforms
-            .iter()
-            .position(|&p| p == self.target_platform)
-            .unwrap_or(0);
-        let next_index = (current_index + 1) % platforms.len();
-        self.target_platform = platforms[next_index];
-
-        // Reload existing YAML for the new platform
-        let output_path = self.working_dir.join(self.target_platform.output_path());
-        self.existing_yaml = std::fs::read_to_string(&output_path).ok();
-
-        self.regenerate_yaml();
-        self.auto_save_ron();
+    /// Keep the reviewed edits over the generated file, if they still parse
+    pub fn keep_hand_edit(&mut self) {
+        let Some(edit) = self.edit_confirm.take() else {
+            return;
+        };
+        // Merging would quietly drop a file that doesn't parse
+        if let Err(e) = serde_yaml::from_str::<serde_yaml::Value>(&edit.after) {
+            self.current_item_description = format!("Hand edits not kept: {}", e);
+            return;
+        }
+        // Generated right away, to know whether the edits merge
+        let previous = self.hand_edits.insert(edit.path.clone(), edit.after);
+        self.generate_preview();
+        if let Some(error) = self.generation_error.take() {
+            match previous {
+                Some(previous) => self.hand_edits.insert(edit.path, previous),
+                None => self.hand_edits.remove(&edit.path),
+            };
+            self.generate_preview();
+            self.current_item_description = format!("Hand edits not kept: {}", error);
+        }
     }
 
-    pub fn toggle_preset(&mut self, preset_id: &str) {
-        // Check if any options are currently enabled
-        let config = match self.preset_configs.get(preset_id) {
-            Some(c) => c,
-            None => return,
-        };
+    pub fn discard_hand_edit(&mut self) {
+        self.edit_confirm = None;
+    }
 
-        let has_enabled = self.has_any_options_enabled(config);
+    /// What the warnings panel lists for the configuration as it is now, by kind
+    fn collect_warnings(&self) -> Vec<Warning> {
+        let mut checks = Vec::new();
+        for (preset, config) in self.active_presets() {
+            for check in preset.local_checks(config, &self.language_version) {
+                checks.push((preset.preset_name().to_string(), check));
+            }
+        }
+        let mut warnings = formatter_conflicts(&checks);
+        for (preset, config) in self.active_presets() {
+            warnings.extend(
+                validate_config(preset.as_ref(), config)
+                    .into_iter()
+                    .map(|problem| {
+                        Warning::new(WarningKind::Invalid, preset.preset_name(), problem)
+                    }),
+            );
+        }
 
-        // Get preset to access all its options
-        let preset = match self.registry.get(preset_id) {
-            Some(p) => p,
-            None => return,
-        };
+        let platform = self.target_platform;
+        let active = self.active_presets();
+        if self.file_naming != FileNaming::Combined
+            && active.iter().any(|(preset, _)| {
+                self.file_naming
+                    .preset_path(platform, preset.preset_id())
+                    .is_none()
+            })
+        {
+            warnings.push(Warning::new(
+                WarningKind::Platform,
+                "File naming",
+                format!(
+                    "{} reads one file, so presets can't have a file each",
+                    platform.name()
+                ),
+            ));
+        }
+        if self.file_naming == FileNaming::Combined {
+            for (path, _) in &self.extra_previews {
+                warnings.push(Warning::new(
+                    WarningKind::Platform,
+                    path.display().to_string(),
+                    "presets with different triggers can't share a file, so this one is written too",
+                ));
+            }
+        }
+        for extra in self.write_platforms() {
+            if let Some(error) = self
+                .platform_errors
+                .get(&extra)
+                .filter(|_| extra != platform)
+            {
+                warnings.push(Warning::new(
+                    WarningKind::Platform,
+                    extra.name(),
+                    format!("also written, but fails to generate: {}", error),
+                ));
+            }
+        }
+        warnings.extend(self.ineffective_options());
 
-        // Toggle all boolean options for this preset
-        for feature in preset.features() {
-            for option in &feature.options {
-                if matches!(option.default_value, OptionValue::Bool(_)) {
-                    self.set_option_value(preset_id, &option.id, OptionValue::Bool(!has_enabled));
+        warnings.extend(self.lint_issues.iter().map(|issue| {
+            let subject = match issue.line {
+                Some(line) => format!("line {}", line),
+                None => issue.location.clone(),
+            };
+            Warning::new(WarningKind::Lint, subject, issue.message.clone())
+        }));
+        warnings.extend(self.required_secrets.iter().map(|secret| {
+            Warning::new(WarningKind::Secret, secret.name.clone(), secret.description)
+        }));
+
+        warnings.sort_by_key(|warning| warning.kind);
+        warnings
+    }
+
+    /// Options changed from their defaults that the target platform's output
+    /// ignores, though another platform's doesn't
+    fn ineffective_options(&self) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+        for (preset, config) in self.active_presets() {
+            let build = |config: &PresetConfig, platform: Platform| {
+                preset
+                    .build(config, platform, &self.language_version)
+                    .map(|pipeline| pipeline.jobs())
+                    .ok()
+            };
+            for feature in preset.features() {
+                for option in &feature.options {
+                    if !is_flippable(option)
+                        || !self.is_option_non_default(preset.preset_id(), &option.id)
+                    {
+                        continue;
+                    }
+                    let other = flipped(config, &[option]);
+                    let changes = |platform| build(config, platform) != build(&other, platform);
+                    if changes(self.target_platform) {
+                        continue;
+                    }
+                    if Platform::all().into_iter().any(changes) {
+                        warnings.push(Warning::new(
+                            WarningKind::Platform,
+                            option.display_name.clone(),
+                            format!("has no effect on {}", self.target_platform.name()),
+                        ));
+                    }
                 }
             }
         }
+        warnings
+    }
 
-        self.regenerate_yaml();
-        self.auto_save_ron();
+    /// Generate output for the given platform from every preset with options enabled
+    ///
+    /// Returns (path, content) pairs. Unless [`FileNaming`] gives each preset a
+    /// file, the presets' pipelines are combined into as few files as the
+    /// platform allows, the first at [`Platform::output_path`]. Returns `None`
+    /// when no preset has any options enabled. Enabled decorator addons extend
+    /// each pipeline before it's rendered.
+    pub fn generate_for_platform(
+        &self,
+        platform: Platform,
+    ) -> Option<Result<Vec<(PathBuf, String)>>> {
+        let active = self.active_presets();
+        if active.is_empty() {
+            return None;
+        }
+        Some(self.render_pipelines(&active, platform))
     }
 
-    pub fn open_platform_menu(&mut self) {
-        self.platform_menu_open = true;
+    fn render_pipelines(
+        &self,
+        active: &[(&Arc<dyn EditorPreset>, &PresetConfig)],
+        platform: Platform,
+    ) -> Result<Vec<(PathBuf, String)>> {
+        let mut pipelines = Vec::new();
+        for (preset, config) in active {
+            let pipeline = preset.build(config, platform, &self.language_version)?;
+            pipelines.push((preset.preset_id().to_string(), pipeline));
+        }
+
+        let mut files = Vec::new();
+        for (_, path, mut pipeline) in place(pipelines, platform, self.file_naming)? {
+            for (decorator, decorator_config) in self.active_decorators() {
+                decorator.decorate(decorator_config, &self.language_version, &mut pipeline)?;
+            }
+            files.push((path, pipeline.render()?));
+        }
+        Ok(files)
     }
 
-    pub fn close_platform_menu(&mut self) {
-        self.platform_menu_open = false;
+    /// Switch between a shared workflow file and one per preset
+    pub fn cycle_file_naming(&mut self) {
+        self.file_naming = self.file_naming.next();
+        self.regenerate_yaml();
+        self.auto_save_ron();
     }
 
-    pub fn select_platform_from_menu(&mut self) {
-        let platforms = Platform::all();
-        if let Some(&platform) = platforms.get(self.platform_menu_cursor) {
-            self.target_platform = platform;
+    /// Where the job behind `item` starts in the preview, as the index of the file
+    /// (0 for the previewed one, then [`Self::extra_previews`]) and a 1-based line
+    ///
+    /// A feature or option's jobs are the ones that change when its options are
+    /// flipped; a preset, or an item that changes no job, leads to the preset's
+    /// first job.
+    pub fn job_location(&self, item: &TreeItem) -> Option<(usize, usize)> {
+        let (preset_id, feature_id, option_id) = match item {
+            TreeItem::Preset(preset_id) => (preset_id, None, None),
+            TreeItem::Feature(preset_id, feature_id) => (preset_id, Some(feature_id), None),
+            TreeItem::Option(preset_id, feature_id, option_id) => {
+                (preset_id, Some(feature_id), Some(option_id))
+            }
+        };
+        let preset = self.registry.get(preset_id)?;
+        let config = self.preset_configs.get(preset_id)?;
+        let build = |config: &PresetConfig| {
+            preset
+                .build(config, self.target_platform, &self.language_version)
+                .map(|pipeline| pipeline.jobs())
+                .ok()
+        };
+        let jobs = build(config)?;
+
+        let mut candidates: Vec<&String> = Vec::new();
+        if let Some(feature) = feature_id.and_then(|feature_id| {
+            preset
+                .features()
+                .into_iter()
+                .find(|feature| &feature.id == feature_id)
+        }) {
+            let mut options: Vec<&OptionMeta> = feature
+                .options
+                .iter()
+                .filter(|option| Some(&option.id) == option_id)
+                .filter(|option| is_flippable(option))
+                .collect();
+            if options.is_empty() {
+                options = feature.options.iter().filter(|o| is_flippable(o)).collect();
+            }
+            if let Some(other) = build(&flipped(config, &options)) {
+                candidates = jobs
+                    .iter()
+                    .filter(|job| !other.contains(job))
+                    .map(|(name, _)| name)
+                    .collect();
+            }
+        }
+        candidates.extend(jobs.iter().map(|(name, _)| name));
 
-            // Reload existing YAML for the new platform
-            let output_path = self.working_dir.join(self.target_platform.output_path());
-            self.existing_yaml = std::fs::read_to_string(&output_path).ok();
+        let mut files = vec![self.yaml_preview.as_str()];
+        files.extend(
+            self.extra_previews
+                .iter()
+                .map(|(_, content)| content.as_str()),
+        );
+        candidates.into_iter().find_map(|job| {
+            files.iter().enumerate().find_map(|(index, content)| {
+                job_line(self.target_platform, content, job).map(|line| (index, line))
+            })
+        })
+    }
 
-            self.regenerate_yaml();
-            self.auto_save_ron();
+    /// Support files the active presets need next to their config on `platform`
+    pub fn support_files_for_platform(&self, platform: Platform) -> Result<Vec<(PathBuf, String)>> {
+        let mut files: Vec<(PathBuf, String)> = Vec::new();
+        for (preset, config) in self.active_presets_and_decorators() {
+            for (path, content) in preset.support_files(config, platform, &self.language_version)? {
+                if !files.iter().any(|(known, _)| *known == path) {
+                    files.push((path, content));
+                }
+            }
         }
-        self.platform_menu_open = false;
+        Ok(files)
     }
 
-    pub fn update_current_item_description(&mut self) {
-        self.current_item_description = match self.current_item() {
-            Some(TreeItem::Preset(preset_id)) => self
-                .registry
-                .get(preset_id)
-                .map(|p| p.preset_description().to_string())
-                .unwrap_or_default(),
-            Some(TreeItem::Feature(preset_id, feature_id)) => self
-                .registry
-                .get(preset_id)
-                .and_then(|preset| {
-                    preset
-                        .features()
-                        .into_iter()
-                        .find(|f| &f.id == feature_id)
-                        .map(|f| f.description.clone())
-                })
-                .unwrap_or_default(),
-            Some(TreeItem::Option(preset_id, feature_id, option_id)) => self
-                .registry
-                .get(preset_id)
-                .and_then(|preset| {
-                    preset
-                        .features()
-                        .into_iter()
-                        .find(|f| &f.id == feature_id)
-                        .and_then(|f| {
-                            f.options
-                                .iter()
-                                .find(|o| &o.id == option_id)
-                                .map(|o| o.description.clone())
-                        })
-                })
-                .unwrap_or_default(),
-            None => String::new(),
-        };
+    /// Presets with options enabled that generate pipelines, in registry order
+    fn active_presets(&self) -> Vec<(&Arc<dyn EditorPreset>, &PresetConfig)> {
+        self.registry
+            .all()
+            .into_iter()
+            .filter_map(|preset| {
+                let config = self.preset_configs.get(preset.preset_id())?;
+                (!preset.is_decorator() && self.has_any_options_enabled(config))
+                    .then_some((preset, config))
+            })
+            .collect()
     }
 
-    pub fn scroll_preview_up(&mut self) {
-        self.preview_scroll = self.preview_scroll.saturating_sub(1);
+    /// Decorator addons with options enabled, applied to the previewed pipelines
+    fn active_decorators(&self) -> Vec<(&Arc<dyn EditorPreset>, &PresetConfig)> {
+        self.registry
+            .all()
+            .into_iter()
+            .filter_map(|preset| {
+                let config = self.preset_configs.get(preset.preset_id())?;
+                (preset.is_decorator() && self.has_any_options_enabled(config))
+                    .then_some((preset, config))
+            })
+            .collect()
     }
 
-    pub fn scroll_preview_down(&mut self) {
-        self.preview_scroll = self.preview_scroll.saturating_add(1);
+    /// Decorators only count once there's a pipeline for them to extend
+    fn active_presets_and_decorators(&self) -> Vec<(&Arc<dyn EditorPreset>, &PresetConfig)> {
+        let mut active = self.active_presets();
+        if !active.is_empty() {
+            active.extend(self.active_decorators());
+        }
+        active
     }
 
-    /// Check if an option value differs from its default value
-    pub fn is_option_non_default(&self, preset_id: &str, option_id: &str) -> bool {
-        let preset = match self.registry.get(preset_id) {
-            Some(p) => p,
-            None => return false,
-        };
+    /// Platforms that the write action targets: the current platform plus any
+    /// additional platforms marked in the platform menu, in menu order
+    pub fn write_platforms(&self) -> Vec<Platform> {
+        Platform::all()
+            .into_iter()
+            .filter(|p| *p == self.target_platform || self.extra_platforms.contains(p))
+            .collect()
+    }
 
-        let current_value = match self.get_option_value(preset_id, option_id) {
-            Some(v) => v,
-            None => return false,
-        };
+    /// Mark or unmark the platform under the menu cursor as an additional write target
+    pub fn toggle_extra_platform_from_menu(&mut self) {
+        if let Some(&platform) = Platform::all().get(self.platform_menu_cursor) {
+            if !self.extra_platforms.remove(&platform) {
+                self.extra_platforms.insert(platform);
+            }
+        }
+    }
 
-        // Find the option metadata to get default value
-        for feature in preset.features() {
-            for option in &feature.options {
-                if option.id == option_id {
-                    return current_value != &option.default_value;
+    /// Every file writing would touch, with jobs added by hand merged back in
+    pub fn planned_writes(&self) -> Result<Vec<PendingWrite>> {
+        if let Some(workspace) = &self.workspace {
+            return self.planned_workspace_writes(workspace);
+        }
+        let mut files = Vec::new();
+        for platform in self.write_platforms() {
+            let pipelines = match self.generate_for_platform(platform) {
+                Some(result) => result?,
+                None => continue,
+            };
+            for (path, content) in pipelines {
+                let mut content = mark_managed(platform, &content)?;
+                if let Some(edit) = self.hand_edits.get(&path) {
+                    content = merge_with_existing(platform, &content, edit)?;
                 }
+                // Keep jobs and steps added to the existing file by hand
+                let content =
+                    merge_into_existing(platform, &content, &self.working_dir.join(&path))?;
+                files.push((path, content));
             }
+            files.extend(self.support_files_for_platform(platform)?);
         }
+        files.extend(self.dependency_updates_file()?);
 
-        false
+        Ok(files
+            .into_iter()
+            .map(|(path, content)| PendingWrite {
+                existing: std::fs::read_to_string(self.working_dir.join(&path)).ok(),
+                path,
+                content,
+            })
+            .collect())
     }
 
-    /// Check if a feature has any non-default options
-    pub fn has_feature_non_defaults(&self, preset_id: &str, feature_id: &str) -> bool {
-        let preset = match self.registry.get(preset_id) {
-            Some(p) => p,
-            None => return false,
+    /// Offer the other projects found in the repository in the project panel
+    ///
+    /// `found` pairs each project's directory with what was detected there, as
+    /// from [`crate::generator::monorepo::find_projects`]; nothing changes
+    /// unless there's a choice.
+    pub fn attach_projects(&mut self, found: Vec<(PathBuf, DetectionResult)>) {
+        let mut projects: Vec<WorkspaceProject> = found
+            .into_iter()
+            .map(|(dir, detection)| WorkspaceProject {
+                dir,
+                detection,
+                state: None,
+            })
+            .collect();
+        if projects.len() < 2 {
+            return;
+        }
+        let active = match projects.iter().position(|p| p.dir == Path::new(".")) {
+            Some(index) => index,
+            None => {
+                projects.insert(
+                    0,
+                    WorkspaceProject {
+                        dir: PathBuf::from("."),
+                        detection: self.detection.clone().unwrap_or(DetectionResult {
+                            project_type: self.project_type.clone(),
+                            confidence: 100,
+                            language_version: Some(self.language_version.clone()),
+                            metadata: HashMap::new(),
+                        }),
+                        state: None,
+                    },
+                );
+                0
+            }
         };
+        self.workspace = Some(Workspace {
+            root: self.working_dir.clone(),
+            projects,
+            active,
+        });
+    }
 
-        let feature = match preset.features().into_iter().find(|f| f.id == feature_id) {
-            Some(f) => f,
-            None => return false,
+    /// Edit the project at `index` in the project panel, keeping this one's
+    /// settings (and undo history) for when it's picked again
+    ///
+    /// The platforms are the repository's, so they come along.
+    pub fn switch_project(&mut self, index: usize) {
+        let Some(mut workspace) = self.workspace.take() else {
+            return;
         };
-
-        for option in &feature.options {
-            if self.is_option_non_default(preset_id, &option.id) {
-                return true;
+        if index == workspace.active || index >= workspace.projects.len() {
+            self.workspace = Some(workspace);
+            return;
+        }
+        let next = match workspace.projects[index].state.take() {
+            Some(state) => Ok(*state),
+            None => self.open_project(&workspace.root, &workspace.projects[index]),
+        };
+        let mut next = match next {
+            Ok(next) => next,
+            Err(e) => {
+                self.current_item_description = format!(
+                    "Couldn't open {}: {}",
+                    workspace.projects[index].dir.display(),
+                    e
+                );
+                self.workspace = Some(workspace);
+                return;
             }
+        };
+
+        next.theme = self.theme;
+        next.keymap = self.keymap.clone();
+        next.defer_generation = self.defer_generation;
+        next.warnings_collapsed = self.warnings_collapsed;
+        next.side_by_side = self.side_by_side;
+        next.preview_wrap = self.preview_wrap;
+        if (next.target_platform, &next.extra_platforms)
+            != (self.target_platform, &self.extra_platforms)
+        {
+            next.target_platform = self.target_platform;
+            next.extra_platforms = self.extra_platforms.clone();
+            next.existing_yaml =
+                std::fs::read_to_string(next.working_dir.join(next.target_platform.output_path()))
+                    .ok();
+            next.regenerate_yaml();
         }
+        next.update_current_item_description();
 
-        false
+        let previous = std::mem::replace(self, next);
+        workspace.projects[workspace.active].state = Some(Box::new(previous));
+        workspace.active = index;
+        self.workspace = Some(workspace);
     }
 
-    /// Check if a preset has any non-default options
-    pub fn has_preset_non_defaults(&self, preset_id: &str) -> bool {
-        let preset = match self.registry.get(preset_id) {
-            Some(p) => p,
-            None => return false,
+    /// Step through the project panel, wrapping around at either end
+    pub fn cycle_project(&mut self, forward: bool) {
+        let Some(workspace) = &self.workspace else {
+            return;
         };
+        let count = workspace.projects.len();
+        let index = if forward {
+            (workspace.active + 1) % count
+        } else {
+            (workspace.active + count - 1) % count
+        };
+        self.switch_project(index);
+    }
 
-        for feature in preset.features() {
-            if self.has_feature_non_defaults(preset_id, &feature.id) {
-                return true;
+    /// A project's settings as it's first opened: its own cci.ron, or what
+    /// detection suggests
+    fn open_project(&self, root: &Path, project: &WorkspaceProject) -> Result<EditorState> {
+        let dir = root.join(&project.dir);
+        let config_path = locate_config(&dir.join("cci.ron"));
+        let mut state = if config_path.exists() {
+            EditorState::from_config_file(&config_path)?
+        } else {
+            EditorState::from_detection(
+                project.detection.clone(),
+                Some(self.target_platform.id().to_string()),
+                dir,
+            )?
+        };
+        state.profiles_dir = self.profiles_dir.clone();
+        Ok(state)
+    }
+
+    /// Every project's files, with paths relative to the repository root,
+    /// each scoped to its project's directory
+    fn planned_workspace_writes(&self, workspace: &Workspace) -> Result<Vec<PendingWrite>> {
+        let mut projects = Vec::new();
+        for (index, project) in workspace.projects.iter().enumerate() {
+            let opened;
+            let state = match &project.state {
+                _ if index == workspace.active => self,
+                Some(state) => state,
+                None => {
+                    opened = self.open_project(&workspace.root, project)?;
+                    &opened
+                }
+            };
+            let mut preset_configs: Vec<(String, PresetConfig)> = state
+                .preset_configs
+                .iter()
+                .filter(|(_, config)| state.has_any_options_enabled(config))
+                .map(|(id, config)| (id.clone(), config.clone()))
+                .collect();
+            if preset_configs.is_empty() {
+                continue;
             }
+            preset_configs.sort_by(|(a, _), (b, _)| a.cmp(b));
+            projects.push(Project {
+                dir: project.dir.clone(),
+                detection: project.detection.clone(),
+                preset_configs,
+            });
         }
 
-        false
+        let mut files = Vec::new();
+        for platform in self.write_platforms() {
+            for (_, path, content) in generate_projects(&projects, self.registry.clone(), platform)?
+            {
+                files.push(PendingWrite {
+                    existing: std::fs::read_to_string(workspace.root.join(&path)).ok(),
+                    path,
+                    content,
+                });
+            }
+        }
+        Ok(files)
     }
 
-    /// Auto-expand presets and features that have non-default values
-    pub fn auto_expand_non_defaults(&mut self) {
-        let preset_ids: Vec<String> = self
-            .registry
-            .all()
-            .into_iter()
-            .map(|p| p.preset_id().to_string())
-            .collect();
+    /// The directory written files' paths are relative to: the repository
+    /// root when editing several projects, otherwise the working directory
+    pub fn write_root(&self) -> &Path {
+        match &self.workspace {
+            Some(workspace) => &workspace.root,
+            None => &self.working_dir,
+        }
+    }
 
-        for preset_id in preset_ids {
-            if self.has_preset_non_defaults(&preset_id) {
-                self.expanded_presets.insert(preset_id.clone());
+    pub fn open_profile_menu(&mut self) {
+        let names = self
+            .profiles_dir
+            .as_deref()
+            .map(profiles::list)
+            .unwrap_or_default();
+        self.profile_menu = Some(ProfileMenu {
+            names,
+            ..ProfileMenu::default()
+        });
+    }
 
-                // Expand features with non-defaults
-                if let Some(preset) = self.registry.get(&preset_id) {
-                    for feature in preset.features() {
-                        if self.has_feature_non_defaults(&preset_id, &feature.id) {
-                            self.expanded_features
-                                .insert((preset_id.clone(), feature.id.clone()));
-                        }
-                    }
+    pub fn close_profile_menu(&mut self) {
+        self.profile_menu = None;
+    }
+
+    /// Replace every preset's settings with the profile under the menu cursor
+    pub fn load_profile_from_menu(&mut self) {
+        let Some(menu) = &mut self.profile_menu else {
+            return;
+        };
+        let Some(name) = menu.names.get(menu.cursor).cloned() else {
+            return;
+        };
+        let Some(dir) = &self.profiles_dir else {
+            return;
+        };
+        match profiles::load(dir, &name) {
+            Ok(configs) => {
+                self.profile_menu = None;
+                self.extends.clear();
+                for preset in self.registry.all() {
+                    self.preset_configs
+                        .insert(preset.preset_id().to_string(), preset.default_config(false));
                 }
+                self.preset_configs.extend(configs);
+
+                self.expanded_presets.clear();
+                self.expanded_features.clear();
+                self.auto_expand_non_defaults();
+                self.regenerate_yaml();
+                self.update_current_item_description();
+                self.auto_save_ron();
             }
+            Err(e) => menu.error = Some(e.to_string()),
         }
+    }
 
-        self.rebuild_tree();
+    /// Save the current presets under the name typed in the menu, which stays open
+    pub fn save_profile_from_menu(&mut self) {
+        let Some(name) = self
+            .profile_menu
+            .as_ref()
+            .and_then(|menu| menu.new_name.clone())
+        else {
+            return;
+        };
+        let result = match &self.profiles_dir {
+            Some(dir) => self
+                .export_to_ron()
+                .and_then(|config| profiles::save(dir, &name, &config))
+                .map(|()| profiles::list(dir)),
+            None => Err(config_error("No home directory to save profiles in")),
+        };
+        let Some(menu) = &mut self.profile_menu else {
+            return;
+        };
+        match result {
+            Ok(names) => {
+                menu.cursor = names.iter().position(|known| *known == name).unwrap_or(0);
+                menu.names = names;
+                menu.new_name = None;
+                menu.error = None;
+            }
+            Err(e) => menu.error = Some(e.to_string()),
+        }
     }
 
-    /// Load RON configuration into TUI state
-    pub fn from_ron_file(path: &std::path::Path) -> Result<Self> {
-        use crate::config::{preset_choice_to_config, CciConfig};
-        use anyhow::Context;
+    /// Show what writing would change before anything is written
+    pub fn open_write_confirm(&mut self) {
+        match self.planned_writes() {
+            Ok(files) => {
+                self.write_confirm = Some(WriteConfirm {
+                    files,
+                    scroll: 0,
+                    backup: false,
+                })
+            }
+            Err(e) => self.generation_error = Some(e.to_string()),
+        }
+    }
 
-        let ron_str = std::fs::read_to_string(path)
-            .with_context(|| format!("Failed to read RON file: {}", path.display()))?;
+    pub fn cancel_write(&mut self) {
+        self.write_confirm = None;
+    }
 
-        let ron_config: CciConfig = match ron::Options::default()
-            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
-            .from_str(&ron_str)
+    /// Cycle the dependency update config written on save: off, Dependabot, Renovate
+    pub fn cycle_dependency_tool(&mut self) {
+        self.dependency_tool = match self.dependency_tool {
+            None => Some(DependencyTool::Dependabot),
+            Some(DependencyTool::Dependabot) => Some(DependencyTool::Renovate),
+            Some(DependencyTool::Renovate) => None,
+        };
+    }
+
+    /// The dependency update config to write, if one is enabled
+    ///
+    /// Writing GitHub workflows makes them an ecosystem to keep up to date too.
+    pub fn dependency_updates_file(&self) -> Result<Option<(PathBuf, String)>> {
+        let Some(tool) = self.dependency_tool else {
+            return Ok(None);
+        };
+
+        let mut ecosystems = detect_ecosystems(&self.working_dir)?;
+        if self.write_platforms().contains(&Platform::GitHub)
+            && !ecosystems.contains(&Ecosystem::GitHubActions)
+        {
+            ecosystems.push(Ecosystem::GitHubActions);
+        }
+
+        let updates = DependencyUpdates {
+            tool,
+            schedule: UpdateSchedule::Weekly,
+            ecosystems,
+        };
+        Ok(Some((tool.output_path(), updates.render()?)))
+    }
+
+    fn has_any_options_enabled(&self, config: &PresetConfig) -> bool {
+        let settings: HashSet<String> = self
+            .registry
+            .get(&config.preset_id)
+            .map(|preset| {
+                preset
+                    .features()
+                    .into_iter()
+                    .filter(|feature| feature.is_workflow_setting())
+                    .flat_map(|feature| feature.options.into_iter().map(|option| option.id))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // Free-form values (names, branch lists) and plain choices are always
+        // populated, so only toggles and optional selections count
+        config
+            .values
+            .iter()
+            .filter(|(id, _)| !settings.contains(*id))
+            .any(|(_, v)| match v {
+                OptionValue::Bool(b) => *b,
+                OptionValue::Enum { selected, variants } => {
+                    variants.iter().any(|v| v == "none") && selected != "none"
+                }
+                OptionValue::String(_) | OptionValue::Int(_) => false,
+            })
+    }
+
+    pub fn get_option_value(&self, preset_id: &str, option_id: &str) -> Option<&OptionValue> {
+        self.preset_configs
+            .get(preset_id)
+            .and_then(|config| config.get(option_id))
+    }
+
+    pub fn set_option_value(&mut self, preset_id: &str, option_id: &str, value: OptionValue) {
+        if let Some(config) = self.preset_configs.get_mut(preset_id) {
+            config.set(option_id.to_string(), value);
+        }
+    }
+
+    pub fn toggle_option(&mut self, preset_id: &str, option_id: &str) {
+        // A switch under one that's off goes on with the options it needs
+        if !self.is_option_available(preset_id, option_id) {
+            self.enable_parents(preset_id, option_id);
+            if let Some(OptionValue::Bool(_)) = self.get_option_value(preset_id, option_id) {
+                self.set_option_value(preset_id, option_id, OptionValue::Bool(true));
+                self.settle_rules(preset_id, Some(option_id));
+                self.regenerate_yaml();
+                self.auto_save_ron();
+                return;
+            }
+        }
+        if let Some(config) = self.preset_configs.get_mut(preset_id) {
+            if let Some(value) = config.get(option_id) {
+                let new_value = match value {
+                    OptionValue::Bool(b) => OptionValue::Bool(!b),
+                    OptionValue::Enum { selected, variants } => {
+                        // Cycle to next variant
+                        let current_index =
+                            variants.iter().position(|v| v == selected).unwrap_or(0);
+                        let next_index = (current_index + 1) % variants.len();
+                        OptionValue::Enum {
+                            selected: variants[next_index].clone(),
+                            variants: variants.clone(),
+                        }
+                    }
+                    other => other.clone(),
+                };
+                config.set(option_id.to_string(), new_value);
+            }
+        }
+        self.settle_rules(preset_id, Some(option_id));
+        self.regenerate_yaml();
+        self.auto_save_ron();
+    }
+
+    /// Turn options off until the preset's rules hold, favoring `keep`
+    fn settle_rules(&mut self, preset_id: &str, keep: Option<&str>) {
+        let Some(preset) = self.registry.get(preset_id).cloned() else {
+            return;
+        };
+        if let Some(config) = self.preset_configs.get_mut(preset_id) {
+            settle_rules(preset.as_ref(), config, keep);
+        }
+    }
+
+    /// The options `option_id` depends on, its parent first
+    pub fn option_parents(&self, preset_id: &str, option_id: &str) -> Vec<OptionMeta> {
+        let mut parents: Vec<OptionMeta> = Vec::new();
+        let mut parent = self
+            .option_meta(preset_id, option_id)
+            .and_then(|option| option.depends_on);
+        while let Some(parent_id) = parent {
+            let Some(meta) = self.option_meta(preset_id, &parent_id) else {
+                break;
+            };
+            if parents.iter().any(|known| known.id == meta.id) {
+                break;
+            }
+            parent = meta.depends_on.clone();
+            parents.push(meta);
+        }
+        parents
+    }
+
+    /// Whether the options `option_id` depends on are all enabled, so that it
+    /// applies; generation treats it as off otherwise
+    pub fn is_option_available(&self, preset_id: &str, option_id: &str) -> bool {
+        self.option_parents(preset_id, option_id)
+            .iter()
+            .all(|parent| {
+                !matches!(
+                    self.get_option_value(preset_id, &parent.id),
+                    Some(OptionValue::Bool(false))
+                )
+            })
+    }
+
+    /// Turn on the options `option_id` depends on, so a change to it shows
+    fn enable_parents(&mut self, preset_id: &str, option_id: &str) {
+        for parent in self.option_parents(preset_id, option_id) {
+            self.set_option_value(preset_id, &parent.id, OptionValue::Bool(true));
+        }
+    }
+
+    /// Metadata of a preset's option
+    fn option_meta(&self, preset_id: &str, option_id: &str) -> Option<OptionMeta> {
+        self.registry.get(preset_id).and_then(|preset| {
+            preset
+                .features()
+                .into_iter()
+                .flat_map(|feature| feature.options)
+                .find(|option| option.id == option_id)
+        })
+    }
+
+    /// Open the input overlay for the string or integer option under the cursor
+    ///
+    /// Returns whether there was one to edit.
+    pub fn open_text_input(&mut self) -> bool {
+        let Some(TreeItem::Option(preset_id, _, option_id)) = self.current_item().cloned() else {
+            return false;
+        };
+        let (text, integer) = match self.get_option_value(&preset_id, &option_id) {
+            Some(OptionValue::String(value)) => (value.clone(), false),
+            Some(OptionValue::Int(value)) => (value.to_string(), true),
+            _ => return false,
+        };
+        let meta = self.option_meta(&preset_id, &option_id);
+        let label = meta
+            .as_ref()
+            .map(|option| option.display_name.clone())
+            .unwrap_or_else(|| option_id.clone());
+        self.text_input = Some(TextInput {
+            preset_id,
+            option_id,
+            label,
+            cursor: text.chars().count(),
+            text,
+            integer,
+            placeholder: meta.and_then(|option| option.placeholder),
+            error: None,
+        });
+        true
+    }
+
+    /// Set the option to the typed value and close the overlay, unless the value isn't valid
+    pub fn submit_text_input(&mut self) {
+        let Some(input) = &self.text_input else {
+            return;
+        };
+        let validation = self
+            .option_meta(&input.preset_id, &input.option_id)
+            .map(|option| option.validation)
+            .unwrap_or_default();
+        let Some(input) = &mut self.text_input else {
+            return;
+        };
+        match input
+            .value()
+            .and_then(|value| validation.check(&value).map(|()| value))
+        {
+            Ok(value) => {
+                let (preset_id, option_id) = (input.preset_id.clone(), input.option_id.clone());
+                self.text_input = None;
+                self.enable_parents(&preset_id, &option_id);
+                self.set_option_value(&preset_id, &option_id, value);
+                self.regenerate_yaml();
+                self.auto_save_ron();
+            }
+            Err(error) => input.error = Some(error),
+        }
+    }
+
+    pub fn cancel_text_input(&mut self) {
+        self.text_input = None;
+    }
+
+    /// Open the popup for the enum option under the cursor, with its current choice highlighted
+    ///
+    /// Returns whether there was one to choose for.
+    pub fn open_enum_menu(&mut self) -> bool {
+        let Some(TreeItem::Option(preset_id, _, option_id)) = self.current_item().cloned() else {
+            return false;
+        };
+        let Some(OptionValue::Enum { selected, variants }) =
+            self.get_option_value(&preset_id, &option_id).cloned()
+        else {
+            return false;
+        };
+        let meta = self.option_meta(&preset_id, &option_id);
+        // Choices without metadata are shown by their IDs
+        let variants: Vec<VariantMeta> = variants
+            .iter()
+            .map(|id| {
+                meta.iter()
+                    .flat_map(|meta| &meta.variants)
+                    .find(|variant| variant.id == *id)
+                    .cloned()
+                    .unwrap_or_else(|| VariantMeta {
+                        id: id.clone(),
+                        display_name: id.clone(),
+                        description: String::new(),
+                    })
+            })
+            .collect();
+        self.enum_menu = Some(EnumMenu {
+            label: meta
+                .map(|meta| meta.display_name)
+                .unwrap_or_else(|| option_id.clone()),
+            cursor: variants
+                .iter()
+                .position(|variant| variant.id == selected)
+                .unwrap_or(0),
+            preset_id,
+            option_id,
+            variants,
+        });
+        true
+    }
+
+    /// Choose the highlighted variant and close the popup
+    pub fn select_from_enum_menu(&mut self) {
+        let Some(menu) = self.enum_menu.take() else {
+            return;
+        };
+        let Some(variant) = menu.variants.get(menu.cursor) else {
+            return;
+        };
+        let value = OptionValue::Enum {
+            selected: variant.id.clone(),
+            variants: menu
+                .variants
+                .iter()
+                .map(|variant| variant.id.clone())
+                .collect(),
+        };
+        self.enable_parents(&menu.preset_id, &menu.option_id);
+        self.set_option_value(&menu.preset_id, &menu.option_id, value);
+        self.settle_rules(&menu.preset_id, Some(&menu.option_id));
+        self.regenerate_yaml();
+        self.auto_save_ron();
+    }
+
+    pub fn close_enum_menu(&mut self) {
+        self.enum_menu = None;
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            preset_configs: self.preset_configs.clone(),
+            platforms: (self.target_platform, self.extra_platforms.clone()),
+            dependency_tool: self.dependency_tool,
+            file_naming: self.file_naming,
+        }
+    }
+
+    /// Remember what changed since `before` so it can be undone
+    ///
+    /// Nothing is recorded if nothing changed; anything that was undone can no
+    /// longer be redone once something else changes.
+    pub fn record_edit(&mut self, before: Snapshot) {
+        let mut presets: Vec<(PresetConfig, PresetConfig)> = before
+            .preset_configs
+            .into_iter()
+            .filter_map(|(id, old)| {
+                let new = self.preset_configs.get(&id)?;
+                let changed = old.values != new.values || old.explicitly_set != new.explicitly_set;
+                changed.then(|| (old, new.clone()))
+            })
+            .collect();
+        presets.sort_by(|(a, _), (b, _)| a.preset_id.cmp(&b.preset_id));
+        let platforms = (self.target_platform, self.extra_platforms.clone());
+        let edit = Edit {
+            presets,
+            platforms: (before.platforms != platforms).then_some((before.platforms, platforms)),
+            dependency_tool: (before.dependency_tool != self.dependency_tool)
+                .then_some((before.dependency_tool, self.dependency_tool)),
+            file_naming: (before.file_naming != self.file_naming)
+                .then_some((before.file_naming, self.file_naming)),
+        };
+        if edit.presets.is_empty()
+            && edit.platforms.is_none()
+            && edit.dependency_tool.is_none()
+            && edit.file_naming.is_none()
         {
+            return;
+        }
+
+        self.history.redo.clear();
+        self.history.undo.push_back(edit);
+        if self.history.undo.len() > HISTORY_LIMIT {
+            self.history.undo.pop_front();
+        }
+    }
+
+    /// Revert the last edit, returning whether there was one
+    pub fn undo(&mut self) -> bool {
+        let Some(edit) = self.history.undo.pop_back() else {
+            return false;
+        };
+        self.apply_edit(&edit, false);
+        self.history.redo.push(edit);
+        true
+    }
+
+    /// Make the last undone edit again, returning whether there was one
+    pub fn redo(&mut self) -> bool {
+        let Some(edit) = self.history.redo.pop() else {
+            return false;
+        };
+        self.apply_edit(&edit, true);
+        self.history.undo.push_back(edit);
+        true
+    }
+
+    /// Put `edit`'s after values in place, or its before values when undoing
+    fn apply_edit(&mut self, edit: &Edit, forward: bool) {
+        fn pick<T>(forward: bool, before: T, after: T) -> T {
+            if forward {
+                after
+            } else {
+                before
+            }
+        }
+
+        for (before, after) in &edit.presets {
+            let config = pick(forward, before, after).clone();
+            self.preset_configs.insert(config.preset_id.clone(), config);
+        }
+        if let Some((before, after)) = &edit.platforms {
+            let (platform, extras) = pick(forward, before, after).clone();
+            if platform != self.target_platform {
+                self.target_platform = platform;
+
+                // Reload existing YAML for the new platform
+                let output_path = self.working_dir.join(self.target_platform.output_path());
+                self.existing_yaml = std::fs::read_to_string(&output_path).ok();
+            }
+            self.extra_platforms = extras;
+        }
+        if let Some((before, after)) = edit.dependency_tool {
+            self.dependency_tool = pick(forward, before, after);
+        }
+        if let Some((before, after)) = edit.file_naming {
+            self.file_naming = pick(forward, before, after);
+        }
+
+        self.regenerate_yaml();
+        self.auto_save_ron();
+    }
+
+    pub fn cycle_platform(&mut self) {
+        self.step_platform(1);
+    }
+
+    pub fn cycle_platform_back(&mut self) {
+        self.step_platform(Platform::all().len() - 1);
+    }
+
+    /// Move the target platform `step` tabs to the right, wrapping around
+    fn step_platform(&mut self, step: usize) {
+        let platforms = Platform::all();
+        let current_index = platforms
+            .iter()
+            .position(|&p| p == self.target_platform)
+            .unwrap_or(0);
+        let next_index = (current_index + step) % platforms.len();
+        self.target_platform = platforms[next_index];
+
+        // Reload existing YAML for the new platform
+        let output_path = self.working_dir.join(self.target_platform.output_path());
+        self.existing_yaml = std::fs::read_to_string(&output_path).ok();
+
+        self.regenerate_yaml();
+        self.auto_save_ron();
+    }
+
+    pub fn toggle_preset(&mut self, preset_id: &str) {
+        // Check if any options are currently enabled
+        let config = match self.preset_configs.get(preset_id) {
+            Some(c) => c,
+            None => return,
+        };
+
+        let has_enabled = self.has_any_options_enabled(config);
+
+        // Get preset to access all its options
+        let preset = match self.registry.get(preset_id) {
+            Some(p) => p,
+            None => return,
+        };
+
+        // Toggle all boolean options for this preset, leaving workflow settings alone
+        for feature in preset.features() {
+            if feature.is_workflow_setting() {
+                continue;
+            }
+            for option in &feature.options {
+                if matches!(option.default_value, OptionValue::Bool(_)) {
+                    self.set_option_value(preset_id, &option.id, OptionValue::Bool(!has_enabled));
+                }
+            }
+        }
+        self.settle_rules(preset_id, None);
+
+        self.regenerate_yaml();
+        self.auto_save_ron();
+    }
+
+    /// Addon presets for the containers and infrastructure next to the
+    /// project, once each and only those not enabled already
+    fn facet_suggestions(&self) -> Vec<Suggestion> {
+        let mut suggestions: Vec<Suggestion> = Vec::new();
+        for facet in detect_facets(&self.working_dir) {
+            let preset_id = facet.facet.preset_id();
+            if self.registry.get(preset_id).is_some()
+                && !self.preset_enabled(preset_id)
+                && !suggestions
+                    .iter()
+                    .any(|suggestion| suggestion.preset_id == preset_id)
+            {
+                suggestions.push(Suggestion {
+                    preset_id: preset_id.to_string(),
+                    facet,
+                });
+            }
+        }
+        suggestions
+    }
+
+    fn preset_enabled(&self, preset_id: &str) -> bool {
+        self.preset_configs
+            .get(preset_id)
+            .is_some_and(|config| self.has_any_options_enabled(config))
+    }
+
+    /// Enable the preset the suggestion shown offers, deploying with the tool
+    /// that was noticed
+    pub fn accept_suggestion(&mut self) {
+        if self.suggestions.is_empty() {
+            return;
+        }
+        let suggestion = self.suggestions.remove(0);
+        let Some(preset) = self.registry.get(&suggestion.preset_id) else {
+            return;
+        };
+        let mut config = preset.default_config(true);
+        if let Some(command) = suggestion.facet.deploy_command() {
+            config.set("deploy_command".to_string(), OptionValue::String(command));
+        }
+        self.preset_configs
+            .insert(suggestion.preset_id.clone(), config);
+        self.expanded_presets.insert(suggestion.preset_id);
+        self.rebuild_tree();
+        self.regenerate_yaml();
+        self.update_current_item_description();
+        self.auto_save_ron();
+    }
+
+    /// Pass on the suggestion shown, or with `all` on every one left
+    pub fn dismiss_suggestion(&mut self, all: bool) {
+        if all {
+            self.suggestions.clear();
+        } else if !self.suggestions.is_empty() {
+            self.suggestions.remove(0);
+        }
+    }
+
+    /// The config detection picked for `preset_id`, before any edits
+    fn detected_config(&self, preset_id: &str) -> Option<PresetConfig> {
+        let preset = self.registry.get(preset_id)?;
+        Some(match self.matching_detection(preset.as_ref()) {
+            Some(detection) => preset.detected_config(detection),
+            None => preset.default_config(self.preset_matches(preset.as_ref())),
+        })
+    }
+
+    /// Whether `preset` suits anything detection found; presets from cci.ron
+    /// go by the project type alone
+    pub fn preset_matches(&self, preset: &dyn EditorPreset) -> bool {
+        match &self.detection {
+            Some(_) => self.matching_detection(preset).is_some(),
+            None => preset.matches_project(&self.project_type, &self.working_dir),
+        }
+    }
+
+    /// The first of the detections `preset` suits
+    fn matching_detection(&self, preset: &dyn EditorPreset) -> Option<&DetectionResult> {
+        self.detection
+            .iter()
+            .chain(&self.other_detections)
+            .find(|detection| preset.matches_detection(detection, &self.working_dir))
+    }
+
+    /// Put what `item` covers back the way detection left it: an option, every
+    /// option of a feature, or a whole preset
+    pub fn reset_item(&mut self, item: &TreeItem) {
+        let (preset_id, feature_id, option_id) = match item {
+            TreeItem::Preset(preset_id) => (preset_id, None, None),
+            TreeItem::Feature(preset_id, feature_id) => (preset_id, Some(feature_id), None),
+            TreeItem::Option(preset_id, feature_id, option_id) => {
+                (preset_id, Some(feature_id), Some(option_id))
+            }
+        };
+        let (Some(preset), Some(detected)) = (
+            self.registry.get(preset_id),
+            self.detected_config(preset_id),
+        ) else {
+            return;
+        };
+        let options: Vec<String> = match (feature_id, option_id) {
+            (_, Some(option_id)) => vec![option_id.clone()],
+            (Some(feature_id), None) => preset
+                .features()
+                .into_iter()
+                .filter(|feature| &feature.id == feature_id)
+                .flat_map(|feature| feature.options.into_iter().map(|option| option.id))
+                .collect(),
+            (None, None) => {
+                self.preset_configs.insert(preset_id.clone(), detected);
+                self.regenerate_yaml();
+                self.auto_save_ron();
+                return;
+            }
+        };
+
+        if let Some(config) = self.preset_configs.get_mut(preset_id) {
+            for option_id in options {
+                match detected.get(&option_id) {
+                    Some(value) => config.values.insert(option_id.clone(), value.clone()),
+                    None => config.values.remove(&option_id),
+                };
+                if detected.explicitly_set.contains(&option_id) {
+                    config.explicitly_set.insert(option_id);
+                } else {
+                    config.explicitly_set.remove(&option_id);
+                }
+            }
+        }
+        self.regenerate_yaml();
+        self.auto_save_ron();
+    }
+
+    /// Detect the project again, e.g. after adding a manifest, without
+    /// restarting; presets still at their detected defaults move to the new ones
+    pub fn redetect(&mut self) -> Result<()> {
+        let mut detections = DetectorRegistry::new().detect(&self.working_dir)?;
+        let detection = detections.remove(0);
+        // Presets from cci.ron weren't detected, so they're all the user's
+        let untouched: Vec<String> = if self.detection.is_some() {
+            self.preset_configs
+                .iter()
+                .filter(|(preset_id, config)| {
+                    self.detected_config(preset_id)
+                        .is_some_and(|detected| detected.values == config.values)
+                })
+                .map(|(preset_id, _)| preset_id.clone())
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        self.project_type = detection.project_type.clone();
+        if let Some(version) = &detection.language_version {
+            self.language_version = version.clone();
+        }
+        self.detection = Some(detection);
+        self.other_detections = detections;
+        self.suggestions = self.facet_suggestions();
+        for preset_id in untouched {
+            if let Some(detected) = self.detected_config(&preset_id) {
+                self.preset_configs.insert(preset_id, detected);
+            }
+        }
+        for preset in self.registry.all() {
+            if self.preset_matches(preset.as_ref()) {
+                self.expanded_presets.insert(preset.preset_id().to_string());
+            }
+        }
+
+        self.rebuild_tree();
+        self.regenerate_yaml();
+        self.update_current_item_description();
+        Ok(())
+    }
+
+    /// Every preset back to what detection picked, and the workflow settings
+    /// back to their defaults; the platforms shown and written are kept
+    pub fn reset_all(&mut self) {
+        let preset_ids: Vec<String> = self.preset_configs.keys().cloned().collect();
+        for preset_id in preset_ids {
+            if let Some(detected) = self.detected_config(&preset_id) {
+                self.preset_configs.insert(preset_id, detected);
+            }
+        }
+        self.dependency_tool = None;
+        self.file_naming = FileNaming::default();
+        self.hand_edits.clear();
+        self.regenerate_yaml();
+        self.auto_save_ron();
+    }
+
+    /// How many of a feature's boolean options are enabled, out of how many
+    /// it has, for the checkbox on its row
+    pub fn feature_enabled_count(&self, preset_id: &str, feature_id: &str) -> (usize, usize) {
+        let Some(feature) = self
+            .registry
+            .get(preset_id)
+            .and_then(|preset| preset.features().into_iter().find(|f| f.id == feature_id))
+        else {
+            return (0, 0);
+        };
+        let values: Vec<bool> = feature
+            .options
+            .iter()
+            .filter_map(
+                |option| match self.get_option_value(preset_id, &option.id) {
+                    Some(OptionValue::Bool(enabled)) => Some(*enabled),
+                    _ => None,
+                },
+            )
+            .collect();
+        let enabled = values.iter().filter(|enabled| **enabled).count();
+        (enabled, values.len())
+    }
+
+    /// Enable every boolean option of a feature, or disable them all if they
+    /// already are
+    pub fn toggle_feature(&mut self, preset_id: &str, feature_id: &str) {
+        let (enabled, total) = self.feature_enabled_count(preset_id, feature_id);
+        let Some(feature) = self
+            .registry
+            .get(preset_id)
+            .and_then(|preset| preset.features().into_iter().find(|f| f.id == feature_id))
+        else {
+            return;
+        };
+
+        for option in &feature.options {
+            if matches!(option.default_value, OptionValue::Bool(_)) {
+                self.set_option_value(preset_id, &option.id, OptionValue::Bool(enabled < total));
+            }
+        }
+        self.settle_rules(preset_id, None);
+
+        self.regenerate_yaml();
+        self.auto_save_ron();
+    }
+
+    pub fn open_platform_menu(&mut self) {
+        self.platform_menu_open = true;
+    }
+
+    pub fn close_platform_menu(&mut self) {
+        self.platform_menu_open = false;
+    }
+
+    pub fn select_platform_from_menu(&mut self) {
+        let platforms = Platform::all();
+        if let Some(&platform) = platforms.get(self.platform_menu_cursor) {
+            self.target_platform = platform;
+
+            // Reload existing YAML for the new platform
+            let output_path = self.working_dir.join(self.target_platform.output_path());
+            self.existing_yaml = std::fs::read_to_string(&output_path).ok();
+
+            self.regenerate_yaml();
+            self.auto_save_ron();
+        }
+        self.platform_menu_open = false;
+    }
+
+    pub fn update_current_item_description(&mut self) {
+        self.current_item_description = match self.current_item() {
+            Some(TreeItem::Preset(preset_id)) => self
+                .registry
+                .get(preset_id)
+                .map(|p| p.preset_description().to_string())
+                .unwrap_or_default(),
+            Some(TreeItem::Feature(preset_id, feature_id)) => self
+                .registry
+                .get(preset_id)
+                .and_then(|preset| {
+                    preset
+                        .features()
+                        .into_iter()
+                        .find(|f| &f.id == feature_id)
+                        .map(|f| f.description.clone())
+                })
+                .unwrap_or_default(),
+            Some(TreeItem::Option(preset_id, feature_id, option_id)) => self
+                .registry
+                .get(preset_id)
+                .and_then(|preset| {
+                    preset
+                        .features()
+                        .into_iter()
+                        .find(|f| &f.id == feature_id)
+                        .and_then(|f| {
+                            f.options
+                                .iter()
+                                .find(|o| &o.id == option_id)
+                                .map(|o| o.description.clone())
+                        })
+                })
+                .unwrap_or_default(),
+            None => String::new(),
+        };
+    }
+
+    pub fn scroll_preview_up(&mut self) {
+        self.preview_scroll = self.preview_scroll.saturating_sub(1);
+    }
+
+    pub fn scroll_preview_down(&mut self) {
+        self.preview_scroll = self.preview_scroll.saturating_add(1);
+    }
+
+    /// Scroll unwrapped preview lines left by [`HSCROLL_COLUMNS`]
+    pub fn scroll_preview_left(&mut self) {
+        self.preview_hscroll = self.preview_hscroll.saturating_sub(HSCROLL_COLUMNS);
+    }
+
+    /// Scroll unwrapped preview lines right; wrapped lines don't scroll sideways
+    pub fn scroll_preview_right(&mut self) {
+        if !self.preview_wrap || self.side_by_side {
+            self.preview_hscroll = self.preview_hscroll.saturating_add(HSCROLL_COLUMNS);
+        }
+    }
+
+    pub fn toggle_preview_wrap(&mut self) {
+        self.preview_wrap = !self.preview_wrap;
+        self.preview_hscroll = 0;
+    }
+
+    /// Check if an option value differs from its default value
+    pub fn is_option_non_default(&self, preset_id: &str, option_id: &str) -> bool {
+        let preset = match self.registry.get(preset_id) {
+            Some(p) => p,
+            None => return false,
+        };
+
+        let current_value = match self.get_option_value(preset_id, option_id) {
+            Some(v) => v,
+            None => return false,
+        };
+
+        // Find the option metadata to get default value
+        for feature in preset.features() {
+            for option in &feature.options {
+                if option.id == option_id {
+                    return current_value != &option.default_value;
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Check if a feature has any non-default options
+    pub fn has_feature_non_defaults(&self, preset_id: &str, feature_id: &str) -> bool {
+        let preset = match self.registry.get(preset_id) {
+            Some(p) => p,
+            None => return false,
+        };
+
+        let feature = match preset.features().into_iter().find(|f| f.id == feature_id) {
+            Some(f) => f,
+            None => return false,
+        };
+
+        for option in &feature.options {
+            if self.is_option_non_default(preset_id, &option.id) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Check if a preset has any non-default options
+    pub fn has_preset_non_defaults(&self, preset_id: &str) -> bool {
+        let preset = match self.registry.get(preset_id) {
+            Some(p) => p,
+            None => return false,
+        };
+
+        for feature in preset.features() {
+            if self.has_feature_non_defaults(preset_id, &feature.id) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Auto-expand presets and features that have non-default values
+    pub fn auto_expand_non_defaults(&mut self) {
+        let preset_ids: Vec<String> = self
+            .registry
+            .all()
+            .into_iter()
+            .map(|p| p.preset_id().to_string())
+            .collect();
+
+        for preset_id in preset_ids {
+            if self.has_preset_non_defaults(&preset_id) {
+                self.expanded_presets.insert(preset_id.clone());
+
+                // Expand features with non-defaults
+                if let Some(preset) = self.registry.get(&preset_id) {
+                    for feature in preset.features() {
+                        if self.has_feature_non_defaults(&preset_id, &feature.id) {
+                            self.expanded_features
+                                .insert((preset_id.clone(), feature.id.clone()));
+                        }
+                    }
+                }
+            }
+        }
+
+        self.rebuild_tree();
+    }
+
+    /// Start from presets reconstructed from existing workflows instead of the defaults
+    ///
+    /// Presets missing from the imports are disabled; when several workflows
+    /// import the same preset, the first one wins.
+    pub fn apply_imported(&mut self, imported: &[ImportedWorkflow]) {
+        if imported.is_empty() {
+            return;
+        }
+
+        for preset in self.registry.all() {
+            self.preset_configs
+                .insert(preset.preset_id().to_string(), preset.default_config(false));
+        }
+        let mut seen = HashSet::new();
+        for config in imported.iter().flat_map(|workflow| &workflow.configs) {
+            if seen.insert(config.preset_id.clone()) {
+                self.preset_configs
+                    .insert(config.preset_id.clone(), config.clone());
+            }
+        }
+
+        let suggestions = std::mem::take(&mut self.suggestions);
+        self.suggestions = suggestions
+            .into_iter()
+            .filter(|suggestion| !self.preset_enabled(&suggestion.preset_id))
+            .collect();
+
+        self.expanded_presets.clear();
+        self.expanded_features.clear();
+        self.auto_expand_non_defaults();
+        self.regenerate_yaml();
+        self.update_current_item_description();
+    }
+
+    /// Load a config file into TUI state, in the format its extension names
+    pub fn from_config_file(path: &std::path::Path) -> Result<Self> {
+        use crate::config::{preset_choice_to_config, ConfigFormat, PresetChoice};
+        use anyhow::Context;
+
+        let format = ConfigFormat::from_path(path);
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+
+        let ron_config = match format.parse(&source) {
             Ok(config) => config,
             Err(e) => {
-                eprintln!("Warning: Failed to parse RON configuration: {}", e);
-                eprintln!("This may be due to unknown or renamed fields in cci.ron");
+                eprintln!(
+                    "Warning: Failed to parse {} configuration: {}",
+                    format.name(),
+                    e
+                );
+                eprintln!(
+                    "This may be due to unknown or renamed fields in {}",
+                    path.display()
+                );
                 eprintln!(
                     "Please check that all field names match the current preset struct definitions"
                 );
-                return Err(anyhow::anyhow!("Failed to parse RON configuration: {}", e));
+                return Err(anyhow::anyhow!(
+                    "Failed to parse {} configuration: {}",
+                    format.name(),
+                    e
+                ));
+            }
+        };
+
+        let registry = Arc::new(build_registry());
+        let mut preset_configs = HashMap::new();
+        let mut extends = HashMap::new();
+        let detection_options = ron_config.detection;
+        let file_naming = ron_config.file_naming;
+        let working_dir = path
+            .parent()
+            .unwrap_or(std::path::Path::new("."))
+            .to_path_buf();
+
+        for preset_choice in ron_config.presets {
+            let (preset_id, config) = match &preset_choice {
+                PresetChoice::Extends(entry) => {
+                    let base = entry.base(&working_dir)?;
+                    let config = entry.apply(&base.config)?;
+                    extends.insert(entry.preset.clone(), base);
+                    (entry.preset.clone(), config)
+                }
+                choice => preset_choice_to_config(choice),
+            };
+            preset_configs.insert(preset_id, config);
+        }
+
+        let target_platform = existing_platform(&working_dir);
+
+        // Try to load existing YAML file
+        let output_path = working_dir.join(target_platform.output_path());
+        let existing_yaml = std::fs::read_to_string(&output_path).ok();
+
+        let mut state = Self {
+            project_type: ProjectType::PythonApp, // Default, doesn't affect RON-loaded config
+            language_version: "stable".to_string(),
+            detection: None,
+            other_detections: Vec::new(),
+            detection_options,
+            working_dir,
+            target_platform,
+            registry,
+            preset_configs,
+            extends,
+            expanded_presets: HashSet::new(),
+            expanded_features: HashSet::new(),
+            tree_items: Vec::new(),
+            tree_cursor: 0,
+            tree_offset: Cell::new(0),
+            platform_menu_open: false,
+            platform_menu_cursor: Platform::all()
+                .iter()
+                .position(|&p| p == target_platform)
+                .unwrap_or(0),
+            text_input: None,
+            enum_menu: None,
+            search: None,
+            write_confirm: None,
+            theme: Theme::default(),
+            keymap: Keymap::default(),
+            help_scroll: None,
+            profile_menu: None,
+            profiles_dir: profiles::profiles_dir(),
+            history: History::default(),
+            extra_platforms: HashSet::new(),
+            dependency_tool: None,
+            file_naming,
+            preview_scroll: 0,
+            side_by_side: false,
+            preview_wrap: true,
+            preview_hscroll: 0,
+            yaml_preview: String::new(),
+            preview_path: target_platform.output_path(),
+            extra_previews: Vec::new(),
+            generation_error: None,
+            platform_errors: HashMap::new(),
+            required_secrets: Vec::new(),
+            lint_issues: Vec::new(),
+            warnings: Vec::new(),
+            warnings_collapsed: false,
+            hand_edits: HashMap::new(),
+            edit_confirm: None,
+            session_restore: None,
+            suggestions: Vec::new(),
+            workspace: None,
+            existing_yaml,
+            existing_modified: None,
+            current_item_description: String::new(),
+            should_quit: false,
+            should_write: false,
+            should_copy: false,
+            should_edit: false,
+            defer_generation: false,
+            preview_pending: false,
+            preview_busy: false,
+            spinner_frame: 0,
+            preview_copied: false,
+        };
+
+        state.suggestions = state.facet_suggestions();
+        state.auto_expand_non_defaults();
+        state.regenerate_yaml();
+        state.update_current_item_description();
+        Ok(state)
+    }
+
+    /// Export current TUI state to RON configuration
+    pub fn export_to_ron(&self) -> Result<String> {
+        crate::config::to_ron_string(&self.export_config())
+    }
+
+    /// The config the current TUI state saves as
+    pub fn export_config(&self) -> crate::config::CciConfig {
+        use crate::config::{preset_config_to_choice, CciConfig, PresetChoice};
+
+        let mut ron_config = Vec::new();
+
+        for (preset_id, config) in &self.preset_configs {
+            if self.has_any_options_enabled(config) {
+                let preset_choice = match self.extends.get(preset_id) {
+                    Some(base) => PresetChoice::Extends(base.extends(config)),
+                    None => preset_config_to_choice(preset_id, config),
+                };
+                ron_config.push(preset_choice);
             }
+        }
+
+        CciConfig {
+            detection: self.detection_options.clone(),
+            file_naming: self.file_naming,
+            ..CciConfig::new(ron_config)
+        }
+    }
+
+    /// Save current state to a config file, in the format its extension names
+    pub fn save_to_config_file(&self, path: &std::path::Path) -> Result<()> {
+        use crate::config::ConfigFormat;
+        use anyhow::Context;
+
+        let text = ConfigFormat::from_path(path).write(&self.export_config())?;
+
+        std::fs::write(path, text)
+            .with_context(|| format!("Failed to write config file: {}", path.display()))?;
+
+        Ok(())
+    }
+
+    /// The choices made so far, to pick up from if the editor doesn't quit cleanly
+    pub fn session(&self) -> Session {
+        Session {
+            presets: self
+                .preset_configs
+                .iter()
+                .map(|(id, config)| (id.clone(), config.clone()))
+                .collect(),
+            platform: self.target_platform,
+            extra_platforms: self.extra_platforms.iter().copied().collect(),
+            dependency_tool: self.dependency_tool,
+            file_naming: self.file_naming,
+            hand_edits: self
+                .hand_edits
+                .iter()
+                .map(|(path, edit)| (path.clone(), edit.clone()))
+                .collect::<BTreeMap<_, _>>(),
+        }
+    }
+
+    /// Offer to put back a session left behind, unless it's what's here already
+    pub fn offer_session(&mut self, session: Session) {
+        let unchanged = match (session.to_ron(), self.session().to_ron()) {
+            (Ok(saved), Ok(current)) => saved == current,
+            _ => false,
+        };
+        if unchanged {
+            return;
+        }
+        let mut restored = self.clone();
+        restored.defer_generation = false;
+        restored.apply_session(&session);
+        self.session_restore = Some(SessionRestore {
+            session,
+            before: self.yaml_preview.clone(),
+            after: restored.yaml_preview,
+            scroll: 0,
+        });
+    }
+
+    /// Pick up where the last session left off
+    pub fn restore_session(&mut self) {
+        if let Some(restore) = self.session_restore.take() {
+            self.apply_session(&restore.session);
+            self.auto_save_ron();
+        }
+    }
+
+    /// Start over without the last session's choices
+    pub fn discard_session(&mut self) {
+        if self.session_restore.take().is_some() {
+            session::clear(&self.working_dir);
+        }
+    }
+
+    fn apply_session(&mut self, session: &Session) {
+        self.preset_configs.extend(session.presets.iter().cloned());
+        self.target_platform = session.platform;
+        self.extra_platforms = session.extra_platforms.iter().copied().collect();
+        self.extra_platforms.remove(&self.target_platform);
+        self.dependency_tool = session.dependency_tool;
+        self.file_naming = session.file_naming;
+        self.hand_edits = session
+            .hand_edits
+            .iter()
+            .map(|(path, edit)| (path.clone(), edit.clone()))
+            .collect();
+
+        self.expanded_presets.clear();
+        self.expanded_features.clear();
+        self.auto_expand_non_defaults();
+        self.regenerate_yaml();
+        self.update_current_item_description();
+    }
+
+    /// Automatically save the current state to cci.ron in the working
+    /// directory, or to the cci.yaml or cci.toml it has instead
+    pub fn auto_save_ron(&self) {
+        let config_path = locate_config(&self.working_dir.join("cci.ron"));
+
+        // Silently attempt to save - don't panic on errors
+        let _ = self.save_to_config_file(&config_path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_rust_library_enables_only_matching_presets() {
+        let dir = tempdir().unwrap();
+
+        let detection = DetectionResult {
+            project_type: ProjectType::RustLibrary,
+            confidence: 100,
+            language_version: Some("stable".to_string()),
+            metadata: HashMap::new(),
+        };
+
+        let state = EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+
+        // All presets should be shown in the tree
+        let preset_items: Vec<&str> = state
+            .tree_items
+            .iter()
+            .filter_map(|item| match item {
+                TreeItem::Preset(preset_id) => Some(preset_id.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(preset_items.len(), 10); // All 4 presets plus the six addons
+        assert!(preset_items.contains(&"rust"));
+        assert!(preset_items.contains(&"python-app"));
+        assert!(preset_items.contains(&"go-app"));
+        assert!(preset_items.contains(&"docker"));
+        // Addons come after every base preset
+        assert_eq!(
+            &preset_items[4..],
+            &[
+                "release",
+                "release-automation",
+                "code-scanning",
+                "nightly",
+                "deploy",
+                "notifications"
+            ]
+        );
+
+        // But only Rust options should be enabled by default
+        let rust_config = state.preset_configs.get("rust").unwrap();
+        assert!(rust_config.get_bool("enable_coverage"));
+        assert!(rust_config.get_bool("enable_linter"));
+
+        let python_config = state.preset_configs.get("python-app").unwrap();
+        assert!(!python_config.get_bool("enable_linter"));
+    }
+
+    #[test]
+    fn test_rust_binary_enables_only_matching_presets() {
+        let dir = tempdir().unwrap();
+
+        let detection = DetectionResult {
+            project_type: ProjectType::RustBinary,
+            confidence: 100,
+            language_version: Some("stable".to_string()),
+            metadata: HashMap::new(),
+        };
+
+        let state = EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+
+        let rust_config = state.preset_configs.get("rust").unwrap();
+        assert!(rust_config.get_bool("enable_linter"));
+        assert!(rust_config.get_bool("build_release"));
+
+        let python_config = state.preset_configs.get("python-app").unwrap();
+        assert!(!python_config.get_bool("enable_linter"));
+    }
+
+    #[test]
+    fn test_python_app_enables_only_matching_presets() {
+        let dir = tempdir().unwrap();
+
+        let detection = DetectionResult {
+            project_type: ProjectType::PythonApp,
+            confidence: 100,
+            language_version: Some("3.11".to_string()),
+            metadata: HashMap::new(),
+        };
+
+        let state = EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+
+        let rust_config = state.preset_configs.get("rust").unwrap();
+        assert!(!rust_config.get_bool("enable_coverage"));
+
+        let python_config = state.preset_configs.get("python-app").unwrap();
+        assert_ne!(python_config.get_enum("linter").as_deref(), Some("none"));
+        assert_ne!(python_config.get_enum("formatter").as_deref(), Some("none"));
+    }
+
+    #[test]
+    fn test_go_app_enables_only_matching_presets() {
+        let dir = tempdir().unwrap();
+
+        let detection = DetectionResult {
+            project_type: ProjectType::GoApp,
+            confidence: 100,
+            language_version: Some("1.21".to_string()),
+            metadata: HashMap::new(),
+        };
+
+        let state = EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+
+        let go_config = state.preset_configs.get("go-app").unwrap();
+        assert!(go_config.get_bool("enable_linter"));
+        assert!(go_config.get_bool("enable_security_scan"));
+    }
+
+    #[test]
+    fn test_docker_preset_shown_for_all_project_types() {
+        let project_types = vec![
+            ProjectType::RustLibrary,
+            ProjectType::RustBinary,
+            ProjectType::PythonApp,
+            ProjectType::GoApp,
+        ];
+
+        for project_type in project_types {
+            let dir = tempdir().unwrap();
+
+            let detection = DetectionResult {
+                project_type: project_type.clone(),
+                confidence: 100,
+                language_version: Some("stable".to_string()),
+                metadata: HashMap::new(),
+            };
+
+            let state =
+                EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+
+            let has_docker = state
+                .tree_items
+                .iter()
+                .any(|item| matches!(item, TreeItem::Preset(id) if id == "docker"));
+
+            assert!(
+                has_docker,
+                "Docker preset should be shown for {:?}",
+                project_type
+            );
+        }
+    }
+
+    #[test]
+    fn test_docker_enabled_with_docker_project_type() {
+        let dir = tempdir().unwrap();
+
+        let detection = DetectionResult {
+            project_type: ProjectType::DockerImage,
+            confidence: 100,
+            language_version: None,
+            metadata: HashMap::new(),
         };
 
-        let registry = Arc::new(build_registry());
-        let mut preset_configs = HashMap::new();
+        let state = EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
 
-        for preset_choice in ron_config {
-            let (preset_id, config) = preset_choice_to_config(&preset_choice);
-            preset_configs.insert(preset_id, config);
-        }
+        let docker_config = state.preset_configs.get("docker").unwrap();
+        assert!(docker_config.get_bool("enable_cache"));
+    }
 
-        let working_dir = path
-            .parent()
-            .unwrap_or(std::path::Path::new("."))
-            .to_path_buf();
-        let target_platform = Platform::GitHub; // Default platform
+    #[test]
+    fn test_every_detected_project_type_enables_its_presets() {
+        let dir = tempdir().unwrap();
 
-        // Try to load existing YAML file
-        let output_path = working_dir.join(target_platform.output_path());
-        let existing_yaml = std::fs::read_to_string(&output_path).ok();
+        let detections = |rust, docker| {
+            vec![
+                DetectionResult {
+                    project_type: ProjectType::RustLibrary,
+                    confidence: rust,
+                    language_version: Some("stable".to_string()),
+                    metadata: HashMap::new(),
+                },
+                DetectionResult {
+                    project_type: ProjectType::DockerImage,
+                    confidence: docker,
+                    language_version: None,
+                    metadata: HashMap::new(),
+                },
+            ]
+        };
 
-        let mut state = Self {
-            project_type: ProjectType::PythonApp, // Default, doesn't affect RON-loaded config
-            language_version: "stable".to_string(),
-            working_dir,
-            target_platform,
-            registry,
-            preset_configs,
-            expanded_presets: HashSet::new(),
-            expanded_features: HashSet::new(),
-            tree_items: Vec::new(),
-            tree_cursor: 0,
-            platform_menu_open: false,
-            platform_menu_cursor: 0,
-            preview_scroll: 0,
-            yaml_preview: String::new(),
-            generation_error: None,
-            existing_yaml,
-            current_item_description: String::new(),
-            should_quit: false,
-            should_write: false,
+        let state =
+            EditorState::from_detections(detections(95, 60), None, dir.path().to_path_buf())
+                .unwrap();
+
+        assert_eq!(state.project_type, ProjectType::RustLibrary);
+        assert!(state.preset_configs["rust"].get_bool("enable_linter"));
+        assert!(state.preset_configs["docker"].get_bool("enable_cache"));
+        assert!(state.expanded_presets.contains("docker"));
+        assert!(!state.preset_configs["python-app"].get_bool("enable_linter"));
+
+        // The tree leads with the presets of the surest detection
+        let position = |state: &EditorState, preset_id: &str| {
+            state
+                .tree_items
+                .iter()
+                .position(|item| *item == TreeItem::Preset(preset_id.to_string()))
+                .unwrap()
         };
+        assert!(position(&state, "rust") < position(&state, "docker"));
+        let state =
+            EditorState::from_detections(detections(60, 95), None, dir.path().to_path_buf())
+                .unwrap();
+        assert!(position(&state, "docker") < position(&state, "rust"));
+    }
 
-        state.auto_expand_non_defaults();
-        state.regenerate_yaml();
-        state.update_current_item_description();
-        Ok(state)
+    #[test]
+    fn test_starts_on_the_platform_already_in_use() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(".gitlab-ci.yml"),
+            "stages: [test]\ntest:\n  stage: test\n  script: [make test]\n",
+        )
+        .unwrap();
+        let detection = || DetectionResult {
+            project_type: ProjectType::GoApp,
+            confidence: 100,
+            language_version: None,
+            metadata: HashMap::new(),
+        };
+
+        let state =
+            EditorState::from_detection(detection(), None, dir.path().to_path_buf()).unwrap();
+        assert_eq!(state.target_platform, Platform::GitLab);
+        assert!(state.existing_yaml.is_some());
+
+        // Asking for a platform still wins
+        let state = EditorState::from_detection(
+            detection(),
+            Some("circleci".to_string()),
+            dir.path().to_path_buf(),
+        )
+        .unwrap();
+        assert_eq!(state.target_platform, Platform::CircleCI);
     }
 
-    /// Export current TUI state to RON configuration
-    pub fn export_to_ron(&self) -> Result<String> {
-        use crate::config::preset_config_to_choice;
+    #[test]
+    fn test_suggests_addons_for_containers_and_infrastructure() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("Dockerfile"), "FROM alpine\n").unwrap();
+        std::fs::create_dir_all(dir.path().join("k8s")).unwrap();
+        std::fs::write(
+            dir.path().join("k8s/deployment.yaml"),
+            "apiVersion: apps/v1\nkind: Deployment\n",
+        )
+        .unwrap();
+        let detection = || DetectionResult {
+            project_type: ProjectType::GoApp,
+            confidence: 100,
+            language_version: None,
+            metadata: HashMap::new(),
+        };
 
-        let mut ron_config = Vec::new();
+        let mut state =
+            EditorState::from_detection(detection(), None, dir.path().to_path_buf()).unwrap();
+        let offered: Vec<&str> = state
+            .suggestions
+            .iter()
+            .map(|suggestion| suggestion.preset_id.as_str())
+            .collect();
+        assert_eq!(offered, vec!["docker", "deploy"]);
+
+        state.accept_suggestion();
+        assert!(state.preset_enabled("docker"));
+        state.accept_suggestion();
+        assert!(state.suggestions.is_empty());
+        assert_eq!(
+            state.preset_configs["deploy"]
+                .get_string("deploy_command")
+                .as_deref(),
+            Some("kubectl apply -f k8s")
+        );
 
-        for (preset_id, config) in &self.preset_configs {
-            if self.has_any_options_enabled(config) {
-                let preset_choice = preset_config_to_choice(preset_id, config);
-                ron_config.push(preset_choice);
-            }
-        }
+        // Skipping them all leaves the presets off
+        let mut state =
+            EditorState::from_detection(detection(), None, dir.path().to_path_buf()).unwrap();
+        state.dismiss_suggestion(true);
+        assert!(state.suggestions.is_empty());
+        assert!(!state.preset_enabled("docker"));
+    }
+
+    #[test]
+    fn test_framework_metadata_tunes_defaults() {
+        let dir = tempdir().unwrap();
 
-        let pretty_config = ron::ser::PrettyConfig::new()
-            .depth_limit(4)
-            .separate_tuple_members(true)
-            .enumerate_arrays(false);
+        let detection = DetectionResult {
+            project_type: ProjectType::PythonApp,
+            confidence: 100,
+            language_version: Some("3.12".to_string()),
+            metadata: HashMap::from([
+                ("framework".to_string(), "fastapi".to_string()),
+                ("cli".to_string(), "typer".to_string()),
+                ("database".to_string(), "postgres".to_string()),
+            ]),
+        };
 
-        let ron_str = ron::ser::to_string_pretty(&ron_config, pretty_config)
-            .map_err(|e| anyhow::anyhow!("Failed to serialize to RON: {}", e))?;
+        let state = EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
 
-        Ok(ron_str)
+        let python = &state.preset_configs["python-app"];
+        assert_eq!(python.get_enum("database").as_deref(), Some("postgres"));
+        assert!(python.get_bool("enable_type_check"));
+        // A CLI gets releases
+        assert!(state.preset_configs["release"].get_bool("create_release"));
+        assert!(state.preset_matches(state.registry.get("release").unwrap().as_ref()));
     }
 
-    /// Save current state to a RON file
-    pub fn save_to_ron_file(&self, path: &std::path::Path) -> Result<()> {
-        use anyhow::Context;
+    #[test]
+    fn test_docker_disabled_for_non_docker_project() {
+        let dir = tempdir().unwrap();
 
-        let ron_str = self.export_to_ron()?;
+        let detection = DetectionResult {
+            project_type: ProjectType::RustLibrary,
+            confidence: 100,
+            language_version: Some("stable".to_string()),
+            metadata: HashMap::new(),
+        };
 
-        std::fs::write(path, ron_str)
-            .with_context(|| format!("Failed to write RON file: {}", path.display()))?;
+        let state = EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
 
-        Ok(())
+        let docker_config = state.preset_configs.get("docker").unwrap();
+        // Docker preset is available but not enabled by default for non-Docker projects
+        assert!(!docker_config.get_bool("enable_cache"));
     }
 
-    /// Automatically save the current state to cci.ron in the working directory
-    pub fn auto_save_ron(&self) {
-        let cci_ron_path = self.working_dir.join("cci.ron");
+    #[test]
+    fn test_docker_preset_available_for_all_projects() {
+        // Docker preset should be available (but not enabled) for all project types
+        // Users can manually enable it if they want to add Docker to their project
+        let dir = tempdir().unwrap();
 
-        // Silently attempt to save - don't panic on errors
-        let _ = self.save_to_ron_file(&cci_ron_path);
+        let detection = DetectionResult {
+            project_type: ProjectType::RustLibrary,
+            confidence: 100,
+            language_version: Some("stable".to_string()),
+            metadata: HashMap::new(),
+        };
+
+        let state = EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+
+        // Docker config should exist
+        let docker_config = state.preset_configs.get("docker");
+        assert!(docker_config.is_some(), "Docker preset should be available");
+
+        // But not enabled by default
+        assert!(!docker_config.unwrap().get_bool("enable_cache"));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::collections::HashMap;
-    use tempfile::tempdir;
+    #[test]
+    fn test_manually_enabling_non_detected_preset_generates_yaml() {
+        let dir = tempdir().unwrap();
+
+        let detection = DetectionResult {
+            project_type: ProjectType::RustLibrary,
+            confidence: 100,
+            language_version: Some("stable".to_string()),
+            metadata: HashMap::new(),
+        };
+
+        let mut state =
+            EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+
+        // The YAML should be for Rust initially
+        assert!(state.yaml_preview.contains("cargo"));
+
+        // Now manually disable Rust and enable Python App
+        use crate::editor::config::OptionValue;
+        state.set_option_value("rust", "enable_coverage", OptionValue::Bool(false));
+        state.set_option_value("rust", "enable_linter", OptionValue::Bool(false));
+        state.set_option_value("rust", "enable_format_check", OptionValue::Bool(false));
+        state.set_option_value("rust", "enable_security_scan", OptionValue::Bool(false));
+        state.set_option_value("rust", "build_release", OptionValue::Bool(false));
+
+        state.set_option_value("python-app", "enable_type_check", OptionValue::Bool(true));
+
+        state.regenerate_yaml();
+
+        // The YAML should now be for Python, even though it's not the detected type
+        assert!(
+            state.yaml_preview.contains("python")
+                || state.yaml_preview.contains("pytest")
+                || state.yaml_preview.contains("Setup Python")
+        );
+        assert!(!state.yaml_preview.contains("cargo"));
+    }
 
     #[test]
-    fn test_rust_library_enables_only_matching_presets() {
+    fn test_multiple_presets_are_combined() {
         let dir = tempdir().unwrap();
 
         let detection = DetectionResult {
             project_type: ProjectType::RustLibrary,
+            confidence: 100,
             language_version: Some("stable".to_string()),
             metadata: HashMap::new(),
         };
 
-        let state = EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        let mut state =
+            EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
 
-        // All presets should be shown in the tree
-        let preset_items: Vec<&str> = state
-            .tree_items
-            .iter()
-            .filter_map(|item| match item {
-                TreeItem::Preset(preset_id) => Some(preset_id.as_str()),
-                _ => None,
-            })
-            .collect();
+        // Enable both Rust and Python (unusual but allowed)
+        use crate::editor::config::OptionValue;
+        state.set_option_value("rust", "enable_linter", OptionValue::Bool(true));
+        state.set_option_value("python-app", "enable_type_check", OptionValue::Bool(true));
 
-        assert_eq!(preset_items.len(), 4); // All 4 presets
-        assert!(preset_items.contains(&"rust"));
-        assert!(preset_items.contains(&"python-app"));
-        assert!(preset_items.contains(&"go-app"));
-        assert!(preset_items.contains(&"docker"));
+        state.regenerate_yaml();
 
-        // But only Rust options should be enabled by default
-        let rust_config = state.preset_configs.get("rust").unwrap();
-        assert_eq!(rust_config.get_bool("enable_coverage"), true);
-        assert_eq!(rust_config.get_bool("enable_linter"), true);
+        // Both run on the same triggers, so they share the workflow
+        assert!(state.yaml_preview.contains("cargo"));
+        assert!(state.yaml_preview.contains("python-test"));
+        assert!(state.extra_previews.is_empty());
 
-        let python_config = state.preset_configs.get("python-app").unwrap();
-        assert_eq!(python_config.get_bool("enable_linter"), false);
+        // Docker also runs on tags, which a GitHub workflow can't give some jobs alone
+        state.set_option_value("docker", "enable_cache", OptionValue::Bool(true));
+        state.regenerate_yaml();
+        let paths: Vec<&PathBuf> = state.extra_previews.iter().map(|(path, _)| path).collect();
+        assert_eq!(paths, vec![&PathBuf::from(".github/workflows/docker.yml")]);
+
+        // GitLab jobs take their own rules, so everything goes in .gitlab-ci.yml
+        state.target_platform = Platform::GitLab;
+        state.regenerate_yaml();
+        assert!(state.extra_previews.is_empty());
+        assert!(state.yaml_preview.contains("docker/build"));
+        assert!(state.yaml_preview.contains("rust/test"));
+    }
+
+    #[test]
+    fn test_file_per_preset() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".github/workflows")).unwrap();
+        std::fs::write(dir.path().join(".github/workflows/rust.yml"), "name: old\n").unwrap();
+
+        let detection = DetectionResult {
+            project_type: ProjectType::RustLibrary,
+            confidence: 100,
+            language_version: Some("stable".to_string()),
+            metadata: HashMap::new(),
+        };
+        let mut state =
+            EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+
+        use crate::editor::config::OptionValue;
+        state.set_option_value("rust", "enable_linter", OptionValue::Bool(true));
+        state.set_option_value("python-app", "enable_type_check", OptionValue::Bool(true));
+        state.regenerate_yaml();
+        assert_eq!(
+            state.preview_path,
+            PathBuf::from(".github/workflows/ci.yml")
+        );
+        assert!(state.existing_yaml.is_none());
+
+        let snapshot = state.snapshot();
+        state.cycle_file_naming();
+        state.record_edit(snapshot);
+        assert_eq!(state.file_naming, FileNaming::PerPreset);
+        assert_eq!(
+            state.preview_path,
+            PathBuf::from(".github/workflows/rust.yml")
+        );
+        assert_eq!(state.existing_yaml.as_deref(), Some("name: old\n"));
+        let paths: Vec<&PathBuf> = state.extra_previews.iter().map(|(path, _)| path).collect();
+        assert_eq!(
+            paths,
+            vec![&PathBuf::from(".github/workflows/python-app.yml")]
+        );
+
+        state.cycle_file_naming();
+        assert_eq!(
+            state.preview_path,
+            PathBuf::from(".github/workflows/ci-rust.yml")
+        );
+        assert!(state.existing_yaml.is_none());
+
+        // The naming is kept in cci.ron, which the CLI generates from too
+        assert!(state
+            .export_to_ron()
+            .unwrap()
+            .contains("file_naming: prefixed"));
+        let reloaded = EditorState::from_config_file(&dir.path().join("cci.ron")).unwrap();
+        assert_eq!(reloaded.file_naming, FileNaming::Prefixed);
+
+        // GitLab reads one file whatever the naming
+        state.target_platform = Platform::GitLab;
+        state.regenerate_yaml();
+        assert_eq!(state.preview_path, PathBuf::from(".gitlab-ci.yml"));
+        assert!(state.extra_previews.is_empty());
+
+        state.target_platform = Platform::GitHub;
+        assert!(state.undo());
+        assert_eq!(state.file_naming, FileNaming::Combined);
+        assert_eq!(
+            state.preview_path,
+            PathBuf::from(".github/workflows/ci.yml")
+        );
+    }
+
+    #[test]
+    fn test_write_confirm_lists_changed_files() {
+        let dir = tempdir().unwrap();
+        let detection = DetectionResult {
+            project_type: ProjectType::RustLibrary,
+            confidence: 100,
+            language_version: Some("stable".to_string()),
+            metadata: HashMap::new(),
+        };
+        let mut state =
+            EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        state.cycle_dependency_tool();
+        state.regenerate_yaml();
+
+        state.open_write_confirm();
+        let confirm = state.write_confirm.clone().unwrap();
+        let paths: Vec<&PathBuf> = confirm.files.iter().map(|file| &file.path).collect();
+        assert_eq!(
+            paths,
+            vec![
+                &PathBuf::from(".github/workflows/ci.yml"),
+                &PathBuf::from(".github/dependabot.yml"),
+            ]
+        );
+        assert!(confirm.files.iter().all(|file| file.existing.is_none()));
+
+        // Once written, the workflow is up to date and the only change is by hand
+        let workflow = &confirm.files[0];
+        std::fs::create_dir_all(dir.path().join(".github/workflows")).unwrap();
+        std::fs::write(dir.path().join(&workflow.path), &workflow.content).unwrap();
+        std::fs::write(dir.path().join(".github/dependabot.yml"), "version: 2\n").unwrap();
+        state.cancel_write();
+        assert!(state.write_confirm.is_none());
+
+        state.open_write_confirm();
+        let files = &state.write_confirm.as_ref().unwrap().files;
+        assert!(files[0].is_unchanged());
+        assert!(!files[1].is_unchanged());
+        assert_eq!(files[1].existing.as_deref(), Some("version: 2\n"));
+    }
+
+    #[test]
+    fn test_foreign_pipelines_are_flagged() {
+        let dir = tempdir().unwrap();
+        let detection = DetectionResult {
+            project_type: ProjectType::RustLibrary,
+            confidence: 100,
+            language_version: Some("stable".to_string()),
+            metadata: HashMap::new(),
+        };
+        let mut state =
+            EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        assert!(state.existing_modified.is_none());
+
+        let workflows = dir.path().join(".github/workflows");
+        std::fs::create_dir_all(&workflows).unwrap();
+        std::fs::write(
+            workflows.join("ci.yml"),
+            "on: push\njobs:\n  build:\n    runs-on: ubuntu-latest\n    steps:\n    - run: make\n",
+        )
+        .unwrap();
+        state.regenerate_yaml();
+        assert!(state.existing_modified.is_some());
+        state.open_write_confirm();
+        let confirm = state.write_confirm.take().unwrap();
+        assert!(confirm.files[0].is_foreign());
+
+        // Once cci has written it, it's cci's
+        std::fs::write(workflows.join("ci.yml"), &confirm.files[0].content).unwrap();
+        state.toggle_option("rust", "enable_linter");
+        state.open_write_confirm();
+        let files = &state.write_confirm.as_ref().unwrap().files;
+        assert!(!files[0].is_unchanged());
+        assert!(!files[0].is_foreign());
+    }
+
+    #[test]
+    fn test_profiles_apply_across_repositories() {
+        use crate::editor::config::OptionValue;
+        let profiles = tempdir().unwrap();
+        let detection = || DetectionResult {
+            project_type: ProjectType::RustLibrary,
+            confidence: 100,
+            language_version: Some("stable".to_string()),
+            metadata: HashMap::new(),
+        };
+
+        let first = tempdir().unwrap();
+        let mut state =
+            EditorState::from_detection(detection(), None, first.path().to_path_buf()).unwrap();
+        state.profiles_dir = Some(profiles.path().to_path_buf());
+        state.set_option_value("docker", "enable_cache", OptionValue::Bool(true));
+        state.open_profile_menu();
+        assert!(state.profile_menu.as_ref().unwrap().names.is_empty());
+        state.profile_menu.as_mut().unwrap().new_name = Some("team".to_string());
+        state.save_profile_from_menu();
+        let menu = state.profile_menu.as_ref().unwrap();
+        assert_eq!(menu.names, vec!["team"]);
+        assert_eq!(menu.new_name, None);
+        assert_eq!(menu.error, None);
+
+        let second = tempdir().unwrap();
+        let mut state =
+            EditorState::from_detection(detection(), None, second.path().to_path_buf()).unwrap();
+        state.profiles_dir = Some(profiles.path().to_path_buf());
+        assert_ne!(
+            state.get_option_value("docker", "enable_cache"),
+            Some(&OptionValue::Bool(true))
+        );
+        state.open_profile_menu();
+        state.load_profile_from_menu();
+        assert!(state.profile_menu.is_none());
+        assert_eq!(
+            state.get_option_value("docker", "enable_cache"),
+            Some(&OptionValue::Bool(true))
+        );
+        assert!(second.path().join("cci.ron").exists());
     }
 
     #[test]
-    fn test_rust_binary_enables_only_matching_presets() {
+    fn test_dependency_updates_follow_detection() {
         let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("go.mod"), "module example.com/app\n").unwrap();
 
         let detection = DetectionResult {
-            project_type: ProjectType::RustBinary,
-            language_version: Some("stable".to_string()),
+            project_type: ProjectType::GoApp,
+            confidence: 100,
+            language_version: Some("1.22".to_string()),
             metadata: HashMap::new(),
         };
+        let mut state =
+            EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        assert!(state.dependency_updates_file().unwrap().is_none());
 
-        let state = EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        state.cycle_dependency_tool();
+        let (path, content) = state.dependency_updates_file().unwrap().unwrap();
+        assert_eq!(path, PathBuf::from(".github/dependabot.yml"));
+        assert!(content.contains("package-ecosystem: gomod"));
+        assert!(content.contains("package-ecosystem: github-actions"));
 
-        let rust_config = state.preset_configs.get("rust").unwrap();
-        assert_eq!(rust_config.get_bool("enable_linter"), true);
-        assert_eq!(rust_config.get_bool("build_release"), true);
+        state.cycle_dependency_tool();
+        let (path, _) = state.dependency_updates_file().unwrap().unwrap();
+        assert_eq!(path, PathBuf::from("renovate.json"));
 
-        let python_config = state.preset_configs.get("python-app").unwrap();
-        assert_eq!(python_config.get_bool("enable_linter"), false);
+        state.cycle_dependency_tool();
+        assert_eq!(state.dependency_tool, None);
     }
 
     #[test]
-    fn test_python_app_enables_only_matching_presets() {
+    fn test_text_input_edits_and_validates() {
         let dir = tempdir().unwrap();
-
         let detection = DetectionResult {
-            project_type: ProjectType::PythonApp,
-            language_version: Some("3.11".to_string()),
+            project_type: ProjectType::RustBinary,
+            confidence: 100,
+            language_version: Some("stable".to_string()),
             metadata: HashMap::new(),
         };
+        let mut state =
+            EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        state.set_option_value("rust", "workflow_name", OptionValue::String("CI".into()));
+        state.set_option_value("rust", "timeout_minutes", OptionValue::Int(30));
+        state.tree_items = vec![
+            TreeItem::Option("rust".into(), "triggers".into(), "workflow_name".into()),
+            TreeItem::Option(
+                "rust".into(),
+                "job_control".into(),
+                "timeout_minutes".into(),
+            ),
+            TreeItem::Option("rust".into(), "lint".into(), "enable_linter".into()),
+            TreeItem::Option("rust".into(), "triggers".into(), "schedule_cron".into()),
+            TreeItem::Option("rust".into(), "runner".into(), "runner_label".into()),
+        ];
 
-        let state = EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        assert!(state.open_text_input());
+        let input = state.text_input.as_mut().unwrap();
+        assert_eq!((input.label.as_str(), input.cursor), ("Workflow Name", 2));
+        input.move_home();
+        for c in "Rüst ".chars() {
+            input.insert(c);
+        }
+        input.move_end();
+        input.backspace();
+        input.insert('D');
+        state.submit_text_input();
+        assert!(state.text_input.is_none());
+        assert_eq!(
+            state.get_option_value("rust", "workflow_name"),
+            Some(&OptionValue::String("Rüst CD".into()))
+        );
 
-        let rust_config = state.preset_configs.get("rust").unwrap();
-        assert_eq!(rust_config.get_bool("enable_coverage"), false);
+        state.tree_cursor = 1;
+        assert!(state.open_text_input());
+        state.text_input.as_mut().unwrap().insert('x');
+        state.submit_text_input();
+        let input = state.text_input.as_mut().unwrap();
+        assert_eq!(input.error.as_deref(), Some("Enter a whole number"));
+        input.backspace();
+        input.move_home();
+        input.delete();
+        state.submit_text_input();
+        assert_eq!(
+            state.get_option_value("rust", "timeout_minutes"),
+            Some(&OptionValue::Int(0))
+        );
 
-        let python_config = state.preset_configs.get("python-app").unwrap();
-        assert_eq!(python_config.get_bool("enable_linter"), true);
-        assert_eq!(python_config.get_bool("enable_formatter"), true);
+        state.tree_cursor = 2;
+        assert!(!state.open_text_input());
+
+        // Values the option's rules don't allow are sent back with why
+        state.tree_cursor = 3;
+        assert!(state.open_text_input());
+        for c in "daily".chars() {
+            state.text_input.as_mut().unwrap().insert(c);
+        }
+        state.submit_text_input();
+        assert_eq!(
+            state.text_input.as_ref().unwrap().error.as_deref(),
+            Some("`daily` isn't five cron fields, like `0 3 * * 1`")
+        );
+        state.cancel_text_input();
+        assert_eq!(
+            state.get_option_value("rust", "schedule_cron"),
+            Some(&OptionValue::String(String::new()))
+        );
+
+        state.tree_cursor = 4;
+        assert!(state.open_text_input());
+        assert_eq!(
+            state.text_input.as_ref().unwrap().placeholder.as_deref(),
+            Some("self-hosted, linux")
+        );
     }
 
     #[test]
-    fn test_go_app_enables_only_matching_presets() {
+    fn test_enum_menu_lists_variants_with_descriptions() {
         let dir = tempdir().unwrap();
-
         let detection = DetectionResult {
-            project_type: ProjectType::GoApp,
-            language_version: Some("1.21".to_string()),
+            project_type: ProjectType::DockerImage,
+            confidence: 100,
+            language_version: None,
             metadata: HashMap::new(),
         };
+        let mut state =
+            EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        state.tree_items = vec![TreeItem::Option(
+            "docker".into(),
+            "registry".into(),
+            "registry".into(),
+        )];
+        let before = state.get_option_value("docker", "registry").cloned();
+
+        assert!(state.open_enum_menu());
+        let menu = state.enum_menu.as_ref().unwrap();
+        let names: Vec<&str> = menu
+            .variants
+            .iter()
+            .map(|variant| variant.display_name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "Docker Hub",
+                "GitHub Container Registry",
+                "None (build only)"
+            ]
+        );
+        assert_eq!(
+            menu.variants[1].description,
+            "Push to GitHub Container Registry (uses GITHUB_TOKEN)"
+        );
+        assert_eq!(menu.variants[menu.cursor].id, "none");
 
-        let state = EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        state.close_enum_menu();
+        assert_eq!(
+            state.get_option_value("docker", "registry").cloned(),
+            before
+        );
 
-        let go_config = state.preset_configs.get("go-app").unwrap();
-        assert_eq!(go_config.get_bool("enable_linter"), true);
-        assert_eq!(go_config.get_bool("enable_security"), true);
+        state.open_enum_menu();
+        state.enum_menu.as_mut().unwrap().cursor = 1;
+        state.select_from_enum_menu();
+        assert!(state.enum_menu.is_none());
+        assert_eq!(
+            state.preset_configs["docker"]
+                .get_enum("registry")
+                .as_deref(),
+            Some("github")
+        );
     }
 
     #[test]
-    fn test_docker_preset_shown_for_all_project_types() {
-        let project_types = vec![
-            ProjectType::RustLibrary,
-            ProjectType::RustBinary,
-            ProjectType::PythonApp,
-            ProjectType::GoApp,
-        ];
+    fn test_imported_workflow_replaces_detection() {
+        use crate::detection::import_github_workflows;
 
-        for project_type in project_types {
-            let dir = tempdir().unwrap();
+        // A Go workflow in a Rust project: the import wins over detection
+        let dir = tempdir().unwrap();
+        let workflows = dir.path().join(".github/workflows");
+        std::fs::create_dir_all(&workflows).unwrap();
+        let registry = build_registry();
+        let go = registry.get("go-app").unwrap();
+        let workflow = go
+            .generate(&go.default_config(true), Platform::GitHub, "1.22")
+            .unwrap();
+        std::fs::write(workflows.join("go.yml"), workflow).unwrap();
 
-            let detection = DetectionResult {
-                project_type: project_type.clone(),
-                language_version: Some("stable".to_string()),
-                metadata: HashMap::new(),
-            };
+        let detection = DetectionResult {
+            project_type: ProjectType::RustLibrary,
+            confidence: 100,
+            language_version: Some("1.22".to_string()),
+            metadata: HashMap::new(),
+        };
+        let mut state =
+            EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        let imported =
+            import_github_workflows(dir.path(), &state.registry, &state.language_version).unwrap();
+        assert_eq!(imported.len(), 1);
+        state.apply_imported(&imported);
+
+        assert!(state.preset_configs["go-app"].get_bool("enable_linter"));
+        assert!(!state.preset_configs["rust"].get_bool("enable_linter"));
+        assert!(state.yaml_preview.contains("go test"));
+    }
 
-            let state =
-                EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+    #[test]
+    fn test_search_filters_tree_and_jumps_between_matches() {
+        assert!(fuzzy_match("clpy", "Clippy Linter"));
+        assert!(fuzzy_match("code cov", "Code Coverage"));
+        assert!(!fuzzy_match("ypl", "Clippy Linter"));
 
-            let has_docker = state
-                .tree_items
-                .iter()
-                .any(|item| matches!(item, TreeItem::Preset(id) if id == "docker"));
+        let dir = tempdir().unwrap();
+        let detection = DetectionResult {
+            project_type: ProjectType::RustBinary,
+            confidence: 100,
+            language_version: Some("stable".to_string()),
+            metadata: HashMap::new(),
+        };
+        let mut state =
+            EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        let unfiltered = state.tree_items.clone();
 
-            assert!(
-                has_docker,
-                "Docker preset should be shown for {:?}",
-                project_type
-            );
+        state.open_search();
+        for c in "clippy".chars() {
+            state.search_insert(c);
         }
+        let clippy = TreeItem::Option("rust".into(), "linting".into(), "enable_linter".into());
+        assert!(state.tree_items.starts_with(&[
+            TreeItem::Preset("rust".into()),
+            TreeItem::Feature("rust".into(), "linting".into()),
+            clippy.clone(),
+        ]));
+        assert_eq!(state.current_item(), Some(&clippy));
+        state.confirm_search();
+        assert!(!state.search.as_ref().unwrap().typing);
+
+        // Clearing the query shows every preset again
+        state.open_search();
+        for _ in 0.."clippy".len() {
+            state.search_backspace();
+        }
+        state.confirm_search();
+        assert!(state.search.is_none());
+        assert_eq!(state.current_item(), Some(&clippy));
+
+        state.open_search();
+        for c in "testing".chars() {
+            state.search_insert(c);
+        }
+        let matches = state.search_matches();
+        assert!(matches.len() > 1, "{:?}", state.tree_items);
+        // Typing moves to the first match after the cursor; n/N wrap around
+        let at = matches
+            .iter()
+            .position(|&i| i == state.tree_cursor)
+            .unwrap();
+        state.jump_to_match(true);
+        assert_eq!(state.tree_cursor, matches[(at + 1) % matches.len()]);
+        state.jump_to_match(false);
+        state.jump_to_match(false);
+        assert_eq!(
+            state.tree_cursor,
+            matches[(at + matches.len() - 1) % matches.len()]
+        );
+
+        state.clear_search();
+        assert!(unfiltered
+            .iter()
+            .all(|item| state.tree_items.contains(item)));
     }
 
     #[test]
-    fn test_docker_enabled_with_docker_project_type() {
+    fn test_undo_and_redo_edits() {
         let dir = tempdir().unwrap();
-
         let detection = DetectionResult {
-            project_type: ProjectType::DockerImage,
-            language_version: None,
+            project_type: ProjectType::RustBinary,
+            confidence: 100,
+            language_version: Some("stable".to_string()),
             metadata: HashMap::new(),
         };
+        let mut state =
+            EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        assert!(!state.undo());
+        let initial = state.preset_configs["rust"].clone();
+
+        let before = state.snapshot();
+        state.toggle_option("rust", "enable_linter");
+        state.record_edit(before);
+        let before = state.snapshot();
+        state.cycle_platform();
+        state.record_edit(before);
+        // Keys that change nothing aren't recorded
+        let before = state.snapshot();
+        state.scroll_preview_down();
+        state.record_edit(before);
+        assert_eq!(state.history.undo.len(), 2);
+
+        assert!(state.undo());
+        assert_eq!(state.target_platform, Platform::GitHub);
+        assert!(state.undo());
+        assert!(!state.undo());
+        assert_eq!(state.preset_configs["rust"].values, initial.values);
+        assert_eq!(
+            state.preset_configs["rust"].explicitly_set,
+            initial.explicitly_set
+        );
 
-        let state = EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
-
-        let docker_config = state.preset_configs.get("docker").unwrap();
-        assert_eq!(docker_config.get_bool("enable_cache"), true);
+        assert!(state.redo());
+        assert_eq!(
+            state.get_option_value("rust", "enable_linter"),
+            Some(&OptionValue::Bool(false))
+        );
+        // A new edit drops what was left to redo
+        let before = state.snapshot();
+        state.toggle_preset("rust");
+        state.record_edit(before);
+        assert!(!state.redo());
+
+        for _ in 0..HISTORY_LIMIT + 10 {
+            let before = state.snapshot();
+            state.cycle_dependency_tool();
+            state.record_edit(before);
+        }
+        assert_eq!(state.history.undo.len(), HISTORY_LIMIT);
     }
 
     #[test]
-    fn test_docker_disabled_for_non_docker_project() {
+    fn test_preview_tabs_cover_every_platform() {
         let dir = tempdir().unwrap();
-
         let detection = DetectionResult {
-            project_type: ProjectType::RustLibrary,
+            project_type: ProjectType::RustBinary,
+            confidence: 100,
             language_version: Some("stable".to_string()),
             metadata: HashMap::new(),
         };
+        let mut state =
+            EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        assert!(
+            state.platform_errors.is_empty(),
+            "{:?}",
+            state.platform_errors
+        );
 
-        let state = EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
-
-        let docker_config = state.preset_configs.get("docker").unwrap();
-        // Docker preset is available but not enabled by default for non-Docker projects
-        assert_eq!(docker_config.get_bool("enable_cache"), false);
+        state.cycle_platform_back();
+        assert_eq!(state.target_platform, Platform::Jenkins);
+        assert!(state.yaml_preview.contains("pipeline"));
+        state.cycle_platform();
+        assert_eq!(state.target_platform, Platform::GitHub);
     }
 
     #[test]
-    fn test_docker_preset_available_for_all_projects() {
-        // Docker preset should be available (but not enabled) for all project types
-        // Users can manually enable it if they want to add Docker to their project
+    fn test_warnings_follow_the_configuration() {
         let dir = tempdir().unwrap();
-
         let detection = DetectionResult {
             project_type: ProjectType::RustLibrary,
+            confidence: 100,
             language_version: Some("stable".to_string()),
             metadata: HashMap::new(),
         };
+        let mut state =
+            EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        use crate::editor::config::OptionValue;
+        state.set_option_value("rust", "enable_coverage", OptionValue::Bool(true));
+        state.regenerate_yaml();
+        assert!(state
+            .warnings
+            .iter()
+            .any(|w| w.kind == WarningKind::Secret && w.subject == "CODECOV_TOKEN"));
+        assert!(!state
+            .warnings
+            .iter()
+            .any(|w| w.kind == WarningKind::Platform));
 
-        let state = EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        // GitLab can't split presets into files of their own
+        state.file_naming = FileNaming::PerPreset;
+        state.target_platform = Platform::GitLab;
+        state.regenerate_yaml();
+        let platform: Vec<&Warning> = state
+            .warnings
+            .iter()
+            .filter(|w| w.kind == WarningKind::Platform)
+            .collect();
+        assert_eq!(platform.len(), 1, "{:?}", state.warnings);
+        assert!(platform[0].message.contains("GitLab"));
+        // Listed by kind, secrets last
+        assert!(state.warnings.is_sorted_by_key(|w| w.kind));
+    }
 
-        // Docker config should exist
-        let docker_config = state.preset_configs.get("docker");
-        assert!(docker_config.is_some(), "Docker preset should be available");
+    #[test]
+    fn test_reset_to_detected_defaults() {
+        let dir = tempdir().unwrap();
+        let detection = DetectionResult {
+            project_type: ProjectType::RustBinary,
+            confidence: 100,
+            language_version: Some("stable".to_string()),
+            metadata: HashMap::new(),
+        };
+        let mut state =
+            EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        let initial = state.preset_configs["rust"].clone();
+        let initial_yaml = state.yaml_preview.clone();
+
+        state.toggle_option("rust", "enable_linter");
+        state.toggle_option("rust", "enable_coverage");
+        state.reset_item(&TreeItem::Option(
+            "rust".to_string(),
+            "linting".to_string(),
+            "enable_linter".to_string(),
+        ));
+        assert_eq!(
+            state.get_option_value("rust", "enable_linter"),
+            initial.get("enable_linter")
+        );
+        // Other features keep their changes
+        assert_ne!(
+            state.get_option_value("rust", "enable_coverage"),
+            initial.get("enable_coverage")
+        );
 
-        // But not enabled by default
-        assert_eq!(docker_config.unwrap().get_bool("enable_cache"), false);
+        state.reset_item(&TreeItem::Feature(
+            "rust".to_string(),
+            "testing".to_string(),
+        ));
+        assert_eq!(state.preset_configs["rust"].values, initial.values);
+        assert_eq!(
+            state.preset_configs["rust"].explicitly_set,
+            initial.explicitly_set
+        );
+        assert_eq!(state.yaml_preview, initial_yaml);
+
+        state.toggle_preset("rust");
+        state.reset_item(&TreeItem::Preset("rust".to_string()));
+        assert_eq!(state.preset_configs["rust"].values, initial.values);
+
+        // Everything, including workflow settings, and undo brings it back
+        state.toggle_preset("rust");
+        state.cycle_file_naming();
+        let before = state.snapshot();
+        state.reset_all();
+        state.record_edit(before);
+        assert_eq!(state.preset_configs["rust"].values, initial.values);
+        assert_eq!(state.file_naming, FileNaming::Combined);
+        assert_eq!(state.yaml_preview, initial_yaml);
+        assert!(state.undo());
+        assert_eq!(state.file_naming, FileNaming::PerPreset);
+        assert_ne!(state.preset_configs["rust"].values, initial.values);
     }
 
     #[test]
-    fn test_manually_enabling_non_detected_preset_generates_yaml() {
+    fn test_toggle_feature_switches_all_its_options() {
         let dir = tempdir().unwrap();
-
         let detection = DetectionResult {
             project_type: ProjectType::RustLibrary,
+            confidence: 100,
             language_version: Some("stable".to_string()),
             metadata: HashMap::new(),
         };
+        let mut state =
+            EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        use crate::editor::config::OptionValue;
+        let options = ["read_only_permissions", "cancel_in_progress", "pin_actions"];
+        for option in options {
+            state.set_option_value("rust", option, OptionValue::Bool(false));
+        }
+        assert_eq!(state.feature_enabled_count("rust", "github"), (0, 3));
+
+        state.set_option_value("rust", "pin_actions", OptionValue::Bool(true));
+        assert_eq!(state.feature_enabled_count("rust", "github"), (1, 3));
+        // Some enabled: all of them go on, then all off
+        state.toggle_feature("rust", "github");
+        assert_eq!(state.feature_enabled_count("rust", "github"), (3, 3));
+        state.toggle_feature("rust", "github");
+        assert_eq!(state.feature_enabled_count("rust", "github"), (0, 3));
+
+        // Features of only values to fill in have nothing to toggle
+        assert_eq!(state.feature_enabled_count("rust", "caching").1, 0);
+    }
 
+    #[test]
+    fn test_dependent_options_follow_their_parent() {
+        let dir = tempdir().unwrap();
+        let detection = DetectionResult {
+            project_type: ProjectType::RustLibrary,
+            confidence: 100,
+            language_version: Some("stable".to_string()),
+            metadata: HashMap::new(),
+        };
         let mut state =
             EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        use crate::editor::config::OptionValue;
+        assert!(state.is_option_available("rust", "coverage_provider"));
+        state.toggle_option("rust", "enable_coverage");
+        assert!(!state.is_option_available("rust", "coverage_provider"));
+        let parents = state.option_parents("rust", "coverage_provider");
+        assert_eq!(parents.len(), 1);
+        assert_eq!(parents[0].id, "enable_coverage");
+
+        // Choosing a provider turns coverage back on
+        state.tree_items = vec![TreeItem::Option(
+            "rust".to_string(),
+            "testing".to_string(),
+            "coverage_provider".to_string(),
+        )];
+        state.tree_cursor = 0;
+        assert!(state.open_enum_menu());
+        state.select_from_enum_menu();
+        assert_eq!(
+            state.get_option_value("rust", "enable_coverage"),
+            Some(&OptionValue::Bool(true))
+        );
 
-        // The YAML should be for Rust initially
-        assert!(state.yaml_preview.contains("cargo"));
+        // Switching on an option under one that's off switches on both
+        state.set_option_value("code-scanning", "enable_scanning", OptionValue::Bool(false));
+        state.set_option_value("code-scanning", "fail_on_findings", OptionValue::Bool(true));
+        assert!(!state.is_option_available("code-scanning", "fail_on_findings"));
+        state.toggle_option("code-scanning", "fail_on_findings");
+        assert_eq!(
+            state.get_option_value("code-scanning", "enable_scanning"),
+            Some(&OptionValue::Bool(true))
+        );
+        assert_eq!(
+            state.get_option_value("code-scanning", "fail_on_findings"),
+            Some(&OptionValue::Bool(true))
+        );
+    }
 
-        // Now manually disable Rust and enable Python App
-        use crate::editor::config::OptionValue;
-        state.set_option_value("rust", "enable_coverage", OptionValue::Bool(false));
-        state.set_option_value("rust", "enable_linter", OptionValue::Bool(false));
-        state.set_option_value("rust", "enable_formatter", OptionValue::Bool(false));
-        state.set_option_value("rust", "enable_security", OptionValue::Bool(false));
-        state.set_option_value("rust", "build_release", OptionValue::Bool(false));
+    #[test]
+    fn test_required_and_conflicting_options_settle() {
+        use crate::editor::config::{validate_config, OptionValue};
+        use crate::editor::registry::builtin_registry;
+        use crate::presets::custom::CustomPreset;
 
-        state.set_option_value("python-app", "enable_linter", OptionValue::Bool(true));
-        state.set_option_value("python-app", "enable_formatter", OptionValue::Bool(true));
+        let dir = tempdir().unwrap();
+        let detection = DetectionResult {
+            project_type: ProjectType::RustBinary,
+            confidence: 100,
+            language_version: Some("stable".to_string()),
+            metadata: HashMap::new(),
+        };
+        let mut state =
+            EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
 
-        state.regenerate_yaml();
+        // Turning off what an option requires turns it off too
+        state.set_option_value("code-scanning", "enable_scanning", OptionValue::Bool(true));
+        state.set_option_value("code-scanning", "fail_on_findings", OptionValue::Bool(true));
+        state.toggle_option("code-scanning", "enable_scanning");
+        assert_eq!(
+            state.get_option_value("code-scanning", "fail_on_findings"),
+            Some(&OptionValue::Bool(false))
+        );
 
-        // The YAML should now be for Python, even though it's not the detected type
-        assert!(
-            state.yaml_preview.contains("python")
-                || state.yaml_preview.contains("pytest")
-                || state.yaml_preview.contains("Setup Python")
+        let path = dir.path().join("lint.toml");
+        std::fs::write(
+            &path,
+            r#"
+id = "acme-lint"
+name = "Acme Lint"
+
+[[features]]
+id = "lint"
+
+[[features.options]]
+id = "eslint"
+default = true
+conflicts = ["biome"]
+
+[[features.options]]
+id = "autofix"
+default = true
+requires = "eslint"
+
+[[features.options]]
+id = "biome"
+default = false
+
+[[jobs]]
+id = "lint"
+steps = [{ run = "npm run lint" }]
+"#,
+        )
+        .unwrap();
+        let preset = Arc::new(CustomPreset::load(&path).unwrap());
+        let mut registry = builtin_registry();
+        registry.register(preset.clone());
+        state.registry = Arc::new(registry);
+        state
+            .preset_configs
+            .insert("acme-lint".to_string(), preset.default_config(true));
+
+        // Turning one on turns off what it conflicts with, and so what needs that
+        state.toggle_option("acme-lint", "biome");
+        let config = &state.preset_configs["acme-lint"];
+        assert!(!config.get_bool("eslint"));
+        assert!(!config.get_bool("autofix"));
+        assert!(config.get_bool("biome"));
+        assert!(validate_config(preset.as_ref(), config).is_empty());
+
+        let mut config = config.clone();
+        config.set("eslint".to_string(), OptionValue::Bool(true));
+        config.set("autofix".to_string(), OptionValue::Bool(true));
+        assert_eq!(
+            validate_config(preset.as_ref(), &config),
+            vec!["eslint (`eslint`) and biome (`biome`) of `acme-lint` can't be on together; turn one off"]
+        );
+        config.set("biome".to_string(), OptionValue::Bool(false));
+        config.set("eslint".to_string(), OptionValue::Bool(false));
+        assert_eq!(
+            validate_config(preset.as_ref(), &config),
+            vec!["autofix (`autofix`) of `acme-lint` requires eslint (`eslint`); turn it on or this off"]
         );
-        assert!(!state.yaml_preview.contains("cargo"));
     }
 
     #[test]
-    fn test_multiple_presets_first_enabled_wins() {
+    fn test_redetect_moves_untouched_presets() {
         let dir = tempdir().unwrap();
+        let detection = DetectionResult {
+            project_type: ProjectType::GoApp,
+            confidence: 100,
+            language_version: Some("1.22".to_string()),
+            metadata: HashMap::new(),
+        };
+        let mut state =
+            EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        // Nothing to detect yet
+        assert!(state.redetect().is_err());
+        assert_eq!(state.project_type, ProjectType::GoApp);
+
+        use crate::editor::config::OptionValue;
+        state.set_option_value("docker", "enable_cache", OptionValue::Bool(false));
+        let docker = state.preset_configs["docker"].clone();
+        std::fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n\n[lib]\n",
+        )
+        .unwrap();
+        state.redetect().unwrap();
+
+        assert_eq!(state.project_type, ProjectType::RustLibrary);
+        let detection = state.detection.as_ref().unwrap();
+        assert_eq!(
+            detection.metadata.get("name").map(String::as_str),
+            Some("demo")
+        );
+        assert!(state.has_any_options_enabled(&state.preset_configs["rust"]));
+        assert!(!state.has_any_options_enabled(&state.preset_configs["go-app"]));
+        // Edited presets stay as they were
+        assert_eq!(state.preset_configs["docker"].values, docker.values);
+        assert!(state.yaml_preview.contains("cargo"));
+    }
 
+    #[test]
+    fn test_hand_edits_are_kept_over_regeneration() {
+        let dir = tempdir().unwrap();
         let detection = DetectionResult {
             project_type: ProjectType::RustLibrary,
+            confidence: 100,
             language_version: Some("stable".to_string()),
             metadata: HashMap::new(),
         };
-
         let mut state =
             EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        let before = state.editable_preview().unwrap();
+        assert!(before.contains("# cci:managed"));
+        let custom =
+            "  custom:\n    runs-on: ubuntu-latest\n    steps:\n    - run: echo polished\n";
+        let after = before.replacen("\njobs:\n", &format!("\njobs:\n{}", custom), 1);
+
+        // Nothing changed, nothing to review
+        state.review_hand_edit(before.clone(), before.clone());
+        assert!(state.edit_confirm.is_none());
+        state.review_hand_edit(before.clone(), after.clone());
+        state.discard_hand_edit();
+        assert!(!state.yaml_preview.contains("echo polished"));
+
+        state.review_hand_edit(before.clone(), after);
+        state.keep_hand_edit();
+        assert!(state.yaml_preview.contains("echo polished"));
+        assert!(!state.yaml_preview.contains("# cci:managed"));
+        state.toggle_option("rust", "enable_linter");
+        assert!(state.yaml_preview.contains("echo polished"));
+        let written = state.planned_writes().unwrap();
+        assert!(written[0].content.contains("echo polished"));
+        // Editing again starts from the edited file
+        assert!(state.editable_preview().unwrap().contains("echo polished"));
+
+        // Edits that don't parse are turned away
+        let broken = before.replacen("jobs:", "jobs: [", 1);
+        state.review_hand_edit(before, broken);
+        state.keep_hand_edit();
+        assert!(state.generation_error.is_none());
+        assert!(state.yaml_preview.contains("echo polished"));
+        assert!(state.current_item_description.contains("not kept"));
+
+        state.reset_all();
+        assert!(!state.yaml_preview.contains("echo polished"));
+    }
 
-        // Enable both Rust and Python (unusual but allowed)
-        use crate::editor::config::OptionValue;
-        state.set_option_value("rust", "enable_linter", OptionValue::Bool(true));
-        state.set_option_value("python-app", "enable_linter", OptionValue::Bool(true));
+    #[test]
+    fn test_deferred_generation_catches_up() {
+        let dir = tempdir().unwrap();
+        let detection = DetectionResult {
+            project_type: ProjectType::RustLibrary,
+            confidence: 100,
+            language_version: Some("stable".to_string()),
+            metadata: HashMap::new(),
+        };
+        let mut state =
+            EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        state.defer_generation = true;
+        assert!(state.start_generation().is_none());
+
+        let before = state.yaml_preview.clone();
+        state.toggle_option("rust", "enable_linter");
+        assert!(state.preview_pending);
+        assert_eq!(state.yaml_preview, before);
+
+        let mut job = state.start_generation().unwrap();
+        assert!(!state.preview_pending);
+        job.generate_preview();
+        state.finish_generation(job);
+        assert_ne!(state.yaml_preview, before);
+        assert!(state.start_generation().is_none());
+    }
 
+    #[test]
+    fn test_sessions_pick_up_where_they_left_off() {
+        let dir = tempdir().unwrap();
+        let detection = DetectionResult {
+            project_type: ProjectType::RustLibrary,
+            confidence: 100,
+            language_version: Some("stable".to_string()),
+            metadata: HashMap::new(),
+        };
+        let fresh = EditorState::from_detection(detection, None, dir.path().to_path_buf()).unwrap();
+        let mut state = fresh.clone();
+        let linter = state.get_option_value("rust", "enable_linter").cloned();
+        state.toggle_option("rust", "enable_linter");
+        state.target_platform = Platform::GitLab;
         state.regenerate_yaml();
+        let last = state.session();
+
+        // Nothing to offer when nothing changed
+        let mut again = state.clone();
+        again.offer_session(state.session());
+        assert!(again.session_restore.is_none());
+
+        let mut state = fresh.clone();
+        state.offer_session(last.clone());
+        let restore = state.session_restore.as_ref().unwrap();
+        assert_ne!(restore.before, restore.after);
+        assert_eq!(state.target_platform, Platform::GitHub);
+        state.restore_session();
+        assert!(state.session_restore.is_none());
+        assert_eq!(state.target_platform, Platform::GitLab);
+        assert_ne!(
+            state.get_option_value("rust", "enable_linter").cloned(),
+            linter
+        );
+
+        let mut state = fresh;
+        session::save(dir.path(), &last.to_ron().unwrap()).unwrap();
+        state.offer_session(last);
+        state.discard_session();
+        assert!(state.session_restore.is_none());
+        assert_eq!(state.target_platform, Platform::GitHub);
+        assert!(session::load(dir.path()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_switching_between_projects() {
+        use crate::generator::monorepo::find_projects;
 
-        // The first preset with options enabled should be used (registry order)
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("go.mod"), "module example.com/app\n\ngo 1.22\n").unwrap();
+        std::fs::create_dir_all(root.join("services/api/src")).unwrap();
+        std::fs::write(
+            root.join("services/api/Cargo.toml"),
+            "[package]\nname = \"api\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("services/api/src/main.rs"), "fn main() {}\n").unwrap();
+
+        let detections = DetectorRegistry::new().detect(root).unwrap();
+        let mut state = EditorState::from_detections(detections, None, root.to_path_buf()).unwrap();
+        state.attach_projects(find_projects(root, &DetectionOptions::default()));
+        assert_eq!(state.workspace.as_ref().unwrap().projects.len(), 2);
+
+        let before = state.snapshot();
+        state.toggle_option("go-app", "enable_linter");
+        state.record_edit(before);
+        let linter = state.get_option_value("go-app", "enable_linter").cloned();
+
+        state.switch_project(1);
+        assert_eq!(state.working_dir, root.join("services/api"));
         assert!(state.yaml_preview.contains("cargo"));
+        state.cycle_project(true);
+        assert_eq!(state.workspace.as_ref().unwrap().active, 0);
+        assert_eq!(
+            state.get_option_value("go-app", "enable_linter").cloned(),
+            linter
+        );
+        // Each project keeps its own history
+        assert!(state.undo());
+        assert_ne!(
+            state.get_option_value("go-app", "enable_linter").cloned(),
+            linter
+        );
+
+        // Every project is written at once, from the repository root
+        assert_eq!(state.write_root(), root);
+        let paths: Vec<PathBuf> = state
+            .planned_writes()
+            .unwrap()
+            .into_iter()
+            .map(|file| file.path)
+            .collect();
+        assert!(paths.contains(&PathBuf::from(".github/workflows/services-api-rust.yml")));
+        assert!(paths
+            .iter()
+            .any(|path| path.starts_with(".github/workflows")
+                && !path.to_string_lossy().contains("services-api")));
+    }
+
+    #[test]
+    fn test_extends_entries_are_saved_as_overrides() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("cci.ron");
+        std::fs::write(
+            &path,
+            r#"(version: 3, presets: [Extends((preset: "rust", options: {"enable_linter": false}))])"#,
+        )
+        .unwrap();
+
+        let mut state = EditorState::from_config_file(&path).unwrap();
+        assert_eq!(
+            state.get_option_value("rust", "enable_linter"),
+            Some(&OptionValue::Bool(false))
+        );
+        state.set_option_value("rust", "enable_coverage", OptionValue::Bool(false));
+
+        let saved = crate::config::migrate::parse_config(&state.export_to_ron().unwrap()).unwrap();
+        let Some(crate::config::PresetChoice::Extends(extends)) = saved.presets.first() else {
+            panic!("expected an Extends entry, got {:?}", saved.presets);
+        };
+        assert_eq!(extends.preset, "rust");
+        assert_eq!(
+            extends.options.keys().collect::<Vec<_>>(),
+            vec!["enable_coverage", "enable_linter"]
+        );
     }
 }
@@ -0,0 +1,122 @@
+//! Colors the editor draws with, by what they mean rather than what they are
+
+use ratatui::style::Color;
+
+/// A color for each role in the editor
+///
+/// Pick one of the built-in themes with `theme = "..."` in
+/// `~/.config/cci/config.toml`, and override single roles under `[colors]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// Titles, popup borders, search matches and most key hints
+    pub accent: Color,
+    /// The cursor
+    pub selected: Color,
+    /// Hints for keys that move around
+    pub navigate: Color,
+    /// Hints for scrolling and undo; booleans and keywords in the preview
+    pub secondary: Color,
+    /// Writing, enabled presets, new files and quoted strings in the preview
+    pub positive: Color,
+    /// Quitting, errors, and platforms that fail to generate
+    pub negative: Color,
+    /// Lint warnings, required secrets, modified files; numbers and list items in the preview
+    pub warning: Color,
+    /// Values changed from their defaults
+    pub text: Color,
+    /// Values left at their defaults, comments and placeholders
+    pub dim: Color,
+    /// Descriptions under options and secrets
+    pub description: Color,
+    pub popup_background: Color,
+    /// Behind preview lines the file on disk doesn't have yet
+    pub added_background: Color,
+    /// Behind preview lines that would be removed from the file on disk
+    pub removed_background: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            accent: Color::Cyan,
+            selected: Color::Yellow,
+            navigate: Color::Blue,
+            secondary: Color::Magenta,
+            positive: Color::Green,
+            negative: Color::Red,
+            warning: Color::Yellow,
+            text: Color::White,
+            dim: Color::DarkGray,
+            description: Color::Gray,
+            popup_background: Color::Black,
+            added_background: Color::Green,
+            removed_background: Color::Red,
+        }
+    }
+}
+
+impl Theme {
+    /// Blue and orange in place of green and red, from the Okabe-Ito palette
+    pub fn colorblind() -> Self {
+        let blue = Color::Rgb(0, 114, 178);
+        let orange = Color::Rgb(230, 159, 0);
+        Self {
+            navigate: Color::Rgb(86, 180, 233),
+            positive: blue,
+            negative: orange,
+            warning: Color::Rgb(240, 228, 66),
+            added_background: blue,
+            removed_background: Color::Rgb(213, 94, 0),
+            ..Self::default()
+        }
+    }
+
+    /// The terminal's own colors throughout; the cursor is still drawn in bold
+    pub fn no_color() -> Self {
+        Self {
+            accent: Color::Reset,
+            selected: Color::Reset,
+            navigate: Color::Reset,
+            secondary: Color::Reset,
+            positive: Color::Reset,
+            negative: Color::Reset,
+            warning: Color::Reset,
+            text: Color::Reset,
+            dim: Color::Reset,
+            description: Color::Reset,
+            popup_background: Color::Reset,
+            added_background: Color::Reset,
+            removed_background: Color::Reset,
+        }
+    }
+
+    /// The built-in theme called `name`
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            "colorblind" => Some(Self::colorblind()),
+            "no-color" => Some(Self::no_color()),
+            _ => None,
+        }
+    }
+
+    /// The role called `name` under `[colors]`
+    pub fn role_mut(&mut self, name: &str) -> Option<&mut Color> {
+        Some(match name {
+            "accent" => &mut self.accent,
+            "selected" => &mut self.selected,
+            "navigate" => &mut self.navigate,
+            "secondary" => &mut self.secondary,
+            "positive" => &mut self.positive,
+            "negative" => &mut self.negative,
+            "warning" => &mut self.warning,
+            "text" => &mut self.text,
+            "dim" => &mut self.dim,
+            "description" => &mut self.description,
+            "popup_background" => &mut self.popup_background,
+            "added_background" => &mut self.added_background,
+            "removed_background" => &mut self.removed_background,
+            _ => return None,
+        })
+    }
+}
@@ -0,0 +1,315 @@
+//! Syntax highlighting for the preview, by syntect's grammars
+//!
+//! Tokens are colored by what they are (a key, a string, a comment) through
+//! the editor's [`Theme`] roles rather than a syntect color scheme, so the
+//! colorblind and no-color themes apply to the preview too.
+
+use crate::diff::{compute_diff, side_by_side, DiffType};
+use crate::editor::theme::Theme;
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use std::cell::RefCell;
+use std::path::Path;
+use std::sync::OnceLock;
+use syntect::parsing::{ParseState, Scope, ScopeStack, ScopeStackOp, SyntaxReference, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+/// The language a generated file is written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Yaml,
+    /// Jenkinsfiles are Groovy scripts
+    Groovy,
+}
+
+impl Language {
+    pub fn for_path(path: &Path) -> Self {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if name.starts_with("Jenkinsfile") || path.extension().is_some_and(|ext| ext == "groovy") {
+            Language::Groovy
+        } else {
+            Language::Yaml
+        }
+    }
+
+    fn syntax(self, syntaxes: &SyntaxSet) -> &SyntaxReference {
+        let found = match self {
+            Language::Yaml => syntaxes.find_syntax_by_extension("yaml"),
+            Language::Groovy => syntaxes.find_syntax_by_extension("groovy"),
+        };
+        found.unwrap_or_else(|| syntaxes.find_syntax_plain_text())
+    }
+}
+
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+type Role = fn(&Theme) -> Option<Color>;
+
+/// Theme roles for scopes; a token takes the role of its innermost scope
+/// that has one
+fn scope_roles() -> &'static [(Scope, Role)] {
+    static ROLES: OnceLock<Vec<(Scope, Role)>> = OnceLock::new();
+    ROLES.get_or_init(|| {
+        let roles: [(&str, Role); 11] = [
+            ("comment", |theme| Some(theme.dim)),
+            // Plain YAML values read as text, as quoted ones didn't before
+            ("string.unquoted", |_| None),
+            ("string", |theme| Some(theme.positive)),
+            ("constant.numeric", |theme| Some(theme.warning)),
+            ("constant.language", |theme| Some(theme.secondary)),
+            ("punctuation.definition.block.sequence.item", |theme| {
+                Some(theme.warning)
+            }),
+            ("entity.name.tag", |theme| Some(theme.accent)),
+            ("entity.name.function", |theme| Some(theme.accent)),
+            ("meta.method", |theme| Some(theme.accent)),
+            ("keyword.operator", |_| None),
+            ("keyword", |theme| Some(theme.secondary)),
+        ];
+        roles
+            .into_iter()
+            .map(|(scope, role)| (Scope::new(scope).expect("valid scope"), role))
+            .collect()
+    })
+}
+
+/// The color of a token inside `stack`
+fn color(stack: &ScopeStack, theme: &Theme) -> Option<Color> {
+    stack
+        .as_slice()
+        .iter()
+        .rev()
+        .find_map(|scope| {
+            scope_roles()
+                .iter()
+                .find(|(prefix, _)| prefix.is_prefix_of(*scope))
+        })
+        .and_then(|(_, role)| role(theme))
+}
+
+/// How many highlighted files are kept: the preview, the file on disk it's
+/// diffed against, and the files of presets written on their own
+const CACHED_FILES: usize = 8;
+
+/// Recently highlighted files, newest last, since the preview is drawn on
+/// every key press but only changes when the configuration does
+type Cache = Vec<(String, Language, Theme, Vec<Line<'static>>)>;
+
+thread_local! {
+    static CACHE: RefCell<Cache> = const { RefCell::new(Vec::new()) };
+}
+
+/// Highlight `text` line by line
+pub fn highlight(text: &str, language: Language, theme: &Theme) -> Vec<Line<'static>> {
+    CACHE.with_borrow_mut(|cache| {
+        let hit = cache
+            .iter()
+            .position(|(t, l, th, _)| t == text && *l == language && th == theme);
+        let entry = match hit {
+            Some(index) => cache.remove(index),
+            None => {
+                let lines = parse(text, language, theme);
+                (text.to_string(), language, *theme, lines)
+            }
+        };
+        let lines = entry.3.clone();
+        cache.push(entry);
+        if cache.len() > CACHED_FILES {
+            cache.remove(0);
+        }
+        lines
+    })
+}
+
+fn parse(text: &str, language: Language, theme: &Theme) -> Vec<Line<'static>> {
+    let syntaxes = syntaxes();
+    let mut state = ParseState::new(language.syntax(syntaxes));
+    let mut stack = ScopeStack::new();
+    let mut lines = Vec::new();
+
+    for line in LinesWithEndings::from(text) {
+        // A line the grammar can't parse is shown plain, and the rest
+        // of the file starts over from a clean state
+        let Ok(ops) = state.parse_line(line, syntaxes) else {
+            lines.push(Line::from(line.trim_end_matches(['\r', '\n']).to_string()));
+            state = ParseState::new(language.syntax(syntaxes));
+            stack = ScopeStack::new();
+            continue;
+        };
+
+        let mut spans = Vec::new();
+        let mut start = 0;
+        // Each op applies from its offset on, so the text before it is in the current scopes
+        let ends = ops.iter().map(|(end, op)| (*end, op));
+        for (end, op) in ends.chain([(line.len(), &ScopeStackOp::Noop)]) {
+            push_token(&mut spans, &line[start..end], &stack, theme);
+            let _ = stack.apply(op);
+            start = end;
+        }
+        lines.push(Line::from(spans));
+    }
+    lines
+}
+
+fn push_token(spans: &mut Vec<Span<'static>>, token: &str, stack: &ScopeStack, theme: &Theme) {
+    let token = token.trim_end_matches(['\r', '\n']);
+    if token.is_empty() {
+        return;
+    }
+    let style = match color(stack, theme) {
+        Some(color) => Style::default().fg(color),
+        None => Style::default(),
+    };
+    spans.push(Span::styled(token.to_string(), style));
+}
+
+/// Highlight `new` against the file on disk, `old`: lines only `new` has get
+/// the added background, and lines only `old` has the removed one
+pub fn highlight_with_diff(
+    new: &str,
+    old: &str,
+    language: Language,
+    theme: &Theme,
+) -> Vec<Line<'static>> {
+    // Each side is highlighted whole, so multi-line strings and blocks keep their colors
+    let mut new_lines = highlight(new, language, theme).into_iter();
+    let mut old_lines = highlight(old, language, theme).into_iter();
+
+    let mut lines = Vec::new();
+    for (_, diff_type) in compute_diff(old, new) {
+        let (line, background) = match diff_type {
+            DiffType::Unchanged => {
+                old_lines.next();
+                (new_lines.next(), None)
+            }
+            DiffType::Added => (new_lines.next(), Some(theme.added_background)),
+            DiffType::Removed => (old_lines.next(), Some(theme.removed_background)),
+        };
+        let mut line = line.unwrap_or_default();
+        if let Some(background) = background {
+            for span in &mut line.spans {
+                span.style = span.style.bg(background);
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// A column of a side-by-side diff: each row's 1-based line number, if
+/// the column has a line in that row, and the line
+pub type Column = Vec<(Option<usize>, Line<'static>)>;
+
+/// Highlight the file on disk, `old`, and `new` as two columns whose rows
+/// line up, with removed lines on the left and added ones on the right
+pub fn highlight_side_by_side(
+    new: &str,
+    old: &str,
+    language: Language,
+    theme: &Theme,
+) -> (Column, Column) {
+    let new_lines = highlight(new, language, theme);
+    let old_lines = highlight(old, language, theme);
+    let new_text: Vec<&str> = new.lines().collect();
+    let old_text: Vec<&str> = old.lines().collect();
+
+    let mut left = Vec::new();
+    let mut right = Vec::new();
+    for (old_index, new_index) in side_by_side(old, new) {
+        let unchanged = match (old_index, new_index) {
+            (Some(o), Some(n)) => old_text.get(o) == new_text.get(n),
+            _ => false,
+        };
+        let side = |lines: &[Line<'static>], index: Option<usize>, background: Color| {
+            let mut line = index
+                .and_then(|index| lines.get(index).cloned())
+                .unwrap_or_default();
+            if !unchanged {
+                for span in &mut line.spans {
+                    span.style = span.style.bg(background);
+                }
+            }
+            (index.map(|index| index + 1), line)
+        };
+        left.push(side(&old_lines, old_index, theme.removed_background));
+        right.push(side(&new_lines, new_index, theme.added_background));
+    }
+    (left, right)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn color_of(lines: &[Line], text: &str) -> Option<Color> {
+        lines
+            .iter()
+            .flat_map(|line| &line.spans)
+            .find(|span| span.content.trim() == text)
+            .unwrap_or_else(|| panic!("no span with {:?}", text))
+            .style
+            .fg
+    }
+
+    #[test]
+    fn test_highlight_yaml_and_groovy() {
+        let theme = Theme::default();
+        let yaml = "# Build\nname: CI\non:\n  push:\n    branches: [\"main\"]\njobs:\n  - enabled: true\n    retries: 3\n";
+        let lines = highlight(yaml, Language::Yaml, &theme);
+        assert_eq!(lines.len(), yaml.lines().count());
+        assert_eq!(color_of(&lines, "Build"), Some(theme.dim));
+        assert_eq!(color_of(&lines, "name"), Some(theme.accent));
+        assert_eq!(color_of(&lines, "main"), Some(theme.positive));
+        assert_eq!(color_of(&lines, "true"), Some(theme.secondary));
+        assert_eq!(color_of(&lines, "3"), Some(theme.warning));
+        assert_eq!(color_of(&lines, "CI"), None);
+
+        let jenkinsfile = "// Generated\npipeline {\n    stages {\n        stage('Test') {\n            steps {\n                sh 'cargo test'\n            }\n        }\n    }\n}\n";
+        let language = Language::for_path(Path::new("Jenkinsfile"));
+        assert_eq!(language, Language::Groovy);
+        let lines = highlight(jenkinsfile, language, &theme);
+        assert_eq!(lines.len(), jenkinsfile.lines().count());
+        assert_eq!(color_of(&lines, "Generated"), Some(theme.dim));
+        assert_eq!(color_of(&lines, "cargo test"), Some(theme.positive));
+        assert_eq!(color_of(&lines, "stage"), Some(theme.accent));
+        // Groovy isn't read as YAML, where `sh 'cargo test'` would be a plain value
+        assert_eq!(color_of(&lines, "sh"), None);
+    }
+
+    #[test]
+    fn test_highlight_with_diff_backgrounds() {
+        let theme = Theme::default();
+        let lines = highlight_with_diff("a: 1\nb: 2\n", "a: 1\nc: 3\n", Language::Yaml, &theme);
+        let backgrounds: Vec<Option<Color>> = lines
+            .iter()
+            .map(|line| line.spans.first().and_then(|span| span.style.bg))
+            .collect();
+        assert_eq!(backgrounds.len(), 3);
+        assert_eq!(backgrounds[0], None);
+        assert!(backgrounds.contains(&Some(theme.added_background)));
+        assert!(backgrounds.contains(&Some(theme.removed_background)));
+    }
+
+    #[test]
+    fn test_side_by_side_rows_line_up() {
+        let theme = Theme::default();
+        let (left, right) =
+            highlight_side_by_side("a: 1\nb: 2\nd: 4\n", "a: 1\nc: 3\n", Language::Yaml, &theme);
+        assert_eq!(left.len(), right.len());
+        assert_eq!(left.len(), 3);
+        let background =
+            |(_, line): &(Option<usize>, Line)| line.spans.first().and_then(|span| span.style.bg);
+        assert_eq!(background(&left[0]), None);
+        assert_eq!(background(&right[0]), None);
+        assert_eq!(background(&left[1]), Some(theme.removed_background));
+        assert_eq!(background(&right[1]), Some(theme.added_background));
+        // Nothing on disk across from the last new line
+        assert_eq!(left[2].0, None);
+        assert!(left[2].1.spans.is_empty());
+        assert_eq!(right[2].0, Some(3));
+        assert_eq!(background(&right[2]), Some(theme.added_background));
+    }
+}
@@ -1,32 +1,62 @@
+mod highlight;
+
+use crate::diff::{compute_diff, unified_diff, DiffType};
 use crate::editor::config::OptionValue;
-use crate::editor::state::{EditorState, Platform, TreeItem};
+use crate::editor::keymap::{Action, Keymap};
+use crate::editor::state::{
+    EditorState, EnumMenu, HandEdit, Platform, ProfileMenu, SessionRestore, Suggestion, TextInput,
+    TreeItem, Workspace, WriteConfirm,
+};
+use crate::editor::theme::Theme;
+use crate::editor::warnings::WarningKind;
+use crate::generator::merge::is_managed;
+use crate::lint::Issue;
+use highlight::{highlight, highlight_side_by_side, highlight_with_diff, Language};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Tabs, Wrap},
     Frame,
 };
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum DiffType {
-    Unchanged,
-    Added,
-    Removed,
+use std::path::Path;
+
+/// Where each panel is drawn, also used to work out what a mouse click hit
+pub struct Areas {
+    pub info: Rect,
+    /// The project panel above the tree, empty unless the repository holds several projects
+    pub projects: Rect,
+    pub tree: Rect,
+    /// The preview, with the platform tabs along its top row
+    pub preview: Rect,
+    pub platform_bar: Rect,
+    /// The warnings panel across the bottom, empty when there's nothing to warn about
+    pub warnings: Rect,
+    pub footer: Rect,
 }
 
-pub fn render_ui(f: &mut Frame, state: &EditorState) {
+/// Warnings shown at once in the expanded panel; the rest are counted
+const WARNING_ROWS: usize = 8;
+
+/// Projects shown at once in the project panel, which scrolls to keep the active one in view
+const PROJECT_ROWS: usize = 6;
+
+pub fn areas(area: Rect, state: &EditorState) -> Areas {
+    // Folded, the panel is just its title along the top border
+    let warnings_height = match state.warnings.len() {
+        0 => 0,
+        _ if state.warnings_collapsed => 1,
+        count => count.min(WARNING_ROWS + 1) as u16 + 2,
+    };
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(4), // Information message bar
-            Constraint::Min(0),    // Main content
-            Constraint::Length(3), // Footer
+            Constraint::Length(4),               // Information message bar
+            Constraint::Min(0),                  // Main content
+            Constraint::Length(warnings_height), // Warnings
+            Constraint::Length(3),               // Footer
         ])
-        .split(f.area());
-
-    // Information message bar (where platform bar was)
-    render_info_bar(f, chunks[0], state);
+        .split(area);
 
     // Main content (two panels: tree + preview)
     let main_chunks = Layout::default()
@@ -37,9 +67,17 @@ pub fn render_ui(f: &mut Frame, state: &EditorState) {
         ])
         .split(chunks[1]);
 
-    render_presets_panel(f, main_chunks[0], state);
+    // Left side: the projects, when there are several, above the tree
+    let projects_height = match &state.workspace {
+        Some(workspace) => workspace.projects.len().min(PROJECT_ROWS) as u16 + 2,
+        None => 0,
+    };
+    let left_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(projects_height), Constraint::Min(0)])
+        .split(main_chunks[0]);
 
-    // Right side: preview above platform selector
+    // Right side: preview above the platform selector
     let right_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -48,55 +86,283 @@ pub fn render_ui(f: &mut Frame, state: &EditorState) {
         ])
         .split(main_chunks[1]);
 
-    render_preview_panel(f, right_chunks[0], state);
-    render_platform_bar(f, right_chunks[1], state);
+    Areas {
+        info: chunks[0],
+        projects: left_chunks[0],
+        tree: left_chunks[1],
+        preview: right_chunks[0],
+        platform_bar: right_chunks[1],
+        warnings: chunks[2],
+        footer: chunks[3],
+    }
+}
+
+pub fn render_ui(f: &mut Frame, state: &EditorState) {
+    let areas = areas(f.area(), state);
+
+    // Information message bar (where platform bar was)
+    render_info_bar(f, areas.info, state);
+
+    if let Some(workspace) = &state.workspace {
+        render_projects_panel(f, areas.projects, workspace, state);
+    }
+    render_presets_panel(f, areas.tree, state);
+    render_preview_panel(f, areas.preview, state);
+    render_platform_bar(f, areas.platform_bar, state);
+    if !state.warnings.is_empty() {
+        render_warnings_panel(f, areas.warnings, state);
+    }
 
     // Footer
-    render_footer(f, chunks[2], state);
+    render_footer(f, areas.footer, state);
 
     // Platform menu overlay (if open)
     if state.platform_menu_open {
         render_platform_menu(f, state);
     }
+
+    if let Some(menu) = &state.enum_menu {
+        render_enum_menu(f, menu, &state.theme);
+    }
+
+    if let Some(input) = &state.text_input {
+        render_text_input(f, input, &state.theme);
+    }
+
+    if let Some(menu) = &state.profile_menu {
+        render_profile_menu(f, menu, &state.theme);
+    }
+
+    if let Some(scroll) = state.help_scroll {
+        render_help(f, state, scroll);
+    }
+
+    if let Some(confirm) = &state.write_confirm {
+        render_write_confirm(f, confirm, &state.theme);
+    }
+
+    if let Some(edit) = &state.edit_confirm {
+        render_edit_confirm(f, edit, &state.theme);
+    }
+
+    if let Some(suggestion) = state.suggestions.first() {
+        render_suggestion(f, suggestion, state);
+    }
+
+    if let Some(restore) = &state.session_restore {
+        render_session_restore(f, restore, state);
+    }
 }
 
 fn render_info_bar(f: &mut Frame, area: Rect, state: &EditorState) {
+    let theme = &state.theme;
     let text = if !state.current_item_description.is_empty() {
         state.current_item_description.clone()
     } else {
-        "Navigate with ↑↓/jk, toggle with Space/Enter, expand/collapse with ←→/hl".to_string()
+        let keymap = &state.keymap;
+        format!(
+            "Navigate with {}, toggle with {}, edit or choose with {}, expand/collapse with {}, {} for every key",
+            keymap.pair_label(Action::Up, Action::Down),
+            keymap.first_label(Action::Toggle),
+            keymap.first_label(Action::Edit),
+            keymap.pair_label(Action::Collapse, Action::Expand),
+            keymap.first_label(Action::Help),
+        )
     };
 
     let paragraph = Paragraph::new(text)
-        .style(Style::default().fg(Color::Gray))
+        .style(Style::default().fg(theme.description))
         .wrap(Wrap { trim: true })
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title(" Information "),
+                .title(" Information ")
+                .title(
+                    Line::styled(detection_summary(state), Style::default().fg(theme.dim))
+                        .right_aligned(),
+                ),
         );
 
     f.render_widget(paragraph, area);
 }
 
-fn render_platform_bar(f: &mut Frame, area: Rect, state: &EditorState) {
-    let text = format!(
-        "Platform: {} (press 'p' to change)",
-        state.target_platform.name()
+/// The detected project type and version, then whatever else detection
+/// found, e.g. " Rust Library + Docker Image · stable · name: cci · R to
+/// detect again "
+fn detection_summary(state: &EditorState) -> String {
+    let redetect = state.keymap.first_label(Action::Redetect);
+    let Some(detection) = &state.detection else {
+        return format!(" Loaded from cci.ron · {} to detect ", redetect);
+    };
+    let project_types: Vec<String> = std::iter::once(detection)
+        .chain(&state.other_detections)
+        .map(|detected| detected.project_type.to_string())
+        .collect();
+    let mut parts = vec![project_types.join(" + ")];
+    if let Some(version) = &detection.language_version {
+        parts.push(version.clone());
+    }
+    let mut metadata: Vec<(&String, &String)> = detection.metadata.iter().collect();
+    metadata.sort();
+    parts.extend(
+        metadata
+            .into_iter()
+            .map(|(key, value)| format!("{}: {}", key.replace('_', " "), value)),
     );
+    parts.push(format!("{} to detect again", redetect));
+    format!(" {} ", parts.join(" · "))
+}
 
-    let paragraph = Paragraph::new(text)
-        .style(Style::default().fg(Color::Cyan))
-        .block(Block::default().borders(Borders::ALL));
+fn render_platform_bar(f: &mut Frame, area: Rect, state: &EditorState) {
+    let theme = &state.theme;
+    let extras: Vec<&str> = state
+        .write_platforms()
+        .into_iter()
+        .filter(|p| *p != state.target_platform)
+        .map(|p| p.name())
+        .collect();
+
+    let text = if extras.is_empty() {
+        format!(
+            "Platform: {} (press 'p' to change)",
+            state.target_platform.name()
+        )
+    } else {
+        format!(
+            "Platform: {} + {} (press 'p' to change)",
+            state.target_platform.name(),
+            extras.join(", ")
+        )
+    };
+
+    let mut spans = vec![Span::styled(text, Style::default().fg(theme.accent))];
+    spans.extend(existing_file_status(state));
+    let paragraph = Paragraph::new(Line::from(spans)).block(Block::default().borders(Borders::ALL));
 
     f.render_widget(paragraph, area);
 }
 
+/// The project on screen row `y` of the project panel, if there's one there
+pub fn project_at(state: &EditorState, screen: Rect, y: u16) -> Option<usize> {
+    let workspace = state.workspace.as_ref()?;
+    let area = areas(screen, state).projects;
+    if y <= area.y || y >= area.bottom().saturating_sub(1) {
+        return None;
+    }
+    // The list scrolls just far enough to show the active project
+    let rows = area.height.saturating_sub(2) as usize;
+    let offset = (workspace.active + 1).saturating_sub(rows);
+    let index = offset + (y - area.y - 1) as usize;
+    (index < workspace.projects.len()).then_some(index)
+}
+
+fn render_projects_panel(f: &mut Frame, area: Rect, workspace: &Workspace, state: &EditorState) {
+    let theme = &state.theme;
+    let items: Vec<ListItem> = workspace
+        .projects
+        .iter()
+        .enumerate()
+        .map(|(index, project)| {
+            let active = index == workspace.active;
+            let name = if project.dir == Path::new(".") {
+                "(root)".to_string()
+            } else {
+                project.dir.display().to_string()
+            };
+            let style = if active {
+                Style::default()
+                    .fg(theme.selected)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(if active { "● " } else { "○ " }, style),
+                Span::styled(name, style),
+                Span::styled(
+                    format!("  {}", project.detection.project_type),
+                    Style::default().fg(theme.dim),
+                ),
+            ]))
+        })
+        .collect();
+
+    let keymap = &state.keymap;
+    let block = Block::default()
+        .title(format!(
+            " Projects ({}/{} to switch) ",
+            keymap.first_label(Action::PreviousProject),
+            keymap.first_label(Action::NextProject),
+        ))
+        .borders(Borders::ALL);
+    let mut list_state = ListState::default().with_selected(Some(workspace.active));
+    f.render_stateful_widget(List::new(items).block(block), area, &mut list_state);
+}
+
+/// Whether the previewed file is already on disk and, if so, whether cci wrote it
+fn existing_file_status(state: &EditorState) -> Vec<Span<'static>> {
+    let theme = &state.theme;
+    let name = state
+        .preview_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    let separator = Span::styled(" · ", Style::default().fg(theme.dim));
+    let Some(existing) = &state.existing_yaml else {
+        return vec![
+            separator,
+            Span::styled(
+                format!("{}: new file", name),
+                Style::default().fg(theme.dim),
+            ),
+        ];
+    };
+    let modified = state
+        .existing_modified
+        .map(|time| {
+            chrono::DateTime::<chrono::Local>::from(time)
+                .format(", modified %Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_default();
+    match is_managed(state.target_platform, existing) {
+        Some(false) => vec![
+            separator,
+            Span::styled(
+                format!("⚠ {} exists and wasn't written by cci{}", name, modified),
+                Style::default()
+                    .fg(theme.negative)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ],
+        managed => vec![
+            separator,
+            Span::styled(
+                format!(
+                    "{} exists{}{}",
+                    name,
+                    if managed.is_some() {
+                        ", written by cci"
+                    } else {
+                        ""
+                    },
+                    modified
+                ),
+                Style::default().fg(theme.dim),
+            ),
+        ],
+    }
+}
+
 fn render_presets_panel(f: &mut Frame, area: Rect, state: &EditorState) {
+    let theme = &state.theme;
     let mut items: Vec<ListItem> = Vec::new();
+    let matches = state.search_matches();
 
     for (i, item) in state.tree_items.iter().enumerate() {
         let is_selected = i == state.tree_cursor;
+        let is_match = matches.contains(&i);
 
         let list_item = match item {
             TreeItem::Preset(preset_id) => {
@@ -114,8 +380,7 @@ fn render_presets_panel(f: &mut Frame, area: Rect, state: &EditorState) {
                             .any(|v| matches!(v, OptionValue::Bool(true)))
                     })
                     .unwrap_or(false);
-                let matches_project =
-                    preset.matches_project(&state.project_type, &state.working_dir);
+                let matches_project = state.preset_matches(preset.as_ref());
                 let has_non_defaults = state.has_preset_non_defaults(preset_id);
 
                 let expand_icon = if is_expanded { "▼" } else { "▶" };
@@ -123,30 +388,36 @@ fn render_presets_panel(f: &mut Frame, area: Rect, state: &EditorState) {
 
                 let circle_color = if has_options_enabled {
                     if matches_project {
-                        Color::Green
+                        theme.positive
                     } else {
-                        Color::DarkGray
+                        theme.dim
                     }
                 } else {
-                    Color::White
+                    theme.text
                 };
 
                 let text_color = if is_selected {
-                    Color::Yellow
+                    theme.selected
+                } else if is_match {
+                    theme.accent
                 } else if !has_non_defaults {
-                    Color::DarkGray
+                    theme.dim
                 } else {
-                    Color::White
+                    theme.text
                 };
 
-                let line = Line::from(vec![
+                let mut spans = vec![
                     Span::styled(format!("{} ", expand_icon), Style::default().fg(text_color)),
                     Span::styled(circle_icon, Style::default().fg(circle_color)),
                     Span::styled(
                         format!(" {}", preset.preset_name()),
                         Style::default().fg(text_color),
                     ),
-                ]);
+                ];
+                if preset.is_addon() {
+                    spans.push(Span::styled(" (addon)", Style::default().fg(theme.dim)));
+                }
+                let line = Line::from(spans);
 
                 let item_style = if is_selected {
                     Style::default().add_modifier(Modifier::BOLD)
@@ -174,15 +445,26 @@ fn render_presets_panel(f: &mut Frame, area: Rect, state: &EditorState) {
                 let has_non_defaults = state.has_feature_non_defaults(preset_id, feature_id);
 
                 let text_color = if is_selected {
-                    Color::Yellow
+                    theme.selected
+                } else if is_match {
+                    theme.accent
                 } else if !has_non_defaults {
-                    Color::DarkGray
+                    theme.dim
                 } else {
-                    Color::White
+                    theme.text
+                };
+
+                // All, some or none of its switches on; features of only
+                // values to fill in have no checkbox
+                let checkbox = match state.feature_enabled_count(preset_id, feature_id) {
+                    (_, 0) => "",
+                    (0, _) => "[ ] ",
+                    (enabled, total) if enabled == total => "[✓] ",
+                    _ => "[-] ",
                 };
 
                 let line = Line::from(vec![Span::styled(
-                    format!("  {} {}", expand_icon, feature.display_name),
+                    format!("  {} {}{}", expand_icon, checkbox, feature.display_name),
                     Style::default().fg(text_color),
                 )]);
 
@@ -231,19 +513,43 @@ fn render_presets_panel(f: &mut Frame, area: Rect, state: &EditorState) {
                         format!("      {} ({})", display_name, selected)
                     }
                     OptionValue::String(s) => {
-                        format!("      {}: {}", display_name, s)
+                        match option_meta.and_then(|o| o.placeholder.as_ref()) {
+                            Some(placeholder) if s.is_empty() => {
+                                format!("      {}: ({})", display_name, placeholder)
+                            }
+                            _ => format!("      {}: {}", display_name, s),
+                        }
                     }
                     OptionValue::Int(n) => {
                         format!("      {}: {}", display_name, n)
                     }
                 };
 
+                // Greyed out while an option it depends on is off; changing
+                // it turns that option on
+                let off = state
+                    .option_parents(preset_id, option_id)
+                    .into_iter()
+                    .rfind(|parent| {
+                        matches!(
+                            state.get_option_value(preset_id, &parent.id),
+                            Some(OptionValue::Bool(false))
+                        )
+                    });
+                let available = off.is_none();
+                let display_text = match off {
+                    Some(parent) => format!("{} (needs {})", display_text, parent.display_name),
+                    None => display_text,
+                };
+
                 let text_color = if is_selected {
-                    Color::Yellow
-                } else if !is_non_default {
-                    Color::DarkGray
+                    theme.selected
+                } else if is_match {
+                    theme.accent
+                } else if !is_non_default || !available {
+                    theme.dim
                 } else {
-                    Color::White
+                    theme.text
                 };
 
                 let item_style = if is_selected {
@@ -259,59 +565,530 @@ fn render_presets_panel(f: &mut Frame, area: Rect, state: &EditorState) {
         items.push(list_item);
     }
 
+    let title = match &state.search {
+        Some(search) => format!(" Presets - /{} ({} found) ", search.query, matches.len()),
+        None => " Presets ".to_string(),
+    };
     let list = List::new(items).block(
         Block::default()
-            .title(" Presets ")
+            .title(title)
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Green)),
+            .border_style(Style::default().fg(theme.positive)),
     );
 
-    f.render_widget(list, area);
+    let mut list_state = ListState::default()
+        .with_offset(state.tree_offset.get())
+        .with_selected(Some(state.tree_cursor));
+    f.render_stateful_widget(list, area, &mut list_state);
+    state.tree_offset.set(list_state.offset());
+
+    if let Some(search) = state.search.as_ref().filter(|search| search.typing) {
+        let offset = " Presets - /".chars().count() + search.query.chars().count();
+        f.set_cursor_position((area.x + 1 + offset as u16, area.y));
+    }
+}
+
+/// One tab per platform: the previewed one highlighted, those that would be
+/// written in white, and those that fail to generate marked in red
+fn render_platform_tabs(f: &mut Frame, area: Rect, state: &EditorState) {
+    let theme = &state.theme;
+    let written = state.write_platforms();
+    let titles: Vec<Line> = Platform::all()
+        .into_iter()
+        .map(|platform| {
+            if state.platform_errors.contains_key(&platform) {
+                Line::from(Span::styled(
+                    format!("{} ✗", platform.name()),
+                    Style::default().fg(theme.negative),
+                ))
+            } else if written.contains(&platform) {
+                Line::from(Span::styled(
+                    platform.name(),
+                    Style::default().fg(theme.text),
+                ))
+            } else {
+                Line::from(Span::styled(
+                    platform.name(),
+                    Style::default().fg(theme.dim),
+                ))
+            }
+        })
+        .collect();
+    let selected = Platform::all()
+        .iter()
+        .position(|&p| p == state.target_platform)
+        .unwrap_or(0);
+
+    let tabs = Tabs::new(titles)
+        .select(selected)
+        .highlight_style(
+            Style::default()
+                .fg(theme.selected)
+                .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+        )
+        .divider("|");
+    f.render_widget(tabs, area);
 }
 
+/// Frames of the spinner shown while the preview is being generated
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
 fn render_preview_panel(f: &mut Frame, area: Rect, state: &EditorState) {
+    let theme = &state.theme;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // Platform tabs
+            Constraint::Min(0),    // Preview
+        ])
+        .split(area);
+    render_platform_tabs(f, chunks[0], state);
+    let area = chunks[1];
+
+    if let Some(existing) = side_by_side_existing(state) {
+        render_side_by_side(f, area, state, existing);
+        return;
+    }
+
     let preview = if let Some(error) = &state.generation_error {
         Paragraph::new(format!("Error: {}", error))
-            .style(Style::default().fg(Color::Red))
+            .style(Style::default().fg(theme.negative))
             .wrap(Wrap { trim: true })
             .scroll((state.preview_scroll, 0))
-    } else {
-        // Apply syntax highlighting to YAML with diff support
-        let lines = if let Some(existing) = &state.existing_yaml {
-            highlight_yaml_with_diff(&state.yaml_preview, existing)
-        } else {
-            highlight_yaml(&state.yaml_preview)
-        };
-        Paragraph::new(lines)
+    } else if state.preview_wrap {
+        Paragraph::new(with_line_numbers(preview_lines(state), 0, theme))
             .wrap(Wrap { trim: false })
             .scroll((state.preview_scroll, 0))
+    } else {
+        Paragraph::new(with_line_numbers(
+            preview_lines(state),
+            state.preview_hscroll,
+            theme,
+        ))
+        .scroll((state.preview_scroll, 0))
     };
 
-    let output_path = state.target_platform.output_path();
-    let filename = output_path.to_str().unwrap_or("config.yml");
+    let filename = state.preview_path.to_str().unwrap_or("config.yml");
 
+    let mut title = vec![Span::raw(format!(" Preview - {} ", filename))];
+    // Written scoped to the project's directory, alongside the other projects
+    if let Some(workspace) = &state.workspace {
+        let dir = &workspace.projects[workspace.active].dir;
+        if dir != Path::new(".") {
+            title.push(Span::styled(
+                format!("for {} ", dir.display()),
+                Style::default().fg(theme.accent),
+            ));
+        }
+    }
+    if !state.extra_previews.is_empty() {
+        let names: Vec<String> = state
+            .extra_previews
+            .iter()
+            .map(|(path, _)| {
+                path.file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        title.push(Span::styled(
+            format!("+ {} ", names.join(", ")),
+            Style::default().fg(theme.accent),
+        ));
+    }
+    if !state.lint_issues.is_empty() {
+        title.push(Span::styled(
+            format!("{} warning(s) ", state.lint_issues.len()),
+            Style::default().fg(theme.warning),
+        ));
+    }
+    if state.preview_pending || state.preview_busy {
+        let frame = SPINNER[state.spinner_frame % SPINNER.len()];
+        title.push(Span::styled(
+            format!("{} ", frame),
+            Style::default().fg(theme.accent),
+        ));
+    }
+    if state.hand_edits.contains_key(&state.preview_path) {
+        title.push(Span::styled(
+            "edited ",
+            Style::default().fg(theme.secondary),
+        ));
+    }
+    if state.preview_copied {
+        title.push(Span::styled("copied ", Style::default().fg(theme.positive)));
+    }
+    let keymap = &state.keymap;
+    let mut hint = format!(
+        "({} to switch, {} to scroll",
+        keymap.first_label(Action::NextPlatform),
+        keymap.pair_label(Action::ScrollPreviewDown, Action::ScrollPreviewUp)
+    );
+    if state.existing_yaml.is_some() {
+        hint.push_str(&format!(
+            ", {} side by side",
+            keymap.first_label(Action::SideBySide)
+        ));
+    }
+    title.push(Span::raw(hint + ") "));
     let block = Block::default()
-        .title(format!(" Preview - {} (Shift+J/K to scroll) ", filename))
+        .title(Line::from(title))
         .borders(Borders::ALL);
 
     f.render_widget(preview.block(block), area);
 }
 
-fn render_platform_menu(f: &mut Frame, state: &EditorState) {
-    let area = f.area();
+/// A preview line with where it is in the generated files: the file's index
+/// (0 for the previewed one, then the extra previews) and a 1-based line
+type Numbered = (Option<(usize, usize)>, Line<'static>);
+
+/// The file on disk, when the preview shows it side by side with the generated one
+fn side_by_side_existing(state: &EditorState) -> Option<&str> {
+    if !state.side_by_side || state.generation_error.is_some() {
+        return None;
+    }
+    state.existing_yaml.as_deref()
+}
+
+/// The previewed file, with changes from the file on disk and lint issues
+/// inline, followed by the extra previews
+fn preview_lines(state: &EditorState) -> Vec<Numbered> {
+    let theme = &state.theme;
+    let language = Language::for_path(&state.preview_path);
+    let lines: Vec<(Option<usize>, Line<'static>)> = if let Some(existing) = &state.existing_yaml {
+        // Removed lines aren't in the generated file, so they have no line number
+        let mut number = 0;
+        compute_diff(existing, &state.yaml_preview)
+            .into_iter()
+            .map(|(_, diff_type)| match diff_type {
+                DiffType::Removed => None,
+                _ => {
+                    number += 1;
+                    Some(number)
+                }
+            })
+            .zip(highlight_with_diff(
+                &state.yaml_preview,
+                existing,
+                language,
+                theme,
+            ))
+            .collect()
+    } else {
+        highlight(&state.yaml_preview, language, theme)
+            .into_iter()
+            .enumerate()
+            .map(|(index, line)| (Some(index + 1), line))
+            .collect()
+    };
+    let mut lines: Vec<Numbered> = with_inline_issues(lines, &state.lint_issues, theme)
+        .into_iter()
+        .map(|(number, line)| (number.map(|number| (0, number)), line))
+        .collect();
+    lines.extend(extra_preview_lines(state));
+    lines
+}
+
+/// Presets that couldn't share the previewed file, in files of their own
+fn extra_preview_lines(state: &EditorState) -> Vec<Numbered> {
+    let theme = &state.theme;
+    let mut lines = Vec::new();
+    for (index, (path, content)) in state.extra_previews.iter().enumerate() {
+        lines.push((None, Line::from("")));
+        lines.push((
+            None,
+            Line::from(Span::styled(
+                format!("# ── {} ──", path.display()),
+                Style::default()
+                    .fg(theme.accent)
+                    .add_modifier(Modifier::BOLD),
+            )),
+        ));
+        lines.extend(
+            highlight(content, Language::for_path(path), theme)
+                .into_iter()
+                .enumerate()
+                .map(|(line, text)| (Some((index + 1, line + 1)), text)),
+        );
+    }
+    lines
+}
+
+/// Both columns of the side-by-side preview; the extra previews follow on the right
+fn side_by_side_lines(state: &EditorState, existing: &str) -> (Vec<Numbered>, Vec<Numbered>) {
+    let language = Language::for_path(&state.preview_path);
+    let (left, right) =
+        highlight_side_by_side(&state.yaml_preview, existing, language, &state.theme);
+    let in_file = |column: Vec<(Option<usize>, Line<'static>)>| -> Vec<Numbered> {
+        column
+            .into_iter()
+            .map(|(number, line)| (number.map(|number| (0, number)), line))
+            .collect()
+    };
+    let mut right = in_file(right);
+    right.extend(extra_preview_lines(state));
+    (in_file(left), right)
+}
+
+/// Put each line's number in a gutter before it, with the line scrolled
+/// `hscroll` columns right; the gutter doesn't scroll
+fn with_line_numbers(lines: Vec<Numbered>, hscroll: u16, theme: &Theme) -> Vec<Line<'static>> {
+    let width = lines
+        .iter()
+        .filter_map(|(number, _)| number.map(|(_, line)| line.to_string().len()))
+        .max()
+        .unwrap_or(1);
+    lines
+        .into_iter()
+        .map(|(number, mut line)| {
+            let gutter = match number {
+                Some((_, number)) => format!("{:>width$} ", number),
+                None => " ".repeat(width + 1),
+            };
+            if hscroll > 0 {
+                line = scrolled(line, hscroll as usize);
+            }
+            line.spans
+                .insert(0, Span::styled(gutter, Style::default().fg(theme.dim)));
+            line
+        })
+        .collect()
+}
 
-    // Center the menu
+/// `line` without its first `columns` characters
+fn scrolled(line: Line<'static>, columns: usize) -> Line<'static> {
+    let mut skip = columns;
+    let spans: Vec<Span<'static>> = line
+        .spans
+        .into_iter()
+        .filter_map(|span| {
+            let length = span.content.chars().count();
+            if skip >= length {
+                skip -= length;
+                return None;
+            }
+            let content: String = span.content.chars().skip(skip).collect();
+            skip = 0;
+            Some(Span::styled(content, span.style))
+        })
+        .collect();
+    Line::from(spans).style(line.style)
+}
+
+/// The preview scroll that puts `line` of file `file` at the top of the
+/// preview on `screen`, counting the rows wrapped lines above it take up
+pub fn preview_row(state: &EditorState, screen: Rect, file: usize, line: usize) -> Option<u16> {
+    if state.generation_error.is_some() {
+        return None;
+    }
+    let target = Some((file, line));
+    if let Some(existing) = side_by_side_existing(state) {
+        // Side by side isn't wrapped
+        let (_, right) = side_by_side_lines(state, existing);
+        let row = right.iter().position(|(number, _)| *number == target)?;
+        return u16::try_from(row).ok();
+    }
+
+    let lines = preview_lines(state);
+    let row = lines.iter().position(|(number, _)| *number == target)?;
+    if !state.preview_wrap {
+        return u16::try_from(row).ok();
+    }
+    let above: Vec<Numbered> = lines.into_iter().take(row).collect();
+    // Less the borders around the preview
+    let width = areas(screen, state).preview.width.saturating_sub(2);
+    let rows = Paragraph::new(with_line_numbers(above, 0, &state.theme))
+        .wrap(Wrap { trim: false })
+        .line_count(width);
+    u16::try_from(rows).ok()
+}
+
+/// Rows of the preview visible at once on a terminal of size `screen`
+pub fn preview_page(state: &EditorState, screen: Rect) -> u16 {
+    // Less the platform tabs and the borders
+    areas(screen, state).preview.height.saturating_sub(3)
+}
+
+/// How far the preview scrolls before its last row reaches the bottom
+pub fn preview_scroll_limit(state: &EditorState, screen: Rect) -> u16 {
+    let preview = areas(screen, state).preview;
+    let rows = if let Some(error) = &state.generation_error {
+        Paragraph::new(format!("Error: {}", error))
+            .wrap(Wrap { trim: true })
+            .line_count(preview.width.saturating_sub(2))
+    } else if let Some(existing) = side_by_side_existing(state) {
+        let (left, right) = side_by_side_lines(state, existing);
+        left.len().max(right.len())
+    } else if state.preview_wrap {
+        Paragraph::new(with_line_numbers(preview_lines(state), 0, &state.theme))
+            .wrap(Wrap { trim: false })
+            .line_count(preview.width.saturating_sub(2))
+    } else {
+        preview_lines(state).len()
+    };
+    u16::try_from(rows)
+        .unwrap_or(u16::MAX)
+        .saturating_sub(preview_page(state, screen))
+}
+
+/// The file on disk and the generated one in two columns, scrolled together
+///
+/// Lines aren't wrapped so that the columns' rows stay side by side.
+fn render_side_by_side(f: &mut Frame, area: Rect, state: &EditorState, existing: &str) {
+    let theme = &state.theme;
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+    let (left, right) = side_by_side_lines(state, existing);
+
+    let filename = state.preview_path.to_str().unwrap_or("config.yml");
+    let mut generated = vec![Span::raw(format!(" Generated - {} ", filename))];
+    if !state.lint_issues.is_empty() {
+        generated.push(Span::styled(
+            format!("{} warning(s) ", state.lint_issues.len()),
+            Style::default().fg(theme.warning),
+        ));
+    }
+    for (lines, title, area) in [
+        (
+            left,
+            Line::from(format!(" On disk - {} ", filename)),
+            columns[0],
+        ),
+        (right, Line::from(generated), columns[1]),
+    ] {
+        let block = Block::default().title(title).borders(Borders::ALL);
+        f.render_widget(
+            Paragraph::new(with_line_numbers(lines, state.preview_hscroll, theme))
+                .block(block)
+                .scroll((state.preview_scroll, 0)),
+            area,
+        );
+    }
+}
+
+/// Put each issue under the line it's about; issues without a line go first
+///
+/// Lines are paired with their line numbers in the generated file; issue
+/// lines have none.
+fn with_inline_issues(
+    lines: Vec<(Option<usize>, Line<'static>)>,
+    issues: &[Issue],
+    theme: &Theme,
+) -> Vec<(Option<usize>, Line<'static>)> {
+    let style = Style::default().fg(theme.warning);
+    let issue_line = |issue: &Issue| {
+        (
+            None,
+            Line::from(Span::styled(format!("  ▲ warning: {}", issue), style)),
+        )
+    };
+    let mut result: Vec<(Option<usize>, Line<'static>)> = issues
+        .iter()
+        .filter(|issue| issue.line.is_none())
+        .map(issue_line)
+        .collect();
+    for (number, line) in lines {
+        result.push((number, line));
+        let Some(number) = number else {
+            continue;
+        };
+        result.extend(
+            issues
+                .iter()
+                .filter(|issue| issue.line == Some(number))
+                .map(issue_line),
+        );
+    }
+    result
+}
+
+fn render_warnings_panel(f: &mut Frame, area: Rect, state: &EditorState) {
+    let theme = &state.theme;
+    let count = state.warnings.len();
+    let toggle = state.keymap.label(Action::Warnings);
+    let noun = if count == 1 { "warning" } else { "warnings" };
+    let title = if state.warnings_collapsed {
+        format!(" ▶ {} {} ({} to show) ", count, noun, toggle)
+    } else {
+        format!(
+            " ▼ {} {} ({} to hide; see `cci secrets`) ",
+            count, noun, toggle
+        )
+    };
+    let borders = if state.warnings_collapsed {
+        Borders::TOP
+    } else {
+        Borders::ALL
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(borders)
+        .border_style(Style::default().fg(theme.warning));
+    if state.warnings_collapsed {
+        f.render_widget(block, area);
+        return;
+    }
+
+    // The last row counts what doesn't fit rather than showing one of them
+    let shown = if count > WARNING_ROWS + 1 {
+        WARNING_ROWS
+    } else {
+        count
+    };
+    let mut lines: Vec<Line> = state.warnings[..shown]
+        .iter()
+        .map(|warning| {
+            let color = match warning.kind {
+                WarningKind::Invalid | WarningKind::Conflict => theme.negative,
+                WarningKind::Platform => theme.secondary,
+                WarningKind::Lint | WarningKind::Secret => theme.warning,
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!("{:<9}", warning.kind.label()),
+                    Style::default().fg(color),
+                ),
+                Span::styled(
+                    warning.subject.clone(),
+                    Style::default()
+                        .fg(theme.warning)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!("  {}", warning.message),
+                    Style::default().fg(theme.description),
+                ),
+            ])
+        })
+        .collect();
+    if shown < count {
+        lines.push(Line::styled(
+            format!("… {} more", count - shown),
+            Style::default().fg(theme.dim),
+        ));
+    }
+
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// The platform menu, centered in `area`, with a platform on each row inside its border
+pub fn platform_menu_area(area: Rect) -> Rect {
     let menu_width = 40;
     let menu_height = 8;
-    let x = (area.width.saturating_sub(menu_width)) / 2;
-    let y = (area.height.saturating_sub(menu_height)) / 2;
-
-    let menu_area = Rect {
-        x,
-        y,
+    Rect {
+        x: (area.width.saturating_sub(menu_width)) / 2,
+        y: (area.height.saturating_sub(menu_height)) / 2,
         width: menu_width,
         height: menu_height,
-    };
+    }
+}
+
+fn render_platform_menu(f: &mut Frame, state: &EditorState) {
+    let theme = &state.theme;
+    let menu_area = platform_menu_area(f.area());
 
     // Clear the background
     f.render_widget(Clear, menu_area);
@@ -327,13 +1104,19 @@ fn render_platform_menu(f: &mut Frame, state: &EditorState) {
 
             let style = if is_selected {
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme.selected)
                     .add_modifier(Modifier::BOLD)
             } else {
                 Style::default()
             };
 
-            let marker = if is_current { "● " } else { "  " };
+            let marker = if is_current {
+                "● "
+            } else if state.extra_platforms.contains(platform) {
+                "+ "
+            } else {
+                "  "
+            };
             let prefix = if is_selected { "> " } else { "  " };
 
             ListItem::new(format!("{}{}{}", prefix, marker, platform.name())).style(style)
@@ -344,325 +1127,906 @@ fn render_platform_menu(f: &mut Frame, state: &EditorState) {
         Block::default()
             .title(" Select Platform ")
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Cyan))
-            .style(Style::default().bg(Color::Black)),
+            .border_style(Style::default().fg(theme.accent))
+            .style(Style::default().bg(theme.popup_background)),
     );
 
     f.render_widget(list, menu_area);
 }
 
-/// Compute a simple line-based diff between old and new text
-fn compute_diff(old: &str, new: &str) -> Vec<(String, DiffType)> {
-    let old_lines: Vec<&str> = old.lines().collect();
-    let new_lines: Vec<&str> = new.lines().collect();
-
-    let mut result = Vec::new();
-    let mut old_idx = 0;
-    let mut new_idx = 0;
-
-    while old_idx < old_lines.len() || new_idx < new_lines.len() {
-        if old_idx >= old_lines.len() {
-            // Remaining lines are added
-            result.push((new_lines[new_idx].to_string(), DiffType::Added));
-            new_idx += 1;
-        } else if new_idx >= new_lines.len() {
-            // Remaining lines are removed
-            result.push((old_lines[old_idx].to_string(), DiffType::Removed));
-            old_idx += 1;
-        } else if old_lines[old_idx] == new_lines[new_idx] {
-            // Lines match
-            result.push((new_lines[new_idx].to_string(), DiffType::Unchanged));
-            old_idx += 1;
-            new_idx += 1;
-        } else {
-            // Lines differ - look ahead to see if we can find a match
-            let mut found_match = false;
-
-            // Look ahead in new for current old line (removed line)
-            for i in (new_idx + 1)..(new_idx + 5).min(new_lines.len()) {
-                if old_lines[old_idx] == new_lines[i] {
-                    // Found old line later in new, so lines between are added
-                    while new_idx < i {
-                        result.push((new_lines[new_idx].to_string(), DiffType::Added));
-                        new_idx += 1;
-                    }
-                    found_match = true;
-                    break;
-                }
-            }
+fn render_enum_menu(f: &mut Frame, menu: &EnumMenu, theme: &Theme) {
+    let area = f.area();
 
-            if !found_match {
-                // Look ahead in old for current new line (added line)
-                for i in (old_idx + 1)..(old_idx + 5).min(old_lines.len()) {
-                    if old_lines[i] == new_lines[new_idx] {
-                        // Found new line later in old, so lines between are removed
-                        while old_idx < i {
-                            result.push((old_lines[old_idx].to_string(), DiffType::Removed));
-                            old_idx += 1;
-                        }
-                        found_match = true;
-                        break;
-                    }
-                }
+    // Each choice takes a line, plus one for its description if it has one
+    let items: Vec<ListItem> = menu
+        .variants
+        .iter()
+        .enumerate()
+        .map(|(i, variant)| {
+            let is_selected = i == menu.cursor;
+            let style = if is_selected {
+                Style::default()
+                    .fg(theme.selected)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            let prefix = if is_selected { "> " } else { "  " };
+            let mut lines = vec![Line::from(Span::styled(
+                format!("{}{}", prefix, variant.display_name),
+                style,
+            ))];
+            if !variant.description.is_empty() {
+                lines.push(Line::from(Span::styled(
+                    format!("    {}", variant.description),
+                    Style::default().fg(theme.description),
+                )));
             }
+            ListItem::new(lines)
+        })
+        .collect();
+    let lines: usize = items.iter().map(ListItem::height).sum();
 
-            if !found_match {
-                // No match found, treat as changed (removed + added)
-                result.push((old_lines[old_idx].to_string(), DiffType::Removed));
-                result.push((new_lines[new_idx].to_string(), DiffType::Added));
-                old_idx += 1;
-                new_idx += 1;
-            }
-        }
-    }
+    let menu_width = 70.min(area.width);
+    let menu_height = (lines as u16 + 2).min(area.height);
+    let menu_area = Rect {
+        x: (area.width.saturating_sub(menu_width)) / 2,
+        y: (area.height.saturating_sub(menu_height)) / 2,
+        width: menu_width,
+        height: menu_height,
+    };
+    f.render_widget(Clear, menu_area);
 
-    result
+    let list = List::new(items).block(
+        Block::default()
+            .title(format!(" {} ", menu.label))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.accent))
+            .style(Style::default().bg(theme.popup_background)),
+    );
+    f.render_stateful_widget(
+        list,
+        menu_area,
+        &mut ListState::default().with_selected(Some(menu.cursor)),
+    );
 }
 
-/// Highlight YAML with diff information
-fn highlight_yaml_with_diff(new_yaml: &str, old_yaml: &str) -> Vec<Line<'static>> {
-    let diff = compute_diff(old_yaml, new_yaml);
-    let mut lines = Vec::new();
+fn render_text_input(f: &mut Frame, input: &TextInput, theme: &Theme) {
+    let area = f.area();
 
-    for (line_text, diff_type) in diff {
-        let bg_color = match diff_type {
-            DiffType::Added => Some(Color::Green),
-            DiffType::Removed => Some(Color::Red),
-            DiffType::Unchanged => None,
-        };
+    let width = 60.min(area.width);
+    let height = 5;
+    let input_area = Rect {
+        x: (area.width.saturating_sub(width)) / 2,
+        y: (area.height.saturating_sub(height)) / 2,
+        width,
+        height,
+    };
+    f.render_widget(Clear, input_area);
+
+    // Keep the cursor in view when the text is wider than the box
+    let visible = width.saturating_sub(2) as usize;
+    let skip = (input.cursor + 1).saturating_sub(visible);
+    let text = match &input.placeholder {
+        Some(placeholder) if input.text.is_empty() => Line::from(Span::styled(
+            placeholder.chars().take(visible).collect::<String>(),
+            Style::default().fg(theme.dim),
+        )),
+        _ => Line::from(
+            input
+                .text
+                .chars()
+                .skip(skip)
+                .take(visible)
+                .collect::<String>(),
+        ),
+    };
+    let status = match &input.error {
+        Some(error) => Line::from(Span::styled(
+            error.clone(),
+            Style::default().fg(theme.negative),
+        )),
+        None => Line::from(Span::styled(
+            if input.integer {
+                "Whole number"
+            } else {
+                "Text"
+            },
+            Style::default().fg(theme.dim),
+        )),
+    };
 
-        // Apply YAML syntax highlighting to the line
-        let highlighted_line = highlight_yaml_line_owned(line_text, bg_color);
-        lines.push(highlighted_line);
-    }
+    let block = Block::default()
+        .title(format!(" {} ", input.label))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent))
+        .style(Style::default().bg(theme.popup_background));
+    f.render_widget(
+        Paragraph::new(vec![text, Line::from(""), status]).block(block),
+        input_area,
+    );
+    f.set_cursor_position((
+        input_area.x + 1 + (input.cursor - skip) as u16,
+        input_area.y + 1,
+    ));
+}
 
-    lines
+/// Every key, tree icon and color, for terminals too narrow for the footer's hints
+fn help_lines(theme: &Theme, keymap: &Keymap) -> Vec<Line<'static>> {
+    let heading = |text: &'static str| {
+        Line::from(Span::styled(
+            text,
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::BOLD),
+        ))
+    };
+    let entry = |key: String, color: Color, text: &'static str| {
+        Line::from(vec![
+            Span::styled(format!("  {:<16}", key), Style::default().fg(color)),
+            Span::raw(text),
+        ])
+    };
+    let action =
+        |action: Action, color: Color, text: &'static str| entry(keymap.label(action), color, text);
+    let pair = |first: Action, second: Action, color: Color, text: &'static str| {
+        entry(keymap.pair_label(first, second), color, text)
+    };
+    let swatch = |color: Color, text: &'static str| entry("■■■".to_string(), color, text);
+
+    vec![
+        heading("Tree"),
+        pair(Action::Up, Action::Down, theme.navigate, "Move the cursor"),
+        pair(
+            Action::Collapse,
+            Action::Expand,
+            theme.navigate,
+            "Collapse / expand",
+        ),
+        action(
+            Action::Toggle,
+            theme.selected,
+            "Toggle a preset, all of a feature's options, or one option",
+        ),
+        action(
+            Action::Edit,
+            theme.selected,
+            "Toggle, edit a value, or choose from a list",
+        ),
+        action(
+            Action::Search,
+            theme.accent,
+            "Search the tree; Enter keeps the filter",
+        ),
+        entry(
+            format!(
+                "{} / {}",
+                keymap.label(Action::NextMatch),
+                keymap.label(Action::PreviousMatch)
+            ),
+            theme.accent,
+            "Next / previous match",
+        ),
+        entry("Esc".to_string(), theme.negative, "Clear the search"),
+        Line::from(""),
+        heading("Preview"),
+        pair(
+            Action::ScrollPreviewDown,
+            Action::ScrollPreviewUp,
+            theme.secondary,
+            "Scroll the preview",
+        ),
+        pair(
+            Action::PreviewPageDown,
+            Action::PreviewPageUp,
+            theme.secondary,
+            "Scroll the preview a page down / up",
+        ),
+        pair(
+            Action::PreviewTop,
+            Action::PreviewBottom,
+            theme.secondary,
+            "Scroll the preview to the top / bottom",
+        ),
+        pair(
+            Action::ScrollPreviewLeft,
+            Action::ScrollPreviewRight,
+            theme.secondary,
+            "Scroll the preview sideways when lines don't wrap",
+        ),
+        action(
+            Action::Wrap,
+            theme.accent,
+            "Wrap long lines or cut them off",
+        ),
+        action(
+            Action::CopyPreview,
+            theme.accent,
+            "Copy the previewed file to the clipboard",
+        ),
+        action(
+            Action::OpenInEditor,
+            theme.accent,
+            "Polish the previewed file in $EDITOR, keeping what you add",
+        ),
+        action(
+            Action::SideBySide,
+            theme.accent,
+            "Diff against the file on disk inline or side by side",
+        ),
+        action(
+            Action::JumpToJob,
+            theme.accent,
+            "Scroll to the job the selected item produces",
+        ),
+        action(
+            Action::Warnings,
+            theme.warning,
+            "Show or hide the warnings panel (or click it)",
+        ),
+        pair(
+            Action::NextPlatform,
+            Action::PreviousPlatform,
+            theme.navigate,
+            "Next / previous platform",
+        ),
+        pair(
+            Action::NextProject,
+            Action::PreviousProject,
+            theme.navigate,
+            "Next / previous project, in a repository holding several (or click one)",
+        ),
+        action(
+            Action::PlatformMenu,
+            theme.accent,
+            "Platform menu; Space there also writes one",
+        ),
+        action(
+            Action::FileNaming,
+            theme.accent,
+            "One workflow file, or one per preset",
+        ),
+        action(
+            Action::DependencyTool,
+            theme.accent,
+            "Dependency updates: off, Dependabot, Renovate",
+        ),
+        Line::from(""),
+        heading("Files"),
+        action(
+            Action::Write,
+            theme.positive,
+            "Review the diff, then y to write or n to cancel",
+        ),
+        entry(
+            "b".to_string(),
+            theme.accent,
+            "In the review, back up replaced files to .bak",
+        ),
+        action(
+            Action::Profiles,
+            theme.accent,
+            "Profiles: Enter loads one, s saves the presets",
+        ),
+        action(
+            Action::Reset,
+            theme.secondary,
+            "Reset the selected option, feature or preset to its detected defaults",
+        ),
+        action(
+            Action::ResetAll,
+            theme.secondary,
+            "Reset everything to the detected defaults",
+        ),
+        action(
+            Action::Redetect,
+            theme.secondary,
+            "Detect the project again, e.g. after adding a manifest",
+        ),
+        pair(Action::Undo, Action::Redo, theme.secondary, "Undo / redo"),
+        action(Action::Help, theme.accent, "This help"),
+        action(Action::Quit, theme.negative, "Quit"),
+        Line::from(""),
+        heading("Mouse"),
+        entry(
+            "Click".to_string(),
+            theme.navigate,
+            "Select; on an arrow, circle or checkbox, toggle it",
+        ),
+        entry(
+            "Wheel".to_string(),
+            theme.secondary,
+            "Scroll the preview, tree or popup under it",
+        ),
+        Line::from(""),
+        heading("Icons"),
+        entry("▶ / ▼".to_string(), theme.text, "Collapsed / expanded"),
+        entry(
+            "● / ○".to_string(),
+            theme.text,
+            "Preset with / without options enabled",
+        ),
+        entry("[✓] / [ ]".to_string(), theme.text, "Option on / off"),
+        entry(
+            "name (value)".to_string(),
+            theme.text,
+            "Choice of a list option",
+        ),
+        entry(
+            "name: value".to_string(),
+            theme.text,
+            "Text or number option",
+        ),
+        entry(
+            "(addon)".to_string(),
+            theme.dim,
+            "Adds to the other presets' pipelines",
+        ),
+        entry(
+            "▲".to_string(),
+            theme.warning,
+            "Lint warning, under the line it's about",
+        ),
+        Line::from(""),
+        heading("Colors"),
+        swatch(theme.selected, "Cursor"),
+        swatch(theme.accent, "Search match"),
+        swatch(theme.text, "Changed from its default"),
+        swatch(theme.dim, "Left at its default"),
+        swatch(theme.positive, "● Enabled and suggested for this project"),
+        swatch(theme.added_background, "Line added to the file on disk"),
+        swatch(
+            theme.removed_background,
+            "Line removed from the file on disk",
+        ),
+        swatch(
+            theme.warning,
+            "Warnings, lint findings and required secrets",
+        ),
+    ]
 }
 
-/// Highlight a single YAML line with optional background color (owned version for diff)
-fn highlight_yaml_line_owned(line: String, bg_color: Option<Color>) -> Line<'static> {
-    let trimmed_start = line.trim_start();
+fn render_help(f: &mut Frame, state: &EditorState, scroll: u16) {
+    let theme = &state.theme;
+    let area = f.area();
+    let lines = help_lines(theme, &state.keymap);
+
+    let width = 70.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let help_area = Rect {
+        x: (area.width.saturating_sub(width)) / 2,
+        y: (area.height.saturating_sub(height)) / 2,
+        width,
+        height,
+    };
+    f.render_widget(Clear, help_area);
 
-    if trimmed_start.is_empty() {
-        return Line::from("");
-    }
+    // Don't scroll past the last line
+    let visible = height.saturating_sub(2);
+    let scroll = scroll.min((lines.len() as u16).saturating_sub(visible));
+    let block = Block::default()
+        .title(" Help (↑↓ to scroll, Esc to close) ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent))
+        .style(Style::default().bg(theme.popup_background));
+    f.render_widget(
+        Paragraph::new(lines).block(block).scroll((scroll, 0)),
+        help_area,
+    );
+}
 
-    // Comment lines
-    if trimmed_start.starts_with('#') {
-        let mut style = Style::default().fg(Color::DarkGray);
-        if let Some(bg) = bg_color {
-            style = style.bg(bg);
-        }
-        return Line::from(Span::styled(line, style));
+fn render_profile_menu(f: &mut Frame, menu: &ProfileMenu, theme: &Theme) {
+    let area = f.area();
+
+    let mut lines: Vec<Line> = if menu.names.is_empty() {
+        vec![Line::from(Span::styled(
+            "  No saved profiles",
+            Style::default().fg(theme.dim),
+        ))]
+    } else {
+        menu.names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                if i == menu.cursor && menu.new_name.is_none() {
+                    Line::from(Span::styled(
+                        format!("> {}", name),
+                        Style::default()
+                            .fg(theme.selected)
+                            .add_modifier(Modifier::BOLD),
+                    ))
+                } else {
+                    Line::from(format!("  {}", name))
+                }
+            })
+            .collect()
+    };
+    lines.push(Line::from(""));
+    if let Some(name) = &menu.new_name {
+        lines.push(Line::from(vec![
+            Span::styled("Save as: ", Style::default().fg(theme.accent)),
+            Span::raw(name.clone()),
+        ]));
+    }
+    if let Some(error) = &menu.error {
+        lines.push(Line::from(Span::styled(
+            error.clone(),
+            Style::default().fg(theme.negative),
+        )));
     }
 
-    // Parse the line into spans
-    let mut spans = Vec::new();
-    let indent = line.len() - trimmed_start.len();
+    let menu_width = 50.min(area.width);
+    let menu_height = (lines.len() as u16 + 2).min(area.height);
+    let menu_area = Rect {
+        x: (area.width.saturating_sub(menu_width)) / 2,
+        y: (area.height.saturating_sub(menu_height)) / 2,
+        width: menu_width,
+        height: menu_height,
+    };
+    f.render_widget(Clear, menu_area);
 
-    // Add indentation
-    if indent > 0 {
-        let mut style = Style::default();
-        if let Some(bg) = bg_color {
-            style = style.bg(bg);
-        }
-        spans.push(Span::styled(" ".repeat(indent), style));
+    let block = Block::default()
+        .title(" Profiles ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent))
+        .style(Style::default().bg(theme.popup_background));
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        menu_area,
+    );
+    if let Some(name) = &menu.new_name {
+        let row = menu_area.y + menu.names.len().max(1) as u16 + 2;
+        f.set_cursor_position((menu_area.x + 10 + name.chars().count() as u16, row));
     }
+}
 
-    // Key-value pairs
-    if let Some(colon_pos) = trimmed_start.find(':') {
-        let key = trimmed_start[..colon_pos].to_string();
-        let rest = &trimmed_start[colon_pos..];
+/// The files 'W' would touch, then a unified diff of each one that changes
+fn render_write_confirm(f: &mut Frame, confirm: &WriteConfirm, theme: &Theme) {
+    let area = f.area();
+    let width = (area.width * 9 / 10).max(40.min(area.width));
+    let height = (area.height * 9 / 10).max(10.min(area.height));
+    let confirm_area = Rect {
+        x: (area.width.saturating_sub(width)) / 2,
+        y: (area.height.saturating_sub(height)) / 2,
+        width,
+        height,
+    };
+    f.render_widget(Clear, confirm_area);
 
-        // Key (cyan)
-        let mut key_style = Style::default().fg(Color::Cyan);
-        if let Some(bg) = bg_color {
-            key_style = key_style.bg(bg);
+    let mut lines = Vec::new();
+    let foreign = confirm
+        .files
+        .iter()
+        .filter(|file| file.is_foreign())
+        .count();
+    if foreign > 0 {
+        lines.push(Line::styled(
+            format!(
+                "  ⚠ {} file(s) weren't written by cci. Their own jobs are kept, but their \
+                 settings and any jobs cci also generates are replaced.",
+                foreign
+            ),
+            Style::default()
+                .fg(theme.negative)
+                .add_modifier(Modifier::BOLD),
+        ));
+        lines.push(Line::from(""));
+    }
+    for file in &confirm.files {
+        let (status, color) = match &file.existing {
+            None => ("new", theme.positive),
+            Some(_) if file.is_unchanged() => ("unchanged", theme.dim),
+            Some(_) if file.is_foreign() => ("replaced", theme.negative),
+            Some(_) => ("modified", theme.warning),
+        };
+        let mut line = vec![
+            Span::styled(format!("  {:<10}", status), Style::default().fg(color)),
+            Span::raw(file.path.display().to_string()),
+        ];
+        if confirm.backup && file.existing.is_some() && !file.is_unchanged() {
+            line.push(Span::styled(
+                " (backed up to .bak)",
+                Style::default().fg(theme.accent),
+            ));
         }
-        spans.push(Span::styled(key, key_style));
+        lines.push(Line::from(line));
+    }
 
-        // Colon
-        let mut colon_style = Style::default();
-        if let Some(bg) = bg_color {
-            colon_style = colon_style.bg(bg);
-        }
-        spans.push(Span::styled(":".to_string(), colon_style));
+    for file in confirm.files.iter().filter(|file| !file.is_unchanged()) {
+        let old = file.existing.as_deref().unwrap_or("");
+        lines.push(Line::from(""));
+        lines.extend(diff_lines(old, &file.content, &file.path, theme));
+    }
 
-        if rest.len() > 1 {
-            let value = rest[1..].trim_start();
+    let changed = confirm
+        .files
+        .iter()
+        .filter(|file| !file.is_unchanged())
+        .count();
+    let title = if changed == 0 {
+        " Nothing to write - every file is up to date ".to_string()
+    } else {
+        format!(" Write {} file(s)? ", changed)
+    };
+    let border = if foreign > 0 {
+        theme.negative
+    } else {
+        theme.accent
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(border))
+        .style(Style::default().bg(theme.popup_background));
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((confirm.scroll, 0)),
+        confirm_area,
+    );
+}
 
-            // Space before value
-            let mut space_style = Style::default();
-            if let Some(bg) = bg_color {
-                space_style = space_style.bg(bg);
-            }
-            spans.push(Span::styled(" ".to_string(), space_style));
-
-            // Check for special values
-            let mut value_style = if value.starts_with('"') || value.starts_with('\'') {
-                // String value (green)
-                Style::default().fg(Color::Green)
-            } else if value == "true" || value == "false" {
-                // Boolean (magenta)
-                Style::default().fg(Color::Magenta)
-            } else if value.parse::<f64>().is_ok() {
-                // Number (yellow)
-                Style::default().fg(Color::Yellow)
+/// A unified diff of `path` going from `old` to `new`, colored line by line
+fn diff_lines(old: &str, new: &str, path: &Path, theme: &Theme) -> Vec<Line<'static>> {
+    let name = path.display().to_string();
+    unified_diff(old, new, &name, &name)
+        .lines()
+        .map(|line| {
+            let style = if line.starts_with("+++") || line.starts_with("---") {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else if line.starts_with("@@") {
+                Style::default().fg(theme.accent)
+            } else if line.starts_with('+') {
+                Style::default().fg(theme.positive)
+            } else if line.starts_with('-') {
+                Style::default().fg(theme.negative)
             } else {
-                // Other value
-                Style::default()
+                Style::default().fg(theme.dim)
             };
-
-            if let Some(bg) = bg_color {
-                value_style = value_style.bg(bg);
-            }
-
-            if !value.is_empty() {
-                spans.push(Span::styled(value.to_string(), value_style));
-            }
-        }
-    } else if trimmed_start.starts_with("- ") {
-        // List item
-        let mut bullet_style = Style::default().fg(Color::Yellow);
-        if let Some(bg) = bg_color {
-            bullet_style = bullet_style.bg(bg);
-        }
-        spans.push(Span::styled("- ".to_string(), bullet_style));
-
-        let mut text_style = Style::default();
-        if let Some(bg) = bg_color {
-            text_style = text_style.bg(bg);
-        }
-        spans.push(Span::styled(trimmed_start[2..].to_string(), text_style));
-    } else {
-        // Other lines
-        let mut style = Style::default();
-        if let Some(bg) = bg_color {
-            style = style.bg(bg);
-        }
-        spans.push(Span::styled(trimmed_start.to_string(), style));
-    }
-
-    Line::from(spans)
+            Line::from(Span::styled(line.to_string(), style))
+        })
+        .collect()
 }
 
-fn highlight_yaml(yaml: &str) -> Vec<Line<'_>> {
-    let mut lines = Vec::new();
-
-    for line in yaml.lines() {
-        let trimmed = line.trim_start();
-
-        if trimmed.is_empty() {
-            lines.push(Line::from(""));
-            continue;
-        }
-
-        // Comment lines
-        if trimmed.starts_with('#') {
-            lines.push(Line::from(Span::styled(
-                line.to_string(),
-                Style::default().fg(Color::DarkGray),
-            )));
-            continue;
-        }
+fn render_edit_confirm(f: &mut Frame, edit: &HandEdit, theme: &Theme) {
+    let area = f.area();
+    let width = (area.width * 9 / 10).max(40.min(area.width));
+    let height = (area.height * 9 / 10).max(10.min(area.height));
+    let confirm_area = Rect {
+        x: (area.width.saturating_sub(width)) / 2,
+        y: (area.height.saturating_sub(height)) / 2,
+        width,
+        height,
+    };
+    f.render_widget(Clear, confirm_area);
 
-        // Parse the line into spans
-        let mut spans = Vec::new();
-        let indent = line.len() - trimmed.len();
+    let mut lines = vec![
+        Line::styled(
+            "  Jobs and steps added here are kept each time the file is regenerated.",
+            Style::default().fg(theme.description),
+        ),
+        Line::from(""),
+    ];
+    lines.extend(diff_lines(&edit.before, &edit.after, &edit.path, theme));
 
-        // Add indentation
-        if indent > 0 {
-            spans.push(Span::raw(" ".repeat(indent)));
-        }
+    let block = Block::default()
+        .title(format!(" Keep your edits to {}? ", edit.path.display()))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent))
+        .style(Style::default().bg(theme.popup_background));
+    f.render_widget(
+        Paragraph::new(lines).block(block).scroll((edit.scroll, 0)),
+        confirm_area,
+    );
+}
 
-        // Key-value pairs
-        if let Some(colon_pos) = trimmed.find(':') {
-            let key = &trimmed[..colon_pos];
-            let rest = &trimmed[colon_pos..];
+fn render_session_restore(f: &mut Frame, restore: &SessionRestore, state: &EditorState) {
+    let theme = &state.theme;
+    let area = f.area();
+    let width = (area.width * 9 / 10).max(40.min(area.width));
+    let height = (area.height * 9 / 10).max(10.min(area.height));
+    let restore_area = Rect {
+        x: (area.width.saturating_sub(width)) / 2,
+        y: (area.height.saturating_sub(height)) / 2,
+        width,
+        height,
+    };
+    f.render_widget(Clear, restore_area);
+
+    let session = &restore.session;
+    let mut lines = vec![
+        Line::styled(
+            "  The editor didn't quit last time. Restoring its choices changes the preview like this:",
+            Style::default().fg(theme.description),
+        ),
+        Line::styled(
+            format!(
+                "  Platform: {}{}",
+                session.platform.name(),
+                if session.hand_edits.is_empty() {
+                    String::new()
+                } else {
+                    format!(", {} hand-edited file(s)", session.hand_edits.len())
+                }
+            ),
+            Style::default().fg(theme.dim),
+        ),
+        Line::from(""),
+    ];
+    lines.extend(diff_lines(
+        &restore.before,
+        &restore.after,
+        &state.preview_path,
+        theme,
+    ));
 
-            // Key (cyan)
-            spans.push(Span::styled(
-                key.to_string(),
-                Style::default().fg(Color::Cyan),
-            ));
+    let block = Block::default()
+        .title(" Restore your last session? ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent))
+        .style(Style::default().bg(theme.popup_background));
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((restore.scroll, 0)),
+        restore_area,
+    );
+}
 
-            // Colon
-            spans.push(Span::raw(":"));
-
-            if rest.len() > 1 {
-                let value = &rest[1..].trim_start();
-
-                // Check for special values
-                if value.starts_with('"') || value.starts_with('\'') {
-                    // String value (green)
-                    spans.push(Span::raw(" "));
-                    spans.push(Span::styled(
-                        value.to_string(),
-                        Style::default().fg(Color::Green),
-                    ));
-                } else if *value == "true" || *value == "false" {
-                    // Boolean (magenta)
-                    spans.push(Span::raw(" "));
-                    spans.push(Span::styled(
-                        value.to_string(),
-                        Style::default().fg(Color::Magenta),
-                    ));
-                } else if value.parse::<f64>().is_ok() {
-                    // Number (yellow)
-                    spans.push(Span::raw(" "));
-                    spans.push(Span::styled(
-                        value.to_string(),
-                        Style::default().fg(Color::Yellow),
-                    ));
-                } else if !value.is_empty() {
-                    // Other value
-                    spans.push(Span::raw(" "));
-                    spans.push(Span::raw(value.to_string()));
-                }
-            }
-        } else if trimmed.starts_with("- ") {
-            // List item
-            spans.push(Span::styled(
-                "- ".to_string(),
-                Style::default().fg(Color::Yellow),
-            ));
-            spans.push(Span::raw(trimmed[2..].to_string()));
-        } else {
-            // Other lines
-            spans.push(Span::raw(trimmed.to_string()));
-        }
+fn render_suggestion(f: &mut Frame, suggestion: &Suggestion, state: &EditorState) {
+    let theme = &state.theme;
+    let preset = state
+        .registry
+        .get(&suggestion.preset_id)
+        .map(|preset| preset.preset_name().to_string())
+        .unwrap_or_else(|| suggestion.preset_id.clone());
+    let question = format!(
+        "We noticed {} — enable the {} preset?",
+        suggestion.facet.facet.description(),
+        preset
+    );
 
-        lines.push(Line::from(spans));
+    let area = f.area();
+    let width = (question.chars().count() as u16 + 6)
+        .clamp(40, 80)
+        .min(area.width);
+    let height = 6.min(area.height);
+    let suggestion_area = Rect {
+        x: (area.width.saturating_sub(width)) / 2,
+        y: (area.height.saturating_sub(height)) / 2,
+        width,
+        height,
+    };
+    f.render_widget(Clear, suggestion_area);
+
+    let mut lines = vec![
+        Line::styled(
+            format!(" {}", question),
+            Style::default().fg(theme.description),
+        ),
+        Line::styled(
+            format!(" Found: {}", suggestion.facet.path.display()),
+            Style::default().fg(theme.dim),
+        ),
+    ];
+    if let Some(command) = suggestion.facet.deploy_command() {
+        lines.push(Line::styled(
+            format!(" Deploys with: {}", command),
+            Style::default().fg(theme.dim),
+        ));
     }
 
-    lines
+    let remaining = state.suggestions.len();
+    let block = Block::default()
+        .title(if remaining > 1 {
+            format!(" Suggestion (1 of {}) ", remaining)
+        } else {
+            " Suggestion ".to_string()
+        })
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent))
+        .style(Style::default().bg(theme.popup_background));
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        suggestion_area,
+    );
 }
 
 fn render_footer(f: &mut Frame, area: Rect, state: &EditorState) {
-    let help_text = if state.platform_menu_open {
+    let theme = &state.theme;
+    let keymap = &state.keymap;
+    // The footer only has room for each action's first key
+    let key = |action: Action| keymap.first_label(action);
+    let help_text = if state.session_restore.is_some() {
+        vec![
+            Span::styled("y", Style::default().fg(theme.positive)),
+            Span::raw(" restore | "),
+            Span::styled("n", Style::default().fg(theme.negative)),
+            Span::raw(" start over | "),
+            Span::styled("↑↓/jk", Style::default().fg(theme.navigate)),
+            Span::raw(" scroll"),
+        ]
+    } else if !state.suggestions.is_empty() {
         vec![
-            Span::styled("↑↓/jk", Style::default().fg(Color::Blue)),
+            Span::styled("y", Style::default().fg(theme.positive)),
+            Span::raw(" enable | "),
+            Span::styled("n", Style::default().fg(theme.negative)),
+            Span::raw(" not now | "),
+            Span::styled("Esc", Style::default().fg(theme.negative)),
+            Span::raw(" skip all"),
+        ]
+    } else if state.edit_confirm.is_some() {
+        vec![
+            Span::styled("y", Style::default().fg(theme.positive)),
+            Span::raw(" keep | "),
+            Span::styled("n", Style::default().fg(theme.negative)),
+            Span::raw(" discard | "),
+            Span::styled("↑↓/jk", Style::default().fg(theme.navigate)),
+            Span::raw(" scroll"),
+        ]
+    } else if let Some(confirm) = &state.write_confirm {
+        vec![
+            Span::styled("y", Style::default().fg(theme.positive)),
+            Span::raw(" write | "),
+            Span::styled("n", Style::default().fg(theme.negative)),
+            Span::raw(" cancel | "),
+            Span::styled("b", Style::default().fg(theme.accent)),
+            Span::raw(format!(
+                " backup: {} | ",
+                if confirm.backup { "on" } else { "off" }
+            )),
+            Span::styled("↑↓/jk", Style::default().fg(theme.navigate)),
+            Span::raw(" scroll"),
+        ]
+    } else if state.help_scroll.is_some() {
+        vec![
+            Span::styled("↑↓/jk", Style::default().fg(theme.navigate)),
+            Span::raw(" scroll | "),
+            Span::styled("Esc", Style::default().fg(theme.negative)),
+            Span::raw(" close"),
+        ]
+    } else if state
+        .profile_menu
+        .as_ref()
+        .is_some_and(|menu| menu.new_name.is_some())
+    {
+        vec![
+            Span::raw("Type a name | "),
+            Span::styled("Enter", Style::default().fg(theme.positive)),
+            Span::raw(" save | "),
+            Span::styled("Esc", Style::default().fg(theme.negative)),
+            Span::raw(" back"),
+        ]
+    } else if state.profile_menu.is_some() {
+        vec![
+            Span::styled("↑↓/jk", Style::default().fg(theme.navigate)),
+            Span::raw(" navigate | "),
+            Span::styled("Enter", Style::default().fg(theme.positive)),
+            Span::raw(" load | "),
+            Span::styled("s", Style::default().fg(theme.accent)),
+            Span::raw(" save current | "),
+            Span::styled("Esc", Style::default().fg(theme.negative)),
+            Span::raw(" close"),
+        ]
+    } else if state.text_input.is_some() {
+        vec![
+            Span::styled("←→", Style::default().fg(theme.navigate)),
+            Span::raw(" move | "),
+            Span::styled("Enter", Style::default().fg(theme.positive)),
+            Span::raw(" save | "),
+            Span::styled("Esc", Style::default().fg(theme.negative)),
+            Span::raw(" cancel"),
+        ]
+    } else if state.search.as_ref().is_some_and(|search| search.typing) {
+        vec![
+            Span::raw("Type to filter | "),
+            Span::styled("↑↓", Style::default().fg(theme.navigate)),
             Span::raw(" navigate | "),
-            Span::styled("Enter", Style::default().fg(Color::Green)),
+            Span::styled("Enter", Style::default().fg(theme.positive)),
+            Span::raw(" keep filter | "),
+            Span::styled("Esc", Style::default().fg(theme.negative)),
+            Span::raw(" clear"),
+        ]
+    } else if state.enum_menu.is_some() {
+        vec![
+            Span::styled("↑↓/jk", Style::default().fg(theme.navigate)),
+            Span::raw(" navigate | "),
+            Span::styled("Enter", Style::default().fg(theme.positive)),
             Span::raw(" select | "),
-            Span::styled("Esc", Style::default().fg(Color::Red)),
+            Span::styled("Esc", Style::default().fg(theme.negative)),
             Span::raw(" close"),
         ]
+    } else if state.platform_menu_open {
+        vec![
+            Span::styled("↑↓/jk", Style::default().fg(theme.navigate)),
+            Span::raw(" navigate | "),
+            Span::styled("Enter", Style::default().fg(theme.positive)),
+            Span::raw(" select | "),
+            Span::styled("Space", Style::default().fg(theme.selected)),
+            Span::raw(" also write | "),
+            Span::styled("Esc", Style::default().fg(theme.negative)),
+            Span::raw(" close"),
+        ]
+    } else if state.search.is_some() {
+        vec![
+            Span::styled(
+                format!("{}/{}", key(Action::NextMatch), key(Action::PreviousMatch)),
+                Style::default().fg(theme.accent),
+            ),
+            Span::raw(" next/previous match | "),
+            Span::styled(key(Action::Search), Style::default().fg(theme.accent)),
+            Span::raw(" edit search | "),
+            Span::styled("Esc", Style::default().fg(theme.negative)),
+            Span::raw(" clear search | "),
+            Span::styled(
+                format!("{}/{}", key(Action::Toggle), key(Action::Edit)),
+                Style::default().fg(theme.selected),
+            ),
+            Span::raw(" toggle/edit | "),
+            Span::styled(
+                keymap.pair_label(Action::Collapse, Action::Expand),
+                Style::default().fg(theme.navigate),
+            ),
+            Span::raw(" expand/collapse | "),
+            Span::styled(key(Action::Write), Style::default().fg(theme.positive)),
+            Span::raw(" write"),
+        ]
     } else {
+        // Help comes first so narrow terminals still show where the rest are
         vec![
-            Span::styled("←→/hl", Style::default().fg(Color::Blue)),
+            Span::styled(key(Action::Help), Style::default().fg(theme.accent)),
+            Span::raw(" help | "),
+            Span::styled(
+                keymap.pair_label(Action::Collapse, Action::Expand),
+                Style::default().fg(theme.navigate),
+            ),
             Span::raw(" expand/collapse | "),
-            Span::styled("Space/Enter", Style::default().fg(Color::Yellow)),
-            Span::raw(" toggle | "),
-            Span::styled("↑↓/jk", Style::default().fg(Color::Blue)),
+            Span::styled(
+                format!("{}/{}", key(Action::Toggle), key(Action::Edit)),
+                Style::default().fg(theme.selected),
+            ),
+            Span::raw(" toggle/edit | "),
+            Span::styled(
+                keymap.pair_label(Action::Up, Action::Down),
+                Style::default().fg(theme.navigate),
+            ),
             Span::raw(" navigate | "),
-            Span::styled("JK", Style::default().fg(Color::Magenta)),
+            Span::styled(
+                keymap.pair_label(Action::ScrollPreviewDown, Action::ScrollPreviewUp),
+                Style::default().fg(theme.secondary),
+            ),
             Span::raw(" scroll preview | "),
-            Span::styled("p", Style::default().fg(Color::Cyan)),
+            Span::styled(key(Action::Search), Style::default().fg(theme.accent)),
+            Span::raw(" search | "),
+            Span::styled(key(Action::PlatformMenu), Style::default().fg(theme.accent)),
             Span::raw(" platform | "),
-            Span::styled("W", Style::default().fg(Color::Green)),
+            Span::styled(
+                key(Action::DependencyTool),
+                Style::default().fg(theme.accent),
+            ),
+            Span::raw(format!(
+                " deps: {} | ",
+                state
+                    .dependency_tool
+                    .map(|tool| tool.name())
+                    .unwrap_or("off")
+            )),
+            Span::styled(key(Action::FileNaming), Style::default().fg(theme.accent)),
+            Span::raw(format!(" files: {} | ", state.file_naming.name())),
+            Span::styled(key(Action::Profiles), Style::default().fg(theme.accent)),
+            Span::raw(" profiles | "),
+            Span::styled(
+                format!("{}/{}", key(Action::Undo), key(Action::Redo)),
+                Style::default().fg(theme.secondary),
+            ),
+            Span::raw(" undo/redo | "),
+            Span::styled(key(Action::Write), Style::default().fg(theme.positive)),
             Span::raw(" write | "),
-            Span::styled("q", Style::default().fg(Color::Red)),
+            Span::styled(key(Action::Quit), Style::default().fg(theme.negative)),
             Span::raw(" quit"),
         ]
     };
@@ -0,0 +1,144 @@
+//! Warnings about the configuration being edited, listed in the panel under
+//! the preview and worked out again on every change
+
+use crate::platforms::checks::{CheckKind, LocalCheck};
+
+/// What a warning is about, in the order the panel lists them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WarningKind {
+    /// An option value its rules don't allow
+    Invalid,
+    /// Options that work against each other
+    Conflict,
+    /// Something the target platform can't do as configured
+    Platform,
+    /// A lint finding in the generated file, e.g. an outdated action
+    Lint,
+    /// A secret the pipeline needs set up on the platform
+    Secret,
+}
+
+impl WarningKind {
+    /// The tag the panel puts in front of the warning
+    pub fn label(self) -> &'static str {
+        match self {
+            WarningKind::Invalid => "invalid",
+            WarningKind::Conflict => "conflict",
+            WarningKind::Platform => "platform",
+            WarningKind::Lint => "lint",
+            WarningKind::Secret => "secret",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    pub kind: WarningKind,
+    /// What the warning is about: a secret's name, a line, an option
+    pub subject: String,
+    pub message: String,
+}
+
+impl Warning {
+    pub fn new(kind: WarningKind, subject: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            subject: subject.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Formatters from different presets that check the same files differently
+///
+/// `checks` pairs each check with the name of the preset it's from. Two
+/// formatters rarely agree on a file's layout, so they can't both pass.
+pub fn formatter_conflicts(checks: &[(String, LocalCheck)]) -> Vec<Warning> {
+    let formatters: Vec<&(String, LocalCheck)> = checks
+        .iter()
+        .filter(|(_, check)| check.kind == CheckKind::Format)
+        .collect();
+    let mut warnings = Vec::new();
+    for (index, (preset, check)) in formatters.iter().enumerate() {
+        for (other_preset, other) in &formatters[index + 1..] {
+            if check.command == other.command {
+                continue;
+            }
+            let Some(shared) = shared_extensions(&check.extensions, &other.extensions) else {
+                continue;
+            };
+            let files = if shared.is_empty() {
+                "the same files".to_string()
+            } else {
+                let extensions: Vec<String> =
+                    shared.iter().map(|ext| format!(".{}", ext)).collect();
+                format!("{} files", extensions.join(", "))
+            };
+            warnings.push(Warning::new(
+                WarningKind::Conflict,
+                "Two formatters",
+                format!(
+                    "`{}` ({}) and `{}` ({}) both check {}; only one can pass",
+                    check.command, preset, other.command, other_preset, files
+                ),
+            ));
+        }
+    }
+    warnings
+}
+
+/// The extensions two checks both look at, empty when both look at every
+/// file, or `None` when they look at different files
+fn shared_extensions(a: &[&'static str], b: &[&'static str]) -> Option<Vec<&'static str>> {
+    let shared: Vec<&'static str> = match (a.is_empty(), b.is_empty()) {
+        (true, true) => return Some(Vec::new()),
+        (true, false) => b.to_vec(),
+        (false, true) => a.to_vec(),
+        (false, false) => a.iter().filter(|ext| b.contains(ext)).copied().collect(),
+    };
+    (!shared.is_empty()).then_some(shared)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn formatter(command: &str, extensions: Vec<&'static str>) -> LocalCheck {
+        LocalCheck {
+            id: "test/fmt".to_string(),
+            name: "Format".to_string(),
+            kind: CheckKind::Format,
+            command: command.to_string(),
+            extensions,
+        }
+    }
+
+    #[test]
+    fn test_formatter_conflicts() {
+        let checks = vec![
+            (
+                "Python".to_string(),
+                formatter("black --check .", vec!["py"]),
+            ),
+            (
+                "Scripts".to_string(),
+                formatter("ruff format --check .", vec!["py", "pyi"]),
+            ),
+            (
+                "Rust".to_string(),
+                formatter("cargo fmt --check", vec!["rs"]),
+            ),
+            // The same formatter twice is duplicated work, not a conflict
+            (
+                "Again".to_string(),
+                formatter("black --check .", vec!["py"]),
+            ),
+        ];
+        let warnings = formatter_conflicts(&checks);
+        assert_eq!(warnings.len(), 2, "{:?}", warnings);
+        assert!(warnings[0].message.contains("black --check ."));
+        assert!(warnings[0].message.contains("ruff format --check ."));
+        assert!(warnings[0].message.contains(".py files"));
+        assert_eq!(warnings[0].kind, WarningKind::Conflict);
+    }
+}
@@ -0,0 +1,545 @@
+//! Combining several presets' pipelines into one file per platform
+//!
+//! Each preset builds a complete pipeline with its own triggers. To share a
+//! file, whatever the presets set differently for the whole pipeline is moved
+//! onto each preset's own jobs: GitLab workflow rules become job rules,
+//! Jenkins `when` conditions become stage conditions, and GitHub permissions,
+//! environment variables and GitLab caches move onto jobs. A GitHub workflow
+//! can't give jobs triggers of their own, so presets with different `on:`
+//! settings keep separate workflow files; Jenkins pipelines with different
+//! agents or conflicting environments stay apart too.
+
+use crate::editor::state::{FileNaming, Platform};
+use crate::error::{config_error, Result};
+use crate::platforms::circleci::models::CircleCIConfig;
+use crate::platforms::github::models::GitHubWorkflow;
+use crate::platforms::gitlab::models::{GitLabCI, GitLabJob, GitLabRule};
+use crate::platforms::helpers::PlatformConfig;
+use crate::platforms::jenkins::models::JenkinsConfig;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Merge `pipelines`, given as (preset ID, pipeline) pairs, into as few as possible
+///
+/// Returns each pipeline left with the IDs of the presets merged into it, in
+/// the order the presets were given; the first holds the first preset.
+pub fn combine(pipelines: Vec<(String, PlatformConfig)>) -> Vec<(Vec<String>, PlatformConfig)> {
+    let mut groups: Vec<Vec<(String, PlatformConfig)>> = Vec::new();
+    'pipelines: for (preset_id, pipeline) in pipelines {
+        for group in &mut groups {
+            if group
+                .iter()
+                .all(|(_, member)| can_share_file(member, &pipeline))
+            {
+                group.push((preset_id, pipeline));
+                continue 'pipelines;
+            }
+        }
+        groups.push(vec![(preset_id, pipeline)]);
+    }
+
+    groups
+        .into_iter()
+        .map(|group| {
+            let presets = group
+                .iter()
+                .map(|(preset_id, _)| preset_id.clone())
+                .collect();
+            (presets, merge(group))
+        })
+        .collect()
+}
+
+/// Split `pipelines`, given as (preset ID, pipeline) pairs, into the files
+/// `naming` puts them in on `platform`
+///
+/// Returns each file's presets, path and pipeline. Unless `naming` gives every
+/// preset a file of its own, the pipelines are [`combine`]d first. Fails when
+/// a platform that reads one file would need more.
+pub fn place(
+    pipelines: Vec<(String, PlatformConfig)>,
+    platform: Platform,
+    naming: FileNaming,
+) -> Result<Vec<(Vec<String>, PathBuf, PlatformConfig)>> {
+    let separate = pipelines
+        .iter()
+        .all(|(preset_id, _)| naming.preset_path(platform, preset_id).is_some());
+    let groups = if separate {
+        pipelines
+            .into_iter()
+            .map(|(preset_id, pipeline)| (vec![preset_id], pipeline))
+            .collect()
+    } else {
+        combine(pipelines)
+    };
+
+    let mut placed: Vec<(Vec<String>, PathBuf, PlatformConfig)> = Vec::new();
+    for (index, (presets, pipeline)) in groups.into_iter().enumerate() {
+        let Some(path) = naming.path(platform, &presets[0], index) else {
+            return Err(config_error(format!(
+                "{} reads a single {}, but the {} and {} presets can't share one",
+                platform.name(),
+                platform.output_path().display(),
+                placed[0].0[0],
+                presets[0]
+            )));
+        };
+        placed.push((presets, path, pipeline));
+    }
+    Ok(placed)
+}
+
+fn can_share_file(a: &PlatformConfig, b: &PlatformConfig) -> bool {
+    match (a, b) {
+        (PlatformConfig::GitHub(a), PlatformConfig::GitHub(b))
+        | (PlatformConfig::Gitea(a), PlatformConfig::Gitea(b)) => {
+            a.on == b.on && a.defaults == b.defaults
+        }
+        (PlatformConfig::GitLab(_), PlatformConfig::GitLab(_))
+        | (PlatformConfig::CircleCI(_), PlatformConfig::CircleCI(_)) => true,
+        (PlatformConfig::Jenkins(a), PlatformConfig::Jenkins(b)) => {
+            a.agent == b.agent
+                && !b.environment.iter().any(|(key, value)| {
+                    a.environment
+                        .iter()
+                        .any(|(known, existing)| known == key && existing != value)
+                })
+        }
+        _ => false,
+    }
+}
+
+/// Merge a group of pipelines that [`can_share_file`]
+fn merge(mut group: Vec<(String, PlatformConfig)>) -> PlatformConfig {
+    if group.len() == 1 {
+        return group.remove(0).1;
+    }
+    let first = group[0].1.clone();
+    let (ids, pipelines): (Vec<String>, Vec<PlatformConfig>) = group.into_iter().unzip();
+    match first {
+        PlatformConfig::GitHub(_) => PlatformConfig::GitHub(merge_github(
+            pipelines
+                .into_iter()
+                .filter_map(|pipeline| match pipeline {
+                    PlatformConfig::GitHub(workflow) => Some(workflow),
+                    _ => None,
+                })
+                .collect(),
+        )),
+        PlatformConfig::Gitea(_) => PlatformConfig::Gitea(merge_github(
+            pipelines
+                .into_iter()
+                .filter_map(|pipeline| match pipeline {
+                    PlatformConfig::Gitea(workflow) => Some(workflow),
+                    _ => None,
+                })
+                .collect(),
+        )),
+        PlatformConfig::GitLab(_) => PlatformConfig::GitLab(merge_gitlab(
+            pipelines
+                .into_iter()
+                .filter_map(|pipeline| match pipeline {
+                    PlatformConfig::GitLab(ci) => Some(ci),
+                    _ => None,
+                })
+                .collect(),
+        )),
+        PlatformConfig::CircleCI(_) => PlatformConfig::CircleCI(merge_circleci(
+            ids.into_iter()
+                .zip(pipelines)
+                .filter_map(|(id, pipeline)| match pipeline {
+                    PlatformConfig::CircleCI(config) => Some((id, config)),
+                    _ => None,
+                })
+                .collect(),
+        )),
+        PlatformConfig::Jenkins(_) => PlatformConfig::Jenkins(merge_jenkins(
+            ids.into_iter()
+                .zip(pipelines)
+                .filter_map(|(id, pipeline)| match pipeline {
+                    PlatformConfig::Jenkins(config) => Some((id, config)),
+                    _ => None,
+                })
+                .collect(),
+        )),
+    }
+}
+
+/// Whether every item is the same
+fn all_equal<T: PartialEq>(mut items: impl Iterator<Item = T>) -> bool {
+    match items.next() {
+        Some(first) => items.all(|item| item == first),
+        None => true,
+    }
+}
+
+fn merge_github(mut workflows: Vec<GitHubWorkflow>) -> GitHubWorkflow {
+    if !all_equal(workflows.iter().map(|workflow| &workflow.permissions)) {
+        for workflow in &mut workflows {
+            if let Some(permissions) = workflow.permissions.take() {
+                for job in workflow.jobs.values_mut() {
+                    job.permissions.get_or_insert_with(|| permissions.clone());
+                }
+            }
+        }
+    }
+    if !all_equal(workflows.iter().map(|workflow| &workflow.env)) {
+        for workflow in &mut workflows {
+            if let Some(env) = workflow.env.take() {
+                for job in workflow.jobs.values_mut() {
+                    let job_env = job.env.get_or_insert_with(BTreeMap::new);
+                    for (key, value) in &env {
+                        job_env.entry(key.clone()).or_insert_with(|| value.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    let mut workflows = workflows.into_iter();
+    let mut merged = workflows.next().expect("a group has a pipeline");
+    for workflow in workflows {
+        if merged.concurrency.is_none() {
+            merged.concurrency = workflow.concurrency;
+        }
+        for (id, job) in workflow.jobs {
+            merged.jobs.insert_if_absent(id, job);
+        }
+    }
+    merged
+}
+
+fn merge_gitlab(mut pipelines: Vec<GitLabCI>) -> GitLabCI {
+    // A pipeline without rules runs for every push, so the merged one must too
+    let rules: Vec<Option<Vec<GitLabRule>>> = pipelines
+        .iter()
+        .map(|ci| {
+            ci.workflow
+                .as_ref()
+                .map(|workflow| workflow.rules.clone())
+                .filter(|rules| !rules.is_empty())
+        })
+        .collect();
+    let merged_rules: Vec<GitLabRule> = if rules.iter().any(Option::is_none) {
+        Vec::new()
+    } else {
+        let mut merged: Vec<GitLabRule> = Vec::new();
+        for rule in rules.iter().flatten().flatten() {
+            if !merged.contains(rule) {
+                merged.push(rule.clone());
+            }
+        }
+        merged
+    };
+    for (ci, rules) in pipelines.iter_mut().zip(rules) {
+        let Some(rules) = rules.filter(|rules| *rules != merged_rules) else {
+            continue;
+        };
+        for job in ci.jobs.values_mut() {
+            restrict_job(job, &rules);
+        }
+    }
+
+    if !all_equal(pipelines.iter().map(|ci| &ci.cache)) {
+        for ci in &mut pipelines {
+            if let Some(cache) = ci.cache.take() {
+                for job in ci.jobs.values_mut() {
+                    job.cache.get_or_insert_with(|| cache.clone());
+                }
+            }
+        }
+    }
+
+    // Jobs take their settings from their own pipeline's defaults
+    if !all_equal(pipelines.iter().map(|ci| &ci.default)) {
+        for ci in &mut pipelines {
+            let Some(default) = ci.default.take() else {
+                continue;
+            };
+            for job in ci.jobs.values_mut() {
+                if job.image.is_none() {
+                    job.image = default.image.clone();
+                }
+                if job.before_script.is_none() {
+                    job.before_script = default.before_script.clone();
+                }
+                if job.interruptible.is_none() {
+                    job.interruptible = default.interruptible;
+                }
+            }
+        }
+    }
+
+    let mut pipelines = pipelines.into_iter();
+    let mut merged = pipelines.next().expect("a group has a pipeline");
+    for mut ci in pipelines {
+        if merged.workflow.is_none() {
+            merged.workflow = ci.workflow.take();
+        }
+        for include in ci.include.into_iter().flatten() {
+            let known = merged.include.get_or_insert_with(Vec::new);
+            if !known.contains(&include) {
+                known.push(include);
+            }
+        }
+        for stage in ci.stages.into_iter().flatten() {
+            let known = merged.stages.get_or_insert_with(Vec::new);
+            if !known.contains(&stage) {
+                known.push(stage);
+            }
+        }
+        // Job variables override global ones, so a clashing value moves onto this pipeline's jobs
+        for (key, value) in ci.variables.into_iter().flatten() {
+            let known = merged.variables.get_or_insert_with(BTreeMap::new);
+            match known.get(&key) {
+                Some(existing) if *existing != value => {
+                    for job in ci.jobs.values_mut() {
+                        job.variables
+                            .get_or_insert_with(BTreeMap::new)
+                            .entry(key.clone())
+                            .or_insert_with(|| value.clone());
+                    }
+                }
+                Some(_) => {}
+                None => {
+                    known.insert(key, value);
+                }
+            }
+        }
+        for (id, job) in ci.jobs {
+            merged.jobs.insert_if_absent(id, job);
+        }
+    }
+    if let Some(workflow) = &mut merged.workflow {
+        workflow.rules = merged_rules;
+    }
+    merged
+}
+
+/// Only run `job` when one of its pipeline's `rules` matches
+///
+/// Rules the job already has are combined with each of the pipeline's. Jobs
+/// limited with `only:` are left alone, since it can't be used with `rules:`.
+fn restrict_job(job: &mut GitLabJob, rules: &[GitLabRule]) {
+    if job.only.is_some() {
+        return;
+    }
+    job.rules = Some(match job.rules.take() {
+        None => rules.to_vec(),
+        Some(job_rules) => rules
+            .iter()
+            .flat_map(|rule| {
+                job_rules.iter().map(move |job_rule| GitLabRule {
+                    if_condition: match (&rule.if_condition, &job_rule.if_condition) {
+                        (Some(a), Some(b)) => Some(format!("({}) && ({})", a, b)),
+                        (a, b) => a.clone().or_else(|| b.clone()),
+                    },
+                    changes: job_rule.changes.clone().or_else(|| rule.changes.clone()),
+                    when: job_rule.when.clone().or_else(|| rule.when.clone()),
+                })
+            })
+            .collect(),
+    });
+}
+
+/// Merge CircleCI configs, each with the ID of the preset that built it
+///
+/// Workflows carry their own triggers, so they're kept apart, prefixed with
+/// the preset ID when their names clash.
+fn merge_circleci(configs: Vec<(String, CircleCIConfig)>) -> CircleCIConfig {
+    let mut configs = configs.into_iter();
+    let (_, mut merged) = configs.next().expect("a group has a pipeline");
+    for (preset_id, config) in configs {
+        for (name, orb) in config.orbs.into_iter().flatten() {
+            merged
+                .orbs
+                .get_or_insert_with(BTreeMap::new)
+                .entry(name)
+                .or_insert(orb);
+        }
+        for (name, parameter) in config.parameters.into_iter().flatten() {
+            merged
+                .parameters
+                .get_or_insert_with(BTreeMap::new)
+                .entry(name)
+                .or_insert(parameter);
+        }
+        for (id, job) in config.jobs {
+            merged.jobs.insert_if_absent(id, job);
+        }
+        for (name, workflow) in config.workflows {
+            let name = if merged.workflows.contains_key(&name) {
+                format!("{}-{}", preset_id, name)
+            } else {
+                name
+            };
+            merged.workflows.entry(name).or_insert(workflow);
+        }
+    }
+    merged
+}
+
+/// Merge Jenkins pipelines, each with the ID of the preset that built it
+///
+/// Stage names must be unique, so a clashing one gets the preset ID added.
+fn merge_jenkins(mut configs: Vec<(String, JenkinsConfig)>) -> JenkinsConfig {
+    if !all_equal(configs.iter().map(|(_, config)| &config.when)) {
+        for (_, config) in &mut configs {
+            let when = std::mem::take(&mut config.when);
+            let condition = match when.as_slice() {
+                [] => continue,
+                [condition] => condition.clone(),
+                _ => format!("anyOf {{ {} }}", when.join("; ")),
+            };
+            for stage in &mut config.stages {
+                stage.when.insert(0, condition.clone());
+            }
+        }
+    }
+
+    let mut configs = configs.into_iter();
+    let (_, mut merged) = configs.next().expect("a group has a pipeline");
+    for (preset_id, config) in configs {
+        for variable in config.environment {
+            if !merged.environment.contains(&variable) {
+                merged.environment.push(variable);
+            }
+        }
+        for trigger in config.triggers {
+            if !merged.triggers.contains(&trigger) {
+                merged.triggers.push(trigger);
+            }
+        }
+        for mut stage in config.stages {
+            if merged.stages.iter().any(|known| known.name == stage.name) {
+                stage.name = format!("{} ({})", stage.name, preset_id);
+            }
+            merged.stages.push(stage);
+        }
+        for (condition, steps) in config.post {
+            match merged
+                .post
+                .iter_mut()
+                .find(|(known, _)| *known == condition)
+            {
+                Some((_, known)) => known.extend(steps),
+                None => merged.post.push((condition, steps)),
+            }
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::registry::build_registry;
+    use crate::editor::state::Platform;
+
+    /// Each preset's default pipeline for `platform`
+    fn pipelines(presets: &[&str], platform: Platform) -> Vec<(String, PlatformConfig)> {
+        let registry = build_registry();
+        presets
+            .iter()
+            .map(|id| {
+                let preset = registry.get(id).unwrap();
+                let config = preset.default_config(true);
+                let pipeline = preset.build(&config, platform, "stable").unwrap();
+                (id.to_string(), pipeline)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_github_workflows_share_a_file_only_with_the_same_triggers() {
+        let combined = combine(pipelines(
+            &["rust", "python-app", "docker"],
+            Platform::GitHub,
+        ));
+        let presets: Vec<&Vec<String>> = combined.iter().map(|(presets, _)| presets).collect();
+        assert_eq!(
+            presets,
+            vec![
+                &vec!["rust".to_string(), "python-app".to_string()],
+                &vec!["docker".to_string()]
+            ]
+        );
+        let PlatformConfig::GitHub(workflow) = &combined[0].1 else {
+            panic!("not a GitHub workflow");
+        };
+        assert!(workflow.jobs.contains_key("rust/test"));
+        assert!(workflow.jobs.contains_key("python/test"));
+    }
+
+    #[test]
+    fn test_gitlab_workflow_rules_move_onto_jobs() {
+        let combined = combine(pipelines(&["rust", "docker", "release"], Platform::GitLab));
+        assert_eq!(combined.len(), 1);
+        let PlatformConfig::GitLab(ci) = &combined[0].1 else {
+            panic!("not a GitLab pipeline");
+        };
+        let rules = &ci.workflow.as_ref().unwrap().rules;
+        assert_eq!(rules.len(), 3);
+        assert_eq!(ci.stages.as_ref().unwrap().last().unwrap(), "release");
+        assert_eq!(ci.variables.as_ref().unwrap()["CARGO_TERM_COLOR"], "always");
+        // Only the jobs of pipelines that cancel superseded runs are interruptible
+        assert!(ci.default.is_none());
+        assert_eq!(ci.jobs["rust/test"].interruptible, Some(true));
+        assert_eq!(ci.jobs["release/publish"].interruptible, Some(false));
+
+        // Docker's rules are the merged ones, so its jobs don't need their own
+        assert!(ci.jobs["docker/build"].rules.is_none());
+        let conditions = |job: &str| -> Vec<String> {
+            ci.jobs[job]
+                .rules
+                .iter()
+                .flatten()
+                .filter_map(|rule| rule.if_condition.clone())
+                .collect()
+        };
+        assert_eq!(conditions("rust/test").len(), 2);
+        assert!(!conditions("rust/test").iter().any(|c| c.contains("TAG")));
+        assert_eq!(
+            conditions("release/publish"),
+            vec!["$CI_COMMIT_TAG =~ /^v.*$/"]
+        );
+    }
+
+    #[test]
+    fn test_jenkins_conditions_move_onto_stages() {
+        let combined = combine(pipelines(
+            &["rust", "python-app", "release"],
+            Platform::Jenkins,
+        ));
+        assert_eq!(combined.len(), 1);
+        let PlatformConfig::Jenkins(config) = &combined[0].1 else {
+            panic!("not a Jenkins pipeline");
+        };
+        assert!(config.when.is_empty());
+        let names: Vec<&str> = config
+            .stages
+            .iter()
+            .map(|stage| stage.name.as_str())
+            .collect();
+        assert!(names.contains(&"Test"));
+        assert!(names.contains(&"Test (python-app)"));
+        let release = config
+            .stages
+            .iter()
+            .find(|stage| stage.name == "Release")
+            .unwrap();
+        assert_eq!(release.when, vec!["tag 'v*'"]);
+        assert!(config.stages[0].when[0].starts_with("anyOf { branch 'main'"));
+    }
+
+    #[test]
+    fn test_circleci_workflows_are_renamed_when_they_clash() {
+        let combined = combine(pipelines(&["python-app", "docker"], Platform::CircleCI));
+        let PlatformConfig::CircleCI(config) = &combined[0].1 else {
+            panic!("not a CircleCI config");
+        };
+        let names: Vec<&String> = config.workflows.keys().collect();
+        assert_eq!(names, vec!["docker-main", "main"]);
+        assert!(config.jobs.contains_key("docker/build"));
+    }
+}
@@ -0,0 +1,302 @@
+//! Dependency update configs
+//!
+//! Dependabot and Renovate keep dependencies current between releases. The
+//! generated config only covers the ecosystems the project actually uses so
+//! neither tool has to scan for manifests that don't exist.
+
+use crate::detection::{DetectorRegistry, ProjectType};
+use crate::error::{config_error, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Tool that opens the dependency update pull requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DependencyTool {
+    Dependabot,
+    Renovate,
+}
+
+impl DependencyTool {
+    pub fn all() -> Vec<DependencyTool> {
+        vec![DependencyTool::Dependabot, DependencyTool::Renovate]
+    }
+
+    pub fn from_id(id: &str) -> Option<DependencyTool> {
+        Self::all().into_iter().find(|tool| tool.id() == id)
+    }
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            DependencyTool::Dependabot => "dependabot",
+            DependencyTool::Renovate => "renovate",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DependencyTool::Dependabot => "Dependabot",
+            DependencyTool::Renovate => "Renovate",
+        }
+    }
+
+    /// Where the tool looks for its config
+    pub fn output_path(&self) -> PathBuf {
+        match self {
+            DependencyTool::Dependabot => PathBuf::from(".github/dependabot.yml"),
+            DependencyTool::Renovate => PathBuf::from("renovate.json"),
+        }
+    }
+}
+
+/// How often updates are checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateSchedule {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl UpdateSchedule {
+    pub fn all() -> Vec<UpdateSchedule> {
+        vec![
+            UpdateSchedule::Daily,
+            UpdateSchedule::Weekly,
+            UpdateSchedule::Monthly,
+        ]
+    }
+
+    pub fn from_id(id: &str) -> Option<UpdateSchedule> {
+        Self::all().into_iter().find(|schedule| schedule.id() == id)
+    }
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            UpdateSchedule::Daily => "daily",
+            UpdateSchedule::Weekly => "weekly",
+            UpdateSchedule::Monthly => "monthly",
+        }
+    }
+
+    /// Renovate takes a natural-language schedule instead of an interval
+    fn renovate_schedule(&self) -> &'static str {
+        match self {
+            UpdateSchedule::Daily => "before 6am",
+            UpdateSchedule::Weekly => "before 6am on monday",
+            UpdateSchedule::Monthly => "before 6am on the first day of the month",
+        }
+    }
+}
+
+/// Package ecosystem with its own manifests
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Ecosystem {
+    Cargo,
+    Pip,
+    Gomod,
+    Npm,
+    Docker,
+    GitHubActions,
+}
+
+impl Ecosystem {
+    /// Dependabot `package-ecosystem` value
+    pub fn dependabot_id(&self) -> &'static str {
+        match self {
+            Ecosystem::Cargo => "cargo",
+            Ecosystem::Pip => "pip",
+            Ecosystem::Gomod => "gomod",
+            Ecosystem::Npm => "npm",
+            Ecosystem::Docker => "docker",
+            Ecosystem::GitHubActions => "github-actions",
+        }
+    }
+
+    /// Renovate managers that read this ecosystem's manifests
+    pub fn renovate_managers(&self) -> &'static [&'static str] {
+        match self {
+            Ecosystem::Cargo => &["cargo"],
+            Ecosystem::Pip => &["pep621", "pip_requirements", "pip_setup"],
+            Ecosystem::Gomod => &["gomod"],
+            Ecosystem::Npm => &["npm"],
+            Ecosystem::Docker => &["dockerfile", "docker-compose"],
+            Ecosystem::GitHubActions => &["github-actions"],
+        }
+    }
+
+    fn from_project_type(project_type: &ProjectType) -> Ecosystem {
+        match project_type {
+            ProjectType::RustLibrary | ProjectType::RustBinary | ProjectType::RustWorkspace => {
+                Ecosystem::Cargo
+            }
+            ProjectType::PythonApp | ProjectType::PythonLibrary => Ecosystem::Pip,
+            ProjectType::GoApp | ProjectType::GoLibrary => Ecosystem::Gomod,
+            ProjectType::NodeApp | ProjectType::NodeLibrary => Ecosystem::Npm,
+            ProjectType::DockerImage => Ecosystem::Docker,
+        }
+    }
+}
+
+/// Ecosystems used by the project in `dir`
+///
+/// GitHub Actions is included once the repository has workflows to update.
+pub fn detect_ecosystems(dir: &Path) -> Result<Vec<Ecosystem>> {
+    let mut ecosystems: Vec<Ecosystem> = DetectorRegistry::new()
+        .detect_all(dir)?
+        .iter()
+        .map(|detection| Ecosystem::from_project_type(&detection.project_type))
+        .collect();
+
+    if dir.join(".github/workflows").is_dir() {
+        ecosystems.push(Ecosystem::GitHubActions);
+    }
+
+    ecosystems.sort();
+    ecosystems.dedup();
+    Ok(ecosystems)
+}
+
+#[derive(Serialize)]
+struct DependabotConfig {
+    version: u32,
+    updates: Vec<DependabotUpdate>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct DependabotUpdate {
+    package_ecosystem: String,
+    directory: String,
+    schedule: DependabotSchedule,
+}
+
+#[derive(Serialize)]
+struct DependabotSchedule {
+    interval: String,
+}
+
+/// Dependency update config for a set of ecosystems
+#[derive(Debug, Clone, PartialEq)]
+pub struct DependencyUpdates {
+    pub tool: DependencyTool,
+    pub schedule: UpdateSchedule,
+    pub ecosystems: Vec<Ecosystem>,
+}
+
+impl DependencyUpdates {
+    /// Render the config file for the chosen tool
+    pub fn render(&self) -> Result<String> {
+        if self.ecosystems.is_empty() {
+            return Err(config_error(
+                "No package ecosystems found to keep up to date",
+            ));
+        }
+
+        match self.tool {
+            DependencyTool::Dependabot => self.render_dependabot(),
+            DependencyTool::Renovate => Ok(self.render_renovate()),
+        }
+    }
+
+    fn render_dependabot(&self) -> Result<String> {
+        let config = DependabotConfig {
+            version: 2,
+            updates: self
+                .ecosystems
+                .iter()
+                .map(|ecosystem| DependabotUpdate {
+                    package_ecosystem: ecosystem.dependabot_id().to_string(),
+                    directory: "/".to_string(),
+                    schedule: DependabotSchedule {
+                        interval: self.schedule.id().to_string(),
+                    },
+                })
+                .collect(),
+        };
+        Ok(serde_yaml::to_string(&config)?)
+    }
+
+    fn render_renovate(&self) -> String {
+        let managers: Vec<String> = self
+            .ecosystems
+            .iter()
+            .flat_map(|ecosystem| ecosystem.renovate_managers())
+            .map(|manager| format!("\"{}\"", manager))
+            .collect();
+
+        format!(
+            "{{\n  \"$schema\": \"https://docs.renovatebot.com/renovate-schema.json\",\n  \"extends\": [\"config:recommended\"],\n  \"enabledManagers\": [{}],\n  \"schedule\": [\"{}\"]\n}}\n",
+            managers.join(", "),
+            self.schedule.renovate_schedule()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_detect_ecosystems() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("Dockerfile"), "FROM rust:1").unwrap();
+        fs::create_dir_all(dir.path().join(".github/workflows")).unwrap();
+
+        assert_eq!(
+            detect_ecosystems(dir.path()).unwrap(),
+            vec![
+                Ecosystem::Cargo,
+                Ecosystem::Docker,
+                Ecosystem::GitHubActions
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_dependabot() {
+        let updates = DependencyUpdates {
+            tool: DependencyTool::Dependabot,
+            schedule: UpdateSchedule::Weekly,
+            ecosystems: vec![Ecosystem::Gomod, Ecosystem::GitHubActions],
+        };
+        let yaml = updates.render().unwrap();
+
+        assert!(yaml.starts_with("version: 2\n"));
+        assert!(yaml.contains(
+            "- package-ecosystem: gomod\n  directory: /\n  schedule:\n    interval: weekly"
+        ));
+        assert!(yaml.contains("package-ecosystem: github-actions"));
+    }
+
+    #[test]
+    fn test_render_renovate() {
+        let updates = DependencyUpdates {
+            tool: DependencyTool::Renovate,
+            schedule: UpdateSchedule::Monthly,
+            ecosystems: vec![Ecosystem::Cargo, Ecosystem::Docker],
+        };
+        let json = updates.render().unwrap();
+
+        assert!(
+            json.contains("\"enabledManagers\": [\"cargo\", \"dockerfile\", \"docker-compose\"]")
+        );
+        assert!(json.contains("\"schedule\": [\"before 6am on the first day of the month\"]"));
+    }
+
+    #[test]
+    fn test_render_without_ecosystems() {
+        let updates = DependencyUpdates {
+            tool: DependencyTool::Renovate,
+            schedule: UpdateSchedule::Weekly,
+            ecosystems: vec![],
+        };
+
+        assert!(updates.render().is_err());
+    }
+}
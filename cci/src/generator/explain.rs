@@ -0,0 +1,241 @@
+//! Trace generated jobs and steps back to the options behind them
+//!
+//! Every enabled option is switched back off in turn and the presets are
+//! rebuilt; jobs and steps that disappear were produced by that option.
+//! Whatever survives every switch comes from the preset itself.
+
+use crate::editor::config::{OptionValue, PresetConfig};
+use crate::editor::registry::PresetRegistry;
+use crate::editor::state::{FileNaming, Platform};
+use crate::error::Result;
+use crate::generator::MultiPresetGenerator;
+use crate::models::adapters::lift;
+use crate::platforms::github::models::job_id;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// A job, or a step of a job, in one generated file
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Item {
+    pub file: PathBuf,
+    pub job: String,
+    /// Step label, or `None` for the job itself
+    pub step: Option<String>,
+    /// Counts steps sharing a label within the job, to tell them apart
+    occurrence: usize,
+}
+
+/// Something that made a job or step part of the output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    /// Always generated by the preset
+    Preset { preset: String },
+    /// Added by a decorator addon enabled in the config
+    Decorator { preset: String },
+    /// Only generated while an option has its current value
+    Option {
+        preset: String,
+        feature: String,
+        option: String,
+        value: String,
+    },
+}
+
+/// Why a job or step exists
+#[derive(Debug, Clone)]
+pub struct Explanation {
+    pub item: Item,
+    pub origins: Vec<Origin>,
+}
+
+impl Explanation {
+    /// Whether `target` names this job, or this step by (part of) its label
+    pub fn matches(&self, target: &str) -> bool {
+        let target = target.to_lowercase();
+        match &self.item.step {
+            // GitHub job IDs have `/` replaced, so `rust/lint` still finds `rust-lint`
+            None => {
+                let job = self.item.job.to_lowercase();
+                job == target || job == job_id(&target)
+            }
+            Some(step) => step.to_lowercase().contains(&target),
+        }
+    }
+}
+
+/// Explain every job and step generated for `platform`, in output order
+///
+/// `file_naming` is the config's, so jobs are listed under the files they're written to.
+pub fn explain(
+    preset_configs: &[(String, PresetConfig)],
+    file_naming: FileNaming,
+    registry: Arc<PresetRegistry>,
+    platform: Platform,
+    language_version: &str,
+) -> Result<Vec<Explanation>> {
+    let build = |configs: Vec<(String, PresetConfig)>| {
+        MultiPresetGenerator::new(
+            configs,
+            registry.clone(),
+            platform,
+            language_version.to_string(),
+        )
+        .with_file_naming(file_naming)
+        .build_pipelines()
+    };
+
+    let mut explanations = Vec::new();
+    let mut owners = Vec::new();
+    for (preset_id, filename, pipeline) in build(preset_configs.to_vec())? {
+        for item in items(filename, &pipeline) {
+            owners.push(preset_id.clone());
+            explanations.push(Explanation {
+                item,
+                origins: Vec::new(),
+            });
+        }
+    }
+
+    for (index, (preset_id, config)) in preset_configs.iter().enumerate() {
+        let Some(preset) = registry.get(preset_id) else {
+            continue;
+        };
+
+        let mut variants = Vec::new();
+        if preset.is_decorator() {
+            let mut without = preset_configs.to_vec();
+            without.remove(index);
+            variants.push((
+                Origin::Decorator {
+                    preset: preset_id.clone(),
+                },
+                without,
+            ));
+        }
+        for feature in preset.features() {
+            for option in &feature.options {
+                let current = config
+                    .get(&option.id)
+                    .cloned()
+                    .unwrap_or_else(|| option.default_value.clone());
+                let Some(off) = switched_off(&current, &option.default_value) else {
+                    continue;
+                };
+                let mut changed = preset_configs.to_vec();
+                changed[index].1.set(option.id.clone(), off);
+                variants.push((
+                    Origin::Option {
+                        preset: preset_id.clone(),
+                        feature: feature.display_name.clone(),
+                        option: option.display_name.clone(),
+                        value: describe_value(&current),
+                    },
+                    changed,
+                ));
+            }
+        }
+
+        for (origin, configs) in variants {
+            // A value some other option requires can't be switched off alone
+            let Ok(pipelines) = build(configs) else {
+                continue;
+            };
+            let remaining: BTreeSet<Item> = pipelines
+                .into_iter()
+                .flat_map(|(_, filename, pipeline)| items(filename, &pipeline))
+                .collect();
+            for explanation in &mut explanations {
+                if !remaining.contains(&explanation.item) {
+                    explanation.origins.push(origin.clone());
+                }
+            }
+        }
+    }
+
+    for (explanation, owner) in explanations.iter_mut().zip(owners) {
+        if explanation.origins.is_empty() {
+            explanation.origins.push(Origin::Preset { preset: owner });
+        }
+    }
+    Ok(explanations)
+}
+
+/// Jobs and steps of one built file
+fn items(file: PathBuf, pipeline: &crate::platforms::helpers::PlatformConfig) -> Vec<Item> {
+    let pipeline = lift(pipeline, &mut Vec::new());
+    let mut items = Vec::new();
+    for job in &pipeline.jobs {
+        items.push(Item {
+            file: file.clone(),
+            job: job.id.clone(),
+            step: None,
+            occurrence: 0,
+        });
+        let mut labels: Vec<String> = Vec::new();
+        for step in &job.steps {
+            let label = step.label();
+            let occurrence = labels.iter().filter(|known| **known == label).count();
+            labels.push(label.clone());
+            items.push(Item {
+                file: file.clone(),
+                job: job.id.clone(),
+                step: Some(label),
+                occurrence,
+            });
+        }
+    }
+    items
+}
+
+/// The value an option falls back to when it's switched off, if it's on
+fn switched_off(current: &OptionValue, default: &OptionValue) -> Option<OptionValue> {
+    match current {
+        OptionValue::Bool(true) => Some(OptionValue::Bool(false)),
+        OptionValue::Enum { .. } if current != default => Some(default.clone()),
+        _ => None,
+    }
+}
+
+/// An option's value as it's shown, e.g. `on` for an enabled toggle
+pub(crate) fn describe_value(value: &OptionValue) -> String {
+    match value {
+        OptionValue::Bool(value) => if *value { "on" } else { "off" }.to_string(),
+        OptionValue::Enum { selected, .. } => selected.clone(),
+        OptionValue::String(value) => value.clone(),
+        OptionValue::Int(value) => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::registry::build_registry;
+
+    #[test]
+    fn test_explains_jobs_by_option() {
+        let registry = Arc::new(build_registry());
+        let mut config = registry.get("rust").unwrap().default_config(true);
+        config.set("enable_linter".to_string(), OptionValue::Bool(true));
+        let explanations = explain(
+            &[("rust".to_string(), config)],
+            FileNaming::default(),
+            registry,
+            Platform::GitHub,
+            "stable",
+        )
+        .unwrap();
+
+        let lint = explanations
+            .iter()
+            .find(|explanation| explanation.item.step.is_none() && explanation.matches("rust/lint"))
+            .expect("lint job");
+        assert!(lint.origins.iter().any(|origin| matches!(
+            origin,
+            Origin::Option { option, value, .. } if option.to_lowercase().contains("lint") && value == "on"
+        )));
+        assert!(explanations
+            .iter()
+            .all(|explanation| !explanation.origins.is_empty()));
+    }
+}
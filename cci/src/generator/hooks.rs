@@ -0,0 +1,279 @@
+//! Git hook configs mirroring the CI checks
+//!
+//! Formatting and lint checks run before each commit; tests and audits, which
+//! take longer, run before each push. Builds are left to CI.
+
+use crate::error::Result;
+use crate::platforms::checks::{CheckKind, LocalCheck};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Hook manager that installs and runs the hooks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookTool {
+    PreCommit,
+    Lefthook,
+}
+
+impl HookTool {
+    pub fn all() -> Vec<HookTool> {
+        vec![HookTool::PreCommit, HookTool::Lefthook]
+    }
+
+    pub fn from_id(id: &str) -> Option<HookTool> {
+        Self::all().into_iter().find(|tool| tool.id() == id)
+    }
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            HookTool::PreCommit => "pre-commit",
+            HookTool::Lefthook => "lefthook",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookTool::PreCommit => "pre-commit",
+            HookTool::Lefthook => "Lefthook",
+        }
+    }
+
+    /// Where the tool looks for its config
+    pub fn output_path(&self) -> PathBuf {
+        match self {
+            HookTool::PreCommit => PathBuf::from(".pre-commit-config.yaml"),
+            HookTool::Lefthook => PathBuf::from("lefthook.yml"),
+        }
+    }
+
+    /// Command that installs the git hooks from the config
+    pub fn install_command(&self) -> &'static str {
+        match self {
+            HookTool::PreCommit => "pre-commit install",
+            HookTool::Lefthook => "lefthook install",
+        }
+    }
+}
+
+/// Git hook a check runs in
+fn hook_stage(kind: CheckKind) -> Option<&'static str> {
+    match kind {
+        CheckKind::Format | CheckKind::Lint => Some("pre-commit"),
+        CheckKind::Test | CheckKind::Audit => Some("pre-push"),
+        CheckKind::Build => None,
+    }
+}
+
+/// Hook config for a set of checks
+pub struct GitHooks {
+    pub tool: HookTool,
+    pub checks: Vec<LocalCheck>,
+}
+
+#[derive(Serialize)]
+struct PreCommitConfig {
+    default_install_hook_types: Vec<&'static str>,
+    repos: Vec<PreCommitRepo>,
+}
+
+#[derive(Serialize)]
+struct PreCommitRepo {
+    repo: &'static str,
+    hooks: Vec<PreCommitHook>,
+}
+
+#[derive(Serialize)]
+struct PreCommitHook {
+    id: String,
+    name: String,
+    entry: String,
+    language: &'static str,
+    pass_filenames: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    files: Option<String>,
+    stages: Vec<&'static str>,
+}
+
+#[derive(Serialize)]
+struct LefthookHook {
+    commands: BTreeMap<String, LefthookCommand>,
+}
+
+#[derive(Serialize)]
+struct LefthookCommand {
+    run: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    glob: Option<String>,
+}
+
+impl GitHooks {
+    /// Checks worth running from a hook, with the hook each runs in
+    fn staged_checks(&self) -> impl Iterator<Item = (&'static str, &LocalCheck)> {
+        self.checks
+            .iter()
+            .filter_map(|check| hook_stage(check.kind).map(|stage| (stage, check)))
+    }
+
+    /// Hooks that have at least one check, in the order git runs them
+    fn stages(&self) -> Vec<&'static str> {
+        let mut stages: Vec<&'static str> = self.staged_checks().map(|(stage, _)| stage).collect();
+        stages.sort();
+        stages.dedup();
+        stages
+    }
+
+    pub fn render(&self) -> Result<String> {
+        match self.tool {
+            HookTool::PreCommit => self.render_pre_commit(),
+            HookTool::Lefthook => self.render_lefthook(),
+        }
+    }
+
+    fn render_pre_commit(&self) -> Result<String> {
+        let hooks = self
+            .staged_checks()
+            .map(|(stage, check)| PreCommitHook {
+                id: hook_id(check),
+                name: check.name.clone(),
+                entry: shell_entry(&check.command),
+                language: "system",
+                // The checks cover the whole project, like the CI jobs do
+                pass_filenames: false,
+                files: match check.extensions.as_slice() {
+                    [] => None,
+                    extensions => Some(format!("\\.({})$", extensions.join("|"))),
+                },
+                stages: vec![stage],
+            })
+            .collect();
+
+        let config = PreCommitConfig {
+            default_install_hook_types: self.stages(),
+            repos: vec![PreCommitRepo {
+                repo: "local",
+                hooks,
+            }],
+        };
+        Ok(serde_yaml::to_string(&config)?)
+    }
+
+    fn render_lefthook(&self) -> Result<String> {
+        let mut hooks: BTreeMap<&'static str, LefthookHook> = BTreeMap::new();
+        for (stage, check) in self.staged_checks() {
+            hooks
+                .entry(stage)
+                .or_insert_with(|| LefthookHook {
+                    commands: BTreeMap::new(),
+                })
+                .commands
+                .insert(
+                    hook_id(check),
+                    LefthookCommand {
+                        run: check.command.clone(),
+                        glob: match check.extensions.as_slice() {
+                            [] => None,
+                            [extension] => Some(format!("*.{}", extension)),
+                            extensions => Some(format!("*.{{{}}}", extensions.join(","))),
+                        },
+                    },
+                );
+        }
+        Ok(serde_yaml::to_string(&hooks)?)
+    }
+}
+
+/// Hook IDs double as names in `SKIP=...`, so keep them free of slashes
+fn hook_id(check: &LocalCheck) -> String {
+    check.id.replace('/', "-")
+}
+
+/// pre-commit runs `entry` without a shell, so wrap commands that need one
+fn shell_entry(command: &str) -> String {
+    if command.contains(['|', '&', ';', '$', '<', '>', '(', ')']) {
+        format!("sh -c '{}'", command.replace('\'', "'\\''"))
+    } else {
+        command.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checks() -> Vec<LocalCheck> {
+        vec![
+            LocalCheck::new(
+                "rust/format",
+                "Check formatting",
+                CheckKind::Format,
+                "cargo fmt -- --check",
+                &["rs"],
+            ),
+            LocalCheck::new(
+                "rust/test",
+                "Run tests",
+                CheckKind::Test,
+                "cargo test",
+                &["rs"],
+            ),
+            LocalCheck::new(
+                "rust/security",
+                "Audit dependencies",
+                CheckKind::Audit,
+                "cargo audit",
+                &["lock", "toml"],
+            ),
+            LocalCheck::new(
+                "rust/build",
+                "Build release",
+                CheckKind::Build,
+                "cargo build --release",
+                &["rs"],
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_pre_commit_config() {
+        let hooks = GitHooks {
+            tool: HookTool::PreCommit,
+            checks: checks(),
+        };
+        let yaml = hooks.render().unwrap();
+
+        assert!(yaml.starts_with(
+            "default_install_hook_types:\n- pre-commit\n- pre-push\nrepos:\n- repo: local\n"
+        ));
+        assert!(yaml.contains("  - id: rust-format\n    name: Check formatting\n    entry: cargo fmt -- --check\n    language: system\n    pass_filenames: false\n    files: \\.(rs)$\n    stages:\n    - pre-commit\n"));
+        assert!(yaml.contains("files: \\.(lock|toml)$\n    stages:\n    - pre-push\n"));
+        // Builds are left to CI
+        assert!(!yaml.contains("rust-build"));
+    }
+
+    #[test]
+    fn test_lefthook_config() {
+        let hooks = GitHooks {
+            tool: HookTool::Lefthook,
+            checks: checks(),
+        };
+        let yaml = hooks.render().unwrap();
+
+        assert!(yaml.starts_with("pre-commit:\n  commands:\n    rust-format:\n      run: cargo fmt -- --check\n      glob: '*.rs'\n"));
+        assert!(yaml.contains("pre-push:\n  commands:\n"));
+        assert!(yaml.contains("glob: '*.{lock,toml}'"));
+    }
+
+    #[test]
+    fn test_shell_entry_wraps_shell_syntax() {
+        assert_eq!(shell_entry("pytest"), "pytest");
+        assert_eq!(
+            shell_entry("test -z \"$(gofmt -l .)\""),
+            "sh -c 'test -z \"$(gofmt -l .)\"'"
+        );
+        assert_eq!(
+            shell_entry("echo 'a' && b"),
+            "sh -c 'echo '\\''a'\\'' && b'"
+        );
+    }
+}
@@ -0,0 +1,645 @@
+//! Regenerating over pipeline files that were edited by hand
+//!
+//! Every job cci writes is marked with a `# cci:managed` comment listing a
+//! fingerprint of each step it generated. When a pipeline is written over an
+//! existing file, jobs without the marker are carried over, and so are steps
+//! inside marked jobs whose fingerprint isn't listed. A generated step the
+//! user edited takes the place of its regenerated version. Marked jobs that
+//! are no longer generated are removed.
+//!
+//! So regenerating doesn't reshuffle a file that's been reviewed, the merged
+//! file keeps the comments the old one started with, and the keys of its maps
+//! stay in the old file's order, with new keys placed after the ones they
+//! follow in the generated file.
+//!
+//! Jenkinsfiles are Groovy rather than YAML and are always overwritten.
+
+use crate::editor::state::Platform;
+use crate::error::Result;
+use crate::platforms::github::pinning::annotate_pins;
+use serde_yaml::{Mapping, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Comment appended to the key of each generated job
+const MARKER: &str = "# cci:managed";
+
+/// Top-level GitLab keys that configure the pipeline rather than name a job
+const GITLAB_KEYWORDS: &[&str] = &[
+    "default",
+    "include",
+    "stages",
+    "variables",
+    "workflow",
+    "image",
+    "services",
+    "cache",
+    "before_script",
+    "after_script",
+];
+
+/// Where a platform keeps its jobs and their steps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Layout {
+    /// Jobs under a top-level `jobs:` map, each with a `steps:` list
+    Jobs,
+    /// Jobs as top-level keys, each with a `script:` list (GitLab)
+    TopLevel,
+}
+
+impl Layout {
+    fn for_platform(platform: Platform) -> Option<Layout> {
+        match platform {
+            Platform::GitHub | Platform::Gitea | Platform::CircleCI => Some(Layout::Jobs),
+            Platform::GitLab => Some(Layout::TopLevel),
+            Platform::Jenkins => None,
+        }
+    }
+
+    fn steps_key(&self) -> &'static str {
+        match self {
+            Layout::Jobs => "steps",
+            Layout::TopLevel => "script",
+        }
+    }
+
+    fn jobs<'a>(&self, root: &'a Value) -> Vec<(String, &'a Value)> {
+        let container = match self {
+            Layout::Jobs => root.get("jobs"),
+            Layout::TopLevel => Some(root),
+        };
+        let Some(Value::Mapping(jobs)) = container else {
+            return Vec::new();
+        };
+        jobs.iter()
+            .filter_map(|(id, job)| Some((id.as_str()?.to_string(), job)))
+            .filter(|(id, _)| self.is_job(id))
+            .collect()
+    }
+
+    fn jobs_mut<'a>(&self, root: &'a mut Value) -> Option<&'a mut Mapping> {
+        match self {
+            Layout::Jobs => root.get_mut("jobs")?.as_mapping_mut(),
+            Layout::TopLevel => root.as_mapping_mut(),
+        }
+    }
+
+    fn is_job(&self, id: &str) -> bool {
+        *self == Layout::Jobs || !GITLAB_KEYWORDS.contains(&id)
+    }
+
+    fn steps<'a>(&self, job: &'a Value) -> &'a [Value] {
+        job.get(self.steps_key())
+            .and_then(Value::as_sequence)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+/// Mark every job in a generated pipeline as managed by cci
+pub fn mark_managed(platform: Platform, content: &str) -> Result<String> {
+    let Some(layout) = Layout::for_platform(platform) else {
+        return Ok(content.to_string());
+    };
+    let root: Value = serde_yaml::from_str(content)?;
+    Ok(annotate(content, layout, &step_fingerprints(layout, &root)))
+}
+
+/// Whether cci wrote `content`, going by the markers on its jobs
+///
+/// `None` for Jenkinsfiles, which aren't marked.
+pub fn is_managed(platform: Platform, content: &str) -> Option<bool> {
+    Layout::for_platform(platform)?;
+    Some(content.contains(MARKER))
+}
+
+/// Content to write to `path`: `generated` merged into the file already there
+///
+/// Files that don't exist, support files without markers, and files that
+/// aren't valid YAML are simply replaced.
+pub fn merge_into_existing(platform: Platform, generated: &str, path: &Path) -> Result<String> {
+    match std::fs::read_to_string(path) {
+        Ok(existing) => merge_with_existing(platform, generated, &existing),
+        Err(_) => Ok(generated.to_string()),
+    }
+}
+
+/// Carry hand-added jobs and steps from `existing` over into `generated`
+pub fn merge_with_existing(platform: Platform, generated: &str, existing: &str) -> Result<String> {
+    let Some(layout) = Layout::for_platform(platform) else {
+        return Ok(generated.to_string());
+    };
+    if !generated.contains(MARKER) {
+        return Ok(generated.to_string());
+    }
+    let Ok(old) = serde_yaml::from_str::<Value>(existing) else {
+        return Ok(generated.to_string());
+    };
+
+    let mut merged: Value = serde_yaml::from_str(generated)?;
+    let fingerprints = step_fingerprints(layout, &merged);
+    let managed = read_markers(existing);
+
+    let mut kept = Vec::new();
+    if let Some(jobs) = layout.jobs_mut(&mut merged) {
+        for (id, old_job) in layout.jobs(&old) {
+            let Some(job) = jobs.get_mut(id.as_str()) else {
+                // Jobs cci wrote but no longer generates go away
+                if !managed.contains_key(&id) {
+                    jobs.insert(id.clone().into(), old_job.clone());
+                    kept.push((id, old_job));
+                }
+                continue;
+            };
+            let Some(managed_steps) = managed.get(&id) else {
+                continue;
+            };
+            if let Some(Value::Sequence(steps)) = job.get_mut(layout.steps_key()) {
+                merge_steps(steps, layout.steps(old_job), managed_steps);
+            }
+        }
+    }
+
+    keep_key_order(&mut merged, &old);
+    match layout {
+        Layout::TopLevel => keep_stages(&mut merged, &kept),
+        Layout::Jobs if platform == Platform::CircleCI => {
+            keep_workflow_entries(&mut merged, &old, &kept)
+        }
+        Layout::Jobs => {}
+    }
+
+    let mut content = serde_yaml::to_string(&merged)?;
+    if matches!(platform, Platform::GitHub | Platform::Gitea) {
+        content = annotate_pins(&content);
+    }
+    let content = annotate(&content, layout, &fingerprints);
+    match leading_comments(existing) {
+        "" => Ok(content),
+        header => Ok(format!(
+            "{}{}",
+            header,
+            &content[leading_comments(&content).len()..]
+        )),
+    }
+}
+
+/// Put the keys of `merged`'s maps, and of the maps nested in them, in the
+/// order `old` has them
+///
+/// Keys `old` doesn't have follow the key before them in `merged`, or go
+/// first. Lists are left alone, since steps are fingerprinted as written.
+fn keep_key_order(merged: &mut Value, old: &Value) {
+    let (Value::Mapping(map), Value::Mapping(old_map)) = (merged, old) else {
+        return;
+    };
+    for (key, value) in map.iter_mut() {
+        if let Some(old_value) = old_map.get(key) {
+            keep_key_order(value, old_value);
+        }
+    }
+
+    let generated: Vec<Value> = map.keys().cloned().collect();
+    let mut order: Vec<&Value> = old_map
+        .keys()
+        .filter(|key| map.contains_key(*key))
+        .collect();
+    for (index, key) in generated.iter().enumerate() {
+        if old_map.contains_key(key) {
+            continue;
+        }
+        let at = match index {
+            0 => 0,
+            _ => {
+                order
+                    .iter()
+                    .position(|known| **known == generated[index - 1])
+                    .unwrap()
+                    + 1
+            }
+        };
+        order.insert(at, key);
+    }
+
+    let mut entries = std::mem::take(map);
+    for key in order {
+        if let Some(value) = entries.remove(key) {
+            map.insert(key.clone(), value);
+        }
+    }
+}
+
+/// The comment lines a file starts with, and the blank lines among them;
+/// empty when it starts with a key
+fn leading_comments(content: &str) -> &str {
+    let mut end = 0;
+    let mut commented = false;
+    for line in content.split_inclusive('\n') {
+        let line_content = line.trim();
+        if !line_content.is_empty() && !line_content.starts_with('#') {
+            break;
+        }
+        commented |= !line_content.is_empty();
+        end += line.len();
+    }
+    if commented {
+        &content[..end]
+    } else {
+        ""
+    }
+}
+
+/// Insert the steps of `old` that cci didn't write next to where they were
+///
+/// Each hand-added step follows the step that preceded it in the old file, or
+/// goes first when that step is gone too. A step cci wrote and the user then
+/// edited replaces the step it was generated as, found by its name or else by
+/// its position among the steps cci wrote, so the user's version is kept
+/// instead of both.
+fn merge_steps(steps: &mut Vec<Value>, old: &[Value], managed: &[String]) {
+    // Generated steps the old file doesn't have as generated
+    let mut unmatched: Vec<usize> = (0..steps.len())
+        .filter(|&at| !old.contains(&steps[at]))
+        .collect();
+
+    let mut insert_at = 0;
+    // Steps of the old file cci wrote, edited or not, seen so far
+    let mut written = 0;
+    for step in old {
+        let print = fingerprint(step);
+        if managed.contains(&print) {
+            written += 1;
+        }
+        if let Some(position) = steps.iter().position(|known| known == step) {
+            insert_at = position + 1;
+            continue;
+        }
+        if managed.contains(&print) {
+            continue;
+        }
+
+        let name = step_name(step);
+        let edit_of = unmatched
+            .iter()
+            .position(|&at| name.is_some() && step_name(&steps[at]) == name)
+            .or_else(|| {
+                let slot = managed.get(written)?;
+                unmatched
+                    .iter()
+                    .position(|&at| fingerprint(&steps[at]) == *slot)
+            });
+        match edit_of {
+            Some(index) => {
+                let at = unmatched.remove(index);
+                steps[at] = step.clone();
+                insert_at = at + 1;
+                written += 1;
+            }
+            None => {
+                steps.insert(insert_at, step.clone());
+                for at in unmatched.iter_mut().filter(|at| **at >= insert_at) {
+                    *at += 1;
+                }
+                insert_at += 1;
+            }
+        }
+    }
+}
+
+/// Display name of a step: `name:` on GitHub and Gitea, the run step's
+/// `name:` on CircleCI; GitLab script lines have none
+fn step_name(step: &Value) -> Option<&str> {
+    step.get("name")
+        .or_else(|| step.get("run").and_then(|run| run.get("name")))
+        .and_then(Value::as_str)
+}
+
+/// Add the stages of kept GitLab jobs that the generated pipeline doesn't declare
+fn keep_stages(merged: &mut Value, kept: &[(String, &Value)]) {
+    let Some(Value::Sequence(stages)) = merged.get_mut("stages") else {
+        return;
+    };
+    for (_, job) in kept {
+        if let Some(stage) = job.get("stage") {
+            if !stages.contains(stage) {
+                stages.push(stage.clone());
+            }
+        }
+    }
+}
+
+/// Keep the workflow entries that run kept CircleCI jobs
+///
+/// Entries go into the generated workflow of the same name; workflows cci
+/// doesn't generate keep only their entries for kept jobs, or are dropped.
+fn keep_workflow_entries(merged: &mut Value, old: &Value, kept: &[(String, &Value)]) {
+    let Some(Value::Mapping(old_workflows)) = old.get("workflows") else {
+        return;
+    };
+    let Some(Value::Mapping(workflows)) = merged.get_mut("workflows") else {
+        return;
+    };
+    let is_kept = |entry: &Value| {
+        let name = match entry {
+            Value::Mapping(entry) => entry.keys().next().and_then(Value::as_str),
+            entry => entry.as_str(),
+        };
+        name.is_some_and(|name| kept.iter().any(|(id, _)| id == name))
+    };
+
+    for (name, old_workflow) in old_workflows {
+        let Some(Value::Sequence(old_entries)) = old_workflow.get("jobs") else {
+            continue;
+        };
+        let entries: Vec<Value> = old_entries.iter().filter(|e| is_kept(e)).cloned().collect();
+        if entries.is_empty() {
+            continue;
+        }
+        match workflows.get_mut(name) {
+            Some(workflow) => {
+                if let Some(Value::Sequence(jobs)) = workflow.get_mut("jobs") {
+                    jobs.extend(entries);
+                }
+            }
+            None => {
+                let mut workflow = old_workflow.clone();
+                workflow["jobs"] = Value::Sequence(entries);
+                workflows.insert(name.clone(), workflow);
+            }
+        }
+    }
+}
+
+/// Fingerprints of each job's steps, keyed by job ID
+fn step_fingerprints(layout: Layout, root: &Value) -> BTreeMap<String, Vec<String>> {
+    layout
+        .jobs(root)
+        .into_iter()
+        .map(|(id, job)| (id, layout.steps(job).iter().map(fingerprint).collect()))
+        .collect()
+}
+
+/// Short stable hash of a step (FNV-1a over its YAML)
+fn fingerprint(step: &Value) -> String {
+    let yaml = serde_yaml::to_string(step).unwrap_or_default();
+    let hash = yaml.bytes().fold(0x811c9dc5u32, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x01000193)
+    });
+    format!("{:08x}", hash)
+}
+
+/// Append the marker to the key line of each job in `fingerprints`
+fn annotate(content: &str, layout: Layout, fingerprints: &BTreeMap<String, Vec<String>>) -> String {
+    let job_indent = match layout {
+        Layout::Jobs => 2,
+        Layout::TopLevel => 0,
+    };
+    let mut in_jobs = layout == Layout::TopLevel;
+    let mut result = String::with_capacity(content.len());
+    for line in content.lines() {
+        result.push_str(line);
+        let indent = line.len() - line.trim_start().len();
+        if layout == Layout::Jobs && indent == 0 && !line.is_empty() {
+            in_jobs = line == "jobs:";
+        }
+        if in_jobs && indent == job_indent {
+            if let Some(steps) = line
+                .strip_suffix(':')
+                .map(unquote)
+                .and_then(|id| fingerprints.get(id))
+            {
+                result.push(' ');
+                result.push_str(MARKER);
+                if !steps.is_empty() {
+                    result.push(' ');
+                    result.push_str(&steps.join(","));
+                }
+            }
+        }
+        result.push('\n');
+    }
+    result
+}
+
+/// Remove the managed markers, leaving the config as rendered
+pub fn strip_markers(content: &str) -> String {
+    let mut result = String::with_capacity(content.len());
+    for line in content.lines() {
+        match line.split_once(MARKER) {
+            Some((before, _)) => result.push_str(before.trim_end()),
+            None => result.push_str(line),
+        }
+        result.push('\n');
+    }
+    result
+}
+
+/// Step fingerprints recorded in a file's markers, keyed by job ID
+fn read_markers(content: &str) -> BTreeMap<String, Vec<String>> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (key, steps) = line.split_once(MARKER)?;
+            let id = unquote(key.trim().strip_suffix(':')?);
+            let steps = steps
+                .split(',')
+                .map(str::trim)
+                .filter(|step| !step.is_empty())
+                .map(str::to_string)
+                .collect();
+            Some((id.to_string(), steps))
+        })
+        .collect()
+}
+
+fn unquote(key: &str) -> &str {
+    let key = key.trim();
+    key.strip_prefix('\'')
+        .and_then(|key| key.strip_suffix('\''))
+        .or_else(|| key.strip_prefix('"').and_then(|key| key.strip_suffix('"')))
+        .unwrap_or(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GENERATED: &str = "name: CI
+on:
+  push:
+    branches:
+    - main
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    steps:
+    - run: cargo build
+    - run: cargo test
+";
+
+    #[test]
+    fn test_mark_managed_lists_steps() {
+        let marked = mark_managed(Platform::GitHub, GENERATED).unwrap();
+        let markers = read_markers(&marked);
+        assert_eq!(markers["test"].len(), 2);
+        assert!(marked.contains("  test: # cci:managed "));
+        assert!(!marked.contains("push: # cci:managed"));
+        assert_eq!(strip_markers(&marked), GENERATED);
+    }
+
+    #[test]
+    fn test_merge_keeps_hand_added_jobs_and_steps() {
+        let previous = mark_managed(Platform::GitHub, GENERATED).unwrap();
+        let edited = previous
+            .replace(
+                "    - run: cargo test\n",
+                "    - run: cargo test\n    - run: ./extra-checks.sh\n",
+            )
+            .replace(
+                "    - run: cargo build\n",
+                "    - run: cargo build --locked\n",
+            )
+            + "  deploy:\n    runs-on: ubuntu-latest\n    steps:\n    - run: ./deploy.sh\n";
+
+        // The regenerated pipeline dropped the build step
+        let regenerated = mark_managed(
+            Platform::GitHub,
+            &GENERATED.replace("    - run: cargo build\n", ""),
+        )
+        .unwrap();
+        let merged = merge_with_existing(Platform::GitHub, &regenerated, &edited).unwrap();
+        let workflow: Value = serde_yaml::from_str(&merged).unwrap();
+
+        let steps: Vec<&str> = workflow["jobs"]["test"]["steps"]
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .map(|step| step["run"].as_str().unwrap())
+            .collect();
+        // The edited build step no longer matches its fingerprint, so it's kept
+        assert_eq!(
+            steps,
+            ["cargo build --locked", "cargo test", "./extra-checks.sh"]
+        );
+        assert!(workflow["jobs"].get("deploy").is_some());
+        assert_eq!(read_markers(&merged)["test"].len(), 1);
+        assert!(!read_markers(&merged).contains_key("deploy"));
+    }
+
+    #[test]
+    fn test_merge_keeps_edited_steps_once() {
+        let previous = mark_managed(Platform::GitHub, GENERATED).unwrap();
+        let edited = previous.replace(
+            "    - run: cargo build\n",
+            "    - run: ./extra-setup.sh\n    - run: cargo build --locked\n",
+        );
+
+        // Regenerating the same pipeline keeps the edit in the build step's place
+        let merged = merge_with_existing(Platform::GitHub, &previous, &edited).unwrap();
+        let workflow: Value = serde_yaml::from_str(&merged).unwrap();
+        let steps: Vec<&str> = workflow["jobs"]["test"]["steps"]
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .map(|step| step["run"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            steps,
+            ["./extra-setup.sh", "cargo build --locked", "cargo test"]
+        );
+        assert_eq!(
+            merge_with_existing(Platform::GitHub, &previous, &merged).unwrap(),
+            merged
+        );
+
+        // A named step is found by its name, even when cci changed it too
+        let named = |build: &str| {
+            GENERATED.replace(
+                "    - run: cargo build\n",
+                &format!("    - name: Build\n      run: {}\n", build),
+            )
+        };
+        let previous = mark_managed(Platform::GitHub, &named("cargo build")).unwrap();
+        let edited = previous.replace("run: cargo build\n", "run: make build\n");
+        let regenerated = mark_managed(Platform::GitHub, &named("cargo build --locked")).unwrap();
+        let merged = merge_with_existing(Platform::GitHub, &regenerated, &edited).unwrap();
+        let workflow: Value = serde_yaml::from_str(&merged).unwrap();
+        let steps = workflow["jobs"]["test"]["steps"].as_sequence().unwrap();
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0]["run"], "make build");
+    }
+
+    #[test]
+    fn test_merge_is_stable() {
+        // `cci check` relies on an up-to-date file merging into itself unchanged
+        let marked = mark_managed(Platform::GitHub, GENERATED).unwrap();
+        assert_eq!(
+            merge_with_existing(Platform::GitHub, &marked, &marked).unwrap(),
+            marked
+        );
+
+        let edited = marked.clone()
+            + "  deploy:\n    runs-on: ubuntu-latest\n    steps:\n    - run: ./deploy.sh\n";
+        let merged = merge_with_existing(Platform::GitHub, &marked, &edited).unwrap();
+        assert_eq!(merged, edited);
+    }
+
+    #[test]
+    fn test_merge_keeps_header_comments_and_key_order() {
+        let marked = mark_managed(Platform::GitHub, GENERATED).unwrap();
+        let edited = "# Reviewed by the release team\n# before any change\n\n".to_string()
+            + &marked
+                .replace("name: CI\non:\n  push:\n    branches:\n    - main\n", "")
+                .replace(
+                    "jobs:\n",
+                    "on:\n  push:\n    branches:\n    - main\nname: CI\njobs:\n",
+                )
+                .replace(
+                    "    runs-on: ubuntu-latest\n    steps:\n    - run: cargo build\n    - run: cargo test\n",
+                    "    steps:\n    - run: cargo build\n    - run: cargo test\n    runs-on: ubuntu-latest\n",
+                );
+
+        let regenerated = mark_managed(
+            Platform::GitHub,
+            &GENERATED.replace(
+                "    runs-on: ubuntu-latest\n",
+                "    runs-on: ubuntu-latest\n    timeout-minutes: 30\n",
+            ),
+        )
+        .unwrap();
+        let merged = merge_with_existing(Platform::GitHub, &regenerated, &edited).unwrap();
+        let expected = edited.replace(
+            "    runs-on: ubuntu-latest\n",
+            "    runs-on: ubuntu-latest\n    timeout-minutes: 30\n",
+        );
+        assert_eq!(merged, expected);
+        assert_eq!(
+            merge_with_existing(Platform::GitHub, &regenerated, &merged).unwrap(),
+            merged
+        );
+    }
+
+    #[test]
+    fn test_merge_drops_jobs_no_longer_generated() {
+        let previous = mark_managed(
+            Platform::GitLab,
+            "stages:\n- test\nlint:\n  stage: test\n  script:\n  - cargo clippy\ntest:\n  stage: test\n  script:\n  - cargo test\n",
+        )
+        .unwrap();
+        let edited = previous + "release:\n  stage: release\n  script:\n  - ./release.sh\n";
+        let regenerated = mark_managed(
+            Platform::GitLab,
+            "stages:\n- test\ntest:\n  stage: test\n  script:\n  - cargo test\n",
+        )
+        .unwrap();
+
+        let merged = merge_with_existing(Platform::GitLab, &regenerated, &edited).unwrap();
+        let ci: Value = serde_yaml::from_str(&merged).unwrap();
+        assert!(ci.get("lint").is_none());
+        assert!(ci.get("release").is_some());
+        assert_eq!(
+            ci["stages"],
+            serde_yaml::from_str::<Value>("[test, release]").unwrap()
+        );
+    }
+}
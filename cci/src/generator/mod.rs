@@ -1,3 +1,10 @@
+pub mod combine;
+pub mod deps;
+pub mod explain;
+pub mod hooks;
+pub mod merge;
+pub mod monorepo;
 pub mod multi_preset;
+pub mod tasks;
 
 pub use multi_preset::*;
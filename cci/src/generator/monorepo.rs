@@ -0,0 +1,479 @@
+//! Generation for repositories holding several projects
+//!
+//! Each project gets its own pipelines, scoped to its directory: GitHub and
+//! Gitea workflows only run when files under it change and run their steps
+//! inside it, and GitLab projects become child pipelines triggered from the
+//! root `.gitlab-ci.yml`.
+
+use crate::detection::{DetectionOptions, DetectionResult, MemberSource, WorkspaceDetector};
+use crate::editor::config::PresetConfig;
+use crate::editor::registry::PresetRegistry;
+use crate::editor::state::Platform;
+use crate::error::{config_error, Result};
+use crate::generator::merge::mark_managed;
+use crate::generator::MultiPresetGenerator;
+use crate::platforms::github::models::{
+    GitHubDefaults, GitHubRunDefaults, GitHubTrigger, GitHubTriggerConfig, GitHubTriggers,
+    GitHubWorkflow,
+};
+use crate::platforms::gitlab::models::{GitLabCI, GitLabCache, GitLabCacheKey, GitLabRule};
+use crate::platforms::helpers::PlatformConfig;
+use serde_yaml::{Mapping, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// A project found in the repository
+#[derive(Debug, Clone)]
+pub struct Project {
+    /// Directory relative to the repository root; `.` for the root itself
+    pub dir: PathBuf,
+    pub detection: DetectionResult,
+    pub preset_configs: Vec<(String, PresetConfig)>,
+}
+
+impl Project {
+    fn is_root(&self) -> bool {
+        self.dir == Path::new(".")
+    }
+
+    /// The directory with forward slashes, as CI configs expect
+    fn dir_str(&self) -> String {
+        self.dir.to_string_lossy().replace('\\', "/")
+    }
+
+    /// File name prefix for the project's workflows, e.g. `services-api`
+    fn slug(&self) -> String {
+        self.dir_str().replace('/', "-")
+    }
+}
+
+/// Directories under `root` holding a project that gets pipelines of its own,
+/// sorted by path
+///
+/// These are what [`WorkspaceDetector`] finds, including directories no
+/// workspace manifest lists, less the members of a Rust workspace at the
+/// root, which are built with it.
+pub fn find_projects(root: &Path, options: &DetectionOptions) -> Vec<(PathBuf, DetectionResult)> {
+    WorkspaceDetector::with_options(options.clone())
+        .search_undeclared()
+        .detect(root)
+        .into_iter()
+        .filter(|member| member.source != MemberSource::Cargo)
+        .map(|member| (member.dir, member.detection))
+        .collect()
+}
+
+/// The base presets suggested for a project without a cci.ron of its own
+pub fn default_presets(
+    registry: &PresetRegistry,
+    detection: &DetectionResult,
+    dir: &Path,
+) -> Vec<(String, PresetConfig)> {
+    registry
+        .all()
+        .into_iter()
+        .filter(|preset| !preset.is_addon() && preset.matches_detection(detection, dir))
+        .map(|preset| {
+            (
+                preset.preset_id().to_string(),
+                preset.detected_config(detection),
+            )
+        })
+        .collect()
+}
+
+/// Generate every project's files for `platform`, with paths relative to the repository root
+///
+/// Returns (project index, path, content) tuples; the GitLab parent pipeline
+/// has no project.
+pub fn generate_projects(
+    projects: &[Project],
+    registry: Arc<PresetRegistry>,
+    platform: Platform,
+) -> Result<Vec<(Option<usize>, PathBuf, String)>> {
+    if matches!(platform, Platform::CircleCI | Platform::Jenkins) {
+        return Err(config_error(format!(
+            "{} reads a single config file, so projects can't get pipelines of their own; \
+             use GitHub, Gitea or GitLab",
+            platform.name()
+        )));
+    }
+
+    let mut outputs = Vec::new();
+    let mut children = Vec::new();
+    for (index, project) in projects.iter().enumerate() {
+        let language_version = project
+            .detection
+            .language_version
+            .clone()
+            .unwrap_or_else(|| "stable".to_string());
+        let generator = MultiPresetGenerator::new(
+            project.preset_configs.clone(),
+            registry.clone(),
+            platform,
+            language_version,
+        );
+
+        let mut includes = Vec::new();
+        for (presets, filename, pipeline) in generator.combined_pipelines()? {
+            let preset_id = &presets[0];
+            let (path, pipeline) = match pipeline {
+                PlatformConfig::GitHub(workflow) => {
+                    let (path, workflow) = scope_workflow(project, preset_id, &filename, workflow);
+                    (path, PlatformConfig::GitHub(workflow))
+                }
+                PlatformConfig::Gitea(workflow) => {
+                    let (path, workflow) = scope_workflow(project, preset_id, &filename, workflow);
+                    (path, PlatformConfig::Gitea(workflow))
+                }
+                PlatformConfig::GitLab(ci) => {
+                    let path = child_path(project, preset_id, &filename);
+                    includes.push(path.clone());
+                    (
+                        path,
+                        PlatformConfig::GitLab(scope_child_pipeline(project, ci)),
+                    )
+                }
+                other => (filename, other),
+            };
+            let content = mark_managed(platform, &pipeline.render()?)?;
+            outputs.push((Some(index), path, content));
+        }
+        if !includes.is_empty() {
+            children.push((project, includes));
+        }
+
+        for (path, content) in generator.support_files()? {
+            outputs.push((Some(index), project.dir.join(path), content));
+        }
+    }
+
+    if platform == Platform::GitLab && !children.is_empty() {
+        outputs.insert(
+            0,
+            (
+                None,
+                PathBuf::from(".gitlab-ci.yml"),
+                parent_pipeline(&children)?,
+            ),
+        );
+    }
+    Ok(outputs)
+}
+
+/// Restrict a workflow to the project's directory and name it after the project
+fn scope_workflow(
+    project: &Project,
+    preset_id: &str,
+    filename: &Path,
+    mut workflow: GitHubWorkflow,
+) -> (PathBuf, GitHubWorkflow) {
+    if project.is_root() {
+        return (filename.to_path_buf(), workflow);
+    }
+
+    let workflows_dir = filename.parent().unwrap_or(Path::new(""));
+    let path = workflows_dir.join(format!("{}-{}.yml", project.slug(), preset_id));
+    let dir = project.dir_str();
+    workflow.name = format!("{} ({})", workflow.name, dir);
+    if let GitHubTriggers::Detailed(events) = &mut workflow.on {
+        for (event, trigger) in events.iter_mut() {
+            if !matches!(event.as_str(), "push" | "pull_request") {
+                continue;
+            }
+            // Changes to the workflow itself should still run it
+            let workflow_path = path.to_string_lossy().replace('\\', "/");
+            let paths = vec![format!("{}/**", dir), workflow_path.clone()];
+            match trigger {
+                // Tag pushes ignore path filters, so release workflows stay as they are
+                GitHubTrigger::Filter(filter)
+                    if filter.branches.is_none() && filter.tags.is_some() => {}
+                // The preset's own filters are relative to the project
+                GitHubTrigger::Filter(filter) => {
+                    filter.paths = Some(match filter.paths.take() {
+                        Some(own) => own
+                            .iter()
+                            .map(|glob| format!("{}/{}", dir, glob))
+                            .chain([workflow_path])
+                            .collect(),
+                        None => paths,
+                    })
+                }
+                GitHubTrigger::Empty => {
+                    *trigger = GitHubTrigger::Filter(GitHubTriggerConfig {
+                        paths: Some(paths),
+                        ..Default::default()
+                    })
+                }
+                GitHubTrigger::Schedule(_) => {}
+            }
+        }
+    }
+    let shell = workflow
+        .defaults
+        .take()
+        .and_then(|defaults| defaults.run.shell);
+    workflow.defaults = Some(GitHubDefaults {
+        run: GitHubRunDefaults {
+            working_directory: Some(dir),
+            shell,
+        },
+    });
+    (path, workflow)
+}
+
+/// Where a project's GitLab pipeline is written
+///
+/// The root's own pipeline can't be `.gitlab-ci.yml`, which triggers the others.
+fn child_path(project: &Project, preset_id: &str, filename: &Path) -> PathBuf {
+    if project.is_root() {
+        PathBuf::from(format!(".gitlab-ci-{}.yml", preset_id))
+    } else {
+        project.dir.join(filename)
+    }
+}
+
+/// Run a project's jobs inside its directory, whenever the root pipeline triggers them
+///
+/// Child pipelines start in the repository root, and `before_script` shares
+/// a shell with `script`. Artifact and cache paths, cache key files and the
+/// `$CI_PROJECT_DIR` paths in variables are relative to the root too. The
+/// root pipeline only triggers a child when its files change, and the child's
+/// own workflow rules would otherwise turn it down: its pipeline source is
+/// `parent_pipeline`, not a push or merge request.
+fn scope_child_pipeline(project: &Project, mut ci: GitLabCI) -> GitLabCI {
+    if let Some(workflow) = &mut ci.workflow {
+        if !workflow.rules.is_empty() {
+            workflow.rules.insert(
+                0,
+                GitLabRule {
+                    if_condition: Some("$CI_PIPELINE_SOURCE == \"parent_pipeline\"".to_string()),
+                    changes: None,
+                    when: None,
+                },
+            );
+        }
+    }
+    if project.is_root() {
+        return ci;
+    }
+    let dir = project.dir_str();
+    let prefix = |paths: &mut Vec<String>| {
+        for path in paths.iter_mut() {
+            *path = format!("{}/{}", dir, path);
+        }
+    };
+    let prefix_cache = |cache: &mut GitLabCache| {
+        prefix(&mut cache.paths);
+        if let GitLabCacheKey::Files { files } = &mut cache.key {
+            prefix(files);
+        }
+    };
+    let prefix_variables = |variables: &mut BTreeMap<String, String>| {
+        for value in variables.values_mut() {
+            if let Some(rest) = value.strip_prefix("$CI_PROJECT_DIR/") {
+                *value = format!("$CI_PROJECT_DIR/{}/{}", dir, rest);
+            }
+        }
+    };
+    for job in ci.jobs.values_mut() {
+        let before_script = job.before_script.get_or_insert_with(Vec::new);
+        before_script.insert(0, format!("cd {}", dir));
+        if let Some(artifacts) = &mut job.artifacts {
+            prefix(&mut artifacts.paths);
+        }
+        if let Some(cache) = &mut job.cache {
+            prefix_cache(cache);
+        }
+        if let Some(variables) = &mut job.variables {
+            prefix_variables(variables);
+        }
+    }
+    if let Some(cache) = &mut ci.cache {
+        prefix_cache(cache);
+    }
+    if let Some(variables) = &mut ci.variables {
+        prefix_variables(variables);
+    }
+    ci
+}
+
+/// The root `.gitlab-ci.yml`, triggering each project's pipeline when its files change
+fn parent_pipeline(children: &[(&Project, Vec<PathBuf>)]) -> Result<String> {
+    let mut root = Mapping::new();
+    for (project, includes) in children {
+        let include: Vec<Value> = includes
+            .iter()
+            .map(|path| {
+                let mut local = Mapping::new();
+                local.insert(
+                    "local".into(),
+                    path.to_string_lossy().replace('\\', "/").into(),
+                );
+                Value::Mapping(local)
+            })
+            .collect();
+        let mut trigger = Mapping::new();
+        trigger.insert("include".into(), Value::Sequence(include));
+        trigger.insert("strategy".into(), "depend".into());
+
+        let mut job = Mapping::new();
+        job.insert("trigger".into(), Value::Mapping(trigger));
+        if !project.is_root() {
+            let mut rule = Mapping::new();
+            rule.insert(
+                "changes".into(),
+                Value::Sequence(vec![format!("{}/**/*", project.dir_str()).into()]),
+            );
+            job.insert("rules".into(), Value::Sequence(vec![Value::Mapping(rule)]));
+        }
+        let id = if project.is_root() {
+            "root".to_string()
+        } else {
+            project.slug()
+        };
+        root.insert(id.into(), Value::Mapping(job));
+    }
+    Ok(serde_yaml::to_string(&root)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detection::ProjectType;
+    use crate::editor::registry::build_registry;
+    use std::collections::HashMap;
+
+    fn project(dir: &str) -> Project {
+        let registry = build_registry();
+        Project {
+            dir: PathBuf::from(dir),
+            detection: DetectionResult {
+                project_type: ProjectType::RustBinary,
+                confidence: 100,
+                language_version: None,
+                metadata: HashMap::new(),
+            },
+            preset_configs: vec![(
+                "rust".to_string(),
+                registry.get("rust").unwrap().default_config(true),
+            )],
+        }
+    }
+
+    #[test]
+    fn test_gitlab_child_pipelines_cache_inside_the_project() {
+        let registry = build_registry();
+        let python = Project {
+            dir: PathBuf::from("tools/py"),
+            preset_configs: vec![(
+                "python-app".to_string(),
+                registry.get("python-app").unwrap().default_config(true),
+            )],
+            ..project("tools/py")
+        };
+        let outputs =
+            generate_projects(&[python], Arc::new(build_registry()), Platform::GitLab).unwrap();
+        let child = &outputs[1].2;
+        assert!(child.contains("PIP_CACHE_DIR: $CI_PROJECT_DIR/tools/py/.cache/pip"));
+        assert!(child.contains("- tools/py/.cache/pip/"));
+        assert!(child.contains("- tools/py/requirements.txt"));
+        assert!(child.contains("- if: $CI_PIPELINE_SOURCE == \"parent_pipeline\""));
+    }
+
+    #[test]
+    fn test_find_projects() {
+        let root = tempfile::tempdir().unwrap();
+        let write = |path: &str, content: &str| {
+            let path = root.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        let crate_toml = "[package]\nname = \"x\"\nversion = \"0.1.0\"\n";
+        write("services/api/Cargo.toml", crate_toml);
+        write("services/api/src/main.rs", "fn main() {}\n");
+        write("services/api/fixtures/Cargo.toml", crate_toml);
+        write("tools/cli/go.mod", "module cli\n\ngo 1.22\n");
+        write("target/debug/Cargo.toml", crate_toml);
+
+        let dirs: Vec<PathBuf> = find_projects(root.path(), &DetectionOptions::default())
+            .into_iter()
+            .map(|(dir, _)| dir)
+            .collect();
+        assert_eq!(
+            dirs,
+            vec![PathBuf::from("services/api"), PathBuf::from("tools/cli")]
+        );
+    }
+
+    #[test]
+    fn test_find_projects_next_to_a_cargo_workspace() {
+        let root = tempfile::tempdir().unwrap();
+        let write = |path: &str, content: &str| {
+            let path = root.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write("Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]\n");
+        write(
+            "crates/core/Cargo.toml",
+            "[package]\nname = \"core\"\nversion = \"0.1.0\"\n",
+        );
+        write("svc/go.mod", "module svc\n\ngo 1.22\n");
+
+        // The workspace member is built with the root; the Go service isn't a member
+        let dirs: Vec<PathBuf> = find_projects(root.path(), &DetectionOptions::default())
+            .into_iter()
+            .map(|(dir, _)| dir)
+            .collect();
+        assert_eq!(dirs, vec![PathBuf::from("."), PathBuf::from("svc")]);
+    }
+
+    #[test]
+    fn test_github_workflows_are_scoped_to_the_project() {
+        let projects = [project("services/api")];
+        let outputs =
+            generate_projects(&projects, Arc::new(build_registry()), Platform::GitHub).unwrap();
+        let (_, path, content) = &outputs[0];
+        assert_eq!(path, Path::new(".github/workflows/services-api-rust.yml"));
+        assert!(content.contains("- services/api/**"));
+        assert!(content.contains("working-directory: services/api"));
+
+        // The preset's own path filters are relative to the project
+        let mut project = project("services/api");
+        project.preset_configs[0].1.set(
+            "paths".to_string(),
+            crate::editor::config::OptionValue::String("src/**".to_string()),
+        );
+        let outputs =
+            generate_projects(&[project], Arc::new(build_registry()), Platform::GitHub).unwrap();
+        let content = &outputs[0].2;
+        assert!(content.contains("- services/api/src/**"));
+        assert!(!content.contains("- services/api/**"));
+        assert!(content.contains("- .github/workflows/services-api-rust.yml"));
+    }
+
+    #[test]
+    fn test_gitlab_projects_become_child_pipelines() {
+        let projects = [project("."), project("services/api")];
+        let outputs =
+            generate_projects(&projects, Arc::new(build_registry()), Platform::GitLab).unwrap();
+        let paths: Vec<&Path> = outputs.iter().map(|(_, path, _)| path.as_path()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                Path::new(".gitlab-ci.yml"),
+                Path::new(".gitlab-ci-rust.yml"),
+                Path::new("services/api/.gitlab-ci.yml"),
+            ]
+        );
+        let parent = &outputs[0].2;
+        assert!(parent.contains("local: services/api/.gitlab-ci.yml"));
+        assert!(parent.contains("- services/api/**/*"));
+        assert!(outputs[2].2.contains("- cd services/api"));
+        assert!(
+            generate_projects(&projects, Arc::new(build_registry()), Platform::Jenkins).is_err()
+        );
+    }
+}
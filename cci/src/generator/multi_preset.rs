@@ -1,7 +1,11 @@
 use crate::editor::config::PresetConfig;
 use crate::editor::registry::PresetRegistry;
-use crate::editor::state::Platform;
+use crate::editor::state::{FileNaming, Platform};
 use crate::error::Result;
+use crate::generator::combine::place;
+use crate::generator::merge::mark_managed;
+use crate::platforms::helpers::PlatformConfig;
+use crate::platforms::secrets::Secret;
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -11,6 +15,7 @@ pub struct MultiPresetGenerator {
     registry: Arc<PresetRegistry>,
     platform: Platform,
     language_version: String,
+    file_naming: FileNaming,
 }
 
 impl MultiPresetGenerator {
@@ -25,63 +30,144 @@ impl MultiPresetGenerator {
             registry,
             platform,
             language_version,
+            file_naming: FileNaming::default(),
         }
     }
 
+    /// Split the presets into files as `file_naming` says, rather than the default
+    pub fn with_file_naming(mut self, file_naming: FileNaming) -> Self {
+        self.file_naming = file_naming;
+        self
+    }
+
     /// Generate all preset configurations
     /// Returns a vector of (filename, content) tuples
+    ///
+    /// Decorator addons don't get a file of their own; they extend every other
+    /// preset's config instead. On platforms that read a single file the
+    /// presets are combined into it. Support files (tool configs some presets
+    /// rely on) follow the CI configs; a path is only emitted once. Jobs in the
+    /// CI configs are marked as managed so they can be merged into files
+    /// edited by hand.
     pub fn generate_all(&self) -> Result<Vec<(PathBuf, String)>> {
-        let mut outputs = Vec::new();
+        let mut outputs: Vec<(PathBuf, String)> = Vec::new();
+        for (_, filename, pipeline) in self.combined_pipelines()? {
+            let content = mark_managed(self.platform, &pipeline.render()?)?;
+            outputs.push((filename, content));
+        }
+        for (path, content) in self.support_files()? {
+            if !outputs.iter().any(|(known, _)| *known == path) {
+                outputs.push((path, content));
+            }
+        }
+        Ok(outputs)
+    }
 
+    /// Support files every configured preset relies on, each path listed once
+    pub fn support_files(&self) -> Result<Vec<(PathBuf, String)>> {
+        let mut files: Vec<(PathBuf, String)> = Vec::new();
         for (preset_id, config) in &self.preset_configs {
-            if let Some(preset) = self.registry.get(preset_id) {
-                let yaml = preset.generate(config, self.platform, &self.language_version)?;
-                let filename = self.derive_filename(preset_id, self.platform);
-                outputs.push((filename, yaml));
+            let Some(preset) = self.registry.get(preset_id) else {
+                continue;
+            };
+            for (path, content) in
+                preset.support_files(config, self.platform, &self.language_version)?
+            {
+                if !files.iter().any(|(known, _)| *known == path) {
+                    files.push((path, content));
+                }
             }
         }
+        Ok(files)
+    }
 
-        Ok(outputs)
+    /// Build and decorate the CI config of every preset that gets a file of its own
+    ///
+    /// Returns (preset ID, filename, config) tuples in config order, each with
+    /// the file [`Self::combined_pipelines`] puts the preset's jobs in.
+    pub fn build_pipelines(&self) -> Result<Vec<(String, PathBuf, PlatformConfig)>> {
+        let pipelines = self.build_undecorated()?;
+        let placed = place(pipelines.clone(), self.platform, self.file_naming)?;
+        let mut decorated = Vec::new();
+        for (preset_id, mut pipeline) in pipelines {
+            self.decorate(&mut pipeline)?;
+            let filename = placed
+                .iter()
+                .find(|(presets, ..)| presets.contains(&preset_id))
+                .map(|(_, path, _)| path.clone())
+                .unwrap_or_else(|| self.platform.output_path());
+            decorated.push((preset_id, filename, pipeline));
+        }
+        Ok(decorated)
     }
 
-    /// Derive the output filename based on preset ID and platform
-    fn derive_filename(&self, preset_id: &str, platform: Platform) -> PathBuf {
-        match platform {
-            Platform::GitHub => {
-                // For GitHub, create separate workflow files per preset
-                PathBuf::from(format!(".github/workflows/{}.yml", preset_id))
-            }
-            Platform::Gitea => {
-                // For Gitea, create separate workflow files per preset (same structure as GitHub)
-                PathBuf::from(format!(".gitea/workflows/{}.yml", preset_id))
+    /// Build the presets' CI configs, split into files as the platform and
+    /// [`FileNaming`] allow, then decorate them
+    ///
+    /// Returns the IDs of the presets in each config, its filename and the
+    /// config itself, in config order. Decorators run on the combined config so
+    /// the jobs they add aren't repeated for every preset.
+    pub fn combined_pipelines(&self) -> Result<Vec<(Vec<String>, PathBuf, PlatformConfig)>> {
+        let mut combined = place(self.build_undecorated()?, self.platform, self.file_naming)?;
+        for (_, _, pipeline) in &mut combined {
+            self.decorate(pipeline)?;
+        }
+        Ok(combined)
+    }
+
+    /// Build the CI config of every preset that isn't a decorator, as
+    /// (preset ID, config) pairs in config order
+    fn build_undecorated(&self) -> Result<Vec<(String, PlatformConfig)>> {
+        let mut pipelines = Vec::new();
+        for (preset_id, config) in &self.preset_configs {
+            let Some(preset) = self.registry.get(preset_id) else {
+                continue;
+            };
+            if preset.is_decorator() {
+                continue;
             }
-            Platform::GitLab => {
-                // GitLab uses a single file - for multiple presets, we need to merge
-                // For now, we'll use the preset ID as a suffix if there are multiple
-                if self.preset_configs.len() > 1 {
-                    PathBuf::from(format!(".gitlab-ci-{}.yml", preset_id))
-                } else {
-                    PathBuf::from(".gitlab-ci.yml")
-                }
+            let pipeline = preset.build(config, self.platform, &self.language_version)?;
+            pipelines.push((preset_id.clone(), pipeline));
+        }
+        Ok(pipelines)
+    }
+
+    /// Apply every configured decorator addon to a built pipeline
+    ///
+    /// Decorators run in registry order rather than config order, so the
+    /// result doesn't depend on how the config file happens to list them.
+    fn decorate(&self, pipeline: &mut PlatformConfig) -> Result<()> {
+        for preset in self.registry.all() {
+            if !preset.is_decorator() {
+                continue;
             }
-            Platform::CircleCI => {
-                // CircleCI uses a single file - for multiple presets, we need to merge
-                // For now, we'll use the preset ID as a suffix if there are multiple
-                if self.preset_configs.len() > 1 {
-                    PathBuf::from(format!(".circleci/config-{}.yml", preset_id))
-                } else {
-                    PathBuf::from(".circleci/config.yml")
-                }
+            if let Some((_, config)) = self
+                .preset_configs
+                .iter()
+                .find(|(preset_id, _)| preset_id == preset.preset_id())
+            {
+                preset.decorate(config, &self.language_version, pipeline)?;
             }
-            Platform::Jenkins => {
-                // Jenkins uses a single file - for multiple presets, we need to merge
-                // For now, we'll use the preset ID as a suffix if there are multiple
-                if self.preset_configs.len() > 1 {
-                    PathBuf::from(format!("Jenkinsfile-{}", preset_id))
-                } else {
-                    PathBuf::from("Jenkinsfile")
+        }
+        Ok(())
+    }
+
+    /// Secrets the generated configurations need, with the presets that need each
+    pub fn required_secrets(&self) -> Vec<(Secret, Vec<String>)> {
+        let mut secrets: Vec<(Secret, Vec<String>)> = Vec::new();
+
+        for (preset_id, config) in &self.preset_configs {
+            let Some(preset) = self.registry.get(preset_id) else {
+                continue;
+            };
+            for secret in preset.required_secrets(config, self.platform, &self.language_version) {
+                match secrets.iter_mut().find(|(known, _)| *known == secret) {
+                    Some((_, presets)) => presets.push(preset_id.clone()),
+                    None => secrets.push((secret, vec![preset_id.clone()])),
                 }
             }
         }
+
+        secrets
     }
 }
@@ -0,0 +1,182 @@
+//! Task runner files mirroring the CI jobs
+//!
+//! Each kind of check becomes one recipe running the same commands as the
+//! matching jobs, plus a default `ci` recipe that runs them all in CI order.
+
+use crate::error::Result;
+use crate::platforms::checks::{CheckKind, LocalCheck};
+use std::path::PathBuf;
+
+/// Task runner the file is written for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskTool {
+    Just,
+    Make,
+}
+
+impl TaskTool {
+    pub fn all() -> Vec<TaskTool> {
+        vec![TaskTool::Just, TaskTool::Make]
+    }
+
+    pub fn from_id(id: &str) -> Option<TaskTool> {
+        Self::all().into_iter().find(|tool| tool.id() == id)
+    }
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            TaskTool::Just => "just",
+            TaskTool::Make => "make",
+        }
+    }
+
+    pub fn output_path(&self) -> PathBuf {
+        match self {
+            TaskTool::Just => PathBuf::from("justfile"),
+            TaskTool::Make => PathBuf::from("Makefile"),
+        }
+    }
+}
+
+/// Recipe name and description for a kind of check
+fn recipe(kind: CheckKind) -> (&'static str, &'static str) {
+    match kind {
+        CheckKind::Format => ("fmt", "Check formatting"),
+        CheckKind::Lint => ("lint", "Run linters"),
+        CheckKind::Test => ("test", "Run tests"),
+        CheckKind::Audit => ("audit", "Audit dependencies"),
+        CheckKind::Build => ("build", "Build"),
+    }
+}
+
+/// Task file for a set of checks
+pub struct TaskFile {
+    pub tool: TaskTool,
+    pub checks: Vec<LocalCheck>,
+}
+
+impl TaskFile {
+    /// Recipes with their commands, in the order CI runs them
+    fn recipes(&self) -> Vec<(&'static str, &'static str, Vec<&str>)> {
+        let kinds = [
+            CheckKind::Format,
+            CheckKind::Lint,
+            CheckKind::Test,
+            CheckKind::Audit,
+            CheckKind::Build,
+        ];
+        kinds
+            .into_iter()
+            .filter_map(|kind| {
+                let commands: Vec<&str> = self
+                    .checks
+                    .iter()
+                    .filter(|check| check.kind == kind)
+                    .map(|check| check.command.as_str())
+                    .collect();
+                let (name, description) = recipe(kind);
+                (!commands.is_empty()).then_some((name, description, commands))
+            })
+            .collect()
+    }
+
+    pub fn render(&self) -> Result<String> {
+        let recipes = self.recipes();
+        let names: Vec<&str> = recipes.iter().map(|(name, _, _)| *name).collect();
+        let mut out = String::from("# Generated by cci from the same config as the CI jobs\n\n");
+
+        match self.tool {
+            TaskTool::Just => {
+                // The first recipe is the default
+                out.push_str("# Run every check CI runs\n");
+                out.push_str(&format!("ci: {}\n", names.join(" ")));
+                for (name, description, commands) in &recipes {
+                    out.push_str(&format!("\n# {}\n{}:\n", description, name));
+                    for command in commands {
+                        // `{{` starts an interpolation in just
+                        out.push_str(&format!("    {}\n", command.replace("{{", "{{ '{{' }}")));
+                    }
+                }
+            }
+            TaskTool::Make => {
+                out.push_str(&format!(".PHONY: ci {}\n\n", names.join(" ")));
+                out.push_str("# Run every check CI runs\n");
+                out.push_str(&format!("ci: {}\n", names.join(" ")));
+                for (name, description, commands) in &recipes {
+                    out.push_str(&format!("\n# {}\n{}:\n", description, name));
+                    for command in commands {
+                        out.push_str(&format!("\t{}\n", command.replace('$', "$$")));
+                    }
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checks() -> Vec<LocalCheck> {
+        vec![
+            LocalCheck::new(
+                "go/lint",
+                "Run golangci-lint",
+                CheckKind::Lint,
+                "golangci-lint run",
+                &["go"],
+            ),
+            LocalCheck::new(
+                "go/test",
+                "Run tests",
+                CheckKind::Test,
+                "go test -v ./...",
+                &["go"],
+            ),
+            LocalCheck::new(
+                "docker/build",
+                "Build image",
+                CheckKind::Build,
+                "docker build -t app:$TAG .",
+                &[],
+            ),
+            LocalCheck::new(
+                "go/build",
+                "Build",
+                CheckKind::Build,
+                "go build -v ./...",
+                &["go"],
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_justfile_recipes() {
+        let file = TaskFile {
+            tool: TaskTool::Just,
+            checks: checks(),
+        };
+        let justfile = file.render().unwrap();
+
+        assert!(justfile.contains("# Run every check CI runs\nci: lint test build\n"));
+        assert!(justfile.contains("\n# Run tests\ntest:\n    go test -v ./...\n"));
+        assert!(
+            justfile.contains("build:\n    docker build -t app:$TAG .\n    go build -v ./...\n")
+        );
+        assert!(!justfile.contains("fmt:"));
+    }
+
+    #[test]
+    fn test_makefile_escapes_variables() {
+        let file = TaskFile {
+            tool: TaskTool::Make,
+            checks: checks(),
+        };
+        let makefile = file.render().unwrap();
+
+        assert!(makefile.contains(".PHONY: ci lint test build\n"));
+        assert!(makefile.contains("lint:\n\tgolangci-lint run\n"));
+        assert!(makefile.contains("\tdocker build -t app:$$TAG .\n"));
+    }
+}
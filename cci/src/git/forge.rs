@@ -0,0 +1,501 @@
+//! Opening pull requests on GitHub, Gitea and GitLab
+//!
+//! Requests go through `curl` with the token read from the environment. The
+//! token and the JSON body are passed as a curl config on stdin, so neither
+//! shows up in the process list.
+
+use crate::editor::state::Platform;
+use crate::error::{config_error, Result};
+use anyhow::{bail, Context};
+use serde::Serialize;
+use serde_yaml::Value;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// The hosting service a remote points at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Forge {
+    GitHub,
+    Gitea,
+    GitLab,
+}
+
+/// Where a git remote lives: `host` and the project's `path` on it, like `owner/repo`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Remote {
+    pub host: String,
+    pub path: String,
+}
+
+impl Remote {
+    /// Parse an SSH (`git@host:owner/repo.git`) or HTTP(S) remote URL
+    pub fn parse(url: &str) -> Result<Self> {
+        let invalid = || config_error(format!("Can't tell where remote `{}` is hosted", url));
+        let (host, path) = match url.split_once("://") {
+            Some((_, rest)) => rest.split_once('/').ok_or_else(invalid)?,
+            None => url.split_once(':').ok_or_else(invalid)?,
+        };
+        // Drop credentials and ports; the API is always served over HTTPS
+        let host = host.rsplit('@').next().unwrap_or(host);
+        let host = host.split(':').next().unwrap_or(host);
+        let path = path.trim_end_matches('/').trim_end_matches(".git");
+        if host.is_empty() || !path.contains('/') {
+            return Err(invalid());
+        }
+        Ok(Self {
+            host: host.to_string(),
+            path: path.to_string(),
+        })
+    }
+}
+
+impl Forge {
+    /// The forge hosting `remote`, from its host name or else the platforms being generated
+    pub fn detect(remote: &Remote, platforms: &[Platform]) -> Result<Self> {
+        let host = remote.host.to_lowercase();
+        if host.contains("github") {
+            return Ok(Forge::GitHub);
+        }
+        if host.contains("gitlab") {
+            return Ok(Forge::GitLab);
+        }
+        if host.contains("gitea") || host.contains("codeberg") || host.contains("forgejo") {
+            return Ok(Forge::Gitea);
+        }
+        platforms
+            .iter()
+            .find_map(|platform| match platform {
+                Platform::GitHub => Some(Forge::GitHub),
+                Platform::Gitea => Some(Forge::Gitea),
+                Platform::GitLab => Some(Forge::GitLab),
+                Platform::CircleCI | Platform::Jenkins => None,
+            })
+            .ok_or_else(|| {
+                config_error(format!(
+                    "Can't tell whether {} runs GitHub, Gitea or GitLab",
+                    remote.host
+                ))
+            })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Forge::GitHub => "GitHub",
+            Forge::Gitea => "Gitea",
+            Forge::GitLab => "GitLab",
+        }
+    }
+
+    /// Environment variables the API token is read from, in order
+    pub fn token_vars(&self) -> &'static [&'static str] {
+        match self {
+            Forge::GitHub => &["GITHUB_TOKEN", "GH_TOKEN"],
+            Forge::Gitea => &["GITEA_TOKEN"],
+            Forge::GitLab => &["GITLAB_TOKEN"],
+        }
+    }
+
+    /// The API token from the environment
+    pub fn token(&self) -> Result<String> {
+        self.token_vars()
+            .iter()
+            .find_map(|var| std::env::var(var).ok().filter(|token| !token.is_empty()))
+            .ok_or_else(|| {
+                config_error(format!(
+                    "Opening a {} {} needs a token in {}",
+                    self.name(),
+                    self.request_name(),
+                    self.token_vars().join(" or ")
+                ))
+            })
+    }
+
+    /// What the forge calls a pull request
+    pub fn request_name(&self) -> &'static str {
+        match self {
+            Forge::GitLab => "merge request",
+            Forge::GitHub | Forge::Gitea => "pull request",
+        }
+    }
+
+    /// The endpoint pull requests for `remote` are created at
+    fn endpoint(&self, remote: &Remote) -> String {
+        match self {
+            Forge::GitHub if remote.host == "github.com" => {
+                format!("https://api.github.com/repos/{}/pulls", remote.path)
+            }
+            Forge::GitHub => format!("https://{}/api/v3/repos/{}/pulls", remote.host, remote.path),
+            Forge::Gitea => format!("https://{}/api/v1/repos/{}/pulls", remote.host, remote.path),
+            Forge::GitLab => format!(
+                "https://{}/api/v4/projects/{}/merge_requests",
+                remote.host,
+                remote.path.replace('/', "%2F")
+            ),
+        }
+    }
+
+    fn auth_header(&self, token: &str) -> String {
+        match self {
+            Forge::GitHub => format!("Authorization: Bearer {}", token),
+            Forge::Gitea => format!("Authorization: token {}", token),
+            Forge::GitLab => format!("PRIVATE-TOKEN: {}", token),
+        }
+    }
+}
+
+/// A pull request to open from `head` into `base`
+#[derive(Debug, Clone)]
+pub struct PullRequest<'a> {
+    pub head: &'a str,
+    pub base: &'a str,
+    pub title: &'a str,
+    pub body: &'a str,
+}
+
+#[derive(Serialize)]
+struct PullBody<'a> {
+    title: &'a str,
+    head: &'a str,
+    base: &'a str,
+    body: &'a str,
+}
+
+#[derive(Serialize)]
+struct MergeRequestBody<'a> {
+    title: &'a str,
+    source_branch: &'a str,
+    target_branch: &'a str,
+    description: &'a str,
+    remove_source_branch: bool,
+}
+
+/// The JSON body that creates `request`
+fn pull_request_body(forge: Forge, request: &PullRequest) -> Result<String> {
+    match forge {
+        Forge::GitHub | Forge::Gitea => crate::cli::json::to_json(&PullBody {
+            title: request.title,
+            head: request.head,
+            base: request.base,
+            body: request.body,
+        }),
+        Forge::GitLab => crate::cli::json::to_json(&MergeRequestBody {
+            title: request.title,
+            source_branch: request.head,
+            target_branch: request.base,
+            description: request.body,
+            remove_source_branch: true,
+        }),
+    }
+}
+
+/// A curl config requesting `url`, posting `data` if there is any
+fn curl_config(url: &str, auth_header: Option<&str>, data: Option<&str>) -> String {
+    let quote = |value: &str| {
+        let escaped = value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n");
+        format!("\"{}\"", escaped)
+    };
+    let mut config = String::new();
+    config.push_str(&format!("url = {}\n", quote(url)));
+    if let Some(header) = auth_header {
+        config.push_str(&format!("header = {}\n", quote(header)));
+    }
+    config.push_str("header = \"Accept: application/json\"\n");
+    if let Some(data) = data {
+        config.push_str("header = \"Content-Type: application/json\"\n");
+        config.push_str(&format!("data = {}\n", quote(data)));
+    }
+    config
+}
+
+/// A response: its status code and parsed body, plus the raw body for errors
+struct Response {
+    status: u16,
+    body: Value,
+    raw: String,
+}
+
+/// Send the request `config` describes to `host`
+fn send(host: &str, config: &str) -> Result<Response> {
+    let mut child = match Command::new("curl")
+        .args(["--silent", "--show-error", "--config", "-"])
+        .args(["--write-out", "\n%{http_code}"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(config_error(format!(
+                "curl isn't installed; it's needed to talk to {}",
+                host
+            )))
+        }
+        Err(e) => return Err(e.into()),
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(config.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!(
+            "Couldn't reach {}: {}",
+            host,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(parse_output(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Split curl's output into the response body and the status code after it
+fn parse_output(output: &str) -> Response {
+    let (raw, status) = output.trim_end().rsplit_once('\n').unwrap_or(("", output));
+    Response {
+        status: status.trim().parse().unwrap_or(0),
+        body: serde_yaml::from_str(raw).unwrap_or(Value::Null),
+        raw: raw.trim().to_string(),
+    }
+}
+
+impl Response {
+    fn ok(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// The error for a response that wasn't `ok`, while trying to do `what`
+    fn refusal(&self, forge: Forge, what: &str) -> anyhow::Error {
+        let message = self
+            .body
+            .get("message")
+            .map(|message| match message {
+                Value::String(message) => message.clone(),
+                other => serde_yaml::to_string(other)
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string(),
+            })
+            .unwrap_or_else(|| self.raw.clone());
+        anyhow::anyhow!(
+            "{} refused to {} (HTTP {}): {}",
+            forge.name(),
+            what,
+            self.status,
+            message
+        )
+    }
+}
+
+/// Open `request` on `remote`, returning its web URL
+pub fn open(forge: Forge, remote: &Remote, token: &str, request: &PullRequest) -> Result<String> {
+    let body = pull_request_body(forge, request)?;
+    let config = curl_config(
+        &forge.endpoint(remote),
+        Some(&forge.auth_header(token)),
+        Some(&body),
+    );
+    let response = send(&remote.host, &config)?;
+    pull_request_url(forge, &response)
+}
+
+/// The new pull request's URL from the response creating it
+fn pull_request_url(forge: Forge, response: &Response) -> Result<String> {
+    if !response.ok() {
+        return Err(response.refusal(forge, &format!("open the {}", forge.request_name())));
+    }
+    let field = match forge {
+        Forge::GitLab => "web_url",
+        Forge::GitHub | Forge::Gitea => "html_url",
+    };
+    response
+        .body
+        .get(field)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .with_context(|| {
+            format!(
+                "{} didn't say where the {} is",
+                forge.name(),
+                forge.request_name()
+            )
+        })
+}
+
+/// A repository belonging to an organization
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrgRepo {
+    pub name: String,
+    pub clone_url: String,
+}
+
+/// Every repository of `org` (a GitLab group, subgroups included) that isn't archived
+///
+/// `org.path` is the organization's name. The token is optional, but without
+/// one only public repositories are listed.
+pub fn list_repos(forge: Forge, org: &Remote, token: Option<&str>) -> Result<Vec<OrgRepo>> {
+    let api = match forge {
+        Forge::GitHub if org.host == "github.com" => "https://api.github.com".to_string(),
+        Forge::GitHub => format!("https://{}/api/v3", org.host),
+        Forge::Gitea => format!("https://{}/api/v1", org.host),
+        Forge::GitLab => format!("https://{}/api/v4", org.host),
+    };
+    let auth = token.map(|token| forge.auth_header(token));
+    let mut repos = Vec::new();
+    // GitHub users have their own endpoint, tried when the org isn't found
+    let mut owner = "orgs";
+    let mut page = 1;
+    loop {
+        let url = match forge {
+            Forge::GitHub => format!(
+                "{}/{}/{}/repos?per_page=100&page={}",
+                api, owner, org.path, page
+            ),
+            Forge::Gitea => format!("{}/orgs/{}/repos?limit=50&page={}", api, org.path, page),
+            Forge::GitLab => format!(
+                "{}/groups/{}/projects?include_subgroups=true&per_page=100&page={}",
+                api,
+                org.path.replace('/', "%2F"),
+                page
+            ),
+        };
+        let response = send(&org.host, &curl_config(&url, auth.as_deref(), None))?;
+        if response.status == 404 && forge == Forge::GitHub && owner == "orgs" {
+            owner = "users";
+            continue;
+        }
+        if !response.ok() {
+            return Err(response.refusal(forge, &format!("list {}'s repositories", org.path)));
+        }
+        let found = repos_in(forge, &response.body);
+        if found.is_empty() {
+            break;
+        }
+        repos.extend(found);
+        page += 1;
+    }
+    Ok(repos)
+}
+
+/// The repositories in one page of a listing
+fn repos_in(forge: Forge, page: &Value) -> Vec<OrgRepo> {
+    let (name_field, url_field) = match forge {
+        Forge::GitHub | Forge::Gitea => ("name", "clone_url"),
+        Forge::GitLab => ("path", "http_url_to_repo"),
+    };
+    page.as_sequence()
+        .into_iter()
+        .flatten()
+        .filter(|repo| repo.get("archived").and_then(Value::as_bool) != Some(true))
+        .filter_map(|repo| {
+            Some(OrgRepo {
+                name: repo.get(name_field)?.as_str()?.to_string(),
+                clone_url: repo.get(url_field)?.as_str()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_remote() {
+        for url in [
+            "git@github.com:fossable/cci.git",
+            "https://github.com/fossable/cci",
+            "https://token@github.com/fossable/cci.git/",
+            "ssh://git@github.com:22/fossable/cci.git",
+        ] {
+            let remote = Remote::parse(url).unwrap();
+            assert_eq!(remote.host, "github.com", "{}", url);
+            assert_eq!(remote.path, "fossable/cci", "{}", url);
+        }
+        let nested = Remote::parse("git@gitlab.com:group/sub/project.git").unwrap();
+        assert_eq!(nested.path, "group/sub/project");
+        assert!(Remote::parse("/srv/git/cci").is_err());
+    }
+
+    #[test]
+    fn test_detect_forge() {
+        let remote = |host: &str| Remote {
+            host: host.to_string(),
+            path: "o/r".to_string(),
+        };
+        assert_eq!(
+            Forge::detect(&remote("gitlab.example.com"), &[]).unwrap(),
+            Forge::GitLab
+        );
+        assert_eq!(
+            Forge::detect(
+                &remote("git.example.com"),
+                &[Platform::Jenkins, Platform::Gitea]
+            )
+            .unwrap(),
+            Forge::Gitea
+        );
+        assert!(Forge::detect(&remote("git.example.com"), &[Platform::Jenkins]).is_err());
+    }
+
+    #[test]
+    fn test_curl_config() {
+        let remote = Remote::parse("git@gitlab.com:group/project.git").unwrap();
+        let request = PullRequest {
+            head: "cci/update-ci",
+            base: "main",
+            title: "Add CI",
+            body: "Generated from \"cci.ron\"\n",
+        };
+        let body = pull_request_body(Forge::GitLab, &request).unwrap();
+        let config = curl_config(
+            &Forge::GitLab.endpoint(&remote),
+            Some(&Forge::GitLab.auth_header("secret")),
+            Some(&body),
+        );
+        assert!(config.starts_with(
+            "url = \"https://gitlab.com/api/v4/projects/group%2Fproject/merge_requests\"\n\
+             header = \"PRIVATE-TOKEN: secret\"\n"
+        ));
+        assert!(config.contains(r#"\"source_branch\": \"cci/update-ci\""#));
+        assert!(config.contains(r#"Generated from \\\"cci.ron\\\"\\n"#));
+        assert_eq!(config.lines().count(), 5);
+    }
+
+    #[test]
+    fn test_pull_request_url() {
+        let created = parse_output("{\"html_url\": \"https://github.com/o/r/pull/7\"}\n201");
+        assert_eq!(
+            pull_request_url(Forge::GitHub, &created).unwrap(),
+            "https://github.com/o/r/pull/7"
+        );
+        let refused = parse_output("{\"message\": \"Validation Failed\"}\n422");
+        let e = pull_request_url(Forge::GitHub, &refused)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            e,
+            "GitHub refused to open the pull request (HTTP 422): Validation Failed"
+        );
+    }
+
+    #[test]
+    fn test_repos_in_listing() {
+        let page: Value = serde_yaml::from_str(
+            r#"[
+                {"name": "api", "clone_url": "https://example.com/o/api.git", "archived": false},
+                {"name": "old", "clone_url": "https://example.com/o/old.git", "archived": true}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            repos_in(Forge::Gitea, &page),
+            vec![OrgRepo {
+                name: "api".to_string(),
+                clone_url: "https://example.com/o/api.git".to_string(),
+            }]
+        );
+        assert!(repos_in(Forge::GitLab, &page).is_empty());
+    }
+}
@@ -0,0 +1,342 @@
+//! `cci generate --commit`: committing generated files and opening a pull request
+//!
+//! Everything goes through the `git` binary so the user's own credentials,
+//! hooks and signing settings apply. Pull requests are opened through the
+//! forge's REST API; see [`forge`].
+
+pub mod forge;
+
+use crate::editor::state::Platform;
+use crate::error::{config_error, Result};
+use anyhow::{bail, Context};
+use forge::{Forge, PullRequest, Remote};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Branch generated files are committed to unless `--branch` says otherwise
+pub const DEFAULT_BRANCH: &str = "cci/update-ci";
+
+/// A git working tree
+#[derive(Debug, Clone)]
+pub struct Repo {
+    dir: PathBuf,
+}
+
+/// How a staged file differs from `HEAD`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Added,
+    Modified,
+}
+
+impl Repo {
+    /// The repository `dir` is in
+    pub fn open(dir: &Path) -> Result<Self> {
+        let repo = Self {
+            dir: dir.to_path_buf(),
+        };
+        repo.git(&["rev-parse", "--show-toplevel"])
+            .map_err(|_| config_error(format!("{} isn't in a git repository", dir.display())))?;
+        Ok(repo)
+    }
+
+    /// Run `git` in the working tree, returning its trimmed stdout
+    fn git(&self, args: &[&str]) -> Result<String> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(&self.dir)
+            .output()
+            .context("Failed to run git")?;
+        if !output.status.success() {
+            bail!(
+                "git {} failed: {}",
+                args.first().unwrap_or(&""),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// The checked out branch
+    pub fn current_branch(&self) -> Result<String> {
+        self.git(&["symbolic-ref", "--short", "HEAD"])
+            .map_err(|_| config_error("HEAD is detached; check out a branch first"))
+    }
+
+    pub fn branch_exists(&self, branch: &str) -> bool {
+        self.git(&[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("refs/heads/{}", branch),
+        ])
+        .is_ok()
+    }
+
+    /// The URL of `remote`
+    pub fn remote_url(&self, remote: &str) -> Result<String> {
+        self.git(&["remote", "get-url", remote])
+            .map_err(|_| config_error(format!("The repository has no `{}` remote", remote)))
+    }
+
+    /// Stage `paths`, returning the ones that differ from `HEAD`
+    pub fn stage(&self, paths: &[PathBuf]) -> Result<Vec<(Change, PathBuf)>> {
+        let paths: Vec<String> = paths
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+        let mut args = vec!["add", "--"];
+        args.extend(paths.iter().map(String::as_str));
+        self.git(&args)?;
+
+        let mut args = vec!["diff", "--cached", "--name-status", "--no-renames", "--"];
+        args.extend(paths.iter().map(String::as_str));
+        Ok(self
+            .git(&args)?
+            .lines()
+            .filter_map(|line| {
+                let (status, path) = line.split_once('\t')?;
+                let change = match status {
+                    "A" => Change::Added,
+                    _ => Change::Modified,
+                };
+                Some((change, PathBuf::from(path)))
+            })
+            .collect())
+    }
+
+    /// Create `branch` from `HEAD` and check it out, keeping local changes
+    pub fn switch_new(&self, branch: &str) -> Result<()> {
+        self.git(&["switch", "-c", branch]).map(|_| ())
+    }
+
+    /// Commit `paths` alone, leaving anything else that's staged for later
+    pub fn commit(&self, message: &str, paths: &[PathBuf]) -> Result<()> {
+        let paths: Vec<String> = paths
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+        let mut args = vec!["commit", "--quiet", "-m", message, "--"];
+        args.extend(paths.iter().map(String::as_str));
+        self.git(&args).map(|_| ())
+    }
+
+    /// Push `branch` to `remote` and track it
+    pub fn push(&self, remote: &str, branch: &str) -> Result<()> {
+        self.git(&["push", "--quiet", "--set-upstream", remote, branch])
+            .map(|_| ())
+    }
+}
+
+/// Clone `url` into `dir`
+pub fn clone(url: &str, dir: &Path) -> Result<Repo> {
+    let output = Command::new("git")
+        .args(["clone", "--quiet", url])
+        .arg(dir)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "git clone {} failed: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Repo::open(dir)
+}
+
+/// Where generated files are committed and whether a pull request is opened for them
+///
+/// [`Publish::prepare`] checks everything that could fail before any file is
+/// written: the branch, the remote and the token.
+#[derive(Debug, Clone)]
+pub struct Publish {
+    /// The branch that was checked out, which the pull request targets
+    pub base: String,
+    pub branch: String,
+    pull_request: Option<(Forge, Remote, String)>,
+}
+
+/// What [`Publish::publish`] did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Published {
+    pub changes: Vec<(Change, PathBuf)>,
+    /// The pull request's URL, if one was opened
+    pub url: Option<String>,
+}
+
+impl Publish {
+    pub fn prepare(
+        repo: &Repo,
+        branch: &str,
+        pull_request: bool,
+        platforms: &[Platform],
+    ) -> Result<Self> {
+        let base = repo.current_branch()?;
+        if branch != base && repo.branch_exists(branch) {
+            bail!(
+                "Branch `{}` already exists; pick another with --branch",
+                branch
+            );
+        }
+        let pull_request = if pull_request {
+            if branch == base {
+                bail!("Already on `{}`; a pull request needs its own branch", base);
+            }
+            let remote = Remote::parse(&repo.remote_url("origin")?)?;
+            let forge = Forge::detect(&remote, platforms)?;
+            let token = forge.token()?;
+            Some((forge, remote, token))
+        } else {
+            None
+        };
+        Ok(Self {
+            base,
+            branch: branch.to_string(),
+            pull_request,
+        })
+    }
+
+    /// What the pull request is called on this forge, if one will be opened
+    pub fn request_name(&self) -> Option<&'static str> {
+        self.pull_request
+            .as_ref()
+            .map(|(forge, _, _)| forge.request_name())
+    }
+
+    /// Commit `paths`, generated from `config` with `presets`, and open the pull request
+    ///
+    /// Returns `None` when the files are already committed.
+    pub fn publish(
+        &self,
+        repo: &Repo,
+        config: &str,
+        presets: &[String],
+        paths: &[PathBuf],
+    ) -> Result<Option<Published>> {
+        let changes = repo.stage(paths)?;
+        if changes.is_empty() {
+            return Ok(None);
+        }
+        if self.branch != self.base {
+            repo.switch_new(&self.branch)?;
+        }
+        let message = commit_message(config, presets, &changes);
+        repo.commit(&message, paths)?;
+
+        let url = match &self.pull_request {
+            Some((forge, remote, token)) => {
+                repo.push("origin", &self.branch)?;
+                let (title, body) = message.split_once("\n\n").unwrap_or((&message, ""));
+                Some(forge::open(
+                    *forge,
+                    remote,
+                    token,
+                    &PullRequest {
+                        head: &self.branch,
+                        base: &self.base,
+                        title,
+                        body,
+                    },
+                )?)
+            }
+            None => None,
+        };
+        Ok(Some(Published { changes, url }))
+    }
+}
+
+/// The message for committing `changes`, generated from `config` with `presets`
+///
+/// The subject says whether the pipelines are new; the body lists the files.
+pub fn commit_message(config: &str, presets: &[String], changes: &[(Change, PathBuf)]) -> String {
+    let subject = if changes.iter().all(|(change, _)| *change == Change::Added) {
+        "Add CI configuration generated by cci"
+    } else {
+        "Update CI configuration generated by cci"
+    };
+    let mut message = format!(
+        "{}\n\nGenerated from {} with the {} preset(s):\n\n",
+        subject,
+        config,
+        presets.join(", ")
+    );
+    for (change, path) in changes {
+        let verb = match change {
+            Change::Added => "add",
+            Change::Modified => "update",
+        };
+        message.push_str(&format!("- {} {}\n", verb, path.display()));
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_message() {
+        let message = commit_message(
+            "cci.ron",
+            &["rust".to_string(), "release".to_string()],
+            &[
+                (Change::Added, PathBuf::from(".github/workflows/rust.yml")),
+                (Change::Modified, PathBuf::from("cci.ron")),
+            ],
+        );
+        assert_eq!(
+            message,
+            "Update CI configuration generated by cci\n\n\
+             Generated from cci.ron with the rust, release preset(s):\n\n\
+             - add .github/workflows/rust.yml\n\
+             - update cci.ron\n"
+        );
+    }
+
+    #[test]
+    fn test_stage_and_commit_on_a_new_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .args(args)
+                .current_dir(dir.path())
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        git(&["init", "--quiet", "-b", "main"]);
+        git(&["config", "user.name", "cci"]);
+        git(&["config", "user.email", "cci@example.com"]);
+        git(&["config", "commit.gpgsign", "false"]);
+        std::fs::write(dir.path().join("README.md"), "hi\n").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "staged\n").unwrap();
+        git(&["add", "README.md"]);
+        git(&["commit", "--quiet", "-m", "init"]);
+        git(&["add", "notes.txt"]);
+
+        let repo = Repo::open(dir.path()).unwrap();
+        assert_eq!(repo.current_branch().unwrap(), "main");
+        assert!(!repo.branch_exists(DEFAULT_BRANCH));
+
+        std::fs::write(dir.path().join("README.md"), "hi\n").unwrap();
+        std::fs::write(dir.path().join(".gitlab-ci.yml"), "stages: []\n").unwrap();
+        let paths = vec![PathBuf::from("README.md"), PathBuf::from(".gitlab-ci.yml")];
+        let changes = repo.stage(&paths).unwrap();
+        assert_eq!(
+            changes,
+            vec![(Change::Added, PathBuf::from(".gitlab-ci.yml"))]
+        );
+
+        repo.switch_new(DEFAULT_BRANCH).unwrap();
+        repo.commit("Add CI", &paths).unwrap();
+        assert_eq!(repo.current_branch().unwrap(), DEFAULT_BRANCH);
+        assert_eq!(repo.git(&["log", "-1", "--format=%s"]).unwrap(), "Add CI");
+        // Files the user had staged aren't swept into the commit
+        assert_eq!(
+            repo.git(&["diff", "--cached", "--name-only"]).unwrap(),
+            "notes.txt"
+        );
+        assert!(repo.remote_url("origin").is_err());
+    }
+}
@@ -1,9 +1,14 @@
 pub mod cli;
 pub mod config;
 pub mod detection;
+pub mod diff;
 pub mod editor;
 pub mod error;
 pub mod generator;
+pub mod git;
+pub mod lint;
+pub mod local;
+pub mod models;
 pub mod platforms;
 pub mod presets;
 pub mod traits;
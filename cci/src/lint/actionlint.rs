@@ -0,0 +1,93 @@
+//! Running `actionlint` on GitHub workflows, when it's installed
+//!
+//! actionlint knows far more than cci's own rules (expression types, action
+//! inputs, shellcheck on `run:` scripts), but it's an external binary, so it
+//! only runs when it's on `PATH`.
+
+use super::{Issue, Severity};
+use anyhow::bail;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+
+/// One finding per line: `line`, `column`, `kind` and `message`, separated by tabs
+const FORMAT: &str =
+    "{{range $err := .}}{{$err.Line}}\t{{$err.Column}}\t{{$err.Kind}}\t{{$err.Message}}\n{{end}}";
+
+/// Check `content` as if it were the workflow at `filename`
+///
+/// Returns `None` when actionlint isn't installed.
+pub fn check(filename: &Path, content: &str) -> Option<crate::error::Result<Vec<Issue>>> {
+    let child = match Command::new("actionlint")
+        .arg("-no-color")
+        .arg("-format")
+        .arg(FORMAT)
+        .arg("-stdin-filename")
+        .arg(filename)
+        .arg("-")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+        Err(e) => return Some(Err(e.into())),
+    };
+    Some(collect(child, content))
+}
+
+fn collect(mut child: Child, content: &str) -> crate::error::Result<Vec<Issue>> {
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(content.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    // 0 is clean and 1 means findings were printed; anything else is a failure
+    if !matches!(output.status.code(), Some(0 | 1)) {
+        bail!(
+            "actionlint failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(parse(&String::from_utf8_lossy(&output.stdout)))
+}
+
+fn parse(output: &str) -> Vec<Issue> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, '\t');
+            let line: usize = fields.next()?.parse().ok()?;
+            let column: usize = fields.next()?.parse().ok()?;
+            let kind = fields.next()?;
+            let message = fields.next()?;
+            Some(Issue {
+                severity: Severity::Error,
+                location: format!("line {}:{}", line, column),
+                message: format!("{} [{}]", message, kind),
+                line: Some(line),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_findings() {
+        let output =
+            "12\t9\texpression\tproperty \"nme\" is not defined in object type {name: string}\n\
+                      not a finding\n\
+                      3\t1\tsyntax-check\tunexpected key \"job\" for \"workflow\" section\n";
+        let issues = parse(output);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].line, Some(12));
+        assert_eq!(
+            issues[0].to_string(),
+            "line 12:9: property \"nme\" is not defined in object type {name: string} [expression]"
+        );
+        assert_eq!(issues[1].location, "line 3:1");
+    }
+}
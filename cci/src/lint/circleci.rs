@@ -0,0 +1,183 @@
+use super::{at, check_cycles, check_keys, check_references, error, string_list, Issue};
+use serde_yaml::{Mapping, Value};
+
+const CONFIG_KEYS: &[&str] = &[
+    "version",
+    "setup",
+    "orbs",
+    "commands",
+    "executors",
+    "parameters",
+    "jobs",
+    "workflows",
+];
+
+const JOB_KEYS: &[&str] = &[
+    "docker",
+    "machine",
+    "macos",
+    "executor",
+    "resource_class",
+    "parallelism",
+    "parameters",
+    "steps",
+    "environment",
+    "working_directory",
+    "shell",
+    "circleci_ip_ranges",
+];
+
+const EXECUTORS: &[&str] = &["docker", "machine", "macos", "executor"];
+
+const WORKFLOW_KEYS: &[&str] = &["triggers", "jobs", "when", "unless", "max_auto_reruns"];
+
+pub(super) fn lint(root: &Mapping, issues: &mut Vec<Issue>) {
+    check_keys(root, CONFIG_KEYS, "", issues);
+    if !root.contains_key("version") {
+        issues.push(error("version", "missing"));
+    }
+
+    let mut ids = Vec::new();
+    if let Some(Value::Mapping(jobs)) = root.get("jobs") {
+        for (id, job) in jobs {
+            let id = id.as_str().unwrap_or_default();
+            let location = at("jobs", id);
+            ids.push(id.to_string());
+            let Value::Mapping(job) = job else {
+                issues.push(error(location, "job must be a mapping"));
+                continue;
+            };
+            check_keys(job, JOB_KEYS, &location, issues);
+
+            let executors = EXECUTORS
+                .iter()
+                .filter(|key| job.contains_key(**key))
+                .count();
+            match executors {
+                0 => issues.push(error(
+                    &location,
+                    "needs an executor (`docker`, `machine`, `macos` or `executor`)",
+                )),
+                1 => {}
+                _ => issues.push(error(&location, "has more than one executor")),
+            }
+            if !matches!(job.get("steps"), Some(Value::Sequence(steps)) if !steps.is_empty()) {
+                issues.push(error(at(&location, "steps"), "missing or empty"));
+            }
+        }
+    }
+
+    let Some(Value::Mapping(workflows)) = root.get("workflows") else {
+        issues.push(error("workflows", "missing; no job ever runs"));
+        return;
+    };
+    for (name, workflow) in workflows {
+        let name = name.as_str().unwrap_or_default();
+        // `version: 2` was required in workflows of 2.0 configs
+        if name == "version" {
+            continue;
+        }
+        let location = at("workflows", name);
+        let Value::Mapping(workflow) = workflow else {
+            issues.push(error(location, "workflow must be a mapping"));
+            continue;
+        };
+        check_keys(workflow, WORKFLOW_KEYS, &location, issues);
+        lint_workflow_jobs(workflow.get("jobs"), &ids, &location, issues);
+    }
+}
+
+/// Check that workflow entries run defined jobs and only require entries of the same workflow
+fn lint_workflow_jobs(
+    jobs: Option<&Value>,
+    ids: &[String],
+    location: &str,
+    issues: &mut Vec<Issue>,
+) {
+    let location = at(location, "jobs");
+    let Some(Value::Sequence(entries)) = jobs else {
+        issues.push(error(location, "missing or not a list"));
+        return;
+    };
+
+    // (name other entries require it by, job it runs, entries it requires, approval)
+    let mut parsed = Vec::new();
+    for entry in entries {
+        let (job, settings) = match entry {
+            Value::String(job) => (job.clone(), None),
+            Value::Mapping(entry) if entry.len() == 1 => {
+                let (job, settings) = entry.iter().next().unwrap();
+                (
+                    job.as_str().unwrap_or_default().to_string(),
+                    settings.as_mapping(),
+                )
+            }
+            _ => {
+                issues.push(error(&location, "entries must name a single job"));
+                continue;
+            }
+        };
+        let name = settings
+            .and_then(|settings| settings.get("name"))
+            .and_then(Value::as_str)
+            .map_or_else(|| job.clone(), str::to_string);
+        let requires = string_list(settings.and_then(|settings| settings.get("requires")));
+        let approval = settings
+            .and_then(|settings| settings.get("type"))
+            .and_then(Value::as_str)
+            == Some("approval");
+        parsed.push((name, job, requires, approval));
+    }
+
+    let names: Vec<String> = parsed.iter().map(|(name, ..)| name.clone()).collect();
+    for (name, job, requires, approval) in &parsed {
+        // Approval jobs and orb jobs (`orb/job`) aren't defined under `jobs:`
+        if !approval && !job.contains('/') && !ids.contains(job) {
+            issues.push(error(&location, format!("unknown job `{}`", job)));
+        }
+        check_references(name, requires, &names, &location, issues);
+    }
+    let dependencies: Vec<(String, Vec<String>)> = parsed
+        .into_iter()
+        .map(|(name, _, requires, _)| (name, requires))
+        .collect();
+    check_cycles(&dependencies, &location, issues);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::lint;
+    use crate::editor::state::Platform;
+
+    #[test]
+    fn test_reports_executors_and_workflow_references() {
+        let config = r#"
+version: 2.1
+jobs:
+  build:
+    steps: [checkout]
+  test:
+    docker:
+      - image: cimg/rust:1.80
+    steps: [checkout]
+workflows:
+  main:
+    jobs:
+      - build
+      - test:
+          requires: [lint]
+      - node/test
+      - deploy
+"#;
+        let issues: Vec<String> = lint(Platform::CircleCI, config)
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect();
+        assert!(issues
+            .iter()
+            .any(|issue| issue.starts_with("jobs.build: needs an executor")));
+        assert!(issues.contains(&"workflows.main.jobs: unknown job `lint`".to_string()));
+        assert!(issues.contains(&"workflows.main.jobs: unknown job `deploy`".to_string()));
+        assert!(!issues.iter().any(|issue| issue.contains("node/test")));
+    }
+}
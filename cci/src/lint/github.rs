@@ -0,0 +1,330 @@
+use super::{at, check_cycles, check_keys, check_references, error, warning, Issue};
+use crate::platforms::github::models::{GitHubRunsOn, GitHubWorkflow};
+use serde_yaml::{Mapping, Value};
+
+const WORKFLOW_KEYS: &[&str] = &[
+    "name",
+    "run-name",
+    "on",
+    "permissions",
+    "env",
+    "defaults",
+    "concurrency",
+    "jobs",
+];
+
+const JOB_KEYS: &[&str] = &[
+    "name",
+    "permissions",
+    "needs",
+    "if",
+    "runs-on",
+    "environment",
+    "concurrency",
+    "outputs",
+    "env",
+    "defaults",
+    "steps",
+    "timeout-minutes",
+    "strategy",
+    "continue-on-error",
+    "container",
+    "services",
+    "uses",
+    "with",
+    "secrets",
+];
+
+const STEP_KEYS: &[&str] = &[
+    "id",
+    "if",
+    "name",
+    "uses",
+    "run",
+    "shell",
+    "with",
+    "env",
+    "continue-on-error",
+    "timeout-minutes",
+    "working-directory",
+];
+
+/// Labels of GitHub-hosted runners
+const HOSTED_RUNNERS: &[&str] = &[
+    "ubuntu-latest",
+    "ubuntu-24.04",
+    "ubuntu-22.04",
+    "ubuntu-20.04",
+    "ubuntu-24.04-arm",
+    "ubuntu-22.04-arm",
+    "windows-latest",
+    "windows-2025",
+    "windows-2022",
+    "windows-2019",
+    "windows-11-arm",
+    "macos-latest",
+    "macos-15",
+    "macos-14",
+    "macos-13",
+    "macos-latest-large",
+    "macos-15-large",
+    "macos-14-large",
+    "macos-13-large",
+    "macos-latest-xlarge",
+    "macos-15-xlarge",
+    "macos-14-xlarge",
+    "macos-13-xlarge",
+];
+
+/// Actions whose older major versions GitHub no longer runs or has deprecated:
+/// (action, first major still supported, why the older ones aren't)
+const DEPRECATED_ACTIONS: &[(&str, u32, &str)] = &[
+    ("actions/checkout", 4, "runs on Node 16"),
+    ("actions/setup-node", 4, "runs on Node 16"),
+    ("actions/setup-python", 5, "runs on Node 16"),
+    ("actions/setup-go", 5, "runs on Node 16"),
+    ("actions/cache", 4, "runs on Node 16"),
+    (
+        "actions/upload-artifact",
+        4,
+        "stopped working in January 2025",
+    ),
+    (
+        "actions/download-artifact",
+        4,
+        "stopped working in January 2025",
+    ),
+    ("codecov/codecov-action", 4, "runs on Node 16"),
+    ("golangci/golangci-lint-action", 4, "runs on Node 16"),
+    ("docker/login-action", 3, "runs on Node 16"),
+    ("docker/setup-buildx-action", 3, "runs on Node 16"),
+    ("docker/metadata-action", 5, "runs on Node 16"),
+    ("docker/build-push-action", 5, "runs on Node 16"),
+];
+
+/// Why `uses` is a deprecated version of its action, if it is
+fn deprecated_action(uses: &str) -> Option<String> {
+    let (action, version) = uses.split_once('@')?;
+    let major: u32 = version.strip_prefix('v')?.split('.').next()?.parse().ok()?;
+    let (_, supported, reason) = DEPRECATED_ACTIONS
+        .iter()
+        .find(|(known, supported, _)| *known == action && major < *supported)?;
+    Some(format!(
+        "`{}` is deprecated ({}); use `{}@v{}`",
+        uses, reason, action, supported
+    ))
+}
+
+/// Check a GitHub-style workflow; `check_runners` is off for Gitea
+pub(super) fn lint(root: &Mapping, content: &str, check_runners: bool, issues: &mut Vec<Issue>) {
+    check_keys(root, WORKFLOW_KEYS, "", issues);
+    if !root.contains_key("on") {
+        issues.push(error("on", "missing; the workflow never runs"));
+    }
+    let Some(Value::Mapping(jobs)) = root.get("jobs") else {
+        issues.push(error("jobs", "missing or not a mapping"));
+        return;
+    };
+    if jobs.is_empty() {
+        issues.push(error("jobs", "no jobs defined"));
+    }
+
+    for (id, job) in jobs {
+        let location = at("jobs", id.as_str().unwrap_or_default());
+        let Value::Mapping(job) = job else {
+            issues.push(error(location, "job must be a mapping"));
+            continue;
+        };
+        lint_job(job, &location, issues);
+    }
+
+    // Reference checks need the typed workflow; configs it can't represent
+    // were already reported above or use features cci doesn't model
+    let workflow = match serde_yaml::from_str::<GitHubWorkflow>(content) {
+        Ok(workflow) => workflow,
+        Err(e) => {
+            issues.push(warning(
+                "",
+                format!("skipped dependency and runner checks: {}", e),
+            ));
+            return;
+        }
+    };
+
+    let ids: Vec<String> = workflow.jobs.keys().cloned().collect();
+    let mut dependencies = Vec::new();
+    for (id, job) in &workflow.jobs {
+        let location = at("jobs", id);
+        let needs = job.needs.clone().unwrap_or_default();
+        check_references(id, &needs, &ids, &at(&location, "needs"), issues);
+        dependencies.push((id.clone(), needs));
+
+        if check_runners {
+            let labels = match &job.runs_on {
+                GitHubRunsOn::Label(label) => vec![label.clone()],
+                GitHubRunsOn::Labels(labels) => labels.clone(),
+            };
+            let self_hosted = labels.iter().any(|label| label == "self-hosted");
+            for label in labels {
+                if !self_hosted
+                    && !label.contains("${{")
+                    && !HOSTED_RUNNERS.contains(&label.as_str())
+                {
+                    issues.push(warning(
+                        at(&location, "runs-on"),
+                        format!(
+                            "`{}` is not a GitHub-hosted runner; add `self-hosted` if it's one of yours",
+                            label
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+    check_cycles(&dependencies, "jobs", issues);
+}
+
+fn lint_job(job: &Mapping, location: &str, issues: &mut Vec<Issue>) {
+    check_keys(job, JOB_KEYS, location, issues);
+
+    // Jobs calling a reusable workflow take neither a runner nor steps
+    if job.contains_key("uses") {
+        return;
+    }
+    if !job.contains_key("runs-on") {
+        issues.push(error(at(location, "runs-on"), "missing"));
+    }
+    let Some(Value::Sequence(steps)) = job.get("steps") else {
+        issues.push(error(at(location, "steps"), "missing or not a list"));
+        return;
+    };
+    if steps.is_empty() {
+        issues.push(error(at(location, "steps"), "no steps defined"));
+    }
+
+    for (index, step) in steps.iter().enumerate() {
+        let location = format!("{}.steps[{}]", location, index);
+        let Value::Mapping(step) = step else {
+            issues.push(error(location, "step must be a mapping"));
+            continue;
+        };
+        check_keys(step, STEP_KEYS, &location, issues);
+
+        match (step.get("uses"), step.contains_key("run")) {
+            (Some(_), true) => issues.push(error(location, "has both `uses` and `run`")),
+            (None, false) => issues.push(error(location, "needs either `uses` or `run`")),
+            (Some(uses), false) => {
+                let uses = uses.as_str().unwrap_or_default();
+                let local = uses.starts_with("./") || uses.starts_with("docker://");
+                if !local && !uses.contains('@') {
+                    issues.push(error(
+                        at(&location, "uses"),
+                        format!("`{}` needs a version, e.g. `{}@v1`", uses, uses),
+                    ));
+                } else if let Some(message) = deprecated_action(uses) {
+                    issues.push(warning(at(&location, "uses"), message));
+                }
+            }
+            (None, true) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::lint;
+    use crate::editor::state::Platform;
+
+    #[test]
+    fn test_reports_bad_keys_needs_and_runners() {
+        let workflow = r#"
+name: CI
+on: [push]
+jobs:
+  build:
+    runs-on: ubuntu-lates
+    steps:
+      - uses: actions/checkout
+      - run: cargo build
+        timeout: 5
+  test:
+    runs-on: ubuntu-latest
+    needs: [biuld]
+    steps:
+      - run: cargo test
+"#;
+        let issues: Vec<String> = lint(Platform::GitHub, workflow)
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect();
+        assert!(issues.contains(&"jobs.build.steps[1].timeout: unknown key".to_string()));
+        assert!(issues.contains(&"jobs.test.needs: unknown job `biuld`".to_string()));
+        assert!(issues
+            .iter()
+            .any(|issue| issue.starts_with("jobs.build.steps[0].uses")));
+        assert!(issues
+            .iter()
+            .any(|issue| issue.starts_with("jobs.build.runs-on: `ubuntu-lates`")));
+        assert!(lint(Platform::Gitea, workflow)
+            .iter()
+            .all(|issue| !issue.location.ends_with("runs-on")));
+    }
+
+    #[test]
+    fn test_checks_scalar_triggers_and_needs() {
+        let workflow = r#"
+name: CI
+on: push
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - run: cargo build
+  test:
+    runs-on: ubuntu-latest
+    needs: build
+    steps:
+      - run: cargo test
+  deploy:
+    runs-on: ubuntu-latest
+    needs: tset
+    steps:
+      - run: ./deploy.sh
+"#;
+        let issues: Vec<String> = lint(Platform::GitHub, workflow)
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect();
+        assert_eq!(
+            issues,
+            ["jobs.deploy.needs: unknown job `tset`".to_string()],
+            "{:?}",
+            issues
+        );
+    }
+
+    #[test]
+    fn test_reports_deprecated_actions() {
+        let workflow = r#"
+name: CI
+on: [push]
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v3
+      - uses: actions/upload-artifact@v4.1.0
+      - uses: codecov/codecov-action@v2.1
+      - uses: actions/cache@0123456789abcdef0123456789abcdef01234567
+"#;
+        let issues: Vec<String> = lint(Platform::GitHub, workflow)
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect();
+        assert_eq!(issues.len(), 2, "{:?}", issues);
+        assert!(
+            issues[0].starts_with("jobs.build.steps[0].uses: `actions/checkout@v3` is deprecated")
+        );
+        assert!(issues[1].ends_with("use `codecov/codecov-action@v4`"));
+    }
+}
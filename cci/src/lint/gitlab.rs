@@ -0,0 +1,188 @@
+use super::{at, check_cycles, check_keys, check_references, error, string_list, Issue};
+use serde_yaml::{Mapping, Value};
+
+/// Top-level keys that configure the pipeline; every other key is a job
+const GLOBAL_KEYS: &[&str] = &[
+    "default",
+    "include",
+    "stages",
+    "variables",
+    "workflow",
+    "image",
+    "services",
+    "cache",
+    "before_script",
+    "after_script",
+];
+
+const JOB_KEYS: &[&str] = &[
+    "after_script",
+    "allow_failure",
+    "artifacts",
+    "before_script",
+    "cache",
+    "coverage",
+    "dast_configuration",
+    "dependencies",
+    "environment",
+    "except",
+    "extends",
+    "hooks",
+    "id_tokens",
+    "identity",
+    "image",
+    "inherit",
+    "interruptible",
+    "needs",
+    "only",
+    "pages",
+    "parallel",
+    "release",
+    "resource_group",
+    "retry",
+    "rules",
+    "run",
+    "script",
+    "secrets",
+    "services",
+    "stage",
+    "tags",
+    "timeout",
+    "trigger",
+    "variables",
+    "when",
+];
+
+/// Stages available without a `stages:` list
+const DEFAULT_STAGES: &[&str] = &[".pre", "build", "test", "deploy", ".post"];
+
+pub(super) fn lint(root: &Mapping, issues: &mut Vec<Issue>) {
+    let mut stages: Vec<String> = match root.get("stages") {
+        Some(stages) => string_list(Some(stages)),
+        None => DEFAULT_STAGES
+            .iter()
+            .map(|stage| stage.to_string())
+            .collect(),
+    };
+    stages.extend([".pre".to_string(), ".post".to_string()]);
+
+    let jobs: Vec<(&str, &Value)> = root
+        .iter()
+        .filter_map(|(key, value)| Some((key.as_str()?, value)))
+        .filter(|(key, _)| !GLOBAL_KEYS.contains(key))
+        .collect();
+    let ids: Vec<String> = jobs
+        .iter()
+        .map(|(id, _)| id.to_string())
+        .filter(|id| !id.starts_with('.'))
+        .collect();
+    // Included templates bring their own jobs
+    if ids.is_empty() && !root.contains_key("include") {
+        issues.push(error("", "no jobs defined"));
+    }
+
+    let mut dependencies = Vec::new();
+    for (id, job) in jobs {
+        let Value::Mapping(job) = job else {
+            issues.push(error(id, "job must be a mapping"));
+            continue;
+        };
+        check_keys(job, JOB_KEYS, id, issues);
+        // Hidden jobs are templates for `extends` and may be incomplete
+        if id.starts_with('.') {
+            continue;
+        }
+
+        let runnable = ["script", "run", "trigger", "extends"]
+            .iter()
+            .any(|key| job.contains_key(*key));
+        if !runnable {
+            issues.push(error(at(id, "script"), "missing"));
+        }
+        if let Some(stage) = job.get("stage").and_then(Value::as_str) {
+            if !stages.iter().any(|known| known == stage) {
+                issues.push(error(
+                    at(id, "stage"),
+                    format!("`{}` is not listed in `stages`", stage),
+                ));
+            }
+        }
+        if job.contains_key("rules") && (job.contains_key("only") || job.contains_key("except")) {
+            issues.push(error(
+                at(id, "rules"),
+                "can't be combined with `only` or `except`",
+            ));
+        }
+
+        let needs = needs(job.get("needs"));
+        check_references(id, &needs, &ids, &at(id, "needs"), issues);
+        let dependencies_list = string_list(job.get("dependencies"));
+        check_references(
+            id,
+            &dependencies_list,
+            &ids,
+            &at(id, "dependencies"),
+            issues,
+        );
+        dependencies.push((id.to_string(), needs));
+    }
+    check_cycles(&dependencies, "", issues);
+}
+
+/// Jobs named under `needs`, given as plain names or `{ job: name }` entries
+///
+/// Entries pointing at another project or pipeline aren't checked.
+fn needs(value: Option<&Value>) -> Vec<String> {
+    let Some(Value::Sequence(entries)) = value else {
+        return Vec::new();
+    };
+    entries
+        .iter()
+        .filter_map(|entry| match entry {
+            Value::String(job) => Some(job.clone()),
+            Value::Mapping(entry)
+                if !entry.contains_key("project") && !entry.contains_key("pipeline") =>
+            {
+                entry.get("job").and_then(Value::as_str).map(str::to_string)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::lint;
+    use crate::editor::state::Platform;
+
+    #[test]
+    fn test_reports_stages_and_cycles() {
+        let ci = r#"
+stages: [build, test]
+.template:
+  image: rust
+build:
+  stage: build
+  needs: [test]
+  script: [cargo build]
+test:
+  stage: tests
+  needs: [build]
+  script: [cargo test]
+lint:
+  stage: test
+  scripts: [cargo clippy]
+"#;
+        let issues: Vec<String> = lint(Platform::GitLab, ci)
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect();
+        assert!(issues.contains(&"test.stage: `tests` is not listed in `stages`".to_string()));
+        assert!(issues.contains(&"lint.scripts: unknown key".to_string()));
+        assert!(issues.contains(&"lint.script: missing".to_string()));
+        assert!(issues
+            .iter()
+            .any(|issue| issue.starts_with("build: dependencies form a cycle")));
+        assert!(!issues.iter().any(|issue| issue.starts_with(".template")));
+    }
+}
@@ -0,0 +1,348 @@
+//! Static checks for CI configs, generated or written by hand
+//!
+//! Each platform checks its keys against the ones the platform accepts and
+//! that jobs only reference jobs that exist. Configs are read as plain YAML
+//! so unknown keys can be reported; GitHub workflows are additionally parsed
+//! through the platform models.
+
+pub mod actionlint;
+mod circleci;
+mod github;
+mod gitlab;
+pub mod schema;
+
+use crate::editor::state::Platform;
+use serde_yaml::{Mapping, Value};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The platform will reject the config or the job can never run
+    Error,
+    /// Valid, but probably not what was intended
+    Warning,
+}
+
+/// A problem found in a config
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub severity: Severity,
+    /// Dotted path of the offending key, e.g. `jobs.test.needs`
+    pub location: String,
+    pub message: String,
+    /// 1-based line the issue is on, when known
+    pub line: Option<usize>,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.location.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.location, self.message)
+        }
+    }
+}
+
+/// Check a config written for `platform`
+pub fn lint(platform: Platform, content: &str) -> Vec<Issue> {
+    let root: Value = match serde_yaml::from_str(content) {
+        Ok(root) => root,
+        Err(e) => return vec![error("", format!("invalid YAML: {}", e))],
+    };
+    let Value::Mapping(root) = root else {
+        return vec![error("", "expected a mapping at the top level")];
+    };
+
+    let mut issues = Vec::new();
+    match platform {
+        Platform::GitHub => github::lint(&root, content, true, &mut issues),
+        // Gitea runners are all self-hosted, so any runner label can be valid
+        Platform::Gitea => github::lint(&root, content, false, &mut issues),
+        Platform::GitLab => gitlab::lint(&root, &mut issues),
+        Platform::CircleCI => circleci::lint(&root, &mut issues),
+        Platform::Jenkins => issues.push(warning("", "Jenkinsfiles can't be linted")),
+    }
+    issues.sort_by_key(|issue| issue.severity);
+    issues
+}
+
+/// Check a file cci just generated, to surface problems in the preview and `cci generate`
+///
+/// GitHub and Gitea workflows also go through actionlint when it's installed.
+/// Issues get the line they're on where it can be found.
+pub fn check_generated(platform: Platform, filename: &Path, content: &str) -> Vec<Issue> {
+    if platform == Platform::Jenkins {
+        return Vec::new();
+    }
+    let mut issues: Vec<Issue> = lint(platform, content)
+        .into_iter()
+        .map(|mut issue| {
+            issue.line = line_of(content, &issue.location);
+            issue
+        })
+        .collect();
+    if matches!(platform, Platform::GitHub | Platform::Gitea) {
+        match actionlint::check(filename, content) {
+            None => {}
+            Some(Ok(found)) => issues.extend(found.into_iter().filter(|issue| {
+                // Gitea runners are all self-hosted, so any runner label can be valid
+                platform == Platform::GitHub || !issue.message.ends_with("[runner-label]")
+            })),
+            Some(Err(e)) => issues.push(warning("", format!("{:#}", e))),
+        }
+    }
+    issues.sort_by_key(|issue| (issue.severity, issue.line));
+    issues
+}
+
+/// Find the line of a dotted location like `jobs.test.needs` in YAML text
+///
+/// Each key is searched for below the previous one, ignoring sequence
+/// indices, so this is a best guess for block-style YAML.
+fn line_of(content: &str, location: &str) -> Option<usize> {
+    if location.is_empty() {
+        return None;
+    }
+    let lines: Vec<&str> = content.lines().collect();
+    let mut start = 0;
+    let mut found = None;
+    for key in location.split('.') {
+        let key = key.split('[').next().unwrap_or(key);
+        let offset = lines[start..].iter().position(|line| {
+            let line = line.trim_start().trim_start_matches("- ");
+            line.strip_prefix(key)
+                .or_else(|| line.strip_prefix(&format!("\"{}\"", key)))
+                .or_else(|| line.strip_prefix(&format!("'{}'", key)))
+                .is_some_and(|rest| rest.starts_with(':'))
+        })?;
+        found = Some(start + offset);
+        start += offset + 1;
+    }
+    found.map(|index| index + 1)
+}
+
+/// Platform a CI file belongs to, judging by its path
+pub fn platform_for_path(path: &Path) -> Option<Platform> {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let name = path.rsplit('/').next().unwrap_or_default();
+    if path.contains(".github/workflows/") {
+        Some(Platform::GitHub)
+    } else if path.contains(".gitea/workflows/") {
+        Some(Platform::Gitea)
+    } else if path.contains(".circleci/") {
+        Some(Platform::CircleCI)
+    } else if name.starts_with(".gitlab-ci") {
+        Some(Platform::GitLab)
+    } else if name.starts_with("Jenkinsfile") {
+        Some(Platform::Jenkins)
+    } else {
+        None
+    }
+}
+
+/// CI files in `dir` that can be linted, sorted by path
+pub fn find_ci_files(dir: &Path) -> Vec<(PathBuf, Platform)> {
+    let is_yaml = |path: &Path| {
+        matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("yml" | "yaml")
+        )
+    };
+    let mut files = Vec::new();
+    for subdir in [".github/workflows", ".gitea/workflows", ".circleci", "."] {
+        let Ok(entries) = std::fs::read_dir(dir.join(subdir)) else {
+            continue;
+        };
+        for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+            if !is_yaml(&path) {
+                continue;
+            }
+            match platform_for_path(&path) {
+                Some(Platform::Jenkins) | None => {}
+                Some(platform) => files.push((path, platform)),
+            }
+        }
+    }
+    files.sort_by(|(a, _), (b, _)| a.cmp(b));
+    files
+}
+
+fn error(location: impl Into<String>, message: impl Into<String>) -> Issue {
+    Issue {
+        severity: Severity::Error,
+        location: location.into(),
+        message: message.into(),
+        line: None,
+    }
+}
+
+fn warning(location: impl Into<String>, message: impl Into<String>) -> Issue {
+    Issue {
+        severity: Severity::Warning,
+        location: location.into(),
+        message: message.into(),
+        line: None,
+    }
+}
+
+/// Join a parent location and a key
+fn at(location: &str, key: &str) -> String {
+    if location.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", location, key)
+    }
+}
+
+/// Report keys of `map` that aren't in `known`
+fn check_keys(map: &Mapping, known: &[&str], location: &str, issues: &mut Vec<Issue>) {
+    for key in map.keys() {
+        match key.as_str() {
+            Some(key) if known.contains(&key) => {}
+            Some(key) => issues.push(error(at(location, key), "unknown key")),
+            None => issues.push(error(location, "keys must be strings")),
+        }
+    }
+}
+
+/// The string entries of a key that may hold one string or a list of them
+fn string_list(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::String(value)) => vec![value.clone()],
+        Some(Value::Sequence(values)) => values
+            .iter()
+            .filter_map(|value| value.as_str().map(str::to_string))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Report references to jobs that don't exist, including a job needing itself
+fn check_references(
+    job: &str,
+    references: &[String],
+    jobs: &[String],
+    location: &str,
+    issues: &mut Vec<Issue>,
+) {
+    for reference in references {
+        if reference == job {
+            issues.push(error(location, format!("`{}` can't depend on itself", job)));
+        } else if !jobs.contains(reference) {
+            issues.push(error(location, format!("unknown job `{}`", reference)));
+        }
+    }
+}
+
+/// Report jobs whose dependencies loop back to them; `jobs` is where the jobs are defined
+fn check_cycles(dependencies: &[(String, Vec<String>)], jobs: &str, issues: &mut Vec<Issue>) {
+    fn reaches(
+        dependencies: &[(String, Vec<String>)],
+        from: &str,
+        target: &str,
+        seen: &mut Vec<String>,
+    ) -> bool {
+        let Some((_, needs)) = dependencies.iter().find(|(id, _)| id == from) else {
+            return false;
+        };
+        needs.iter().any(|need| {
+            if need == target {
+                return true;
+            }
+            if seen.contains(need) {
+                return false;
+            }
+            seen.push(need.clone());
+            reaches(dependencies, need, target, seen)
+        })
+    }
+
+    for (id, needs) in dependencies {
+        let cyclic = needs
+            .iter()
+            .filter(|need| *need != id)
+            .any(|need| reaches(dependencies, need, id, &mut Vec::new()));
+        if cyclic {
+            issues.push(error(
+                at(jobs, id),
+                "dependencies form a cycle, so the job can never start",
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::registry::build_registry;
+
+    #[test]
+    fn test_generated_configs_lint_clean() {
+        let registry = build_registry();
+        for preset in registry.all() {
+            if preset.is_decorator() {
+                continue;
+            }
+            let config = preset.default_config(true);
+            for platform in Platform::all() {
+                if platform == Platform::Jenkins {
+                    continue;
+                }
+                // Some presets refuse platforms they can't support
+                let Ok(pipeline) = preset.build(&config, platform, "stable") else {
+                    continue;
+                };
+                let issues = lint(platform, &pipeline.render().unwrap());
+                assert!(
+                    issues.is_empty(),
+                    "{} on {:?}: {:?}",
+                    preset.preset_id(),
+                    platform,
+                    issues
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_check_generated_finds_lines() {
+        let content = "name: CI\non:\n  push:\njobs:\n  test:\n    runs-on: ubuntu-latest\n    needs: [lint]\n    steps:\n      - run: make\n";
+        let issues = check_generated(
+            Platform::GitHub,
+            Path::new(".github/workflows/ci.yml"),
+            content,
+        );
+        let needs = issues
+            .iter()
+            .find(|issue| issue.location == "jobs.test.needs")
+            .unwrap();
+        assert_eq!(needs.line, Some(7));
+        assert!(
+            check_generated(Platform::Jenkins, Path::new("Jenkinsfile"), "pipeline {}").is_empty()
+        );
+    }
+
+    #[test]
+    fn test_line_of() {
+        let content = "on: push\njobs:\n  build:\n    steps:\n      - run: make\n  test:\n    needs: [lint]\n";
+        assert_eq!(line_of(content, "jobs.test.needs"), Some(7));
+        assert_eq!(line_of(content, "jobs.build.steps[0].run"), Some(5));
+        assert_eq!(line_of(content, "jobs.deploy"), None);
+        assert_eq!(line_of(content, ""), None);
+    }
+
+    #[test]
+    fn test_platform_for_path() {
+        assert_eq!(
+            platform_for_path(Path::new("./.github/workflows/ci.yml")),
+            Some(Platform::GitHub)
+        );
+        assert_eq!(
+            platform_for_path(Path::new(".gitlab-ci-rust.yml")),
+            Some(Platform::GitLab)
+        );
+        assert_eq!(platform_for_path(Path::new("docker-compose.yml")), None);
+    }
+}
@@ -0,0 +1,470 @@
+//! Checking configs against the platforms' JSON schemas
+//!
+//! The GitHub Actions and GitLab CI schemas are bundled, so validation works
+//! offline. They're checked with a small JSON Schema (draft-07) validator
+//! reading the schema through `serde_yaml`, covering the keywords the
+//! bundled schemas use: `$ref`, `type`, `enum`, `const`, `properties`,
+//! `patternProperties`, `additionalProperties`, `propertyNames`, `required`,
+//! `minProperties`, `items`, `minItems`, `maxItems`, `minLength`, `pattern`,
+//! `minimum`, `maximum`, `allOf`, `anyOf`, `oneOf` and `not`.
+
+use super::{at, error, Issue};
+use crate::editor::state::Platform;
+use regex::Regex;
+use serde_yaml::{Mapping, Value};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+const GITHUB_WORKFLOW: &str = include_str!("schemas/github-workflow.json");
+const GITLAB_CI: &str = include_str!("schemas/gitlab-ci.json");
+
+/// A parsed JSON Schema document
+pub struct Schema {
+    root: Value,
+    patterns: Mutex<HashMap<String, Option<Regex>>>,
+}
+
+impl Schema {
+    pub fn parse(text: &str) -> crate::error::Result<Self> {
+        Ok(Self {
+            root: serde_yaml::from_str(text)?,
+            patterns: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// The bundled schema for `platform`, if there is one
+    ///
+    /// Gitea reads GitHub's workflow syntax.
+    pub fn for_platform(platform: Platform) -> Option<&'static Schema> {
+        static GITHUB: OnceLock<Schema> = OnceLock::new();
+        static GITLAB: OnceLock<Schema> = OnceLock::new();
+        match platform {
+            Platform::GitHub | Platform::Gitea => {
+                Some(GITHUB.get_or_init(|| Schema::parse(GITHUB_WORKFLOW).expect("bundled schema")))
+            }
+            Platform::GitLab => {
+                Some(GITLAB.get_or_init(|| Schema::parse(GITLAB_CI).expect("bundled schema")))
+            }
+            Platform::CircleCI | Platform::Jenkins => None,
+        }
+    }
+
+    /// Every place `instance` doesn't match the schema
+    pub fn validate(&self, instance: &Value) -> Vec<Issue> {
+        let mut issues = Vec::new();
+        self.check(&self.root, instance, "", &mut issues);
+        issues
+    }
+
+    fn check(&self, schema: &Value, instance: &Value, location: &str, issues: &mut Vec<Issue>) {
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => {
+                issues.push(error(location, "not allowed here"));
+                return;
+            }
+            Value::Mapping(schema) => schema,
+            _ => return,
+        };
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            match self.resolve(reference) {
+                Some(target) => self.check(target, instance, location, issues),
+                None => issues.push(error(location, format!("unknown schema `{}`", reference))),
+            }
+            return;
+        }
+
+        if let Some(expected) = schema.get("type") {
+            let allowed: Vec<&str> = match expected {
+                Value::String(name) => vec![name.as_str()],
+                Value::Sequence(names) => names.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
+            };
+            if !allowed.iter().any(|name| has_type(instance, name)) {
+                issues.push(error(
+                    location,
+                    format!(
+                        "expected {}, found {}",
+                        allowed.join(" or "),
+                        type_name(instance)
+                    ),
+                ));
+                return;
+            }
+        }
+
+        if let Some(Value::Sequence(values)) = schema.get("enum") {
+            if !values.iter().any(|value| same(value, instance)) {
+                let values: Vec<String> = values.iter().map(display).collect();
+                issues.push(error(
+                    location,
+                    format!("{} isn't one of {}", display(instance), values.join(", ")),
+                ));
+            }
+        }
+        if let Some(value) = schema.get("const") {
+            if !same(value, instance) {
+                issues.push(error(location, format!("must be {}", display(value))));
+            }
+        }
+
+        match instance {
+            Value::String(text) => self.check_string(schema, text, location, issues),
+            Value::Number(number) => check_number(schema, number.as_f64(), location, issues),
+            Value::Sequence(items) => self.check_array(schema, items, location, issues),
+            Value::Mapping(map) => self.check_object(schema, map, location, issues),
+            _ => {}
+        }
+
+        if let Some(Value::Sequence(schemas)) = schema.get("allOf") {
+            for schema in schemas {
+                self.check(schema, instance, location, issues);
+            }
+        }
+        if let Some(Value::Sequence(schemas)) = schema.get("anyOf") {
+            self.check_alternatives(schemas, instance, location, false, issues);
+        }
+        if let Some(Value::Sequence(schemas)) = schema.get("oneOf") {
+            self.check_alternatives(schemas, instance, location, true, issues);
+        }
+        if let Some(schema) = schema.get("not") {
+            let mut nested = Vec::new();
+            self.check(schema, instance, location, &mut nested);
+            if nested.is_empty() {
+                issues.push(error(location, "matches a form that isn't allowed"));
+            }
+        }
+    }
+
+    fn check_string(&self, schema: &Mapping, text: &str, location: &str, issues: &mut Vec<Issue>) {
+        if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+            if (text.chars().count() as u64) < min {
+                issues.push(error(
+                    location,
+                    format!("must be at least {} characters", min),
+                ));
+            }
+        }
+        if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+            let mut patterns = self.patterns.lock().unwrap_or_else(|e| e.into_inner());
+            let regex = patterns
+                .entry(pattern.to_string())
+                .or_insert_with(|| Regex::new(pattern).ok());
+            if regex.as_ref().is_some_and(|regex| !regex.is_match(text)) {
+                // Patterns are hard to read, so prefer saying what they stand for
+                let message = match schema.get("description").and_then(Value::as_str) {
+                    Some(description) => format!("`{}` isn't {}", text, description),
+                    None => format!("`{}` doesn't match `{}`", text, pattern),
+                };
+                issues.push(error(location, message));
+            }
+        }
+    }
+
+    fn check_array(
+        &self,
+        schema: &Mapping,
+        items: &[Value],
+        location: &str,
+        issues: &mut Vec<Issue>,
+    ) {
+        if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+            if (items.len() as u64) < min {
+                issues.push(error(location, format!("needs at least {} item(s)", min)));
+            }
+        }
+        if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+            if items.len() as u64 > max {
+                issues.push(error(location, format!("allows at most {} item(s)", max)));
+            }
+        }
+        if let Some(item_schema) = schema.get("items") {
+            for (index, item) in items.iter().enumerate() {
+                let location = format!("{}[{}]", location, index);
+                self.check(item_schema, item, &location, issues);
+            }
+        }
+    }
+
+    fn check_object(
+        &self,
+        schema: &Mapping,
+        map: &Mapping,
+        location: &str,
+        issues: &mut Vec<Issue>,
+    ) {
+        if let Some(Value::Sequence(required)) = schema.get("required") {
+            for key in required.iter().filter_map(Value::as_str) {
+                if !map.contains_key(key) {
+                    issues.push(error(at(location, key), "missing"));
+                }
+            }
+        }
+        if let Some(min) = schema.get("minProperties").and_then(Value::as_u64) {
+            if (map.len() as u64) < min {
+                issues.push(error(location, format!("needs at least {} key(s)", min)));
+            }
+        }
+
+        let properties = schema.get("properties").and_then(Value::as_mapping);
+        let pattern_properties = schema.get("patternProperties").and_then(Value::as_mapping);
+        for (key, value) in map {
+            let key = match key {
+                Value::String(key) => key.clone(),
+                other => display(other),
+            };
+            let location = at(location, &key);
+            if let Some(names) = schema.get("propertyNames") {
+                let mut nested = Vec::new();
+                self.check(names, &Value::String(key.clone()), &location, &mut nested);
+                if !nested.is_empty() {
+                    issues.extend(nested);
+                    continue;
+                }
+            }
+            let mut matched = false;
+            if let Some(property) = properties.and_then(|properties| properties.get(key.as_str())) {
+                matched = true;
+                self.check(property, value, &location, issues);
+            }
+            for (pattern, property) in pattern_properties.into_iter().flatten() {
+                let Some(pattern) = pattern.as_str() else {
+                    continue;
+                };
+                if self.matches(pattern, &key) {
+                    matched = true;
+                    self.check(property, value, &location, issues);
+                }
+            }
+            if matched {
+                continue;
+            }
+            match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => issues.push(error(location, "unknown key")),
+                Some(additional) => self.check(additional, value, &location, issues),
+                None => {}
+            }
+        }
+    }
+
+    /// `anyOf` and `oneOf`; when nothing matches, report the closest alternative
+    fn check_alternatives(
+        &self,
+        schemas: &[Value],
+        instance: &Value,
+        location: &str,
+        exactly_one: bool,
+        issues: &mut Vec<Issue>,
+    ) {
+        let results: Vec<Vec<Issue>> = schemas
+            .iter()
+            .map(|schema| {
+                let mut nested = Vec::new();
+                self.check(schema, instance, location, &mut nested);
+                nested
+            })
+            .collect();
+        let matching = results.iter().filter(|nested| nested.is_empty()).count();
+        if matching == 1 || (matching > 1 && !exactly_one) {
+            return;
+        }
+        if matching > 1 {
+            issues.push(error(
+                location,
+                "matches more than one of the allowed forms",
+            ));
+            return;
+        }
+
+        // The alternative that got furthest into the value is most likely the one intended
+        let depth = |nested: &Vec<Issue>| {
+            nested
+                .iter()
+                .map(|issue| issue.location.len())
+                .max()
+                .unwrap_or(0)
+        };
+        let deepest = results.iter().map(depth).max().unwrap_or(0);
+        let closest: Vec<&Vec<Issue>> = results
+            .iter()
+            .filter(|nested| depth(nested) == deepest)
+            .collect();
+        if deepest == location.len() && closest.len() > 1 {
+            let forms: Vec<String> = closest
+                .iter()
+                .flat_map(|nested| nested.iter().map(|issue| issue.message.clone()))
+                .collect();
+            issues.push(error(
+                location,
+                format!("doesn't match any allowed form ({})", forms.join("; ")),
+            ));
+        } else if let Some(nested) = closest.into_iter().min_by_key(|nested| nested.len()) {
+            issues.extend(nested.iter().cloned());
+        }
+    }
+
+    /// Look up a `#/definitions/...` style JSON pointer
+    fn resolve(&self, reference: &str) -> Option<&Value> {
+        let pointer = reference.strip_prefix('#')?;
+        let mut target = &self.root;
+        for part in pointer.split('/').filter(|part| !part.is_empty()) {
+            let part = part.replace("~1", "/").replace("~0", "~");
+            target = target.as_mapping()?.get(part.as_str())?;
+        }
+        Some(target)
+    }
+
+    fn matches(&self, pattern: &str, text: &str) -> bool {
+        let mut patterns = self.patterns.lock().unwrap_or_else(|e| e.into_inner());
+        patterns
+            .entry(pattern.to_string())
+            .or_insert_with(|| Regex::new(pattern).ok())
+            .as_ref()
+            .is_some_and(|regex| regex.is_match(text))
+    }
+}
+
+fn check_number(schema: &Mapping, number: Option<f64>, location: &str, issues: &mut Vec<Issue>) {
+    let Some(number) = number else {
+        return;
+    };
+    if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+        if number < min {
+            issues.push(error(location, format!("must be at least {}", min)));
+        }
+    }
+    if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+        if number > max {
+            issues.push(error(location, format!("must be at most {}", max)));
+        }
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match (name, value) {
+        ("null", Value::Null)
+        | ("boolean", Value::Bool(_))
+        | ("number", Value::Number(_))
+        | ("string", Value::String(_))
+        | ("array", Value::Sequence(_))
+        | ("object", Value::Mapping(_)) => true,
+        ("integer", Value::Number(number)) => {
+            number.is_i64() || number.is_u64() || number.as_f64().is_some_and(|f| f.fract() == 0.0)
+        }
+        (_, Value::Tagged(tagged)) => has_type(&tagged.value, name),
+        _ => false,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Sequence(_) => "array",
+        Value::Mapping(_) => "object",
+        Value::Tagged(tagged) => type_name(&tagged.value),
+    }
+}
+
+/// JSON equality, where 1 and 1.0 are the same number
+fn same(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        _ => a == b,
+    }
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(text) => format!("`{}`", text),
+        other => serde_yaml::to_string(other)
+            .map(|text| text.trim().to_string())
+            .unwrap_or_default(),
+    }
+}
+
+/// Check YAML written for `platform` against its bundled schema
+///
+/// Returns `None` for platforms without one.
+pub fn validate(platform: Platform, content: &str) -> Option<Vec<Issue>> {
+    let schema = Schema::for_platform(platform)?;
+    Some(match serde_yaml::from_str::<Value>(content) {
+        Ok(instance) => schema.validate(&instance),
+        Err(e) => vec![error("", format!("invalid YAML: {}", e))],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::registry::build_registry;
+
+    #[test]
+    fn test_generated_configs_match_schemas() {
+        let registry = build_registry();
+        for preset in registry.all() {
+            if preset.is_decorator() {
+                continue;
+            }
+            let config = preset.default_config(true);
+            for platform in [Platform::GitHub, Platform::Gitea, Platform::GitLab] {
+                let Ok(pipeline) = preset.build(&config, platform, "stable") else {
+                    continue;
+                };
+                let issues = validate(platform, &pipeline.render().unwrap()).unwrap();
+                assert!(
+                    issues.is_empty(),
+                    "{} on {:?}: {:?}",
+                    preset.preset_id(),
+                    platform,
+                    issues
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_reports_schema_violations() {
+        let workflow = r#"
+on: push
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    timeout-minutes: soon
+    steps:
+      - name: Build
+        shell: bash
+      - run: cargo test
+        with: [1]
+  rust/lint:
+    runs-on: ubuntu-latest
+    steps:
+      - run: cargo clippy
+"#;
+        let issues: Vec<String> = validate(Platform::GitHub, workflow)
+            .unwrap()
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect();
+        let expected = [
+            "jobs.test.timeout-minutes: doesn't match any allowed form (expected number, found string; `soon` isn't a `${{ }}` expression)",
+            "jobs.test.steps[0].uses: missing",
+            "jobs.rust/lint: `rust/lint` isn't a valid job ID (letters, digits, `-` and `_`)",
+        ];
+        for message in expected {
+            assert!(issues.iter().any(|issue| issue == message), "{:?}", issues);
+        }
+        assert!(issues
+            .iter()
+            .any(|issue| issue.starts_with("jobs.test.steps[1].with")));
+
+        let pipeline = "test:\n  stage: test\n  script: make test\n  when: sometimes\n";
+        let issues = validate(Platform::GitLab, pipeline).unwrap();
+        assert_eq!(issues.len(), 1, "{:?}", issues);
+        assert_eq!(issues[0].location, "test.when");
+        assert!(validate(Platform::CircleCI, pipeline).is_none());
+    }
+}
@@ -0,0 +1,286 @@
+//! `cci run`: running the generated pipeline on this machine
+//!
+//! GitHub and Gitea workflows run through [act](https://nektosact.com), GitLab
+//! jobs through `gitlab-runner exec`. Both pull the images the hosted runners
+//! use, so a job that passes here is likely to pass in CI. [`native`] runs
+//! the commands itself, for when neither is installed.
+
+pub mod native;
+
+use crate::editor::state::Platform;
+use crate::error::{config_error, Result};
+use crate::platforms::github::models::job_id;
+use crate::platforms::gitlab::models::GitLabCI;
+use anyhow::{bail, Context};
+use serde_yaml::Value;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// A job in a generated pipeline file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Job {
+    pub id: String,
+    /// File the job is defined in, relative to the project
+    pub file: PathBuf,
+    /// GitLab stage the job runs in
+    pub stage: Option<String>,
+}
+
+/// Jobs in the generated pipeline files, in the order they'd run
+///
+/// `outputs` are the generated files for `platform`; files that aren't
+/// pipelines, like `dependabot.yml`, are skipped.
+pub fn jobs(platform: Platform, outputs: &[(PathBuf, String)]) -> Result<Vec<Job>> {
+    let mut jobs = Vec::new();
+    for (file, content) in outputs {
+        if crate::lint::platform_for_path(file) != Some(platform) {
+            continue;
+        }
+        match platform {
+            Platform::GitHub | Platform::Gitea => {
+                let workflow: Value = serde_yaml::from_str(content)?;
+                let Some(Value::Mapping(workflow_jobs)) = workflow.get("jobs") else {
+                    continue;
+                };
+                for id in workflow_jobs.keys().filter_map(Value::as_str) {
+                    jobs.push(Job {
+                        id: id.to_string(),
+                        file: file.clone(),
+                        stage: None,
+                    });
+                }
+            }
+            Platform::GitLab => {
+                let ci: GitLabCI = serde_yaml::from_str(content)
+                    .with_context(|| format!("Failed to read {}", file.display()))?;
+                let stages = ci.stages.unwrap_or_default();
+                let mut file_jobs: Vec<(usize, Job)> = ci
+                    .jobs
+                    .into_iter()
+                    .filter(|(id, _)| !id.starts_with('.'))
+                    .map(|(id, job)| {
+                        let order = stages
+                            .iter()
+                            .position(|stage| *stage == job.stage)
+                            .unwrap_or(stages.len());
+                        let job = Job {
+                            id,
+                            file: file.clone(),
+                            stage: Some(job.stage),
+                        };
+                        (order, job)
+                    })
+                    .collect();
+                file_jobs.sort_by_key(|(order, _)| *order);
+                jobs.extend(file_jobs.into_iter().map(|(_, job)| job));
+            }
+            Platform::CircleCI | Platform::Jenkins => return Err(unsupported(platform)),
+        }
+    }
+    Ok(jobs)
+}
+
+/// The jobs `target` names, or all of them
+///
+/// `rust/test` and `rust-test` name the same job, since GitHub job IDs
+/// can't contain slashes.
+pub fn select<'a>(jobs: &'a [Job], target: Option<&str>) -> Result<Vec<&'a Job>> {
+    let Some(target) = target else {
+        return Ok(jobs.iter().collect());
+    };
+    let wanted = job_id(target);
+    let selected: Vec<&Job> = jobs
+        .iter()
+        .filter(|job| job_id(&job.id).eq_ignore_ascii_case(&wanted))
+        .collect();
+    if selected.is_empty() {
+        let known: Vec<&str> = jobs.iter().map(|job| job.id.as_str()).collect();
+        bail!(
+            "No job named `{}`; the pipeline has {}",
+            target,
+            known.join(", ")
+        );
+    }
+    Ok(selected)
+}
+
+/// One run of `act` or `gitlab-runner`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invocation {
+    /// What the run covers: a job, or a whole workflow file
+    pub label: String,
+    pub program: &'static str,
+    pub args: Vec<String>,
+}
+
+impl fmt::Display for Invocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.program)?;
+        for arg in &self.args {
+            if arg.contains(|c: char| c.is_whitespace() || c == '\'') {
+                write!(f, " '{}'", arg.replace('\'', "'\\''"))?;
+            } else {
+                write!(f, " {}", arg)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The runs that execute `selected` jobs
+///
+/// GitHub workflows are read from `workflow_dir`, where the generated files
+/// were written under their own names. Without a `job`, each workflow runs
+/// as a whole so act can follow `needs`; GitLab jobs always run one at a time
+/// because `gitlab-runner exec` can't run a pipeline. `extra_args` go to
+/// every run.
+pub fn invocations(
+    platform: Platform,
+    selected: &[&Job],
+    whole_files: bool,
+    workflow_dir: &Path,
+    extra_args: &[String],
+) -> Result<Vec<Invocation>> {
+    let mut runs = Vec::new();
+    match platform {
+        Platform::GitHub | Platform::Gitea => {
+            for job in selected {
+                let file_name = job.file.file_name().unwrap_or(job.file.as_os_str());
+                let workflow = workflow_dir.join(file_name).to_string_lossy().to_string();
+                let mut args = vec!["-W".to_string(), workflow];
+                let label = if whole_files {
+                    if runs
+                        .iter()
+                        .any(|run: &Invocation| run.label == job.file.display().to_string())
+                    {
+                        continue;
+                    }
+                    job.file.display().to_string()
+                } else {
+                    args.extend(["-j".to_string(), job.id.clone()]);
+                    job.id.clone()
+                };
+                args.extend(extra_args.iter().cloned());
+                runs.push(Invocation {
+                    label,
+                    program: "act",
+                    args,
+                });
+            }
+        }
+        Platform::GitLab => {
+            for job in selected {
+                let mut args = vec!["exec".to_string(), "docker".to_string(), job.id.clone()];
+                args.extend(extra_args.iter().cloned());
+                runs.push(Invocation {
+                    label: job.id.clone(),
+                    program: "gitlab-runner",
+                    args,
+                });
+            }
+        }
+        Platform::CircleCI | Platform::Jenkins => return Err(unsupported(platform)),
+    }
+    Ok(runs)
+}
+
+/// Run `invocation` in `dir` with its output going straight to the terminal
+///
+/// Returns whether it succeeded.
+pub fn execute(invocation: &Invocation, dir: &Path) -> Result<bool> {
+    let status = std::process::Command::new(invocation.program)
+        .args(&invocation.args)
+        .current_dir(dir)
+        .status();
+    match status {
+        Ok(status) => Ok(status.success()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(config_error(format!(
+            "{} isn't installed; {}",
+            invocation.program,
+            match invocation.program {
+                "act" => "see https://nektosact.com/installation",
+                _ => "see https://docs.gitlab.com/runner/install",
+            }
+        ))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn unsupported(platform: Platform) -> anyhow::Error {
+    config_error(format!(
+        "{} pipelines can't be run locally; cci run supports GitHub, Gitea and GitLab",
+        platform.name()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(id: &str, file: &str) -> Job {
+        Job {
+            id: id.to_string(),
+            file: PathBuf::from(file),
+            stage: None,
+        }
+    }
+
+    #[test]
+    fn test_gitlab_jobs_in_stage_order() {
+        let content = "stages: [lint, test]\n\
+                       rust/test:\n  stage: test\n  script: [cargo test]\n\
+                       rust/lint:\n  stage: lint\n  script: [cargo clippy]\n";
+        let jobs = jobs(
+            Platform::GitLab,
+            &[
+                (PathBuf::from(".gitlab-ci.yml"), content.to_string()),
+                (PathBuf::from("renovate.json"), "{}".to_string()),
+            ],
+        )
+        .unwrap();
+        let ids: Vec<&str> = jobs.iter().map(|job| job.id.as_str()).collect();
+        assert_eq!(ids, vec!["rust/lint", "rust/test"]);
+    }
+
+    #[test]
+    fn test_select_maps_job_names() {
+        let jobs = [
+            job("rust-test", ".github/workflows/rust.yml"),
+            job("rust-lint", ".github/workflows/rust.yml"),
+        ];
+        let selected = select(&jobs, Some("rust/test")).unwrap();
+        assert_eq!(selected, vec![&jobs[0]]);
+        assert_eq!(select(&jobs, None).unwrap().len(), 2);
+        let e = select(&jobs, Some("deploy")).unwrap_err().to_string();
+        assert!(e.contains("rust-test, rust-lint"), "{}", e);
+    }
+
+    #[test]
+    fn test_invocations() {
+        let jobs = [
+            job("rust-test", ".github/workflows/rust.yml"),
+            job("rust-lint", ".github/workflows/rust.yml"),
+        ];
+        let selected: Vec<&Job> = jobs.iter().collect();
+        let dir = Path::new("/tmp/cci");
+
+        let whole = invocations(Platform::GitHub, &selected, true, dir, &[]).unwrap();
+        assert_eq!(whole.len(), 1);
+        assert_eq!(whole[0].to_string(), "act -W /tmp/cci/rust.yml");
+
+        let extra = [
+            "--container-architecture".to_string(),
+            "linux/amd64".to_string(),
+        ];
+        let one = invocations(Platform::GitHub, &selected[..1], false, dir, &extra).unwrap();
+        assert_eq!(
+            one[0].to_string(),
+            "act -W /tmp/cci/rust.yml -j rust-test --container-architecture linux/amd64"
+        );
+
+        let gitlab = [job("rust/test", ".gitlab-ci.yml")];
+        let runs = invocations(Platform::GitLab, &[&gitlab[0]], true, dir, &[]).unwrap();
+        assert_eq!(runs[0].to_string(), "gitlab-runner exec docker rust/test");
+        assert!(invocations(Platform::Jenkins, &[], true, dir, &[]).is_err());
+    }
+}
@@ -0,0 +1,558 @@
+//! `cci run --native`: running the [`Pipeline`] model's commands without act or gitlab-runner
+//!
+//! Jobs come from the GitLab pipeline, whose jobs are plain scripts in
+//! container images, so they translate directly into shell commands. A job's
+//! steps run as one `bash -e` (or `sh -e`) script in the project directory, or
+//! with `docker exec` in a container started from the job's image, so
+//! environment changes carry from step to step. GitHub Actions steps can't run
+//! and are skipped.
+//!
+//! Nothing runs on this machine itself unless [`Options::host`] allows it, and
+//! steps that publish something, or install tools for the whole machine, are
+//! skipped unless [`Options::all_steps`] asks for them.
+
+use crate::error::Result;
+use crate::models::adapters::{GitLabAdapter, PlatformAdapter};
+use crate::models::{Job, Pipeline, Step};
+use crate::platforms::gitlab::models::{GitLabCI, GitLabJob, GitLabRule};
+use anyhow::{bail, Context};
+use colored::Colorize;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Runs the command in `$1` with bash when there is one, like GitLab's runner, and sh otherwise
+const SHELL: &str =
+    "if command -v bash >/dev/null 2>&1; then exec bash -ec \"$1\"; fi; exec sh -ec \"$1\"";
+
+/// Where steps run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// Project directory the steps run in
+    pub dir: PathBuf,
+    /// Run each job in a container from its image, instead of on this machine
+    pub docker: bool,
+    /// Let jobs run on this machine: every job without `docker`, the ones
+    /// without an image with it
+    pub host: bool,
+    /// Also run the steps [`step_hazard`] warns about
+    pub all_steps: bool,
+    /// Hide the steps' output and progress, only returning the reports
+    pub quiet: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Passed,
+    Failed {
+        /// The step that failed
+        step: String,
+    },
+    Skipped {
+        reason: String,
+    },
+}
+
+/// How one job went
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobReport {
+    pub id: String,
+    pub outcome: Outcome,
+    pub duration: Duration,
+    /// Steps that couldn't run and other things worth knowing
+    pub notes: Vec<String>,
+}
+
+/// The jobs a branch push would run, in dependency order, and the jobs it wouldn't
+///
+/// Jobs that only run for tags, on a schedule, by hand, after a failure or to
+/// deploy somewhere are reported as skipped unless one of `targets` names
+/// them, since running a release or a deploy by accident would be hard to
+/// undo. The same goes for every job of a pipeline whose workflow rules only
+/// match such events.
+pub fn local_pipeline(configs: &[GitLabCI], targets: &[String]) -> (Pipeline, Vec<JobReport>) {
+    let mut pipeline = Pipeline::default();
+    let mut skipped = Vec::new();
+    for ci in configs {
+        let mut ci = ci.clone();
+        let pipeline_runs = ci
+            .workflow
+            .as_ref()
+            .is_none_or(|workflow| workflow.rules.is_empty() || runs_on_push(&workflow.rules));
+        ci.jobs.retain(|id, job| {
+            if targets.contains(id) || (pipeline_runs && !is_conditional(job)) {
+                return true;
+            }
+            skipped.push(JobReport {
+                id: id.clone(),
+                outcome: Outcome::Skipped {
+                    reason: "only runs for some events; name it to run it anyway".to_string(),
+                },
+                duration: Duration::ZERO,
+                notes: Vec::new(),
+            });
+            false
+        });
+        let lifted = GitLabAdapter::lift(&ci, &mut Vec::new());
+        pipeline.env.extend(lifted.env);
+        pipeline.jobs.extend(lifted.jobs);
+    }
+    pipeline.sort_jobs();
+    (pipeline, skipped)
+}
+
+fn is_conditional(job: &GitLabJob) -> bool {
+    let special_refs = ["tags", "schedules", "web", "triggers", "api", "pipelines"];
+    job.environment.is_some()
+        || job.when.as_deref().is_some_and(|when| when != "on_success")
+        || job.rules.as_ref().is_some_and(|rules| !runs_on_push(rules))
+        || job.only.as_ref().is_some_and(|only| {
+            only.refs().is_some_and(|refs| {
+                refs.iter()
+                    .all(|reference| special_refs.contains(&reference.as_str()))
+            })
+        })
+}
+
+/// Whether any rule can match an ordinary branch push
+fn runs_on_push(rules: &[GitLabRule]) -> bool {
+    rules.iter().any(|rule| {
+        let when = rule.when.as_deref().unwrap_or("on_success");
+        let condition = rule.if_condition.as_deref().unwrap_or_default();
+        !matches!(when, "never" | "manual" | "delayed")
+            && !condition.contains("$CI_COMMIT_TAG")
+            && !condition.contains("schedule")
+    })
+}
+
+/// Run the jobs in `pipeline`, or just `targets` when there are any
+///
+/// A job whose `needs` didn't pass is skipped. Output streams to the
+/// terminal unless `options.quiet` is set.
+pub fn run(pipeline: &Pipeline, targets: &[String], options: &Options) -> Result<Vec<JobReport>> {
+    let mut not_passed: HashSet<&str> = HashSet::new();
+    let mut reports = Vec::new();
+    for job in &pipeline.jobs {
+        if !targets.is_empty() && !targets.contains(&job.id) {
+            continue;
+        }
+        let report = match job
+            .needs
+            .iter()
+            .find(|need| not_passed.contains(need.as_str()))
+        {
+            Some(need) => JobReport {
+                id: job.id.clone(),
+                outcome: Outcome::Skipped {
+                    reason: format!("`{}` didn't pass", need),
+                },
+                duration: Duration::ZERO,
+                notes: Vec::new(),
+            },
+            None => run_job(pipeline, job, options)?,
+        };
+        if !options.quiet {
+            print_outcome(&report);
+        }
+        if report.outcome != Outcome::Passed {
+            not_passed.insert(&job.id);
+        }
+        reports.push(report);
+    }
+    Ok(reports)
+}
+
+fn run_job(pipeline: &Pipeline, job: &Job, options: &Options) -> Result<JobReport> {
+    let started = Instant::now();
+    let mut notes = Vec::new();
+    let mut env = pipeline.env.clone();
+    env.extend(job.env.clone());
+    env.insert("CI".to_string(), "true".to_string());
+
+    if !options.quiet {
+        println!("\n{} {}", "Running".cyan().bold(), job.id.yellow());
+    }
+    let container = match (&job.image, options.docker) {
+        (Some(image), true) => match start_container(image, options) {
+            Ok(container) => Some(container),
+            Err(e) => {
+                return Ok(JobReport {
+                    id: job.id.clone(),
+                    outcome: Outcome::Failed {
+                        step: format!("start {}: {:#}", image, e),
+                    },
+                    duration: started.elapsed(),
+                    notes,
+                })
+            }
+        },
+        (None, true) if options.host => {
+            notes.push("no image, so it ran on this machine".to_string());
+            None
+        }
+        (_, docker) if !options.host => {
+            let reason = match docker {
+                true => "has no image, so it would run on this machine; pass --host to allow it",
+                false => "would run on this machine; pass --host to allow it, or --docker",
+            };
+            return Ok(JobReport {
+                id: job.id.clone(),
+                outcome: Outcome::Skipped {
+                    reason: reason.to_string(),
+                },
+                duration: Duration::ZERO,
+                notes,
+            });
+        }
+        _ => None,
+    };
+
+    let mut steps = Vec::new();
+    for step in &job.steps {
+        match step {
+            // The project is already here, or mounted into the container
+            Step::Checkout => {}
+            Step::Action { uses, .. } => {
+                notes.push(format!("skipped `{}`: actions can't run natively", uses))
+            }
+            Step::Run { command, .. } => {
+                match step_hazard(command, container.is_none()).filter(|_| !options.all_steps) {
+                    Some(hazard) => notes.push(format!(
+                        "skipped `{}`: it {}; pass --all-steps to run it",
+                        step.label(),
+                        hazard
+                    )),
+                    None => steps.push((step.label(), command.as_str())),
+                }
+            }
+        }
+    }
+
+    // One shell runs every step, as on a real runner, so `source` and `cd`
+    // carry over; it records which step it's on for reporting a failure
+    let progress = match &container {
+        Some(_) => "/tmp/cci-step".to_string(),
+        None => std::env::temp_dir()
+            .join(format!(
+                "cci-step-{}-{}",
+                std::process::id(),
+                RUNS.fetch_add(1, Ordering::Relaxed)
+            ))
+            .to_string_lossy()
+            .to_string(),
+    };
+    let script = job_script(&steps, &progress, !options.quiet);
+    let mut process = match &container {
+        Some(container) => {
+            let mut process = Command::new("docker");
+            process.args(["exec", "-w", "/workspace"]);
+            for (key, value) in &env {
+                process.arg("-e").arg(format!("{}={}", key, value));
+            }
+            process.args([container.as_str(), "sh", "-c", SHELL, "cci", &script]);
+            process
+        }
+        None => {
+            let mut process = Command::new("sh");
+            process
+                .args(["-c", SHELL, "cci", &script])
+                .current_dir(&options.dir)
+                .envs(&env);
+            process
+        }
+    };
+    if options.quiet {
+        process.stdout(Stdio::null()).stderr(Stdio::null());
+    }
+    let status = process
+        .status()
+        .with_context(|| format!("Failed to run job `{}`", job.id))?;
+    let outcome = if status.success() {
+        Outcome::Passed
+    } else {
+        let reached = match &container {
+            Some(container) => Command::new("docker")
+                .args(["exec", container.as_str(), "cat", &progress])
+                .output()
+                .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+                .unwrap_or_default(),
+            None => std::fs::read_to_string(&progress).unwrap_or_default(),
+        };
+        let step = reached
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| steps.get(index))
+            .map_or_else(|| "setup".to_string(), |(label, _)| label.clone());
+        Outcome::Failed { step }
+    };
+    if container.is_none() {
+        let _ = std::fs::remove_file(&progress);
+    }
+
+    if let Some(container) = container {
+        let _ = Command::new("docker")
+            .args(["rm", "-f", &container])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+    Ok(JobReport {
+        id: job.id.clone(),
+        outcome,
+        duration: started.elapsed(),
+        notes,
+    })
+}
+
+/// What a step's `command` would do that shouldn't happen without being asked for, if anything
+///
+/// Publishing reaches past this machine wherever the step runs. Installing a
+/// tool for the whole machine only matters `on_host`; a container is thrown
+/// away afterwards.
+pub fn step_hazard(command: &str, on_host: bool) -> Option<&'static str> {
+    const PUBLISHES: &[&str] = &[
+        "cargo publish",
+        "docker push",
+        "twine upload",
+        "npm publish",
+        "gh release",
+        "codecov",
+        "upload",
+    ];
+    const INSTALLS: &[&str] = &[
+        "| sh",
+        "| bash",
+        "cargo install",
+        "go install",
+        "npm install -g",
+        "sudo ",
+        "apt-get install",
+        "apk add",
+    ];
+    let command = command.to_lowercase();
+    if PUBLISHES.iter().any(|pattern| command.contains(pattern)) {
+        Some("publishes or uploads something")
+    } else if on_host && INSTALLS.iter().any(|pattern| command.contains(pattern)) {
+        Some("installs software for the whole machine")
+    } else {
+        None
+    }
+}
+
+/// Jobs run on the host so far, so each gets its own progress file
+static RUNS: AtomicUsize = AtomicUsize::new(0);
+
+/// A script running `steps` in order, writing each one's index to `progress` before it starts
+fn job_script(steps: &[(String, &str)], progress: &str, announce: bool) -> String {
+    let quote = |text: &str| format!("'{}'", text.replace('\'', "'\\''"));
+    let mut script = String::new();
+    for (index, (label, command)) in steps.iter().enumerate() {
+        if announce {
+            let line = format!("  {} {}", "▸".blue(), label);
+            script.push_str(&format!("printf '%s\\n' {}\n", quote(&line)));
+        }
+        script.push_str(&format!("echo {} > {}\n", index, quote(progress)));
+        script.push_str(command);
+        script.push('\n');
+    }
+    script
+}
+
+/// Start an idle container from `image` with the project mounted, returning its ID
+fn start_container(image: &str, options: &Options) -> Result<String> {
+    let dir = options
+        .dir
+        .canonicalize()
+        .with_context(|| format!("Failed to find {}", options.dir.display()))?;
+    let output = Command::new("docker")
+        .args(["run", "-d", "--rm", "-v"])
+        .arg(format!("{}:/workspace", dir.display()))
+        .args(["--entrypoint", "sh", image, "-c"])
+        .arg("trap 'exit 0' TERM; while :; do sleep 1; done")
+        .stderr(Stdio::piped())
+        .output();
+    let output = match output {
+        Ok(output) => output,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => bail!("docker isn't installed"),
+        Err(e) => return Err(e.into()),
+    };
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn print_outcome(report: &JobReport) {
+    let seconds = format!("({:.1}s)", report.duration.as_secs_f64()).dimmed();
+    match &report.outcome {
+        Outcome::Passed => println!("  {} {} {}", "✓".green().bold(), report.id, seconds),
+        Outcome::Failed { step } => println!(
+            "  {} {} failed at `{}` {}",
+            "✗".red().bold(),
+            report.id.yellow(),
+            step,
+            seconds
+        ),
+        Outcome::Skipped { reason } => {
+            println!("  {} {} skipped: {}", "-".dimmed(), report.id, reason)
+        }
+    }
+    for note in &report.notes {
+        println!("    {} {}", "note:".dimmed(), note);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn job(id: &str, needs: &[&str], steps: Vec<Step>) -> Job {
+        Job {
+            id: id.to_string(),
+            needs: needs.iter().map(|need| need.to_string()).collect(),
+            steps,
+            ..Job::default()
+        }
+    }
+
+    fn run_step(command: &str) -> Step {
+        Step::Run {
+            name: None,
+            command: command.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_runs_jobs_and_skips_dependents_of_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let pipeline = Pipeline {
+            env: BTreeMap::from([("GREETING".to_string(), "hi".to_string())]),
+            jobs: vec![
+                job(
+                    "build",
+                    &[],
+                    vec![
+                        Step::Checkout,
+                        run_step("export WHO=\"$GREETING $CI\""),
+                        run_step("cd src"),
+                        run_step("echo \"$WHO\" > ../out.txt"),
+                    ],
+                ),
+                job(
+                    "test",
+                    &["build"],
+                    vec![run_step("true"), run_step("false"), run_step("touch never")],
+                ),
+                job("deploy", &["test"], vec![run_step("true")]),
+                job(
+                    "lint",
+                    &[],
+                    vec![
+                        Step::Action {
+                            name: None,
+                            uses: "actions/setup-node@v4".to_string(),
+                            with: BTreeMap::new(),
+                        },
+                        run_step("true"),
+                    ],
+                ),
+            ],
+            ..Pipeline::default()
+        };
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        let options = Options {
+            dir: dir.path().to_path_buf(),
+            docker: false,
+            host: true,
+            all_steps: false,
+            quiet: true,
+        };
+        let reports = run(&pipeline, &[], &options).unwrap();
+
+        let outcomes: Vec<&Outcome> = reports.iter().map(|report| &report.outcome).collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                &Outcome::Passed,
+                &Outcome::Failed {
+                    step: "false".to_string()
+                },
+                &Outcome::Skipped {
+                    reason: "`test` didn't pass".to_string()
+                },
+                &Outcome::Passed,
+            ]
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("out.txt")).unwrap(),
+            "hi true\n"
+        );
+        assert!(!dir.path().join("never").exists());
+        assert!(reports[3].notes[0].contains("actions/setup-node@v4"));
+
+        let only_lint = run(&pipeline, &["lint".to_string()], &options).unwrap();
+        assert_eq!(only_lint.len(), 1);
+    }
+
+    #[test]
+    fn test_conditional_jobs_are_skipped_unless_named() {
+        let ci: GitLabCI = serde_yaml::from_str(
+            "workflow:\n  rules:\n    - if: $CI_COMMIT_BRANCH == \"main\"\n\
+             stages: [test, release]\n\
+             test:\n  stage: test\n  script: [cargo test]\n\
+             publish:\n  stage: release\n  script: [cargo publish]\n  rules:\n    - if: $CI_COMMIT_TAG\n",
+        )
+        .unwrap();
+
+        let (pipeline, skipped) = local_pipeline(std::slice::from_ref(&ci), &[]);
+        let ids: Vec<&str> = pipeline.jobs.iter().map(|job| job.id.as_str()).collect();
+        assert_eq!(ids, vec!["test"]);
+        assert_eq!(skipped[0].id, "publish");
+
+        let (pipeline, skipped) = local_pipeline(&[ci], &["publish".to_string()]);
+        assert_eq!(pipeline.jobs.len(), 2);
+        assert!(skipped.is_empty());
+    }
+
+    #[test]
+    fn test_host_runs_and_hazardous_steps_need_asking_for() {
+        let dir = tempfile::tempdir().unwrap();
+        let pipeline = Pipeline {
+            jobs: vec![job(
+                "test",
+                &[],
+                vec![
+                    run_step("curl -sSf https://sh.rustup.rs | sh -s -- -y"),
+                    run_step("touch ran"),
+                    run_step("bash <(curl -s https://codecov.io/bash)"),
+                ],
+            )],
+            ..Pipeline::default()
+        };
+        let mut options = Options {
+            dir: dir.path().to_path_buf(),
+            docker: false,
+            host: false,
+            all_steps: false,
+            quiet: true,
+        };
+        let reports = run(&pipeline, &[], &options).unwrap();
+        assert!(matches!(reports[0].outcome, Outcome::Skipped { .. }));
+        assert!(!dir.path().join("ran").exists());
+
+        options.host = true;
+        let reports = run(&pipeline, &[], &options).unwrap();
+        assert_eq!(reports[0].outcome, Outcome::Passed);
+        assert!(dir.path().join("ran").exists());
+        assert_eq!(reports[0].notes.len(), 2);
+        assert!(reports[0].notes[0].contains("installs software"));
+        assert!(reports[0].notes[1].contains("publishes"));
+
+        assert_eq!(step_hazard("cargo install cargo-audit", false), None);
+        assert!(step_hazard("docker push demo", false).is_some());
+    }
+}
@@ -1,6 +1,8 @@
-use cci::cli::{Cli, Commands};
+use cci::cli::{Cli, Commands, ConfigCommand, PresetsCommand};
+use cci::detection::DetectionOptions;
 use cci::error::Result;
-use clap::Parser;
+use clap::{CommandFactory, Parser};
+use std::path::Path;
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
@@ -10,10 +12,191 @@ fn main() -> Result<()> {
             config,
             platform,
             force,
-        }) => cci::cli::commands::handle_generate(&config, platform, force),
-        Some(Commands::Validate { config }) => cci::cli::commands::handle_validate(&config),
+            stdout,
+            dry_run,
+            recursive,
+            online,
+            no_lint,
+            commit,
+            branch,
+            pr,
+        }) => {
+            if online {
+                cci::platforms::github::pinning::enable_online_resolution();
+            }
+            if commit {
+                cci::cli::commands::handle_generate_commit(
+                    &config, platform, force, !no_lint, &branch, pr,
+                )
+            } else {
+                cci::cli::commands::handle_generate(
+                    &config, platform, force, stdout, dry_run, recursive, !no_lint,
+                )
+            }
+        }
+        Some(Commands::Check {
+            config,
+            platform,
+            online,
+            format,
+        }) => {
+            if online {
+                cci::platforms::github::pinning::enable_online_resolution();
+            }
+            cci::cli::commands::handle_check(&config, platform, format)
+        }
+        Some(Commands::Explain {
+            target,
+            config,
+            platform,
+        }) => cci::cli::commands::handle_explain(&target, &config, platform),
+        Some(Commands::Diff { old, new, platform }) => {
+            cci::cli::commands::handle_diff(&old, &new, platform)
+        }
+        Some(Commands::Secrets { config, platform }) => {
+            cci::cli::commands::handle_secrets(&config, platform)
+        }
+        Some(Commands::Deps {
+            dir,
+            tool,
+            schedule,
+            force,
+        }) => cci::cli::commands::handle_deps(&dir, &tool, &schedule, force),
+        Some(Commands::Hooks {
+            config,
+            tool,
+            force,
+        }) => cci::cli::commands::handle_hooks(&config, &tool, force),
+        Some(Commands::Tasks {
+            config,
+            tool,
+            force,
+        }) => cci::cli::commands::handle_tasks(&config, &tool, force),
+        Some(Commands::Import { dir, output, force }) => {
+            cci::cli::commands::handle_import(&dir, &output, force)
+        }
+        Some(Commands::Migrate {
+            from,
+            to,
+            input,
+            output,
+            force,
+        }) => cci::cli::commands::handle_migrate(
+            &from,
+            &to,
+            input.as_deref(),
+            output.as_deref(),
+            force,
+        ),
+        Some(Commands::Run {
+            job,
+            config,
+            native: true,
+            docker,
+            host,
+            all_steps,
+            list,
+            dry_run,
+            ..
+        }) => cci::cli::commands::handle_run_native(
+            job.as_deref(),
+            &config,
+            docker,
+            host,
+            all_steps,
+            list,
+            dry_run,
+        ),
+        Some(Commands::Run {
+            job,
+            config,
+            platform,
+            list,
+            dry_run,
+            args,
+            ..
+        }) => {
+            cci::cli::commands::handle_run(job.as_deref(), &config, &platform, list, dry_run, &args)
+        }
+        Some(Commands::Fleet {
+            repos,
+            from,
+            org,
+            clone_dir,
+            template,
+            platform,
+            commit,
+            branch,
+            pr,
+        }) => cci::cli::commands::handle_fleet(
+            repos,
+            from.as_deref(),
+            org.as_deref().map(|org| (org, clone_dir.as_str())),
+            template.as_deref(),
+            platform,
+            commit.then_some((branch.as_str(), pr)),
+        ),
+        Some(Commands::Lint { file, platform }) => {
+            cci::cli::commands::handle_lint(file.as_deref(), platform.as_deref())
+        }
+        Some(Commands::Validate {
+            config,
+            schema,
+            platform,
+            format,
+        }) => cci::cli::commands::handle_validate(&config, format, schema, platform),
         Some(Commands::Editor { dir }) => cci::editor::run_with_args(&dir, None),
-        Some(Commands::Detect { dir }) => cci::cli::commands::handle_detect(&dir),
+        Some(Commands::Detect {
+            dir,
+            max_depth,
+            no_gitignore,
+            exclude,
+            format,
+        }) => {
+            let mut options = DetectionOptions::load(Path::new(&dir));
+            if let Some(max_depth) = max_depth {
+                options.max_depth = max_depth;
+            }
+            if no_gitignore {
+                options.respect_gitignore = false;
+            }
+            options.exclude.extend(exclude);
+            cci::cli::commands::handle_detect(&dir, &options, format)
+        }
+        Some(Commands::Completions { shell }) => {
+            print!(
+                "{}",
+                cci::cli::completions::completions(shell, &mut Cli::command())
+            );
+            Ok(())
+        }
+        Some(Commands::Man) => {
+            print!("{}", cci::cli::completions::man_page(&mut Cli::command()));
+            Ok(())
+        }
+        Some(Commands::Presets { command }) => match command {
+            PresetsCommand::List { format } => cci::cli::commands::handle_presets_list(format),
+            PresetsCommand::Show { id, format, schema } => match id {
+                Some(id) if !schema => cci::cli::commands::handle_presets_show(&id, format),
+                id => cci::cli::commands::handle_presets_schema(id.as_deref()),
+            },
+        },
+        Some(Commands::Wizard {
+            dir,
+            output,
+            yes,
+            force,
+        }) => cci::cli::commands::handle_wizard(&dir, &output, yes, force),
+        Some(Commands::Config { command }) => match command {
+            ConfigCommand::Migrate { config, dry_run } => {
+                cci::cli::commands::handle_config_migrate(&config, dry_run)
+            }
+            ConfigCommand::Convert {
+                input,
+                output,
+                force,
+            } => cci::cli::commands::handle_config_convert(&input, &output, force),
+        },
         _ => {
             // Default to editor if no command specified
             cci::editor::run_with_args(".", None)
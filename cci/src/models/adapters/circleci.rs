@@ -0,0 +1,283 @@
+use super::{artifact_consumers, PlatformAdapter};
+use crate::models::{Job, Pipeline, Step};
+use crate::platforms::circleci::models::{
+    CircleCIConfig, CircleCIDocker, CircleCIJob, CircleCIJobRequires, CircleCIRun, CircleCIStep,
+    CircleCIWorkflow, CircleCIWorkflowJob,
+};
+use crate::platforms::services::Services;
+use crate::platforms::triggers::TriggerConfig;
+use std::collections::BTreeMap;
+
+/// Image for jobs that don't name one
+const DEFAULT_IMAGE: &str = "cimg/base:stable";
+
+/// CircleCI pipelines
+pub struct CircleCIAdapter;
+
+impl PlatformAdapter for CircleCIAdapter {
+    type Config = CircleCIConfig;
+
+    fn lift(config: &CircleCIConfig, notes: &mut Vec<String>) -> Pipeline {
+        if config.orbs.is_some() {
+            notes.push("orbs were dropped".to_string());
+        }
+
+        let mut triggers = TriggerConfig {
+            push_branches: vec!["*".to_string()],
+            ..TriggerConfig::default()
+        };
+        let mut needs: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for (name, workflow) in &config.workflows {
+            if let Some(schedule) = workflow.triggers.iter().flatten().next() {
+                triggers
+                    .schedule_cron
+                    .get_or_insert_with(|| schedule.schedule.cron.clone());
+            } else if triggers.name.is_empty() {
+                triggers.name = name.clone();
+            }
+
+            for entry in &workflow.jobs {
+                let CircleCIWorkflowJob::WithRequires { job } = entry else {
+                    continue;
+                };
+                for (id, requires) in job {
+                    let dropped = [
+                        ("filters", requires.filters.is_some()),
+                        ("matrix", requires.matrix.is_some()),
+                        ("approval", requires.job_type.is_some()),
+                    ];
+                    for (what, present) in dropped {
+                        if present {
+                            notes.push(format!("job `{}`: workflow {} was dropped", id, what));
+                        }
+                    }
+                    let job_needs = needs.entry(id).or_default();
+                    for need in &requires.requires {
+                        if !job_needs.contains(need) {
+                            job_needs.push(need.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut pipeline = Pipeline {
+            triggers,
+            env: BTreeMap::new(),
+            jobs: config
+                .jobs
+                .iter()
+                .map(|(id, job)| {
+                    let job_needs = needs.get(id.as_str()).cloned().unwrap_or_default();
+                    lift_job(id, job, job_needs, notes)
+                })
+                .collect(),
+        };
+        pipeline.sort_jobs();
+        pipeline
+    }
+
+    fn lower(pipeline: &Pipeline, notes: &mut Vec<String>) -> CircleCIConfig {
+        if !pipeline.triggers.paths.is_empty() {
+            notes.push("path filters were dropped; the pipeline runs for every change".to_string());
+        }
+        let jobs = pipeline
+            .jobs
+            .iter()
+            .map(|job| (job.id.clone(), lower_job(job, &pipeline.env, notes)))
+            .collect();
+        let workflow_jobs = pipeline
+            .jobs
+            .iter()
+            .map(|job| {
+                if job.needs.is_empty() && job.matrix.is_none() {
+                    CircleCIWorkflowJob::Simple(job.id.clone())
+                } else {
+                    CircleCIWorkflowJob::WithRequires {
+                        job: BTreeMap::from([(
+                            job.id.clone(),
+                            CircleCIJobRequires {
+                                job_type: None,
+                                requires: job.needs.clone(),
+                                filters: None,
+                                matrix: job.matrix.as_ref().map(|matrix| matrix.to_circleci()),
+                            },
+                        )]),
+                    }
+                }
+            })
+            .collect();
+
+        let mut config = CircleCIConfig {
+            version: "2.1".to_string(),
+            orbs: None,
+            parameters: None,
+            jobs,
+            workflows: BTreeMap::from([(
+                "main".to_string(),
+                CircleCIWorkflow {
+                    triggers: None,
+                    jobs: workflow_jobs,
+                },
+            )]),
+        };
+        // The workspace only reaches downstream jobs, so files no job
+        // needs aren't persisted
+        for (producer, consumers) in artifact_consumers(pipeline) {
+            if consumers.is_empty() {
+                continue;
+            }
+            for artifact in &producer.artifacts {
+                artifact.apply_circleci(&mut config, &producer.id, &consumers);
+            }
+        }
+        pipeline.triggers.apply_circleci(&mut config);
+        config
+    }
+}
+
+fn lift_job(id: &str, job: &CircleCIJob, needs: Vec<String>, notes: &mut Vec<String>) -> Job {
+    let dropped = [
+        ("additional docker images", job.docker.len() > 1),
+        ("machine executor", job.machine.is_some()),
+        ("macos executor", job.macos.is_some()),
+        ("resource_class", job.resource_class.is_some()),
+        ("parameters", job.parameters.is_some()),
+    ];
+    for (what, present) in dropped {
+        if present {
+            notes.push(format!("job `{}`: {} was dropped", id, what));
+        }
+    }
+
+    let mut steps = Vec::new();
+    for step in &job.steps {
+        match step {
+            CircleCIStep::Simple(name) if name == "checkout" => steps.push(Step::Checkout),
+            CircleCIStep::Simple(name) => {
+                notes.push(format!("job `{}`: step `{}` was dropped", id, name))
+            }
+            CircleCIStep::Command { run } => steps.push(match run {
+                CircleCIRun::Simple(command) => Step::Run {
+                    name: None,
+                    command: command.clone(),
+                },
+                CircleCIRun::Detailed { name, command } => Step::Run {
+                    name: Some(name.clone()),
+                    command: command.clone(),
+                },
+                CircleCIRun::Timed { name, command, .. } => {
+                    notes.push(format!(
+                        "job `{}`: step `{}` lost its no_output_timeout",
+                        id, name
+                    ));
+                    Step::Run {
+                        name: Some(name.clone()),
+                        command: command.clone(),
+                    }
+                }
+                CircleCIRun::Conditional { name, command, .. } => {
+                    notes.push(format!(
+                        "job `{}`: step `{}` now runs unconditionally",
+                        id, name
+                    ));
+                    Step::Run {
+                        name: Some(name.clone()),
+                        command: command.clone(),
+                    }
+                }
+            }),
+            CircleCIStep::Cache { .. } | CircleCIStep::SaveCache { .. } => {
+                notes.push(format!("job `{}`: cache steps were dropped", id))
+            }
+            CircleCIStep::PersistToWorkspace { .. } | CircleCIStep::AttachWorkspace { .. } => {
+                notes.push(format!("job `{}`: workspace steps were dropped", id))
+            }
+        }
+    }
+    notes.dedup();
+
+    Job {
+        id: id.to_string(),
+        name: None,
+        stage: None,
+        image: job.docker.first().map(|docker| docker.image.clone()),
+        runner: None,
+        needs,
+        env: job.environment.clone().unwrap_or_default(),
+        timeout_minutes: None,
+        matrix: None,
+        services: Vec::new(),
+        cache: None,
+        artifacts: Vec::new(),
+        steps,
+    }
+}
+
+fn lower_job(job: &Job, env: &BTreeMap<String, String>, notes: &mut Vec<String>) -> CircleCIJob {
+    if job.timeout_minutes.is_some() {
+        notes.push(format!("job `{}`: timeout was dropped", job.id));
+    }
+
+    let mut steps = Vec::new();
+    for step in &job.steps {
+        match step {
+            Step::Checkout => steps.push(CircleCIStep::Simple("checkout".to_string())),
+            Step::Run {
+                name: Some(name),
+                command,
+            } => steps.push(CircleCIStep::Command {
+                run: CircleCIRun::Detailed {
+                    name: name.clone(),
+                    command: command.clone(),
+                },
+            }),
+            Step::Run {
+                name: None,
+                command,
+            } => steps.push(CircleCIStep::Command {
+                run: CircleCIRun::Simple(command.clone()),
+            }),
+            Step::Action { uses, .. } => notes.push(format!(
+                "job `{}`: action `{}` has no CircleCI equivalent and was dropped",
+                job.id, uses
+            )),
+        }
+    }
+    if let Some(cache) = &job.cache {
+        let checkout = steps
+            .iter()
+            .position(|step| matches!(step, CircleCIStep::Simple(name) if name == "checkout"))
+            .map_or(0, |index| index + 1);
+        steps.insert(checkout, cache.circleci_restore_step());
+        steps.push(cache.circleci_save_step());
+    }
+
+    // CircleCI has no pipeline-wide variables, so every job gets a copy
+    let mut environment = env.clone();
+    environment.extend(job.env.clone());
+
+    let mut lowered = CircleCIJob {
+        docker: vec![CircleCIDocker {
+            image: job
+                .image
+                .clone()
+                .unwrap_or_else(|| DEFAULT_IMAGE.to_string()),
+            environment: None,
+        }],
+        machine: None,
+        macos: None,
+        resource_class: None,
+        parameters: job
+            .matrix
+            .as_ref()
+            .map(|matrix| matrix.circleci_parameters()),
+        steps,
+        environment: (!environment.is_empty()).then_some(environment),
+    };
+    Services {
+        services: job.services.clone(),
+    }
+    .apply_circleci(&mut lowered);
+    lowered
+}
@@ -0,0 +1,222 @@
+use super::{artifact_consumers, PlatformAdapter};
+use crate::models::{steps, Job, Pipeline, Step};
+use crate::platforms::github::models::{
+    GitHubJob, GitHubRunsOn, GitHubStep, GitHubTriggers, GitHubWorkflow,
+};
+use crate::platforms::matrix::Matrix;
+use crate::platforms::services::Services;
+use crate::platforms::triggers::TriggerConfig;
+use std::collections::BTreeMap;
+
+/// Events [`TriggerConfig`] can describe
+const SUPPORTED_EVENTS: [&str; 4] = ["push", "pull_request", "schedule", "workflow_dispatch"];
+
+/// GitHub Actions workflows, also used for Gitea
+pub struct GitHubAdapter;
+
+impl PlatformAdapter for GitHubAdapter {
+    type Config = GitHubWorkflow;
+
+    fn lift(workflow: &GitHubWorkflow, notes: &mut Vec<String>) -> Pipeline {
+        if workflow.permissions.is_some() {
+            notes.push("workflow permissions were dropped".to_string());
+        }
+        if workflow.concurrency.is_some() {
+            notes.push("workflow concurrency was dropped".to_string());
+        }
+        let events: Vec<&String> = match &workflow.on {
+            GitHubTriggers::Single(event) => vec![event],
+            GitHubTriggers::Simple(events) => events.iter().collect(),
+            GitHubTriggers::Detailed(events) => events.keys().collect(),
+        };
+        for event in events {
+            if !SUPPORTED_EVENTS.contains(&event.as_str()) {
+                notes.push(format!("`{}` trigger was dropped", event));
+            }
+        }
+
+        let mut pipeline = Pipeline {
+            triggers: TriggerConfig::from_github(workflow),
+            env: workflow.env.clone().unwrap_or_default(),
+            jobs: workflow
+                .jobs
+                .iter()
+                .map(|(id, job)| lift_job(id, job, notes))
+                .collect(),
+        };
+        pipeline.sort_jobs();
+        pipeline
+    }
+
+    fn lower(pipeline: &Pipeline, _notes: &mut Vec<String>) -> GitHubWorkflow {
+        let name = if pipeline.triggers.name.is_empty() {
+            "CI".to_string()
+        } else {
+            pipeline.triggers.name.clone()
+        };
+        let mut jobs = pipeline
+            .jobs
+            .iter()
+            .map(|job| (job.id.clone(), lower_job(job)))
+            .collect();
+        for (producer, consumers) in artifact_consumers(pipeline) {
+            for artifact in &producer.artifacts {
+                artifact.apply_github(&mut jobs, &producer.id, &consumers);
+            }
+        }
+        GitHubWorkflow {
+            name,
+            on: pipeline.triggers.to_github(),
+            permissions: None,
+            concurrency: None,
+            env: non_empty(&pipeline.env),
+            defaults: None,
+            jobs,
+        }
+    }
+}
+
+fn lift_job(id: &str, job: &GitHubJob, notes: &mut Vec<String>) -> Job {
+    let dropped = [
+        ("permissions", job.permissions.is_some()),
+        ("matrix strategy", job.strategy.is_some()),
+        ("services", job.services.is_some()),
+        ("if condition", job.if_condition.is_some()),
+        ("environment", job.environment.is_some()),
+        ("continue-on-error", job.continue_on_error.is_some()),
+    ];
+    for (what, present) in dropped {
+        if present {
+            notes.push(format!("job `{}`: {} was dropped", id, what));
+        }
+    }
+
+    Job {
+        id: id.to_string(),
+        name: None,
+        stage: None,
+        image: job.container.clone(),
+        runner: Some(match &job.runs_on {
+            GitHubRunsOn::Label(label) => label.clone(),
+            GitHubRunsOn::Labels(labels) => labels.join(", "),
+        }),
+        needs: job.needs.clone().unwrap_or_default(),
+        env: job.env.clone().unwrap_or_default(),
+        timeout_minutes: job.timeout_minutes,
+        matrix: None,
+        services: Vec::new(),
+        cache: None,
+        artifacts: Vec::new(),
+        steps: job
+            .steps
+            .iter()
+            .filter_map(|step| lift_step(id, step, notes))
+            .collect(),
+    }
+}
+
+fn lift_step(job: &str, step: &GitHubStep, notes: &mut Vec<String>) -> Option<Step> {
+    let lifted = if let Some(uses) = &step.uses {
+        if uses.starts_with("actions/checkout@") {
+            if step.with.is_some() {
+                notes.push(format!("job `{}`: checkout options were dropped", job));
+            }
+            Step::Checkout
+        } else {
+            Step::Action {
+                name: step.name.clone(),
+                uses: uses.clone(),
+                with: step.with.clone().unwrap_or_default(),
+            }
+        }
+    } else {
+        Step::Run {
+            name: step.name.clone(),
+            command: step.run.clone()?,
+        }
+    };
+    if step.env.is_some() {
+        notes.push(format!(
+            "job `{}`: env of step `{}` was dropped",
+            job,
+            lifted.label()
+        ));
+    }
+    Some(lifted)
+}
+
+fn lower_job(job: &Job) -> GitHubJob {
+    let runs_on = match &job.runner {
+        Some(runner) if runner.contains(',') => GitHubRunsOn::Labels(
+            runner
+                .split(',')
+                .map(|label| label.trim().to_string())
+                .collect(),
+        ),
+        Some(runner) => GitHubRunsOn::Label(runner.clone()),
+        None => "ubuntu-latest".into(),
+    };
+    let mut steps: Vec<GitHubStep> = job.steps.iter().map(GitHubStep::from).collect();
+    if let Some(cache) = &job.cache {
+        // Once the toolchain is set up, before the first command fetches anything
+        let first_command = job
+            .steps
+            .iter()
+            .position(|step| matches!(step, Step::Run { .. }))
+            .unwrap_or(steps.len());
+        steps.splice(
+            first_command..first_command,
+            steps::cache(cache).iter().map(GitHubStep::from),
+        );
+    }
+
+    let mut lowered = GitHubJob {
+        runs_on,
+        container: job.image.clone(),
+        permissions: None,
+        strategy: job.matrix.as_ref().map(Matrix::to_github),
+        services: None,
+        env: non_empty(&job.env),
+        steps,
+        needs: (!job.needs.is_empty()).then(|| job.needs.clone()),
+        timeout_minutes: job.timeout_minutes,
+        continue_on_error: None,
+        if_condition: None,
+        environment: None,
+    };
+    Services {
+        services: job.services.clone(),
+    }
+    .apply_github(&mut lowered);
+    lowered
+}
+
+/// The GitHub step a pipeline step becomes, for presets assembling
+/// workflows from [`steps`](crate::models::steps) blocks
+impl From<&Step> for GitHubStep {
+    fn from(step: &Step) -> Self {
+        match step {
+            Step::Checkout => GitHubStep::checkout(),
+            Step::Run { name, command } => GitHubStep {
+                name: name.clone(),
+                if_condition: None,
+                uses: None,
+                run: Some(command.clone()),
+                with: None,
+                env: None,
+            },
+            Step::Action { name, uses, with } => GitHubStep {
+                name: name.clone(),
+                if_condition: None,
+                uses: Some(uses.clone()),
+                run: None,
+                with: (!with.is_empty()).then(|| with.clone()),
+                env: None,
+            },
+        }
+    }
+}
+
+fn non_empty(env: &BTreeMap<String, String>) -> Option<BTreeMap<String, String>> {
+    (!env.is_empty()).then(|| env.clone())
+}
@@ -0,0 +1,269 @@
+use super::{artifact_consumers, PlatformAdapter};
+use crate::models::{Job, Pipeline, Step};
+use crate::platforms::gitlab::models::{GitLabCI, GitLabJob};
+use crate::platforms::jobs::Jobs;
+use crate::platforms::matrix::Matrix;
+use crate::platforms::services::Services;
+use crate::platforms::triggers::TriggerConfig;
+
+/// Stages GitLab uses when a config doesn't list its own
+const DEFAULT_STAGES: [&str; 3] = ["build", "test", "deploy"];
+
+/// GitLab CI pipelines
+pub struct GitLabAdapter;
+
+impl PlatformAdapter for GitLabAdapter {
+    type Config = GitLabCI;
+
+    fn lift(ci: &GitLabCI, notes: &mut Vec<String>) -> Pipeline {
+        if ci.include.is_some() {
+            notes.push("included templates were dropped".to_string());
+        }
+        if ci.cache.is_some() {
+            notes.push("the default cache was dropped".to_string());
+        }
+        // Without workflow rules GitLab runs a pipeline for every push
+        let triggers = match ci.workflow.as_ref() {
+            Some(workflow) if !workflow.rules.is_empty() => {
+                TriggerConfig::from_gitlab(workflow, notes)
+            }
+            workflow => TriggerConfig {
+                name: workflow
+                    .and_then(|workflow| workflow.name.clone())
+                    .unwrap_or_default(),
+                push_branches: vec!["*".to_string()],
+                ..TriggerConfig::default()
+            },
+        };
+
+        let stages: Vec<String> = ci.stages.clone().unwrap_or_else(|| {
+            DEFAULT_STAGES
+                .iter()
+                .map(|stage| stage.to_string())
+                .collect()
+        });
+        let stage_index = |stage: &str| {
+            stages
+                .iter()
+                .position(|s| s == stage)
+                .unwrap_or(stages.len())
+        };
+
+        // Jobs take the defaults they don't set themselves
+        let default = ci.default.clone().unwrap_or_default();
+        if default.interruptible.is_some() {
+            notes.push("the default interruptible setting was dropped".to_string());
+        }
+        let with_defaults: Vec<(&String, GitLabJob)> = ci
+            .jobs
+            .iter()
+            .filter(|(id, _)| !id.starts_with('.'))
+            .map(|(id, job)| {
+                let mut job = job.clone();
+                if job.image.is_none() {
+                    job.image = default.image.clone();
+                }
+                if job.before_script.is_none() {
+                    job.before_script = default.before_script.clone();
+                }
+                (id, job)
+            })
+            .collect();
+        let mut jobs: Vec<(&String, &GitLabJob)> =
+            with_defaults.iter().map(|(id, job)| (*id, job)).collect();
+        jobs.sort_by_key(|(_, job)| stage_index(&job.stage));
+
+        let lifted = jobs
+            .iter()
+            .map(|(id, job)| {
+                // Without `needs`, a job waits for every job in the stage before it
+                let needs = job.needs.clone().unwrap_or_else(|| {
+                    let earlier = jobs
+                        .iter()
+                        .map(|(_, other)| stage_index(&other.stage))
+                        .filter(|index| *index < stage_index(&job.stage))
+                        .max();
+                    jobs.iter()
+                        .filter(|(_, other)| Some(stage_index(&other.stage)) == earlier)
+                        .map(|(other, _)| other.to_string())
+                        .collect()
+                });
+                lift_job(id, job, needs, notes)
+            })
+            .collect();
+
+        let mut pipeline = Pipeline {
+            triggers,
+            env: ci.variables.clone().unwrap_or_default(),
+            jobs: lifted,
+        };
+        pipeline.sort_jobs();
+        pipeline
+    }
+
+    fn lower(pipeline: &Pipeline, notes: &mut Vec<String>) -> GitLabCI {
+        let mut stages: Vec<String> = Vec::new();
+        let mut jobs = Jobs::new();
+        for job in &pipeline.jobs {
+            let stage = job.stage.clone().unwrap_or_else(|| "test".to_string());
+            if !stages.contains(&stage) {
+                stages.push(stage.clone());
+            }
+            // Without `needs`, a job in a later stage would wait for every
+            // job before it rather than only the ones it needs
+            let later_stage = stages[0] != stage;
+            jobs.insert(job.id.clone(), lower_job(job, stage, later_stage, notes));
+        }
+        for (producer, consumers) in artifact_consumers(pipeline) {
+            for artifact in &producer.artifacts {
+                artifact.apply_gitlab(&mut jobs, &producer.id, &consumers);
+            }
+        }
+
+        let mut ci = GitLabCI {
+            include: None,
+            workflow: None,
+            default: None,
+            stages: Some(stages),
+            variables: (!pipeline.env.is_empty()).then(|| pipeline.env.clone()),
+            cache: None,
+            jobs,
+        };
+        pipeline.triggers.apply_gitlab(&mut ci);
+        ci
+    }
+}
+
+fn lift_job(id: &str, job: &GitLabJob, needs: Vec<String>, notes: &mut Vec<String>) -> Job {
+    let dropped = [
+        ("services", job.services.is_some()),
+        ("after_script", job.after_script.is_some()),
+        ("cache", job.cache.is_some()),
+        ("artifacts", job.artifacts.is_some()),
+        ("only", job.only.is_some()),
+        ("tags", job.tags.is_some()),
+        ("parallel matrix", job.parallel.is_some()),
+        ("rules", job.rules.is_some()),
+        ("when", job.when.is_some()),
+        ("retry", job.retry.is_some()),
+        ("allow_failure", job.allow_failure.is_some()),
+        ("interruptible", job.interruptible.is_some()),
+        ("coverage", job.coverage.is_some()),
+        ("environment", job.environment.is_some()),
+    ];
+    for (what, present) in dropped {
+        if present {
+            notes.push(format!("job `{}`: {} was dropped", id, what));
+        }
+    }
+
+    let timeout_minutes = job.timeout.as_deref().and_then(|timeout| {
+        let minutes = parse_timeout(timeout);
+        if minutes.is_none() {
+            notes.push(format!(
+                "job `{}`: timeout `{}` was not understood",
+                id, timeout
+            ));
+        }
+        minutes
+    });
+
+    // GitLab clones the repository before running any script, and runs
+    // `before_script` and `script` in one shell
+    let commands: Vec<&str> = job
+        .before_script
+        .iter()
+        .flatten()
+        .chain(&job.script)
+        .map(String::as_str)
+        .collect();
+    let steps = vec![
+        Step::Checkout,
+        Step::Run {
+            name: None,
+            command: commands.join("\n"),
+        },
+    ];
+
+    Job {
+        id: id.to_string(),
+        name: None,
+        stage: Some(job.stage.clone()),
+        image: job.image.clone(),
+        runner: None,
+        needs,
+        env: job.variables.clone().unwrap_or_default(),
+        timeout_minutes,
+        matrix: None,
+        services: Vec::new(),
+        cache: None,
+        artifacts: Vec::new(),
+        steps,
+    }
+}
+
+fn lower_job(job: &Job, stage: String, later_stage: bool, notes: &mut Vec<String>) -> GitLabJob {
+    let mut script = Vec::new();
+    for step in &job.steps {
+        match step {
+            Step::Checkout => {}
+            Step::Run { command, .. } => script.push(command.clone()),
+            Step::Action { uses, .. } => notes.push(format!(
+                "job `{}`: action `{}` has no GitLab equivalent and was dropped",
+                job.id, uses
+            )),
+        }
+    }
+    if script.is_empty() {
+        notes.push(format!("job `{}`: no commands left to run", job.id));
+        script.push("echo \"Nothing to run\"".to_string());
+    }
+
+    let mut lowered = GitLabJob {
+        stage,
+        image: job.image.clone(),
+        services: None,
+        variables: (!job.env.is_empty()).then(|| job.env.clone()),
+        script,
+        before_script: None,
+        after_script: None,
+        needs: (!job.needs.is_empty() || later_stage).then(|| job.needs.clone()),
+        cache: None,
+        artifacts: None,
+        only: None,
+        tags: None,
+        parallel: job.matrix.as_ref().map(Matrix::to_gitlab),
+        timeout: job.timeout_minutes.map(|minutes| format!("{}m", minutes)),
+        retry: None,
+        allow_failure: None,
+        rules: None,
+        when: None,
+        interruptible: None,
+        coverage: None,
+        environment: None,
+    };
+    if let Some(cache) = &job.cache {
+        cache.apply_gitlab(&mut lowered);
+    }
+    Services {
+        services: job.services.clone(),
+    }
+    .apply_gitlab(&mut lowered);
+    lowered
+}
+
+/// Minutes in a GitLab duration such as `30m`, `1h` or `1h 30m`
+fn parse_timeout(timeout: &str) -> Option<u32> {
+    let mut minutes = 0;
+    for part in timeout.split_whitespace() {
+        let split = part.find(|c: char| !c.is_ascii_digit())?;
+        let (value, unit) = part.split_at(split);
+        let value: u32 = value.parse().ok()?;
+        minutes += match unit {
+            "m" | "min" | "mins" | "minute" | "minutes" => value,
+            "h" | "hr" | "hour" | "hours" => value * 60,
+            _ => return None,
+        };
+    }
+    (minutes > 0).then_some(minutes)
+}
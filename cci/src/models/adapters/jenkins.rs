@@ -0,0 +1,200 @@
+use super::PlatformAdapter;
+use crate::models::{Job, Pipeline, Step};
+use crate::platforms::jenkins::helpers::{groovy_quote, groovy_unquote};
+use crate::platforms::jenkins::models::{JenkinsAgent, JenkinsConfig, JenkinsStage};
+use crate::platforms::triggers::TriggerConfig;
+
+/// Jenkins declarative pipelines
+///
+/// Stages run one after another, so lifted jobs each need the stage before.
+pub struct JenkinsAdapter;
+
+impl PlatformAdapter for JenkinsAdapter {
+    type Config = JenkinsConfig;
+
+    fn lift(config: &JenkinsConfig, notes: &mut Vec<String>) -> Pipeline {
+        let (image, runner) = match &config.agent {
+            JenkinsAgent::Any => (None, None),
+            JenkinsAgent::Label(label) => (None, Some(label.clone())),
+            JenkinsAgent::Docker(image) => (Some(image.clone()), None),
+        };
+
+        let mut triggers = TriggerConfig {
+            push_branches: vec!["*".to_string()],
+            ..TriggerConfig::default()
+        };
+        for trigger in &config.triggers {
+            let cron = trigger
+                .strip_prefix("cron(")
+                .and_then(|rest| rest.strip_suffix(')'))
+                .and_then(groovy_unquote);
+            match cron {
+                Some(cron) => triggers.schedule_cron = Some(cron),
+                None => notes.push(format!("trigger `{}` was dropped", trigger)),
+            }
+        }
+        if !config.when.is_empty() {
+            notes.push("pipeline-wide `when` conditions were dropped".to_string());
+        }
+        if !config.post.is_empty() {
+            notes.push("`post` steps were dropped".to_string());
+        }
+
+        let mut previous: Option<String> = None;
+        let jobs = config
+            .stages
+            .iter()
+            .map(|stage| {
+                if !stage.when.is_empty() {
+                    notes.push(format!(
+                        "stage `{}`: `when` conditions were dropped",
+                        stage.name
+                    ));
+                }
+                let steps = stage
+                    .steps
+                    .iter()
+                    .filter_map(|step| {
+                        let lifted = lift_step(step);
+                        if lifted.is_none() {
+                            notes.push(format!(
+                                "stage `{}`: step `{}` was dropped",
+                                stage.name, step
+                            ));
+                        }
+                        lifted
+                    })
+                    .collect();
+                let job = Job {
+                    id: stage.name.clone(),
+                    name: None,
+                    stage: None,
+                    image: image.clone(),
+                    runner: runner.clone(),
+                    needs: previous.iter().cloned().collect(),
+                    env: Default::default(),
+                    timeout_minutes: None,
+                    matrix: None,
+                    services: Vec::new(),
+                    cache: None,
+                    artifacts: Vec::new(),
+                    steps,
+                };
+                previous = Some(stage.name.clone());
+                job
+            })
+            .collect();
+
+        Pipeline {
+            triggers,
+            env: config.environment.iter().cloned().collect(),
+            jobs,
+        }
+    }
+
+    fn lower(pipeline: &Pipeline, notes: &mut Vec<String>) -> JenkinsConfig {
+        if !pipeline.triggers.paths.is_empty() {
+            notes.push("path filters were dropped; the pipeline runs for every change".to_string());
+        }
+        let mut images: Vec<&String> = pipeline
+            .jobs
+            .iter()
+            .filter_map(|job| job.image.as_ref())
+            .collect();
+        images.sort();
+        images.dedup();
+        let agent = match images.as_slice() {
+            [image] => JenkinsAgent::Docker(image.to_string()),
+            [] => JenkinsAgent::Any,
+            _ => {
+                notes.push(
+                    "jobs use different images; the pipeline runs on `agent any`".to_string(),
+                );
+                JenkinsAgent::Any
+            }
+        };
+
+        let stages = pipeline
+            .jobs
+            .iter()
+            .map(|job| {
+                if job.timeout_minutes.is_some() {
+                    notes.push(format!("job `{}`: timeout was dropped", job.id));
+                }
+                if !job.env.is_empty() {
+                    notes.push(format!("job `{}`: env was dropped", job.id));
+                }
+                if job.matrix.is_some() {
+                    notes.push(format!("job `{}`: matrix was dropped", job.id));
+                }
+                if !job.services.is_empty() {
+                    notes.push(format!("job `{}`: services were dropped", job.id));
+                }
+                let mut steps = Vec::new();
+                for step in &job.steps {
+                    match step {
+                        // Declarative pipelines check out the repository before the first stage
+                        Step::Checkout => {}
+                        Step::Run { command, .. } => {
+                            steps.push(format!("sh {}", groovy_quote(command)))
+                        }
+                        Step::Action { uses, .. } => notes.push(format!(
+                            "job `{}`: action `{}` has no Jenkins equivalent and was dropped",
+                            job.id, uses
+                        )),
+                    }
+                }
+                // Stages share the workspace; archiving keeps the files with the build
+                for artifact in &job.artifacts {
+                    let patterns: Vec<String> = artifact
+                        .paths
+                        .iter()
+                        .map(|path| match path.strip_suffix('/') {
+                            Some(dir) => format!("{}/**", dir),
+                            None => path.clone(),
+                        })
+                        .collect();
+                    steps.push(format!(
+                        "archiveArtifacts artifacts: {}",
+                        groovy_quote(&patterns.join(","))
+                    ));
+                }
+                if steps.is_empty() {
+                    steps.push("echo 'Nothing to run'".to_string());
+                }
+                JenkinsStage {
+                    name: job.name.clone().unwrap_or_else(|| job.id.clone()),
+                    when: Vec::new(),
+                    steps,
+                }
+            })
+            .collect();
+
+        let mut config = JenkinsConfig {
+            agent,
+            environment: pipeline
+                .env
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            triggers: Vec::new(),
+            when: Vec::new(),
+            stages,
+            post: Vec::new(),
+        };
+        pipeline.triggers.apply_jenkins(&mut config);
+        config
+    }
+}
+
+/// A step the model can express: `checkout scm` or `sh` with a literal command
+fn lift_step(step: &str) -> Option<Step> {
+    if step == "checkout scm" {
+        return Some(Step::Checkout);
+    }
+    let command = step.strip_prefix("sh ")?;
+    Some(Step::Run {
+        name: None,
+        command: groovy_unquote(command.trim())?,
+    })
+}
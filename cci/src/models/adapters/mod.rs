@@ -0,0 +1,322 @@
+//! Translation between platform configs and the [`Pipeline`] model
+//!
+//! Lifting keeps what the model can express and notes everything else, so a
+//! migration can report what needs porting by hand.
+
+mod circleci;
+mod github;
+mod gitlab;
+mod jenkins;
+
+pub use circleci::CircleCIAdapter;
+pub use github::GitHubAdapter;
+pub use gitlab::GitLabAdapter;
+pub use jenkins::JenkinsAdapter;
+
+use super::{Job, Pipeline};
+use crate::editor::state::Platform;
+use crate::error::{config_error, Result};
+use crate::platforms::helpers::PlatformConfig;
+
+/// Converts one platform's config to and from a [`Pipeline`]
+pub trait PlatformAdapter {
+    type Config;
+
+    /// Read a config into the model, noting constructs that were dropped
+    fn lift(config: &Self::Config, notes: &mut Vec<String>) -> Pipeline;
+
+    /// Write the model as a config, noting parts the platform can't express
+    fn lower(pipeline: &Pipeline, notes: &mut Vec<String>) -> Self::Config;
+}
+
+/// A config translated to another platform
+#[derive(Debug, Clone)]
+pub struct Migration {
+    /// Rendered config for the target platform
+    pub output: String,
+    /// Constructs that were dropped or approximated
+    pub notes: Vec<String>,
+}
+
+/// Jobs keeping artifacts, each with the IDs of the jobs that need it
+fn artifact_consumers(pipeline: &Pipeline) -> Vec<(&Job, Vec<&str>)> {
+    pipeline
+        .jobs
+        .iter()
+        .filter(|job| !job.artifacts.is_empty())
+        .map(|producer| {
+            let consumers = pipeline
+                .jobs
+                .iter()
+                .filter(|job| job.needs.contains(&producer.id))
+                .map(|job| job.id.as_str())
+                .collect();
+            (producer, consumers)
+        })
+        .collect()
+}
+
+/// Read an already built config into the model
+pub fn lift(config: &PlatformConfig, notes: &mut Vec<String>) -> Pipeline {
+    match config {
+        // Use the job IDs written to the file
+        PlatformConfig::GitHub(workflow) | PlatformConfig::Gitea(workflow) => {
+            GitHubAdapter::lift(&workflow.with_valid_job_ids(), notes)
+        }
+        PlatformConfig::GitLab(ci) => GitLabAdapter::lift(ci, notes),
+        PlatformConfig::CircleCI(config) => CircleCIAdapter::lift(config, notes),
+        PlatformConfig::Jenkins(config) => JenkinsAdapter::lift(config, notes),
+    }
+}
+
+/// Write the model as `platform`'s config, noting parts it can't express
+pub fn lower(pipeline: &Pipeline, platform: Platform, notes: &mut Vec<String>) -> PlatformConfig {
+    match platform {
+        Platform::GitHub => PlatformConfig::GitHub(GitHubAdapter::lower(pipeline, notes)),
+        Platform::Gitea => PlatformConfig::Gitea(GitHubAdapter::lower(pipeline, notes)),
+        Platform::GitLab => PlatformConfig::GitLab(GitLabAdapter::lower(pipeline, notes)),
+        Platform::CircleCI => PlatformConfig::CircleCI(CircleCIAdapter::lower(pipeline, notes)),
+        Platform::Jenkins => PlatformConfig::Jenkins(JenkinsAdapter::lower(pipeline, notes)),
+    }
+}
+
+/// Translate a config from one platform to another
+///
+/// Jenkinsfiles are Groovy programs rather than data, so they can only be
+/// written, not read.
+pub fn migrate(content: &str, from: Platform, to: Platform) -> Result<Migration> {
+    let mut notes = Vec::new();
+    let pipeline = match from {
+        Platform::GitHub | Platform::Gitea => {
+            GitHubAdapter::lift(&serde_yaml::from_str(content)?, &mut notes)
+        }
+        Platform::GitLab => GitLabAdapter::lift(&serde_yaml::from_str(content)?, &mut notes),
+        Platform::CircleCI => CircleCIAdapter::lift(&serde_yaml::from_str(content)?, &mut notes),
+        Platform::Jenkins => {
+            return Err(config_error(
+                "Jenkinsfiles can't be read; migrate from a YAML platform instead",
+            ))
+        }
+    };
+
+    let config = lower(&pipeline, to, &mut notes);
+    Ok(Migration {
+        output: config.render()?,
+        notes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::steps;
+    use crate::models::Step;
+    use crate::platforms::circleci::models::{CircleCIConfig, CircleCIWorkflowJob};
+    use crate::platforms::github::models::GitHubWorkflow;
+    use crate::platforms::triggers::TriggerConfig;
+
+    #[test]
+    fn test_gitlab_to_github_orders_stages() {
+        let gitlab = r#"
+workflow:
+  rules:
+    - if: $CI_COMMIT_BRANCH == "main"
+    - if: $CI_PIPELINE_SOURCE == "merge_request_event"
+stages: [build, test]
+variables:
+  CARGO_TERM_COLOR: always
+build:
+  stage: build
+  image: rust:latest
+  cache:
+    key: cargo
+    paths: [target]
+  script:
+    - cargo build
+test:
+  stage: test
+  image: rust:latest
+  timeout: 1h
+  before_script:
+    - cargo fetch
+  script:
+    - cargo test
+"#;
+        let migration = migrate(gitlab, Platform::GitLab, Platform::GitHub).unwrap();
+        let workflow: GitHubWorkflow = serde_yaml::from_str(&migration.output).unwrap();
+
+        let test = &workflow.jobs["test"];
+        assert_eq!(test.needs, Some(vec!["build".to_string()]));
+        assert_eq!(test.container.as_deref(), Some("rust:latest"));
+        assert_eq!(test.timeout_minutes, Some(60));
+        assert_eq!(test.steps.len(), 2);
+        assert_eq!(
+            test.steps[1].run.as_deref(),
+            Some("cargo fetch\ncargo test")
+        );
+        assert_eq!(
+            workflow.env.as_ref().unwrap()["CARGO_TERM_COLOR"],
+            "always".to_string()
+        );
+        let triggers = TriggerConfig::from_github(&workflow);
+        assert_eq!(triggers.push_branches, ["main"]);
+        assert_eq!(triggers.pr_branches, ["*"]);
+        assert!(migration
+            .notes
+            .iter()
+            .any(|note| note.contains("build") && note.contains("cache")));
+    }
+
+    #[test]
+    fn test_gitlab_hidden_jobs_and_defaults() {
+        let gitlab = r#"
+default:
+  image: rust:latest
+  before_script:
+    - cargo fetch
+.template:
+  image: alpine
+lint:
+  extends: .template
+  only: [main]
+  script:
+    - cargo clippy
+"#;
+        let ci: crate::platforms::gitlab::models::GitLabCI = serde_yaml::from_str(gitlab).unwrap();
+        assert_eq!(ci.jobs.keys().collect::<Vec<_>>(), ["lint"]);
+
+        let mut notes = Vec::new();
+        let pipeline = GitLabAdapter::lift(&ci, &mut notes);
+        let lint = &pipeline.jobs[0];
+        assert_eq!(lint.stage.as_deref(), Some("test"));
+        assert_eq!(lint.image.as_deref(), Some("rust:latest"));
+        assert_eq!(
+            lint.steps[1],
+            Step::Run {
+                name: None,
+                command: "cargo fetch\ncargo clippy".to_string(),
+            }
+        );
+        assert!(notes.iter().any(|note| note.contains("only")));
+        assert!(migrate(gitlab, Platform::GitLab, Platform::GitHub).is_ok());
+    }
+
+    #[test]
+    fn test_github_to_circleci_notes_actions() {
+        let github = r#"
+name: CI
+on: [push]
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+      - uses: actions/setup-node@v4
+        with:
+          node-version: 20
+      - name: Test
+        run: npm test
+"#;
+        let migration = migrate(github, Platform::GitHub, Platform::CircleCI).unwrap();
+        let config: CircleCIConfig = serde_yaml::from_str(&migration.output).unwrap();
+
+        let test = &config.jobs["test"];
+        assert_eq!(test.steps.len(), 2);
+        assert!(config.workflows.contains_key("main"));
+        assert!(migration
+            .notes
+            .iter()
+            .any(|note| note.contains("actions/setup-node@v4")));
+    }
+
+    #[test]
+    fn test_lowers_matrix_cache_and_artifacts_everywhere() {
+        use crate::platforms::artifacts::Artifact;
+        use crate::platforms::cache::{CacheMode, CacheStrategy};
+        use crate::platforms::matrix::Matrix;
+
+        let job = |id: &str, steps: Vec<Step>| Job {
+            id: id.to_string(),
+            name: None,
+            stage: None,
+            image: None,
+            runner: None,
+            needs: Vec::new(),
+            env: Default::default(),
+            timeout_minutes: None,
+            matrix: None,
+            services: Vec::new(),
+            cache: None,
+            artifacts: Vec::new(),
+            steps,
+        };
+        let pipeline = Pipeline {
+            triggers: TriggerConfig::default(),
+            env: Default::default(),
+            jobs: vec![
+                Job {
+                    stage: Some("build".to_string()),
+                    matrix: Matrix::from_list("toolchain", "stable, nightly"),
+                    cache: Some(CacheStrategy::rust(CacheMode::Manual)),
+                    artifacts: vec![Artifact::new("release", &["target/release/"])],
+                    ..job(
+                        "build",
+                        vec![Step::Checkout, steps::run("Build", "cargo build")],
+                    )
+                },
+                Job {
+                    stage: Some("deploy".to_string()),
+                    needs: vec!["build".to_string()],
+                    ..job("deploy", vec![steps::run("Deploy", "./deploy.sh")])
+                },
+            ],
+        };
+        let mut notes = Vec::new();
+
+        let github = GitHubAdapter::lower(&pipeline, &mut notes);
+        let build = &github.jobs["build"];
+        assert!(build
+            .strategy
+            .as_ref()
+            .unwrap()
+            .matrix
+            .axes
+            .contains_key("toolchain"));
+        let uses: Vec<_> = build
+            .steps
+            .iter()
+            .filter_map(|step| step.uses.as_deref())
+            .collect();
+        assert!(uses.contains(&"actions/cache@v4"));
+        assert!(uses.contains(&"actions/upload-artifact@v4"));
+        assert!(github.jobs["deploy"]
+            .steps
+            .iter()
+            .any(|step| step.uses.as_deref() == Some("actions/download-artifact@v4")));
+
+        let gitlab = GitLabAdapter::lower(&pipeline, &mut notes);
+        let build = &gitlab.jobs["build"];
+        assert!(build.parallel.is_some());
+        assert!(build.cache.is_some());
+        assert!(build.artifacts.is_some());
+
+        let circleci = CircleCIAdapter::lower(&pipeline, &mut notes);
+        assert!(circleci.jobs["build"].parameters.is_some());
+        assert!(circleci.workflows["main"].jobs.iter().any(|entry| matches!(
+            entry,
+            CircleCIWorkflowJob::WithRequires { job } if job.get("build").is_some_and(|build| build.matrix.is_some())
+        )));
+
+        notes.clear();
+        let jenkins = JenkinsAdapter::lower(&pipeline, &mut notes);
+        assert!(jenkins.stages[0]
+            .steps
+            .contains(&"archiveArtifacts artifacts: 'target/release/**'".to_string()));
+        assert!(notes.iter().any(|note| note.contains("matrix was dropped")));
+    }
+
+    #[test]
+    fn test_jenkins_source_is_rejected() {
+        assert!(migrate("pipeline {}", Platform::Jenkins, Platform::GitHub).is_err());
+    }
+}
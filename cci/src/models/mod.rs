@@ -0,0 +1,131 @@
+//! Platform-neutral pipeline model
+//!
+//! Adapters lift a platform's config into a [`Pipeline`] and lower it back
+//! out, so a pipeline written for one platform can be re-serialized for
+//! another. Presets implementing [`ToPipeline`](crate::traits::ToPipeline)
+//! build one directly and are lowered the same way for every platform, and
+//! [`steps`] has the step sequences presets assemble their jobs from.
+
+pub mod adapters;
+pub mod steps;
+
+use crate::platforms::artifacts::Artifact;
+use crate::platforms::cache::CacheStrategy;
+use crate::platforms::matrix::Matrix;
+use crate::platforms::services::Service;
+use crate::platforms::triggers::TriggerConfig;
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+
+/// A CI pipeline independent of any platform's syntax
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Pipeline {
+    /// When the pipeline runs; its `name` is the pipeline's display name
+    pub triggers: TriggerConfig,
+    /// Variables set for every job
+    pub env: BTreeMap<String, String>,
+    /// Jobs in dependency order
+    pub jobs: Vec<Job>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Job {
+    pub id: String,
+    /// Display name, on platforms that show one instead of the ID
+    pub name: Option<String>,
+    /// Stage the job belongs to, on platforms that group jobs into stages
+    pub stage: Option<String>,
+    /// Container image the steps run in
+    pub image: Option<String>,
+    /// Runner label for jobs that run directly on a VM
+    pub runner: Option<String>,
+    /// Jobs that must finish first
+    pub needs: Vec<String>,
+    pub env: BTreeMap<String, String>,
+    pub timeout_minutes: Option<u32>,
+    /// Axes the job fans out over, running once per combination
+    pub matrix: Option<Matrix>,
+    /// Containers started next to the job, such as a test database
+    pub services: Vec<Service>,
+    /// Dependency cache restored before the job's commands and saved after them
+    pub cache: Option<CacheStrategy>,
+    /// Files the job keeps, which the jobs that need it get too
+    pub artifacts: Vec<Artifact>,
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// Check out the repository
+    Checkout,
+    /// Run a shell command
+    Run {
+        name: Option<String>,
+        command: String,
+    },
+    /// Use a GitHub Action, which only GitHub-style workflows can run
+    Action {
+        name: Option<String>,
+        uses: String,
+        with: BTreeMap<String, Value>,
+    },
+}
+
+impl Step {
+    /// Short description for messages about the step
+    pub fn label(&self) -> String {
+        match self {
+            Step::Checkout => "checkout".to_string(),
+            Step::Run { name, command } => name
+                .clone()
+                .unwrap_or_else(|| command.lines().next().unwrap_or_default().to_string()),
+            Step::Action { name, uses, .. } => name.clone().unwrap_or_else(|| uses.clone()),
+        }
+    }
+}
+
+impl Pipeline {
+    /// Order jobs so each one follows the jobs it needs, otherwise keeping their order
+    ///
+    /// Jobs caught in a dependency cycle keep their relative order at the end.
+    pub fn sort_jobs(&mut self) {
+        let mut pending = std::mem::take(&mut self.jobs);
+        while !pending.is_empty() {
+            let ready = pending.iter().position(|job| {
+                job.needs
+                    .iter()
+                    .all(|need| !pending.iter().any(|other| other.id == *need))
+            });
+            match ready {
+                Some(index) => self.jobs.push(pending.remove(index)),
+                None => self.jobs.append(&mut pending),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_jobs_follows_needs() {
+        let job = |id: &str, needs: &[&str]| Job {
+            id: id.to_string(),
+            needs: needs.iter().map(|need| need.to_string()).collect(),
+            ..Job::default()
+        };
+        let mut pipeline = Pipeline {
+            jobs: vec![
+                job("deploy", &["build"]),
+                job("build", &["test"]),
+                job("test", &[]),
+            ],
+            ..Pipeline::default()
+        };
+        pipeline.sort_jobs();
+
+        let ids: Vec<&str> = pipeline.jobs.iter().map(|job| job.id.as_str()).collect();
+        assert_eq!(ids, ["test", "build", "deploy"]);
+    }
+}
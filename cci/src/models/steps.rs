@@ -0,0 +1,211 @@
+//! Step sequences shared by presets, as pipeline steps
+//!
+//! Most jobs start the same way: check out the code, set up the language's
+//! toolchain, restore the dependency cache. Presets assemble their jobs from
+//! these blocks instead of spelling each step out, and the platform adapters
+//! in [`adapters`](super::adapters) lower them for every backend. Actions
+//! only run on GitHub and Gitea, so presets pick shell steps for the others.
+//! Steps the model can't describe, like ones with their own environment or
+//! condition, are still patched into the lowered config per platform.
+
+use crate::models::Step;
+use crate::platforms::cache::{CacheMode, CacheStrategy, CacheToolchain};
+use crate::platforms::coverage::CoverageUpload;
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+
+/// Language toolchain a job sets up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Toolchain {
+    Rust,
+    Python,
+    Go,
+}
+
+impl Toolchain {
+    /// Step name, setup action, and the action's version input
+    fn action(self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            Toolchain::Rust => (
+                "Setup Rust toolchain",
+                "dtolnay/rust-toolchain@v1",
+                "toolchain",
+            ),
+            Toolchain::Python => ("Setup Python", "actions/setup-python@v5", "python-version"),
+            Toolchain::Go => ("Setup Go", "actions/setup-go@v5", "go-version"),
+        }
+    }
+
+    /// Action installing `version`, with extra inputs such as cache settings
+    /// or rustup components
+    pub fn setup(
+        self,
+        version: impl Into<String>,
+        inputs: impl IntoIterator<Item = (String, Value)>,
+    ) -> Step {
+        let (name, uses, key) = self.action();
+        let mut with = BTreeMap::from([(key.to_string(), Value::String(version.into()))]);
+        with.extend(inputs);
+        Step::Action {
+            name: Some(name.to_string()),
+            uses: uses.to_string(),
+            with,
+        }
+    }
+}
+
+/// Check out the repository
+pub fn checkout() -> Step {
+    Step::Checkout
+}
+
+/// Named shell command
+pub fn run(name: impl Into<String>, command: impl Into<String>) -> Step {
+    Step::Run {
+        name: Some(name.into()),
+        command: command.into(),
+    }
+}
+
+/// Step using a GitHub Action
+pub fn action(
+    name: impl Into<String>,
+    uses: impl Into<String>,
+    inputs: impl IntoIterator<Item = (String, Value)>,
+) -> Step {
+    Step::Action {
+        name: Some(name.into()),
+        uses: uses.into(),
+        with: inputs.into_iter().collect(),
+    }
+}
+
+/// Dependency cache steps to run after the toolchain is set up
+///
+/// In action mode setup-python and setup-go cache through their own inputs,
+/// from [`CacheStrategy::github_setup_inputs`], so only Rust adds a step.
+pub fn cache(strategy: &CacheStrategy) -> Vec<Step> {
+    let action = |uses: &str, with: BTreeMap<String, Value>| Step::Action {
+        name: Some("Cache dependencies".to_string()),
+        uses: uses.to_string(),
+        with,
+    };
+    match (strategy.mode, strategy.toolchain) {
+        (CacheMode::Action, CacheToolchain::Rust) => {
+            vec![action("Swatinem/rust-cache@v2", BTreeMap::new())]
+        }
+        (CacheMode::Action, _) => vec![],
+        (CacheMode::Manual, _) => {
+            let prefix = format!("${{{{ runner.os }}}}-{}-", strategy.name);
+            vec![action(
+                "actions/cache@v4",
+                BTreeMap::from([
+                    ("path".to_string(), strategy.paths.join("\n").into()),
+                    (
+                        "key".to_string(),
+                        format!(
+                            "{}${{{{ hashFiles('**/{}') }}}}",
+                            prefix, strategy.lock_file
+                        )
+                        .into(),
+                    ),
+                    ("restore-keys".to_string(), prefix.into()),
+                ]),
+            )]
+        }
+    }
+}
+
+/// Upload of a coverage report with the provider's CLI, which reads its
+/// token from the job's environment
+pub fn coverage_upload(upload: &CoverageUpload) -> Step {
+    run(upload.step_name(), upload.command())
+}
+
+/// Write the commits since the previous tag to CHANGELOG.md, for a release
+/// to use as its notes
+pub fn changelog(tag: &str) -> Step {
+    run("Generate changelog", changelog_script(tag))
+}
+
+/// Shell command behind [`changelog`]
+///
+/// Avoids single quotes so it can be embedded in a Jenkins `sh '...'` step.
+pub fn changelog_script(tag: &str) -> String {
+    format!(
+        "previous=$(git describe --tags --abbrev=0 \"{tag}^\" 2>/dev/null || true); \
+         git log --pretty=format:\"- %s (%h)\" ${{previous:+\"$previous..\"}}\"{tag}\" > CHANGELOG.md"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::adapters::{
+        CircleCIAdapter, GitHubAdapter, GitLabAdapter, JenkinsAdapter, PlatformAdapter,
+    };
+    use crate::models::{Job, Pipeline};
+    use crate::platforms::circleci::models::{CircleCIRun, CircleCIStep};
+    use crate::platforms::github::models::GitHubStep;
+
+    /// One job running `steps`, with the dependency cache for Rust
+    fn pipeline(steps: Vec<Step>) -> Pipeline {
+        Pipeline {
+            triggers: Default::default(),
+            env: Default::default(),
+            jobs: vec![Job {
+                id: "test".to_string(),
+                name: None,
+                stage: None,
+                image: None,
+                runner: None,
+                needs: Vec::new(),
+                env: Default::default(),
+                timeout_minutes: None,
+                matrix: None,
+                services: Vec::new(),
+                cache: Some(CacheStrategy::rust(CacheMode::Action)),
+                artifacts: Vec::new(),
+                steps,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_blocks_lower_to_the_steps_presets_write() {
+        let mut notes = Vec::new();
+        let github = GitHubAdapter::lower(
+            &pipeline(vec![
+                checkout(),
+                Toolchain::Go.setup("1.22", [("cache".to_string(), false.into())]),
+                run("Run tests", "go test ./..."),
+            ]),
+            &mut notes,
+        );
+        let steps = &github.jobs["test"].steps;
+        assert_eq!(steps[0], GitHubStep::checkout());
+        assert_eq!(steps[1].name.as_deref(), Some("Setup Go"));
+        assert_eq!(steps[1].uses.as_deref(), Some("actions/setup-go@v5"));
+        let with = steps[1].with.as_ref().unwrap();
+        assert_eq!(with["go-version"], "1.22");
+        assert_eq!(with["cache"], false);
+        // The job's cache goes in before its first command
+        assert_eq!(steps[2].uses.as_deref(), Some("Swatinem/rust-cache@v2"));
+        assert!(steps[2].with.is_none());
+        assert!(cache(&CacheStrategy::python(CacheMode::Action)).is_empty());
+
+        // Shell blocks lower on every backend
+        let shell = pipeline(vec![checkout(), run("Run tests", "cargo test")]);
+        let gitlab = GitLabAdapter::lower(&shell, &mut notes);
+        assert_eq!(gitlab.jobs["test"].script, ["cargo test"]);
+        let circleci = CircleCIAdapter::lower(&shell, &mut notes);
+        let steps = &circleci.jobs["test"].steps;
+        assert!(matches!(&steps[0], CircleCIStep::Simple(step) if step == "checkout"));
+        assert!(steps.iter().any(|step| matches!(
+            step,
+            CircleCIStep::Command { run: CircleCIRun::Detailed { command, .. } } if command == "cargo test"
+        )));
+        let jenkins = JenkinsAdapter::lower(&shell, &mut notes);
+        assert_eq!(jenkins.stages[0].steps, ["sh 'cargo test'"]);
+    }
+}
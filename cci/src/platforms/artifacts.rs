@@ -0,0 +1,377 @@
+use crate::platforms::circleci::models::{
+    CircleCIConfig, CircleCIJobRequires, CircleCIStep, CircleCIWorkflowJob,
+    CircleCIWorkspaceAttach, CircleCIWorkspacePersist,
+};
+use crate::platforms::github::models::{GitHubJob, GitHubStep};
+use crate::platforms::gitlab::models::{GitLabArtifacts, GitLabJob};
+use crate::platforms::jobs::Jobs;
+use std::collections::BTreeMap;
+
+/// Files one job produces for jobs that run after it
+///
+/// The producing job declares the artifact and downstream jobs consume it;
+/// each `apply_*` method emits the platform's upload/download mechanism and
+/// the job ordering needed for the files to be available.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Artifact {
+    pub name: String,
+    /// Paths relative to the checkout; directories end with `/`
+    pub paths: Vec<String>,
+}
+
+impl Artifact {
+    pub fn new(name: impl Into<String>, paths: &[&str]) -> Self {
+        Self {
+            name: name.into(),
+            paths: paths.iter().map(|path| path.to_string()).collect(),
+        }
+    }
+
+    /// Deepest directory containing every path
+    ///
+    /// `actions/upload-artifact` strips this prefix, so downloads must be
+    /// placed back under it.
+    fn common_root(&self) -> String {
+        let mut dirs = self.paths.iter().map(|path| {
+            let mut parts: Vec<&str> = path.split('/').filter(|p| !p.is_empty()).collect();
+            if !path.ends_with('/') {
+                parts.pop();
+            }
+            parts
+        });
+        let Some(mut root) = dirs.next() else {
+            return ".".to_string();
+        };
+        for dir in dirs {
+            let common = root.iter().zip(&dir).take_while(|(a, b)| a == b).count();
+            root.truncate(common);
+        }
+
+        if root.is_empty() {
+            ".".to_string()
+        } else {
+            root.join("/")
+        }
+    }
+
+    /// `actions/upload-artifact` step for the producing job
+    pub fn github_upload_step(&self) -> GitHubStep {
+        GitHubStep::action_with_config(
+            format!("Upload {}", self.name),
+            "actions/upload-artifact@v4",
+            BTreeMap::from([
+                ("name".to_string(), self.name.clone().into()),
+                ("path".to_string(), self.paths.join("\n").into()),
+            ]),
+        )
+    }
+
+    /// `actions/download-artifact` step for a consuming job
+    pub fn github_download_step(&self) -> GitHubStep {
+        GitHubStep::action_with_config(
+            format!("Download {}", self.name),
+            "actions/download-artifact@v4",
+            BTreeMap::from([
+                ("name".to_string(), self.name.clone().into()),
+                ("path".to_string(), self.common_root().into()),
+            ]),
+        )
+    }
+
+    /// GitLab job `artifacts:` block for the producing job
+    pub fn to_gitlab(&self) -> GitLabArtifacts {
+        GitLabArtifacts {
+            paths: self.paths.clone(),
+            name: Some(self.name.clone()),
+        }
+    }
+
+    /// Upload from `producer` and download in each consumer, which then needs the producer
+    ///
+    /// Jobs that don't exist are skipped so presets can pass optional jobs.
+    pub fn apply_github(&self, jobs: &mut Jobs<GitHubJob>, producer: &str, consumers: &[&str]) {
+        let Some(job) = jobs.get_mut(producer) else {
+            return;
+        };
+        job.steps.push(self.github_upload_step());
+
+        for consumer in consumers {
+            let Some(job) = jobs.get_mut(consumer) else {
+                continue;
+            };
+            // Download after checkout so the files aren't overwritten
+            let position = job
+                .steps
+                .iter()
+                .position(|step| {
+                    step.uses
+                        .as_deref()
+                        .is_some_and(|uses| uses.starts_with("actions/checkout@"))
+                })
+                .map_or(0, |i| i + 1);
+            job.steps.insert(position, self.github_download_step());
+            add_need(job.needs.get_or_insert_with(Vec::new), producer);
+        }
+    }
+
+    /// Keep the files as job artifacts; consumers list the producer in `needs`,
+    /// which makes GitLab download them
+    pub fn apply_gitlab(&self, jobs: &mut Jobs<GitLabJob>, producer: &str, consumers: &[&str]) {
+        let Some(job) = jobs.get_mut(producer) else {
+            return;
+        };
+        match &mut job.artifacts {
+            Some(artifacts) => {
+                for path in &self.paths {
+                    if !artifacts.paths.contains(path) {
+                        artifacts.paths.push(path.clone());
+                    }
+                }
+            }
+            None => job.artifacts = Some(self.to_gitlab()),
+        }
+
+        for consumer in consumers {
+            if let Some(job) = jobs.get_mut(consumer) {
+                add_need(job.needs.get_or_insert_with(Vec::new), producer);
+            }
+        }
+    }
+
+    /// Persist the files to the workflow workspace and attach it in each consumer
+    ///
+    /// Consumers also gain a `requires` on the producer in every workflow that
+    /// runs them, since the workspace is only shared downstream.
+    pub fn apply_circleci(&self, config: &mut CircleCIConfig, producer: &str, consumers: &[&str]) {
+        let Some(job) = config.jobs.get_mut(producer) else {
+            return;
+        };
+        job.steps.push(CircleCIStep::PersistToWorkspace {
+            persist_to_workspace: CircleCIWorkspacePersist {
+                root: ".".to_string(),
+                paths: self.paths.clone(),
+            },
+        });
+
+        for consumer in consumers {
+            let Some(job) = config.jobs.get_mut(consumer) else {
+                continue;
+            };
+            let position = job
+                .steps
+                .iter()
+                .position(|step| matches!(step, CircleCIStep::Simple(s) if s == "checkout"))
+                .map_or(0, |i| i + 1);
+            job.steps.insert(
+                position,
+                CircleCIStep::AttachWorkspace {
+                    attach_workspace: CircleCIWorkspaceAttach {
+                        at: ".".to_string(),
+                    },
+                },
+            );
+
+            for workflow in config.workflows.values_mut() {
+                for entry in &mut workflow.jobs {
+                    require(entry, consumer, producer);
+                }
+            }
+        }
+    }
+}
+
+fn add_need(needs: &mut Vec<String>, producer: &str) {
+    if !needs.iter().any(|need| need == producer) {
+        needs.push(producer.to_string());
+    }
+}
+
+/// Make a workflow entry for `consumer` require `producer`
+fn require(entry: &mut CircleCIWorkflowJob, consumer: &str, producer: &str) {
+    if let CircleCIWorkflowJob::Simple(name) = entry {
+        if name != consumer {
+            return;
+        }
+        *entry = CircleCIWorkflowJob::WithRequires {
+            job: BTreeMap::from([(
+                name.clone(),
+                CircleCIJobRequires {
+                    requires: vec![],
+                    filters: None,
+                    matrix: None,
+                    job_type: None,
+                },
+            )]),
+        };
+    }
+    if let CircleCIWorkflowJob::WithRequires { job } = entry {
+        if let Some(requires) = job.get_mut(consumer) {
+            add_need(&mut requires.requires, producer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platforms::circleci::models::{CircleCIDocker, CircleCIJob, CircleCIWorkflow};
+
+    fn artifact() -> Artifact {
+        Artifact::new("dist", &["dist/app.tar.gz", "dist/checksums.txt"])
+    }
+
+    fn github_job(steps: Vec<GitHubStep>) -> GitHubJob {
+        GitHubJob {
+            runs_on: "ubuntu-latest".into(),
+            container: None,
+            permissions: None,
+            strategy: None,
+            services: None,
+            env: None,
+            steps,
+            needs: None,
+            timeout_minutes: None,
+            continue_on_error: None,
+            if_condition: None,
+            environment: None,
+        }
+    }
+
+    fn circleci_job() -> CircleCIJob {
+        CircleCIJob {
+            docker: vec![CircleCIDocker {
+                image: "cimg/base:stable".to_string(),
+                environment: None,
+            }],
+            machine: None,
+            macos: None,
+            resource_class: None,
+            parameters: None,
+            steps: vec![CircleCIStep::Simple("checkout".to_string())],
+            environment: None,
+        }
+    }
+
+    #[test]
+    fn test_common_root() {
+        assert_eq!(artifact().common_root(), "dist");
+        assert_eq!(
+            Artifact::new("release", &["target/release/"]).common_root(),
+            "target/release"
+        );
+        assert_eq!(
+            Artifact::new("mixed", &["dist/app", "docs/index.html"]).common_root(),
+            "."
+        );
+    }
+
+    #[test]
+    fn test_apply_github() {
+        let mut jobs = Jobs::from([
+            (
+                "build".to_string(),
+                github_job(vec![GitHubStep::checkout()]),
+            ),
+            (
+                "publish".to_string(),
+                github_job(vec![GitHubStep::checkout()]),
+            ),
+        ]);
+        artifact().apply_github(&mut jobs, "build", &["publish", "missing"]);
+
+        let upload = jobs["build"].steps.last().unwrap();
+        assert_eq!(upload.uses.as_deref(), Some("actions/upload-artifact@v4"));
+
+        let publish = &jobs["publish"];
+        assert_eq!(
+            publish.steps[1].uses.as_deref(),
+            Some("actions/download-artifact@v4")
+        );
+        assert_eq!(
+            publish.steps[1].with.as_ref().unwrap()["path"],
+            serde_yaml::Value::String("dist".to_string())
+        );
+        assert_eq!(publish.needs, Some(vec!["build".to_string()]));
+    }
+
+    #[test]
+    fn test_apply_gitlab() {
+        let job = |stage: &str| GitLabJob {
+            stage: stage.to_string(),
+            image: None,
+            services: None,
+            variables: None,
+            script: vec![],
+            before_script: None,
+            after_script: None,
+            needs: None,
+            cache: None,
+            artifacts: None,
+            only: None,
+            tags: None,
+            parallel: None,
+            timeout: None,
+            retry: None,
+            allow_failure: None,
+            rules: None,
+            when: None,
+            interruptible: None,
+            coverage: None,
+            environment: None,
+        };
+        let mut jobs = Jobs::from([
+            ("build".to_string(), job("build")),
+            ("publish".to_string(), job("deploy")),
+        ]);
+        artifact().apply_gitlab(&mut jobs, "build", &["publish"]);
+
+        assert_eq!(jobs["build"].artifacts, Some(artifact().to_gitlab()));
+        assert_eq!(jobs["publish"].needs, Some(vec!["build".to_string()]));
+    }
+
+    #[test]
+    fn test_apply_circleci() {
+        let mut config = CircleCIConfig {
+            version: "2.1".to_string(),
+            orbs: None,
+            parameters: None,
+            jobs: Jobs::from([
+                ("build".to_string(), circleci_job()),
+                ("publish".to_string(), circleci_job()),
+            ]),
+            workflows: BTreeMap::from([(
+                "main".to_string(),
+                CircleCIWorkflow {
+                    triggers: None,
+                    jobs: vec![
+                        CircleCIWorkflowJob::Simple("build".to_string()),
+                        CircleCIWorkflowJob::Simple("publish".to_string()),
+                    ],
+                },
+            )]),
+        };
+        artifact().apply_circleci(&mut config, "build", &["publish"]);
+
+        assert!(matches!(
+            config.jobs["build"].steps.last(),
+            Some(CircleCIStep::PersistToWorkspace { .. })
+        ));
+        assert!(matches!(
+            config.jobs["publish"].steps[1],
+            CircleCIStep::AttachWorkspace { .. }
+        ));
+        let workflow = &config.workflows["main"];
+        assert_eq!(
+            workflow.jobs[0],
+            CircleCIWorkflowJob::Simple("build".to_string())
+        );
+        let CircleCIWorkflowJob::WithRequires { job } = &workflow.jobs[1] else {
+            panic!("expected publish to require build");
+        };
+        assert_eq!(job["publish"].requires, vec!["build".to_string()]);
+
+        let yaml = serde_yaml::to_string(&config).unwrap();
+        assert!(yaml.contains("persist_to_workspace:"));
+        assert!(yaml.contains("attach_workspace:"));
+    }
+}
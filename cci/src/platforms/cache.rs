@@ -0,0 +1,212 @@
+use crate::models::steps;
+use crate::platforms::circleci::models::{CircleCICache, CircleCICacheSave, CircleCIStep};
+use crate::platforms::github::models::GitHubStep;
+use crate::platforms::gitlab::models::{GitLabCache, GitLabCacheKey, GitLabJob};
+use cci_macros::PresetEnum;
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+
+/// How dependency caches are set up on GitHub-style workflows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, PresetEnum)]
+#[preset_enum(default = "Action")]
+#[serde(rename_all = "lowercase")]
+pub enum CacheMode {
+    /// Let the toolchain's action handle caching (rust-cache, setup-python, setup-go)
+    #[preset_variant(id = "action", display = "Action-based")]
+    Action,
+    /// Explicit `actions/cache` keys and paths
+    #[preset_variant(id = "manual", display = "Manual (actions/cache)")]
+    Manual,
+}
+
+/// Toolchain a cache belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheToolchain {
+    Rust,
+    Python,
+    Go,
+}
+
+/// Language-aware dependency cache shared by all platform backends
+///
+/// GitLab and CircleCI have no caching actions, so both modes map to their
+/// native lock-file keyed caches there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheStrategy {
+    pub mode: CacheMode,
+    pub toolchain: CacheToolchain,
+    /// Prefix used in cache keys (e.g. `cargo`)
+    pub name: &'static str,
+    /// Lock file whose contents key the cache
+    pub lock_file: &'static str,
+    /// Directories cached on VM runners
+    pub paths: Vec<&'static str>,
+    /// Directories cached in the official language images CircleCI jobs use
+    pub container_paths: Vec<&'static str>,
+    /// Directories cached on GitLab, which only caches inside the project
+    pub project_paths: Vec<&'static str>,
+    /// Variables that move the tool's cache into `project_paths` on GitLab
+    pub project_env: Vec<(&'static str, &'static str)>,
+}
+
+impl CacheStrategy {
+    pub fn rust(mode: CacheMode) -> Self {
+        Self {
+            mode,
+            toolchain: CacheToolchain::Rust,
+            name: "cargo",
+            lock_file: "Cargo.lock",
+            paths: vec!["~/.cargo/registry", "~/.cargo/git", "target"],
+            container_paths: vec![
+                "/usr/local/cargo/registry",
+                "/usr/local/cargo/git",
+                "target",
+            ],
+            project_paths: vec!["target/", ".cargo/"],
+            project_env: vec![],
+        }
+    }
+
+    pub fn python(mode: CacheMode) -> Self {
+        Self {
+            mode,
+            toolchain: CacheToolchain::Python,
+            name: "pip",
+            lock_file: "requirements.txt",
+            paths: vec!["~/.cache/pip"],
+            container_paths: vec!["~/.cache/pip"],
+            project_paths: vec![".cache/pip/"],
+            project_env: vec![("PIP_CACHE_DIR", "$CI_PROJECT_DIR/.cache/pip")],
+        }
+    }
+
+    pub fn go(mode: CacheMode) -> Self {
+        Self {
+            mode,
+            toolchain: CacheToolchain::Go,
+            name: "go",
+            lock_file: "go.sum",
+            paths: vec!["~/go/pkg/mod", "~/.cache/go-build"],
+            container_paths: vec!["/go/pkg/mod", "~/.cache/go-build"],
+            project_paths: vec![".go/pkg/mod/"],
+            project_env: vec![("GOPATH", "$CI_PROJECT_DIR/.go")],
+        }
+    }
+
+    /// Extra inputs for the toolchain setup action (setup-python, setup-go)
+    pub fn github_setup_inputs(&self) -> Vec<(String, Value)> {
+        match (self.mode, self.toolchain) {
+            (CacheMode::Action, CacheToolchain::Python) => {
+                vec![("cache".to_string(), "pip".into())]
+            }
+            // setup-go caches by default, which would duplicate the manual cache
+            (CacheMode::Manual, CacheToolchain::Go) => vec![("cache".to_string(), false.into())],
+            _ => vec![],
+        }
+    }
+
+    /// Cache steps to run after the toolchain is set up
+    pub fn github_steps(&self) -> Vec<GitHubStep> {
+        steps::cache(self).iter().map(GitHubStep::from).collect()
+    }
+
+    /// GitLab `cache:` keyed on the lock file
+    pub fn to_gitlab(&self) -> GitLabCache {
+        GitLabCache {
+            key: GitLabCacheKey::Files {
+                files: vec![self.lock_file.to_string()],
+            },
+            paths: self.project_paths.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    /// Set the job's cache and the variables that point the tool at it
+    pub fn apply_gitlab(&self, job: &mut GitLabJob) {
+        job.cache = Some(self.to_gitlab());
+        if !self.project_env.is_empty() {
+            job.variables.get_or_insert_with(BTreeMap::new).extend(
+                self.project_env
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string())),
+            );
+        }
+    }
+
+    fn circleci_key(&self) -> String {
+        format!("v1-{}-{{{{ checksum \"{}\" }}}}", self.name, self.lock_file)
+    }
+
+    /// CircleCI `restore_cache`, falling back to the newest cache for any lock file
+    pub fn circleci_restore_step(&self) -> CircleCIStep {
+        CircleCIStep::Cache {
+            restore_cache: CircleCICache {
+                keys: vec![self.circleci_key(), format!("v1-{}-", self.name)],
+            },
+        }
+    }
+
+    /// CircleCI `save_cache` for the end of the job
+    pub fn circleci_save_step(&self) -> CircleCIStep {
+        CircleCIStep::SaveCache {
+            save_cache: CircleCICacheSave {
+                key: self.circleci_key(),
+                paths: self.container_paths.iter().map(|p| p.to_string()).collect(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_github_action_mode() {
+        let rust = CacheStrategy::rust(CacheMode::Action);
+        assert_eq!(
+            rust.github_steps()[0].uses.as_deref(),
+            Some("Swatinem/rust-cache@v2")
+        );
+        assert!(rust.github_setup_inputs().is_empty());
+
+        let python = CacheStrategy::python(CacheMode::Action);
+        assert!(python.github_steps().is_empty());
+        assert_eq!(
+            python.github_setup_inputs(),
+            vec![("cache".to_string(), Value::from("pip"))]
+        );
+    }
+
+    #[test]
+    fn test_github_manual_mode() {
+        let go = CacheStrategy::go(CacheMode::Manual);
+        assert_eq!(
+            go.github_setup_inputs(),
+            vec![("cache".to_string(), Value::from(false))]
+        );
+
+        let steps = go.github_steps();
+        assert_eq!(steps[0].uses.as_deref(), Some("actions/cache@v4"));
+        let with = steps[0].with.as_ref().unwrap();
+        assert_eq!(
+            with["key"],
+            Value::from("${{ runner.os }}-go-${{ hashFiles('**/go.sum') }}")
+        );
+        assert_eq!(with["path"], Value::from("~/go/pkg/mod\n~/.cache/go-build"));
+    }
+
+    #[test]
+    fn test_gitlab_and_circleci_keys() {
+        let python = CacheStrategy::python(CacheMode::Action);
+        let yaml = serde_yaml::to_string(&python.to_gitlab()).unwrap();
+        assert!(yaml.contains("files:\n  - requirements.txt"));
+
+        let CircleCIStep::Cache { restore_cache } = python.circleci_restore_step() else {
+            panic!("expected restore_cache step");
+        };
+        assert_eq!(
+            restore_cache.keys,
+            vec!["v1-pip-{{ checksum \"requirements.txt\" }}", "v1-pip-"]
+        );
+    }
+}
@@ -0,0 +1,98 @@
+//! Checks that CI runs and developers can run locally
+//!
+//! Presets list the commands behind their jobs so git hooks and task runners
+//! can invoke exactly what the pipeline does, without going through a
+//! platform config.
+
+use crate::editor::config::PresetConfig;
+use crate::editor::registry::PresetRegistry;
+
+/// What a check verifies, which decides when it is worth running locally
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CheckKind {
+    /// Formatting is already applied (`--check` mode, never rewrites files)
+    Format,
+    /// Static analysis: linters and type checkers
+    Lint,
+    Test,
+    /// Dependency and security audits
+    Audit,
+    Build,
+}
+
+/// A command one of the generated jobs runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalCheck {
+    /// Job-style identifier, e.g. `rust/lint`
+    pub id: String,
+    /// Human-readable description
+    pub name: String,
+    pub kind: CheckKind,
+    /// Shell command, run from the project root
+    pub command: String,
+    /// File extensions whose changes make the check relevant (empty for any)
+    pub extensions: Vec<&'static str>,
+}
+
+impl LocalCheck {
+    pub fn new(
+        id: &str,
+        name: &str,
+        kind: CheckKind,
+        command: impl Into<String>,
+        extensions: &[&'static str],
+    ) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            kind,
+            command: command.into(),
+            extensions: extensions.to_vec(),
+        }
+    }
+}
+
+/// Checks of every configured preset, in config order, each ID only once
+pub fn collect_checks(
+    preset_configs: &[(String, PresetConfig)],
+    registry: &PresetRegistry,
+    language_version: &str,
+) -> Vec<LocalCheck> {
+    let mut checks: Vec<LocalCheck> = Vec::new();
+    for (preset_id, config) in preset_configs {
+        let Some(preset) = registry.get(preset_id) else {
+            continue;
+        };
+        for check in preset.local_checks(config, language_version) {
+            if !checks.iter().any(|known| known.id == check.id) {
+                checks.push(check);
+            }
+        }
+    }
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::config::OptionValue;
+    use crate::editor::registry::build_registry;
+
+    #[test]
+    fn test_collect_checks_follows_preset_options() {
+        let registry = build_registry();
+        let mut rust = registry.get("rust").unwrap().default_config(true);
+        rust.set("enable_linter".to_string(), OptionValue::Bool(false));
+        let release = registry.get("release").unwrap().default_config(true);
+        let configs = vec![("rust".to_string(), rust), ("release".to_string(), release)];
+
+        let ids: Vec<String> = collect_checks(&configs, &registry, "stable")
+            .into_iter()
+            .map(|check| check.id)
+            .collect();
+        assert_eq!(
+            ids,
+            vec!["rust/format", "rust/test", "rust/security", "rust/build"]
+        );
+    }
+}
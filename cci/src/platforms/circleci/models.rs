@@ -1,3 +1,4 @@
+use crate::platforms::jobs::Jobs;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
@@ -6,13 +7,25 @@ pub struct CircleCIConfig {
     pub version: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub orbs: Option<BTreeMap<String, String>>,
-    pub jobs: BTreeMap<String, CircleCIJob>,
+    /// Pipeline parameters, which can be set when a pipeline is triggered
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<BTreeMap<String, CircleCIParameter>>,
+    pub jobs: Jobs<CircleCIJob>,
     pub workflows: BTreeMap<String, CircleCIWorkflow>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CircleCIJob {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub docker: Vec<CircleCIDocker>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub machine: Option<CircleCIMachine>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub macos: Option<CircleCIMacos>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_class: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<BTreeMap<String, CircleCIParameter>>,
     pub steps: Vec<CircleCIStep>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub environment: Option<BTreeMap<String, String>>,
@@ -21,6 +34,33 @@ pub struct CircleCIJob {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CircleCIDocker {
     pub image: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub environment: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CircleCIParameter {
+    #[serde(rename = "type")]
+    pub param_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+}
+
+/// Machine executor: `machine: true` (self-hosted) or a VM image
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CircleCIMachine {
+    Enabled(bool),
+    Image {
+        image: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        shell: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CircleCIMacos {
+    pub xcode: String,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -38,13 +78,34 @@ pub enum CircleCIStep {
         #[serde(rename = "save_cache")]
         save_cache: CircleCICacheSave,
     },
+    PersistToWorkspace {
+        persist_to_workspace: CircleCIWorkspacePersist,
+    },
+    AttachWorkspace {
+        attach_workspace: CircleCIWorkspaceAttach,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum CircleCIRun {
     Simple(String),
-    Detailed { name: String, command: String },
+    /// Runs only when earlier steps ended a certain way (`on_fail`, `on_success`, `always`)
+    Conditional {
+        name: String,
+        command: String,
+        when: String,
+    },
+    /// Fails once the command has printed nothing for `no_output_timeout`, e.g. `30m`
+    Timed {
+        name: String,
+        command: String,
+        no_output_timeout: String,
+    },
+    Detailed {
+        name: String,
+        command: String,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -58,11 +119,52 @@ pub struct CircleCICacheSave {
     pub paths: Vec<String>,
 }
 
+/// Files handed to downstream jobs in the same workflow
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CircleCIWorkspacePersist {
+    pub root: String,
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CircleCIWorkspaceAttach {
+    pub at: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CircleCIWorkflow {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub triggers: Option<Vec<CircleCITrigger>>,
     pub jobs: Vec<CircleCIWorkflowJob>,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CircleCITrigger {
+    pub schedule: CircleCISchedule,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CircleCISchedule {
+    pub cron: String,
+    pub filters: CircleCIFilters,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CircleCIFilters {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branches: Option<CircleCIFilter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<CircleCIFilter>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CircleCIFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub only: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ignore: Option<Vec<String>>,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum CircleCIWorkflowJob {
@@ -75,5 +177,20 @@ pub enum CircleCIWorkflowJob {
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CircleCIJobRequires {
+    /// `approval` for a job that waits for someone to approve it in the UI
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub job_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub requires: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filters: Option<CircleCIFilters>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matrix: Option<CircleCIMatrix>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CircleCIMatrix {
+    pub parameters: BTreeMap<String, Vec<String>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<BTreeMap<String, String>>,
 }
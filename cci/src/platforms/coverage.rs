@@ -0,0 +1,263 @@
+//! Coverage report uploads shared by all platform backends
+//!
+//! The test job writes a report file; the upload step hands it to the chosen
+//! service. GitHub-style workflows use each service's action, the other
+//! platforms download the service's CLI and read the token from the
+//! environment.
+
+use crate::platforms::artifacts::Artifact;
+use crate::platforms::github::models::{GitHubJob, GitHubStep};
+use crate::platforms::gitlab::models::GitLabJob;
+use crate::platforms::jenkins::helpers::groovy_quote;
+use crate::platforms::jenkins::models::JenkinsConfig;
+use crate::platforms::secrets::{self, Secret};
+use cci_macros::PresetEnum;
+use std::collections::BTreeMap;
+
+/// Service coverage reports are uploaded to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, PresetEnum)]
+#[preset_enum(default = "Codecov")]
+#[serde(rename_all = "lowercase")]
+pub enum CoverageProvider {
+    #[preset_variant(id = "codecov", display = "Codecov")]
+    Codecov,
+    #[preset_variant(id = "coveralls", display = "Coveralls")]
+    Coveralls,
+    #[preset_variant(id = "codeclimate", display = "Code Climate")]
+    CodeClimate,
+}
+
+impl CoverageProvider {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CoverageProvider::Codecov => "Codecov",
+            CoverageProvider::Coveralls => "Coveralls",
+            CoverageProvider::CodeClimate => "Code Climate",
+        }
+    }
+
+    /// Token the upload authenticates with
+    pub fn secret(&self) -> Secret {
+        match self {
+            CoverageProvider::Codecov => secrets::CODECOV_TOKEN,
+            CoverageProvider::Coveralls => secrets::COVERALLS_REPO_TOKEN,
+            CoverageProvider::CodeClimate => secrets::CC_TEST_REPORTER_ID,
+        }
+    }
+}
+
+/// File format a test run writes its coverage report in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoverageFormat {
+    /// Cobertura XML (tarpaulin, pytest-cov)
+    Cobertura,
+    /// `go test -coverprofile` output
+    GoCover,
+}
+
+impl CoverageFormat {
+    /// Format name understood by the Code Climate test reporter
+    fn codeclimate_type(&self) -> &'static str {
+        match self {
+            CoverageFormat::Cobertura => "cobertura",
+            CoverageFormat::GoCover => "gocov",
+        }
+    }
+}
+
+/// Regexes GitLab's `coverage:` reads a test job's total from, keyed to the
+/// summary line each tool prints
+pub mod gitlab_total {
+    /// `85.00% coverage, 170/200 lines covered`
+    pub const TARPAULIN: &str = r"/(\d+\.\d+)% coverage/";
+    /// `TOTAL    200    30    85%`
+    pub const PYTEST_COV: &str = r"/^TOTAL.*\s(\d+(?:\.\d+)?)%$/";
+    /// `total:    (statements)    85.0%`, from `go tool cover -func`
+    pub const GO_COVER: &str = r"/^total:\s+\(statements\)\s+(\d+\.\d+)%/";
+}
+
+/// Upload of one coverage report to a provider
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverageUpload {
+    pub provider: CoverageProvider,
+    /// Report path relative to the checkout
+    pub report: &'static str,
+    pub format: CoverageFormat,
+}
+
+impl CoverageUpload {
+    pub fn new(provider: CoverageProvider, report: &'static str, format: CoverageFormat) -> Self {
+        Self {
+            provider,
+            report,
+            format,
+        }
+    }
+
+    /// Token the upload needs, whatever the platform
+    pub fn required_secrets(&self) -> Vec<Secret> {
+        vec![self.provider.secret()]
+    }
+
+    /// Name of the upload step on every platform
+    pub fn step_name(&self) -> String {
+        format!("Upload coverage to {}", self.provider.name())
+    }
+
+    fn secret_expression(&self) -> String {
+        format!("${{{{ secrets.{} }}}}", self.provider.secret().name)
+    }
+
+    /// Provider action step for GitHub-style workflows
+    pub fn github_step(&self) -> GitHubStep {
+        let secret = self.provider.secret().name.to_string();
+        match self.provider {
+            CoverageProvider::Codecov => GitHubStep::action_with_config(
+                self.step_name(),
+                "codecov/codecov-action@v4",
+                BTreeMap::from([
+                    ("token".to_string(), self.secret_expression().into()),
+                    ("files".to_string(), self.report.into()),
+                ]),
+            ),
+            // The reporter prefers an explicit repo token over the workflow's
+            // GITHUB_TOKEN, which also makes the step work on Gitea
+            CoverageProvider::Coveralls => GitHubStep {
+                env: Some(BTreeMap::from([(secret, self.secret_expression())])),
+                ..GitHubStep::action_with_config(
+                    self.step_name(),
+                    "coverallsapp/github-action@v2",
+                    BTreeMap::from([("file".to_string(), self.report.into())]),
+                )
+            },
+            CoverageProvider::CodeClimate => GitHubStep {
+                env: Some(BTreeMap::from([(secret, self.secret_expression())])),
+                ..GitHubStep::action_with_config(
+                    self.step_name(),
+                    "paambaati/codeclimate-action@v9",
+                    BTreeMap::from([(
+                        "coverageLocations".to_string(),
+                        format!("{}:{}", self.report, self.format.codeclimate_type()).into(),
+                    )]),
+                )
+            },
+        }
+    }
+
+    /// Shell command that uploads the report with the provider's CLI
+    ///
+    /// The CLIs read their token from the environment variable named after
+    /// the secret. The command contains no single quotes so it can be wrapped
+    /// in a Groovy `sh '...'`.
+    pub fn command(&self) -> String {
+        match self.provider {
+            CoverageProvider::Codecov => codecov_command(self.report),
+            CoverageProvider::Coveralls => format!(
+                "curl -sSfL https://coveralls.io/coveralls-linux.tar.gz | tar -xz && ./coveralls report {}",
+                self.report
+            ),
+            CoverageProvider::CodeClimate => format!(
+                "curl -sSfLo cc-test-reporter https://codeclimate.com/downloads/test-reporter/test-reporter-latest-linux-amd64 && chmod +x cc-test-reporter && ./cc-test-reporter format-coverage -t {} -o codeclimate.json {} && ./cc-test-reporter upload-coverage -i codeclimate.json",
+                self.format.codeclimate_type(),
+                self.report
+            ),
+        }
+    }
+
+    /// Jenkins step that binds the token credential around the upload
+    pub fn jenkins_step(&self) -> String {
+        let secret = self.provider.secret().name;
+        format!(
+            "withCredentials([string(credentialsId: '{secret}', variable: '{secret}')]) {{ sh '{}' }}",
+            self.command()
+        )
+    }
+
+    /// Swap the CLI upload pipelines run, from
+    /// [`steps::coverage_upload`](crate::models::steps::coverage_upload),
+    /// for the provider's action
+    pub fn apply_github(&self, job: &mut GitHubJob) {
+        let name = self.step_name();
+        for step in &mut job.steps {
+            if step.name.as_deref() == Some(name.as_str()) && step.uses.is_none() {
+                *step = self.github_step();
+            }
+        }
+    }
+
+    /// Read the job's total with `total`, one of the [`gitlab_total`]
+    /// regexes, and keep the report with the job
+    pub fn apply_gitlab(&self, job: &mut GitLabJob, total: &str) {
+        job.coverage = Some(total.to_string());
+        match &mut job.artifacts {
+            Some(artifacts) => artifacts.paths.push(self.report.to_string()),
+            None => job.artifacts = Some(Artifact::new("coverage", &[self.report]).to_gitlab()),
+        }
+    }
+
+    /// Bind the token around the CLI upload in every stage that runs it
+    pub fn apply_jenkins(&self, config: &mut JenkinsConfig) {
+        let upload = format!("sh {}", groovy_quote(&self.command()));
+        for step in config.stages.iter_mut().flat_map(|stage| &mut stage.steps) {
+            if *step == upload {
+                *step = self.jenkins_step();
+            }
+        }
+    }
+}
+
+/// Upload of `report` with the Codecov CLI, which reads `CODECOV_TOKEN`
+pub fn codecov_command(report: &str) -> String {
+    format!(
+        "curl -sSfLo codecov https://cli.codecov.io/latest/linux/codecov && chmod +x codecov && ./codecov upload-process -t \"$CODECOV_TOKEN\" -f {}",
+        report
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_yaml::Value;
+
+    #[test]
+    fn test_github_steps_per_provider() {
+        let codecov = CoverageUpload::new(
+            CoverageProvider::Codecov,
+            "cobertura.xml",
+            CoverageFormat::Cobertura,
+        )
+        .github_step();
+        assert_eq!(codecov.uses.as_deref(), Some("codecov/codecov-action@v4"));
+        assert_eq!(
+            codecov.with.unwrap()["token"],
+            Value::from("${{ secrets.CODECOV_TOKEN }}")
+        );
+
+        let codeclimate = CoverageUpload::new(
+            CoverageProvider::CodeClimate,
+            "coverage.out",
+            CoverageFormat::GoCover,
+        )
+        .github_step();
+        assert_eq!(
+            codeclimate.with.unwrap()["coverageLocations"],
+            Value::from("coverage.out:gocov")
+        );
+        assert!(codeclimate.env.unwrap().contains_key("CC_TEST_REPORTER_ID"));
+    }
+
+    #[test]
+    fn test_jenkins_step_binds_secret() {
+        let upload = CoverageUpload::new(
+            CoverageProvider::Coveralls,
+            "coverage.xml",
+            CoverageFormat::Cobertura,
+        );
+        let step = upload.jenkins_step();
+        assert!(step.starts_with(
+            "withCredentials([string(credentialsId: 'COVERALLS_REPO_TOKEN', variable: 'COVERALLS_REPO_TOKEN')])"
+        ));
+        assert!(step.contains("./coveralls report coverage.xml"));
+        assert!(!upload.command().contains('\''));
+    }
+}
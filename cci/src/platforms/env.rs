@@ -0,0 +1,57 @@
+use crate::models::Pipeline;
+
+/// Variables every job of a preset's pipeline runs with
+///
+/// They become the [`Pipeline`]'s env, which GitHub workflows, GitLab
+/// pipelines and Jenkinsfiles set once at the top; CircleCI has no
+/// pipeline-wide environment, so each job gets them. Variables the pipeline
+/// already sets keep their values.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PipelineEnv {
+    pub vars: Vec<(&'static str, &'static str)>,
+}
+
+impl PipelineEnv {
+    /// Colored cargo output, which CI logs show but cargo turns off without a terminal
+    pub fn rust() -> Self {
+        Self {
+            vars: vec![("CARGO_TERM_COLOR", "always")],
+        }
+    }
+
+    /// Output as it's printed, and no warnings about pip's own version
+    pub fn python() -> Self {
+        Self {
+            vars: vec![
+                ("PYTHONUNBUFFERED", "1"),
+                ("PIP_DISABLE_PIP_VERSION_CHECK", "1"),
+            ],
+        }
+    }
+
+    /// Set the variables on `pipeline`
+    pub fn apply(&self, pipeline: &mut Pipeline) {
+        for (key, value) in &self.vars {
+            pipeline
+                .env
+                .entry(key.to_string())
+                .or_insert_with(|| value.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_existing_values_are_kept() {
+        let mut pipeline = Pipeline::default();
+        pipeline
+            .env
+            .insert("PYTHONUNBUFFERED".to_string(), "0".to_string());
+        PipelineEnv::python().apply(&mut pipeline);
+        assert_eq!(pipeline.env["PYTHONUNBUFFERED"], "0");
+        assert_eq!(pipeline.env["PIP_DISABLE_PIP_VERSION_CHECK"], "1");
+    }
+}
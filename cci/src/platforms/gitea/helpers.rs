@@ -0,0 +1,137 @@
+//! Adapting GitHub workflows to Gitea runners
+//!
+//! Presets build their Gitea workflows as GitHub ones. act_runner runs most
+//! actions unchanged, but a few expect GitHub's hosted runners or services;
+//! [`substitute_actions`] swaps those steps for equivalents that work on a
+//! stock Gitea runner.
+
+use super::models::{GiteaStep, GiteaWorkflow};
+use crate::platforms::cache::{CacheMode, CacheStrategy};
+use crate::platforms::coverage::codecov_command;
+use serde_yaml::Value;
+
+/// Steps standing in for one using an action
+type Substitute = fn(&GiteaStep) -> Vec<GiteaStep>;
+
+/// Actions that don't run reliably on stock Gitea runners, by name without
+/// the version, and what replaces them
+const SUBSTITUTES: &[(&str, Substitute)] = &[
+    ("Swatinem/rust-cache", rust_cache),
+    ("codecov/codecov-action", codecov_upload),
+];
+
+/// Explicit `actions/cache` paths, which act_runner's cache server serves
+fn rust_cache(_: &GiteaStep) -> Vec<GiteaStep> {
+    CacheStrategy::rust(CacheMode::Manual).github_steps()
+}
+
+/// The Codecov CLI, downloaded and run with the action's token and files
+fn codecov_upload(step: &GiteaStep) -> Vec<GiteaStep> {
+    let input = |key: &str| {
+        step.with
+            .as_ref()
+            .and_then(|with| with.get(key))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    let mut upload = GiteaStep::run(
+        step.name.clone().unwrap_or_default(),
+        codecov_command(&input("files").unwrap_or_default()),
+    );
+    let mut env = step.env.clone().unwrap_or_default();
+    if let Some(token) = input("token") {
+        env.insert("CODECOV_TOKEN".to_string(), token);
+    }
+    upload.env = (!env.is_empty()).then_some(env);
+    vec![upload]
+}
+
+/// Replace steps using actions that don't work on typical Gitea runners
+///
+/// Actions are matched by name, so pinned references are replaced too.
+pub fn substitute_actions(workflow: &mut GiteaWorkflow) {
+    for job in workflow.jobs.values_mut() {
+        job.steps = std::mem::take(&mut job.steps)
+            .into_iter()
+            .flat_map(|step| {
+                let substitute = step.uses.as_deref().and_then(|uses| {
+                    let action = uses.split('@').next().unwrap_or(uses);
+                    SUBSTITUTES
+                        .iter()
+                        .find(|(name, _)| *name == action)
+                        .map(|(_, substitute)| substitute)
+                });
+                match substitute {
+                    Some(substitute) => substitute(&step),
+                    None => vec![step],
+                }
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platforms::gitea::models::{GiteaJob, GiteaTriggers};
+    use crate::platforms::jobs::Jobs;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_substitutes_cache_and_codecov_steps() {
+        let mut workflow = GiteaWorkflow {
+            name: "CI".to_string(),
+            on: GiteaTriggers::Simple(vec!["push".to_string()]),
+            env: None,
+            defaults: None,
+            jobs: Jobs::from([(
+                "test",
+                GiteaJob {
+                    runs_on: "ubuntu-latest".into(),
+                    steps: vec![
+                        GiteaStep::checkout(),
+                        GiteaStep::action(
+                            "Cache dependencies",
+                            "Swatinem/rust-cache@9d47c6ad4b02e050fd481d890b2ea34778fd09d6",
+                        ),
+                        GiteaStep::action_with_config(
+                            "Upload coverage to Codecov",
+                            "codecov/codecov-action@v4",
+                            BTreeMap::from([
+                                ("token".to_string(), "${{ secrets.CODECOV_TOKEN }}".into()),
+                                ("files".to_string(), "cobertura.xml".into()),
+                            ]),
+                        ),
+                    ],
+                    needs: None,
+                    timeout_minutes: None,
+                    continue_on_error: None,
+                    container: None,
+                    permissions: None,
+                    strategy: None,
+                    services: None,
+                    env: None,
+                    if_condition: None,
+                    environment: None,
+                },
+            )]),
+            permissions: None,
+            concurrency: None,
+        };
+        substitute_actions(&mut workflow);
+
+        let steps = &workflow.jobs["test"].steps;
+        assert_eq!(steps[0].uses.as_deref(), Some("actions/checkout@v4"));
+        assert_eq!(steps[1].uses.as_deref(), Some("actions/cache@v4"));
+        assert!(steps[2].uses.is_none());
+        assert!(steps[2]
+            .run
+            .as_deref()
+            .unwrap()
+            .ends_with("-f cobertura.xml"));
+        assert_eq!(
+            steps[2].env.as_ref().unwrap()["CODECOV_TOKEN"],
+            "${{ secrets.CODECOV_TOKEN }}"
+        );
+    }
+}
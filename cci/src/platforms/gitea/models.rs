@@ -2,14 +2,16 @@
 // We re-export GitHub Actions models with Gitea-specific type aliases
 
 pub use crate::platforms::github::{
-    GitHubJob as GiteaJob, GitHubStep as GiteaStep, GitHubTriggerConfig as GiteaTriggerConfig,
-    GitHubTriggers as GiteaTriggers, GitHubWorkflow as GiteaWorkflow,
+    GitHubConcurrency as GiteaConcurrency, GitHubJob as GiteaJob, GitHubRunsOn as GiteaRunsOn,
+    GitHubSchedule as GiteaSchedule, GitHubStep as GiteaStep, GitHubTrigger as GiteaTrigger,
+    GitHubTriggerConfig as GiteaTriggerConfig, GitHubTriggers as GiteaTriggers,
+    GitHubWorkflow as GiteaWorkflow,
 };
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::BTreeMap;
+    use crate::platforms::jobs::Jobs;
 
     #[test]
     fn test_serialize_gitea_workflow() {
@@ -18,12 +20,14 @@ mod tests {
             name: "Gitea CI".to_string(),
             on: GiteaTriggers::Simple(vec!["push".to_string()]),
             env: None,
-            jobs: BTreeMap::from([(
+            defaults: None,
+            jobs: Jobs::from([(
                 "test".to_string(),
                 GiteaJob {
-                    runs_on: "ubuntu-latest".to_string(),
+                    runs_on: "ubuntu-latest".into(),
                     steps: vec![GiteaStep {
                         name: Some("Checkout".to_string()),
+                        if_condition: None,
                         uses: Some("actions/checkout@v4".to_string()),
                         run: None,
                         with: None,
@@ -32,8 +36,17 @@ mod tests {
                     needs: None,
                     timeout_minutes: None,
                     continue_on_error: None,
+                    container: None,
+                    permissions: None,
+                    strategy: None,
+                    services: None,
+                    env: None,
+                    if_condition: None,
+                    environment: None,
                 },
             )]),
+            permissions: None,
+            concurrency: None,
         };
 
         let yaml = serde_yaml::to_string(&workflow).unwrap();
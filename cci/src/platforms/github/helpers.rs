@@ -1,13 +1,35 @@
-use super::models::GitHubStep;
+use super::models::{GitHubConcurrency, GitHubStep, GitHubWorkflow};
 use serde_yaml::Value;
 use std::collections::BTreeMap;
 
+/// Helper functions for workflow-level settings
+impl GitHubWorkflow {
+    /// Restrict the default GITHUB_TOKEN to read-only repository access
+    ///
+    /// Jobs that need more (e.g. pushing packages) declare their own `permissions`.
+    pub fn set_read_only_permissions(&mut self) {
+        self.permissions = Some(BTreeMap::from([(
+            "contents".to_string(),
+            "read".to_string(),
+        )]));
+    }
+
+    /// Cancel in-progress runs of this workflow for the same ref
+    pub fn set_cancel_in_progress(&mut self) {
+        self.concurrency = Some(GitHubConcurrency {
+            group: "${{ github.workflow }}-${{ github.ref }}".to_string(),
+            cancel_in_progress: true,
+        });
+    }
+}
+
 /// Helper functions for creating common GitHub Actions steps
 impl GitHubStep {
     /// Create a checkout step using actions/checkout@v4
     pub fn checkout() -> Self {
         Self {
             name: Some("Checkout code".to_string()),
+            if_condition: None,
             uses: Some("actions/checkout@v4".to_string()),
             run: None,
             with: None,
@@ -19,6 +41,7 @@ impl GitHubStep {
     pub fn action(name: impl Into<String>, uses: impl Into<String>) -> Self {
         Self {
             name: Some(name.into()),
+            if_condition: None,
             uses: Some(uses.into()),
             run: None,
             with: None,
@@ -34,6 +57,7 @@ impl GitHubStep {
     ) -> Self {
         Self {
             name: Some(name.into()),
+            if_condition: None,
             uses: Some(uses.into()),
             run: None,
             with: Some(with),
@@ -45,6 +69,7 @@ impl GitHubStep {
     pub fn run(name: impl Into<String>, command: impl Into<String>) -> Self {
         Self {
             name: Some(name.into()),
+            if_condition: None,
             uses: None,
             run: Some(command.into()),
             with: None,
@@ -1,4 +1,5 @@
 pub mod helpers;
 pub mod models;
+pub mod pinning;
 
 pub use models::*;
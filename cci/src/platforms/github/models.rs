@@ -1,3 +1,4 @@
+use crate::platforms::jobs::Jobs;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
@@ -6,42 +7,220 @@ pub struct GitHubWorkflow {
     pub name: String,
     pub on: GitHubTriggers,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<BTreeMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<GitHubConcurrency>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub env: Option<BTreeMap<String, String>>,
-    pub jobs: BTreeMap<String, GitHubJob>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub defaults: Option<GitHubDefaults>,
+    pub jobs: Jobs<GitHubJob>,
+}
+
+impl GitHubWorkflow {
+    /// A copy with job IDs GitHub accepts
+    ///
+    /// Presets name jobs `preset/job`, which GitLab and CircleCI allow but
+    /// GitHub rejects, so IDs are only rewritten when the workflow is written.
+    pub fn with_valid_job_ids(&self) -> GitHubWorkflow {
+        let mut workflow = self.clone();
+        workflow.jobs = std::mem::take(&mut workflow.jobs)
+            .into_iter()
+            .map(|(id, mut job)| {
+                if let Some(needs) = &mut job.needs {
+                    for need in needs.iter_mut() {
+                        *need = job_id(need);
+                    }
+                }
+                (job_id(&id), job)
+            })
+            .collect();
+        workflow
+    }
+}
+
+/// Job ID with characters GitHub doesn't allow (e.g. the `/` in `rust/test`) replaced
+pub fn job_id(id: &str) -> String {
+    id.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Settings applied to every job, e.g. the directory `run` steps start in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct GitHubDefaults {
+    pub run: GitHubRunDefaults,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct GitHubRunDefaults {
+    #[serde(rename = "working-directory", skip_serializing_if = "Option::is_none")]
+    pub working_directory: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shell: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum GitHubTriggers {
+    /// A lone event, `on: push`
+    Single(String),
     Simple(Vec<String>),
-    Detailed(BTreeMap<String, GitHubTriggerConfig>),
+    Detailed(BTreeMap<String, GitHubTrigger>),
+}
+
+/// Configuration for a single event under `on:`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GitHubTrigger {
+    /// `schedule:` takes a list of cron entries
+    Schedule(Vec<GitHubSchedule>),
+    /// Branch/tag filters (`push`, `pull_request`) or an empty map (`workflow_dispatch`)
+    Filter(GitHubTriggerConfig),
+    /// Event listed without any configuration
+    Empty,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitHubSchedule {
+    pub cron: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct GitHubTriggerConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub branches: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tags: Option<Vec<String>>,
+    /// Only run when a changed file matches one of these globs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paths: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitHubConcurrency {
+    pub group: String,
+    #[serde(rename = "cancel-in-progress")]
+    pub cancel_in_progress: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitHubStrategy {
+    pub matrix: GitHubMatrix,
+    #[serde(rename = "fail-fast", skip_serializing_if = "Option::is_none")]
+    pub fail_fast: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitHubMatrix {
+    #[serde(flatten)]
+    pub axes: BTreeMap<String, Vec<String>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<BTreeMap<String, String>>,
+}
+
+/// Service container started alongside a job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitHubService {
+    pub image: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ports: Vec<String>,
+    /// Extra `docker create` options, used for health checks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<String>,
+}
+
+/// Runner selection for a job: a single label or a list of labels
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GitHubRunsOn {
+    Label(String),
+    Labels(Vec<String>),
+}
+
+/// Read a key that holds either one string or a list of them
+fn string_or_list<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrList {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(
+        Option::<StringOrList>::deserialize(deserializer)?.map(|value| match value {
+            StringOrList::One(item) => vec![item],
+            StringOrList::Many(items) => items,
+        }),
+    )
+}
+
+impl From<&str> for GitHubRunsOn {
+    fn from(label: &str) -> Self {
+        GitHubRunsOn::Label(label.to_string())
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GitHubJob {
     #[serde(rename = "runs-on")]
-    pub runs_on: String,
-    pub steps: Vec<GitHubStep>,
+    pub runs_on: GitHubRunsOn,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<BTreeMap<String, String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<GitHubStrategy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub services: Option<BTreeMap<String, GitHubService>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<BTreeMap<String, String>>,
+    pub steps: Vec<GitHubStep>,
+    /// Jobs that must finish first; GitHub also takes a lone job, `needs: build`
+    #[serde(
+        default,
+        deserialize_with = "string_or_list",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub needs: Option<Vec<String>>,
     #[serde(rename = "timeout-minutes", skip_serializing_if = "Option::is_none")]
     pub timeout_minutes: Option<u32>,
     #[serde(rename = "continue-on-error", skip_serializing_if = "Option::is_none")]
     pub continue_on_error: Option<bool>,
+    /// Expression deciding whether the job runs, e.g. `failure()`
+    #[serde(rename = "if", skip_serializing_if = "Option::is_none")]
+    pub if_condition: Option<String>,
+    /// Deployment environment, whose protection rules gate the job
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub environment: Option<GitHubEnvironment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitHubEnvironment {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GitHubStep {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    /// Expression that must hold for the step to run
+    #[serde(rename = "if", skip_serializing_if = "Option::is_none")]
+    pub if_condition: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub uses: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -62,12 +241,14 @@ mod tests {
             name: "CI".to_string(),
             on: GitHubTriggers::Simple(vec!["push".to_string()]),
             env: None,
-            jobs: BTreeMap::from([(
+            defaults: None,
+            jobs: Jobs::from([(
                 "test".to_string(),
                 GitHubJob {
-                    runs_on: "ubuntu-latest".to_string(),
+                    runs_on: "ubuntu-latest".into(),
                     steps: vec![GitHubStep {
                         name: Some("Checkout".to_string()),
+                        if_condition: None,
                         uses: Some("actions/checkout@v4".to_string()),
                         run: None,
                         with: None,
@@ -76,8 +257,17 @@ mod tests {
                     needs: None,
                     timeout_minutes: None,
                     continue_on_error: None,
+                    container: None,
+                    permissions: None,
+                    strategy: None,
+                    services: None,
+                    env: None,
+                    if_condition: None,
+                    environment: None,
                 },
             )]),
+            permissions: None,
+            concurrency: None,
         };
 
         let yaml = serde_yaml::to_string(&workflow).unwrap();
@@ -0,0 +1,426 @@
+//! Pinning GitHub Actions references to immutable commit SHAs
+//!
+//! Action tags like `actions/checkout@v4` can be moved by their owners, so many
+//! organizations require `uses:` to reference a full commit SHA instead. Pins
+//! come from a bundled lock table, or are resolved against GitHub with
+//! `git ls-remote` when online resolution is enabled (`cci generate --online`).
+
+use super::models::GitHubWorkflow;
+use crate::error::{validation_error, Result};
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+/// Bundled pins: (action reference, commit SHA, exact release the SHA belongs to)
+///
+/// Covers every action the presets emit, so `pin_actions` works offline.
+/// `cargo test -- --ignored bundled_pins` checks them against GitHub.
+const BUNDLED_PINS: &[(&str, &str, &str)] = &[
+    (
+        "actions/checkout@v4",
+        "11bd71901bbe5b1630ceea73d27597364c9af683",
+        "v4.2.2",
+    ),
+    (
+        "actions/cache@v4",
+        "5a3ec84eff668545956fd18022155c47e93e2684",
+        "v4.2.3",
+    ),
+    (
+        "actions/upload-artifact@v4",
+        "ea165f8d65b6e75b540449e92b4886f43607fa02",
+        "v4.6.2",
+    ),
+    (
+        "actions/download-artifact@v4",
+        "fa0a91b85d4f404e444e00e005971372dc801d16",
+        "v4.1.8",
+    ),
+    (
+        "actions/setup-go@v5",
+        "d35c59abb061a4a6fb18e82ac0862c26744d6ab5",
+        "v5.5.0",
+    ),
+    (
+        "actions/setup-node@v4",
+        "49933ea5288caeca8642d1e84afbd3f7d6820020",
+        "v4.4.0",
+    ),
+    (
+        "actions/setup-python@v5",
+        "a26af69be951a213d495a4c3e4e4022e16d87065",
+        "v5.6.0",
+    ),
+    (
+        "dtolnay/rust-toolchain@v1",
+        "e97e2d8cc328f1b50210efc529dca0028893a2d9",
+        "v1",
+    ),
+    (
+        "Swatinem/rust-cache@v2",
+        "9d47c6ad4b02e050fd481d890b2ea34778fd09d6",
+        "v2.7.8",
+    ),
+    (
+        "rustsec/audit-check@v1",
+        "dd51754d4e59da7395a4cd9b593f0ff2d61a9b95",
+        "v1.4.1",
+    ),
+    (
+        "golangci/golangci-lint-action@v4",
+        "3cfe3a4abbb849e10058ce4af15d205b6da42804",
+        "v4.0.0",
+    ),
+    (
+        "codecov/codecov-action@v4",
+        "b9fd7d16f6d7d1b5d2bec1a2887e65ceed900238",
+        "v4.6.0",
+    ),
+    (
+        "coverallsapp/github-action@v2",
+        "648a8eb78e6d50909eff900e4ec85cab4524a45b",
+        "v2.3.6",
+    ),
+    (
+        "paambaati/codeclimate-action@v9",
+        "f429536ee076d758a24705203199548125a28ca7",
+        "v9.0.0",
+    ),
+    (
+        "github/codeql-action/init@v3",
+        "ff0a06e83cb2de871e5a09832bc6a81e7276941f",
+        "v3.28.18",
+    ),
+    (
+        "github/codeql-action/autobuild@v3",
+        "ff0a06e83cb2de871e5a09832bc6a81e7276941f",
+        "v3.28.18",
+    ),
+    (
+        "github/codeql-action/analyze@v3",
+        "ff0a06e83cb2de871e5a09832bc6a81e7276941f",
+        "v3.28.18",
+    ),
+    (
+        "googleapis/release-please-action@v4",
+        "a02a34c4d625f9be7cb89156071d8567266a2445",
+        "v4.2.0",
+    ),
+    (
+        "docker/login-action@v3",
+        "74a5d142397b4f367a81961eba4e8cd7edddf772",
+        "v3.4.0",
+    ),
+    (
+        "docker/metadata-action@v5",
+        "902fa8ec7d6ecbf8d84d538b9b233a880e428804",
+        "v5.7.0",
+    ),
+    (
+        "docker/setup-buildx-action@v3",
+        "b5ca514318bd6ebac0fb2aedd5d36ec1b5c232a2",
+        "v3.10.0",
+    ),
+    (
+        "docker/setup-qemu-action@v3",
+        "29109295f81e9208d7d86ff1c6c12d2833863392",
+        "v3.6.0",
+    ),
+    (
+        "docker/build-push-action@v5",
+        "ca052bb54ab0790a636c9b5f226502c73d547a25",
+        "v5.4.0",
+    ),
+];
+
+static ONLINE: AtomicBool = AtomicBool::new(false);
+
+/// SHAs resolved online during this run, keyed by action reference
+fn online_cache() -> &'static Mutex<HashMap<String, Option<String>>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, Option<String>>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Allow actions missing from the bundled table to be resolved against GitHub
+pub fn enable_online_resolution() {
+    ONLINE.store(true, Ordering::Relaxed);
+}
+
+/// Whether a ref is already a full commit SHA
+pub(crate) fn is_sha(reference: &str) -> bool {
+    reference.len() == 40 && reference.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Resolve an action reference (`owner/repo@ref`) to a commit SHA
+pub fn resolve(uses: &str) -> Option<String> {
+    if let Some((_, sha, _)) = BUNDLED_PINS.iter().find(|(action, _, _)| *action == uses) {
+        return Some(sha.to_string());
+    }
+    if !ONLINE.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    let mut cache = online_cache().lock().unwrap();
+    cache
+        .entry(uses.to_string())
+        .or_insert_with(|| resolve_online(uses))
+        .clone()
+}
+
+/// Look up a ref with `git ls-remote`, preferring the peeled commit of annotated tags
+fn resolve_online(uses: &str) -> Option<String> {
+    let (action, reference) = uses.split_once('@')?;
+    // Actions in subdirectories (owner/repo/path@ref) live in owner/repo
+    let repo: Vec<&str> = action.splitn(3, '/').take(2).collect();
+    let url = format!("https://github.com/{}", repo.join("/"));
+
+    let output = Command::new("git")
+        .args([
+            "ls-remote",
+            &url,
+            &format!("refs/tags/{}", reference),
+            &format!("refs/tags/{}^{{}}", reference),
+            &format!("refs/heads/{}", reference),
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let refs: Vec<(&str, &str)> = stdout
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .collect();
+    refs.iter()
+        .find(|(_, name)| name.ends_with("^{}"))
+        .or_else(|| refs.first())
+        .map(|(sha, _)| sha.to_string())
+}
+
+/// The ref a pinned SHA was resolved from, for annotating the output
+fn reference_for(action: &str, sha: &str) -> Option<String> {
+    if let Some((_, _, version)) = BUNDLED_PINS
+        .iter()
+        .find(|(uses, pinned, _)| *pinned == sha && uses.starts_with(&format!("{}@", action)))
+    {
+        return Some(version.to_string());
+    }
+
+    let cache = online_cache().lock().unwrap();
+    cache.iter().find_map(|(uses, pinned)| {
+        let (cached_action, reference) = uses.split_once('@')?;
+        (cached_action == action && pinned.as_deref() == Some(sha)).then(|| reference.to_string())
+    })
+}
+
+/// Replace every action tag in the workflow with its commit SHA
+///
+/// Local actions (`./`) and Docker references are left alone. Fails if any
+/// action can't be resolved, since a partially pinned workflow would still
+/// violate a pinning policy.
+pub fn pin_workflow(workflow: &mut GitHubWorkflow) -> Result<()> {
+    let mut unresolved = Vec::new();
+
+    for job in workflow.jobs.values_mut() {
+        for step in &mut job.steps {
+            let Some(uses) = &step.uses else {
+                continue;
+            };
+            if uses.starts_with("./") || uses.starts_with("docker://") {
+                continue;
+            }
+            let Some((action, reference)) = uses.split_once('@') else {
+                continue;
+            };
+            if is_sha(reference) {
+                continue;
+            }
+
+            match resolve(uses) {
+                Some(sha) => step.uses = Some(format!("{}@{}", action, sha)),
+                None => unresolved.push(uses.clone()),
+            }
+        }
+    }
+
+    if unresolved.is_empty() {
+        Ok(())
+    } else {
+        unresolved.sort();
+        unresolved.dedup();
+        Err(validation_error(format!(
+            "no pinned commit SHA known for {}; run `cci generate --online` to resolve them",
+            unresolved.join(", ")
+        )))
+    }
+}
+
+/// Append `# <ref>` comments to pinned `uses:` lines so the version stays readable
+pub fn annotate_pins(yaml: &str) -> String {
+    let mut result = String::with_capacity(yaml.len());
+    for line in yaml.lines() {
+        result.push_str(line);
+        if let Some((_, value)) = line.split_once("uses: ") {
+            if let Some((action, sha)) = value.trim().split_once('@') {
+                if is_sha(sha) {
+                    if let Some(reference) = reference_for(action, sha) {
+                        result.push_str(&format!(" # {}", reference));
+                    }
+                }
+            }
+        }
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::config::{settle_rules, OptionMeta, OptionValue};
+    use crate::editor::registry::build_registry;
+    use crate::editor::state::Platform;
+    use crate::platforms::github::models::{GitHubJob, GitHubStep, GitHubTriggers};
+    use crate::platforms::jobs::Jobs;
+    use std::collections::BTreeSet;
+
+    fn workflow(uses: &[&str]) -> GitHubWorkflow {
+        GitHubWorkflow {
+            name: "CI".to_string(),
+            on: GitHubTriggers::Simple(vec!["push".to_string()]),
+            permissions: None,
+            concurrency: None,
+            env: None,
+            defaults: None,
+            jobs: Jobs::from([(
+                "test".to_string(),
+                GitHubJob {
+                    runs_on: "ubuntu-latest".into(),
+                    container: None,
+                    permissions: None,
+                    steps: uses
+                        .iter()
+                        .map(|uses| GitHubStep::action("Step", *uses))
+                        .collect(),
+                    needs: None,
+                    timeout_minutes: None,
+                    continue_on_error: None,
+                    strategy: None,
+                    services: None,
+                    env: None,
+                    if_condition: None,
+                    environment: None,
+                },
+            )]),
+        }
+    }
+
+    #[test]
+    fn test_pin_bundled_actions() {
+        let mut workflow = workflow(&["actions/checkout@v4", "./local-action"]);
+        pin_workflow(&mut workflow).unwrap();
+
+        let steps = &workflow.jobs["test"].steps;
+        assert_eq!(
+            steps[0].uses.as_deref(),
+            Some("actions/checkout@11bd71901bbe5b1630ceea73d27597364c9af683")
+        );
+        assert_eq!(steps[1].uses.as_deref(), Some("./local-action"));
+
+        let yaml = annotate_pins(&serde_yaml::to_string(&workflow).unwrap());
+        assert!(yaml.contains("@11bd71901bbe5b1630ceea73d27597364c9af683 # v4.2.2"));
+    }
+
+    #[test]
+    fn test_pin_unknown_action_fails_offline() {
+        let mut workflow = workflow(&["example/unknown-action@v1"]);
+        let err = pin_workflow(&mut workflow).unwrap_err();
+        assert!(err.to_string().contains("example/unknown-action@v1"));
+        assert!(err.to_string().contains("--online"));
+    }
+
+    /// Every `uses:` in the GitHub workflows of every preset, with each
+    /// option turned on and each enum option set to each of its variants
+    ///
+    /// Actions are left unpinned, so they can be looked up afterwards.
+    fn emitted_actions() -> BTreeSet<String> {
+        let registry = build_registry();
+        let mut actions = BTreeSet::new();
+        for preset in registry.all() {
+            if preset.is_decorator() {
+                continue;
+            }
+            let options: Vec<OptionMeta> = preset
+                .features()
+                .into_iter()
+                .flat_map(|feature| feature.options)
+                .collect();
+            let mut everything = preset.default_config(true);
+            for option in &options {
+                if matches!(option.default_value, OptionValue::Bool(_))
+                    && option.id != "pin_actions"
+                {
+                    everything.set(option.id.clone(), OptionValue::Bool(true));
+                }
+            }
+
+            let mut configs = vec![preset.default_config(true)];
+            for option in options.iter().filter(|option| !option.variants.is_empty()) {
+                for variant in &option.variants {
+                    let mut config = everything.clone();
+                    if let Some(OptionValue::Enum { variants, .. }) = config.get(&option.id) {
+                        let variants = variants.clone();
+                        config.set(
+                            option.id.clone(),
+                            OptionValue::Enum {
+                                selected: variant.id.clone(),
+                                variants,
+                            },
+                        );
+                    }
+                    settle_rules(preset.as_ref(), &mut config, Some(&option.id));
+                    configs.push(config);
+                }
+            }
+            settle_rules(preset.as_ref(), &mut everything, None);
+            configs.push(everything);
+
+            for config in configs {
+                let Ok(pipeline) = preset.build(&config, Platform::GitHub, "stable") else {
+                    continue;
+                };
+                for line in pipeline.render().unwrap().lines() {
+                    if let Some((_, uses)) = line.split_once("uses: ") {
+                        actions.insert(uses.trim().to_string());
+                    }
+                }
+            }
+        }
+        actions
+    }
+
+    #[test]
+    fn test_every_emitted_action_is_bundled() {
+        let actions = emitted_actions();
+        assert!(actions.contains("actions/checkout@v4"));
+        let unresolved: Vec<&String> = actions
+            .iter()
+            .filter(|uses| !uses.starts_with("./") && !uses.starts_with("docker://"))
+            .filter(|uses| resolve(uses).is_none())
+            .collect();
+        assert!(unresolved.is_empty(), "not bundled: {:?}", unresolved);
+    }
+
+    #[test]
+    #[ignore = "needs network access to GitHub"]
+    fn test_bundled_pins_match_their_releases() {
+        for (uses, sha, release) in BUNDLED_PINS {
+            let (action, _) = uses.split_once('@').unwrap();
+            let resolved = resolve_online(&format!("{}@{}", action, release));
+            assert_eq!(resolved.as_deref(), Some(*sha), "{} ({})", uses, release);
+        }
+    }
+}
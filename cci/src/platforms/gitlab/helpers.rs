@@ -0,0 +1,13 @@
+use super::models::{GitLabCI, GitLabDefault};
+
+/// Helper functions for pipeline-level settings
+impl GitLabCI {
+    /// Let a newer pipeline for the same ref cancel this one's jobs
+    ///
+    /// GitLab only cancels redundant pipelines whose jobs are all interruptible.
+    pub fn set_interruptible(&mut self) {
+        self.default
+            .get_or_insert_with(GitLabDefault::default)
+            .interruptible = Some(true);
+    }
+}
@@ -1,46 +1,145 @@
+use crate::platforms::jobs::Jobs;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GitLabCI {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include: Option<Vec<GitLabInclude>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workflow: Option<GitLabWorkflow>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<GitLabDefault>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stages: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub variables: Option<BTreeMap<String, String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cache: Option<GitLabCache>,
-    #[serde(flatten)]
-    pub jobs: BTreeMap<String, GitLabJob>,
+    /// Jobs by ID; hidden `.template` jobs are only read by `extends`, so
+    /// they're left out
+    #[serde(flatten, deserialize_with = "visible_jobs")]
+    pub jobs: Jobs<GitLabJob>,
+}
+
+/// The jobs of a config, without the hidden `.`-prefixed ones, which may
+/// leave out keys a runnable job needs
+fn visible_jobs<'de, D>(deserializer: D) -> Result<Jobs<GitLabJob>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let mut jobs = Jobs::new();
+    for (id, job) in Jobs::<serde_yaml::Value>::deserialize(deserializer)? {
+        if id.starts_with('.') {
+            continue;
+        }
+        let job = serde_yaml::from_value(job)
+            .map_err(|e| serde::de::Error::custom(format!("job `{}`: {}", id, e)))?;
+        jobs.insert(id, job);
+    }
+    Ok(jobs)
+}
+
+/// Stage of jobs that don't name one
+fn default_stage() -> String {
+    "test".to_string()
+}
+
+/// Configuration pulled in from one of GitLab's bundled templates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitLabInclude {
+    pub template: String,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GitLabJob {
+    #[serde(default = "default_stage")]
     pub stage: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub services: Option<Vec<GitLabService>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variables: Option<BTreeMap<String, String>>,
     pub script: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub before_script: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub after_script: Option<Vec<String>>,
+    /// Jobs this one waits for instead of every earlier stage; empty to start at once
     #[serde(skip_serializing_if = "Option::is_none")]
     pub needs: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cache: Option<GitLabCache>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub artifacts: Option<GitLabArtifacts>,
+    /// Legacy ref filter; cci writes `rules`, but imported pipelines may use it
     #[serde(skip_serializing_if = "Option::is_none")]
     pub only: Option<GitLabOnly>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel: Option<GitLabParallel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub timeout: Option<String>,
+    /// Times the job is retried after a failure, at most 2
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry: Option<u32>,
+    /// Whether the pipeline passes even if the job fails
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_failure: Option<bool>,
+    /// Conditions deciding whether the job is added to a pipeline
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rules: Option<Vec<GitLabRule>>,
+    /// When the job runs relative to earlier stages, e.g. `on_failure`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub when: Option<String>,
+    /// Whether a newer pipeline for the same ref may cancel the job
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interruptible: Option<bool>,
+    /// Regex finding the job's total coverage in its log
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<String>,
+    /// Environment the job deploys to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub environment: Option<GitLabEnvironment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitLabEnvironment {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// Service container started alongside a job, reachable by its alias
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitLabService {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GitLabCache {
-    pub key: String,
+    pub key: GitLabCacheKey,
     pub paths: Vec<String>,
 }
 
+/// Cache key: a fixed name, or derived from the contents of lock files
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GitLabCacheKey {
+    Name(String),
+    Files { files: Vec<String> },
+}
+
+impl From<&str> for GitLabCacheKey {
+    fn from(name: &str) -> Self {
+        GitLabCacheKey::Name(name.to_string())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GitLabArtifacts {
     pub paths: Vec<String>,
@@ -49,7 +148,59 @@ pub struct GitLabArtifacts {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct GitLabOnly {
+#[serde(untagged)]
+pub enum GitLabOnly {
+    /// `only: [main, tags]`
+    Refs(Vec<String>),
+    Detailed {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        refs: Option<Vec<String>>,
+    },
+}
+
+impl GitLabOnly {
+    /// The refs the job is limited to, in either form
+    pub fn refs(&self) -> Option<&[String]> {
+        match self {
+            GitLabOnly::Refs(refs) => Some(refs),
+            GitLabOnly::Detailed { refs } => refs.as_deref(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitLabWorkflow {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<GitLabRule>,
+}
+
+/// Settings every job has unless it sets its own
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GitLabDefault {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before_script: Option<Vec<String>>,
+    /// Whether a newer pipeline for the same ref may cancel the job
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interruptible: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitLabRule {
+    #[serde(rename = "if", skip_serializing_if = "Option::is_none")]
+    pub if_condition: Option<String>,
+    /// Globs of which a changed file must match one for the rule to apply
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changes: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub refs: Option<Vec<String>>,
+    pub when: Option<String>,
+}
+
+/// `parallel: matrix:` - each entry expands to the product of its variable lists
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitLabParallel {
+    pub matrix: Vec<BTreeMap<String, Vec<String>>>,
 }
@@ -1,8 +1,119 @@
 use crate::editor::state::Platform;
 use crate::error::Result;
-use crate::platforms::jenkins::models::JenkinsConfig;
+use crate::platforms::circleci::models::CircleCIConfig;
+use crate::platforms::gitea::models::GiteaWorkflow;
+use crate::platforms::github::models::GitHubWorkflow;
+use crate::platforms::github::pinning::annotate_pins;
+use crate::platforms::gitlab::models::GitLabCI;
+use crate::platforms::jenkins::models::{JenkinsAgent, JenkinsConfig};
 use crate::traits::{ToCircleCI, ToGitHub, ToGitLab, ToGitea, ToJenkins};
 
+/// A platform config that has been built but not yet rendered to text
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlatformConfig {
+    GitHub(GitHubWorkflow),
+    Gitea(GiteaWorkflow),
+    GitLab(GitLabCI),
+    CircleCI(CircleCIConfig),
+    Jenkins(JenkinsConfig),
+}
+
+impl PlatformConfig {
+    /// Render to the YAML/Groovy text written to disk
+    pub fn render(&self) -> Result<String> {
+        match self {
+            PlatformConfig::GitHub(workflow) | PlatformConfig::Gitea(workflow) => Ok(
+                annotate_pins(&serde_yaml::to_string(&workflow.with_valid_job_ids())?),
+            ),
+            PlatformConfig::GitLab(config) => Ok(serde_yaml::to_string(config)?),
+            PlatformConfig::CircleCI(config) => Ok(serde_yaml::to_string(config)?),
+            PlatformConfig::Jenkins(config) => Ok(jenkins_to_string(config)),
+        }
+    }
+
+    /// Each job's name as rendered, with a fingerprint that changes with its contents
+    ///
+    /// Jenkins stages stand in for jobs.
+    pub fn jobs(&self) -> Vec<(String, String)> {
+        match self {
+            PlatformConfig::GitHub(workflow) | PlatformConfig::Gitea(workflow) => workflow
+                .with_valid_job_ids()
+                .jobs
+                .iter()
+                .map(|(id, job)| (id.clone(), format!("{:?}", job)))
+                .collect(),
+            PlatformConfig::GitLab(config) => config
+                .jobs
+                .iter()
+                .map(|(id, job)| (id.clone(), format!("{:?}", job)))
+                .collect(),
+            PlatformConfig::CircleCI(config) => config
+                .jobs
+                .iter()
+                .map(|(id, job)| (id.clone(), format!("{:?}", job)))
+                .collect(),
+            PlatformConfig::Jenkins(config) => config
+                .stages
+                .iter()
+                .map(|stage| (stage.name.clone(), format!("{:?}", stage)))
+                .collect(),
+        }
+    }
+}
+
+/// The 1-based line `job` starts on in a config `rendered` for `platform`
+pub fn job_line(platform: Platform, rendered: &str, job: &str) -> Option<usize> {
+    if platform == Platform::Jenkins {
+        let heading = format!("stage('{}') {{", job);
+        return rendered
+            .lines()
+            .position(|line| line.trim() == heading)
+            .map(|index| index + 1);
+    }
+
+    // Keys are quoted when they'd otherwise read as something else
+    let is_key = |key: &str| {
+        [
+            format!("{}:", job),
+            format!("'{}':", job),
+            format!("\"{}\":", job),
+        ]
+        .iter()
+        .any(|expected| key == expected)
+    };
+    // GitLab's jobs are top-level keys; the others' are nested under `jobs:`
+    let mut in_jobs = platform == Platform::GitLab;
+    for (index, line) in rendered.lines().enumerate() {
+        let key = line.trim_end();
+        if !line.starts_with([' ', '#']) && !key.is_empty() {
+            if platform == Platform::GitLab {
+                if is_key(key) {
+                    return Some(index + 1);
+                }
+                continue;
+            }
+            in_jobs = key == "jobs:";
+        } else if in_jobs && !line.starts_with("   ") && is_key(key.trim_start()) {
+            return Some(index + 1);
+        }
+    }
+    None
+}
+
+/// Build the platform config for a preset without rendering it
+pub fn build_for_platform<T>(preset: &T, platform: Platform) -> Result<PlatformConfig>
+where
+    T: ToGitHub + ToGitea + ToGitLab + ToCircleCI + ToJenkins,
+{
+    Ok(match platform {
+        Platform::GitHub => PlatformConfig::GitHub(preset.to_github()?),
+        Platform::Gitea => PlatformConfig::Gitea(preset.to_gitea()?),
+        Platform::GitLab => PlatformConfig::GitLab(preset.to_gitlab()?),
+        Platform::CircleCI => PlatformConfig::CircleCI(preset.to_circleci()?),
+        Platform::Jenkins => PlatformConfig::Jenkins(preset.to_jenkins()?),
+    })
+}
+
 /// Generate CI configuration for the specified platform
 ///
 /// This helper function eliminates code duplication across preset implementations
@@ -22,28 +133,7 @@ pub fn generate_for_platform<T>(preset: &T, platform: Platform) -> Result<String
 where
     T: ToGitHub + ToGitea + ToGitLab + ToCircleCI + ToJenkins,
 {
-    match platform {
-        Platform::GitHub => {
-            let workflow = preset.to_github()?;
-            Ok(serde_yaml::to_string(&workflow)?)
-        }
-        Platform::Gitea => {
-            let workflow = preset.to_gitea()?;
-            Ok(serde_yaml::to_string(&workflow)?)
-        }
-        Platform::GitLab => {
-            let config = preset.to_gitlab()?;
-            Ok(serde_yaml::to_string(&config)?)
-        }
-        Platform::CircleCI => {
-            let config = preset.to_circleci()?;
-            Ok(serde_yaml::to_string(&config)?)
-        }
-        Platform::Jenkins => {
-            let config = preset.to_jenkins()?;
-            Ok(jenkins_to_string(&config))
-        }
-    }
+    build_for_platform(preset, platform)?.render()
 }
 
 /// Convert a JenkinsConfig to Groovy pipeline string
@@ -59,9 +149,21 @@ where
 pub fn jenkins_to_string(config: &JenkinsConfig) -> String {
     let mut result = String::new();
     result.push_str("pipeline {\n");
-    result.push_str("    agent {\n");
-    result.push_str(&format!("        label '{}'\n", config.agent));
-    result.push_str("    }\n\n");
+    match &config.agent {
+        JenkinsAgent::Any => result.push_str("    agent any\n\n"),
+        JenkinsAgent::Label(label) => {
+            result.push_str("    agent {\n");
+            result.push_str(&format!("        label '{}'\n", label));
+            result.push_str("    }\n\n");
+        }
+        JenkinsAgent::Docker(image) => {
+            result.push_str("    agent {\n");
+            result.push_str("        docker {\n");
+            result.push_str(&format!("            image '{}'\n", image));
+            result.push_str("        }\n");
+            result.push_str("    }\n\n");
+        }
+    }
 
     if !config.environment.is_empty() {
         result.push_str("    environment {\n");
@@ -71,9 +173,32 @@ pub fn jenkins_to_string(config: &JenkinsConfig) -> String {
         result.push_str("    }\n\n");
     }
 
+    if !config.triggers.is_empty() {
+        result.push_str("    triggers {\n");
+        for trigger in &config.triggers {
+            result.push_str(&format!("        {}\n", trigger));
+        }
+        result.push_str("    }\n\n");
+    }
+
     result.push_str("    stages {\n");
     for stage in &config.stages {
         result.push_str(&format!("        stage('{}') {{\n", stage.name));
+        if !config.when.is_empty() || !stage.when.is_empty() {
+            // Conditions listed directly under `when` must all hold
+            result.push_str("            when {\n");
+            if !config.when.is_empty() {
+                result.push_str("                anyOf {\n");
+                for condition in &config.when {
+                    result.push_str(&format!("                    {}\n", condition));
+                }
+                result.push_str("                }\n");
+            }
+            for condition in &stage.when {
+                result.push_str(&format!("                {}\n", condition));
+            }
+            result.push_str("            }\n");
+        }
         result.push_str("            steps {\n");
         for step in &stage.steps {
             result.push_str(&format!("                {}\n", step));
@@ -82,6 +207,18 @@ pub fn jenkins_to_string(config: &JenkinsConfig) -> String {
         result.push_str("        }\n");
     }
     result.push_str("    }\n");
+
+    if !config.post.is_empty() {
+        result.push_str("\n    post {\n");
+        for (condition, steps) in &config.post {
+            result.push_str(&format!("        {} {{\n", condition));
+            for step in steps {
+                result.push_str(&format!("            {}\n", step));
+            }
+            result.push_str("        }\n");
+        }
+        result.push_str("    }\n");
+    }
     result.push_str("}\n");
     result
 }
@@ -91,15 +228,37 @@ mod tests {
     use super::*;
     use crate::platforms::jenkins::models::JenkinsStage;
 
+    #[test]
+    fn test_job_line() {
+        let github =
+            "name: CI\njobs:\n  rust-test:\n    runs-on: ubuntu\n  rust-lint:\n    steps: []\n";
+        assert_eq!(job_line(Platform::GitHub, github, "rust-lint"), Some(5));
+        assert_eq!(job_line(Platform::GitHub, github, "runs-on"), None);
+
+        // CircleCI's workflows have keys at the same depth as its jobs
+        let circleci = "version: '2.1'\nworkflows:\n  rust-test:\n    jobs: []\njobs:\n  rust-test:\n    docker: []\n";
+        assert_eq!(job_line(Platform::CircleCI, circleci, "rust-test"), Some(6));
+
+        let gitlab = "stages:\n- test\n'rust:test':\n  script: []\n";
+        assert_eq!(job_line(Platform::GitLab, gitlab, "rust:test"), Some(3));
+
+        let jenkinsfile = "pipeline {\n    stages {\n        stage('Test') {\n";
+        assert_eq!(job_line(Platform::Jenkins, jenkinsfile, "Test"), Some(3));
+    }
+
     #[test]
     fn test_jenkins_to_string_basic() {
         let config = JenkinsConfig {
-            agent: "docker".to_string(),
+            agent: JenkinsAgent::Label("docker".to_string()),
             environment: vec![],
+            triggers: vec![],
+            when: vec![],
             stages: vec![JenkinsStage {
                 name: "Build".to_string(),
+                when: vec![],
                 steps: vec!["sh 'cargo build'".to_string()],
             }],
+            post: vec![],
         };
 
         let result = jenkins_to_string(&config);
@@ -118,9 +277,12 @@ mod tests {
         ];
 
         let config = JenkinsConfig {
-            agent: "any".to_string(),
+            agent: JenkinsAgent::Any,
             environment: env,
+            triggers: vec![],
+            when: vec![],
             stages: vec![],
+            post: vec![],
         };
 
         let result = jenkins_to_string(&config);
@@ -132,21 +294,26 @@ mod tests {
     #[test]
     fn test_jenkins_to_string_multiple_stages() {
         let config = JenkinsConfig {
-            agent: "linux".to_string(),
+            agent: JenkinsAgent::Label("linux".to_string()),
             environment: vec![],
+            triggers: vec![],
+            when: vec![],
             stages: vec![
                 JenkinsStage {
                     name: "Test".to_string(),
+                    when: vec![],
                     steps: vec!["sh 'cargo test'".to_string()],
                 },
                 JenkinsStage {
                     name: "Deploy".to_string(),
+                    when: vec![],
                     steps: vec![
                         "sh 'docker build .'".to_string(),
                         "sh 'docker push'".to_string(),
                     ],
                 },
             ],
+            post: vec![],
         };
 
         let result = jenkins_to_string(&config);
@@ -156,4 +323,37 @@ mod tests {
         assert!(result.contains("sh 'docker build .'"));
         assert!(result.contains("sh 'docker push'"));
     }
+
+    #[test]
+    fn test_jenkins_to_string_triggers_and_when() {
+        let config = JenkinsConfig {
+            agent: JenkinsAgent::Any,
+            environment: vec![],
+            triggers: vec!["cron('0 2 * * *')".to_string()],
+            when: vec!["branch 'main'".to_string(), "tag 'v*'".to_string()],
+            stages: vec![
+                JenkinsStage {
+                    name: "Build".to_string(),
+                    when: vec![],
+                    steps: vec!["sh 'make'".to_string()],
+                },
+                JenkinsStage {
+                    name: "Audit".to_string(),
+                    when: vec!["triggeredBy 'TimerTrigger'".to_string()],
+                    steps: vec!["sh 'make audit'".to_string()],
+                },
+            ],
+            post: vec![],
+        };
+
+        let result = jenkins_to_string(&config);
+        assert!(result.contains("triggers {\n        cron('0 2 * * *')"));
+        assert!(result.contains("when {"));
+        assert!(result.contains("anyOf {"));
+        assert!(result.contains("branch 'main'"));
+        assert!(result.contains("tag 'v*'"));
+        assert!(result.contains(
+            "                }\n                triggeredBy 'TimerTrigger'\n            }\n            steps {\n                sh 'make audit'"
+        ));
+    }
 }
@@ -0,0 +1,43 @@
+/// Quote a value as a single-quoted Groovy string
+pub fn groovy_quote(value: &str) -> String {
+    format!(
+        "'{}'",
+        value
+            .replace('\\', "\\\\")
+            .replace('\'', "\\'")
+            .replace('\n', "\\n")
+    )
+}
+
+/// The value of a single-quoted Groovy string, if `value` is exactly one
+pub fn groovy_unquote(value: &str) -> Option<String> {
+    let inner = value.strip_prefix('\'')?.strip_suffix('\'')?;
+    let mut unquoted = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                'n' => unquoted.push('\n'),
+                escaped => unquoted.push(escaped),
+            },
+            // An unescaped quote ends the string early
+            '\'' => return None,
+            c => unquoted.push(c),
+        }
+    }
+    Some(unquoted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_round_trip() {
+        let command = "echo 'done' \\o/\necho next";
+        let quoted = groovy_quote(command);
+        assert_eq!(quoted, r"'echo \'done\' \\o/\necho next'");
+        assert_eq!(groovy_unquote(&quoted).as_deref(), Some(command));
+        assert_eq!(groovy_unquote("'a' + 'b'"), None);
+    }
+}
@@ -3,13 +3,32 @@
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct JenkinsConfig {
-    pub agent: String,
+    pub agent: JenkinsAgent,
     pub environment: Vec<(String, String)>,
+    /// Pipeline triggers, e.g. `cron('H 2 * * *')`
+    pub triggers: Vec<String>,
+    /// Conditions applied to every stage as `when { anyOf { ... } }`
+    pub when: Vec<String>,
     pub stages: Vec<JenkinsStage>,
+    /// Steps run after all stages, keyed by condition (`failure`, `success`, `always`)
+    pub post: Vec<(String, Vec<String>)>,
+}
+
+/// Where the pipeline runs
+#[derive(Debug, Clone, PartialEq)]
+pub enum JenkinsAgent {
+    /// `agent any`
+    Any,
+    /// `agent { label '...' }`
+    Label(String),
+    /// `agent { docker { image '...' } }`
+    Docker(String),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct JenkinsStage {
     pub name: String,
+    /// Conditions for this stage alone, all of which must hold
+    pub when: Vec<String>,
     pub steps: Vec<String>,
 }
@@ -0,0 +1,237 @@
+//! Jobs in the order they're written
+//!
+//! Pipelines hold their jobs in [`Jobs`] rather than a map sorted by ID, so
+//! they're written in the order presets add them (test, then lint, then
+//! build) and renaming a job doesn't move it. Inserting a job that's already
+//! there replaces it in place.
+
+use serde::de::{MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Jobs by ID, in insertion order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Jobs<J> {
+    entries: Vec<(String, J)>,
+}
+
+impl<J> Jobs<J> {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn position(&self, id: &str) -> Option<usize> {
+        self.entries.iter().position(|(known, _)| known == id)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&J> {
+        self.position(id).map(|index| &self.entries[index].1)
+    }
+
+    pub fn get_mut(&mut self, id: &str) -> Option<&mut J> {
+        self.position(id).map(|index| &mut self.entries[index].1)
+    }
+
+    pub fn contains_key(&self, id: &str) -> bool {
+        self.position(id).is_some()
+    }
+
+    /// Add `job` last, or put it in place of the job with the same ID,
+    /// returning that one
+    pub fn insert(&mut self, id: impl Into<String>, job: J) -> Option<J> {
+        let id = id.into();
+        match self.position(&id) {
+            Some(index) => Some(std::mem::replace(&mut self.entries[index].1, job)),
+            None => {
+                self.entries.push((id, job));
+                None
+            }
+        }
+    }
+
+    /// Add `job` last unless there's already a job with its ID
+    pub fn insert_if_absent(&mut self, id: impl Into<String>, job: J) {
+        let id = id.into();
+        if !self.contains_key(&id) {
+            self.entries.push((id, job));
+        }
+    }
+
+    /// Take out a job, keeping the others in order
+    pub fn remove(&mut self, id: &str) -> Option<J> {
+        self.position(id).map(|index| self.entries.remove(index).1)
+    }
+
+    pub fn retain(&mut self, mut keep: impl FnMut(&String, &mut J) -> bool) {
+        self.entries.retain_mut(|(id, job)| keep(id, job));
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&String, &J)> {
+        self.entries.iter().map(|(id, job)| (id, job))
+    }
+
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = (&String, &mut J)> {
+        self.entries.iter_mut().map(|(id, job)| (&*id, job))
+    }
+
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &String> {
+        self.entries.iter().map(|(id, _)| id)
+    }
+
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &J> {
+        self.entries.iter().map(|(_, job)| job)
+    }
+
+    pub fn values_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut J> {
+        self.entries.iter_mut().map(|(_, job)| job)
+    }
+}
+
+impl<J> Default for Jobs<J> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<J> std::ops::Index<&str> for Jobs<J> {
+    type Output = J;
+
+    fn index(&self, id: &str) -> &J {
+        self.get(id)
+            .unwrap_or_else(|| panic!("no job with ID `{}`", id))
+    }
+}
+
+impl<J, K: Into<String>> Extend<(K, J)> for Jobs<J> {
+    fn extend<I: IntoIterator<Item = (K, J)>>(&mut self, jobs: I) {
+        for (id, job) in jobs {
+            self.insert(id, job);
+        }
+    }
+}
+
+impl<J, K: Into<String>> FromIterator<(K, J)> for Jobs<J> {
+    fn from_iter<I: IntoIterator<Item = (K, J)>>(jobs: I) -> Self {
+        let mut result = Self::new();
+        result.extend(jobs);
+        result
+    }
+}
+
+impl<J, K: Into<String>, const N: usize> From<[(K, J); N]> for Jobs<J> {
+    fn from(jobs: [(K, J); N]) -> Self {
+        jobs.into_iter().collect()
+    }
+}
+
+impl<J> IntoIterator for Jobs<J> {
+    type Item = (String, J);
+    type IntoIter = std::vec::IntoIter<(String, J)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a, J> IntoIterator for &'a Jobs<J> {
+    type Item = (&'a String, &'a J);
+    type IntoIter = std::iter::Map<
+        std::slice::Iter<'a, (String, J)>,
+        fn(&'a (String, J)) -> (&'a String, &'a J),
+    >;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter().map(|(id, job)| (id, job))
+    }
+}
+
+impl<'a, J> IntoIterator for &'a mut Jobs<J> {
+    type Item = (&'a String, &'a mut J);
+    type IntoIter = std::iter::Map<
+        std::slice::IterMut<'a, (String, J)>,
+        fn(&'a mut (String, J)) -> (&'a String, &'a mut J),
+    >;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter_mut().map(|(id, job)| (&*id, job))
+    }
+}
+
+impl<J: Serialize> Serialize for Jobs<J> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.entries.len()))?;
+        for (id, job) in &self.entries {
+            map.serialize_entry(id, job)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de, J: Deserialize<'de>> Deserialize<'de> for Jobs<J> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct JobsVisitor<J>(PhantomData<J>);
+
+        impl<'de, J: Deserialize<'de>> Visitor<'de> for JobsVisitor<J> {
+            type Value = Jobs<J>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of jobs by ID")
+            }
+
+            fn visit_map<M: MapAccess<'de>>(self, mut access: M) -> Result<Jobs<J>, M::Error> {
+                let mut jobs = Jobs::new();
+                while let Some((id, job)) = access.next_entry::<String, J>()? {
+                    jobs.insert(id, job);
+                }
+                Ok(jobs)
+            }
+        }
+
+        deserializer.deserialize_map(JobsVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jobs_keep_insertion_order() {
+        let mut jobs = Jobs::new();
+        jobs.insert("test", 1);
+        jobs.insert("lint", 2);
+        jobs.insert("build", 3);
+        assert_eq!(jobs.insert("lint", 4), Some(2));
+        jobs.insert_if_absent("test", 5);
+        assert_eq!(
+            jobs.iter()
+                .map(|(id, job)| (id.as_str(), *job))
+                .collect::<Vec<_>>(),
+            [("test", 1), ("lint", 4), ("build", 3)]
+        );
+
+        jobs.remove("test");
+        jobs.insert("test", 1);
+        assert_eq!(jobs.keys().collect::<Vec<_>>(), ["lint", "build", "test"]);
+    }
+
+    #[test]
+    fn test_jobs_round_trip_in_order() {
+        let jobs = Jobs::from([("test", 1), ("lint", 2), ("build", 3)]);
+        let yaml = serde_yaml::to_string(&jobs).unwrap();
+        assert_eq!(yaml, "test: 1\nlint: 2\nbuild: 3\n");
+        assert_eq!(serde_yaml::from_str::<Jobs<u32>>(&yaml).unwrap(), jobs);
+    }
+}
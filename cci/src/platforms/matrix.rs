@@ -0,0 +1,289 @@
+use crate::editor::state::Platform;
+use crate::platforms::circleci::models::{CircleCIMatrix, CircleCIParameter};
+use crate::platforms::github::models::{GitHubMatrix, GitHubStrategy, GitHubWorkflow};
+use crate::platforms::gitlab::models::GitLabParallel;
+use std::collections::BTreeMap;
+
+/// Platform-neutral build matrix for a single job
+///
+/// The job runs once for every combination of axis values, minus `exclude`,
+/// plus any extra `include` combinations.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Matrix {
+    /// Axis name -> values, in declaration order
+    pub axes: Vec<(String, Vec<String>)>,
+    /// Extra combinations to run
+    pub include: Vec<BTreeMap<String, String>>,
+    /// Combinations to skip
+    pub exclude: Vec<BTreeMap<String, String>>,
+}
+
+impl Matrix {
+    /// Build a single-axis matrix from a comma-separated option, if it has any values
+    pub fn from_list(axis: &str, values: &str) -> Option<Self> {
+        let values: Vec<String> = values
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect();
+        (!values.is_empty()).then(|| Self {
+            axes: vec![(axis.to_string(), values)],
+            ..Self::default()
+        })
+    }
+
+    /// The values of `axis` in the first of `workflow`'s jobs with it, as the
+    /// comma-separated option [`Matrix::from_list`] reads
+    pub fn list_from_github(workflow: &GitHubWorkflow, axis: &str) -> Option<String> {
+        workflow
+            .jobs
+            .values()
+            .find_map(|job| job.strategy.as_ref()?.matrix.axes.get(axis))
+            .map(|values| values.join(", "))
+    }
+
+    /// Every combination the job runs with
+    pub fn combinations(&self) -> Vec<BTreeMap<String, String>> {
+        let mut combinations = vec![BTreeMap::new()];
+        for (axis, values) in &self.axes {
+            combinations = combinations
+                .into_iter()
+                .flat_map(|combination| {
+                    values.iter().map(move |value| {
+                        let mut next = combination.clone();
+                        next.insert(axis.clone(), value.clone());
+                        next
+                    })
+                })
+                .collect();
+        }
+
+        combinations.retain(|combination| {
+            !self
+                .exclude
+                .iter()
+                .any(|exclude| exclude.iter().all(|(k, v)| combination.get(k) == Some(v)))
+        });
+        for include in &self.include {
+            if !combinations.contains(include) {
+                combinations.push(include.clone());
+            }
+        }
+        combinations
+    }
+
+    /// Reference to an axis value inside a GitHub Actions job
+    pub fn github_var(axis: &str) -> String {
+        format!("${{{{ matrix.{} }}}}", axis)
+    }
+
+    /// Reference to an axis value inside a GitLab CI job (matrix keys become variables)
+    pub fn gitlab_var(axis: &str) -> String {
+        format!("${}", Self::gitlab_key(axis))
+    }
+
+    /// Reference to an axis value inside a parameterized CircleCI job
+    pub fn circleci_var(axis: &str) -> String {
+        format!("<< parameters.{} >>", axis)
+    }
+
+    /// Reference to an axis value inside a job on `platform`
+    ///
+    /// The Jenkins model has no matrices; there it's the environment variable
+    /// a declarative `matrix` directive would set.
+    pub fn var(platform: Platform, axis: &str) -> String {
+        match platform {
+            Platform::GitHub | Platform::Gitea => Self::github_var(axis),
+            Platform::GitLab => Self::gitlab_var(axis),
+            Platform::CircleCI => Self::circleci_var(axis),
+            Platform::Jenkins => format!("${{{}}}", Self::gitlab_key(axis)),
+        }
+    }
+
+    fn gitlab_key(axis: &str) -> String {
+        axis.to_uppercase().replace('-', "_")
+    }
+
+    /// GitHub Actions `strategy:` block; include/exclude map directly
+    pub fn to_github(&self) -> GitHubStrategy {
+        GitHubStrategy {
+            matrix: GitHubMatrix {
+                axes: self.axes.iter().cloned().collect(),
+                include: self.include.clone(),
+                exclude: self.exclude.clone(),
+            },
+            // One failing combination shouldn't hide results from the others
+            fail_fast: Some(false),
+        }
+    }
+
+    /// GitLab CI `parallel: matrix:` block
+    ///
+    /// GitLab has no include/exclude, so those matrices are expanded into one
+    /// entry per combination.
+    pub fn to_gitlab(&self) -> GitLabParallel {
+        let matrix = if self.include.is_empty() && self.exclude.is_empty() {
+            vec![self
+                .axes
+                .iter()
+                .map(|(axis, values)| (Self::gitlab_key(axis), values.clone()))
+                .collect()]
+        } else {
+            self.combinations()
+                .into_iter()
+                .map(|combination| {
+                    combination
+                        .into_iter()
+                        .map(|(axis, value)| (Self::gitlab_key(&axis), vec![value]))
+                        .collect()
+                })
+                .collect()
+        };
+        GitLabParallel { matrix }
+    }
+
+    /// CircleCI workflow `matrix:` block
+    ///
+    /// CircleCI only supports excludes, so includes are folded in by widening
+    /// each axis and excluding every combination that wasn't requested.
+    pub fn to_circleci(&self) -> CircleCIMatrix {
+        if self.include.is_empty() {
+            return CircleCIMatrix {
+                parameters: self.axes.iter().cloned().collect(),
+                exclude: self.exclude.clone(),
+            };
+        }
+
+        let combinations = self.combinations();
+        let mut widened: Vec<(String, Vec<String>)> = self.axes.clone();
+        for combination in &combinations {
+            for (axis, value) in combination {
+                match widened.iter_mut().find(|(name, _)| name == axis) {
+                    Some((_, values)) if !values.contains(value) => values.push(value.clone()),
+                    Some(_) => {}
+                    None => widened.push((axis.clone(), vec![value.clone()])),
+                }
+            }
+        }
+
+        let full = Matrix {
+            axes: widened.clone(),
+            ..Matrix::default()
+        };
+        CircleCIMatrix {
+            parameters: widened.into_iter().collect(),
+            exclude: full
+                .combinations()
+                .into_iter()
+                .filter(|combination| !combinations.contains(combination))
+                .collect(),
+        }
+    }
+
+    /// Parameter declarations the matrix job must accept on CircleCI
+    pub fn circleci_parameters(&self) -> BTreeMap<String, CircleCIParameter> {
+        self.axes
+            .iter()
+            .map(|(axis, values)| {
+                (
+                    axis.clone(),
+                    CircleCIParameter {
+                        param_type: "string".to_string(),
+                        default: values.first().cloned(),
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix() -> Matrix {
+        Matrix {
+            axes: vec![
+                (
+                    "toolchain".to_string(),
+                    vec!["stable".to_string(), "nightly".to_string()],
+                ),
+                (
+                    "os".to_string(),
+                    vec!["linux".to_string(), "windows".to_string()],
+                ),
+            ],
+            include: vec![BTreeMap::from([
+                ("toolchain".to_string(), "1.70".to_string()),
+                ("os".to_string(), "linux".to_string()),
+            ])],
+            exclude: vec![BTreeMap::from([
+                ("toolchain".to_string(), "nightly".to_string()),
+                ("os".to_string(), "windows".to_string()),
+            ])],
+        }
+    }
+
+    #[test]
+    fn test_from_list() {
+        let matrix = Matrix::from_list("python_version", "3.10, 3.11,,").unwrap();
+        assert_eq!(
+            matrix.axes,
+            vec![(
+                "python_version".to_string(),
+                vec!["3.10".to_string(), "3.11".to_string()]
+            )]
+        );
+        assert!(Matrix::from_list("python_version", " ").is_none());
+    }
+
+    #[test]
+    fn test_combinations() {
+        let combinations = matrix().combinations();
+        assert_eq!(combinations.len(), 4);
+        assert!(!combinations
+            .iter()
+            .any(|c| c["toolchain"] == "nightly" && c["os"] == "windows"));
+        assert!(combinations.iter().any(|c| c["toolchain"] == "1.70"));
+    }
+
+    #[test]
+    fn test_to_gitlab_expands_include_exclude() {
+        let parallel = matrix().to_gitlab();
+        assert_eq!(parallel.matrix.len(), 4);
+        assert!(parallel
+            .matrix
+            .iter()
+            .all(|entry| entry.contains_key("TOOLCHAIN")));
+
+        let simple = Matrix::from_list("toolchain", "stable, beta").unwrap();
+        assert_eq!(
+            simple.to_gitlab().matrix,
+            vec![BTreeMap::from([(
+                "TOOLCHAIN".to_string(),
+                vec!["stable".to_string(), "beta".to_string()]
+            )])]
+        );
+    }
+
+    #[test]
+    fn test_to_circleci_folds_includes_into_excludes() {
+        let circleci = matrix().to_circleci();
+        assert_eq!(
+            circleci.parameters["toolchain"],
+            vec!["stable", "nightly", "1.70"]
+        );
+        // 3 toolchains x 2 OSes, minus the 4 requested combinations
+        assert_eq!(circleci.exclude.len(), 2);
+    }
+
+    #[test]
+    fn test_to_github_serializes_axes() {
+        let yaml = serde_yaml::to_string(&matrix().to_github()).unwrap();
+        assert!(yaml.contains("fail-fast: false"));
+        assert!(yaml.contains("toolchain:"));
+        assert!(yaml.contains("include:"));
+        assert!(yaml.contains("exclude:"));
+    }
+}
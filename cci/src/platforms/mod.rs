@@ -1,6 +1,18 @@
+pub mod artifacts;
+pub mod cache;
+pub mod checks;
 pub mod circleci;
+pub mod coverage;
+pub mod env;
 pub mod gitea;
 pub mod github;
 pub mod gitlab;
 pub mod helpers;
 pub mod jenkins;
+pub mod jobs;
+pub mod matrix;
+pub mod os;
+pub mod runner;
+pub mod secrets;
+pub mod services;
+pub mod triggers;
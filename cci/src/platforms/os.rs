@@ -0,0 +1,135 @@
+//! Test jobs across operating systems
+//!
+//! GitHub and Gitea workflows can run a test job on Linux, macOS and Windows
+//! runners at once, through an `os` matrix axis. Run steps are written for
+//! bash, which every hosted runner has (Git Bash on Windows), so the same
+//! commands and `/`-separated paths work everywhere. Steps that only work on
+//! Linux, like coverage with tarpaulin, are skipped on the rest; service and
+//! job containers need Linux runners altogether.
+
+use crate::platforms::github::models::{GitHubDefaults, GitHubStep, GitHubWorkflow};
+use crate::platforms::matrix::Matrix;
+
+/// Operating system a job runs on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Os {
+    Linux,
+    MacOS,
+    Windows,
+}
+
+impl Os {
+    /// Every OS, in the order they're listed
+    pub const ALL: [Os; 3] = [Os::Linux, Os::MacOS, Os::Windows];
+
+    /// Name in the comma-separated option, e.g. `macos`
+    pub fn id(self) -> &'static str {
+        match self {
+            Os::Linux => "linux",
+            Os::MacOS => "macos",
+            Os::Windows => "windows",
+        }
+    }
+
+    /// GitHub-hosted runner label
+    pub fn github_runner(self) -> &'static str {
+        match self {
+            Os::Linux => "ubuntu-latest",
+            Os::MacOS => "macos-latest",
+            Os::Windows => "windows-latest",
+        }
+    }
+
+    fn from_runner(label: &str) -> Option<Self> {
+        Os::ALL
+            .into_iter()
+            .find(|os| label.starts_with(os.github_runner().split('-').next().unwrap()))
+    }
+}
+
+/// Operating systems a preset's test job runs on
+#[derive(Debug, Clone, PartialEq)]
+pub struct OsMatrix {
+    pub systems: Vec<Os>,
+}
+
+impl OsMatrix {
+    /// Read the comma-separated option; there's nothing to do for an empty
+    /// list or Linux alone
+    pub fn from_list(list: &str) -> Option<Self> {
+        let mut systems = Vec::new();
+        for id in list.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+            if let Some(os) = Os::ALL.into_iter().find(|os| os.id() == id) {
+                if !systems.contains(&os) {
+                    systems.push(os);
+                }
+            }
+        }
+        (systems.iter().any(|os| *os != Os::Linux)).then_some(Self { systems })
+    }
+
+    /// The systems of the first of `workflow`'s jobs with an `os` axis, as
+    /// the comma-separated option [`OsMatrix::from_list`] reads
+    pub fn list_from_github(workflow: &GitHubWorkflow) -> Option<String> {
+        let runners = Matrix::list_from_github(workflow, "os")?;
+        let ids: Vec<&str> = runners
+            .split(", ")
+            .filter_map(Os::from_runner)
+            .map(Os::id)
+            .collect();
+        (!ids.is_empty()).then(|| ids.join(", "))
+    }
+
+    /// Only run `step` on Linux runners
+    pub fn linux_only(step: &mut GitHubStep) {
+        step.if_condition = Some("runner.os == 'Linux'".to_string());
+    }
+
+    /// Run `job_id` once per system, and run steps under bash
+    ///
+    /// The `os` axis joins whatever matrix the job already has. Call this
+    /// after the runner selection, since it replaces the job's `runs-on`.
+    pub fn apply_github(&self, workflow: &mut GitHubWorkflow, job_id: &str) {
+        let Some(job) = workflow.jobs.get_mut(job_id) else {
+            return;
+        };
+        let runners = self
+            .systems
+            .iter()
+            .map(|os| os.github_runner().to_string())
+            .collect();
+        let strategy = job
+            .strategy
+            .get_or_insert_with(|| Matrix::default().to_github());
+        strategy.matrix.axes.insert("os".to_string(), runners);
+        job.runs_on = Matrix::github_var("os").as_str().into();
+
+        if self.systems.contains(&Os::Windows) {
+            workflow
+                .defaults
+                .get_or_insert_with(GitHubDefaults::default)
+                .run
+                .shell
+                .get_or_insert_with(|| "bash".to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_os_list() {
+        assert!(OsMatrix::from_list("").is_none());
+        assert!(OsMatrix::from_list("linux").is_none());
+        assert_eq!(
+            OsMatrix::from_list("linux, windows, linux")
+                .unwrap()
+                .systems,
+            vec![Os::Linux, Os::Windows]
+        );
+        assert_eq!(Os::from_runner("macos-14"), Some(Os::MacOS));
+        assert_eq!(Os::from_runner("ubuntu-22.04"), Some(Os::Linux));
+    }
+}
@@ -0,0 +1,258 @@
+use crate::platforms::circleci::models::{
+    CircleCIConfig, CircleCIDocker, CircleCIMachine, CircleCIMacos,
+};
+use crate::platforms::github::models::{GitHubRunsOn, GitHubWorkflow};
+use crate::platforms::gitlab::models::GitLabCI;
+use crate::platforms::jenkins::models::{JenkinsAgent, JenkinsConfig};
+use cci_macros::PresetEnum;
+
+/// Where CI jobs run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, PresetEnum)]
+#[preset_enum(default = "UbuntuLatest")]
+#[serde(rename_all = "lowercase")]
+pub enum RunnerKind {
+    #[preset_variant(id = "ubuntu-latest", display = "Ubuntu (latest)")]
+    #[serde(rename = "ubuntu_latest")]
+    UbuntuLatest,
+    #[preset_variant(id = "ubuntu-22.04", display = "Ubuntu 22.04")]
+    #[serde(rename = "ubuntu_22_04")]
+    Ubuntu2204,
+    #[preset_variant(id = "macos", display = "macOS")]
+    MacOS,
+    #[preset_variant(id = "windows", display = "Windows")]
+    Windows,
+    #[preset_variant(id = "self-hosted", display = "Self-hosted")]
+    #[serde(rename = "self_hosted")]
+    SelfHosted,
+    #[preset_variant(id = "container", display = "Container Image")]
+    Container,
+}
+
+/// Platform-neutral runner selection
+///
+/// Presets build jobs for the default Linux runner and then call the
+/// matching `apply_*` method to retarget them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunnerConfig {
+    pub kind: RunnerKind,
+    /// Extra labels for self-hosted runners, or the image for container jobs
+    pub labels: Vec<String>,
+}
+
+impl RunnerConfig {
+    /// Build a runner config from the preset's runner options
+    ///
+    /// `label` is a comma-separated list of self-hosted runner labels, or a
+    /// container image when `kind` is [`RunnerKind::Container`].
+    pub fn from_options(kind: RunnerKind, label: &str) -> Self {
+        Self {
+            kind,
+            labels: label
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+
+    /// Container image to run jobs in, if one was selected
+    fn container_image(&self) -> Option<&str> {
+        match self.kind {
+            RunnerKind::Container => self.labels.first().map(String::as_str),
+            _ => None,
+        }
+    }
+
+    /// GitHub Actions `runs-on` value
+    pub fn github_runs_on(&self) -> GitHubRunsOn {
+        match self.kind {
+            RunnerKind::UbuntuLatest | RunnerKind::Container => "ubuntu-latest".into(),
+            RunnerKind::Ubuntu2204 => "ubuntu-22.04".into(),
+            RunnerKind::MacOS => "macos-latest".into(),
+            RunnerKind::Windows => "windows-latest".into(),
+            RunnerKind::SelfHosted => {
+                let mut labels = vec!["self-hosted".to_string()];
+                labels.extend(self.labels.iter().cloned());
+                GitHubRunsOn::Labels(labels)
+            }
+        }
+    }
+
+    /// Set `runs-on` (and `container`) on every job
+    pub fn apply_github(&self, workflow: &mut GitHubWorkflow) {
+        for job in workflow.jobs.values_mut() {
+            job.runs_on = self.github_runs_on();
+            if let Some(image) = self.container_image() {
+                job.container = Some(image.to_string());
+            }
+        }
+    }
+
+    /// Gitea Actions `runs-on` value
+    ///
+    /// Gitea runners only match the labels they're registered with and have
+    /// no implicit `self-hosted` label, so self-hosted labels are used as is.
+    pub fn gitea_runs_on(&self) -> GitHubRunsOn {
+        match self.kind {
+            RunnerKind::SelfHosted if self.labels.is_empty() => "ubuntu-latest".into(),
+            RunnerKind::SelfHosted => GitHubRunsOn::Labels(self.labels.clone()),
+            _ => self.github_runs_on(),
+        }
+    }
+
+    /// Retarget the jobs of a workflow built for GitHub that run on the
+    /// selected runner, leaving ones that pick theirs from a matrix
+    pub fn apply_gitea(&self, workflow: &mut GitHubWorkflow) {
+        let github = self.github_runs_on();
+        for job in workflow.jobs.values_mut() {
+            if job.runs_on == github {
+                job.runs_on = self.gitea_runs_on();
+            }
+        }
+    }
+
+    /// Select runners through job `tags`, or override the job image for containers
+    ///
+    /// macOS and Windows use the GitLab.com hosted runner tags.
+    pub fn apply_gitlab(&self, ci: &mut GitLabCI) {
+        let tags = match self.kind {
+            RunnerKind::MacOS => Some(vec!["saas-macos-medium-m1".to_string()]),
+            RunnerKind::Windows => Some(vec!["saas-windows-medium-amd64".to_string()]),
+            RunnerKind::SelfHosted if !self.labels.is_empty() => Some(self.labels.clone()),
+            _ => None,
+        };
+
+        for job in ci.jobs.values_mut() {
+            if let Some(tags) = &tags {
+                job.tags = Some(tags.clone());
+            }
+            if let Some(image) = self.container_image() {
+                job.image = Some(image.to_string());
+            }
+        }
+    }
+
+    /// Switch job executors away from the default Docker executor
+    pub fn apply_circleci(&self, config: &mut CircleCIConfig) {
+        for job in config.jobs.values_mut() {
+            match self.kind {
+                RunnerKind::UbuntuLatest => {}
+                RunnerKind::Container => {
+                    if let Some(image) = self.container_image() {
+                        // Only the primary image; the rest are service containers
+                        let primary = CircleCIDocker {
+                            image: image.to_string(),
+                            environment: None,
+                        };
+                        match job.docker.first_mut() {
+                            Some(docker) => *docker = primary,
+                            None => job.docker.push(primary),
+                        }
+                    }
+                }
+                RunnerKind::Ubuntu2204 => {
+                    job.docker.clear();
+                    job.machine = Some(CircleCIMachine::Image {
+                        image: "ubuntu-2204:current".to_string(),
+                        shell: None,
+                    });
+                }
+                RunnerKind::MacOS => {
+                    job.docker.clear();
+                    job.macos = Some(CircleCIMacos {
+                        xcode: "15.4.0".to_string(),
+                    });
+                    job.resource_class = Some("macos.m1.medium.gen1".to_string());
+                }
+                RunnerKind::Windows => {
+                    job.docker.clear();
+                    job.machine = Some(CircleCIMachine::Image {
+                        image: "windows-server-2022-gui:current".to_string(),
+                        shell: Some("bash.exe".to_string()),
+                    });
+                    job.resource_class = Some("windows.medium".to_string());
+                }
+                RunnerKind::SelfHosted => {
+                    job.docker.clear();
+                    job.machine = Some(CircleCIMachine::Enabled(true));
+                    // Self-hosted runners are addressed by their resource class
+                    job.resource_class = self.labels.first().cloned();
+                }
+            }
+        }
+    }
+
+    /// Set the pipeline agent
+    pub fn apply_jenkins(&self, config: &mut JenkinsConfig) {
+        config.agent = match self.kind {
+            RunnerKind::UbuntuLatest => JenkinsAgent::Any,
+            RunnerKind::Ubuntu2204 => JenkinsAgent::Label("ubuntu-22.04".to_string()),
+            RunnerKind::MacOS => JenkinsAgent::Label("macos".to_string()),
+            RunnerKind::Windows => JenkinsAgent::Label("windows".to_string()),
+            RunnerKind::SelfHosted if self.labels.is_empty() => JenkinsAgent::Any,
+            RunnerKind::SelfHosted => JenkinsAgent::Label(self.labels.join(" && ")),
+            RunnerKind::Container => match self.container_image() {
+                Some(image) => JenkinsAgent::Docker(image.to_string()),
+                None => JenkinsAgent::Any,
+            },
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_github_runs_on() {
+        let default = RunnerConfig::default();
+        assert_eq!(default.github_runs_on(), "ubuntu-latest".into());
+
+        let self_hosted = RunnerConfig::from_options(RunnerKind::SelfHosted, "linux, gpu");
+        assert_eq!(
+            self_hosted.github_runs_on(),
+            GitHubRunsOn::Labels(vec![
+                "self-hosted".to_string(),
+                "linux".to_string(),
+                "gpu".to_string()
+            ])
+        );
+        assert_eq!(
+            self_hosted.gitea_runs_on(),
+            GitHubRunsOn::Labels(vec!["linux".to_string(), "gpu".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_apply_jenkins_agent() {
+        let mut config = JenkinsConfig {
+            agent: JenkinsAgent::Any,
+            environment: vec![],
+            triggers: vec![],
+            when: vec![],
+            stages: vec![],
+            post: vec![],
+        };
+
+        RunnerConfig::from_options(RunnerKind::Container, "rust:1.75").apply_jenkins(&mut config);
+        assert_eq!(config.agent, JenkinsAgent::Docker("rust:1.75".to_string()));
+
+        RunnerConfig::from_options(RunnerKind::SelfHosted, "linux, docker")
+            .apply_jenkins(&mut config);
+        assert_eq!(
+            config.agent,
+            JenkinsAgent::Label("linux && docker".to_string())
+        );
+    }
+
+    #[test]
+    fn test_runner_kind_ids() {
+        assert_eq!(RunnerKind::default(), RunnerKind::UbuntuLatest);
+        assert_eq!(
+            RunnerKind::from_str("self-hosted"),
+            Some(RunnerKind::SelfHosted)
+        );
+        assert_eq!(RunnerKind::Ubuntu2204.as_str(), "ubuntu-22.04");
+    }
+}
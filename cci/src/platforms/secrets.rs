@@ -0,0 +1,89 @@
+//! Secrets that generated configs reference
+//!
+//! Presets declare the secrets their output needs for each platform so users
+//! can set them up before the first run instead of debugging a red build.
+
+use crate::editor::state::Platform;
+use std::borrow::Cow;
+
+/// A secret or protected variable the CI platform must provide
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Secret {
+    pub name: Cow<'static, str>,
+    pub description: &'static str,
+}
+
+impl Secret {
+    /// Secret whose name is chosen by the user
+    pub fn named(name: impl Into<String>, description: &'static str) -> Self {
+        Self {
+            name: Cow::Owned(name.into()),
+            description,
+        }
+    }
+}
+
+pub const CODECOV_TOKEN: Secret = Secret {
+    name: Cow::Borrowed("CODECOV_TOKEN"),
+    description: "Upload token from the repository's Codecov settings",
+};
+
+pub const COVERALLS_REPO_TOKEN: Secret = Secret {
+    name: Cow::Borrowed("COVERALLS_REPO_TOKEN"),
+    description: "Repo token from the repository's Coveralls settings",
+};
+
+pub const CC_TEST_REPORTER_ID: Secret = Secret {
+    name: Cow::Borrowed("CC_TEST_REPORTER_ID"),
+    description: "Test reporter ID from the repository's Code Climate settings",
+};
+
+pub const DOCKER_USERNAME: Secret = Secret {
+    name: Cow::Borrowed("DOCKER_USERNAME"),
+    description: "Docker Hub account name",
+};
+
+pub const DOCKER_PASSWORD: Secret = Secret {
+    name: Cow::Borrowed("DOCKER_PASSWORD"),
+    description: "Docker Hub access token with push permission",
+};
+
+pub const GITHUB_USERNAME: Secret = Secret {
+    name: Cow::Borrowed("GITHUB_USERNAME"),
+    description: "GitHub account that owns the container packages",
+};
+
+pub const GITHUB_TOKEN: Secret = Secret {
+    name: Cow::Borrowed("GITHUB_TOKEN"),
+    description: "GitHub personal access token with write:packages",
+};
+
+pub const GH_TOKEN: Secret = Secret {
+    name: Cow::Borrowed("GH_TOKEN"),
+    description: "GitHub token with contents: write, used by gh to publish releases",
+};
+
+pub const GITLAB_TOKEN: Secret = Secret {
+    name: Cow::Borrowed("GITLAB_TOKEN"),
+    description: "GitLab project access token with the api and write_repository scopes",
+};
+
+/// Where secrets are configured on a platform
+pub fn setup_location(platform: Platform) -> &'static str {
+    match platform {
+        Platform::GitHub => "Repository Settings → Secrets and variables → Actions",
+        Platform::Gitea => "Repository Settings → Actions → Secrets",
+        Platform::GitLab => "Settings → CI/CD → Variables (mark them as masked)",
+        Platform::CircleCI => "Project Settings → Environment Variables, or a context",
+        Platform::Jenkins => "Manage Jenkins → Credentials, bound as environment variables",
+    }
+}
+
+/// CLI command that sets a secret, for platforms that have one
+pub fn setup_command(platform: Platform, secret: &Secret) -> Option<String> {
+    match platform {
+        Platform::GitHub => Some(format!("gh secret set {}", secret.name)),
+        Platform::GitLab => Some(format!("glab variable set {} --masked", secret.name)),
+        Platform::Gitea | Platform::CircleCI | Platform::Jenkins => None,
+    }
+}
@@ -0,0 +1,1023 @@
+use crate::platforms::circleci::models::{
+    CircleCIConfig, CircleCIFilter, CircleCIFilters, CircleCIJobRequires, CircleCISchedule,
+    CircleCITrigger, CircleCIWorkflow, CircleCIWorkflowJob,
+};
+use crate::platforms::github::models::{
+    GitHubSchedule, GitHubTrigger, GitHubTriggerConfig, GitHubTriggers, GitHubWorkflow,
+};
+use crate::platforms::gitlab::models::{GitLabCI, GitLabRule, GitLabWorkflow};
+use crate::platforms::jenkins::models::JenkinsConfig;
+use std::collections::BTreeMap;
+
+/// Platform-neutral description of when a pipeline runs
+///
+/// Presets build this from their "Triggers" options and each platform
+/// backend translates it into its native syntax.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TriggerConfig {
+    /// Workflow/pipeline display name
+    pub name: String,
+    /// Branch patterns that trigger on push
+    pub push_branches: Vec<String>,
+    /// Target branch patterns that trigger on pull/merge requests
+    pub pr_branches: Vec<String>,
+    /// Tag patterns that trigger on push
+    pub tags: Vec<String>,
+    /// Cron expression for scheduled runs
+    pub schedule_cron: Option<String>,
+    /// Allow manually starting the pipeline
+    pub manual_dispatch: bool,
+    /// Jobs that only run on the schedule instead of on every push
+    ///
+    /// Entries match a job's full ID (`rust/security`), the part after its
+    /// last `/` (`security`), or the first word of a Jenkins stage name.
+    pub scheduled_jobs: Vec<String>,
+    /// Globs limiting push and pull request runs to changes touching them
+    ///
+    /// GitHub, Gitea and GitLab filter on them; CircleCI and Jenkins can't
+    /// without extra setup, so they run for every change.
+    pub paths: Vec<String>,
+}
+
+impl TriggerConfig {
+    /// Build a trigger config from the comma-separated option strings used by presets
+    pub fn from_options(
+        name: &str,
+        push_branches: &str,
+        pr_branches: &str,
+        tags: &str,
+        schedule_cron: &str,
+        manual_dispatch: bool,
+    ) -> Self {
+        let cron = schedule_cron.trim();
+        Self {
+            name: name.trim().to_string(),
+            push_branches: split_list(push_branches),
+            pr_branches: split_list(pr_branches),
+            tags: split_list(tags),
+            schedule_cron: (!cron.is_empty()).then(|| cron.to_string()),
+            manual_dispatch,
+            scheduled_jobs: Vec::new(),
+            paths: Vec::new(),
+        }
+    }
+
+    /// Read the triggers back from a GitHub-style workflow, the inverse of [`Self::to_github`]
+    ///
+    /// Events listed without filters run for every branch, written as `*`.
+    pub fn from_github(workflow: &GitHubWorkflow) -> Self {
+        let mut triggers = Self {
+            name: workflow.name.clone(),
+            ..Self::default()
+        };
+        let events = match &workflow.on {
+            GitHubTriggers::Single(event) => {
+                BTreeMap::from([(event.clone(), GitHubTrigger::Empty)])
+            }
+            GitHubTriggers::Simple(events) => events
+                .iter()
+                .map(|event| (event.clone(), GitHubTrigger::Empty))
+                .collect(),
+            GitHubTriggers::Detailed(events) => events.clone(),
+        };
+
+        let all = || vec!["*".to_string()];
+        for (event, trigger) in events {
+            let filter = match &trigger {
+                GitHubTrigger::Filter(filter) => Some(filter),
+                _ => None,
+            };
+            let branches = filter.and_then(|filter| filter.branches.clone());
+            let tags = filter.and_then(|filter| filter.tags.clone());
+            if let Some(paths) = filter.and_then(|filter| filter.paths.clone()) {
+                if triggers.paths.is_empty() {
+                    triggers.paths = paths;
+                }
+            }
+            match event.as_str() {
+                "push" => {
+                    triggers.tags = tags.clone().unwrap_or_default();
+                    // A tag filter alone means branch pushes don't trigger
+                    triggers.push_branches = match (branches, tags) {
+                        (Some(branches), _) => branches,
+                        (None, Some(_)) => Vec::new(),
+                        (None, None) => all(),
+                    };
+                }
+                "pull_request" => triggers.pr_branches = branches.unwrap_or_else(all),
+                "schedule" => {
+                    if let GitHubTrigger::Schedule(entries) = &trigger {
+                        triggers.schedule_cron = entries.first().map(|entry| entry.cron.clone());
+                    }
+                }
+                "workflow_dispatch" => triggers.manual_dispatch = true,
+                _ => {}
+            }
+        }
+        triggers
+    }
+
+    /// Read the triggers back from GitLab workflow rules, the inverse of [`Self::apply_gitlab`]
+    ///
+    /// Rules on the branch, merge request target, tag and pipeline source are
+    /// understood; others are noted and dropped. GitLab keeps schedules in
+    /// its settings, so a schedule rule carries no cron over.
+    pub fn from_gitlab(workflow: &GitLabWorkflow, notes: &mut Vec<String>) -> Self {
+        let mut triggers = Self {
+            name: workflow.name.clone().unwrap_or_default(),
+            ..Self::default()
+        };
+        let all = || vec!["*".to_string()];
+        for rule in &workflow.rules {
+            let condition = rule.if_condition.as_deref().unwrap_or_default();
+            if rule.when.as_deref() == Some("never") || condition.is_empty() {
+                notes.push(format!(
+                    "workflow rule `{}` was dropped",
+                    serde_yaml::to_string(rule).unwrap_or_default().trim()
+                ));
+                continue;
+            }
+            if let (Some(changes), true) = (&rule.changes, triggers.paths.is_empty()) {
+                triggers.paths = changes.clone();
+            }
+
+            let clauses: Vec<&str> = condition.split("&&").map(str::trim).collect();
+            let patterns = |variable: &str| {
+                clauses
+                    .iter()
+                    .find_map(|clause| clause_patterns(clause, variable))
+            };
+            let source = patterns("$CI_PIPELINE_SOURCE");
+            let source = source
+                .as_ref()
+                .and_then(|source| source.first())
+                .map(String::as_str);
+            let read = match source {
+                Some("merge_request_event") => {
+                    triggers.pr_branches =
+                        patterns("$CI_MERGE_REQUEST_TARGET_BRANCH_NAME").unwrap_or_else(all);
+                    true
+                }
+                Some("schedule") => {
+                    notes.push(
+                        "the schedule is set in GitLab's pipeline settings; add its cron by hand"
+                            .to_string(),
+                    );
+                    true
+                }
+                Some("web") => {
+                    triggers.manual_dispatch = true;
+                    true
+                }
+                Some("push") | None => {
+                    if let Some(tags) = patterns("$CI_COMMIT_TAG") {
+                        triggers.tags = tags;
+                        true
+                    } else if let Some(branches) = patterns("$CI_COMMIT_BRANCH") {
+                        triggers.push_branches = branches;
+                        true
+                    } else if source.is_some() {
+                        triggers.push_branches = all();
+                        true
+                    } else {
+                        false
+                    }
+                }
+                Some(_) => false,
+            };
+            if !read {
+                notes.push(format!("workflow rule `{}` was dropped", condition));
+            }
+        }
+        triggers
+    }
+
+    /// Whether a job or stage is one of `scheduled_jobs`
+    pub fn is_scheduled_job(&self, job: &str) -> bool {
+        let job = job.to_lowercase();
+        let short = job.rsplit('/').next().unwrap_or_default();
+        let first_word = short.split([' ', '-', '_']).next().unwrap_or_default();
+        self.scheduled_jobs.iter().any(|pattern| {
+            let pattern = pattern.to_lowercase();
+            pattern == job || pattern == short || pattern == first_word
+        })
+    }
+
+    /// Whether there is anything for the `apply_scheduled_*` methods to do
+    fn has_scheduled_jobs(&self) -> bool {
+        self.schedule_cron.is_some() && !self.scheduled_jobs.is_empty()
+    }
+
+    /// Add the schedule to an existing workflow and limit `scheduled_jobs` to it
+    ///
+    /// Other jobs keep running on every event, the schedule included, and stop
+    /// waiting on the scheduled jobs so they are not skipped along with them.
+    pub fn apply_scheduled_github(&self, workflow: &mut GitHubWorkflow) {
+        let Some(cron) = self
+            .schedule_cron
+            .clone()
+            .filter(|_| self.has_scheduled_jobs())
+        else {
+            return;
+        };
+        let scheduled: Vec<String> = workflow
+            .jobs
+            .keys()
+            .filter(|id| self.is_scheduled_job(id))
+            .cloned()
+            .collect();
+        if scheduled.is_empty() {
+            return;
+        }
+
+        let listed = match &workflow.on {
+            GitHubTriggers::Single(event) => Some(vec![event.clone()]),
+            GitHubTriggers::Simple(events) => Some(events.clone()),
+            GitHubTriggers::Detailed(_) => None,
+        };
+        if let Some(events) = listed {
+            workflow.on = GitHubTriggers::Detailed(
+                events
+                    .into_iter()
+                    .map(|event| (event, GitHubTrigger::Empty))
+                    .collect(),
+            );
+        }
+        if let GitHubTriggers::Detailed(events) = &mut workflow.on {
+            let entry = events
+                .entry("schedule".to_string())
+                .or_insert_with(|| GitHubTrigger::Schedule(vec![]));
+            if let GitHubTrigger::Schedule(schedules) = entry {
+                if !schedules.iter().any(|s| s.cron == cron) {
+                    schedules.push(GitHubSchedule { cron });
+                }
+            }
+        }
+
+        for (id, job) in workflow.jobs.iter_mut() {
+            if scheduled.contains(id) {
+                let schedule_only = "github.event_name == 'schedule'";
+                job.if_condition = Some(match job.if_condition.take() {
+                    Some(condition) => format!("({}) && {}", condition, schedule_only),
+                    None => schedule_only.to_string(),
+                });
+            } else if let Some(needs) = &mut job.needs {
+                needs.retain(|need| !scheduled.contains(need));
+                if needs.is_empty() {
+                    job.needs = None;
+                }
+            }
+        }
+    }
+
+    /// Limit `scheduled_jobs` to scheduled pipelines
+    ///
+    /// GitLab keeps schedules in the project settings rather than in
+    /// `.gitlab-ci.yml`, so the cron expression has to be added there.
+    pub fn apply_scheduled_gitlab(&self, ci: &mut GitLabCI) {
+        if !self.has_scheduled_jobs() {
+            return;
+        }
+        let scheduled: Vec<String> = ci
+            .jobs
+            .keys()
+            .filter(|id| self.is_scheduled_job(id))
+            .cloned()
+            .collect();
+        if scheduled.is_empty() {
+            return;
+        }
+
+        let schedule_rule = GitLabRule {
+            if_condition: Some("$CI_PIPELINE_SOURCE == \"schedule\"".to_string()),
+            changes: None,
+            when: None,
+        };
+        // Workflow rules decide whether a pipeline is created at all
+        if let Some(workflow) = &mut ci.workflow {
+            if !workflow.rules.is_empty() && !workflow.rules.contains(&schedule_rule) {
+                workflow.rules.push(schedule_rule.clone());
+            }
+        }
+
+        for (id, job) in ci.jobs.iter_mut() {
+            if scheduled.contains(id) {
+                // `only` and `rules` cannot be combined
+                job.only = None;
+                job.rules = Some(vec![schedule_rule.clone()]);
+            } else if let Some(needs) = &mut job.needs {
+                needs.retain(|need| !scheduled.contains(need));
+                if needs.is_empty() {
+                    job.needs = None;
+                }
+            }
+        }
+    }
+
+    /// Move `scheduled_jobs` out of the existing workflows into a scheduled `nightly` workflow
+    ///
+    /// The schedule builds the first of `push_branches`, or `main`.
+    pub fn apply_scheduled_circleci(&self, config: &mut CircleCIConfig) {
+        let Some(cron) = self
+            .schedule_cron
+            .clone()
+            .filter(|_| self.has_scheduled_jobs())
+        else {
+            return;
+        };
+        let scheduled: Vec<String> = config
+            .jobs
+            .keys()
+            .filter(|id| self.is_scheduled_job(id))
+            .cloned()
+            .collect();
+        if scheduled.is_empty() {
+            return;
+        }
+
+        let mut nightly_jobs: Vec<CircleCIWorkflowJob> = Vec::new();
+        for workflow in config.workflows.values_mut() {
+            let (moved, kept): (Vec<_>, Vec<_>) = workflow
+                .jobs
+                .drain(..)
+                .partition(|job| scheduled.iter().any(|id| id == workflow_job_name(job)));
+            workflow.jobs = kept;
+            for job in &mut workflow.jobs {
+                retain_requires(job, |need| !scheduled.contains(need));
+            }
+            for job in moved {
+                if !nightly_jobs
+                    .iter()
+                    .any(|existing| workflow_job_name(existing) == workflow_job_name(&job))
+                {
+                    nightly_jobs.push(job);
+                }
+            }
+        }
+        config
+            .workflows
+            .retain(|_, workflow| !workflow.jobs.is_empty());
+
+        // Branch filters of the original workflow do not apply to the schedule
+        for job in &mut nightly_jobs {
+            retain_requires(job, |need| scheduled.contains(need));
+            if let CircleCIWorkflowJob::WithRequires { job } = job {
+                for requires in job.values_mut() {
+                    requires.filters = None;
+                }
+            }
+        }
+
+        config.workflows.insert(
+            "nightly".to_string(),
+            CircleCIWorkflow {
+                triggers: Some(vec![CircleCITrigger {
+                    schedule: CircleCISchedule {
+                        cron,
+                        filters: CircleCIFilters {
+                            branches: Some(CircleCIFilter {
+                                only: Some(vec![self
+                                    .push_branches
+                                    .first()
+                                    .cloned()
+                                    .unwrap_or_else(|| "main".to_string())]),
+                                ignore: None,
+                            }),
+                            tags: None,
+                        },
+                    },
+                }]),
+                jobs: nightly_jobs,
+            },
+        );
+    }
+
+    /// Add the schedule to the pipeline and limit `scheduled_jobs` stages to timer builds
+    pub fn apply_scheduled_jenkins(&self, config: &mut JenkinsConfig) {
+        let Some(cron) = self
+            .schedule_cron
+            .as_ref()
+            .filter(|_| self.has_scheduled_jobs())
+        else {
+            return;
+        };
+        if !config
+            .stages
+            .iter()
+            .any(|stage| self.is_scheduled_job(&stage.name))
+        {
+            return;
+        }
+
+        let trigger = format!("cron('{}')", cron);
+        if !config.triggers.contains(&trigger) {
+            config.triggers.push(trigger);
+        }
+        // A pipeline-wide condition would otherwise skip every stage of a timer build
+        let timer = "triggeredBy 'TimerTrigger'".to_string();
+        if !config.when.is_empty() && !config.when.contains(&timer) {
+            config.when.push(timer.clone());
+        }
+
+        for stage in &mut config.stages {
+            if self.is_scheduled_job(&stage.name) {
+                stage.when.push(timer.clone());
+            }
+        }
+    }
+
+    /// Convert to the GitHub Actions `on:` block
+    pub fn to_github(&self) -> GitHubTriggers {
+        let mut events = BTreeMap::new();
+
+        if !self.push_branches.is_empty() || !self.tags.is_empty() {
+            events.insert(
+                "push".to_string(),
+                GitHubTrigger::Filter(GitHubTriggerConfig {
+                    branches: non_empty(&self.push_branches),
+                    tags: non_empty(&self.tags),
+                    paths: non_empty(&self.paths),
+                }),
+            );
+        }
+
+        if !self.pr_branches.is_empty() {
+            events.insert(
+                "pull_request".to_string(),
+                GitHubTrigger::Filter(GitHubTriggerConfig {
+                    branches: Some(self.pr_branches.clone()),
+                    tags: None,
+                    paths: non_empty(&self.paths),
+                }),
+            );
+        }
+
+        if let Some(cron) = &self.schedule_cron {
+            events.insert(
+                "schedule".to_string(),
+                GitHubTrigger::Schedule(vec![GitHubSchedule { cron: cron.clone() }]),
+            );
+        }
+
+        if self.manual_dispatch {
+            events.insert(
+                "workflow_dispatch".to_string(),
+                GitHubTrigger::Filter(GitHubTriggerConfig::default()),
+            );
+        }
+
+        GitHubTriggers::Detailed(events)
+    }
+
+    /// Add a `workflow:` block with rules deciding when the pipeline is created
+    ///
+    /// Push and merge request rules only match changes to `paths`, if any.
+    pub fn apply_gitlab(&self, ci: &mut GitLabCI) {
+        let mut rules = Vec::new();
+        let changes = non_empty(&self.paths);
+
+        if !self.push_branches.is_empty() {
+            rules.push((
+                format!(
+                    "$CI_COMMIT_BRANCH =~ {}",
+                    patterns_to_regex(&self.push_branches)
+                ),
+                changes.clone(),
+            ));
+        }
+        if !self.pr_branches.is_empty() {
+            rules.push((format!(
+                "$CI_PIPELINE_SOURCE == \"merge_request_event\" && $CI_MERGE_REQUEST_TARGET_BRANCH_NAME =~ {}",
+                patterns_to_regex(&self.pr_branches)
+            ), changes));
+        }
+        if !self.tags.is_empty() {
+            rules.push((
+                format!("$CI_COMMIT_TAG =~ {}", patterns_to_regex(&self.tags)),
+                None,
+            ));
+        }
+        if self.schedule_cron.is_some() {
+            rules.push(("$CI_PIPELINE_SOURCE == \"schedule\"".to_string(), None));
+        }
+        if self.manual_dispatch {
+            rules.push(("$CI_PIPELINE_SOURCE == \"web\"".to_string(), None));
+        }
+
+        ci.workflow = Some(GitLabWorkflow {
+            name: (!self.name.is_empty()).then(|| self.name.clone()),
+            rules: rules
+                .into_iter()
+                .map(|(condition, changes)| GitLabRule {
+                    if_condition: Some(condition),
+                    changes,
+                    when: None,
+                })
+                .collect(),
+        });
+    }
+
+    /// Add branch/tag filters to every workflow job and a scheduled workflow if requested
+    ///
+    /// CircleCI builds pull requests on their source branch and has no filter for
+    /// the target branch, so branches are only restricted when PR builds are off.
+    /// Manual runs are always allowed.
+    pub fn apply_circleci(&self, config: &mut CircleCIConfig) {
+        let branches = if !self.pr_branches.is_empty() {
+            None
+        } else if !self.push_branches.is_empty() {
+            Some(CircleCIFilter {
+                only: Some(
+                    self.push_branches
+                        .iter()
+                        .map(|p| pattern_to_regex(p))
+                        .collect(),
+                ),
+                ignore: None,
+            })
+        } else if !self.tags.is_empty() {
+            // Tags-only pipelines must explicitly ignore branches
+            Some(CircleCIFilter {
+                only: None,
+                ignore: Some(vec!["/.*/".to_string()]),
+            })
+        } else {
+            None
+        };
+        let filters = CircleCIFilters {
+            branches,
+            tags: non_empty(&self.tags).map(|patterns| CircleCIFilter {
+                only: Some(patterns.iter().map(|p| pattern_to_regex(p)).collect()),
+                ignore: None,
+            }),
+        };
+
+        let mut scheduled = None;
+        for workflow in config.workflows.values_mut() {
+            if let Some(cron) = &self.schedule_cron {
+                scheduled.get_or_insert_with(|| CircleCIWorkflow {
+                    triggers: Some(vec![CircleCITrigger {
+                        schedule: CircleCISchedule {
+                            cron: cron.clone(),
+                            filters: CircleCIFilters {
+                                branches: Some(CircleCIFilter {
+                                    only: Some(vec![self
+                                        .push_branches
+                                        .first()
+                                        .cloned()
+                                        .unwrap_or_else(|| "main".to_string())]),
+                                    ignore: None,
+                                }),
+                                tags: None,
+                            },
+                        },
+                    }]),
+                    jobs: workflow.jobs.clone(),
+                });
+            }
+
+            if filters != CircleCIFilters::default() {
+                for job in &mut workflow.jobs {
+                    with_filters(job, &filters);
+                }
+            }
+        }
+
+        if let Some(workflow) = scheduled {
+            config.workflows.insert("scheduled".to_string(), workflow);
+        }
+    }
+
+    /// Fill in pipeline triggers and per-stage `when` conditions
+    pub fn apply_jenkins(&self, config: &mut JenkinsConfig) {
+        if let Some(cron) = &self.schedule_cron {
+            config.triggers.push(format!("cron('{}')", cron));
+        }
+
+        config.when.extend(
+            self.push_branches
+                .iter()
+                .map(|branch| format!("branch '{}'", branch)),
+        );
+        config.when.extend(
+            self.pr_branches
+                .iter()
+                .map(|branch| format!("changeRequest target: '{}'", branch)),
+        );
+        config
+            .when
+            .extend(self.tags.iter().map(|tag| format!("tag '{}'", tag)));
+        if self.schedule_cron.is_some() {
+            config.when.push("triggeredBy 'TimerTrigger'".to_string());
+        }
+        if self.manual_dispatch {
+            config
+                .when
+                .push("triggeredBy cause: 'UserIdCause'".to_string());
+        }
+    }
+}
+
+/// Attach filters to a workflow job, expanding the short `- job` form if needed
+fn with_filters(job: &mut CircleCIWorkflowJob, filters: &CircleCIFilters) {
+    if let CircleCIWorkflowJob::Simple(name) = job {
+        *job = CircleCIWorkflowJob::WithRequires {
+            job: BTreeMap::from([(
+                name.clone(),
+                CircleCIJobRequires {
+                    requires: vec![],
+                    filters: None,
+                    matrix: None,
+                    job_type: None,
+                },
+            )]),
+        };
+    }
+    if let CircleCIWorkflowJob::WithRequires { job } = job {
+        for requires in job.values_mut() {
+            requires.filters = Some(filters.clone());
+        }
+    }
+}
+
+/// Name of the job a workflow entry runs
+fn workflow_job_name(job: &CircleCIWorkflowJob) -> &str {
+    match job {
+        CircleCIWorkflowJob::Simple(name) => name,
+        CircleCIWorkflowJob::WithRequires { job } => {
+            job.keys().next().map(String::as_str).unwrap_or_default()
+        }
+    }
+}
+
+/// Drop `requires` entries of a workflow job that fail `keep`
+fn retain_requires(job: &mut CircleCIWorkflowJob, keep: impl Fn(&String) -> bool) {
+    if let CircleCIWorkflowJob::WithRequires { job } = job {
+        for requires in job.values_mut() {
+            requires.requires.retain(&keep);
+        }
+    }
+}
+
+/// Split a comma-separated option into trimmed, non-empty entries
+pub(crate) fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn non_empty(values: &[String]) -> Option<Vec<String>> {
+    (!values.is_empty()).then(|| values.to_vec())
+}
+
+/// Values a rule clause such as `$CI_COMMIT_BRANCH == "main"` or
+/// `$CI_COMMIT_TAG =~ /^v.*$/` matches `variable` against, as glob patterns
+///
+/// A bare `$VARIABLE` matches any value. `None` when the clause is about
+/// another variable or the regex isn't one [`patterns_to_regex`] writes.
+fn clause_patterns(clause: &str, variable: &str) -> Option<Vec<String>> {
+    let rest = clause.strip_prefix(variable)?.trim();
+    if rest.is_empty() {
+        return Some(vec!["*".to_string()]);
+    }
+    if let Some(value) = rest.strip_prefix("==") {
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .or_else(|| {
+                value
+                    .strip_prefix('\'')
+                    .and_then(|value| value.strip_suffix('\''))
+            })?;
+        return Some(vec![value.to_string()]);
+    }
+    let regex = rest.strip_prefix("=~")?.trim();
+    let body = regex.strip_prefix("/^")?.strip_suffix("$/")?;
+    let body = body
+        .strip_prefix('(')
+        .and_then(|body| body.strip_suffix(')'))
+        .unwrap_or(body);
+    body.split('|').map(glob_from_body).collect()
+}
+
+/// The glob [`glob_body`] turned into `body`
+fn glob_from_body(body: &str) -> Option<String> {
+    let mut glob = String::new();
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => glob.push(chars.next()?),
+            '.' if chars.clone().next() == Some('*') => {
+                chars.next();
+                glob.push('*');
+            }
+            '.' => glob.push('?'),
+            '*' | '+' | '(' | ')' | '[' | ']' | '{' | '}' | '^' | '$' => return None,
+            _ => glob.push(c),
+        }
+    }
+    Some(glob)
+}
+
+/// Convert a glob-style pattern (`v*`, `release/*`) into an anchored regex
+fn pattern_to_regex(pattern: &str) -> String {
+    format!("/^{}$/", glob_body(pattern))
+}
+
+/// Convert several glob patterns into one anchored alternation regex
+fn patterns_to_regex(patterns: &[String]) -> String {
+    let bodies: Vec<String> = patterns.iter().map(|p| glob_body(p)).collect();
+    if bodies.len() == 1 {
+        format!("/^{}$/", bodies[0])
+    } else {
+        format!("/^({})$/", bodies.join("|"))
+    }
+}
+
+fn glob_body(pattern: &str) -> String {
+    let mut body = String::new();
+    for c in pattern.chars() {
+        match c {
+            '*' => body.push_str(".*"),
+            '?' => body.push('.'),
+            '.' | '+' | '(' | ')' | '|' | '[' | ']' | '{' | '}' | '^' | '$' | '\\' => {
+                body.push('\\');
+                body.push(c);
+            }
+            '/' => body.push_str("\\/"),
+            _ => body.push(c),
+        }
+    }
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platforms::github::models::{GitHubJob, GitHubStep};
+    use crate::platforms::jenkins::models::JenkinsAgent;
+    use crate::platforms::jobs::Jobs;
+
+    fn triggers() -> TriggerConfig {
+        TriggerConfig::from_options("CI", "main, master", "main", "v*", "0 3 * * 1", true)
+    }
+
+    #[test]
+    fn test_from_options_parses_lists() {
+        let config = triggers();
+        assert_eq!(config.push_branches, vec!["main", "master"]);
+        assert_eq!(config.pr_branches, vec!["main"]);
+        assert_eq!(config.tags, vec!["v*"]);
+        assert_eq!(config.schedule_cron.as_deref(), Some("0 3 * * 1"));
+
+        let empty = TriggerConfig::from_options("CI", " , ", "", "", "  ", false);
+        assert!(empty.push_branches.is_empty());
+        assert!(empty.schedule_cron.is_none());
+    }
+
+    #[test]
+    fn test_to_github_events() {
+        let GitHubTriggers::Detailed(events) = triggers().to_github() else {
+            panic!("expected detailed triggers");
+        };
+
+        assert!(events.contains_key("push"));
+        assert!(events.contains_key("pull_request"));
+        assert!(events.contains_key("workflow_dispatch"));
+        assert_eq!(
+            events.get("schedule"),
+            Some(&GitHubTrigger::Schedule(vec![GitHubSchedule {
+                cron: "0 3 * * 1".to_string()
+            }]))
+        );
+
+        let yaml = serde_yaml::to_string(&triggers().to_github()).unwrap();
+        assert!(yaml.contains("workflow_dispatch: {}"));
+        assert!(yaml.contains("- cron: 0 3 * * 1"));
+    }
+
+    #[test]
+    fn test_apply_gitlab_rules() {
+        let mut ci = GitLabCI {
+            include: None,
+            workflow: None,
+            default: None,
+            stages: None,
+            variables: None,
+            cache: None,
+            jobs: Jobs::new(),
+        };
+        triggers().apply_gitlab(&mut ci);
+
+        let workflow = ci.workflow.unwrap();
+        assert_eq!(workflow.name.as_deref(), Some("CI"));
+        let conditions: Vec<_> = workflow
+            .rules
+            .iter()
+            .filter_map(|r| r.if_condition.as_deref())
+            .collect();
+        assert!(conditions.contains(&"$CI_COMMIT_BRANCH =~ /^(main|master)$/"));
+        assert!(conditions.contains(&"$CI_COMMIT_TAG =~ /^v.*$/"));
+        assert!(conditions.contains(&"$CI_PIPELINE_SOURCE == \"web\""));
+    }
+
+    #[test]
+    fn test_from_gitlab_reads_rules_back() {
+        let mut ci = GitLabCI {
+            include: None,
+            workflow: None,
+            default: None,
+            stages: None,
+            variables: None,
+            cache: None,
+            jobs: Jobs::new(),
+        };
+        let written = TriggerConfig {
+            pr_branches: vec!["main".to_string(), "release/*".to_string()],
+            schedule_cron: None,
+            ..triggers()
+        };
+        written.apply_gitlab(&mut ci);
+        let mut notes = Vec::new();
+        assert_eq!(
+            TriggerConfig::from_gitlab(ci.workflow.as_ref().unwrap(), &mut notes),
+            written
+        );
+        assert!(notes.is_empty(), "{:?}", notes);
+
+        let workflow: GitLabWorkflow = serde_yaml::from_str(
+            r#"
+rules:
+  - if: $CI_COMMIT_BRANCH == "main"
+  - if: $CI_PIPELINE_SOURCE == "merge_request_event"
+  - if: $CI_COMMIT_TAG
+  - if: $CI_PIPELINE_SOURCE == "schedule"
+  - if: $CI_COMMIT_BRANCH =~ /^feature-[0-9]+$/
+"#,
+        )
+        .unwrap();
+        let triggers = TriggerConfig::from_gitlab(&workflow, &mut notes);
+        assert_eq!(triggers.push_branches, ["main"]);
+        assert_eq!(triggers.pr_branches, ["*"]);
+        assert_eq!(triggers.tags, ["*"]);
+        assert_eq!(triggers.schedule_cron, None);
+        assert_eq!(notes.len(), 2, "{:?}", notes);
+        assert!(notes[0].contains("cron"));
+        assert!(notes[1].contains("feature-[0-9]+"));
+    }
+
+    #[test]
+    fn test_paths_limit_push_and_merge_request_runs() {
+        let triggers = TriggerConfig {
+            paths: vec!["src/**".to_string(), "Cargo.*".to_string()],
+            ..triggers()
+        };
+        let workflow = GitHubWorkflow {
+            name: "CI".to_string(),
+            on: triggers.to_github(),
+            env: None,
+            defaults: None,
+            jobs: Jobs::new(),
+            permissions: None,
+            concurrency: None,
+        };
+        let GitHubTriggers::Detailed(events) = &workflow.on else {
+            panic!("expected detailed triggers");
+        };
+        for event in ["push", "pull_request"] {
+            let GitHubTrigger::Filter(filter) = &events[event] else {
+                panic!("expected a filter");
+            };
+            assert_eq!(filter.paths.as_ref(), Some(&triggers.paths));
+        }
+        assert_eq!(TriggerConfig::from_github(&workflow).paths, triggers.paths);
+
+        let mut ci = GitLabCI {
+            include: None,
+            workflow: None,
+            default: None,
+            stages: None,
+            variables: None,
+            cache: None,
+            jobs: Jobs::new(),
+        };
+        triggers.apply_gitlab(&mut ci);
+        let rules = ci.workflow.unwrap().rules;
+        let filtered: Vec<bool> = rules.iter().map(|rule| rule.changes.is_some()).collect();
+        // Push, merge request, tag, schedule, web
+        assert_eq!(filtered, [true, true, false, false, false]);
+        assert_eq!(rules[0].changes.as_ref(), Some(&triggers.paths));
+    }
+
+    #[test]
+    fn test_apply_circleci_filters_and_schedule() {
+        let mut config = CircleCIConfig {
+            version: "2.1".to_string(),
+            orbs: None,
+            parameters: None,
+            jobs: Jobs::new(),
+            workflows: BTreeMap::from([(
+                "main".to_string(),
+                CircleCIWorkflow {
+                    triggers: None,
+                    jobs: vec![CircleCIWorkflowJob::Simple("test".to_string())],
+                },
+            )]),
+        };
+        let triggers = TriggerConfig {
+            pr_branches: vec![],
+            ..triggers()
+        };
+        triggers.apply_circleci(&mut config);
+
+        let CircleCIWorkflowJob::WithRequires { job } = &config.workflows["main"].jobs[0] else {
+            panic!("expected job with filters");
+        };
+        let filters = job["test"].filters.as_ref().unwrap();
+        assert_eq!(
+            filters.branches.as_ref().unwrap().only,
+            Some(vec!["/^main$/".to_string(), "/^master$/".to_string()])
+        );
+        assert_eq!(
+            filters.tags.as_ref().unwrap().only,
+            Some(vec!["/^v.*$/".to_string()])
+        );
+
+        let scheduled = &config.workflows["scheduled"];
+        assert_eq!(
+            scheduled.triggers.as_ref().unwrap()[0].schedule.cron,
+            "0 3 * * 1"
+        );
+    }
+
+    #[test]
+    fn test_apply_jenkins_conditions() {
+        let mut config = JenkinsConfig {
+            agent: JenkinsAgent::Any,
+            environment: vec![],
+            triggers: vec![],
+            when: vec![],
+            stages: vec![],
+            post: vec![],
+        };
+        triggers().apply_jenkins(&mut config);
+
+        assert_eq!(config.triggers, vec!["cron('0 3 * * 1')"]);
+        assert!(config.when.contains(&"branch 'main'".to_string()));
+        assert!(config
+            .when
+            .contains(&"changeRequest target: 'main'".to_string()));
+        assert!(config.when.contains(&"tag 'v*'".to_string()));
+    }
+
+    #[test]
+    fn test_apply_scheduled_github_drops_needs() {
+        let job = |needs: Option<Vec<String>>| GitHubJob {
+            runs_on: "ubuntu-latest".into(),
+            container: None,
+            permissions: None,
+            strategy: None,
+            services: None,
+            env: None,
+            steps: vec![GitHubStep::checkout()],
+            needs,
+            timeout_minutes: None,
+            continue_on_error: None,
+            if_condition: None,
+            environment: None,
+        };
+        let mut workflow = GitHubWorkflow {
+            name: "CI".to_string(),
+            on: GitHubTriggers::Simple(vec!["push".to_string()]),
+            env: None,
+            defaults: None,
+            jobs: Jobs::from([
+                ("go/test".to_string(), job(None)),
+                (
+                    "go/build".to_string(),
+                    job(Some(vec!["go/test".to_string()])),
+                ),
+            ]),
+            permissions: None,
+            concurrency: None,
+        };
+        let triggers = TriggerConfig {
+            schedule_cron: Some("0 2 * * *".to_string()),
+            scheduled_jobs: vec!["Test".to_string()],
+            ..TriggerConfig::default()
+        };
+        assert!(triggers.is_scheduled_job("go/test"));
+        assert!(triggers.is_scheduled_job("Test Suite"));
+        assert!(!triggers.is_scheduled_job("go/build"));
+
+        triggers.apply_scheduled_github(&mut workflow);
+        assert!(workflow.jobs["go/build"].needs.is_none());
+        assert!(workflow.jobs["go/test"].if_condition.is_some());
+        let yaml = serde_yaml::to_string(&workflow.on).unwrap();
+        assert_eq!(yaml, "push: null\nschedule:\n- cron: 0 2 * * *\n");
+    }
+}
@@ -0,0 +1,55 @@
+use crate::error::Result;
+use crate::platforms::circleci::models::CircleCIConfig;
+use crate::platforms::jobs::Jobs;
+use crate::traits::ToCircleCI;
+
+use super::{CodeScanningPreset, SEMGREP_IMAGE};
+
+impl ToCircleCI for CodeScanningPreset {
+    fn to_circleci(&self) -> Result<CircleCIConfig> {
+        use crate::platforms::circleci::models::*;
+        use std::collections::BTreeMap;
+
+        self.ensure_enabled()?;
+
+        let mut config = CircleCIConfig {
+            version: "2.1".to_string(),
+            orbs: None,
+            parameters: None,
+            jobs: Jobs::from([(
+                "code-scanning/semgrep".to_string(),
+                CircleCIJob {
+                    docker: vec![CircleCIDocker {
+                        image: SEMGREP_IMAGE.to_string(),
+                        environment: None,
+                    }],
+                    machine: None,
+                    macos: None,
+                    resource_class: None,
+                    parameters: None,
+                    steps: vec![
+                        CircleCIStep::Simple("checkout".to_string()),
+                        CircleCIStep::Command {
+                            run: CircleCIRun::Detailed {
+                                name: "Run Semgrep".to_string(),
+                                command: self.semgrep_command(),
+                            },
+                        },
+                    ],
+                    environment: None,
+                },
+            )]),
+            workflows: BTreeMap::from([(
+                "code-scanning".to_string(),
+                CircleCIWorkflow {
+                    triggers: None,
+                    jobs: vec![CircleCIWorkflowJob::Simple(
+                        "code-scanning/semgrep".to_string(),
+                    )],
+                },
+            )]),
+        };
+        self.triggers().apply_circleci(&mut config);
+        Ok(config)
+    }
+}
@@ -0,0 +1,56 @@
+use crate::platforms::circleci::models::{CircleCIConfig, CircleCIRun, CircleCIStep};
+use crate::platforms::github::models::GitHubWorkflow;
+use crate::platforms::gitlab::models::GitLabCI;
+use crate::platforms::jenkins::models::JenkinsConfig;
+use crate::traits::Detectable;
+
+use super::CodeScanningPreset;
+
+impl Detectable for CodeScanningPreset {
+    fn matches_github(&self, workflow: &GitHubWorkflow) -> bool {
+        workflow.jobs.values().any(|job| {
+            job.steps.iter().any(|step| {
+                step.uses
+                    .as_ref()
+                    .map(|u| u.starts_with("github/codeql-action/"))
+                    .unwrap_or(false)
+                    || step
+                        .run
+                        .as_deref()
+                        .map(|run| run.contains("semgrep"))
+                        .unwrap_or(false)
+            })
+        })
+    }
+
+    fn matches_gitea(&self, workflow: &crate::platforms::gitea::models::GiteaWorkflow) -> bool {
+        // Gitea Actions uses the same workflow format as GitHub Actions
+        self.matches_github(workflow)
+    }
+
+    fn matches_gitlab(&self, config: &GitLabCI) -> bool {
+        config
+            .include
+            .iter()
+            .flatten()
+            .any(|include| include.template.ends_with("SAST.gitlab-ci.yml"))
+    }
+
+    fn matches_circleci(&self, config: &CircleCIConfig) -> bool {
+        config.jobs.values().any(|job| {
+            job.steps.iter().any(|step| match step {
+                CircleCIStep::Command {
+                    run: CircleCIRun::Detailed { command, .. },
+                } => command.contains("semgrep"),
+                _ => false,
+            })
+        })
+    }
+
+    fn matches_jenkins(&self, config: &JenkinsConfig) -> bool {
+        config
+            .stages
+            .iter()
+            .any(|stage| stage.steps.iter().any(|step| step.contains("semgrep")))
+    }
+}
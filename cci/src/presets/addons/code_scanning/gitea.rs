@@ -0,0 +1,33 @@
+use crate::error::Result;
+use crate::platforms::github::models::{GitHubJob, GitHubStep};
+use crate::traits::ToGitea;
+
+use super::{CodeScanningPreset, SEMGREP_IMAGE};
+
+impl ToGitea for CodeScanningPreset {
+    fn to_gitea(&self) -> Result<crate::platforms::gitea::models::GiteaWorkflow> {
+        self.ensure_enabled()?;
+
+        // Gitea has no code scanning alerts, so CodeQL results would go nowhere
+        Ok(self.scan_workflow(
+            "code-scanning/semgrep",
+            GitHubJob {
+                runs_on: "ubuntu-latest".into(),
+                container: Some(SEMGREP_IMAGE.to_string()),
+                permissions: None,
+                strategy: None,
+                services: None,
+                env: None,
+                steps: vec![
+                    GitHubStep::checkout(),
+                    GitHubStep::run("Run Semgrep", self.semgrep_command()),
+                ],
+                needs: None,
+                timeout_minutes: Some(30),
+                continue_on_error: None,
+                if_condition: None,
+                environment: None,
+            },
+        ))
+    }
+}
@@ -0,0 +1,69 @@
+use crate::error::Result;
+use crate::platforms::github::models::{GitHubJob, GitHubStep, GitHubWorkflow};
+use crate::platforms::jobs::Jobs;
+use crate::traits::ToGitHub;
+use std::collections::BTreeMap;
+
+use super::CodeScanningPreset;
+
+impl CodeScanningPreset {
+    /// Single-job workflow shared by the GitHub and Gitea backends
+    pub(super) fn scan_workflow(&self, job_id: &str, job: GitHubJob) -> GitHubWorkflow {
+        let triggers = self.triggers();
+        GitHubWorkflow {
+            name: triggers.name.clone(),
+            on: triggers.to_github(),
+            env: None,
+            defaults: None,
+            jobs: Jobs::from([(job_id.to_string(), job)]),
+            permissions: None,
+            concurrency: None,
+        }
+    }
+}
+
+impl ToGitHub for CodeScanningPreset {
+    fn to_github(&self) -> Result<GitHubWorkflow> {
+        self.ensure_enabled()?;
+
+        let init = match self.codeql_languages() {
+            Some(languages) => GitHubStep::action_with_config(
+                "Initialize CodeQL",
+                "github/codeql-action/init@v3",
+                BTreeMap::from([("languages".to_string(), languages.into())]),
+            ),
+            None => GitHubStep::action("Initialize CodeQL", "github/codeql-action/init@v3"),
+        };
+
+        let steps = vec![
+            GitHubStep::checkout(),
+            init,
+            // Compiled languages need a build to trace; a no-op for the rest
+            GitHubStep::action("Build", "github/codeql-action/autobuild@v3"),
+            GitHubStep::action("Analyze", "github/codeql-action/analyze@v3"),
+        ];
+
+        Ok(self.scan_workflow(
+            "code-scanning/codeql",
+            GitHubJob {
+                runs_on: "ubuntu-latest".into(),
+                container: None,
+                // Results are uploaded to the repository's code scanning alerts
+                permissions: Some(BTreeMap::from([
+                    ("actions".to_string(), "read".to_string()),
+                    ("contents".to_string(), "read".to_string()),
+                    ("security-events".to_string(), "write".to_string()),
+                ])),
+                strategy: None,
+                services: None,
+                env: None,
+                steps,
+                needs: None,
+                timeout_minutes: Some(60),
+                continue_on_error: None,
+                if_condition: None,
+                environment: None,
+            },
+        ))
+    }
+}
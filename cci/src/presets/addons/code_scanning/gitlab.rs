@@ -0,0 +1,30 @@
+use crate::error::Result;
+use crate::platforms::gitlab::models::GitLabCI;
+use crate::platforms::jobs::Jobs;
+use crate::traits::ToGitLab;
+
+use super::CodeScanningPreset;
+
+impl ToGitLab for CodeScanningPreset {
+    fn to_gitlab(&self) -> Result<GitLabCI> {
+        use crate::platforms::gitlab::models::*;
+
+        self.ensure_enabled()?;
+
+        // The template picks analyzers for the languages it finds and reports
+        // into the merge request security widget; its jobs run in `test`
+        let mut ci = GitLabCI {
+            include: Some(vec![GitLabInclude {
+                template: "Jobs/SAST.gitlab-ci.yml".to_string(),
+            }]),
+            workflow: None,
+            default: None,
+            stages: Some(vec!["test".to_string()]),
+            variables: None,
+            cache: None,
+            jobs: Jobs::new(),
+        };
+        self.triggers().apply_gitlab(&mut ci);
+        Ok(ci)
+    }
+}
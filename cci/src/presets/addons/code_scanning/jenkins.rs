@@ -0,0 +1,28 @@
+use crate::error::Result;
+use crate::platforms::jenkins::models::JenkinsConfig;
+use crate::traits::ToJenkins;
+
+use super::{CodeScanningPreset, SEMGREP_IMAGE};
+
+impl ToJenkins for CodeScanningPreset {
+    fn to_jenkins(&self) -> Result<JenkinsConfig> {
+        use crate::platforms::jenkins::models::*;
+
+        self.ensure_enabled()?;
+
+        let mut config = JenkinsConfig {
+            agent: JenkinsAgent::Docker(SEMGREP_IMAGE.to_string()),
+            environment: vec![],
+            triggers: vec![],
+            when: vec![],
+            stages: vec![JenkinsStage {
+                name: "Code Scanning".to_string(),
+                when: vec![],
+                steps: vec![format!("sh '{}'", self.semgrep_command())],
+            }],
+            post: vec![],
+        };
+        self.triggers().apply_jenkins(&mut config);
+        Ok(config)
+    }
+}
@@ -0,0 +1,290 @@
+use crate::error::{config_error, Result};
+use crate::platforms::triggers::TriggerConfig;
+use crate::traits::{
+    DecoratesPipelines, DetectedDefaults, LocalChecks, PresetInfo, RequiresSecrets, SupportFiles,
+};
+use cci_macros::Preset;
+
+mod circleci;
+mod detectable;
+mod gitea;
+mod github;
+mod gitlab;
+mod jenkins;
+
+/// Published Semgrep image, used where no native scanner exists
+pub(super) const SEMGREP_IMAGE: &str = "semgrep/semgrep";
+
+/// Addon that runs static application security testing on every change
+///
+/// Each platform gets its native scanner: CodeQL on GitHub and the bundled
+/// SAST template on GitLab. Gitea, CircleCI and Jenkins fall back to Semgrep.
+#[derive(Debug, Clone, Preset)]
+#[preset(
+    id = "code-scanning",
+    name = "Code Scanning",
+    description = "Static security analysis with CodeQL, GitLab SAST, or Semgrep",
+    addon
+)]
+pub struct CodeScanningPreset {
+    #[preset_field(
+        feature = "analysis",
+        feature_display = "Static Analysis",
+        display = "Enable Code Scanning",
+        description = "CodeQL on GitHub, the SAST template on GitLab, Semgrep elsewhere",
+        default = "true"
+    )]
+    pub(super) enable_scanning: bool,
+
+    #[preset_field(
+        feature = "analysis",
+        feature_display = "Static Analysis",
+        display = "CodeQL Languages",
+        description = "Comma-separated CodeQL languages (empty to detect from the repository)",
+        default = "String::new()"
+    )]
+    pub(super) languages: String,
+
+    #[preset_field(
+        feature = "analysis",
+        feature_display = "Static Analysis",
+        display = "Semgrep Rules",
+        description = "Semgrep ruleset for platforms without a native scanner",
+        default = "\"p/default\".to_string()"
+    )]
+    pub(super) semgrep_config: String,
+
+    #[preset_field(
+        feature = "analysis",
+        feature_display = "Static Analysis",
+        display = "Fail on Findings",
+        description = "Fail the Semgrep job when it reports any findings",
+        default = "false",
+        requires = "enable_scanning"
+    )]
+    pub(super) fail_on_findings: bool,
+
+    #[preset_field(
+        feature = "triggers",
+        feature_display = "Triggers",
+        display = "Workflow Name",
+        description = "Name shown for the generated workflow/pipeline",
+        default = "\"Code Scanning\".to_string()"
+    )]
+    pub(super) workflow_name: String,
+
+    #[preset_field(
+        feature = "triggers",
+        feature_display = "Triggers",
+        display = "Push Branches",
+        description = "Comma-separated branches that trigger a scan on push",
+        default = "\"main\".to_string()"
+    )]
+    pub(super) push_branches: String,
+
+    #[preset_field(
+        feature = "triggers",
+        feature_display = "Triggers",
+        display = "PR Branches",
+        description = "Comma-separated target branches that trigger a scan on pull requests",
+        default = "\"main\".to_string()"
+    )]
+    pub(super) pr_branches: String,
+
+    #[preset_field(
+        feature = "triggers",
+        feature_display = "Triggers",
+        display = "Schedule (cron)",
+        description = "Cron expression for periodic rescans with updated rules (empty to disable)",
+        default = "\"0 6 * * 1\".to_string()",
+        validation = "Validation::cron()"
+    )]
+    pub(super) schedule_cron: String,
+}
+
+impl CodeScanningPreset {
+    /// Constant default instance for registry initialization
+    pub const DEFAULT: Self = Self {
+        enable_scanning: false,
+        languages: String::new(),
+        semgrep_config: String::new(),
+        fail_on_findings: false,
+        workflow_name: String::new(),
+        push_branches: String::new(),
+        pr_branches: String::new(),
+        schedule_cron: String::new(),
+    };
+
+    /// Trigger configuration shared by all platform backends
+    pub(super) fn triggers(&self) -> TriggerConfig {
+        TriggerConfig::from_options(
+            &self.workflow_name,
+            &self.push_branches,
+            &self.pr_branches,
+            "",
+            &self.schedule_cron,
+            false,
+        )
+    }
+
+    /// CodeQL languages, comma-separated as the init action expects them
+    pub(super) fn codeql_languages(&self) -> Option<String> {
+        let languages: Vec<&str> = self
+            .languages
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect();
+        (!languages.is_empty()).then(|| languages.join(","))
+    }
+
+    /// Scanning is the addon's only job, so there is nothing to generate without it
+    pub(super) fn ensure_enabled(&self) -> Result<()> {
+        if self.enable_scanning {
+            Ok(())
+        } else {
+            Err(config_error(
+                "code scanning is disabled; enable it or remove the addon",
+            ))
+        }
+    }
+
+    /// Semgrep invocation for the fallback platforms
+    ///
+    /// Avoids single quotes so it can be embedded in a Jenkins `sh '...'` step.
+    pub(super) fn semgrep_command(&self) -> String {
+        let rules = match self.semgrep_config.trim() {
+            "" => "p/default",
+            rules => rules,
+        };
+        let mut command = format!("semgrep scan --config {} --metrics off", rules);
+        if self.fail_on_findings {
+            command.push_str(" --error");
+        }
+        command
+    }
+}
+
+impl RequiresSecrets for CodeScanningPreset {}
+
+impl SupportFiles for CodeScanningPreset {}
+
+impl DecoratesPipelines for CodeScanningPreset {}
+
+impl DetectedDefaults for CodeScanningPreset {}
+
+impl LocalChecks for CodeScanningPreset {}
+
+impl PresetInfo for CodeScanningPreset {
+    fn name(&self) -> &str {
+        "code-scanning"
+    }
+
+    fn description(&self) -> &str {
+        "Static security analysis with CodeQL, GitLab SAST, or Semgrep"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::state::Platform;
+    use crate::traits::{Detectable, ToCircleCI, ToGitHub, ToGitLab, ToGitea, ToJenkins};
+
+    #[test]
+    fn test_to_github_codeql() {
+        let preset = CodeScanningPreset {
+            languages: "python, go".to_string(),
+            ..CodeScanningPreset::default()
+        };
+        let workflow = preset.to_github().unwrap();
+
+        let job = &workflow.jobs["code-scanning/codeql"];
+        assert_eq!(
+            job.permissions.as_ref().unwrap()["security-events"],
+            "write".to_string()
+        );
+        let uses: Vec<&str> = job.steps.iter().filter_map(|s| s.uses.as_deref()).collect();
+        assert_eq!(
+            uses,
+            vec![
+                "actions/checkout@v4",
+                "github/codeql-action/init@v3",
+                "github/codeql-action/autobuild@v3",
+                "github/codeql-action/analyze@v3",
+            ]
+        );
+        let init = job.steps[1].with.as_ref().unwrap();
+        assert_eq!(init["languages"], serde_yaml::Value::from("python,go"));
+        assert!(preset.matches_github(&workflow));
+
+        // Without languages CodeQL detects them itself
+        let workflow = CodeScanningPreset::default().to_github().unwrap();
+        assert!(workflow.jobs["code-scanning/codeql"].steps[1]
+            .with
+            .is_none());
+    }
+
+    #[test]
+    fn test_to_gitlab_includes_sast_template() {
+        let preset = CodeScanningPreset::default();
+        let ci = preset.to_gitlab().unwrap();
+
+        assert_eq!(
+            ci.include.as_ref().unwrap()[0].template,
+            "Jobs/SAST.gitlab-ci.yml"
+        );
+        assert_eq!(ci.stages, Some(vec!["test".to_string()]));
+        assert!(ci.jobs.is_empty());
+        assert!(preset.matches_gitlab(&ci));
+
+        let yaml = serde_yaml::to_string(&ci).unwrap();
+        assert!(yaml.starts_with("include:\n- template: Jobs/SAST.gitlab-ci.yml\n"));
+    }
+
+    #[test]
+    fn test_semgrep_fallback() {
+        let preset = CodeScanningPreset {
+            fail_on_findings: true,
+            ..CodeScanningPreset::default()
+        };
+        let command = "semgrep scan --config p/default --metrics off --error";
+
+        let gitea = preset.to_gitea().unwrap();
+        let job = &gitea.jobs["code-scanning/semgrep"];
+        assert_eq!(job.container.as_deref(), Some(SEMGREP_IMAGE));
+        assert_eq!(job.steps[1].run.as_deref(), Some(command));
+        assert!(preset.matches_gitea(&gitea));
+
+        let circleci = preset.to_circleci().unwrap();
+        assert!(serde_yaml::to_string(&circleci).unwrap().contains(command));
+        assert!(preset.matches_circleci(&circleci));
+
+        let jenkins = preset.to_jenkins().unwrap();
+        assert_eq!(jenkins.stages[0].steps, vec![format!("sh '{}'", command)]);
+        assert!(preset.matches_jenkins(&jenkins));
+    }
+
+    #[test]
+    fn test_disabled_scanning_errors() {
+        let preset = CodeScanningPreset {
+            enable_scanning: false,
+            ..CodeScanningPreset::default()
+        };
+        assert!(preset.to_github().is_err());
+        assert!(preset.to_gitlab().is_err());
+        assert!(preset.required_secrets(Platform::GitHub).is_empty());
+    }
+
+    #[test]
+    fn test_fail_on_findings_needs_scanning() {
+        use crate::editor::config::{OptionValue, PresetConfig};
+        let mut config = PresetConfig::new("code-scanning".to_string());
+        config.set("fail_on_findings".to_string(), OptionValue::Bool(true));
+        config.set("enable_scanning".to_string(), OptionValue::Bool(false));
+        assert!(!CodeScanningPreset::from_config(&config, "").fail_on_findings);
+
+        config.set("enable_scanning".to_string(), OptionValue::Bool(true));
+        assert!(CodeScanningPreset::from_config(&config, "").fail_on_findings);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::error::Result;
+use crate::platforms::circleci::models::CircleCIConfig;
+use crate::traits::ToCircleCI;
+use std::collections::BTreeMap;
+
+use super::{decorator_only_error, DeployPreset};
+
+impl DeployPreset {
+    /// Add a deploy job to every unscheduled workflow, after an approval job if required
+    pub(super) fn decorate_circleci(&self, config: &mut CircleCIConfig) {
+        use crate::platforms::circleci::models::*;
+
+        let job_id = self.job_id();
+        let approve_id = format!("{}/approve", job_id);
+        let filters = CircleCIFilters {
+            branches: Some(CircleCIFilter {
+                only: Some(vec![self.branch().to_string()]),
+                ignore: None,
+            }),
+            tags: None,
+        };
+        let entry = |name: &str, job_type: Option<&str>, requires: Vec<String>| {
+            CircleCIWorkflowJob::WithRequires {
+                job: BTreeMap::from([(
+                    name.to_string(),
+                    CircleCIJobRequires {
+                        job_type: job_type.map(str::to_string),
+                        requires,
+                        filters: Some(filters.clone()),
+                        matrix: None,
+                    },
+                )]),
+            }
+        };
+
+        for workflow in config.workflows.values_mut() {
+            if workflow.triggers.is_some() {
+                continue;
+            }
+            let mut requires: Vec<String> = workflow
+                .jobs
+                .iter()
+                .filter_map(|job| match job {
+                    CircleCIWorkflowJob::Simple(name) => Some(name.clone()),
+                    CircleCIWorkflowJob::WithRequires { job } => job.keys().next().cloned(),
+                })
+                .collect();
+            if self.require_approval {
+                workflow
+                    .jobs
+                    .push(entry(&approve_id, Some("approval"), requires));
+                requires = vec![approve_id.clone()];
+            }
+            workflow.jobs.push(entry(&job_id, None, requires));
+        }
+
+        config.jobs.insert(
+            job_id,
+            CircleCIJob {
+                docker: vec![CircleCIDocker {
+                    image: "cimg/base:stable".to_string(),
+                    environment: None,
+                }],
+                steps: vec![
+                    CircleCIStep::Simple("checkout".to_string()),
+                    CircleCIStep::Command {
+                        run: CircleCIRun::Detailed {
+                            name: "Deploy".to_string(),
+                            command: self.deploy_command.trim().to_string(),
+                        },
+                    },
+                ],
+                environment: None,
+                machine: None,
+                macos: None,
+                resource_class: None,
+                parameters: None,
+            },
+        );
+    }
+}
+
+impl ToCircleCI for DeployPreset {
+    fn to_circleci(&self) -> Result<CircleCIConfig> {
+        Err(decorator_only_error())
+    }
+}
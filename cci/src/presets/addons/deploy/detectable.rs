@@ -0,0 +1,33 @@
+use crate::platforms::circleci::models::CircleCIConfig;
+use crate::platforms::github::models::GitHubWorkflow;
+use crate::platforms::gitlab::models::GitLabCI;
+use crate::platforms::jenkins::models::JenkinsConfig;
+use crate::traits::Detectable;
+
+use super::DeployPreset;
+
+impl Detectable for DeployPreset {
+    fn matches_github(&self, workflow: &GitHubWorkflow) -> bool {
+        workflow.jobs.values().any(|job| job.environment.is_some())
+    }
+
+    fn matches_gitea(&self, workflow: &crate::platforms::gitea::models::GiteaWorkflow) -> bool {
+        // Gitea Actions uses the same workflow format as GitHub Actions
+        self.matches_github(workflow)
+    }
+
+    fn matches_gitlab(&self, config: &GitLabCI) -> bool {
+        config.jobs.values().any(|job| job.environment.is_some())
+    }
+
+    fn matches_circleci(&self, config: &CircleCIConfig) -> bool {
+        config.jobs.keys().any(|name| name.starts_with("deploy/"))
+    }
+
+    fn matches_jenkins(&self, config: &JenkinsConfig) -> bool {
+        config
+            .stages
+            .iter()
+            .any(|stage| stage.name.starts_with("Deploy to "))
+    }
+}
@@ -0,0 +1,54 @@
+use crate::error::Result;
+use crate::platforms::github::models::{GitHubEnvironment, GitHubJob, GitHubStep, GitHubWorkflow};
+use crate::traits::ToGitHub;
+
+use super::{decorator_only_error, DeployPreset};
+
+impl DeployPreset {
+    /// Append a deploy job that waits for every unconditional job
+    ///
+    /// Conditional jobs, like nightly-only ones, are skipped on many runs and
+    /// would skip the deployment with them. Gitea shares this, though it has
+    /// no environment protection rules to gate the job.
+    pub(super) fn decorate_github(&self, workflow: &mut GitHubWorkflow) {
+        let needs: Vec<String> = workflow
+            .jobs
+            .iter()
+            .filter(|(_, job)| job.if_condition.is_none())
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        workflow.jobs.insert(
+            self.job_id(),
+            GitHubJob {
+                runs_on: "ubuntu-latest".into(),
+                container: None,
+                permissions: None,
+                strategy: None,
+                services: None,
+                env: None,
+                steps: vec![
+                    GitHubStep::checkout(),
+                    GitHubStep::run("Deploy", self.deploy_command.trim()),
+                ],
+                needs: (!needs.is_empty()).then_some(needs),
+                timeout_minutes: None,
+                continue_on_error: None,
+                if_condition: Some(format!(
+                    "github.event_name == 'push' && github.ref == 'refs/heads/{}'",
+                    self.branch()
+                )),
+                environment: Some(GitHubEnvironment {
+                    name: self.environment.trim().to_string(),
+                    url: self.url(),
+                }),
+            },
+        );
+    }
+}
+
+impl ToGitHub for DeployPreset {
+    fn to_github(&self) -> Result<GitHubWorkflow> {
+        Err(decorator_only_error())
+    }
+}
@@ -0,0 +1,56 @@
+use crate::error::Result;
+use crate::platforms::gitlab::models::{GitLabCI, GitLabEnvironment, GitLabJob, GitLabRule};
+use crate::traits::ToGitLab;
+
+use super::{decorator_only_error, DeployPreset};
+
+impl DeployPreset {
+    /// Add a job in a final `deploy` stage, manual when approval is required
+    pub(super) fn decorate_gitlab(&self, ci: &mut GitLabCI) {
+        let stages = ci.stages.get_or_insert_with(Vec::new);
+        if !stages.iter().any(|stage| stage == "deploy") {
+            stages.push("deploy".to_string());
+        }
+
+        ci.jobs.insert(
+            self.job_id(),
+            GitLabJob {
+                stage: "deploy".to_string(),
+                image: None,
+                services: None,
+                variables: None,
+                script: vec![self.deploy_command.trim().to_string()],
+                before_script: None,
+                after_script: None,
+                needs: None,
+                cache: None,
+                artifacts: None,
+                only: None,
+                tags: None,
+                parallel: None,
+                timeout: None,
+                retry: None,
+                allow_failure: None,
+                rules: Some(vec![GitLabRule {
+                    if_condition: Some(format!("$CI_COMMIT_BRANCH == \"{}\"", self.branch())),
+                    changes: None,
+                    when: self.require_approval.then(|| "manual".to_string()),
+                }]),
+                when: None,
+                // A newer pipeline mustn't cancel a deploy halfway through
+                interruptible: Some(false),
+                coverage: None,
+                environment: Some(GitLabEnvironment {
+                    name: self.environment.trim().to_string(),
+                    url: self.url(),
+                }),
+            },
+        );
+    }
+}
+
+impl ToGitLab for DeployPreset {
+    fn to_gitlab(&self) -> Result<GitLabCI> {
+        Err(decorator_only_error())
+    }
+}
@@ -0,0 +1,33 @@
+use crate::error::Result;
+use crate::platforms::jenkins::helpers::groovy_quote;
+use crate::platforms::jenkins::models::{JenkinsConfig, JenkinsStage};
+use crate::traits::ToJenkins;
+
+use super::{decorator_only_error, DeployPreset};
+
+impl DeployPreset {
+    /// Append a final stage, paused on an `input` step when approval is required
+    pub(super) fn decorate_jenkins(&self, config: &mut JenkinsConfig) {
+        let environment = self.environment.trim();
+        let mut steps = Vec::new();
+        if self.require_approval {
+            steps.push(format!(
+                "input message: {}",
+                groovy_quote(&format!("Deploy to {}?", environment))
+            ));
+        }
+        steps.push(format!("sh {}", groovy_quote(self.deploy_command.trim())));
+
+        config.stages.push(JenkinsStage {
+            name: format!("Deploy to {}", environment),
+            when: vec![format!("branch {}", groovy_quote(self.branch()))],
+            steps,
+        });
+    }
+}
+
+impl ToJenkins for DeployPreset {
+    fn to_jenkins(&self) -> Result<JenkinsConfig> {
+        Err(decorator_only_error())
+    }
+}
@@ -0,0 +1,286 @@
+use crate::error::{config_error, Result};
+use crate::platforms::helpers::PlatformConfig;
+use crate::traits::{
+    DecoratesPipelines, DetectedDefaults, LocalChecks, PresetInfo, RequiresSecrets, SupportFiles,
+};
+use cci_macros::Preset;
+
+mod circleci;
+mod detectable;
+mod gitea;
+mod github;
+mod gitlab;
+mod jenkins;
+
+/// Addon that deploys to an environment once the other presets' jobs pass
+///
+/// The deployment is chained after every job of the pipelines generated by the
+/// other configured presets, so it has no workflow of its own.
+#[derive(Debug, Clone, Preset)]
+#[preset(
+    id = "deploy",
+    name = "Deploy",
+    description = "Deploy to an environment after the pipeline passes, optionally behind an approval",
+    decorator
+)]
+pub struct DeployPreset {
+    #[preset_field(
+        feature = "deployment",
+        feature_display = "Deployment",
+        display = "Environment",
+        description = "Name of the environment deployed to",
+        default = "\"production\".to_string()"
+    )]
+    pub(super) environment: String,
+
+    #[preset_field(
+        feature = "deployment",
+        feature_display = "Deployment",
+        display = "Environment URL",
+        description = "URL shown for the deployment on GitHub and GitLab (empty for none)",
+        default = "String::new()"
+    )]
+    pub(super) environment_url: String,
+
+    #[preset_field(
+        feature = "deployment",
+        feature_display = "Deployment",
+        display = "Deploy Command",
+        description = "Shell command that performs the deployment",
+        default = "\"./deploy.sh\".to_string()"
+    )]
+    pub(super) deploy_command: String,
+
+    #[preset_field(
+        feature = "deployment",
+        feature_display = "Deployment",
+        display = "Branch",
+        description = "Only pushes to this branch are deployed",
+        default = "\"main\".to_string()"
+    )]
+    pub(super) branch: String,
+
+    #[preset_field(
+        feature = "approval",
+        feature_display = "Approval",
+        display = "Require Approval",
+        description = "Wait for a manual approval before deploying (on GitHub, set required reviewers on the environment)",
+        default = "true"
+    )]
+    pub(super) require_approval: bool,
+}
+
+impl DeployPreset {
+    /// Constant default instance for registry initialization
+    pub const DEFAULT: Self = Self {
+        environment: String::new(),
+        environment_url: String::new(),
+        deploy_command: String::new(),
+        branch: String::new(),
+        require_approval: false,
+    };
+
+    /// ID of the deploy job, e.g. `deploy/production`
+    pub(super) fn job_id(&self) -> String {
+        let slug: String = self
+            .environment
+            .trim()
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        format!("deploy/{}", slug)
+    }
+
+    pub(super) fn url(&self) -> Option<String> {
+        let url = self.environment_url.trim();
+        (!url.is_empty()).then(|| url.to_string())
+    }
+
+    pub(super) fn branch(&self) -> &str {
+        match self.branch.trim() {
+            "" => "main",
+            branch => branch,
+        }
+    }
+
+    fn ensure_configured(&self) -> Result<()> {
+        if self.environment.trim().is_empty() {
+            return Err(config_error("the deploy addon needs an environment name"));
+        }
+        if self.deploy_command.trim().is_empty() {
+            return Err(config_error("the deploy addon needs a deploy command"));
+        }
+        Ok(())
+    }
+}
+
+impl DecoratesPipelines for DeployPreset {
+    fn decorate(&self, pipeline: &mut PlatformConfig) -> Result<()> {
+        self.ensure_configured()?;
+
+        match pipeline {
+            PlatformConfig::GitHub(workflow) | PlatformConfig::Gitea(workflow) => {
+                self.decorate_github(workflow)
+            }
+            PlatformConfig::GitLab(ci) => self.decorate_gitlab(ci),
+            PlatformConfig::CircleCI(config) => self.decorate_circleci(config),
+            PlatformConfig::Jenkins(config) => self.decorate_jenkins(config),
+        }
+        Ok(())
+    }
+}
+
+impl RequiresSecrets for DeployPreset {}
+
+impl SupportFiles for DeployPreset {}
+
+impl DetectedDefaults for DeployPreset {}
+
+impl LocalChecks for DeployPreset {}
+
+impl PresetInfo for DeployPreset {
+    fn name(&self) -> &str {
+        "deploy"
+    }
+
+    fn description(&self) -> &str {
+        "Deploy to an environment after the pipeline passes, optionally behind an approval"
+    }
+}
+
+/// Reported when a decorator is generated on its own
+pub(super) fn decorator_only_error() -> anyhow::Error {
+    config_error("deployments are chained after other presets' jobs; enable a preset to deploy")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::registry::build_registry;
+    use crate::editor::state::Platform;
+    use crate::generator::MultiPresetGenerator;
+    use crate::platforms::circleci::models::CircleCIWorkflowJob;
+    use crate::platforms::helpers::build_for_platform;
+    use crate::presets::RustPreset;
+    use crate::traits::{Detectable, ToGitHub};
+    use std::sync::Arc;
+
+    fn decorated(platform: Platform, preset: &DeployPreset) -> PlatformConfig {
+        let mut pipeline = build_for_platform(&RustPreset::default(), platform).unwrap();
+        preset.decorate(&mut pipeline).unwrap();
+        pipeline
+    }
+
+    #[test]
+    fn test_github_environment_job() {
+        let preset = DeployPreset {
+            environment: "Staging EU".to_string(),
+            environment_url: "https://staging.example.com".to_string(),
+            ..DeployPreset::default()
+        };
+        let PlatformConfig::GitHub(workflow) = decorated(Platform::GitHub, &preset) else {
+            panic!("expected a GitHub workflow");
+        };
+
+        let deploy = &workflow.jobs["deploy/staging-eu"];
+        let environment = deploy.environment.as_ref().unwrap();
+        assert_eq!(environment.name, "Staging EU");
+        assert_eq!(
+            environment.url.as_deref(),
+            Some("https://staging.example.com")
+        );
+        assert_eq!(
+            deploy.needs.as_ref().unwrap().len(),
+            workflow.jobs.len() - 1
+        );
+        assert_eq!(
+            deploy.if_condition.as_deref(),
+            Some("github.event_name == 'push' && github.ref == 'refs/heads/main'")
+        );
+        assert_eq!(deploy.steps[1].run.as_deref(), Some("./deploy.sh"));
+        assert!(preset.matches_github(&workflow));
+        assert!(preset.to_github().is_err());
+    }
+
+    #[test]
+    fn test_gitlab_manual_deploy_stage() {
+        let preset = DeployPreset::default();
+        let PlatformConfig::GitLab(ci) = decorated(Platform::GitLab, &preset) else {
+            panic!("expected a GitLab config");
+        };
+
+        assert_eq!(ci.stages.as_ref().unwrap().last().unwrap(), "deploy");
+        let deploy = &ci.jobs["deploy/production"];
+        let rule = &deploy.rules.as_ref().unwrap()[0];
+        assert_eq!(
+            rule.if_condition.as_deref(),
+            Some("$CI_COMMIT_BRANCH == \"main\"")
+        );
+        assert_eq!(rule.when.as_deref(), Some("manual"));
+        assert_eq!(deploy.environment.as_ref().unwrap().name, "production");
+        assert!(preset.matches_gitlab(&ci));
+    }
+
+    #[test]
+    fn test_circleci_approval_and_jenkins_input() {
+        let preset = DeployPreset {
+            deploy_command: "kubectl apply -f 'k8s/'".to_string(),
+            ..DeployPreset::default()
+        };
+
+        let PlatformConfig::CircleCI(config) = decorated(Platform::CircleCI, &preset) else {
+            panic!("expected a CircleCI config");
+        };
+        let jobs = &config.workflows["main"].jobs;
+        let CircleCIWorkflowJob::WithRequires { job: approve } = &jobs[jobs.len() - 2] else {
+            panic!("expected an approval job");
+        };
+        let approve = &approve["deploy/production/approve"];
+        assert_eq!(approve.job_type.as_deref(), Some("approval"));
+        assert!(approve.requires.contains(&"rust/test".to_string()));
+        let CircleCIWorkflowJob::WithRequires { job: deploy } = &jobs[jobs.len() - 1] else {
+            panic!("expected the deploy job");
+        };
+        assert_eq!(
+            deploy["deploy/production"].requires,
+            vec!["deploy/production/approve"]
+        );
+        assert!(preset.matches_circleci(&config));
+
+        let pipeline = decorated(Platform::Jenkins, &preset);
+        let groovy = pipeline.render().unwrap();
+        assert!(groovy.contains("stage('Deploy to production')"));
+        assert!(groovy.contains("                branch 'main'\n"));
+        assert!(groovy.contains("input message: 'Deploy to production?'"));
+        assert!(groovy.contains("sh 'kubectl apply -f \\'k8s/\\''"));
+    }
+
+    #[test]
+    fn test_notifications_wait_for_deploy() {
+        let registry = Arc::new(build_registry());
+        let config = |id: &str| {
+            (
+                id.to_string(),
+                registry.get(id).unwrap().default_config(true),
+            )
+        };
+        // Listed before deploy, but still applied after it
+        let generator = MultiPresetGenerator::new(
+            vec![config("rust"), config("notifications"), config("deploy")],
+            registry.clone(),
+            Platform::GitHub,
+            "stable".to_string(),
+        );
+
+        let outputs = generator.generate_all().unwrap();
+        assert_eq!(outputs.len(), 1);
+        let workflow: crate::platforms::github::models::GitHubWorkflow =
+            serde_yaml::from_str(&outputs[0].1).unwrap();
+        assert!(workflow.jobs["notify"]
+            .needs
+            .as_ref()
+            .unwrap()
+            .contains(&"deploy-production".to_string()));
+    }
+}
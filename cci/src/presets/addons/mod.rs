@@ -0,0 +1,21 @@
+//! Addons layer extra jobs on top of whichever base preset a project uses
+//!
+//! Most are regular presets that generate their own workflow file, so they
+//! compose with any base preset without knowing its job layout. Decorators
+//! like notifications, nightly builds, and deployments instead extend the other presets' pipelines.
+
+pub mod code_scanning;
+pub mod deploy;
+pub mod nightly;
+pub mod notifications;
+pub mod release;
+pub mod release_automation;
+
+pub use code_scanning::{CodeScanningConfig, CodeScanningPreset};
+pub use deploy::{DeployConfig, DeployPreset};
+pub use nightly::{NightlyConfig, NightlyPreset};
+pub use notifications::{NotificationsConfig, NotificationsPreset, NotifyChannel};
+pub use release::{ReleaseConfig, ReleaseKind, ReleasePreset};
+pub use release_automation::{
+    ReleaseAutomationConfig, ReleaseAutomationPreset, ReleaseTool, ReleaseType,
+};
@@ -0,0 +1,11 @@
+use crate::error::Result;
+use crate::platforms::circleci::models::CircleCIConfig;
+use crate::traits::ToCircleCI;
+
+use super::{decorator_only_error, NightlyPreset};
+
+impl ToCircleCI for NightlyPreset {
+    fn to_circleci(&self) -> Result<CircleCIConfig> {
+        Err(decorator_only_error())
+    }
+}
@@ -0,0 +1,44 @@
+use crate::platforms::circleci::models::CircleCIConfig;
+use crate::platforms::github::models::GitHubWorkflow;
+use crate::platforms::gitlab::models::GitLabCI;
+use crate::platforms::jenkins::models::JenkinsConfig;
+use crate::traits::Detectable;
+
+use super::NightlyPreset;
+
+impl Detectable for NightlyPreset {
+    fn matches_github(&self, workflow: &GitHubWorkflow) -> bool {
+        workflow.jobs.values().any(|job| {
+            job.if_condition
+                .as_deref()
+                .is_some_and(|condition| condition.contains("github.event_name == 'schedule'"))
+        })
+    }
+
+    fn matches_gitea(&self, workflow: &crate::platforms::gitea::models::GiteaWorkflow) -> bool {
+        // Gitea Actions uses the same workflow format as GitHub Actions
+        self.matches_github(workflow)
+    }
+
+    fn matches_gitlab(&self, config: &GitLabCI) -> bool {
+        config.jobs.values().any(|job| {
+            job.rules.iter().flatten().any(|rule| {
+                rule.if_condition.as_deref() == Some("$CI_PIPELINE_SOURCE == \"schedule\"")
+            })
+        })
+    }
+
+    fn matches_circleci(&self, config: &CircleCIConfig) -> bool {
+        config
+            .workflows
+            .get("nightly")
+            .is_some_and(|workflow| workflow.triggers.is_some())
+    }
+
+    fn matches_jenkins(&self, config: &JenkinsConfig) -> bool {
+        config
+            .stages
+            .iter()
+            .any(|stage| stage.when.iter().any(|c| c == "triggeredBy 'TimerTrigger'"))
+    }
+}
@@ -0,0 +1,10 @@
+use crate::error::Result;
+use crate::traits::ToGitea;
+
+use super::{decorator_only_error, NightlyPreset};
+
+impl ToGitea for NightlyPreset {
+    fn to_gitea(&self) -> Result<crate::platforms::gitea::models::GiteaWorkflow> {
+        Err(decorator_only_error())
+    }
+}
@@ -0,0 +1,11 @@
+use crate::error::Result;
+use crate::platforms::github::models::GitHubWorkflow;
+use crate::traits::ToGitHub;
+
+use super::{decorator_only_error, NightlyPreset};
+
+impl ToGitHub for NightlyPreset {
+    fn to_github(&self) -> Result<GitHubWorkflow> {
+        Err(decorator_only_error())
+    }
+}
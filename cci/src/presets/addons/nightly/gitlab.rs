@@ -0,0 +1,11 @@
+use crate::error::Result;
+use crate::platforms::gitlab::models::GitLabCI;
+use crate::traits::ToGitLab;
+
+use super::{decorator_only_error, NightlyPreset};
+
+impl ToGitLab for NightlyPreset {
+    fn to_gitlab(&self) -> Result<GitLabCI> {
+        Err(decorator_only_error())
+    }
+}
@@ -0,0 +1,11 @@
+use crate::error::Result;
+use crate::platforms::jenkins::models::JenkinsConfig;
+use crate::traits::ToJenkins;
+
+use super::{decorator_only_error, NightlyPreset};
+
+impl ToJenkins for NightlyPreset {
+    fn to_jenkins(&self) -> Result<JenkinsConfig> {
+        Err(decorator_only_error())
+    }
+}
@@ -0,0 +1,226 @@
+use crate::error::{config_error, Result};
+use crate::platforms::helpers::PlatformConfig;
+use crate::platforms::triggers::{split_list, TriggerConfig};
+use crate::traits::{
+    DecoratesPipelines, DetectedDefaults, LocalChecks, PresetInfo, RequiresSecrets, SupportFiles,
+};
+use cci_macros::Preset;
+
+mod circleci;
+mod detectable;
+mod gitea;
+mod github;
+mod gitlab;
+mod jenkins;
+
+/// Addon that moves slow or noisy jobs from every push to a nightly schedule
+///
+/// Like notifications it has no workflow of its own: it rewrites the jobs of
+/// the pipelines generated by every other configured preset. GitLab schedules
+/// live in the project settings, so only the job rules are generated there.
+#[derive(Debug, Clone, Preset)]
+#[preset(
+    id = "nightly",
+    name = "Nightly Builds",
+    description = "Run selected jobs on a cron schedule instead of on every push",
+    decorator
+)]
+pub struct NightlyPreset {
+    #[preset_field(
+        feature = "schedule",
+        feature_display = "Schedule",
+        display = "Nightly Jobs",
+        description = "Comma-separated jobs that only run on the schedule (e.g., security, rust/build)",
+        default = "\"security\".to_string()"
+    )]
+    pub(super) nightly_jobs: String,
+
+    #[preset_field(
+        feature = "schedule",
+        feature_display = "Schedule",
+        display = "Schedule (cron)",
+        description = "Cron expression for the nightly run, in UTC",
+        default = "\"0 2 * * *\".to_string()",
+        validation = "Validation::cron()"
+    )]
+    pub(super) schedule_cron: String,
+
+    #[preset_field(
+        feature = "schedule",
+        feature_display = "Schedule",
+        display = "Branch",
+        description = "Branch built by the nightly run where the platform needs one (CircleCI)",
+        default = "\"main\".to_string()"
+    )]
+    pub(super) branch: String,
+}
+
+impl NightlyPreset {
+    /// Constant default instance for registry initialization
+    pub const DEFAULT: Self = Self {
+        nightly_jobs: String::new(),
+        schedule_cron: String::new(),
+        branch: String::new(),
+    };
+
+    /// Trigger configuration with the schedule and the jobs limited to it
+    pub(super) fn triggers(&self) -> TriggerConfig {
+        let mut triggers =
+            TriggerConfig::from_options("", &self.branch, "", "", &self.schedule_cron, false);
+        triggers.scheduled_jobs = split_list(&self.nightly_jobs);
+        triggers
+    }
+}
+
+impl DecoratesPipelines for NightlyPreset {
+    fn decorate(&self, pipeline: &mut PlatformConfig) -> Result<()> {
+        let triggers = self.triggers();
+        if triggers.scheduled_jobs.is_empty() {
+            return Ok(());
+        }
+        if triggers.schedule_cron.is_none() {
+            return Err(config_error("nightly jobs need a cron schedule"));
+        }
+
+        match pipeline {
+            PlatformConfig::GitHub(workflow) | PlatformConfig::Gitea(workflow) => {
+                triggers.apply_scheduled_github(workflow)
+            }
+            PlatformConfig::GitLab(ci) => triggers.apply_scheduled_gitlab(ci),
+            PlatformConfig::CircleCI(config) => triggers.apply_scheduled_circleci(config),
+            PlatformConfig::Jenkins(config) => triggers.apply_scheduled_jenkins(config),
+        }
+        Ok(())
+    }
+}
+
+impl RequiresSecrets for NightlyPreset {}
+
+impl SupportFiles for NightlyPreset {}
+
+impl DetectedDefaults for NightlyPreset {}
+
+impl LocalChecks for NightlyPreset {}
+
+impl PresetInfo for NightlyPreset {
+    fn name(&self) -> &str {
+        "nightly"
+    }
+
+    fn description(&self) -> &str {
+        "Run selected jobs on a cron schedule instead of on every push"
+    }
+}
+
+/// Reported when a decorator is generated on its own
+pub(super) fn decorator_only_error() -> anyhow::Error {
+    config_error("nightly builds reschedule other presets' jobs; enable a preset to schedule")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::state::Platform;
+    use crate::platforms::github::models::{GitHubTrigger, GitHubTriggers};
+    use crate::platforms::helpers::build_for_platform;
+    use crate::presets::RustPreset;
+    use crate::traits::{Detectable, ToGitHub};
+
+    fn decorated(platform: Platform, preset: &NightlyPreset) -> PlatformConfig {
+        let mut pipeline = build_for_platform(&RustPreset::default(), platform).unwrap();
+        preset.decorate(&mut pipeline).unwrap();
+        pipeline
+    }
+
+    #[test]
+    fn test_github_security_job_runs_on_schedule() {
+        let preset = NightlyPreset::default();
+        let PlatformConfig::GitHub(workflow) = decorated(Platform::GitHub, &preset) else {
+            panic!("expected a GitHub workflow");
+        };
+
+        let GitHubTriggers::Detailed(events) = &workflow.on else {
+            panic!("expected detailed triggers");
+        };
+        assert!(events.contains_key("push"));
+        let Some(GitHubTrigger::Schedule(schedules)) = events.get("schedule") else {
+            panic!("expected a schedule");
+        };
+        assert_eq!(schedules[0].cron, "0 2 * * *");
+
+        assert_eq!(
+            workflow.jobs["rust/security"].if_condition.as_deref(),
+            Some("github.event_name == 'schedule'")
+        );
+        assert!(workflow.jobs["rust/test"].if_condition.is_none());
+        assert!(preset.matches_github(&workflow));
+    }
+
+    #[test]
+    fn test_gitlab_rules_and_jenkins_stage_conditions() {
+        let preset = NightlyPreset::default();
+        let schedule = "$CI_PIPELINE_SOURCE == \"schedule\"";
+
+        let PlatformConfig::GitLab(ci) = decorated(Platform::GitLab, &preset) else {
+            panic!("expected a GitLab config");
+        };
+        let rules = ci.jobs["rust/security"].rules.as_ref().unwrap();
+        assert_eq!(rules[0].if_condition.as_deref(), Some(schedule));
+        assert!(ci.jobs["rust/test"].rules.is_none());
+        assert!(ci
+            .workflow
+            .as_ref()
+            .unwrap()
+            .rules
+            .iter()
+            .any(|rule| rule.if_condition.as_deref() == Some(schedule)));
+        assert!(preset.matches_gitlab(&ci));
+
+        let pipeline = decorated(Platform::Jenkins, &preset);
+        let PlatformConfig::Jenkins(config) = &pipeline else {
+            panic!("expected a Jenkins pipeline");
+        };
+        assert!(config.triggers.contains(&"cron('0 2 * * *')".to_string()));
+        let stage = config
+            .stages
+            .iter()
+            .find(|stage| stage.name == "Security Scan")
+            .unwrap();
+        assert_eq!(stage.when, vec!["triggeredBy 'TimerTrigger'"]);
+        assert!(preset.matches_jenkins(config));
+    }
+
+    #[test]
+    fn test_circleci_moves_jobs_to_nightly_workflow() {
+        let preset = NightlyPreset {
+            nightly_jobs: "rust/lint".to_string(),
+            ..NightlyPreset::default()
+        };
+        let PlatformConfig::CircleCI(config) = decorated(Platform::CircleCI, &preset) else {
+            panic!("expected a CircleCI config");
+        };
+
+        let nightly = &config.workflows["nightly"];
+        let trigger = &nightly.triggers.as_ref().unwrap()[0];
+        assert_eq!(trigger.schedule.cron, "0 2 * * *");
+        assert_eq!(
+            trigger.schedule.filters.branches.as_ref().unwrap().only,
+            Some(vec!["main".to_string()])
+        );
+        assert_eq!(nightly.jobs.len(), 1);
+        let yaml = serde_yaml::to_string(&config.workflows["main"]).unwrap();
+        assert!(!yaml.contains("rust/lint"));
+        assert!(preset.matches_circleci(&config));
+    }
+
+    #[test]
+    fn test_jobs_without_schedule_error() {
+        let preset = NightlyPreset {
+            schedule_cron: " ".to_string(),
+            ..NightlyPreset::default()
+        };
+        let mut pipeline = build_for_platform(&RustPreset::default(), Platform::GitHub).unwrap();
+        assert!(preset.decorate(&mut pipeline).is_err());
+        assert!(NightlyPreset::default().to_github().is_err());
+    }
+}
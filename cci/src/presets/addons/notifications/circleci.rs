@@ -0,0 +1,40 @@
+use crate::error::Result;
+use crate::platforms::circleci::models::{CircleCIConfig, CircleCIRun, CircleCIStep};
+use crate::traits::ToCircleCI;
+
+use super::{decorator_only_error, NotificationsPreset};
+
+impl NotificationsPreset {
+    /// Append conditional steps to every job; the image needs curl
+    pub(super) fn decorate_circleci(&self, config: &mut CircleCIConfig) {
+        let outcomes = [
+            (self.notify_on_failure, "on_fail", "failed"),
+            (self.notify_on_success, "on_success", "passed"),
+        ];
+
+        for job in config.jobs.values_mut() {
+            for (enabled, when, status) in outcomes {
+                if !enabled {
+                    continue;
+                }
+                let message = format!(
+                    "$CIRCLE_PROJECT_REPONAME $CIRCLE_JOB {} on $CIRCLE_BRANCH: $CIRCLE_BUILD_URL",
+                    status
+                );
+                job.steps.push(CircleCIStep::Command {
+                    run: CircleCIRun::Conditional {
+                        name: "Send notification".to_string(),
+                        command: self.notify_command(&message),
+                        when: when.to_string(),
+                    },
+                });
+            }
+        }
+    }
+}
+
+impl ToCircleCI for NotificationsPreset {
+    fn to_circleci(&self) -> Result<CircleCIConfig> {
+        Err(decorator_only_error())
+    }
+}
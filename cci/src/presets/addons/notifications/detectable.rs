@@ -0,0 +1,42 @@
+use crate::platforms::circleci::models::{CircleCIConfig, CircleCIRun, CircleCIStep};
+use crate::platforms::github::models::GitHubWorkflow;
+use crate::platforms::gitlab::models::GitLabCI;
+use crate::platforms::jenkins::models::JenkinsConfig;
+use crate::traits::Detectable;
+
+use super::NotificationsPreset;
+
+impl Detectable for NotificationsPreset {
+    fn matches_github(&self, workflow: &GitHubWorkflow) -> bool {
+        workflow.jobs.contains_key("notify")
+    }
+
+    fn matches_gitea(&self, workflow: &crate::platforms::gitea::models::GiteaWorkflow) -> bool {
+        // Gitea Actions uses the same workflow format as GitHub Actions
+        self.matches_github(workflow)
+    }
+
+    fn matches_gitlab(&self, config: &GitLabCI) -> bool {
+        config.jobs.keys().any(|name| name.starts_with("notify/"))
+    }
+
+    fn matches_circleci(&self, config: &CircleCIConfig) -> bool {
+        config.jobs.values().any(|job| {
+            job.steps.iter().any(|step| {
+                matches!(
+                    step,
+                    CircleCIStep::Command {
+                        run: CircleCIRun::Conditional { name, .. },
+                    } if name == "Send notification"
+                )
+            })
+        })
+    }
+
+    fn matches_jenkins(&self, config: &JenkinsConfig) -> bool {
+        config
+            .post
+            .iter()
+            .any(|(_, steps)| steps.iter().any(|step| step.contains("curl")))
+    }
+}
@@ -0,0 +1,10 @@
+use crate::error::Result;
+use crate::traits::ToGitea;
+
+use super::{decorator_only_error, NotificationsPreset};
+
+impl ToGitea for NotificationsPreset {
+    fn to_gitea(&self) -> Result<crate::platforms::gitea::models::GiteaWorkflow> {
+        Err(decorator_only_error())
+    }
+}
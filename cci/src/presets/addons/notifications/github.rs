@@ -0,0 +1,57 @@
+use crate::error::Result;
+use crate::platforms::github::models::{GitHubJob, GitHubStep, GitHubWorkflow};
+use crate::traits::ToGitHub;
+use std::collections::BTreeMap;
+
+use super::{decorator_only_error, NotificationsPreset};
+
+impl NotificationsPreset {
+    /// Append a `notify` job that waits for every other job
+    ///
+    /// Gitea shares this, since it understands the same job conditions.
+    pub(super) fn decorate_github(&self, workflow: &mut GitHubWorkflow) {
+        let condition = match (self.notify_on_failure, self.notify_on_success) {
+            (true, false) => "failure()",
+            (false, true) => "success()",
+            _ => "!cancelled()",
+        };
+        let secret = self.secret().to_string();
+
+        // Untrusted values like branch names only reach the script through the environment
+        let env = BTreeMap::from([
+            (
+                "NOTIFY_MESSAGE".to_string(),
+                "${{ github.workflow }} ${{ contains(needs.*.result, 'failure') && 'failed' || 'passed' }} on ${{ github.ref_name }}: ${{ github.server_url }}/${{ github.repository }}/actions/runs/${{ github.run_id }}".to_string(),
+            ),
+            (secret.clone(), format!("${{{{ secrets.{} }}}}", secret)),
+        ]);
+
+        let needs: Vec<String> = workflow.jobs.keys().cloned().collect();
+        workflow.jobs.insert(
+            "notify".to_string(),
+            GitHubJob {
+                runs_on: "ubuntu-latest".into(),
+                container: None,
+                permissions: None,
+                strategy: None,
+                services: None,
+                env: Some(env),
+                steps: vec![GitHubStep::run(
+                    "Send notification",
+                    self.notify_command("$NOTIFY_MESSAGE"),
+                )],
+                needs: Some(needs),
+                timeout_minutes: Some(5),
+                continue_on_error: None,
+                if_condition: Some(condition.to_string()),
+                environment: None,
+            },
+        );
+    }
+}
+
+impl ToGitHub for NotificationsPreset {
+    fn to_github(&self) -> Result<GitHubWorkflow> {
+        Err(decorator_only_error())
+    }
+}
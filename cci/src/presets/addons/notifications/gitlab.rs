@@ -0,0 +1,57 @@
+use crate::error::Result;
+use crate::platforms::gitlab::models::{GitLabCI, GitLabJob};
+use crate::traits::ToGitLab;
+
+use super::{decorator_only_error, NotificationsPreset};
+
+impl NotificationsPreset {
+    /// Add jobs to the implicit `.post` stage, which runs after every other stage
+    pub(super) fn decorate_gitlab(&self, ci: &mut GitLabCI) {
+        let outcomes = [
+            (self.notify_on_failure, "failure", "on_failure", "failed"),
+            (self.notify_on_success, "success", "on_success", "passed"),
+        ];
+
+        for (enabled, suffix, when, status) in outcomes {
+            if !enabled {
+                continue;
+            }
+            let message = format!(
+                "$CI_PROJECT_PATH pipeline {} on $CI_COMMIT_REF_NAME: $CI_PIPELINE_URL",
+                status
+            );
+            ci.jobs.insert(
+                format!("notify/{}", suffix),
+                GitLabJob {
+                    stage: ".post".to_string(),
+                    image: Some("alpine:latest".to_string()),
+                    services: None,
+                    variables: None,
+                    script: vec![self.notify_command(&message)],
+                    before_script: Some(vec!["apk add --no-cache curl".to_string()]),
+                    after_script: None,
+                    needs: None,
+                    cache: None,
+                    artifacts: None,
+                    only: None,
+                    tags: None,
+                    parallel: None,
+                    timeout: None,
+                    retry: None,
+                    allow_failure: None,
+                    rules: None,
+                    when: Some(when.to_string()),
+                    interruptible: None,
+                    coverage: None,
+                    environment: None,
+                },
+            );
+        }
+    }
+}
+
+impl ToGitLab for NotificationsPreset {
+    fn to_gitlab(&self) -> Result<GitLabCI> {
+        Err(decorator_only_error())
+    }
+}
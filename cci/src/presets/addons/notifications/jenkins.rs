@@ -0,0 +1,37 @@
+use crate::error::Result;
+use crate::platforms::jenkins::models::JenkinsConfig;
+use crate::traits::ToJenkins;
+
+use super::{decorator_only_error, NotificationsPreset};
+
+impl NotificationsPreset {
+    /// Add `post` conditions that run once the stages finish
+    pub(super) fn decorate_jenkins(&self, config: &mut JenkinsConfig) {
+        let outcomes = [
+            (self.notify_on_failure, "failure", "failed"),
+            (self.notify_on_success, "success", "passed"),
+        ];
+        let secret = self.secret();
+
+        for (enabled, condition, status) in outcomes {
+            if !enabled {
+                continue;
+            }
+            let message = format!("$JOB_NAME {} on $BRANCH_NAME: $BUILD_URL", status);
+            // Groovy unescapes single-quoted strings, so backslashes must survive it
+            let command = self.notify_command(&message).replace('\\', "\\\\");
+            config.post.push((
+                condition.to_string(),
+                vec![format!(
+                    "withCredentials([string(credentialsId: '{secret}', variable: '{secret}')]) {{ sh '{command}' }}"
+                )],
+            ));
+        }
+    }
+}
+
+impl ToJenkins for NotificationsPreset {
+    fn to_jenkins(&self) -> Result<JenkinsConfig> {
+        Err(decorator_only_error())
+    }
+}
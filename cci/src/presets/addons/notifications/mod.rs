@@ -0,0 +1,362 @@
+use crate::editor::state::Platform;
+use crate::error::{config_error, Result};
+use crate::platforms::helpers::PlatformConfig;
+use crate::platforms::secrets::Secret;
+use crate::traits::{
+    DecoratesPipelines, DetectedDefaults, LocalChecks, PresetInfo, RequiresSecrets, SupportFiles,
+};
+use cci_macros::{Preset, PresetEnum};
+
+mod circleci;
+mod detectable;
+mod gitea;
+mod github;
+mod gitlab;
+mod jenkins;
+
+/// Where notifications are sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, PresetEnum)]
+#[preset_enum(default = "Slack")]
+#[serde(rename_all = "lowercase")]
+pub enum NotifyChannel {
+    /// Incoming webhook
+    #[preset_variant(id = "slack", display = "Slack")]
+    Slack,
+    /// Channel webhook
+    #[preset_variant(id = "discord", display = "Discord")]
+    Discord,
+    /// Room message sent with a bot account's access token
+    #[preset_variant(id = "matrix", display = "Matrix")]
+    Matrix,
+    /// Mail sent through an SMTP server
+    #[preset_variant(id = "email", display = "Email")]
+    Email,
+}
+
+impl NotifyChannel {
+    /// Secret name used when none is configured
+    pub fn default_secret(&self) -> &'static str {
+        match self {
+            NotifyChannel::Slack => "SLACK_WEBHOOK_URL",
+            NotifyChannel::Discord => "DISCORD_WEBHOOK_URL",
+            NotifyChannel::Matrix => "MATRIX_ACCESS_TOKEN",
+            NotifyChannel::Email => "SMTP_CREDENTIALS",
+        }
+    }
+
+    fn secret_description(&self) -> &'static str {
+        match self {
+            NotifyChannel::Slack => "Slack incoming webhook URL",
+            NotifyChannel::Discord => "Discord channel webhook URL",
+            NotifyChannel::Matrix => "Access token of the Matrix account that posts to the room",
+            NotifyChannel::Email => "SMTP login as user:password; the user is the sender address",
+        }
+    }
+}
+
+/// Addon that reports pipeline results to a chat channel or mailbox
+///
+/// Unlike the other addons it has no workflow of its own: the steps are added
+/// to the pipelines generated by every other configured preset.
+#[derive(Debug, Clone, Preset)]
+#[preset(
+    id = "notifications",
+    name = "Notifications",
+    description = "Failure and success messages to Slack, Discord, Matrix, or email",
+    decorator
+)]
+pub struct NotificationsPreset {
+    #[preset_field(
+        feature = "notifications",
+        feature_display = "Notifications",
+        display = "Notify on Failure",
+        description = "Send a message when the pipeline fails",
+        default = "true"
+    )]
+    pub(super) notify_on_failure: bool,
+
+    #[preset_field(
+        feature = "notifications",
+        feature_display = "Notifications",
+        display = "Notify on Success",
+        description = "Send a message when the pipeline passes",
+        default = "false"
+    )]
+    pub(super) notify_on_success: bool,
+
+    #[preset_field(
+        feature = "notifications",
+        feature_display = "Notifications",
+        display = "Channel",
+        description = "Where messages are sent",
+        default = "NotifyChannel::Slack"
+    )]
+    pub(super) channel: NotifyChannel,
+
+    #[preset_field(
+        feature = "notifications",
+        feature_display = "Notifications",
+        display = "Secret Name",
+        description = "Secret with the webhook URL, access token, or SMTP login (empty for the channel's default)",
+        default = "String::new()"
+    )]
+    pub(super) secret_name: String,
+
+    #[preset_field(
+        feature = "notifications",
+        feature_display = "Notifications",
+        display = "Server",
+        description = "Matrix homeserver URL or SMTP server URL (e.g., smtps://smtp.example.com:465)",
+        default = "String::new()"
+    )]
+    pub(super) server: String,
+
+    #[preset_field(
+        feature = "notifications",
+        feature_display = "Notifications",
+        display = "Recipient",
+        description = "Matrix room ID or email address that receives the messages",
+        default = "String::new()"
+    )]
+    pub(super) recipient: String,
+}
+
+impl NotificationsPreset {
+    /// Constant default instance for registry initialization
+    pub const DEFAULT: Self = Self {
+        notify_on_failure: false,
+        notify_on_success: false,
+        channel: NotifyChannel::Slack,
+        secret_name: String::new(),
+        server: String::new(),
+        recipient: String::new(),
+    };
+
+    /// Name of the secret the notification step reads
+    pub(super) fn secret(&self) -> &str {
+        match self.secret_name.trim() {
+            "" => self.channel.default_secret(),
+            name => name,
+        }
+    }
+
+    /// Matrix and email need to know where to deliver the message
+    fn ensure_configured(&self) -> Result<()> {
+        let needs_target = matches!(self.channel, NotifyChannel::Matrix | NotifyChannel::Email);
+        if needs_target && (self.server.trim().is_empty() || self.recipient.trim().is_empty()) {
+            return Err(config_error(format!(
+                "{:?} notifications need a server and a recipient",
+                self.channel
+            )));
+        }
+        Ok(())
+    }
+
+    /// Shell command sending `message`, which is placed inside double quotes
+    ///
+    /// Avoids single quotes so it can be embedded in a Jenkins `sh '...'` step.
+    pub(super) fn notify_command(&self, message: &str) -> String {
+        let secret = self.secret();
+        let server = self.server.trim().trim_end_matches('/');
+        let recipient = self.recipient.trim();
+
+        match self.channel {
+            NotifyChannel::Slack => format!(
+                "curl -sS -X POST -H \"Content-Type: application/json\" -d \"{{\\\"text\\\": \\\"{message}\\\"}}\" \"${secret}\""
+            ),
+            NotifyChannel::Discord => format!(
+                "curl -sS -X POST -H \"Content-Type: application/json\" -d \"{{\\\"content\\\": \\\"{message}\\\"}}\" \"${secret}\""
+            ),
+            // The transaction ID only has to be unique per access token
+            NotifyChannel::Matrix => format!(
+                "curl -sS -X PUT -H \"Authorization: Bearer ${secret}\" -H \"Content-Type: application/json\" -d \"{{\\\"msgtype\\\": \\\"m.text\\\", \\\"body\\\": \\\"{message}\\\"}}\" \"{server}/_matrix/client/v3/rooms/{recipient}/send/m.room.message/$(date +%s%N)\""
+            ),
+            NotifyChannel::Email => format!(
+                "printf \"Subject: %s\\n\\n%s\\n\" \"{message}\" \"{message}\" | curl -sS --ssl-reqd --url \"{server}\" --user \"${secret}\" --mail-from \"${{{secret}%%:*}}\" --mail-rcpt \"{recipient}\" --upload-file -"
+            ),
+        }
+    }
+}
+
+impl DecoratesPipelines for NotificationsPreset {
+    fn decorate(&self, pipeline: &mut PlatformConfig) -> Result<()> {
+        if !self.notify_on_failure && !self.notify_on_success {
+            return Ok(());
+        }
+        self.ensure_configured()?;
+
+        match pipeline {
+            PlatformConfig::GitHub(workflow) | PlatformConfig::Gitea(workflow) => {
+                self.decorate_github(workflow)
+            }
+            PlatformConfig::GitLab(ci) => self.decorate_gitlab(ci),
+            PlatformConfig::CircleCI(config) => self.decorate_circleci(config),
+            PlatformConfig::Jenkins(config) => self.decorate_jenkins(config),
+        }
+        Ok(())
+    }
+}
+
+impl RequiresSecrets for NotificationsPreset {
+    fn required_secrets(&self, _platform: Platform) -> Vec<Secret> {
+        if self.notify_on_failure || self.notify_on_success {
+            vec![Secret::named(
+                self.secret(),
+                self.channel.secret_description(),
+            )]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+impl SupportFiles for NotificationsPreset {}
+
+impl DetectedDefaults for NotificationsPreset {}
+
+impl LocalChecks for NotificationsPreset {}
+
+impl PresetInfo for NotificationsPreset {
+    fn name(&self) -> &str {
+        "notifications"
+    }
+
+    fn description(&self) -> &str {
+        "Failure and success messages to Slack, Discord, Matrix, or email"
+    }
+}
+
+/// Reported when a decorator is generated on its own
+pub(super) fn decorator_only_error() -> anyhow::Error {
+    config_error(
+        "notifications are added to other presets' pipelines; enable a preset to notify about",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::registry::build_registry;
+    use crate::generator::MultiPresetGenerator;
+    use crate::platforms::helpers::build_for_platform;
+    use crate::presets::RustPreset;
+    use crate::traits::{Detectable, ToGitHub};
+    use std::sync::Arc;
+
+    fn decorated(platform: Platform, preset: &NotificationsPreset) -> PlatformConfig {
+        let mut pipeline = build_for_platform(&RustPreset::default(), platform).unwrap();
+        preset.decorate(&mut pipeline).unwrap();
+        pipeline
+    }
+
+    #[test]
+    fn test_github_notify_job_needs_every_job() {
+        let preset = NotificationsPreset::default();
+        let PlatformConfig::GitHub(workflow) = decorated(Platform::GitHub, &preset) else {
+            panic!("expected a GitHub workflow");
+        };
+
+        let notify = &workflow.jobs["notify"];
+        let needs = notify.needs.as_ref().unwrap();
+        assert_eq!(needs.len(), workflow.jobs.len() - 1);
+        assert!(!needs.contains(&"notify".to_string()));
+        assert_eq!(notify.if_condition.as_deref(), Some("failure()"));
+        let env = notify.env.as_ref().unwrap();
+        assert_eq!(env["SLACK_WEBHOOK_URL"], "${{ secrets.SLACK_WEBHOOK_URL }}");
+        assert!(notify.steps[0]
+            .run
+            .as_deref()
+            .unwrap()
+            .ends_with("\"$SLACK_WEBHOOK_URL\""));
+        assert!(preset.matches_github(&workflow));
+    }
+
+    #[test]
+    fn test_gitlab_post_stage_jobs() {
+        let preset = NotificationsPreset {
+            notify_on_success: true,
+            channel: NotifyChannel::Discord,
+            secret_name: "CI_DISCORD_HOOK".to_string(),
+            ..NotificationsPreset::default()
+        };
+        let PlatformConfig::GitLab(ci) = decorated(Platform::GitLab, &preset) else {
+            panic!("expected a GitLab config");
+        };
+
+        let failure = &ci.jobs["notify/failure"];
+        assert_eq!(failure.stage, ".post");
+        assert_eq!(failure.when.as_deref(), Some("on_failure"));
+        assert!(failure.script[0].contains("\\\"content\\\""));
+        assert!(failure.script[0].ends_with("\"$CI_DISCORD_HOOK\""));
+        assert_eq!(
+            ci.jobs["notify/success"].when.as_deref(),
+            Some("on_success")
+        );
+        assert_eq!(
+            preset.required_secrets(Platform::GitLab)[0].name,
+            "CI_DISCORD_HOOK"
+        );
+    }
+
+    #[test]
+    fn test_circleci_and_jenkins() {
+        let preset = NotificationsPreset {
+            channel: NotifyChannel::Matrix,
+            server: "https://matrix.example.com/".to_string(),
+            recipient: "!room:example.com".to_string(),
+            ..NotificationsPreset::default()
+        };
+
+        let PlatformConfig::CircleCI(config) = decorated(Platform::CircleCI, &preset) else {
+            panic!("expected a CircleCI config");
+        };
+        let yaml = serde_yaml::to_string(&config).unwrap();
+        assert!(yaml.contains("when: on_fail"));
+        assert!(yaml
+            .contains("https://matrix.example.com/_matrix/client/v3/rooms/!room:example.com/send"));
+        assert!(preset.matches_circleci(&config));
+
+        let pipeline = decorated(Platform::Jenkins, &preset);
+        let PlatformConfig::Jenkins(config) = &pipeline else {
+            panic!("expected a Jenkins pipeline");
+        };
+        assert!(preset.matches_jenkins(config));
+        let groovy = pipeline.render().unwrap();
+        assert!(groovy.contains("    post {\n        failure {\n"));
+        assert!(groovy.contains("credentialsId: 'MATRIX_ACCESS_TOKEN'"));
+        // Groovy would otherwise unescape the JSON quotes before the shell sees them
+        assert!(groovy.contains("\\\\\"msgtype\\\\\""));
+    }
+
+    #[test]
+    fn test_matrix_and_email_need_a_target() {
+        let preset = NotificationsPreset {
+            channel: NotifyChannel::Email,
+            ..NotificationsPreset::default()
+        };
+        let mut pipeline = build_for_platform(&RustPreset::default(), Platform::GitHub).unwrap();
+        assert!(preset.decorate(&mut pipeline).is_err());
+        assert!(preset.to_github().is_err());
+    }
+
+    #[test]
+    fn test_generator_decorates_other_presets() {
+        let registry = Arc::new(build_registry());
+        let rust = registry.get("rust").unwrap().default_config(true);
+        let notifications = registry.get("notifications").unwrap().default_config(true);
+        let generator = MultiPresetGenerator::new(
+            vec![
+                ("rust".to_string(), rust),
+                ("notifications".to_string(), notifications),
+            ],
+            registry,
+            Platform::GitLab,
+            "stable".to_string(),
+        );
+
+        let outputs = generator.generate_all().unwrap();
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].0, std::path::PathBuf::from(".gitlab-ci.yml"));
+        assert!(outputs[0].1.contains("notify/failure:"));
+    }
+}
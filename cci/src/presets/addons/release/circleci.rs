@@ -0,0 +1,76 @@
+use crate::error::Result;
+use crate::models::steps;
+use crate::platforms::circleci::models::CircleCIConfig;
+use crate::platforms::jobs::Jobs;
+use crate::traits::ToCircleCI;
+
+use super::ReleasePreset;
+
+impl ToCircleCI for ReleasePreset {
+    fn to_circleci(&self) -> Result<CircleCIConfig> {
+        use crate::platforms::circleci::models::*;
+        use std::collections::BTreeMap;
+
+        let run = |name: &str, command: String| CircleCIStep::Command {
+            run: CircleCIRun::Detailed {
+                name: name.to_string(),
+                command,
+            },
+        };
+
+        let mut steps = vec![CircleCIStep::Simple("checkout".to_string())];
+        if !self.build_command.trim().is_empty() {
+            steps.push(run(
+                "Build release assets",
+                self.build_command.trim().to_string(),
+            ));
+        }
+        if self.changelog {
+            steps.push(run(
+                "Generate changelog",
+                steps::changelog_script("$CIRCLE_TAG"),
+            ));
+        }
+        // CircleCI has no release mechanism of its own, so releases go to GitHub
+        if self.create_release {
+            steps.push(run(
+                "Install GitHub CLI",
+                "sudo apt-get update && sudo apt-get install -y gh".to_string(),
+            ));
+            steps.push(run(
+                "Create GitHub release",
+                self.gh_release_command("$CIRCLE_TAG"),
+            ));
+        }
+
+        let mut config = CircleCIConfig {
+            version: "2.1".to_string(),
+            orbs: None,
+            parameters: None,
+            jobs: Jobs::from([(
+                "release/publish".to_string(),
+                CircleCIJob {
+                    docker: vec![CircleCIDocker {
+                        image: "cimg/base:stable".to_string(),
+                        environment: None,
+                    }],
+                    machine: None,
+                    macos: None,
+                    resource_class: None,
+                    parameters: None,
+                    steps,
+                    environment: None,
+                },
+            )]),
+            workflows: BTreeMap::from([(
+                "release".to_string(),
+                CircleCIWorkflow {
+                    triggers: None,
+                    jobs: vec![CircleCIWorkflowJob::Simple("release/publish".to_string())],
+                },
+            )]),
+        };
+        self.triggers().apply_circleci(&mut config);
+        Ok(config)
+    }
+}
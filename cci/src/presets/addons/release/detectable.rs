@@ -0,0 +1,59 @@
+use crate::platforms::circleci::models::{CircleCIConfig, CircleCIRun, CircleCIStep};
+use crate::platforms::github::models::GitHubWorkflow;
+use crate::platforms::gitlab::models::GitLabCI;
+use crate::platforms::jenkins::models::JenkinsConfig;
+use crate::traits::Detectable;
+
+use super::ReleasePreset;
+
+impl Detectable for ReleasePreset {
+    fn matches_github(&self, workflow: &GitHubWorkflow) -> bool {
+        workflow.jobs.values().any(|job| {
+            job.steps.iter().any(|step| {
+                step.run
+                    .as_ref()
+                    .map(|r| r.contains("gh release create"))
+                    .unwrap_or(false)
+            })
+        })
+    }
+
+    fn matches_gitea(&self, workflow: &crate::platforms::gitea::models::GiteaWorkflow) -> bool {
+        workflow.jobs.values().any(|job| {
+            job.steps.iter().any(|step| {
+                step.run
+                    .as_ref()
+                    .map(|r| r.contains("/api/v1/repos/") && r.contains("/releases"))
+                    .unwrap_or(false)
+            })
+        })
+    }
+
+    fn matches_gitlab(&self, config: &GitLabCI) -> bool {
+        config.jobs.values().any(|job| {
+            job.script
+                .iter()
+                .any(|line| line.starts_with("release-cli create"))
+        })
+    }
+
+    fn matches_circleci(&self, config: &CircleCIConfig) -> bool {
+        config.jobs.values().any(|job| {
+            job.steps.iter().any(|step| match step {
+                CircleCIStep::Command {
+                    run: CircleCIRun::Detailed { command, .. },
+                } => command.contains("gh release create"),
+                _ => false,
+            })
+        })
+    }
+
+    fn matches_jenkins(&self, config: &JenkinsConfig) -> bool {
+        config.stages.iter().any(|stage| {
+            stage
+                .steps
+                .iter()
+                .any(|step| step.contains("gh release create"))
+        })
+    }
+}
@@ -0,0 +1,45 @@
+use crate::error::Result;
+use crate::platforms::github::models::GitHubStep;
+use crate::traits::ToGitea;
+use std::collections::BTreeMap;
+
+use super::{ReleaseKind, ReleasePreset};
+
+impl ToGitea for ReleasePreset {
+    fn to_gitea(&self) -> Result<crate::platforms::gitea::models::GiteaWorkflow> {
+        // Gitea has no gh equivalent on its runners, so the release API is called directly
+        let body = if self.changelog {
+            "--rawfile body CHANGELOG.md"
+        } else {
+            "--arg body \"\""
+        };
+        let mut script = format!(
+            "release_id=$(jq -n --arg tag \"$GITHUB_REF_NAME\" {} \
+             --argjson draft {} --argjson prerelease {} \
+             '{{tag_name: $tag, name: $tag, body: $body, draft: $draft, prerelease: $prerelease}}' \
+             | curl -sSf -X POST -H \"Authorization: token $GITEA_TOKEN\" -H \"Content-Type: application/json\" \
+             -d @- \"$GITHUB_SERVER_URL/api/v1/repos/$GITHUB_REPOSITORY/releases\" | jq -r .id)\n",
+            body,
+            self.release_kind == ReleaseKind::Draft,
+            self.release_kind == ReleaseKind::Prerelease,
+        );
+
+        let assets = self.asset_patterns();
+        if !assets.is_empty() {
+            script.push_str(&format!(
+                "for file in {}; do\n  curl -sSf -X POST -H \"Authorization: token $GITEA_TOKEN\" \
+                 -F \"attachment=@$file\" \
+                 \"$GITHUB_SERVER_URL/api/v1/repos/$GITHUB_REPOSITORY/releases/$release_id/assets?name=$(basename \"$file\")\"\ndone\n",
+                assets.join(" ")
+            ));
+        }
+
+        let mut publish = GitHubStep::run("Create Gitea release", script);
+        publish.env = Some(BTreeMap::from([(
+            "GITEA_TOKEN".to_string(),
+            "${{ secrets.GITHUB_TOKEN }}".to_string(),
+        )]));
+
+        Ok(self.release_workflow(publish))
+    }
+}
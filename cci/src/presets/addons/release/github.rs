@@ -0,0 +1,80 @@
+use crate::error::Result;
+use crate::models::steps::changelog;
+use crate::platforms::github::models::{GitHubJob, GitHubStep, GitHubWorkflow};
+use crate::platforms::jobs::Jobs;
+use crate::traits::ToGitHub;
+use std::collections::BTreeMap;
+
+use super::ReleasePreset;
+
+impl ReleasePreset {
+    /// Release workflow around the platform-specific publish step
+    ///
+    /// Gitea shares everything but the publish step, which uses its own API.
+    pub(super) fn release_workflow(&self, publish: GitHubStep) -> GitHubWorkflow {
+        // Full history so the changelog can find the previous tag
+        let mut steps = vec![GitHubStep::action_with_config(
+            "Checkout code",
+            "actions/checkout@v4",
+            BTreeMap::from([("fetch-depth".to_string(), 0.into())]),
+        )];
+
+        if !self.build_command.trim().is_empty() {
+            steps.push(GitHubStep::run(
+                "Build release assets",
+                self.build_command.trim(),
+            ));
+        }
+        if self.changelog {
+            steps.push(GitHubStep::from(&changelog("$GITHUB_REF_NAME")));
+        }
+        if self.create_release {
+            steps.push(publish);
+        }
+
+        let triggers = self.triggers();
+        GitHubWorkflow {
+            name: triggers.name.clone(),
+            on: triggers.to_github(),
+            env: None,
+            defaults: None,
+            jobs: Jobs::from([(
+                "release/publish".to_string(),
+                GitHubJob {
+                    runs_on: "ubuntu-latest".into(),
+                    container: None,
+                    permissions: Some(BTreeMap::from([(
+                        "contents".to_string(),
+                        "write".to_string(),
+                    )])),
+                    strategy: None,
+                    services: None,
+                    env: None,
+                    steps,
+                    needs: None,
+                    timeout_minutes: Some(30),
+                    continue_on_error: None,
+                    if_condition: None,
+                    environment: None,
+                },
+            )]),
+            permissions: None,
+            concurrency: None,
+        }
+    }
+}
+
+impl ToGitHub for ReleasePreset {
+    fn to_github(&self) -> Result<GitHubWorkflow> {
+        let mut publish = GitHubStep::run(
+            "Create GitHub release",
+            self.gh_release_command("$GITHUB_REF_NAME"),
+        );
+        publish.env = Some(BTreeMap::from([(
+            "GH_TOKEN".to_string(),
+            "${{ github.token }}".to_string(),
+        )]));
+
+        Ok(self.release_workflow(publish))
+    }
+}
@@ -0,0 +1,126 @@
+use crate::error::Result;
+use crate::models::steps;
+use crate::platforms::artifacts::Artifact;
+use crate::platforms::gitlab::models::GitLabCI;
+use crate::platforms::jobs::Jobs;
+use crate::traits::ToGitLab;
+use std::collections::BTreeMap;
+
+use super::ReleasePreset;
+
+impl ToGitLab for ReleasePreset {
+    fn to_gitlab(&self) -> Result<GitLabCI> {
+        use crate::platforms::gitlab::models::*;
+
+        let mut jobs = Jobs::new();
+        let mut stages = Vec::new();
+        let assets = self.asset_patterns();
+
+        // release-cli runs in a minimal image, so assets are built in a separate job
+        let has_build = !self.build_command.trim().is_empty();
+        if has_build {
+            stages.push("build".to_string());
+            jobs.insert(
+                "release/build".to_string(),
+                GitLabJob {
+                    stage: "build".to_string(),
+                    image: None,
+                    services: None,
+                    variables: None,
+                    script: vec![self.build_command.trim().to_string()],
+                    before_script: None,
+                    after_script: None,
+                    needs: None,
+                    cache: None,
+                    artifacts: None,
+                    only: None,
+                    tags: None,
+                    parallel: None,
+                    timeout: None,
+                    retry: None,
+                    allow_failure: None,
+                    rules: None,
+                    when: None,
+                    interruptible: None,
+                    coverage: None,
+                    environment: None,
+                },
+            );
+        }
+
+        let mut script = Vec::new();
+        if self.changelog {
+            script.push(steps::changelog_script("$CI_COMMIT_TAG"));
+        }
+        if self.create_release {
+            let mut create =
+                "release-cli create --name \"$CI_COMMIT_TAG\" --tag-name \"$CI_COMMIT_TAG\""
+                    .to_string();
+            if self.changelog {
+                create.push_str(" --description CHANGELOG.md");
+            }
+            // GitLab releases link to files by URL; this job re-uploads the
+            // assets as its own artifacts so the links resolve
+            if !assets.is_empty() {
+                script.push("set --".to_string());
+                script.push(format!(
+                    "for file in {}; do set -- \"$@\" --assets-link \"{{\\\"name\\\":\\\"$(basename \"$file\")\\\",\\\"url\\\":\\\"$CI_PROJECT_URL/-/jobs/$CI_JOB_ID/artifacts/raw/$file\\\"}}\"; done",
+                    assets.join(" ")
+                ));
+                create.push_str(" \"$@\"");
+            }
+            script.push(create);
+        }
+
+        stages.push("release".to_string());
+        jobs.insert(
+            "release/publish".to_string(),
+            GitLabJob {
+                stage: "release".to_string(),
+                image: Some("registry.gitlab.com/gitlab-org/release-cli:latest".to_string()),
+                services: None,
+                // Full history so the changelog can find the previous tag
+                variables: Some(BTreeMap::from([("GIT_DEPTH".to_string(), "0".to_string())])),
+                script,
+                before_script: self
+                    .changelog
+                    .then(|| vec!["apk add --no-cache git".to_string()]),
+                after_script: None,
+                needs: None,
+                cache: None,
+                artifacts: None,
+                only: None,
+                tags: None,
+                parallel: None,
+                timeout: None,
+                retry: None,
+                allow_failure: None,
+                rules: None,
+                when: None,
+                interruptible: Some(false),
+                coverage: None,
+                environment: None,
+            },
+        );
+
+        if !assets.is_empty() {
+            let artifact = Artifact::new("release-assets", &assets);
+            artifact.apply_gitlab(&mut jobs, "release/build", &["release/publish"]);
+            if let Some(publish) = jobs.get_mut("release/publish") {
+                publish.artifacts = Some(artifact.to_gitlab());
+            }
+        }
+
+        let mut ci = GitLabCI {
+            include: None,
+            workflow: None,
+            default: None,
+            stages: Some(stages),
+            variables: None,
+            cache: None,
+            jobs,
+        };
+        self.triggers().apply_gitlab(&mut ci);
+        Ok(ci)
+    }
+}
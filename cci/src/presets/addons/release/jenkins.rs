@@ -0,0 +1,42 @@
+use crate::error::Result;
+use crate::models::steps;
+use crate::platforms::jenkins::models::JenkinsConfig;
+use crate::traits::ToJenkins;
+
+use super::ReleasePreset;
+
+impl ToJenkins for ReleasePreset {
+    fn to_jenkins(&self) -> Result<JenkinsConfig> {
+        use crate::platforms::jenkins::models::*;
+
+        let mut steps = Vec::new();
+        if !self.build_command.trim().is_empty() {
+            steps.push(format!("sh '{}'", self.build_command.trim()));
+        }
+        if self.changelog {
+            steps.push(format!("sh '{}'", steps::changelog_script("$TAG_NAME")));
+        }
+        // Requires the GitHub CLI on the agent
+        if self.create_release {
+            steps.push(format!(
+                "withCredentials([string(credentialsId: 'GH_TOKEN', variable: 'GH_TOKEN')]) {{ sh '{}' }}",
+                self.gh_release_command("$TAG_NAME")
+            ));
+        }
+
+        let mut config = JenkinsConfig {
+            agent: JenkinsAgent::Any,
+            environment: vec![],
+            triggers: vec![],
+            when: vec![],
+            stages: vec![JenkinsStage {
+                name: "Release".to_string(),
+                when: vec![],
+                steps,
+            }],
+            post: vec![],
+        };
+        self.triggers().apply_jenkins(&mut config);
+        Ok(config)
+    }
+}
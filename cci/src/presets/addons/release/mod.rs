@@ -0,0 +1,308 @@
+use crate::editor::state::Platform;
+use crate::platforms::secrets::{self, Secret};
+use crate::platforms::triggers::TriggerConfig;
+use crate::traits::{
+    DecoratesPipelines, DetectedDefaults, LocalChecks, PresetInfo, RequiresSecrets, SupportFiles,
+};
+use cci_macros::{Preset, PresetEnum};
+use std::collections::HashMap;
+
+mod circleci;
+mod detectable;
+mod gitea;
+mod github;
+mod gitlab;
+mod jenkins;
+
+/// How a release is published
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, PresetEnum)]
+#[preset_enum(default = "Published")]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseKind {
+    #[preset_variant(id = "published", display = "Published")]
+    Published,
+    /// Created as a draft for manual review
+    #[preset_variant(id = "draft", display = "Draft")]
+    Draft,
+    /// Published but marked as a pre-release
+    #[preset_variant(id = "prerelease", display = "Pre-release")]
+    Prerelease,
+}
+
+/// Addon that publishes a release whenever a version tag is pushed
+#[derive(Debug, Clone, Preset)]
+#[preset(
+    id = "release",
+    name = "Release",
+    description = "Tag-triggered releases with a changelog and attached build artifacts",
+    addon
+)]
+pub struct ReleasePreset {
+    #[preset_field(
+        feature = "release",
+        feature_display = "Release",
+        display = "Create Release",
+        description = "Publish a GitHub/Gitea/GitLab release for each matching tag",
+        default = "true"
+    )]
+    pub(super) create_release: bool,
+
+    #[preset_field(
+        feature = "release",
+        feature_display = "Release",
+        display = "Generate Changelog",
+        description = "Use the commits since the previous tag as release notes",
+        default = "true"
+    )]
+    pub(super) changelog: bool,
+
+    #[preset_field(
+        feature = "release",
+        feature_display = "Release",
+        display = "Release Type",
+        description = "Publish immediately, as a draft, or as a pre-release",
+        default = "ReleaseKind::Published"
+    )]
+    pub(super) release_kind: ReleaseKind,
+
+    #[preset_field(
+        feature = "release",
+        feature_display = "Release",
+        display = "Build Command",
+        description = "Command that produces the release assets (empty to skip)",
+        default = "String::new()"
+    )]
+    pub(super) build_command: String,
+
+    #[preset_field(
+        feature = "release",
+        feature_display = "Release",
+        display = "Assets",
+        description = "Comma-separated files or globs attached to the release (e.g., dist/*)",
+        default = "String::new()"
+    )]
+    pub(super) assets: String,
+
+    #[preset_field(
+        feature = "triggers",
+        feature_display = "Triggers",
+        display = "Workflow Name",
+        description = "Name shown for the generated workflow/pipeline",
+        default = "\"Release\".to_string()"
+    )]
+    pub(super) workflow_name: String,
+
+    #[preset_field(
+        feature = "triggers",
+        feature_display = "Triggers",
+        display = "Tags",
+        description = "Comma-separated tag patterns that publish a release",
+        default = "\"v*\".to_string()"
+    )]
+    pub(super) tags: String,
+}
+
+impl ReleasePreset {
+    /// Constant default instance for registry initialization
+    pub const DEFAULT: Self = Self {
+        create_release: false,
+        changelog: false,
+        release_kind: ReleaseKind::Published,
+        build_command: String::new(),
+        assets: String::new(),
+        workflow_name: String::new(),
+        tags: String::new(),
+    };
+
+    /// Tag-only trigger configuration shared by all platform backends
+    pub(super) fn triggers(&self) -> TriggerConfig {
+        TriggerConfig::from_options(&self.workflow_name, "", "", &self.tags, "", false)
+    }
+
+    /// Asset globs, left unquoted in scripts so the shell expands them
+    pub(super) fn asset_patterns(&self) -> Vec<&str> {
+        self.assets
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect()
+    }
+
+    /// `gh release create` invocation for platforms that publish to GitHub
+    pub(super) fn gh_release_command(&self, tag: &str) -> String {
+        let mut command = format!("gh release create \"{tag}\" --title \"{tag}\"");
+        if self.changelog {
+            command.push_str(" --notes-file CHANGELOG.md");
+        } else {
+            command.push_str(" --notes \"\"");
+        }
+        match self.release_kind {
+            ReleaseKind::Published => {}
+            ReleaseKind::Draft => command.push_str(" --draft"),
+            ReleaseKind::Prerelease => command.push_str(" --prerelease"),
+        }
+        for pattern in self.asset_patterns() {
+            command.push(' ');
+            command.push_str(pattern);
+        }
+        command
+    }
+}
+
+impl RequiresSecrets for ReleasePreset {
+    fn required_secrets(&self, platform: Platform) -> Vec<Secret> {
+        match platform {
+            // Actions workflows and GitLab jobs get a token with release access
+            Platform::GitHub | Platform::Gitea | Platform::GitLab => Vec::new(),
+            Platform::CircleCI | Platform::Jenkins if self.create_release => {
+                vec![secrets::GH_TOKEN]
+            }
+            Platform::CircleCI | Platform::Jenkins => Vec::new(),
+        }
+    }
+}
+
+impl SupportFiles for ReleasePreset {}
+
+impl DecoratesPipelines for ReleasePreset {}
+
+impl DetectedDefaults for ReleasePreset {
+    /// Command-line tools are released as binaries
+    fn matches_metadata(&self, metadata: &HashMap<String, String>) -> bool {
+        metadata.contains_key("cli")
+    }
+}
+
+impl LocalChecks for ReleasePreset {}
+
+impl PresetInfo for ReleasePreset {
+    fn name(&self) -> &str {
+        "release"
+    }
+
+    fn description(&self) -> &str {
+        "Tag-triggered releases with a changelog and attached build artifacts"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platforms::github::models::{GitHubTrigger, GitHubTriggers};
+    use crate::traits::{Detectable, ToCircleCI, ToGitHub, ToGitLab, ToGitea, ToJenkins};
+
+    fn release_preset() -> ReleasePreset {
+        ReleasePreset {
+            build_command: "make dist".to_string(),
+            assets: "dist/*, checksums.txt".to_string(),
+            ..ReleasePreset::default()
+        }
+    }
+
+    #[test]
+    fn test_to_github_tag_trigger() {
+        let workflow = release_preset().to_github().unwrap();
+
+        assert_eq!(workflow.name, "Release");
+        let GitHubTriggers::Detailed(triggers) = &workflow.on else {
+            panic!("expected detailed triggers");
+        };
+        let Some(GitHubTrigger::Filter(push)) = triggers.get("push") else {
+            panic!("expected push trigger");
+        };
+        assert!(push.branches.is_none());
+        assert_eq!(push.tags, Some(vec!["v*".to_string()]));
+        assert!(!triggers.contains_key("pull_request"));
+
+        let job = &workflow.jobs["release/publish"];
+        assert_eq!(
+            job.permissions.as_ref().unwrap()["contents"],
+            "write".to_string()
+        );
+        let runs: Vec<&str> = job.steps.iter().filter_map(|s| s.run.as_deref()).collect();
+        assert_eq!(runs[0], "make dist");
+        assert!(runs[1].ends_with("> CHANGELOG.md"));
+        assert_eq!(
+            runs[2],
+            "gh release create \"$GITHUB_REF_NAME\" --title \"$GITHUB_REF_NAME\" --notes-file CHANGELOG.md dist/* checksums.txt"
+        );
+        assert!(release_preset().matches_github(&workflow));
+    }
+
+    #[test]
+    fn test_release_kind_flags() {
+        let preset = ReleasePreset {
+            changelog: false,
+            release_kind: ReleaseKind::Draft,
+            ..ReleasePreset::default()
+        };
+        assert_eq!(
+            preset.gh_release_command("$CIRCLE_TAG"),
+            "gh release create \"$CIRCLE_TAG\" --title \"$CIRCLE_TAG\" --notes \"\" --draft"
+        );
+    }
+
+    #[test]
+    fn test_to_gitea_uses_api() {
+        let workflow = release_preset().to_gitea().unwrap();
+        let publish = workflow.jobs["release/publish"].steps.last().unwrap();
+        let script = publish.run.as_deref().unwrap();
+        assert!(script.contains("/api/v1/repos/$GITHUB_REPOSITORY/releases"));
+        assert!(script.contains("for file in dist/* checksums.txt; do"));
+        assert!(!script.contains("gh release"));
+        assert!(release_preset().matches_gitea(&workflow));
+    }
+
+    #[test]
+    fn test_to_gitlab_links_assets() {
+        let ci = release_preset().to_gitlab().unwrap();
+
+        assert_eq!(
+            ci.stages,
+            Some(vec!["build".to_string(), "release".to_string()])
+        );
+        let publish = &ci.jobs["release/publish"];
+        assert_eq!(publish.needs, Some(vec!["release/build".to_string()]));
+        assert!(publish
+            .script
+            .last()
+            .unwrap()
+            .starts_with("release-cli create"));
+        assert!(publish
+            .script
+            .iter()
+            .any(|line| line.contains("--assets-link")));
+        assert!(release_preset().matches_gitlab(&ci));
+    }
+
+    #[test]
+    fn test_circleci_and_jenkins() {
+        let config = release_preset().to_circleci().unwrap();
+        let yaml = serde_yaml::to_string(&config).unwrap();
+        assert!(yaml.contains("gh release create \"$CIRCLE_TAG\""));
+        assert!(yaml.contains("ignore:\n            - /.*/"));
+
+        let jenkins = release_preset().to_jenkins().unwrap();
+        assert!(jenkins.when.contains(&"tag 'v*'".to_string()));
+        assert!(jenkins.stages[0]
+            .steps
+            .iter()
+            .any(|step| step.contains("credentialsId: 'GH_TOKEN'")));
+    }
+
+    #[test]
+    fn test_required_secrets() {
+        let preset = release_preset();
+        assert!(preset.required_secrets(Platform::GitHub).is_empty());
+        assert_eq!(
+            preset.required_secrets(Platform::Jenkins),
+            vec![secrets::GH_TOKEN]
+        );
+
+        let notes_only = ReleasePreset {
+            create_release: false,
+            ..release_preset()
+        };
+        assert!(notes_only.required_secrets(Platform::CircleCI).is_empty());
+    }
+}
@@ -0,0 +1,73 @@
+use crate::editor::state::Platform;
+use crate::error::Result;
+use crate::platforms::circleci::models::CircleCIConfig;
+use crate::platforms::jobs::Jobs;
+use crate::traits::ToCircleCI;
+
+use super::{ReleaseAutomationPreset, ReleaseTool};
+
+impl ToCircleCI for ReleaseAutomationPreset {
+    fn to_circleci(&self) -> Result<CircleCIConfig> {
+        use crate::platforms::circleci::models::*;
+        use std::collections::BTreeMap;
+
+        // Both tools release to GitHub, authenticated with GH_TOKEN
+        let (commands, environment) = match self.tool {
+            ReleaseTool::ReleasePlease => (
+                Self::release_please_commands("$CIRCLE_PROJECT_USERNAME/$CIRCLE_PROJECT_REPONAME"),
+                Some(BTreeMap::from([(
+                    "RELEASE_BRANCH".to_string(),
+                    self.branch().to_string(),
+                )])),
+            ),
+            ReleaseTool::SemanticRelease => (
+                vec![("Release", self.semantic_release_command(Platform::CircleCI))],
+                None,
+            ),
+        };
+
+        let mut steps = vec![CircleCIStep::Simple("checkout".to_string())];
+        steps.extend(
+            commands
+                .into_iter()
+                .map(|(name, command)| CircleCIStep::Command {
+                    run: CircleCIRun::Detailed {
+                        name: name.to_string(),
+                        command,
+                    },
+                }),
+        );
+
+        let mut config = CircleCIConfig {
+            version: "2.1".to_string(),
+            orbs: None,
+            parameters: None,
+            jobs: Jobs::from([(
+                "release-automation/release".to_string(),
+                CircleCIJob {
+                    docker: vec![CircleCIDocker {
+                        image: "cimg/node:lts".to_string(),
+                        environment: None,
+                    }],
+                    machine: None,
+                    macos: None,
+                    resource_class: None,
+                    parameters: None,
+                    steps,
+                    environment,
+                },
+            )]),
+            workflows: BTreeMap::from([(
+                "release-automation".to_string(),
+                CircleCIWorkflow {
+                    triggers: None,
+                    jobs: vec![CircleCIWorkflowJob::Simple(
+                        "release-automation/release".to_string(),
+                    )],
+                },
+            )]),
+        };
+        self.triggers().apply_circleci(&mut config);
+        Ok(config)
+    }
+}
@@ -0,0 +1,56 @@
+use crate::platforms::circleci::models::{CircleCIConfig, CircleCIRun, CircleCIStep};
+use crate::platforms::github::models::GitHubWorkflow;
+use crate::platforms::gitlab::models::GitLabCI;
+use crate::platforms::jenkins::models::JenkinsConfig;
+use crate::traits::Detectable;
+
+use super::ReleaseAutomationPreset;
+
+/// Whether a command runs either release tool
+fn runs_release_tool(command: &str) -> bool {
+    command.contains("semantic-release") || command.contains("release-please")
+}
+
+impl Detectable for ReleaseAutomationPreset {
+    fn matches_github(&self, workflow: &GitHubWorkflow) -> bool {
+        workflow.jobs.values().any(|job| {
+            job.steps.iter().any(|step| {
+                step.uses
+                    .as_ref()
+                    .map(|u| u.contains("release-please-action"))
+                    .unwrap_or(false)
+                    || step.run.as_deref().map(runs_release_tool).unwrap_or(false)
+            })
+        })
+    }
+
+    fn matches_gitea(&self, workflow: &crate::platforms::gitea::models::GiteaWorkflow) -> bool {
+        // Gitea Actions uses the same workflow format as GitHub Actions
+        self.matches_github(workflow)
+    }
+
+    fn matches_gitlab(&self, config: &GitLabCI) -> bool {
+        config
+            .jobs
+            .values()
+            .any(|job| job.script.iter().any(|line| runs_release_tool(line)))
+    }
+
+    fn matches_circleci(&self, config: &CircleCIConfig) -> bool {
+        config.jobs.values().any(|job| {
+            job.steps.iter().any(|step| match step {
+                CircleCIStep::Command {
+                    run: CircleCIRun::Detailed { command, .. },
+                } => runs_release_tool(command),
+                _ => false,
+            })
+        })
+    }
+
+    fn matches_jenkins(&self, config: &JenkinsConfig) -> bool {
+        config
+            .stages
+            .iter()
+            .any(|stage| stage.steps.iter().any(|step| runs_release_tool(step)))
+    }
+}
@@ -0,0 +1,28 @@
+use crate::editor::state::Platform;
+use crate::error::Result;
+use crate::traits::ToGitea;
+use std::collections::BTreeMap;
+
+use super::ReleaseAutomationPreset;
+
+impl ToGitea for ReleaseAutomationPreset {
+    fn to_gitea(&self) -> Result<crate::platforms::gitea::models::GiteaWorkflow> {
+        self.ensure_supported(Platform::Gitea)?;
+
+        // The Gitea plugin reads the server and token from its own variables
+        let steps = self.semantic_release_steps(
+            Platform::Gitea,
+            BTreeMap::from([
+                (
+                    "GITEA_TOKEN".to_string(),
+                    "${{ secrets.GITHUB_TOKEN }}".to_string(),
+                ),
+                (
+                    "GITEA_URL".to_string(),
+                    "${{ github.server_url }}".to_string(),
+                ),
+            ]),
+        );
+        Ok(self.release_workflow(steps, &["contents", "issues", "pull-requests"]))
+    }
+}
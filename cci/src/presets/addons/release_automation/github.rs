@@ -0,0 +1,103 @@
+use crate::editor::state::Platform;
+use crate::error::Result;
+use crate::platforms::github::models::{GitHubJob, GitHubStep, GitHubWorkflow};
+use crate::platforms::jobs::Jobs;
+use crate::traits::ToGitHub;
+use std::collections::BTreeMap;
+
+use super::{ReleaseAutomationPreset, ReleaseTool, RELEASE_PLEASE_CONFIG, RELEASE_PLEASE_MANIFEST};
+
+impl ReleaseAutomationPreset {
+    /// Workflow with a single job that needs write access to the repository
+    pub(super) fn release_workflow(
+        &self,
+        steps: Vec<GitHubStep>,
+        permissions: &[&str],
+    ) -> GitHubWorkflow {
+        let triggers = self.triggers();
+        GitHubWorkflow {
+            name: triggers.name.clone(),
+            on: triggers.to_github(),
+            env: None,
+            defaults: None,
+            jobs: Jobs::from([(
+                "release-automation/release".to_string(),
+                GitHubJob {
+                    runs_on: "ubuntu-latest".into(),
+                    container: None,
+                    permissions: Some(
+                        permissions
+                            .iter()
+                            .map(|scope| (scope.to_string(), "write".to_string()))
+                            .collect(),
+                    ),
+                    strategy: None,
+                    services: None,
+                    env: None,
+                    steps,
+                    needs: None,
+                    timeout_minutes: Some(15),
+                    continue_on_error: None,
+                    if_condition: None,
+                    environment: None,
+                },
+            )]),
+            permissions: None,
+            concurrency: None,
+        }
+    }
+
+    /// semantic-release steps authenticated through `env`
+    pub(super) fn semantic_release_steps(
+        &self,
+        platform: Platform,
+        env: BTreeMap<String, String>,
+    ) -> Vec<GitHubStep> {
+        let mut release = GitHubStep::run("Release", self.semantic_release_command(platform));
+        release.env = Some(env);
+
+        vec![
+            // Full history so every commit since the last release is analyzed
+            GitHubStep::action_with_config(
+                "Checkout code",
+                "actions/checkout@v4",
+                BTreeMap::from([("fetch-depth".to_string(), 0.into())]),
+            ),
+            GitHubStep::action_with_config(
+                "Setup Node.js",
+                "actions/setup-node@v4",
+                BTreeMap::from([("node-version".to_string(), "lts/*".into())]),
+            ),
+            release,
+        ]
+    }
+}
+
+impl ToGitHub for ReleaseAutomationPreset {
+    fn to_github(&self) -> Result<GitHubWorkflow> {
+        Ok(match self.tool {
+            ReleaseTool::ReleasePlease => self.release_workflow(
+                vec![GitHubStep::action_with_config(
+                    "Run release-please",
+                    "googleapis/release-please-action@v4",
+                    BTreeMap::from([
+                        ("config-file".to_string(), RELEASE_PLEASE_CONFIG.into()),
+                        ("manifest-file".to_string(), RELEASE_PLEASE_MANIFEST.into()),
+                        ("target-branch".to_string(), self.branch().into()),
+                    ]),
+                )],
+                &["contents", "pull-requests"],
+            ),
+            ReleaseTool::SemanticRelease => self.release_workflow(
+                self.semantic_release_steps(
+                    Platform::GitHub,
+                    BTreeMap::from([(
+                        "GITHUB_TOKEN".to_string(),
+                        "${{ secrets.GITHUB_TOKEN }}".to_string(),
+                    )]),
+                ),
+                &["contents", "issues", "pull-requests"],
+            ),
+        })
+    }
+}
@@ -0,0 +1,54 @@
+use crate::editor::state::Platform;
+use crate::error::Result;
+use crate::platforms::gitlab::models::GitLabCI;
+use crate::platforms::jobs::Jobs;
+use crate::traits::ToGitLab;
+use std::collections::BTreeMap;
+
+use super::ReleaseAutomationPreset;
+
+impl ToGitLab for ReleaseAutomationPreset {
+    fn to_gitlab(&self) -> Result<GitLabCI> {
+        use crate::platforms::gitlab::models::*;
+
+        self.ensure_supported(Platform::GitLab)?;
+
+        let mut ci = GitLabCI {
+            include: None,
+            workflow: None,
+            default: None,
+            stages: Some(vec!["release".to_string()]),
+            variables: None,
+            cache: None,
+            jobs: Jobs::from([(
+                "release-automation/release".to_string(),
+                GitLabJob {
+                    stage: "release".to_string(),
+                    image: Some("node:lts".to_string()),
+                    services: None,
+                    // Full history so every commit since the last release is analyzed
+                    variables: Some(BTreeMap::from([("GIT_DEPTH".to_string(), "0".to_string())])),
+                    script: vec![self.semantic_release_command(Platform::GitLab)],
+                    before_script: None,
+                    after_script: None,
+                    needs: None,
+                    cache: None,
+                    artifacts: None,
+                    only: None,
+                    tags: None,
+                    parallel: None,
+                    timeout: None,
+                    retry: None,
+                    allow_failure: None,
+                    rules: None,
+                    when: None,
+                    interruptible: Some(false),
+                    coverage: None,
+                    environment: None,
+                },
+            )]),
+        };
+        self.triggers().apply_gitlab(&mut ci);
+        Ok(ci)
+    }
+}
@@ -0,0 +1,58 @@
+use crate::editor::state::Platform;
+use crate::error::Result;
+use crate::platforms::jenkins::models::JenkinsConfig;
+use crate::traits::ToJenkins;
+
+use super::{ReleaseAutomationPreset, ReleaseTool};
+
+impl ToJenkins for ReleaseAutomationPreset {
+    fn to_jenkins(&self) -> Result<JenkinsConfig> {
+        use crate::platforms::jenkins::models::*;
+
+        let mut environment = vec![];
+        let commands = match self.tool {
+            ReleaseTool::ReleasePlease => {
+                environment.push(("RELEASE_BRANCH".to_string(), self.branch().to_string()));
+                // owner/repo from the clone URL the git plugin exports
+                Self::release_please_commands("$REPO")
+                    .into_iter()
+                    .map(|(_, command)| {
+                        format!(
+                            "REPO=${{GIT_URL%.git}}; REPO=${{REPO#*github.com[:/]}}; {}",
+                            command
+                        )
+                    })
+                    .collect()
+            }
+            ReleaseTool::SemanticRelease => {
+                vec![self.semantic_release_command(Platform::Jenkins)]
+            }
+        };
+
+        // Requires Node.js on the agent
+        let steps = commands
+            .into_iter()
+            .map(|command| {
+                format!(
+                    "withCredentials([string(credentialsId: 'GH_TOKEN', variable: 'GH_TOKEN')]) {{ sh '{}' }}",
+                    command
+                )
+            })
+            .collect();
+
+        let mut config = JenkinsConfig {
+            agent: JenkinsAgent::Any,
+            environment,
+            triggers: vec![],
+            when: vec![],
+            stages: vec![JenkinsStage {
+                name: "Release".to_string(),
+                when: vec![],
+                steps,
+            }],
+            post: vec![],
+        };
+        self.triggers().apply_jenkins(&mut config);
+        Ok(config)
+    }
+}
@@ -0,0 +1,418 @@
+use crate::editor::state::Platform;
+use crate::error::{config_error, Result};
+use crate::platforms::secrets::{self, Secret};
+use crate::platforms::triggers::TriggerConfig;
+use crate::traits::{
+    DecoratesPipelines, DetectedDefaults, LocalChecks, PresetInfo, RequiresSecrets, SupportFiles,
+};
+use cci_macros::{Preset, PresetEnum};
+use std::path::PathBuf;
+
+mod circleci;
+mod detectable;
+mod gitea;
+mod github;
+mod gitlab;
+mod jenkins;
+
+/// Tool that turns conventional commits into versions and releases
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, PresetEnum)]
+#[preset_enum(default = "ReleasePlease")]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseTool {
+    /// Maintains a release PR; merging it tags and publishes the release
+    #[preset_variant(id = "release-please", display = "release-please")]
+    #[serde(rename = "release_please")]
+    ReleasePlease,
+    /// Publishes a release on every push with releasable commits
+    #[preset_variant(id = "semantic-release", display = "semantic-release")]
+    #[serde(rename = "semantic_release")]
+    SemanticRelease,
+}
+
+/// Package manifest release-please keeps in sync with the version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, PresetEnum)]
+#[preset_enum(default = "Simple")]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseType {
+    /// Only `version.txt` and the changelog
+    #[preset_variant(id = "simple", display = "Simple")]
+    Simple,
+    #[preset_variant(id = "rust", display = "Rust (Cargo.toml)")]
+    Rust,
+    #[preset_variant(id = "python", display = "Python (pyproject.toml)")]
+    Python,
+    #[preset_variant(id = "go", display = "Go")]
+    Go,
+}
+
+impl ReleaseType {
+    /// release-please `release-type` name
+    fn release_please_name(&self) -> &'static str {
+        match self {
+            ReleaseType::Simple => "simple",
+            ReleaseType::Rust => "rust",
+            ReleaseType::Python => "python",
+            ReleaseType::Go => "go",
+        }
+    }
+}
+
+pub(super) const RELEASE_PLEASE_CONFIG: &str = "release-please-config.json";
+pub(super) const RELEASE_PLEASE_MANIFEST: &str = ".release-please-manifest.json";
+pub(super) const SEMANTIC_RELEASE_CONFIG: &str = ".releaserc";
+
+/// Addon that versions and releases the project from conventional commits
+#[derive(Debug, Clone, Preset)]
+#[preset(
+    id = "release-automation",
+    name = "Release Automation",
+    description = "Versioning and changelogs from conventional commits with release-please or semantic-release",
+    addon
+)]
+pub struct ReleaseAutomationPreset {
+    #[preset_field(
+        feature = "release_automation",
+        feature_display = "Release Automation",
+        display = "Update CHANGELOG.md",
+        description = "Commit a changelog generated from conventional commits with each release",
+        default = "true"
+    )]
+    pub(super) changelog: bool,
+
+    #[preset_field(
+        feature = "release_automation",
+        feature_display = "Release Automation",
+        display = "Tool",
+        description = "release-please opens a release PR; semantic-release releases on every qualifying push",
+        default = "ReleaseTool::ReleasePlease"
+    )]
+    pub(super) tool: ReleaseTool,
+
+    #[preset_field(
+        feature = "release_automation",
+        feature_display = "Release Automation",
+        display = "Release Type",
+        description = "Manifest release-please bumps the version in",
+        default = "ReleaseType::Simple"
+    )]
+    pub(super) release_type: ReleaseType,
+
+    #[preset_field(
+        feature = "release_automation",
+        feature_display = "Release Automation",
+        display = "Release Branch",
+        description = "Branch releases are cut from",
+        default = "\"main\".to_string()"
+    )]
+    pub(super) release_branch: String,
+
+    #[preset_field(
+        feature = "triggers",
+        feature_display = "Triggers",
+        display = "Workflow Name",
+        description = "Name shown for the generated workflow/pipeline",
+        default = "\"Release Automation\".to_string()"
+    )]
+    pub(super) workflow_name: String,
+}
+
+impl ReleaseAutomationPreset {
+    /// Constant default instance for registry initialization
+    pub const DEFAULT: Self = Self {
+        changelog: false,
+        tool: ReleaseTool::ReleasePlease,
+        release_type: ReleaseType::Simple,
+        release_branch: String::new(),
+        workflow_name: String::new(),
+    };
+
+    fn branch(&self) -> &str {
+        match self.release_branch.trim() {
+            "" => "main",
+            branch => branch,
+        }
+    }
+
+    /// Runs on pushes to the release branch
+    pub(super) fn triggers(&self) -> TriggerConfig {
+        TriggerConfig::from_options(&self.workflow_name, self.branch(), "", "", "", false)
+    }
+
+    /// release-please only talks to the GitHub API
+    pub(super) fn ensure_supported(&self, platform: Platform) -> Result<()> {
+        match (self.tool, platform) {
+            (ReleaseTool::ReleasePlease, Platform::Gitea | Platform::GitLab) => {
+                Err(config_error(format!(
+                    "release-please only supports GitHub repositories; use semantic-release on {}",
+                    platform.name()
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// `npx` invocation with the plugins `.releaserc` references
+    pub(super) fn semantic_release_command(&self, platform: Platform) -> String {
+        let mut command = "npx -p semantic-release".to_string();
+        if self.changelog {
+            command.push_str(" -p @semantic-release/changelog -p @semantic-release/git");
+        }
+        match platform {
+            Platform::Gitea => command.push_str(" -p @saithodev/semantic-release-gitea"),
+            Platform::GitLab => command.push_str(" -p @semantic-release/gitlab"),
+            // @semantic-release/github ships with semantic-release
+            Platform::GitHub | Platform::CircleCI | Platform::Jenkins => {}
+        }
+        command.push_str(" semantic-release");
+        command
+    }
+
+    /// Named release-please CLI commands for platforms without the GitHub action
+    pub(super) fn release_please_commands(repo: &str) -> Vec<(&'static str, String)> {
+        [
+            ("Update release PR", "release-pr"),
+            ("Publish release", "github-release"),
+        ]
+        .into_iter()
+        .map(|(name, command)| {
+            (
+                name,
+                format!(
+                    "npx release-please {} --token=\"$GH_TOKEN\" --repo-url=\"{}\" --target-branch=\"$RELEASE_BRANCH\" --config-file={} --manifest-file={}",
+                    command, repo, RELEASE_PLEASE_CONFIG, RELEASE_PLEASE_MANIFEST
+                ),
+            )
+        })
+        .collect()
+    }
+
+    fn release_please_config(&self) -> String {
+        let skip_changelog = if self.changelog {
+            ""
+        } else {
+            ",\n      \"skip-changelog\": true"
+        };
+        format!(
+            "{{\n  \"packages\": {{\n    \".\": {{\n      \"release-type\": \"{}\"{}\n    }}\n  }}\n}}\n",
+            self.release_type.release_please_name(),
+            skip_changelog
+        )
+    }
+
+    fn semantic_release_config(&self, platform: Platform) -> Result<String> {
+        use serde_yaml::Value;
+
+        let host_plugin = match platform {
+            Platform::Gitea => "@saithodev/semantic-release-gitea",
+            Platform::GitLab => "@semantic-release/gitlab",
+            Platform::GitHub | Platform::CircleCI | Platform::Jenkins => "@semantic-release/github",
+        };
+        let mut plugins: Vec<Value> = vec![
+            "@semantic-release/commit-analyzer".into(),
+            "@semantic-release/release-notes-generator".into(),
+        ];
+        if self.changelog {
+            plugins.push("@semantic-release/changelog".into());
+        }
+        plugins.push(host_plugin.into());
+        if self.changelog {
+            // Commit the updated changelog back to the release branch
+            plugins.push(Value::Sequence(vec![
+                "@semantic-release/git".into(),
+                Value::Mapping(
+                    [(
+                        "assets".into(),
+                        Value::Sequence(vec!["CHANGELOG.md".into()]),
+                    )]
+                    .into_iter()
+                    .collect(),
+                ),
+            ]));
+        }
+        let config: serde_yaml::Mapping = [
+            (
+                "branches".into(),
+                Value::Sequence(vec![self.branch().into()]),
+            ),
+            ("plugins".into(), Value::Sequence(plugins)),
+        ]
+        .into_iter()
+        .collect();
+        Ok(serde_yaml::to_string(&config)?)
+    }
+}
+
+impl SupportFiles for ReleaseAutomationPreset {
+    fn support_files(&self, platform: Platform) -> Result<Vec<(PathBuf, String)>> {
+        self.ensure_supported(platform)?;
+        Ok(match self.tool {
+            ReleaseTool::ReleasePlease => vec![
+                (
+                    PathBuf::from(RELEASE_PLEASE_CONFIG),
+                    self.release_please_config(),
+                ),
+                // An empty manifest makes release-please start from the latest tag
+                (PathBuf::from(RELEASE_PLEASE_MANIFEST), "{}\n".to_string()),
+            ],
+            ReleaseTool::SemanticRelease => vec![(
+                PathBuf::from(SEMANTIC_RELEASE_CONFIG),
+                self.semantic_release_config(platform)?,
+            )],
+        })
+    }
+}
+
+impl DecoratesPipelines for ReleaseAutomationPreset {}
+
+impl RequiresSecrets for ReleaseAutomationPreset {
+    fn required_secrets(&self, platform: Platform) -> Vec<Secret> {
+        match platform {
+            // Actions workflows get a token that can push and create releases
+            Platform::GitHub | Platform::Gitea => Vec::new(),
+            // CI_JOB_TOKEN can't push the changelog commit
+            Platform::GitLab => vec![secrets::GITLAB_TOKEN],
+            Platform::CircleCI | Platform::Jenkins => vec![secrets::GH_TOKEN],
+        }
+    }
+}
+
+impl DetectedDefaults for ReleaseAutomationPreset {}
+
+impl LocalChecks for ReleaseAutomationPreset {}
+
+impl PresetInfo for ReleaseAutomationPreset {
+    fn name(&self) -> &str {
+        "release-automation"
+    }
+
+    fn description(&self) -> &str {
+        "Versioning and changelogs from conventional commits with release-please or semantic-release"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{Detectable, ToCircleCI, ToGitHub, ToGitLab, ToGitea, ToJenkins};
+
+    fn semantic_release() -> ReleaseAutomationPreset {
+        ReleaseAutomationPreset {
+            tool: ReleaseTool::SemanticRelease,
+            ..ReleaseAutomationPreset::default()
+        }
+    }
+
+    #[test]
+    fn test_release_please_github() {
+        let preset = ReleaseAutomationPreset {
+            release_type: ReleaseType::Rust,
+            ..ReleaseAutomationPreset::default()
+        };
+        let workflow = preset.to_github().unwrap();
+        let job = &workflow.jobs["release-automation/release"];
+        let step = &job.steps[0];
+        assert_eq!(
+            step.uses.as_deref(),
+            Some("googleapis/release-please-action@v4")
+        );
+        assert_eq!(
+            step.with.as_ref().unwrap()["config-file"],
+            serde_yaml::Value::from(RELEASE_PLEASE_CONFIG)
+        );
+        assert_eq!(
+            job.permissions.as_ref().unwrap()["pull-requests"],
+            "write".to_string()
+        );
+        assert!(preset.matches_github(&workflow));
+
+        let files = preset.support_files(Platform::GitHub).unwrap();
+        assert_eq!(files[0].0, PathBuf::from(RELEASE_PLEASE_CONFIG));
+        assert!(files[0].1.contains("\"release-type\": \"rust\""));
+        assert_eq!(files[1].0, PathBuf::from(RELEASE_PLEASE_MANIFEST));
+    }
+
+    #[test]
+    fn test_release_please_unsupported_hosts() {
+        let preset = ReleaseAutomationPreset::default();
+        assert!(preset.to_gitlab().is_err());
+        assert!(preset.to_gitea().is_err());
+        assert!(preset.support_files(Platform::GitLab).is_err());
+
+        // The CLI works from any CI as long as the repository is on GitHub
+        let config = preset.to_circleci().unwrap();
+        let yaml = serde_yaml::to_string(&config).unwrap();
+        assert!(yaml.contains("npx release-please release-pr"));
+        assert!(yaml.contains("$CIRCLE_PROJECT_USERNAME/$CIRCLE_PROJECT_REPONAME"));
+    }
+
+    #[test]
+    fn test_semantic_release_config_per_platform() {
+        let preset = semantic_release();
+
+        let files = preset.support_files(Platform::GitLab).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].0, PathBuf::from(SEMANTIC_RELEASE_CONFIG));
+        let config: serde_yaml::Value = serde_yaml::from_str(&files[0].1).unwrap();
+        assert_eq!(config["branches"][0], serde_yaml::Value::from("main"));
+        assert_eq!(
+            config["plugins"][3],
+            serde_yaml::Value::from("@semantic-release/gitlab")
+        );
+
+        let ci = preset.to_gitlab().unwrap();
+        let job = &ci.jobs["release-automation/release"];
+        assert!(job.script[0].contains("-p @semantic-release/gitlab semantic-release"));
+        assert!(preset.matches_gitlab(&ci));
+
+        let gitea = preset.to_gitea().unwrap();
+        let run = gitea.jobs["release-automation/release"]
+            .steps
+            .iter()
+            .find_map(|step| step.run.as_deref())
+            .unwrap();
+        assert!(run.contains("semantic-release-gitea"));
+    }
+
+    #[test]
+    fn test_without_changelog() {
+        let preset = ReleaseAutomationPreset {
+            changelog: false,
+            ..ReleaseAutomationPreset::default()
+        };
+        let files = preset.support_files(Platform::GitHub).unwrap();
+        assert!(files[0].1.contains("\"skip-changelog\": true"));
+
+        let semantic = ReleaseAutomationPreset {
+            changelog: false,
+            ..semantic_release()
+        };
+        assert_eq!(
+            semantic.semantic_release_command(Platform::GitHub),
+            "npx -p semantic-release semantic-release"
+        );
+        let files = semantic.support_files(Platform::GitHub).unwrap();
+        assert!(!files[0].1.contains("CHANGELOG.md"));
+    }
+
+    #[test]
+    fn test_jenkins_uses_credentials() {
+        let jenkins = semantic_release().to_jenkins().unwrap();
+        assert!(jenkins.when.contains(&"branch 'main'".to_string()));
+        assert!(jenkins.stages[0].steps[0].contains("credentialsId: 'GH_TOKEN'"));
+    }
+
+    #[test]
+    fn test_required_secrets() {
+        let preset = semantic_release();
+        assert!(preset.required_secrets(Platform::GitHub).is_empty());
+        assert_eq!(
+            preset.required_secrets(Platform::GitLab),
+            vec![secrets::GITLAB_TOKEN]
+        );
+        assert_eq!(
+            preset.required_secrets(Platform::CircleCI),
+            vec![secrets::GH_TOKEN]
+        );
+    }
+}
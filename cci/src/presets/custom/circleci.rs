@@ -0,0 +1,14 @@
+use crate::editor::state::Platform;
+use crate::error::Result;
+use crate::models::adapters::{CircleCIAdapter, PlatformAdapter};
+use crate::platforms::circleci::models::CircleCIConfig;
+use crate::traits::{ToCircleCI, ToPipeline};
+
+use super::CustomPipeline;
+
+impl ToCircleCI for CustomPipeline<'_> {
+    fn to_circleci(&self) -> Result<CircleCIConfig> {
+        let pipeline = self.to_pipeline(Platform::CircleCI)?;
+        Ok(CircleCIAdapter::lower(&pipeline, &mut Vec::new()))
+    }
+}
@@ -0,0 +1,458 @@
+use crate::detection::ProjectType;
+use crate::editor::config::{OptionValue, PresetConfig, Validation};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashSet};
+
+/// Options every custom preset gets, so its workflow triggers like the
+/// built-in presets'
+pub(super) const TRIGGER_OPTIONS: &[&str] = &[
+    "workflow_name",
+    "push_branches",
+    "pr_branches",
+    "tags",
+    "schedule_cron",
+    "manual_dispatch",
+];
+
+/// A preset declared in a TOML or RON file
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomPresetDef {
+    /// Lowercase letters, digits and dashes, e.g. "acme-node"
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Project types the preset suits, e.g. `["NodeApp", "NodeLibrary"]`
+    #[serde(default)]
+    pub matches: Vec<String>,
+    /// Layer jobs on top of a base preset instead of being one
+    #[serde(default)]
+    pub addon: bool,
+    /// A built-in preset whose options and jobs this one starts from, e.g.
+    /// "rust"; its own jobs are added to that preset's pipeline
+    pub extends: Option<String>,
+    /// Secrets the steps reference, e.g. `["NPM_TOKEN"]`
+    #[serde(default)]
+    pub secrets: Vec<String>,
+    #[serde(default)]
+    pub features: Vec<FeatureDef>,
+    pub jobs: Vec<JobDef>,
+    /// The options of the preset it extends, which its jobs can use too
+    #[serde(skip)]
+    pub inherited: Vec<OptionDef>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FeatureDef {
+    pub id: String,
+    pub name: Option<String>,
+    #[serde(default)]
+    pub options: Vec<OptionDef>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OptionDef {
+    pub id: String,
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: String,
+    /// A bool for a toggle, otherwise a string; one of `variants` if it has any
+    pub default: DefaultValue,
+    /// The choices of an enum option
+    #[serde(default)]
+    pub variants: Vec<String>,
+    /// Bool option this one only applies with
+    pub depends_on: Option<String>,
+    /// Regex a string option's value must match as a whole
+    pub pattern: Option<String>,
+    /// What `pattern` accepts, shown when a value doesn't match
+    pub expected: Option<String>,
+    /// Group of bool options only one of which can be on
+    pub exclusive: Option<String>,
+    /// Example shown while a string option is empty
+    pub placeholder: Option<String>,
+    /// Bool option that must be on while this one is
+    pub requires: Option<String>,
+    /// Options that can't be on while this one is
+    #[serde(default)]
+    pub conflicts: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum DefaultValue {
+    Bool(bool),
+    String(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JobDef {
+    pub id: String,
+    pub name: Option<String>,
+    /// Condition the job is generated under, see [`StepDef::when`]
+    pub when: Option<String>,
+    /// Container image for GitLab and CircleCI
+    pub image: Option<String>,
+    /// Jobs of the same preset that must pass first
+    #[serde(default)]
+    pub needs: Vec<String>,
+    pub steps: Vec<StepDef>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StepDef {
+    pub name: Option<String>,
+    /// The command run on every platform without a template of its own
+    pub run: Option<String>,
+    /// A bool option (`e2e`) or an enum option's variant
+    /// (`package_manager == pnpm`) the step is generated under
+    pub when: Option<String>,
+    /// Used for Gitea as well
+    pub github: Option<StepTemplate>,
+    pub gitlab: Option<StepTemplate>,
+    pub circleci: Option<StepTemplate>,
+    pub jenkins: Option<StepTemplate>,
+}
+
+/// How a step reads on one platform
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StepTemplate {
+    pub run: Option<String>,
+    /// GitHub and Gitea only: an action, e.g. `actions/setup-node@v4`
+    pub uses: Option<String>,
+    /// The action's inputs
+    #[serde(default)]
+    pub with: BTreeMap<String, String>,
+}
+
+impl CustomPresetDef {
+    /// Every option the preset's features declare
+    pub fn options(&self) -> impl Iterator<Item = &OptionDef> {
+        self.features.iter().flat_map(|feature| &feature.options)
+    }
+
+    fn option(&self, id: &str) -> Option<&OptionDef> {
+        self.options()
+            .chain(&self.inherited)
+            .find(|option| option.id == id)
+    }
+
+    /// The value `option` starts with
+    pub fn default_value(option: &OptionDef) -> OptionValue {
+        match &option.default {
+            DefaultValue::Bool(enabled) => OptionValue::Bool(*enabled),
+            DefaultValue::String(value) if option.variants.is_empty() => {
+                OptionValue::String(value.clone())
+            }
+            DefaultValue::String(value) => OptionValue::Enum {
+                selected: value.clone(),
+                variants: option.variants.clone(),
+            },
+        }
+    }
+
+    /// Why the definition can't be used, if it can't
+    pub fn check(&self) -> Result<(), String> {
+        if self.id.is_empty()
+            || !self
+                .id
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        {
+            return Err(format!(
+                "id `{}` must be lowercase letters, digits and dashes",
+                self.id
+            ));
+        }
+        for project_type in &self.matches {
+            if !ProjectType::all()
+                .iter()
+                .any(|known| format!("{:?}", known) == *project_type)
+            {
+                return Err(format!("matches names no project type `{}`", project_type));
+            }
+        }
+
+        if self.addon && self.extends.is_some() {
+            return Err("extends a preset, so it can't be an add-on".to_string());
+        }
+
+        let mut ids = HashSet::new();
+        for option in self.options() {
+            if !ids.insert(option.id.as_str()) || TRIGGER_OPTIONS.contains(&option.id.as_str()) {
+                return Err(format!("option `{}` is declared twice", option.id));
+            }
+            if self.inherited.iter().any(|known| known.id == option.id) {
+                return Err(format!(
+                    "option `{}` is already one of `{}`'s",
+                    option.id,
+                    self.extends.as_deref().unwrap_or_default()
+                ));
+            }
+            match &option.default {
+                DefaultValue::Bool(_) if !option.variants.is_empty() => {
+                    return Err(format!(
+                        "option `{}` has variants, so its default must be one of them",
+                        option.id
+                    ))
+                }
+                DefaultValue::String(value)
+                    if !option.variants.is_empty() && !option.variants.contains(value) =>
+                {
+                    return Err(format!(
+                        "option `{}` defaults to `{}`, which isn't one of its variants",
+                        option.id, value
+                    ))
+                }
+                _ => {}
+            }
+            if let Some(pattern) = &option.pattern {
+                if !matches!(option.default, DefaultValue::String(_)) || !option.variants.is_empty()
+                {
+                    return Err(format!(
+                        "option `{}` has a pattern, so must be a string",
+                        option.id
+                    ));
+                }
+                regex::Regex::new(pattern)
+                    .map_err(|e| format!("option `{}` has an invalid pattern: {}", option.id, e))?;
+            }
+            if option.placeholder.is_some()
+                && (!matches!(option.default, DefaultValue::String(_))
+                    || !option.variants.is_empty())
+            {
+                return Err(format!(
+                    "option `{}` has a placeholder, so must be a string",
+                    option.id
+                ));
+            }
+            if option.exclusive.is_some() && !matches!(option.default, DefaultValue::Bool(_)) {
+                return Err(format!(
+                    "option `{}` is exclusive, so must be a bool",
+                    option.id
+                ));
+            }
+            if let Some(parent) = &option.depends_on {
+                self.check_toggle(parent)
+                    .map_err(|e| format!("option `{}` depends_on {}", option.id, e))?;
+            }
+            if let Some(required) = &option.requires {
+                if option.depends_on.is_some() {
+                    return Err(format!(
+                        "option `{}` has both depends_on and requires",
+                        option.id
+                    ));
+                }
+                self.check_toggle(required)
+                    .map_err(|e| format!("option `{}` requires {}", option.id, e))?;
+            }
+            if (option.requires.is_some() || !option.conflicts.is_empty()) && !option.is_toggle() {
+                return Err(format!(
+                    "option `{}` has rules for when it's on, so must be a bool or have variants",
+                    option.id
+                ));
+            }
+            for other in &option.conflicts {
+                if !self
+                    .option(other)
+                    .is_some_and(|other| other.is_toggle() && other.id != option.id)
+                {
+                    return Err(format!(
+                        "option `{}` conflicts with `{}`, which isn't another bool option or one with variants",
+                        option.id, other
+                    ));
+                }
+            }
+        }
+
+        if self.jobs.is_empty() {
+            return Err("declares no jobs".to_string());
+        }
+        let mut job_ids = HashSet::new();
+        for job in &self.jobs {
+            if !job_ids.insert(job.id.as_str()) {
+                return Err(format!("job `{}` is declared twice", job.id));
+            }
+        }
+        for job in &self.jobs {
+            let context = |e: String| format!("job `{}`: {}", job.id, e);
+            if let Some(need) = job
+                .needs
+                .iter()
+                .find(|need| !job_ids.contains(need.as_str()))
+            {
+                return Err(context(format!("needs names no job `{}`", need)));
+            }
+            if let Some(when) = &job.when {
+                self.check_condition(when).map_err(context)?;
+            }
+            if let Some(image) = &job.image {
+                self.check_template(image).map_err(context)?;
+            }
+            for step in &job.steps {
+                self.check_step(step).map_err(context)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn check_step(&self, step: &StepDef) -> Result<(), String> {
+        let name = step.name.as_deref().unwrap_or("unnamed");
+        if let Some(when) = &step.when {
+            self.check_condition(when)?;
+        }
+        let templates = [&step.gitlab, &step.circleci, &step.jenkins];
+        if templates
+            .iter()
+            .any(|template| template.as_ref().is_some_and(|t| t.uses.is_some()))
+        {
+            return Err(format!("step `{}` uses an action outside GitHub", name));
+        }
+        let mut runs_anywhere = step.run.is_some();
+        for template in templates.into_iter().chain([&step.github]).flatten() {
+            if template.run.is_some() == template.uses.is_some() {
+                return Err(format!("step `{}` needs one of run or uses", name));
+            }
+            runs_anywhere = true;
+            for text in template.run.iter().chain(template.with.values()) {
+                self.check_template(text)?;
+            }
+        }
+        if !runs_anywhere {
+            return Err(format!("step `{}` runs nothing", name));
+        }
+        step.run
+            .as_ref()
+            .map_or(Ok(()), |run| self.check_template(run))
+    }
+
+    /// `when` names a bool option, or compares an enum option to a variant
+    fn check_condition(&self, when: &str) -> Result<(), String> {
+        match when.split_once("==") {
+            None => self.check_toggle(when.trim()),
+            Some((id, variant)) => {
+                let (id, variant) = (id.trim(), variant.trim());
+                match self.option(id) {
+                    Some(option) if option.variants.iter().any(|known| known == variant) => Ok(()),
+                    Some(_) => Err(format!("`{}` has no variant `{}`", id, variant)),
+                    None => Err(format!("`{}` names no option", id)),
+                }
+            }
+        }
+    }
+
+    fn check_toggle(&self, id: &str) -> Result<(), String> {
+        match self.option(id).map(|option| &option.default) {
+            Some(DefaultValue::Bool(_)) => Ok(()),
+            Some(_) => Err(format!("`{}` isn't a bool option", id)),
+            None => Err(format!("`{}` names no option", id)),
+        }
+    }
+
+    /// Every `{{ option }}` names an option
+    fn check_template(&self, text: &str) -> Result<(), String> {
+        for id in placeholders(text) {
+            if self.option(id).is_none() && !TRIGGER_OPTIONS.contains(&id) {
+                return Err(format!("`{{{{ {} }}}}` names no option", id));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl OptionDef {
+    /// Whether the option is on or off, as a bool or a choice that can be "none" is
+    fn is_toggle(&self) -> bool {
+        matches!(self.default, DefaultValue::Bool(_)) || !self.variants.is_empty()
+    }
+
+    /// The rules the option's value keeps to
+    pub fn validation(&self) -> Validation {
+        Validation {
+            pattern: self.pattern.clone(),
+            expected: self.expected.clone(),
+            exclusive: self.exclusive.clone(),
+            requires: self.requires.clone(),
+            conflicts: self.conflicts.clone(),
+            ..Validation::default()
+        }
+    }
+}
+
+/// An option's value as cci.ron stores it
+#[derive(Debug, Clone, PartialEq, serde::Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CustomValue {
+    Bool(bool),
+    String(String),
+}
+
+impl CustomValue {
+    pub fn from_option_value(value: &OptionValue) -> Self {
+        match value {
+            OptionValue::Bool(enabled) => CustomValue::Bool(*enabled),
+            OptionValue::Enum { selected, .. } => CustomValue::String(selected.clone()),
+            OptionValue::String(value) => CustomValue::String(value.clone()),
+            OptionValue::Int(value) => CustomValue::String(value.to_string()),
+        }
+    }
+
+    pub(super) fn to_option_value(&self) -> OptionValue {
+        match self {
+            CustomValue::Bool(enabled) => OptionValue::Bool(*enabled),
+            CustomValue::String(value) => OptionValue::String(value.clone()),
+        }
+    }
+}
+
+/// The option IDs of a template's `{{ option }}` placeholders
+fn placeholders(text: &str) -> Vec<&str> {
+    let mut ids = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        ids.push(rest[start + 2..start + end].trim());
+        rest = &rest[start + end + 2..];
+    }
+    ids
+}
+
+/// `text` with each `{{ option }}` replaced by the option's value in `config`
+pub(super) fn fill(text: &str, config: &PresetConfig) -> String {
+    let mut filled = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        filled.push_str(&rest[..start]);
+        let id = rest[start + 2..start + end].trim();
+        filled.push_str(&match config.get(id) {
+            Some(OptionValue::Bool(enabled)) => enabled.to_string(),
+            Some(OptionValue::Enum { selected, .. }) => selected.clone(),
+            Some(OptionValue::String(value)) => value.clone(),
+            Some(OptionValue::Int(value)) => value.to_string(),
+            None => String::new(),
+        });
+        rest = &rest[start + end + 2..];
+    }
+    filled.push_str(rest);
+    filled
+}
+
+/// Whether a `when` condition holds in `config`; no condition always does
+pub(super) fn holds(when: Option<&str>, config: &PresetConfig) -> bool {
+    match when.map(|when| when.split_once("==")) {
+        None => true,
+        Some(None) => config.get_bool(when.unwrap_or_default().trim()),
+        Some(Some((id, variant))) => config.get_enum(id.trim()).as_deref() == Some(variant.trim()),
+    }
+}
@@ -0,0 +1,16 @@
+use crate::error::Result;
+use crate::platforms::gitea::helpers::substitute_actions;
+use crate::platforms::gitea::models::GiteaWorkflow;
+use crate::traits::{ToGitHub, ToGitea};
+
+use super::CustomPipeline;
+
+impl ToGitea for CustomPipeline<'_> {
+    fn to_gitea(&self) -> Result<GiteaWorkflow> {
+        // Gitea Actions uses the same workflow format as GitHub Actions, but
+        // can't use every action
+        let mut workflow = self.to_github()?;
+        substitute_actions(&mut workflow);
+        Ok(workflow)
+    }
+}
@@ -0,0 +1,14 @@
+use crate::editor::state::Platform;
+use crate::error::Result;
+use crate::models::adapters::{GitHubAdapter, PlatformAdapter};
+use crate::platforms::github::models::GitHubWorkflow;
+use crate::traits::{ToGitHub, ToPipeline};
+
+use super::CustomPipeline;
+
+impl ToGitHub for CustomPipeline<'_> {
+    fn to_github(&self) -> Result<GitHubWorkflow> {
+        let pipeline = self.to_pipeline(Platform::GitHub)?;
+        Ok(GitHubAdapter::lower(&pipeline, &mut Vec::new()))
+    }
+}
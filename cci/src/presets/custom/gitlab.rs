@@ -0,0 +1,14 @@
+use crate::editor::state::Platform;
+use crate::error::Result;
+use crate::models::adapters::{GitLabAdapter, PlatformAdapter};
+use crate::platforms::gitlab::models::GitLabCI;
+use crate::traits::{ToGitLab, ToPipeline};
+
+use super::CustomPipeline;
+
+impl ToGitLab for CustomPipeline<'_> {
+    fn to_gitlab(&self) -> Result<GitLabCI> {
+        let pipeline = self.to_pipeline(Platform::GitLab)?;
+        Ok(GitLabAdapter::lower(&pipeline, &mut Vec::new()))
+    }
+}
@@ -0,0 +1,14 @@
+use crate::editor::state::Platform;
+use crate::error::Result;
+use crate::models::adapters::{JenkinsAdapter, PlatformAdapter};
+use crate::platforms::jenkins::models::JenkinsConfig;
+use crate::traits::{ToJenkins, ToPipeline};
+
+use super::CustomPipeline;
+
+impl ToJenkins for CustomPipeline<'_> {
+    fn to_jenkins(&self) -> Result<JenkinsConfig> {
+        let pipeline = self.to_pipeline(Platform::Jenkins)?;
+        Ok(JenkinsAdapter::lower(&pipeline, &mut Vec::new()))
+    }
+}
@@ -0,0 +1,1132 @@
+//! Presets declared in files instead of code
+//!
+//! Each `*.toml` or `*.ron` file in `~/.config/cci/presets/` (or
+//! `$XDG_CONFIG_HOME/cci/presets/`) declares one preset, registered next to
+//! the built-in ones, so a team can share its own pipeline without forking:
+//!
+//! ```toml
+//! id = "acme-node"
+//! name = "Acme Node"
+//! description = "Node services built the Acme way"
+//! matches = ["NodeApp"]
+//!
+//! [[features]]
+//! id = "testing"
+//! name = "Testing"
+//!
+//! [[features.options]]
+//! id = "node_version"
+//! name = "Node Version"
+//! default = "20"
+//!
+//! [[features.options]]
+//! id = "e2e"
+//! name = "End-to-end Tests"
+//! default = false
+//!
+//! [[jobs]]
+//! id = "test"
+//! image = "node:{{ node_version }}"
+//!
+//! [[jobs.steps]]
+//! name = "Setup Node"
+//! github = { uses = "actions/setup-node@v4", with = { node-version = "{{ node_version }}" } }
+//!
+//! [[jobs.steps]]
+//! name = "Test"
+//! run = "npm ci && npm test"
+//!
+//! [[jobs.steps]]
+//! name = "End-to-end tests"
+//! run = "npm run e2e"
+//! when = "e2e"
+//! ```
+//!
+//! `run` is the step's command on every platform; a `github`, `gitlab`,
+//! `circleci` or `jenkins` table replaces it on that platform, and on GitHub
+//! and Gitea can use an action instead. `{{ option }}` is replaced by the
+//! option's value.
+//!
+//! With `extends = "rust"` a preset starts from a built-in one instead: it
+//! has that preset's options, which its own `when` and `{{ option }}` can
+//! use, and its jobs are added to the pipeline that preset generates.
+
+use crate::detection::{read_workflow_settings, DetectionResult, ProjectType};
+use crate::editor::config::{
+    EditorPreset, FeatureMeta, OptionMeta, OptionValue, PresetConfig, Validation, VariantMeta,
+    WORKFLOW_SETTING_FEATURES,
+};
+use crate::editor::settings::config_dir;
+use crate::editor::state::Platform;
+use crate::error::{config_error, Result};
+use crate::generator::combine::combine;
+use crate::platforms::checks::LocalCheck;
+use crate::platforms::github::models::{job_id, GitHubWorkflow};
+use crate::platforms::helpers::{build_for_platform, PlatformConfig};
+use crate::platforms::secrets::Secret;
+use crate::platforms::triggers::TriggerConfig;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+mod circleci;
+mod definition;
+mod gitea;
+mod github;
+mod gitlab;
+mod jenkins;
+mod pipeline;
+
+use definition::{fill, holds, StepDef, StepTemplate};
+pub use definition::{CustomPresetDef, CustomValue, DefaultValue, FeatureDef, JobDef, OptionDef};
+
+/// A custom preset's options as cci.ron stores them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomConfig {
+    pub preset: String,
+    #[serde(default)]
+    pub options: BTreeMap<String, CustomValue>,
+}
+
+impl CustomConfig {
+    pub fn from_preset_config(preset_id: &str, config: &PresetConfig) -> Self {
+        Self {
+            preset: preset_id.to_string(),
+            options: config
+                .values
+                .iter()
+                .map(|(id, value)| (id.clone(), CustomValue::from_option_value(value)))
+                .collect(),
+        }
+    }
+
+    /// The editor's config, typed by the preset's definition when it's
+    /// installed; without it every value reads as a bool or a string
+    pub fn to_preset_config(&self) -> PresetConfig {
+        if let Some(preset) = installed().iter().find(|p| p.def.id == self.preset) {
+            return preset.config_from_values(&self.options);
+        }
+        let mut config = PresetConfig::new(self.preset.clone());
+        for (id, value) in &self.options {
+            config.set(id.clone(), value.to_option_value());
+        }
+        config
+    }
+}
+
+/// A preset read from a definition file
+pub struct CustomPreset {
+    pub def: CustomPresetDef,
+    pub path: PathBuf,
+    /// The built-in preset it extends
+    base: Option<Arc<dyn EditorPreset>>,
+    // `EditorPreset` hands out 'static strings; custom presets are read once
+    // and kept for the rest of the run, so these are leaked on load
+    id: &'static str,
+    name: &'static str,
+    description: &'static str,
+}
+
+impl CustomPreset {
+    /// Read and check the definition at `path`, TOML or RON by its extension
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut def: CustomPresetDef = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("ron") => ron::from_str(&text).map_err(|e| config_error(e.to_string())),
+            _ => toml::from_str(&text).map_err(|e| config_error(e.to_string())),
+        }
+        .with_context(|| format!("Invalid preset {}", path.display()))?;
+        let base = match &def.extends {
+            Some(base_id) => {
+                let base = crate::editor::registry::builtin_registry()
+                    .get(base_id)
+                    .filter(|base| !base.is_decorator())
+                    .cloned()
+                    .ok_or_else(|| config_error(format!("extends names no preset `{}`", base_id)))
+                    .with_context(|| format!("Invalid preset {}", path.display()))?;
+                def.inherited = base
+                    .default_config(true)
+                    .values
+                    .iter()
+                    .map(|(id, value)| inherited_option(id, value))
+                    .collect();
+                Some(base)
+            }
+            None => None,
+        };
+        def.check()
+            .map_err(config_error)
+            .with_context(|| format!("Invalid preset {}", path.display()))?;
+        Ok(Self {
+            id: Box::leak(def.id.clone().into_boxed_str()),
+            name: Box::leak(def.name.clone().into_boxed_str()),
+            description: Box::leak(def.description.clone().into_boxed_str()),
+            path: path.to_path_buf(),
+            base,
+            def,
+        })
+    }
+
+    /// A config from cci.ron's values, typed by the options; options without
+    /// a value keep their defaults, and values of unknown options are dropped
+    pub fn config_from_values(&self, values: &BTreeMap<String, CustomValue>) -> PresetConfig {
+        let mut config = self.default_config(true);
+        for (id, value) in values {
+            let value = match (config.get(id), value) {
+                (Some(OptionValue::Enum { variants, .. }), CustomValue::String(selected)) => {
+                    OptionValue::Enum {
+                        selected: selected.clone(),
+                        variants: variants.clone(),
+                    }
+                }
+                (Some(_), value) => value.to_option_value(),
+                (None, _) => continue,
+            };
+            config.set(id.clone(), value);
+        }
+        config
+    }
+
+    fn pipeline<'a>(&'a self, config: &'a PresetConfig) -> CustomPipeline<'a> {
+        CustomPipeline {
+            def: &self.def,
+            config,
+        }
+    }
+
+    /// `config` as the preset it extends reads it
+    fn base_config(base: &dyn EditorPreset, config: &PresetConfig) -> PresetConfig {
+        let mut config = config.clone();
+        config.preset_id = base.preset_id().to_string();
+        config
+    }
+
+    /// `config` as this preset's, with its own options at their defaults
+    fn with_own_options(&self, mut config: PresetConfig, detected: bool) -> PresetConfig {
+        config.preset_id = self.def.id.clone();
+        for feature in &self.def.features {
+            for option in &feature.options {
+                let value = match CustomPresetDef::default_value(option) {
+                    OptionValue::Bool(_)
+                        if !detected
+                            && !WORKFLOW_SETTING_FEATURES.contains(&feature.id.as_str()) =>
+                    {
+                        OptionValue::Bool(false)
+                    }
+                    value => value,
+                };
+                config.set(option.id.clone(), value);
+            }
+        }
+        config
+    }
+}
+
+/// A built-in preset's option, hidden ones included, as one a definition
+/// can refer to
+fn inherited_option(id: &str, value: &OptionValue) -> OptionDef {
+    let (default, variants) = match value {
+        OptionValue::Bool(enabled) => (DefaultValue::Bool(*enabled), Vec::new()),
+        OptionValue::Enum { selected, variants } => {
+            (DefaultValue::String(selected.clone()), variants.clone())
+        }
+        OptionValue::String(value) => (DefaultValue::String(value.clone()), Vec::new()),
+        OptionValue::Int(value) => (DefaultValue::String(value.to_string()), Vec::new()),
+    };
+    OptionDef {
+        id: id.to_string(),
+        name: None,
+        description: String::new(),
+        default,
+        variants,
+        depends_on: None,
+        pattern: None,
+        expected: None,
+        exclusive: None,
+        placeholder: None,
+        requires: None,
+        conflicts: Vec::new(),
+    }
+}
+
+/// `own` added to the pipeline of the preset it extends, triggering and
+/// running where that pipeline does
+fn layer(
+    base_id: &str,
+    base: PlatformConfig,
+    own_id: &str,
+    mut own: PlatformConfig,
+) -> Result<PlatformConfig> {
+    match (&base, &mut own) {
+        (PlatformConfig::GitHub(base), PlatformConfig::GitHub(own))
+        | (PlatformConfig::Gitea(base), PlatformConfig::Gitea(own)) => {
+            own.on = base.on.clone();
+            own.defaults = base.defaults.clone();
+        }
+        (PlatformConfig::Jenkins(base), PlatformConfig::Jenkins(own)) => {
+            own.agent = base.agent.clone();
+        }
+        _ => {}
+    }
+    let mut groups = combine(vec![(base_id.to_string(), base), (own_id.to_string(), own)]);
+    if groups.len() != 1 {
+        return Err(config_error(format!(
+            "`{}`'s jobs can't be added to `{}`'s pipeline",
+            own_id, base_id
+        )));
+    }
+    Ok(groups.remove(0).1)
+}
+
+/// Where custom presets are kept, if there's a home directory to keep them in
+pub fn presets_dir() -> Option<PathBuf> {
+    Some(config_dir()?.join("presets"))
+}
+
+/// Every definition in `dir`, sorted by file name, each read or why it couldn't be
+pub fn load_dir(dir: &Path) -> Vec<(PathBuf, Result<CustomPreset>)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            matches!(
+                path.extension().and_then(|ext| ext.to_str()),
+                Some("toml" | "ron")
+            )
+        })
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| {
+            let preset = CustomPreset::load(&path);
+            (path, preset)
+        })
+        .collect()
+}
+
+/// The custom presets in [`presets_dir`], read on first use
+///
+/// Definitions that don't parse, or whose ID is already taken, are skipped
+/// with a warning so one broken file doesn't stop cci.
+pub fn installed() -> &'static [Arc<CustomPreset>] {
+    static INSTALLED: OnceLock<Vec<Arc<CustomPreset>>> = OnceLock::new();
+    INSTALLED.get_or_init(|| {
+        let Some(dir) = presets_dir() else {
+            return Vec::new();
+        };
+        let builtin = crate::editor::registry::builtin_registry();
+        let mut installed: Vec<Arc<CustomPreset>> = Vec::new();
+        for (path, preset) in load_dir(&dir) {
+            match preset {
+                Ok(preset)
+                    if builtin.get(preset.id).is_some()
+                        || installed.iter().any(|known| known.id == preset.id) =>
+                {
+                    eprintln!(
+                        "Warning: skipping {}: preset `{}` already exists",
+                        path.display(),
+                        preset.id
+                    );
+                }
+                Ok(preset) => installed.push(Arc::new(preset)),
+                Err(e) => eprintln!("Warning: skipping {:#}", e),
+            }
+        }
+        installed
+    })
+}
+
+/// A custom preset's definition with the values it's generated with
+pub(super) struct CustomPipeline<'a> {
+    def: &'a CustomPresetDef,
+    config: &'a PresetConfig,
+}
+
+impl CustomPipeline<'_> {
+    /// The jobs whose condition holds, with their plain and `preset/job` IDs
+    fn jobs(&self) -> Vec<(&JobDef, String)> {
+        self.def
+            .jobs
+            .iter()
+            .filter(|job| holds(job.when.as_deref(), self.config))
+            .map(|job| (job, self.job_key(&job.id)))
+            .collect()
+    }
+
+    fn job_key(&self, id: &str) -> String {
+        format!("{}/{}", self.def.id, id)
+    }
+
+    /// The keys of the jobs `job` needs that are generated
+    fn needs(&self, job: &JobDef) -> Vec<String> {
+        job.needs
+            .iter()
+            .filter(|need| {
+                self.def
+                    .jobs
+                    .iter()
+                    .any(|other| other.id == **need && holds(other.when.as_deref(), self.config))
+            })
+            .map(|need| self.job_key(need))
+            .collect()
+    }
+
+    fn job_name(&self, job: &JobDef) -> String {
+        job.name.clone().unwrap_or_else(|| job.id.clone())
+    }
+
+    fn image(&self, job: &JobDef) -> Option<String> {
+        job.image.as_ref().map(|image| self.fill(image))
+    }
+
+    /// The steps whose condition holds, as they read on `platform`; steps
+    /// without a command there are left out
+    fn steps(&self, job: &JobDef, platform: Platform) -> Vec<(String, StepTemplate)> {
+        job.steps
+            .iter()
+            .filter(|step| holds(step.when.as_deref(), self.config))
+            .filter_map(|step| {
+                let template = self.template(step, platform)?;
+                let name = step
+                    .name
+                    .clone()
+                    .or_else(|| template.run.clone())
+                    .or_else(|| template.uses.clone())
+                    .unwrap_or_default();
+                Some((
+                    name,
+                    StepTemplate {
+                        run: template.run.as_deref().map(|run| self.fill(run)),
+                        uses: template.uses.clone(),
+                        with: template
+                            .with
+                            .iter()
+                            .map(|(key, value)| (key.clone(), self.fill(value)))
+                            .collect(),
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    fn template(&self, step: &StepDef, platform: Platform) -> Option<StepTemplate> {
+        let own = match platform {
+            Platform::GitHub | Platform::Gitea => &step.github,
+            Platform::GitLab => &step.gitlab,
+            Platform::CircleCI => &step.circleci,
+            Platform::Jenkins => &step.jenkins,
+        };
+        own.clone().or_else(|| {
+            Some(StepTemplate {
+                run: Some(step.run.clone()?),
+                uses: None,
+                with: BTreeMap::new(),
+            })
+        })
+    }
+
+    fn fill(&self, text: &str) -> String {
+        fill(text, self.config)
+    }
+
+    fn triggers(&self) -> TriggerConfig {
+        let string = |id: &str| self.config.get_string(id).unwrap_or_default();
+        TriggerConfig::from_options(
+            &string("workflow_name"),
+            &string("push_branches"),
+            &string("pr_branches"),
+            &string("tags"),
+            &string("schedule_cron"),
+            self.config.get_bool("manual_dispatch"),
+        )
+    }
+}
+
+impl EditorPreset for CustomPreset {
+    fn preset_id(&self) -> &'static str {
+        self.id
+    }
+
+    fn preset_name(&self) -> &'static str {
+        self.name
+    }
+
+    fn preset_description(&self) -> &'static str {
+        self.description
+    }
+
+    fn features(&self) -> Vec<FeatureMeta> {
+        let mut features: Vec<FeatureMeta> = self
+            .def
+            .features
+            .iter()
+            .map(|feature| FeatureMeta {
+                id: feature.id.clone(),
+                display_name: feature.name.clone().unwrap_or_else(|| feature.id.clone()),
+                description: String::new(),
+                options: feature
+                    .options
+                    .iter()
+                    .map(|option| OptionMeta {
+                        id: option.id.clone(),
+                        display_name: option.name.clone().unwrap_or_else(|| option.id.clone()),
+                        description: option.description.clone(),
+                        default_value: CustomPresetDef::default_value(option),
+                        variants: option
+                            .variants
+                            .iter()
+                            .map(|variant| VariantMeta {
+                                id: variant.clone(),
+                                display_name: variant.clone(),
+                                description: String::new(),
+                            })
+                            .collect(),
+                        depends_on: option.depends_on.clone().or(option.requires.clone()),
+                        validation: option.validation(),
+                        placeholder: option.placeholder.clone(),
+                    })
+                    .collect(),
+            })
+            .collect();
+        if let Some(base) = &self.base {
+            let mut inherited = base.features();
+            inherited.append(&mut features);
+            return inherited;
+        }
+        features.push(FeatureMeta {
+            id: "triggers".to_string(),
+            display_name: "Triggers".to_string(),
+            description: String::new(),
+            options: trigger_options(&self.def)
+                .into_iter()
+                .map(
+                    |(id, display_name, description, default_value)| OptionMeta {
+                        id: id.to_string(),
+                        display_name: display_name.to_string(),
+                        description: description.to_string(),
+                        default_value,
+                        variants: Vec::new(),
+                        depends_on: None,
+                        validation: match id {
+                            "schedule_cron" => Validation::cron(),
+                            _ => Validation::default(),
+                        },
+                        placeholder: None,
+                    },
+                )
+                .collect(),
+        });
+        features
+    }
+
+    fn config_schema(&self) -> serde_json::Value {
+        let options: serde_json::Map<String, serde_json::Value> = self
+            .features()
+            .iter()
+            .flat_map(|feature| &feature.options)
+            .map(|option| (option.id.clone(), option_schema(option)))
+            .collect();
+        serde_json::json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "title": self.name,
+            "description": self.description,
+            "type": "object",
+            "properties": {
+                "preset": { "const": self.id },
+                "options": {
+                    "type": "object",
+                    "properties": options,
+                    "additionalProperties": false,
+                },
+            },
+            "required": ["preset"],
+        })
+    }
+
+    fn generate(
+        &self,
+        config: &PresetConfig,
+        platform: Platform,
+        language_version: &str,
+    ) -> Result<String> {
+        self.build(config, platform, language_version)?.render()
+    }
+
+    fn build(
+        &self,
+        config: &PresetConfig,
+        platform: Platform,
+        language_version: &str,
+    ) -> Result<PlatformConfig> {
+        let own = build_for_platform(&self.pipeline(config), platform)?;
+        let Some(base) = &self.base else {
+            return Ok(own);
+        };
+        let pipeline = base.build(
+            &Self::base_config(base.as_ref(), config),
+            platform,
+            language_version,
+        )?;
+        layer(base.preset_id(), pipeline, self.id, own)
+    }
+
+    fn decorate(
+        &self,
+        _config: &PresetConfig,
+        _language_version: &str,
+        _pipeline: &mut PlatformConfig,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn required_secrets(
+        &self,
+        config: &PresetConfig,
+        platform: Platform,
+        language_version: &str,
+    ) -> Vec<Secret> {
+        let mut secrets = match &self.base {
+            Some(base) => base.required_secrets(
+                &Self::base_config(base.as_ref(), config),
+                platform,
+                language_version,
+            ),
+            None => Vec::new(),
+        };
+        secrets.extend(
+            self.def
+                .secrets
+                .iter()
+                .map(|name| Secret::named(name.clone(), "Used by a custom preset's steps")),
+        );
+        secrets
+    }
+
+    fn support_files(
+        &self,
+        config: &PresetConfig,
+        platform: Platform,
+        language_version: &str,
+    ) -> Result<Vec<(PathBuf, String)>> {
+        match &self.base {
+            Some(base) => base.support_files(
+                &Self::base_config(base.as_ref(), config),
+                platform,
+                language_version,
+            ),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn local_checks(&self, config: &PresetConfig, language_version: &str) -> Vec<LocalCheck> {
+        match &self.base {
+            Some(base) => {
+                base.local_checks(&Self::base_config(base.as_ref(), config), language_version)
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Every job generated without a condition is in the workflow, as is
+    /// the preset it extends
+    fn matches_github(&self, workflow: &GitHubWorkflow) -> bool {
+        self.base
+            .as_ref()
+            .is_none_or(|base| base.matches_github(workflow))
+            && self
+                .def
+                .jobs
+                .iter()
+                .filter(|job| job.when.is_none())
+                .all(|job| {
+                    let key = job_id(&format!("{}/{}", self.def.id, job.id));
+                    workflow.jobs.keys().any(|id| job_id(id) == key)
+                })
+    }
+
+    fn extract_config(&self, workflow: &GitHubWorkflow) -> PresetConfig {
+        if let Some(base) = &self.base {
+            return self.with_own_options(base.extract_config(workflow), true);
+        }
+        let mut config = self.default_config(true);
+        read_workflow_settings(workflow, &mut config);
+        config
+    }
+
+    /// The project types it names, or the preset it extends' without any
+    fn matches_project(&self, project_type: &ProjectType, working_dir: &Path) -> bool {
+        match &self.base {
+            Some(base) if self.def.matches.is_empty() => {
+                base.matches_project(project_type, working_dir)
+            }
+            _ => self
+                .def
+                .matches
+                .iter()
+                .any(|name| *name == format!("{:?}", project_type)),
+        }
+    }
+
+    fn is_addon(&self) -> bool {
+        match &self.base {
+            Some(base) => base.is_addon(),
+            None => self.def.addon,
+        }
+    }
+
+    fn is_decorator(&self) -> bool {
+        false
+    }
+
+    /// Like the built-in presets, an undetected preset starts with its
+    /// toggles off, besides workflow settings
+    fn default_config(&self, detected: bool) -> PresetConfig {
+        if let Some(base) = &self.base {
+            return self.with_own_options(base.default_config(detected), detected);
+        }
+        let mut config = PresetConfig::new(self.def.id.clone());
+        for (id, _, _, value) in trigger_options(&self.def) {
+            config.set(id.to_string(), value);
+        }
+        self.with_own_options(config, detected)
+    }
+
+    fn matches_detection(&self, detection: &DetectionResult, working_dir: &Path) -> bool {
+        match &self.base {
+            Some(base) if self.def.matches.is_empty() => {
+                base.matches_detection(detection, working_dir)
+            }
+            _ => self.matches_project(&detection.project_type, working_dir),
+        }
+    }
+
+    fn detected_config(&self, detection: &DetectionResult) -> PresetConfig {
+        match &self.base {
+            Some(base) => self.with_own_options(base.detected_config(detection), true),
+            None => self.default_config(true),
+        }
+    }
+}
+
+/// The schema of an option's value as `CustomValue` holds it, with integers
+/// written as strings
+fn option_schema(option: &OptionMeta) -> serde_json::Value {
+    let mut schema = match &option.default_value {
+        OptionValue::Bool(_) => serde_json::json!({ "type": "boolean" }),
+        OptionValue::Enum { variants, .. } => serde_json::json!({ "enum": variants }),
+        OptionValue::String(_) => match &option.validation.pattern {
+            Some(pattern) => serde_json::json!({ "type": "string", "pattern": pattern }),
+            None => serde_json::json!({ "type": "string" }),
+        },
+        OptionValue::Int(_) => serde_json::json!({ "type": "string", "pattern": "^-?[0-9]+$" }),
+    };
+    schema["title"] = serde_json::json!(option.display_name);
+    if !option.description.is_empty() {
+        schema["description"] = serde_json::json!(option.description);
+    }
+    schema["default"] = serde_json::to_value(CustomValue::from_option_value(&option.default_value))
+        .unwrap_or_default();
+    schema
+}
+
+/// The shared trigger options, with their names, descriptions and defaults
+fn trigger_options(
+    def: &CustomPresetDef,
+) -> Vec<(&'static str, &'static str, &'static str, OptionValue)> {
+    let branches = || OptionValue::String("main, master".to_string());
+    vec![
+        (
+            "workflow_name",
+            "Workflow Name",
+            "Name shown for the generated workflow/pipeline",
+            OptionValue::String(def.name.clone()),
+        ),
+        (
+            "push_branches",
+            "Push Branches",
+            "Comma-separated branches that trigger a run on push",
+            branches(),
+        ),
+        (
+            "pr_branches",
+            "PR Branches",
+            "Comma-separated target branches that trigger a run on pull requests",
+            branches(),
+        ),
+        (
+            "tags",
+            "Tags",
+            "Comma-separated tag patterns that trigger a run (e.g., v*)",
+            OptionValue::String(String::new()),
+        ),
+        (
+            "schedule_cron",
+            "Schedule (cron)",
+            "Cron expression for scheduled runs (empty to disable)",
+            OptionValue::String(String::new()),
+        ),
+        (
+            "manual_dispatch",
+            "Manual Dispatch",
+            "Allow starting the pipeline manually",
+            OptionValue::Bool(false),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{preset_config_to_choice, to_ron_string, CciConfig, PresetChoice};
+    use crate::editor::config::validate_config;
+    use std::fs;
+
+    const ACME: &str = r#"
+id = "acme-node"
+name = "Acme Node"
+matches = ["NodeApp"]
+secrets = ["NPM_TOKEN"]
+
+[[features]]
+id = "testing"
+name = "Testing"
+
+[[features.options]]
+id = "node_version"
+default = "20"
+
+[[features.options]]
+id = "e2e"
+default = false
+
+[[features.options]]
+id = "package_manager"
+default = "npm"
+variants = ["npm", "pnpm"]
+
+[[jobs]]
+id = "test"
+image = "node:{{ node_version }}"
+
+[[jobs.steps]]
+name = "Setup Node"
+github = { uses = "actions/setup-node@v4", with = { node-version = "{{ node_version }}" } }
+
+[[jobs.steps]]
+run = "npm ci"
+when = "package_manager == npm"
+
+[[jobs.steps]]
+run = "pnpm install"
+when = "package_manager == pnpm"
+
+[[jobs]]
+id = "e2e"
+when = "e2e"
+needs = ["test"]
+steps = [{ run = "npm run e2e", jenkins = { run = "xvfb-run npm run e2e" } }]
+"#;
+
+    fn load(text: &str, extension: &str) -> Result<CustomPreset> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(format!("preset.{}", extension));
+        fs::write(&path, text).unwrap();
+        CustomPreset::load(&path)
+    }
+
+    #[test]
+    fn test_custom_preset_generates_per_platform() {
+        let preset = load(ACME, "toml").unwrap();
+        assert_eq!(preset.preset_id(), "acme-node");
+        assert!(preset.matches_project(&ProjectType::NodeApp, Path::new(".")));
+        assert_eq!(
+            preset
+                .features()
+                .iter()
+                .map(|feature| feature.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["testing", "triggers"]
+        );
+
+        let mut config = preset.default_config(true);
+        let PlatformConfig::GitHub(workflow) =
+            preset.build(&config, Platform::GitHub, "stable").unwrap()
+        else {
+            panic!("expected a GitHub workflow");
+        };
+        assert_eq!(workflow.name, "Acme Node");
+        assert_eq!(
+            workflow.jobs.keys().collect::<Vec<_>>(),
+            vec!["acme-node/test"]
+        );
+        let steps = &workflow.jobs["acme-node/test"].steps;
+        assert_eq!(steps[1].uses.as_deref(), Some("actions/setup-node@v4"));
+        assert_eq!(
+            steps[1].with.as_ref().unwrap()["node-version"],
+            serde_yaml::Value::String("20".to_string())
+        );
+        assert_eq!(steps[2].run.as_deref(), Some("npm ci"));
+        assert!(preset.matches_github(&workflow));
+
+        config.set("e2e".to_string(), OptionValue::Bool(true));
+        config.set(
+            "package_manager".to_string(),
+            OptionValue::Enum {
+                selected: "pnpm".to_string(),
+                variants: vec!["npm".to_string(), "pnpm".to_string()],
+            },
+        );
+        let PlatformConfig::GitLab(ci) = preset.build(&config, Platform::GitLab, "stable").unwrap()
+        else {
+            panic!("expected a GitLab pipeline");
+        };
+        // The GitHub-only setup step is left out
+        assert_eq!(ci.jobs["acme-node/test"].script, vec!["pnpm install"]);
+        assert_eq!(ci.jobs["acme-node/test"].image.as_deref(), Some("node:20"));
+        assert_eq!(
+            ci.jobs["acme-node/e2e"].needs,
+            Some(vec!["acme-node/test".to_string()])
+        );
+
+        let jenkins = preset
+            .generate(&config, Platform::Jenkins, "stable")
+            .unwrap();
+        assert!(jenkins.contains("sh 'xvfb-run npm run e2e'"));
+        assert_eq!(
+            preset.required_secrets(&config, Platform::GitHub, "stable")[0].name,
+            "NPM_TOKEN"
+        );
+    }
+
+    #[test]
+    fn test_custom_pipeline_is_built_once_for_every_platform() {
+        use crate::models::Step;
+        use crate::traits::ToPipeline;
+
+        let preset = load(ACME, "toml").unwrap();
+        let mut config = preset.default_config(true);
+        config.set("e2e".to_string(), OptionValue::Bool(true));
+        let pipeline = preset.pipeline(&config);
+
+        let github = pipeline.to_pipeline(Platform::GitHub).unwrap();
+        let jenkins = pipeline.to_pipeline(Platform::Jenkins).unwrap();
+        let ids = |pipeline: &crate::models::Pipeline| -> Vec<(String, Vec<String>)> {
+            pipeline
+                .jobs
+                .iter()
+                .map(|job| (job.id.clone(), job.needs.clone()))
+                .collect()
+        };
+        assert_eq!(ids(&github), ids(&jenkins));
+        assert!(matches!(github.jobs[0].steps[1], Step::Action { .. }));
+        assert_eq!(
+            jenkins.jobs[1].steps[1].label(),
+            "xvfb-run npm run e2e".to_string()
+        );
+        // Only container platforms run the job in its image
+        assert!(github.jobs[0].image.is_none());
+        assert_eq!(
+            pipeline.to_pipeline(Platform::GitLab).unwrap().jobs[0]
+                .image
+                .as_deref(),
+            Some("node:20")
+        );
+
+        let jenkinsfile = preset
+            .generate(&config, Platform::Jenkins, "stable")
+            .unwrap();
+        assert!(jenkinsfile.contains("stage('test')"));
+        assert!(jenkinsfile.contains("agent any"));
+    }
+
+    #[test]
+    fn test_custom_config_round_trips_through_cci_ron() {
+        let preset = load(ACME, "toml").unwrap();
+        let mut config = preset.default_config(true);
+        config.set("e2e".to_string(), OptionValue::Bool(true));
+
+        let choice = preset_config_to_choice("acme-node", &config);
+        assert!(matches!(choice, PresetChoice::Custom(_)));
+        let text = to_ron_string(&CciConfig::new(vec![choice])).unwrap();
+        let parsed: CciConfig = crate::config::migrate::parse_config(&text).unwrap();
+        let PresetChoice::Custom(custom) = &parsed.presets[0] else {
+            panic!("expected a custom preset");
+        };
+        let restored = preset.config_from_values(&custom.options);
+        assert_eq!(restored.values, config.values);
+    }
+
+    #[test]
+    fn test_custom_preset_definitions_are_checked() {
+        let ron =
+            r#"(id: "tiny", name: "Tiny", jobs: [(id: "build", steps: [(run: Some("make"))])])"#;
+        assert_eq!(load(ron, "ron").unwrap().def.jobs[0].id, "build");
+
+        let invalid = [
+            ACME.replace("id = \"acme-node\"", "id = \"Acme Node\""),
+            ACME.replace("[\"NodeApp\"]", "[\"Cobol\"]"),
+            ACME.replace("when = \"e2e\"", "when = \"e2e_tests\""),
+            ACME.replace("package_manager == pnpm", "package_manager == yarn"),
+            ACME.replace("{{ node_version }}\"\n", "{{ node }}\"\n"),
+            ACME.replace("needs = [\"test\"]", "needs = [\"lint\"]"),
+            ACME.replace("jenkins = { run", "jenkins = { uses"),
+            ACME.replace("default = \"npm\"", "default = \"yarn\""),
+        ];
+        for text in invalid {
+            assert!(load(&text, "toml").is_err(), "accepted:\n{}", text);
+        }
+        assert!(load("id = \"x\"\nname = \"X\"\njobs = []\n", "toml").is_err());
+    }
+
+    const COMPANY_RUST: &str = r#"
+id = "company-rust"
+name = "Company Rust"
+extends = "rust"
+
+[[features]]
+id = "compliance"
+
+[[features.options]]
+id = "license_scan"
+default = true
+
+[[jobs]]
+id = "licenses"
+when = "license_scan"
+steps = [{ run = "cargo deny check licenses" }]
+
+[[jobs]]
+id = "docs"
+when = "runner == ubuntu-latest"
+steps = [{ run = "cargo doc --no-deps --target-dir docs/{{ runner }}" }]
+"#;
+
+    #[test]
+    fn test_custom_preset_extends_builtin() {
+        let preset = load(COMPANY_RUST, "toml").unwrap();
+        assert!(preset.matches_project(&ProjectType::RustLibrary, Path::new(".")));
+        assert!(!preset.is_addon());
+        let features = preset.features();
+        assert!(features.iter().any(|feature| feature.id == "testing"));
+        assert_eq!(features.last().unwrap().id, "compliance");
+
+        let mut config = preset.default_config(true);
+        assert_eq!(config.preset_id, "company-rust");
+        assert!(config.get_bool("enable_coverage") && config.get_bool("license_scan"));
+        let PlatformConfig::GitHub(workflow) =
+            preset.build(&config, Platform::GitHub, "stable").unwrap()
+        else {
+            panic!("expected a GitHub workflow");
+        };
+        assert_eq!(workflow.name, "CI");
+        for job in ["rust/test", "company-rust/licenses", "company-rust/docs"] {
+            assert!(workflow.jobs.contains_key(job), "missing {}", job);
+        }
+        assert_eq!(
+            workflow.jobs["company-rust/docs"].steps[1].run.as_deref(),
+            Some("cargo doc --no-deps --target-dir docs/ubuntu-latest")
+        );
+        assert!(preset.matches_github(&workflow));
+
+        config.set(
+            "runner".to_string(),
+            OptionValue::Enum {
+                selected: "macos".to_string(),
+                variants: Vec::new(),
+            },
+        );
+        let PlatformConfig::GitLab(ci) = preset.build(&config, Platform::GitLab, "stable").unwrap()
+        else {
+            panic!("expected a GitLab pipeline");
+        };
+        assert!(ci.jobs.contains_key("rust/test"));
+        assert!(ci.jobs.contains_key("company-rust/licenses"));
+        assert!(!ci.jobs.contains_key("company-rust/docs"));
+
+        let invalid = [
+            COMPANY_RUST.replace("\"rust\"", "\"cobol\""),
+            COMPANY_RUST.replace("\"rust\"", "\"notifications\""),
+            COMPANY_RUST.replace("\"license_scan\"\ndefault", "\"enable_coverage\"\ndefault"),
+            COMPANY_RUST.replace("extends =", "addon = true\nextends ="),
+        ];
+        for text in invalid {
+            assert!(load(&text, "toml").is_err(), "accepted:\n{}", text);
+        }
+    }
+
+    #[test]
+    fn test_custom_option_rules() {
+        let text = ACME
+            .replace(
+                "id = \"node_version\"\n",
+                "id = \"node_version\"\npattern = '\\d+'\nexpected = \"a major version\"\n\
+                 placeholder = \"20\"\n",
+            )
+            .replace(
+                "id = \"e2e\"\ndefault = false\n",
+                "id = \"e2e\"\ndefault = true\nexclusive = \"tests\"\n\n\
+                 [[features.options]]\nid = \"smoke\"\ndefault = false\nexclusive = \"tests\"\n",
+            );
+        let preset = load(&text, "toml").unwrap();
+        let mut config = preset.default_config(true);
+        assert!(validate_config(&preset, &config).is_empty());
+        assert!(preset
+            .features()
+            .iter()
+            .flat_map(|feature| &feature.options)
+            .any(|option| option.placeholder.as_deref() == Some("20")));
+
+        config.set(
+            "node_version".to_string(),
+            OptionValue::String("latest".to_string()),
+        );
+        config.set("smoke".to_string(), OptionValue::Bool(true));
+        assert_eq!(
+            validate_config(&preset, &config),
+            vec![
+                "node_version (`acme-node.node_version`): `latest` isn't a major version",
+                "e2e (`e2e`), smoke (`smoke`) of `acme-node` can't be on together; \
+                 turn all but one off",
+            ]
+        );
+
+        for invalid in [
+            text.replace("pattern = '\\d+'", "pattern = '('"),
+            text.replace("default = false\nexclusive", "default = \"no\"\nexclusive"),
+            text.replace(
+                "default = false\nexclusive",
+                "default = false\nplaceholder = \"no\"\nexclusive",
+            ),
+            text.replace(
+                "exclusive = \"tests\"\n\n",
+                "conflicts = [\"node_version\"]\n\n",
+            ),
+            text.replace(
+                "exclusive = \"tests\"\n\n",
+                "requires = \"package_manager\"\n\n",
+            ),
+        ] {
+            assert!(load(&invalid, "toml").is_err(), "accepted:\n{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_load_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("acme.toml"), ACME).unwrap();
+        fs::write(dir.path().join("broken.toml"), "id = ").unwrap();
+        fs::write(dir.path().join("notes.md"), "").unwrap();
+
+        let loaded = load_dir(dir.path());
+        assert_eq!(loaded.len(), 2);
+        assert!(loaded[0].1.is_ok());
+        assert!(loaded[1].1.is_err());
+        assert!(load_dir(&dir.path().join("missing")).is_empty());
+    }
+}
@@ -0,0 +1,63 @@
+use crate::editor::state::Platform;
+use crate::error::Result;
+use crate::models::steps;
+use crate::models::{Job, Pipeline, Step};
+use crate::traits::ToPipeline;
+use serde_yaml::Value;
+
+use super::CustomPipeline;
+
+impl ToPipeline for CustomPipeline<'_> {
+    fn to_pipeline(&self, platform: Platform) -> Result<Pipeline> {
+        // Images are for the platforms that run jobs in containers anyway;
+        // GitHub and Gitea jobs keep running on the runner
+        let containers = platform.runs_jobs_in_images();
+        let jobs = self
+            .jobs()
+            .into_iter()
+            .map(|(job, key)| {
+                let mut steps = vec![steps::checkout()];
+                for (name, template) in self.steps(job, platform) {
+                    steps.push(match (template.uses, template.run) {
+                        (Some(uses), _) => Step::Action {
+                            name: Some(name),
+                            uses,
+                            with: template
+                                .with
+                                .into_iter()
+                                .map(|(key, value)| (key, Value::String(value)))
+                                .collect(),
+                        },
+                        (None, Some(command)) => Step::Run {
+                            name: Some(name),
+                            command,
+                        },
+                        (None, None) => continue,
+                    });
+                }
+                Job {
+                    id: key,
+                    name: Some(self.job_name(job)),
+                    stage: Some("test".to_string()),
+                    image: self.image(job).filter(|_| containers),
+                    runner: None,
+                    needs: self.needs(job),
+                    env: Default::default(),
+                    // Left out where jobs can't have a timeout
+                    timeout_minutes: Some(30),
+                    matrix: None,
+                    services: Vec::new(),
+                    cache: None,
+                    artifacts: Vec::new(),
+                    steps,
+                }
+            })
+            .collect();
+
+        Ok(Pipeline {
+            triggers: self.triggers(),
+            env: Default::default(),
+            jobs,
+        })
+    }
+}
@@ -55,6 +55,13 @@ impl Detectable for DockerPreset {
                         crate::platforms::circleci::models::CircleCIRun::Detailed {
                             command,
                             ..
+                        }
+                        | crate::platforms::circleci::models::CircleCIRun::Timed {
+                            command, ..
+                        }
+                        | crate::platforms::circleci::models::CircleCIRun::Conditional {
+                            command,
+                            ..
                         } => command.contains("docker build"),
                     }
                 } else {
@@ -1,11 +1,17 @@
 use crate::error::Result;
+use crate::platforms::gitea::helpers::substitute_actions;
+use crate::platforms::gitea::models::GiteaWorkflow;
 use crate::traits::{ToGitHub, ToGitea};
 
 use super::DockerPreset;
 
 impl ToGitea for DockerPreset {
-    fn to_gitea(&self) -> Result<crate::platforms::gitea::models::GiteaWorkflow> {
-        // Gitea Actions uses the same workflow format as GitHub Actions
-        self.to_github()
+    fn to_gitea(&self) -> Result<GiteaWorkflow> {
+        // Gitea Actions uses the same workflow format as GitHub Actions, but
+        // its runners are labelled differently and can't use every action
+        let mut workflow = self.to_github()?;
+        self.runner.config().apply_gitea(&mut workflow);
+        substitute_actions(&mut workflow);
+        Ok(workflow)
     }
 }
@@ -1,4 +1,16 @@
-use crate::traits::PresetInfo;
+use crate::editor::state::Platform;
+use crate::platforms::checks::{CheckKind, LocalCheck};
+use crate::platforms::secrets::{self, Secret};
+use crate::platforms::triggers::TriggerConfig;
+use crate::presets::job_control::{JobControl, JobControlConfig};
+use crate::presets::multi_arch::{MultiArch, MultiArchConfig};
+use crate::presets::workflow::{
+    GitHubSettings, GitHubSettingsConfig, RunnerSettings, RunnerSettingsConfig, Triggers,
+    TriggersConfig,
+};
+use crate::traits::{
+    DecoratesPipelines, DetectedDefaults, LocalChecks, PresetInfo, RequiresSecrets, SupportFiles,
+};
 use cci_macros::{Preset, PresetEnum};
 
 mod circleci;
@@ -7,6 +19,7 @@ mod gitea;
 mod github;
 mod gitlab;
 mod jenkins;
+mod pipeline;
 
 /// Container registry options for Docker image pushing
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, PresetEnum)]
@@ -53,11 +66,23 @@ pub struct DockerPreset {
     /// Registry to push to
     pub(super) registry: DockerRegistry,
 
-    #[preset_field(hidden = true, default = "\"./Dockerfile\".to_string()")]
+    #[preset_field(
+        feature = "configuration",
+        feature_display = "Configuration",
+        display = "Dockerfile",
+        description = "Path to the Dockerfile, from the repository root",
+        default = "\"./Dockerfile\".to_string()"
+    )]
     /// Dockerfile path (default: "./Dockerfile")
     pub(super) dockerfile_path: String,
 
-    #[preset_field(hidden = true, default = "\".\".to_string()")]
+    #[preset_field(
+        feature = "configuration",
+        feature_display = "Configuration",
+        display = "Build Context",
+        description = "Directory sent to the Docker build",
+        default = "\".\".to_string()"
+    )]
     /// Docker build context (default: ".")
     pub(super) build_context: String,
 
@@ -80,18 +105,78 @@ pub struct DockerPreset {
     )]
     /// Push on tags only (if false, pushes on main/master branch)
     pub(super) push_on_tags_only: bool,
+
+    #[preset_field(flatten, feature = "multi_arch", feature_display = "Multi-arch Images")]
+    pub(super) multi_arch: MultiArch,
+
+    #[preset_field(flatten, feature = "github", feature_display = "GitHub Actions")]
+    pub(super) github: GitHubSettings,
+
+    #[preset_field(flatten, feature = "runner", feature_display = "Runner")]
+    pub(super) runner: RunnerSettings,
+
+    #[preset_field(flatten, feature = "job_control", feature_display = "Job Control")]
+    pub(super) job_control: JobControl,
+
+    #[preset_field(
+        flatten,
+        feature = "triggers",
+        feature_display = "Triggers",
+        default = "Triggers { workflow_name: \"Docker Build and Push\".to_string(), tags: \"v*\".to_string(), ..Triggers::default() }"
+    )]
+    pub(super) triggers: Triggers,
 }
 
 impl DockerPreset {
-    /// Constant default instance for registry initialization
-    pub const DEFAULT: Self = Self {
-        image_name: String::new(),
-        registry: DockerRegistry::None,
-        dockerfile_path: String::new(),
-        build_context: String::new(),
-        enable_cache: false,
-        push_on_tags_only: false,
-    };
+    /// The configured triggers, without branch pushes under `push_on_tags_only`
+    pub(super) fn trigger_config(&self) -> TriggerConfig {
+        let mut triggers = self.triggers.config();
+        if self.push_on_tags_only {
+            triggers.push_branches.clear();
+            if triggers.tags.is_empty() {
+                triggers.tags.push("v*".to_string());
+            }
+        }
+        triggers
+    }
+}
+
+impl RequiresSecrets for DockerPreset {
+    fn required_secrets(&self, platform: Platform) -> Vec<Secret> {
+        match (&self.registry, platform) {
+            // GitLab pushes to its own registry with predefined CI_REGISTRY_* variables
+            (DockerRegistry::None, _) | (_, Platform::GitLab) => Vec::new(),
+            (DockerRegistry::DockerHub, _) => {
+                vec![secrets::DOCKER_USERNAME, secrets::DOCKER_PASSWORD]
+            }
+            // Actions workflows get a GITHUB_TOKEN automatically
+            (DockerRegistry::GitHubRegistry, Platform::GitHub | Platform::Gitea) => Vec::new(),
+            (DockerRegistry::GitHubRegistry, _) => {
+                vec![secrets::GITHUB_USERNAME, secrets::GITHUB_TOKEN]
+            }
+        }
+    }
+}
+
+impl SupportFiles for DockerPreset {}
+
+impl DecoratesPipelines for DockerPreset {}
+
+impl DetectedDefaults for DockerPreset {}
+
+impl LocalChecks for DockerPreset {
+    fn local_checks(&self) -> Vec<LocalCheck> {
+        vec![LocalCheck::new(
+            "docker/build",
+            "Build image",
+            CheckKind::Build,
+            format!(
+                "docker build -t {} -f {} {}",
+                self.image_name, self.dockerfile_path, self.build_context
+            ),
+            &[],
+        )]
+    }
 }
 
 impl PresetInfo for DockerPreset {
@@ -104,23 +189,42 @@ impl PresetInfo for DockerPreset {
     }
 }
 
-// Tests temporarily disabled - need to be updated after macro migration
 #[cfg(test)]
-#[cfg(disabled)]
 mod tests {
     use super::*;
-    use crate::platforms::github::models::{GitHubJob, GitHubStep, GitHubTriggers, GitHubWorkflow};
-    use crate::traits::{ToCircleCI, ToGitHub, ToGitLab, ToJenkins};
-    use std::collections::BTreeMap;
+    use crate::platforms::github::models::{
+        GitHubJob, GitHubStep, GitHubTrigger, GitHubTriggers, GitHubWorkflow,
+    };
+    use crate::platforms::jenkins::models::JenkinsAgent;
+    use crate::platforms::jobs::Jobs;
+    use crate::traits::{Detectable, ToCircleCI, ToGitHub, ToGitLab, ToJenkins};
+
+    fn docker_preset(
+        image_name: &str,
+        registry: DockerRegistry,
+        dockerfile_path: &str,
+        build_context: &str,
+        enable_cache: bool,
+        push_on_tags_only: bool,
+    ) -> DockerPreset {
+        DockerPreset {
+            image_name: image_name.to_string(),
+            registry,
+            dockerfile_path: dockerfile_path.to_string(),
+            build_context: build_context.to_string(),
+            enable_cache,
+            push_on_tags_only,
+            ..DockerPreset::default()
+        }
+    }
 
     #[test]
     fn test_builder_defaults() {
-        let preset = DockerPreset::new(
-            "myapp".to_string(),
+        let preset = docker_preset(
+            "myapp",
             DockerRegistry::None,
-            "./Dockerfile".to_string(),
-            ".".to_string(),
-            vec![],
+            "./Dockerfile",
+            ".",
             false,
             false,
         );
@@ -135,12 +239,11 @@ mod tests {
 
     #[test]
     fn test_builder_with_dockerhub() {
-        let preset = DockerPreset::new(
-            "myorg/myapp".to_string(),
+        let preset = docker_preset(
+            "myorg/myapp",
             DockerRegistry::DockerHub,
-            "./Dockerfile".to_string(),
-            ".".to_string(),
-            vec![],
+            "./Dockerfile",
+            ".",
             true,
             false,
         );
@@ -151,12 +254,11 @@ mod tests {
 
     #[test]
     fn test_builder_with_github_registry() {
-        let preset = DockerPreset::new(
-            "myapp".to_string(),
+        let preset = docker_preset(
+            "myapp",
             DockerRegistry::GitHubRegistry,
-            "./Dockerfile".to_string(),
-            ".".to_string(),
-            vec![],
+            "./Dockerfile",
+            ".",
             false,
             true,
         );
@@ -165,17 +267,131 @@ mod tests {
         assert!(preset.push_on_tags_only);
     }
 
+    #[test]
+    fn test_paths_are_options() {
+        use crate::editor::config::{EditorPreset, OptionValue};
+
+        let mut config = DockerPreset::default().default_config(true);
+        let preset = DockerPreset::from_config(&config, "stable");
+        assert_eq!(preset.dockerfile_path, "./Dockerfile");
+        assert_eq!(preset.build_context, ".");
+
+        config.set(
+            "build_context".to_string(),
+            OptionValue::String("./app".to_string()),
+        );
+        let ron = DockerPreset::preset_config_to_ron(&config);
+        assert_eq!(ron.build_context, "./app");
+        assert_eq!(
+            DockerPreset::from_config(&DockerPreset::ron_to_preset_config(ron), "stable")
+                .build_context,
+            "./app"
+        );
+    }
+
+    #[test]
+    fn test_multi_arch_is_flattened() {
+        use crate::editor::config::{EditorPreset, OptionValue};
+
+        let features = DockerPreset::default().features();
+        let multi_arch = features.iter().find(|f| f.id == "multi_arch").unwrap();
+        let ids: Vec<&str> = multi_arch.options.iter().map(|o| o.id.as_str()).collect();
+        assert_eq!(ids, ["enable_multi_arch", "platforms"]);
+        let mut option_ids = DockerPreset::OPTION_IDS.to_vec();
+        let defaults = DockerPreset::default().default_config(true);
+        let mut config_ids: Vec<&str> = defaults.values.keys().map(String::as_str).collect();
+        option_ids.sort();
+        config_ids.sort();
+        assert_eq!(option_ids, config_ids);
+        assert_eq!(
+            multi_arch.options[1].depends_on.as_deref(),
+            Some("enable_multi_arch")
+        );
+
+        // Configs from before the group keep building for the runner's own platform
+        let ron: DockerConfig = ron::from_str("(image_name: \"myapp\")").unwrap();
+        let mut config = DockerPreset::ron_to_preset_config(ron);
+        assert!(!config.get_bool("enable_multi_arch"));
+        assert_eq!(
+            config.get_string("platforms").unwrap(),
+            "linux/amd64, linux/arm64"
+        );
+
+        config.set("enable_multi_arch".to_string(), OptionValue::Bool(true));
+        config.set(
+            "registry".to_string(),
+            OptionValue::Enum {
+                selected: "dockerhub".to_string(),
+                variants: DockerRegistry::all_variants()
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+            },
+        );
+        let ron = DockerPreset::preset_config_to_ron(&config);
+        assert!(ron.multi_arch.enable_multi_arch);
+        let preset = DockerPreset::from_config(&DockerPreset::ron_to_preset_config(ron), "stable");
+        assert_eq!(
+            preset.multi_arch.platform_arg().as_deref(),
+            Some("linux/amd64,linux/arm64")
+        );
+
+        let workflow = preset.to_github().unwrap();
+        let steps = &workflow.jobs["docker/build"].steps;
+        assert!(steps
+            .iter()
+            .any(|s| s.uses.as_deref() == Some("docker/setup-qemu-action@v3")));
+        let build = steps.last().unwrap().with.as_ref().unwrap();
+        assert_eq!(
+            build["platforms"],
+            serde_yaml::Value::String("linux/amd64,linux/arm64".to_string())
+        );
+        assert!(DockerPreset::default()
+            .extract_config(&workflow)
+            .get_bool("enable_multi_arch"));
+
+        // The builder pushes multi-arch images itself
+        let script = &preset.to_gitlab().unwrap().jobs["docker/build"].script;
+        assert!(script.iter().any(|line| line
+            .starts_with("docker buildx build --platform linux/amd64,linux/arm64 --push")));
+        assert!(!script.iter().any(|line| line.starts_with("docker push")));
+    }
+
+    #[test]
+    fn test_triggers_keep_dockers_defaults() {
+        use crate::editor::config::EditorPreset;
+
+        let defaults = DockerPreset::default().default_config(false);
+        assert_eq!(
+            defaults.get_string("workflow_name").unwrap(),
+            "Docker Build and Push"
+        );
+        assert_eq!(defaults.get_string("tags").unwrap(), "v*");
+
+        // Settings left out of the group take Docker's defaults, not the group's
+        let ron: DockerConfig =
+            ron::from_str("(image_name: \"myapp\", triggers: (push_branches: \"dev\"))").unwrap();
+        assert_eq!(ron.triggers.workflow_name, "Docker Build and Push");
+        assert_eq!(ron.triggers.tags, "v*");
+        assert_eq!(ron.triggers.push_branches, "dev");
+        assert!(ron::from_str::<DockerConfig>("(triggers: (branches: \"dev\"))").is_err());
+
+        let schema = DockerPreset::schema();
+        let triggers = &schema["properties"]["triggers"]["properties"];
+        assert_eq!(
+            triggers["workflow_name"]["default"],
+            "Docker Build and Push"
+        );
+        assert_eq!(triggers["push_branches"]["default"], "main, master");
+    }
+
     #[test]
     fn test_builder_with_custom_paths() {
-        let preset = DockerPreset::new(
-            "myapp".to_string(),
+        let preset = docker_preset(
+            "myapp",
             DockerRegistry::None,
-            "./docker/Dockerfile".to_string(),
-            "./app".to_string(),
-            vec![
-                ("VERSION".to_string(), "1.0.0".to_string()),
-                ("BUILD_DATE".to_string(), "2024-01-01".to_string()),
-            ],
+            "./docker/Dockerfile",
+            "./app",
             false,
             false,
         );
@@ -186,12 +402,11 @@ mod tests {
 
     #[test]
     fn test_to_github_basic() {
-        let preset = DockerPreset::new(
-            "myapp".to_string(),
+        let preset = docker_preset(
+            "myapp",
             DockerRegistry::None,
-            "./Dockerfile".to_string(),
-            ".".to_string(),
-            vec![],
+            "./Dockerfile",
+            ".",
             false,
             false,
         );
@@ -202,7 +417,7 @@ mod tests {
         assert!(workflow.jobs.contains_key("docker/build"));
 
         let job = &workflow.jobs["docker/build"];
-        assert_eq!(job.runs_on, "ubuntu-latest");
+        assert_eq!(job.runs_on, "ubuntu-latest".into());
 
         // Should have checkout, buildx setup, metadata, and build steps
         assert!(job.steps.len() >= 4);
@@ -210,12 +425,11 @@ mod tests {
 
     #[test]
     fn test_to_github_with_dockerhub() {
-        let preset = DockerPreset::new(
-            "myorg/myapp".to_string(),
+        let preset = docker_preset(
+            "myorg/myapp",
             DockerRegistry::DockerHub,
-            "./Dockerfile".to_string(),
-            ".".to_string(),
-            vec![],
+            "./Dockerfile",
+            ".",
             false,
             false,
         );
@@ -235,12 +449,11 @@ mod tests {
 
     #[test]
     fn test_to_github_with_github_registry() {
-        let preset = DockerPreset::new(
-            "myapp".to_string(),
+        let preset = docker_preset(
+            "myapp",
             DockerRegistry::GitHubRegistry,
-            "./Dockerfile".to_string(),
-            ".".to_string(),
-            vec![],
+            "./Dockerfile",
+            ".",
             false,
             false,
         );
@@ -258,14 +471,40 @@ mod tests {
         assert!(has_ghcr_login);
     }
 
+    #[test]
+    fn test_to_github_ghcr_job_permissions() {
+        let preset = docker_preset(
+            "myapp",
+            DockerRegistry::GitHubRegistry,
+            "./Dockerfile",
+            ".",
+            true,
+            false,
+        );
+
+        let workflow = preset.to_github().unwrap();
+        let permissions = workflow.jobs["docker/build"].permissions.as_ref().unwrap();
+        assert_eq!(
+            permissions.get("packages").map(String::as_str),
+            Some("write")
+        );
+        assert_eq!(
+            workflow
+                .permissions
+                .unwrap()
+                .get("contents")
+                .map(String::as_str),
+            Some("read")
+        );
+    }
+
     #[test]
     fn test_to_github_tags_only_trigger() {
-        let preset = DockerPreset::new(
-            "myapp".to_string(),
+        let preset = docker_preset(
+            "myapp",
             DockerRegistry::DockerHub,
-            "./Dockerfile".to_string(),
-            ".".to_string(),
-            vec![],
+            "./Dockerfile",
+            ".",
             false,
             true,
         );
@@ -273,22 +512,23 @@ mod tests {
         let workflow = preset.to_github().unwrap();
 
         // Verify trigger configuration
-        if let GitHubTriggers::Detailed(triggers) = &workflow.on {
-            if let Some(push_trigger) = triggers.get("push") {
-                assert!(push_trigger.branches.is_none());
-                assert!(push_trigger.tags.is_some());
-            }
-        }
+        let GitHubTriggers::Detailed(triggers) = &workflow.on else {
+            panic!("expected detailed triggers");
+        };
+        let Some(GitHubTrigger::Filter(push_trigger)) = triggers.get("push") else {
+            panic!("expected push trigger");
+        };
+        assert!(push_trigger.branches.is_none());
+        assert_eq!(push_trigger.tags, Some(vec!["v*".to_string()]));
     }
 
     #[test]
     fn test_to_gitlab_basic() {
-        let preset = DockerPreset::new(
-            "myapp".to_string(),
+        let preset = docker_preset(
+            "myapp",
             DockerRegistry::None,
-            "./Dockerfile".to_string(),
-            ".".to_string(),
-            vec![],
+            "./Dockerfile",
+            ".",
             false,
             false,
         );
@@ -301,12 +541,11 @@ mod tests {
 
     #[test]
     fn test_to_circleci_basic() {
-        let preset = DockerPreset::new(
-            "myapp".to_string(),
+        let preset = docker_preset(
+            "myapp",
             DockerRegistry::None,
-            "./Dockerfile".to_string(),
-            ".".to_string(),
-            vec![],
+            "./Dockerfile",
+            ".",
             false,
             false,
         );
@@ -319,31 +558,61 @@ mod tests {
 
     #[test]
     fn test_to_jenkins_basic() {
-        let preset = DockerPreset::new(
-            "myapp".to_string(),
+        let preset = docker_preset(
+            "myapp",
             DockerRegistry::None,
-            "./Dockerfile".to_string(),
-            ".".to_string(),
-            vec![],
+            "./Dockerfile",
+            ".",
             false,
             false,
         );
 
         let config = preset.to_jenkins().unwrap();
 
-        assert_eq!(config.agent, "any");
+        assert_eq!(config.agent, JenkinsAgent::Any);
         assert!(!config.stages.is_empty());
         assert_eq!(config.stages[0].name, "Docker Build");
     }
 
+    #[test]
+    fn test_required_secrets() {
+        let dockerhub = docker_preset(
+            "myapp",
+            DockerRegistry::DockerHub,
+            "./Dockerfile",
+            ".",
+            false,
+            false,
+        );
+        let names = |secrets: Vec<Secret>| secrets.into_iter().map(|s| s.name).collect::<Vec<_>>();
+        assert_eq!(
+            names(dockerhub.required_secrets(Platform::GitHub)),
+            vec!["DOCKER_USERNAME", "DOCKER_PASSWORD"]
+        );
+        assert!(dockerhub.required_secrets(Platform::GitLab).is_empty());
+
+        let ghcr = docker_preset(
+            "myapp",
+            DockerRegistry::GitHubRegistry,
+            "./Dockerfile",
+            ".",
+            false,
+            false,
+        );
+        assert!(ghcr.required_secrets(Platform::GitHub).is_empty());
+        assert_eq!(
+            names(ghcr.required_secrets(Platform::CircleCI)),
+            vec!["GITHUB_USERNAME", "GITHUB_TOKEN"]
+        );
+    }
+
     #[test]
     fn test_preset_info() {
-        let preset = DockerPreset::new(
-            "myapp".to_string(),
+        let preset = docker_preset(
+            "myapp",
             DockerRegistry::None,
-            "./Dockerfile".to_string(),
-            ".".to_string(),
-            vec![],
+            "./Dockerfile",
+            ".",
             false,
             false,
         );
@@ -354,24 +623,24 @@ mod tests {
 
     #[test]
     fn test_detectable_github() {
-        let preset = DockerPreset::new(
-            "myapp".to_string(),
+        let preset = docker_preset(
+            "myapp",
             DockerRegistry::None,
-            "./Dockerfile".to_string(),
-            ".".to_string(),
-            vec![],
+            "./Dockerfile",
+            ".",
             false,
             false,
         );
 
         // Create a workflow with docker build action
-        let mut jobs = BTreeMap::new();
+        let mut jobs = Jobs::new();
         jobs.insert(
             "build".to_string(),
             GitHubJob {
-                runs_on: "ubuntu-latest".to_string(),
+                runs_on: "ubuntu-latest".into(),
                 steps: vec![GitHubStep {
                     name: Some("Build".to_string()),
+                    if_condition: None,
                     uses: Some("docker/build-push-action@v5".to_string()),
                     run: None,
                     with: None,
@@ -380,6 +649,13 @@ mod tests {
                 needs: None,
                 timeout_minutes: None,
                 continue_on_error: None,
+                container: None,
+                permissions: None,
+                strategy: None,
+                services: None,
+                env: None,
+                if_condition: None,
+                environment: None,
             },
         );
 
@@ -387,7 +663,10 @@ mod tests {
             name: "Test".to_string(),
             on: GitHubTriggers::Simple(vec!["push".to_string()]),
             env: None,
+            defaults: None,
             jobs,
+            permissions: None,
+            concurrency: None,
         };
 
         assert!(preset.matches_github(&workflow));
@@ -0,0 +1,187 @@
+use crate::editor::state::Platform;
+use crate::error::Result;
+use crate::models::steps;
+use crate::models::{Job, Pipeline, Step};
+use crate::traits::ToPipeline;
+use serde_yaml::Value;
+
+use super::{DockerPreset, DockerRegistry};
+
+impl DockerPreset {
+    /// Setup, login, metadata and build-push actions
+    fn action_steps(&self) -> Vec<Step> {
+        let input = |key: &str, value: String| (key.to_string(), Value::String(value));
+        let mut build_steps = vec![steps::checkout()];
+
+        // QEMU emulates the architectures the runner doesn't have
+        if self.multi_arch.platform_arg().is_some() {
+            build_steps.push(steps::action(
+                "Set up QEMU",
+                "docker/setup-qemu-action@v3",
+                [],
+            ));
+        }
+
+        // Add Docker buildx setup for better caching and multi-platform support
+        build_steps.push(steps::action(
+            "Set up Docker Buildx",
+            "docker/setup-buildx-action@v3",
+            [],
+        ));
+
+        // Login step depends on registry type
+        match &self.registry {
+            DockerRegistry::DockerHub => build_steps.push(steps::action(
+                "Login to Docker Hub",
+                "docker/login-action@v3",
+                [
+                    input("username", "${{ secrets.DOCKER_USERNAME }}".to_string()),
+                    input("password", "${{ secrets.DOCKER_PASSWORD }}".to_string()),
+                ],
+            )),
+            DockerRegistry::GitHubRegistry => build_steps.push(steps::action(
+                "Login to GitHub Container Registry",
+                "docker/login-action@v3",
+                [
+                    input("registry", "ghcr.io".to_string()),
+                    input("username", "${{ github.actor }}".to_string()),
+                    input("password", "${{ secrets.GITHUB_TOKEN }}".to_string()),
+                ],
+            )),
+            DockerRegistry::None => {}
+        }
+
+        // Extract metadata for tags and labels
+        let images = match &self.registry {
+            DockerRegistry::GitHubRegistry => {
+                format!("ghcr.io/${{ github.repository_owner }}/{}", self.image_name)
+            }
+            _ => self.image_name.clone(),
+        };
+        build_steps.push(steps::action(
+            "Extract Docker metadata",
+            "docker/metadata-action@v5",
+            [
+                input("images", images),
+                input(
+                    "tags",
+                    "type=ref,event=branch\ntype=ref,event=pr\ntype=semver,pattern={{version}}\ntype=semver,pattern={{major}}.{{minor}}".to_string(),
+                ),
+            ],
+        ));
+
+        // Build and push step
+        let mut build_push_with = vec![
+            input("context", self.build_context.clone()),
+            input("file", self.dockerfile_path.clone()),
+            input("tags", "${{ steps.meta.outputs.tags }}".to_string()),
+            input("labels", "${{ steps.meta.outputs.labels }}".to_string()),
+        ];
+
+        // Only push if registry is configured
+        if self.registry != DockerRegistry::None {
+            build_push_with.push(input("push", "true".to_string()));
+        }
+
+        if let Some(platforms) = self.multi_arch.platform_arg() {
+            build_push_with.push(input("platforms", platforms));
+        }
+
+        // Add caching if enabled
+        if self.enable_cache {
+            build_push_with.push(input("cache-from", "type=gha".to_string()));
+            build_push_with.push(input("cache-to", "type=gha,mode=max".to_string()));
+        }
+
+        build_steps.push(steps::action(
+            "Build and push Docker image",
+            "docker/build-push-action@v5",
+            build_push_with,
+        ));
+        build_steps
+    }
+
+    /// Login, build and push as shell commands
+    fn shell_steps(&self, platform: Platform) -> Vec<Step> {
+        let command = |command: &str| Step::Run {
+            name: None,
+            command: command.to_string(),
+        };
+        let mut build_steps = vec![steps::checkout()];
+
+        // GitLab logs in to its own registry; elsewhere the login depends on
+        // the registry type
+        if platform == Platform::GitLab {
+            build_steps.push(command(
+                "docker login -u $CI_REGISTRY_USER -p $CI_REGISTRY_PASSWORD $CI_REGISTRY",
+            ));
+        } else {
+            match &self.registry {
+                DockerRegistry::DockerHub => build_steps.push(command(
+                    "echo $DOCKER_PASSWORD | docker login -u $DOCKER_USERNAME --password-stdin",
+                )),
+                DockerRegistry::GitHubRegistry => build_steps.push(command(
+                    "echo $GITHUB_TOKEN | docker login ghcr.io -u $GITHUB_USERNAME --password-stdin",
+                )),
+                DockerRegistry::None => {}
+            }
+        }
+
+        build_steps.extend(
+            self.multi_arch
+                .setup_commands()
+                .iter()
+                .map(|setup| command(setup)),
+        );
+
+        let build_cmd = format!(
+            "{} -t {} -f {} {}",
+            self.multi_arch
+                .build_command(self.registry != DockerRegistry::None),
+            self.image_name,
+            self.dockerfile_path,
+            self.build_context
+        );
+        build_steps.push(steps::run("Build Docker image", build_cmd));
+
+        // Push if registry is configured
+        if self.registry != DockerRegistry::None && self.multi_arch.needs_push() {
+            build_steps.push(steps::run(
+                "Push Docker image",
+                format!("docker push {}", self.image_name),
+            ));
+        }
+        build_steps
+    }
+}
+
+impl ToPipeline for DockerPreset {
+    fn to_pipeline(&self, platform: Platform) -> Result<Pipeline> {
+        let (image, build_steps) = match platform {
+            Platform::GitHub | Platform::Gitea => (None, self.action_steps()),
+            Platform::GitLab => (Some("docker:latest"), self.shell_steps(platform)),
+            Platform::CircleCI => (Some("cimg/base:stable"), self.shell_steps(platform)),
+            Platform::Jenkins => (None, self.shell_steps(platform)),
+        };
+        let build = Job {
+            id: "docker/build".to_string(),
+            name: Some("Docker Build".to_string()),
+            stage: Some("build".to_string()),
+            image: image.map(str::to_string),
+            runner: None,
+            needs: Vec::new(),
+            env: Default::default(),
+            timeout_minutes: Some(30),
+            matrix: None,
+            services: Vec::new(),
+            cache: None,
+            artifacts: Vec::new(),
+            steps: build_steps,
+        };
+        Ok(Pipeline {
+            triggers: self.trigger_config(),
+            env: Default::default(),
+            jobs: vec![build],
+        })
+    }
+}
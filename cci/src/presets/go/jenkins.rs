@@ -1,32 +1,20 @@
+use crate::editor::state::Platform;
 use crate::error::Result;
+use crate::models::adapters::{JenkinsAdapter, PlatformAdapter};
 use crate::platforms::jenkins::models::JenkinsConfig;
-use crate::traits::ToJenkins;
+use crate::traits::{ToJenkins, ToPipeline};
 
 use super::GoAppPreset;
 
 impl ToJenkins for GoAppPreset {
     fn to_jenkins(&self) -> Result<JenkinsConfig> {
-        use crate::platforms::jenkins::models::*;
-
-        let mut test_steps = Vec::new();
-
-        if self.enable_security_scan {
-            test_steps.push("sh 'gosec ./...'".to_string());
-        }
-
-        if self.enable_linter {
-            test_steps.push("sh 'golangci-lint run'".to_string());
+        let pipeline = self.to_pipeline(Platform::Jenkins)?;
+        let mut config = JenkinsAdapter::lower(&pipeline, &mut Vec::new());
+        if let Some(coverage) = self.coverage() {
+            coverage.apply_jenkins(&mut config);
         }
-
-        test_steps.push("sh 'go test -v ./...'".to_string());
-
-        Ok(JenkinsConfig {
-            agent: "any".to_string(),
-            environment: vec![],
-            stages: vec![JenkinsStage {
-                name: "Test".to_string(),
-                steps: test_steps,
-            }],
-        })
+        self.runner.config().apply_jenkins(&mut config);
+        self.job_control.apply_jenkins(&mut config);
+        Ok(config)
     }
 }
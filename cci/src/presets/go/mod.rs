@@ -1,12 +1,29 @@
-use crate::traits::PresetInfo;
+use crate::editor::config::{OptionValue, PresetConfig};
+use crate::editor::state::Platform;
+use crate::platforms::cache::{CacheMode, CacheStrategy};
+use crate::platforms::checks::{CheckKind, LocalCheck};
+use crate::platforms::coverage::{CoverageFormat, CoverageProvider, CoverageUpload};
+use crate::platforms::github::models::GitHubWorkflow;
+use crate::platforms::os::OsMatrix;
+use crate::platforms::secrets::Secret;
+use crate::platforms::services::{tune_database, ServiceKind, Services};
+use crate::presets::job_control::{JobControl, JobControlConfig};
+use crate::presets::workflow::{
+    GitHubSettings, GitHubSettingsConfig, RunnerSettings, RunnerSettingsConfig, Triggers,
+    TriggersConfig,
+};
+use crate::traits::{
+    DecoratesPipelines, DetectedDefaults, LocalChecks, PresetInfo, RequiresSecrets, SupportFiles,
+};
 use cci_macros::Preset;
+use std::collections::HashMap;
 
 mod circleci;
-mod detectable;
 mod gitea;
 mod github;
 mod gitlab;
 mod jenkins;
+mod pipeline;
 
 /// Preset for Go application projects
 #[derive(Debug, Clone, Preset)]
@@ -14,7 +31,9 @@ mod jenkins;
     id = "go-app",
     name = "Go App",
     description = "CI pipeline for Go applications with testing and linting",
-    matches = "GoApp | GoLibrary"
+    matches = "GoApp | GoLibrary",
+    detect_uses = "setup-go",
+    detect_run = "go test"
 )]
 pub struct GoAppPreset {
     #[preset_field(default = "\"1.21\".to_string()", hidden = true)]
@@ -25,7 +44,8 @@ pub struct GoAppPreset {
         feature_display = "Linting",
         display = "Enable Linter",
         description = "Run golangci-lint for code quality",
-        default = "true"
+        default = "true",
+        github_step = "golangci-lint"
     )]
     pub(super) enable_linter: bool,
 
@@ -34,18 +54,171 @@ pub struct GoAppPreset {
         feature_display = "Security",
         display = "Security Scan",
         description = "Run gosec for security vulnerabilities",
-        default = "true"
+        default = "true",
+        github_step = "gosec"
     )]
     pub(super) enable_security_scan: bool,
+
+    #[preset_field(
+        feature = "testing",
+        feature_display = "Testing",
+        display = "Code Coverage",
+        description = "Write a coverage profile and upload the report",
+        default = "false",
+        github_step = "-coverprofile"
+    )]
+    pub(super) enable_coverage: bool,
+
+    #[preset_field(
+        feature = "testing",
+        feature_display = "Testing",
+        display = "Coverage Provider",
+        description = "Service coverage reports are uploaded to",
+        default = "CoverageProvider::Codecov",
+        depends_on = "enable_coverage"
+    )]
+    pub(super) coverage_provider: CoverageProvider,
+
+    #[preset_field(
+        feature = "testing",
+        feature_display = "Testing",
+        display = "Operating Systems",
+        description = "Comma-separated systems the test job runs on in GitHub and Gitea workflows (linux, macos, windows); database services need Linux",
+        default = "String::new()",
+        validation = "Validation::os_list()"
+    )]
+    pub(super) os_matrix: String,
+
+    #[preset_field(
+        feature = "services",
+        feature_display = "Services",
+        display = "Database Service",
+        description = "Start a database container for the test job (None, PostgreSQL, MySQL, or Redis)",
+        default = "None"
+    )]
+    pub(super) database: Option<ServiceKind>,
+
+    #[preset_field(
+        feature = "caching",
+        feature_display = "Caching",
+        display = "Cache Strategy",
+        description = "Action-based uses the toolchain action's built-in cache; manual emits explicit actions/cache keys",
+        default = "CacheMode::Action"
+    )]
+    pub(super) cache_strategy: CacheMode,
+
+    #[preset_field(flatten, feature = "github", feature_display = "GitHub Actions")]
+    pub(super) github: GitHubSettings,
+
+    #[preset_field(flatten, feature = "runner", feature_display = "Runner")]
+    pub(super) runner: RunnerSettings,
+
+    #[preset_field(flatten, feature = "job_control", feature_display = "Job Control")]
+    pub(super) job_control: JobControl,
+
+    #[preset_field(flatten, feature = "triggers", feature_display = "Triggers")]
+    pub(super) triggers: Triggers,
 }
 
 impl GoAppPreset {
-    /// Constant default instance for registry initialization
-    pub const DEFAULT: Self = Self {
-        go_version: String::new(),
-        enable_linter: false,
-        enable_security_scan: false,
-    };
+    /// Command that runs the test suite, writing coverage.out if coverage is enabled
+    pub(super) fn test_command(&self) -> &'static str {
+        if self.enable_coverage {
+            "go test -v -coverprofile=coverage.out ./..."
+        } else {
+            "go test -v ./..."
+        }
+    }
+
+    /// Systems besides Linux the test job runs on, if any
+    pub(super) fn os_matrix(&self) -> Option<OsMatrix> {
+        OsMatrix::from_list(&self.os_matrix)
+    }
+
+    /// Upload of the coverage profile, if coverage is enabled
+    pub(super) fn coverage(&self) -> Option<CoverageUpload> {
+        self.enable_coverage.then(|| {
+            CoverageUpload::new(
+                self.coverage_provider,
+                "coverage.out",
+                CoverageFormat::GoCover,
+            )
+        })
+    }
+
+    /// Service containers for the test job
+    pub(super) fn services(&self) -> Services {
+        Services::from_options(self.database)
+    }
+
+    /// Dependency cache shared by all platform backends
+    pub(super) fn cache(&self) -> CacheStrategy {
+        CacheStrategy::go(self.cache_strategy)
+    }
+}
+
+impl RequiresSecrets for GoAppPreset {
+    fn required_secrets(&self, _platform: Platform) -> Vec<Secret> {
+        // Every backend uploads coverage with the provider's token
+        self.coverage()
+            .map(|coverage| coverage.required_secrets())
+            .unwrap_or_default()
+    }
+}
+
+impl SupportFiles for GoAppPreset {}
+
+impl DecoratesPipelines for GoAppPreset {}
+
+impl DetectedDefaults for GoAppPreset {
+    fn tune_defaults(&self, metadata: &HashMap<String, String>, config: &mut PresetConfig) {
+        tune_database(metadata, config);
+    }
+
+    fn extract_github(&self, workflow: &GitHubWorkflow, config: &mut PresetConfig) {
+        if let Some(systems) = OsMatrix::list_from_github(workflow) {
+            config.set("os_matrix".to_string(), OptionValue::String(systems));
+        }
+    }
+}
+
+impl LocalChecks for GoAppPreset {
+    fn local_checks(&self) -> Vec<LocalCheck> {
+        let mut checks = Vec::new();
+        if self.enable_linter {
+            checks.push(LocalCheck::new(
+                "go/lint",
+                "Run golangci-lint",
+                CheckKind::Lint,
+                "golangci-lint run",
+                &["go"],
+            ));
+        }
+        checks.push(LocalCheck::new(
+            "go/test",
+            "Run tests",
+            CheckKind::Test,
+            "go test -v ./...",
+            &["go"],
+        ));
+        if self.enable_security_scan {
+            checks.push(LocalCheck::new(
+                "go/security",
+                "Run gosec",
+                CheckKind::Audit,
+                "gosec ./...",
+                &["go"],
+            ));
+        }
+        checks.push(LocalCheck::new(
+            "go/build",
+            "Build",
+            CheckKind::Build,
+            "go build -v ./...",
+            &["go"],
+        ));
+        checks
+    }
 }
 
 impl PresetInfo for GoAppPreset {
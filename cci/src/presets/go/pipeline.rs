@@ -0,0 +1,119 @@
+use crate::editor::state::Platform;
+use crate::error::Result;
+use crate::models::steps::{self, Toolchain};
+use crate::models::{Job, Pipeline, Step};
+use crate::traits::ToPipeline;
+
+use super::GoAppPreset;
+
+/// gosec release the security job runs
+const GOSEC_VERSION: &str = "v2.22.0";
+
+/// Image the lint job runs in on the platforms that run jobs in one
+const LINT_IMAGE: &str = "golangci/golangci-lint:latest";
+
+impl GoAppPreset {
+    /// Checkout, then Go itself where the job doesn't run in a Go image
+    fn setup(&self, platform: Platform, cache: bool) -> Vec<Step> {
+        let mut setup = vec![steps::checkout()];
+        if platform.runs_actions() {
+            let inputs = match cache {
+                true => self.cache().github_setup_inputs(),
+                false => Vec::new(),
+            };
+            setup.push(Toolchain::Go.setup(&self.go_version, inputs));
+        }
+        setup
+    }
+}
+
+impl ToPipeline for GoAppPreset {
+    fn to_pipeline(&self, platform: Platform) -> Result<Pipeline> {
+        let image = format!("golang:{}", self.go_version);
+        let job = |id: &str, name: &str, stage: &str, timeout: u32, steps: Vec<Step>| Job {
+            id: id.to_string(),
+            name: Some(name.to_string()),
+            stage: Some(stage.to_string()),
+            image: platform.runs_jobs_in_images().then(|| image.clone()),
+            runner: None,
+            needs: Vec::new(),
+            env: Default::default(),
+            timeout_minutes: Some(timeout),
+            matrix: None,
+            services: Vec::new(),
+            cache: None,
+            artifacts: Vec::new(),
+            steps,
+        };
+        let mut jobs = Vec::new();
+
+        // Test job (always present)
+        let mut test_steps = self.setup(platform, true);
+        test_steps.extend([
+            steps::run("Download dependencies", "go mod download"),
+            steps::run("Run tests", self.test_command()),
+            steps::run("Build", "go build -v ./..."),
+        ]);
+        if let Some(coverage) = self.coverage() {
+            if platform == Platform::GitLab {
+                // Prints the total that `coverage:` picks up
+                test_steps.push(steps::run(
+                    "Report coverage",
+                    "go tool cover -func=coverage.out",
+                ));
+            }
+            test_steps.push(steps::coverage_upload(&coverage));
+        }
+        jobs.push(Job {
+            services: self.services().services,
+            cache: Some(self.cache()),
+            ..job("go/test", "Test", "test", 30, test_steps)
+        });
+
+        // Lint job (optional)
+        if self.enable_linter {
+            let mut lint_steps = self.setup(platform, false);
+            if platform.runs_actions() {
+                lint_steps.push(steps::action(
+                    "Run golangci-lint",
+                    "golangci/golangci-lint-action@v4",
+                    [("version".to_string(), "latest".into())],
+                ));
+            } else {
+                lint_steps.push(steps::run("Run golangci-lint", "golangci-lint run"));
+            }
+            jobs.push(Job {
+                image: platform
+                    .runs_jobs_in_images()
+                    .then(|| LINT_IMAGE.to_string()),
+                ..job("go/lint", "Lint", "lint", 15, lint_steps)
+            });
+        }
+
+        // Security scan job (optional)
+        if self.enable_security_scan {
+            let mut security_steps = self.setup(platform, false);
+            // A tagged release rather than the action's `master`
+            security_steps.push(steps::run(
+                "Run gosec",
+                format!(
+                    "go run github.com/securego/gosec/v2/cmd/gosec@{} ./...",
+                    GOSEC_VERSION
+                ),
+            ));
+            jobs.push(job(
+                "go/security",
+                "Security Scan",
+                "security",
+                10,
+                security_steps,
+            ));
+        }
+
+        Ok(Pipeline {
+            triggers: self.triggers.config(),
+            env: Default::default(),
+            jobs,
+        })
+    }
+}
@@ -0,0 +1,128 @@
+//! Time limits, retries and allowed failures for a preset's jobs, for
+//! presets to flatten into their own options
+//!
+//! ```text
+//! #[preset_field(flatten, feature = "job_control", feature_display = "Job Control")]
+//! pub(super) job_control: JobControl,
+//! ```
+//!
+//! Each setting applies to every job the preset writes. Platforms honor what
+//! they can express: GitHub has no job retries, CircleCI limits how long a
+//! step may go without output rather than how long a job runs, and neither
+//! CircleCI retries nor lets jobs fail.
+
+use crate::platforms::circleci::models::{CircleCIConfig, CircleCIRun, CircleCIStep};
+use crate::platforms::github::models::GitHubWorkflow;
+use crate::platforms::gitlab::models::GitLabCI;
+use crate::platforms::jenkins::models::JenkinsConfig;
+use cci_macros::Preset;
+
+/// Per-job limits and failure handling
+#[derive(Debug, Clone, Preset)]
+#[preset(group)]
+pub struct JobControl {
+    #[preset_field(
+        display = "Timeout (minutes)",
+        description = "Minutes any job may run before it's cancelled; 0 keeps each job's own limit",
+        default = "0",
+        placeholder = "30",
+        min = 0,
+        max = 360
+    )]
+    pub timeout_minutes: u32,
+
+    #[preset_field(
+        display = "Retries",
+        description = "Times a failed job is run again (GitLab and Jenkins)",
+        default = "0",
+        min = 0,
+        max = 2
+    )]
+    pub retries: u32,
+
+    #[preset_field(
+        display = "Allow Failure",
+        description = "Let the pipeline pass even when a job fails",
+        default = "false"
+    )]
+    pub allow_failure: bool,
+}
+
+impl JobControl {
+    /// Set each job's `timeout-minutes` and `continue-on-error`
+    pub fn apply_github(&self, workflow: &mut GitHubWorkflow) {
+        for job in workflow.jobs.values_mut() {
+            if self.timeout_minutes > 0 {
+                job.timeout_minutes = Some(self.timeout_minutes);
+            }
+            if self.allow_failure {
+                job.continue_on_error = Some(true);
+            }
+        }
+    }
+
+    /// Set each job's `timeout`, `retry` and `allow_failure`
+    pub fn apply_gitlab(&self, ci: &mut GitLabCI) {
+        for job in ci.jobs.values_mut() {
+            if self.timeout_minutes > 0 {
+                job.timeout = Some(format!("{}m", self.timeout_minutes));
+            }
+            if self.retries > 0 {
+                job.retry = Some(self.retries);
+            }
+            if self.allow_failure {
+                job.allow_failure = Some(true);
+            }
+        }
+    }
+
+    /// Set `no_output_timeout` on each named `run` step
+    pub fn apply_circleci(&self, config: &mut CircleCIConfig) {
+        if self.timeout_minutes == 0 {
+            return;
+        }
+        let timeout = format!("{}m", self.timeout_minutes);
+        for step in config.jobs.values_mut().flat_map(|job| &mut job.steps) {
+            let CircleCIStep::Command { run } = step else {
+                continue;
+            };
+            if let CircleCIRun::Detailed { name, command }
+            | CircleCIRun::Timed { name, command, .. } = run
+            {
+                *run = CircleCIRun::Timed {
+                    name: std::mem::take(name),
+                    command: std::mem::take(command),
+                    no_output_timeout: timeout.clone(),
+                };
+            }
+        }
+    }
+
+    /// Wrap each stage's steps in `retry`, `timeout` and `catchError` blocks
+    pub fn apply_jenkins(&self, config: &mut JenkinsConfig) {
+        let mut wrappers = Vec::new();
+        if self.retries > 0 {
+            // `retry` counts the first attempt too
+            wrappers.push(format!("retry({})", self.retries + 1));
+        }
+        if self.timeout_minutes > 0 {
+            wrappers.push(format!(
+                "timeout(time: {}, unit: 'MINUTES')",
+                self.timeout_minutes
+            ));
+        }
+        if self.allow_failure {
+            wrappers.push("catchError(buildResult: 'SUCCESS', stageResult: 'FAILURE')".to_string());
+        }
+        for stage in &mut config.stages {
+            for wrapper in &wrappers {
+                let steps = std::mem::take(&mut stage.steps);
+                stage.steps.push(format!("{} {{", wrapper));
+                stage
+                    .steps
+                    .extend(steps.into_iter().map(|step| format!("    {}", step)));
+                stage.steps.push("}".to_string());
+            }
+        }
+    }
+}
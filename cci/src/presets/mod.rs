@@ -1,9 +1,27 @@
+pub mod addons;
+pub mod custom;
 pub mod docker;
 pub mod go;
+pub mod job_control;
+pub mod multi_arch;
 pub mod python;
 pub mod rust;
+pub mod workflow;
 
+pub use addons::{
+    CodeScanningConfig, CodeScanningPreset, DeployConfig, DeployPreset, NightlyConfig,
+    NightlyPreset, NotificationsConfig, NotificationsPreset, NotifyChannel,
+    ReleaseAutomationConfig, ReleaseAutomationPreset, ReleaseConfig, ReleaseKind, ReleasePreset,
+    ReleaseTool, ReleaseType,
+};
+pub use custom::{CustomConfig, CustomPreset};
 pub use docker::{DockerConfig, DockerPreset, DockerRegistry};
 pub use go::{GoAppConfig, GoAppPreset};
+pub use job_control::{JobControl, JobControlConfig};
+pub use multi_arch::{MultiArch, MultiArchConfig};
 pub use python::{PythonAppConfig, PythonAppPreset, PythonFormatter, PythonLinter};
 pub use rust::{RustConfig, RustPreset};
+pub use workflow::{
+    GitHubSettings, GitHubSettingsConfig, RunnerSettings, RunnerSettingsConfig, Triggers,
+    TriggersConfig,
+};
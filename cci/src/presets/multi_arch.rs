@@ -0,0 +1,70 @@
+//! Multi-architecture image builds, for presets that build containers to
+//! flatten into their own options
+//!
+//! ```text
+//! #[preset_field(flatten, feature = "multi_arch", feature_display = "Multi-arch Images")]
+//! pub(super) multi_arch: MultiArch,
+//! ```
+
+use crate::platforms::triggers::split_list;
+use cci_macros::Preset;
+
+/// Building one image for several CPU architectures with QEMU and Buildx
+#[derive(Debug, Clone, Preset)]
+#[preset(group)]
+pub struct MultiArch {
+    #[preset_field(
+        display = "Multi-arch Build",
+        description = "Build the image for several CPU architectures with QEMU and Buildx",
+        default = "false",
+        github_step = "docker/setup-qemu-action"
+    )]
+    pub enable_multi_arch: bool,
+
+    #[preset_field(
+        display = "Target Platforms",
+        description = "Comma-separated platforms the image is built for",
+        default = "\"linux/amd64, linux/arm64\".to_string()",
+        placeholder = "linux/amd64, linux/arm64",
+        depends_on = "enable_multi_arch"
+    )]
+    pub platforms: String,
+}
+
+impl MultiArch {
+    /// The `--platform` value, or `None` to build for the runner's own
+    pub fn platform_arg(&self) -> Option<String> {
+        let platforms = split_list(&self.platforms);
+        (self.enable_multi_arch && !platforms.is_empty()).then(|| platforms.join(","))
+    }
+
+    /// Commands registering QEMU emulators and a Buildx builder, for
+    /// platforms without setup actions
+    pub fn setup_commands(&self) -> Vec<String> {
+        match self.platform_arg() {
+            Some(_) => vec![
+                "docker run --privileged --rm tonistiigi/binfmt --install all".to_string(),
+                "docker buildx create --use".to_string(),
+            ],
+            None => Vec::new(),
+        }
+    }
+
+    /// The build command without its tag, file and context arguments;
+    /// multi-arch images only exist in the builder, so they're pushed as
+    /// part of the build
+    pub fn build_command(&self, push: bool) -> String {
+        match self.platform_arg() {
+            Some(platforms) if push => {
+                format!("docker buildx build --platform {} --push", platforms)
+            }
+            Some(platforms) => format!("docker buildx build --platform {}", platforms),
+            None => "docker build".to_string(),
+        }
+    }
+
+    /// Whether the image still needs a `docker push` after the build
+    pub fn needs_push(&self) -> bool {
+        self.platform_arg().is_none()
+    }
+}
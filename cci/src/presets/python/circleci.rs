@@ -1,98 +1,17 @@
+use crate::editor::state::Platform;
 use crate::error::Result;
+use crate::models::adapters::{CircleCIAdapter, PlatformAdapter};
 use crate::platforms::circleci::models::CircleCIConfig;
-use crate::traits::ToCircleCI;
+use crate::traits::{ToCircleCI, ToPipeline};
 
 use super::PythonAppPreset;
 
 impl ToCircleCI for PythonAppPreset {
     fn to_circleci(&self) -> Result<CircleCIConfig> {
-        use crate::platforms::circleci::models::*;
-        use std::collections::BTreeMap;
-
-        let mut steps = vec![
-            CircleCIStep::Simple("checkout".to_string()),
-            CircleCIStep::Command {
-                run: CircleCIRun::Detailed {
-                    name: "Install dependencies".to_string(),
-                    command: "pip install -r requirements.txt".to_string(),
-                },
-            },
-        ];
-
-        if let Some(linter) = &self.linter {
-            steps.push(CircleCIStep::Command {
-                run: CircleCIRun::Detailed {
-                    name: format!("Install {}", linter.name()),
-                    command: format!("pip install {}", linter.name()),
-                },
-            });
-            steps.push(CircleCIStep::Command {
-                run: CircleCIRun::Detailed {
-                    name: "Lint".to_string(),
-                    command: linter.check_command().to_string(),
-                },
-            });
-        }
-
-        if self.enable_type_check {
-            steps.push(CircleCIStep::Command {
-                run: CircleCIRun::Detailed {
-                    name: "Install mypy".to_string(),
-                    command: "pip install mypy".to_string(),
-                },
-            });
-            steps.push(CircleCIStep::Command {
-                run: CircleCIRun::Detailed {
-                    name: "Type check".to_string(),
-                    command: "mypy .".to_string(),
-                },
-            });
-        }
-
-        if let Some(formatter) = &self.formatter {
-            steps.push(CircleCIStep::Command {
-                run: CircleCIRun::Detailed {
-                    name: format!("Install {}", formatter.name()),
-                    command: format!("pip install {}", formatter.name()),
-                },
-            });
-            steps.push(CircleCIStep::Command {
-                run: CircleCIRun::Detailed {
-                    name: "Format check".to_string(),
-                    command: formatter.check_command().to_string(),
-                },
-            });
-        }
-
-        steps.push(CircleCIStep::Command {
-            run: CircleCIRun::Detailed {
-                name: "Run tests".to_string(),
-                command: "pytest".to_string(),
-            },
-        });
-
-        let mut jobs = BTreeMap::new();
-        jobs.insert(
-            "python/test".to_string(),
-            CircleCIJob {
-                docker: vec![CircleCIDocker {
-                    image: format!("python:{}", self.python_version),
-                }],
-                steps,
-                environment: None,
-            },
-        );
-
-        Ok(CircleCIConfig {
-            version: "2.1".to_string(),
-            orbs: None,
-            jobs,
-            workflows: BTreeMap::from([(
-                "main".to_string(),
-                CircleCIWorkflow {
-                    jobs: vec![CircleCIWorkflowJob::Simple("python/test".to_string())],
-                },
-            )]),
-        })
+        let pipeline = self.to_pipeline(Platform::CircleCI)?;
+        let mut config = CircleCIAdapter::lower(&pipeline, &mut Vec::new());
+        self.runner.config().apply_circleci(&mut config);
+        self.job_control.apply_circleci(&mut config);
+        Ok(config)
     }
 }
@@ -1,177 +1,23 @@
+use crate::editor::state::Platform;
 use crate::error::Result;
-use crate::platforms::github::models::{
-    GitHubJob, GitHubStep, GitHubTriggerConfig, GitHubTriggers, GitHubWorkflow,
-};
-use crate::traits::ToGitHub;
-use std::collections::BTreeMap;
+use crate::models::adapters::{GitHubAdapter, PlatformAdapter};
+use crate::platforms::github::models::GitHubWorkflow;
+use crate::traits::{ToGitHub, ToPipeline};
 
 use super::PythonAppPreset;
 
 impl ToGitHub for PythonAppPreset {
     fn to_github(&self) -> Result<GitHubWorkflow> {
-        let mut jobs = BTreeMap::new();
-
-        // Test job (always present)
-        let test_steps = vec![
-            GitHubStep {
-                name: Some("Checkout code".to_string()),
-                uses: Some("actions/checkout@v4".to_string()),
-                run: None,
-                with: None,
-                env: None,
-            },
-            GitHubStep {
-                name: Some("Setup Python".to_string()),
-                uses: Some("actions/setup-python@v5".to_string()),
-                run: None,
-                with: Some(BTreeMap::from([(
-                    "python-version".to_string(),
-                    serde_yaml::Value::String(self.python_version.clone()),
-                )])),
-                env: None,
-            },
-            GitHubStep {
-                name: Some("Install dependencies".to_string()),
-                uses: None,
-                run: Some("pip install -r requirements.txt".to_string()),
-                with: None,
-                env: None,
-            },
-            GitHubStep {
-                name: Some("Run tests".to_string()),
-                uses: None,
-                run: Some("pytest".to_string()),
-                with: None,
-                env: None,
-            },
-        ];
-
-        jobs.insert(
-            "python/test".to_string(),
-            GitHubJob {
-                runs_on: "ubuntu-latest".to_string(),
-                steps: test_steps,
-                needs: None,
-                timeout_minutes: Some(30),
-                continue_on_error: None,
-            },
-        );
-
-        // Lint job (optional)
-        if let Some(linter) = &self.linter {
-            let linter_name = linter.name();
-            let linter_cmd = linter.check_command();
-
-            jobs.insert(
-                "python/lint".to_string(),
-                GitHubJob {
-                    runs_on: "ubuntu-latest".to_string(),
-                    steps: vec![
-                        GitHubStep {
-                            name: Some("Checkout code".to_string()),
-                            uses: Some("actions/checkout@v4".to_string()),
-                            run: None,
-                            with: None,
-                            env: None,
-                        },
-                        GitHubStep {
-                            name: Some("Setup Python".to_string()),
-                            uses: Some("actions/setup-python@v5".to_string()),
-                            run: None,
-                            with: Some(BTreeMap::from([(
-                                "python-version".to_string(),
-                                serde_yaml::Value::String(self.python_version.clone()),
-                            )])),
-                            env: None,
-                        },
-                        GitHubStep {
-                            name: Some(format!("Install {}", linter_name)),
-                            uses: None,
-                            run: Some(format!("pip install {}", linter_name)),
-                            with: None,
-                            env: None,
-                        },
-                        GitHubStep {
-                            name: Some(format!("Run {}", linter_name)),
-                            uses: None,
-                            run: Some(linter_cmd.to_string()),
-                            with: None,
-                            env: None,
-                        },
-                    ],
-                    needs: None,
-                    timeout_minutes: Some(15),
-                    continue_on_error: None,
-                },
-            );
+        let pipeline = self.to_pipeline(Platform::GitHub)?;
+        let mut workflow = GitHubAdapter::lower(&pipeline, &mut Vec::new());
+        if let (Some(coverage), Some(test)) =
+            (self.coverage(), workflow.jobs.get_mut("python/test"))
+        {
+            coverage.apply_github(test);
         }
-
-        // Type check job (optional)
-        if self.enable_type_check {
-            jobs.insert(
-                "python/type-check".to_string(),
-                GitHubJob {
-                    runs_on: "ubuntu-latest".to_string(),
-                    steps: vec![
-                        GitHubStep {
-                            name: Some("Checkout code".to_string()),
-                            uses: Some("actions/checkout@v4".to_string()),
-                            run: None,
-                            with: None,
-                            env: None,
-                        },
-                        GitHubStep {
-                            name: Some("Setup Python".to_string()),
-                            uses: Some("actions/setup-python@v5".to_string()),
-                            run: None,
-                            with: Some(BTreeMap::from([(
-                                "python-version".to_string(),
-                                serde_yaml::Value::String(self.python_version.clone()),
-                            )])),
-                            env: None,
-                        },
-                        GitHubStep {
-                            name: Some("Install mypy".to_string()),
-                            uses: None,
-                            run: Some("pip install mypy".to_string()),
-                            with: None,
-                            env: None,
-                        },
-                        GitHubStep {
-                            name: Some("Run mypy".to_string()),
-                            uses: None,
-                            run: Some("mypy .".to_string()),
-                            with: None,
-                            env: None,
-                        },
-                    ],
-                    needs: None,
-                    timeout_minutes: Some(15),
-                    continue_on_error: None,
-                },
-            );
-        }
-
-        Ok(GitHubWorkflow {
-            name: "CI".to_string(),
-            on: GitHubTriggers::Detailed(BTreeMap::from([
-                (
-                    "push".to_string(),
-                    GitHubTriggerConfig {
-                        branches: Some(vec!["main".to_string(), "master".to_string()]),
-                        tags: None,
-                    },
-                ),
-                (
-                    "pull_request".to_string(),
-                    GitHubTriggerConfig {
-                        branches: Some(vec!["main".to_string(), "master".to_string()]),
-                        tags: None,
-                    },
-                ),
-            ])),
-            env: None,
-            jobs,
-        })
+        self.runner.config().apply_github(&mut workflow);
+        self.job_control.apply_github(&mut workflow);
+        self.github.apply_github(&mut workflow)?;
+        Ok(workflow)
     }
 }
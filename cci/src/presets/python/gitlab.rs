@@ -1,57 +1,22 @@
+use crate::editor::state::Platform;
 use crate::error::Result;
+use crate::models::adapters::{GitLabAdapter, PlatformAdapter};
+use crate::platforms::coverage::gitlab_total;
 use crate::platforms::gitlab::models::GitLabCI;
-use crate::traits::ToGitLab;
+use crate::traits::{ToGitLab, ToPipeline};
 
 use super::PythonAppPreset;
 
 impl ToGitLab for PythonAppPreset {
     fn to_gitlab(&self) -> Result<GitLabCI> {
-        use crate::platforms::gitlab::models::*;
-        use std::collections::BTreeMap;
-
-        let mut jobs = BTreeMap::new();
-
-        let mut script = vec![
-            "pip install -r requirements.txt".to_string(),
-            "pytest".to_string(),
-        ];
-
-        if let Some(linter) = &self.linter {
-            script.insert(1, format!("pip install {}", linter.name()));
-            script.insert(2, linter.check_command().to_string());
-        }
-
-        if self.enable_type_check {
-            script.insert(1, "pip install mypy".to_string());
-            script.insert(2, "mypy .".to_string());
+        let pipeline = self.to_pipeline(Platform::GitLab)?;
+        let mut ci = GitLabAdapter::lower(&pipeline, &mut Vec::new());
+        if let (Some(coverage), Some(test)) = (self.coverage(), ci.jobs.get_mut("python/test")) {
+            coverage.apply_gitlab(test, gitlab_total::PYTEST_COV);
         }
-
-        if let Some(formatter) = &self.formatter {
-            script.insert(1, format!("pip install {}", formatter.name()));
-            script.insert(2, formatter.check_command().to_string());
-        }
-
-        jobs.insert(
-            "python/test".to_string(),
-            GitLabJob {
-                stage: "test".to_string(),
-                image: Some(format!("python:{}", self.python_version)),
-                script,
-                before_script: None,
-                after_script: None,
-                needs: None,
-                cache: None,
-                artifacts: None,
-                only: None,
-                timeout: None,
-            },
-        );
-
-        Ok(GitLabCI {
-            stages: Some(vec!["test".to_string()]),
-            variables: None,
-            cache: None,
-            jobs,
-        })
+        self.runner.config().apply_gitlab(&mut ci);
+        self.job_control.apply_gitlab(&mut ci);
+        self.github.apply_gitlab(&mut ci);
+        Ok(ci)
     }
 }
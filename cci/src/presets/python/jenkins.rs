@@ -1,32 +1,20 @@
+use crate::editor::state::Platform;
 use crate::error::Result;
+use crate::models::adapters::{JenkinsAdapter, PlatformAdapter};
 use crate::platforms::jenkins::models::JenkinsConfig;
-use crate::traits::ToJenkins;
+use crate::traits::{ToJenkins, ToPipeline};
 
 use super::PythonAppPreset;
 
 impl ToJenkins for PythonAppPreset {
     fn to_jenkins(&self) -> Result<JenkinsConfig> {
-        use crate::platforms::jenkins::models::*;
-
-        let mut test_steps = vec!["sh 'pip install -r requirements.txt'".to_string()];
-
-        if self.enable_type_check {
-            test_steps.push("sh 'mypy .'".to_string());
-        }
-
-        if let Some(linter) = &self.linter {
-            test_steps.push(format!("sh '{}'", linter.check_command()));
+        let pipeline = self.to_pipeline(Platform::Jenkins)?;
+        let mut config = JenkinsAdapter::lower(&pipeline, &mut Vec::new());
+        if let Some(coverage) = self.coverage() {
+            coverage.apply_jenkins(&mut config);
         }
-
-        test_steps.push("sh 'pytest'".to_string());
-
-        Ok(JenkinsConfig {
-            agent: "any".to_string(),
-            environment: vec![],
-            stages: vec![JenkinsStage {
-                name: "Test".to_string(),
-                steps: test_steps,
-            }],
-        })
+        self.runner.config().apply_jenkins(&mut config);
+        self.job_control.apply_jenkins(&mut config);
+        Ok(config)
     }
 }
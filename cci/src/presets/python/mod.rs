@@ -1,12 +1,30 @@
-use crate::traits::PresetInfo;
+use crate::editor::config::{OptionValue, PresetConfig};
+use crate::editor::state::Platform;
+use crate::platforms::cache::{CacheMode, CacheStrategy};
+use crate::platforms::checks::{CheckKind, LocalCheck};
+use crate::platforms::coverage::{CoverageFormat, CoverageProvider, CoverageUpload};
+use crate::platforms::env::PipelineEnv;
+use crate::platforms::github::models::GitHubWorkflow;
+use crate::platforms::matrix::Matrix;
+use crate::platforms::secrets::Secret;
+use crate::platforms::services::{tune_database, ServiceKind, Services};
+use crate::presets::job_control::{JobControl, JobControlConfig};
+use crate::presets::workflow::{
+    GitHubSettings, GitHubSettingsConfig, RunnerSettings, RunnerSettingsConfig, Triggers,
+    TriggersConfig,
+};
+use crate::traits::{
+    DecoratesPipelines, DetectedDefaults, LocalChecks, PresetInfo, RequiresSecrets, SupportFiles,
+};
 use cci_macros::{Preset, PresetEnum};
+use std::collections::HashMap;
 
 mod circleci;
-mod detectable;
 mod gitea;
 mod github;
 mod gitlab;
 mod jenkins;
+mod pipeline;
 
 /// Linter tool options for Python
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, PresetEnum)]
@@ -82,7 +100,9 @@ impl PythonFormatter {
     id = "python-app",
     name = "Python",
     description = "CI pipeline for Python applications with pytest, linting, and type checking",
-    matches = "PythonApp | PythonLibrary"
+    matches = "PythonApp | PythonLibrary",
+    detect_uses = "setup-python",
+    detect_run = "pytest"
 )]
 pub struct PythonAppPreset {
     #[preset_field(default = "\"3.11\".to_string()", hidden = true)]
@@ -93,7 +113,7 @@ pub struct PythonAppPreset {
         feature_display = "Linting",
         display = "Linter",
         description = "Choose linter tool (None, Flake8, or Ruff)",
-        default = "None"
+        default = "Some(PythonLinter::Flake8)"
     )]
     pub(super) linter: Option<PythonLinter>,
 
@@ -102,28 +122,191 @@ pub struct PythonAppPreset {
         feature_display = "Testing",
         display = "Type Checking",
         description = "Enable mypy static type checking",
-        default = "false"
+        default = "false",
+        github_step = "mypy"
     )]
     pub(super) enable_type_check: bool,
 
+    #[preset_field(
+        feature = "testing",
+        feature_display = "Testing",
+        display = "Python Versions",
+        description = "Comma-separated Python versions to test against (e.g., 3.10, 3.11, 3.12)",
+        default = "String::new()",
+        validation = "Validation::version_list(\"3.11, 3.12\")"
+    )]
+    pub(super) python_versions: String,
+
+    #[preset_field(
+        feature = "testing",
+        feature_display = "Testing",
+        display = "Code Coverage",
+        description = "Measure coverage with pytest-cov and upload the report",
+        default = "false",
+        github_step = "pytest-cov"
+    )]
+    pub(super) enable_coverage: bool,
+
+    #[preset_field(
+        feature = "testing",
+        feature_display = "Testing",
+        display = "Coverage Provider",
+        description = "Service coverage reports are uploaded to",
+        default = "CoverageProvider::Codecov",
+        depends_on = "enable_coverage"
+    )]
+    pub(super) coverage_provider: CoverageProvider,
+
     #[preset_field(
         feature = "formatting",
         feature_display = "Formatting",
         display = "Formatter",
         description = "Choose formatter tool (None, Black, or Ruff)",
-        default = "None"
+        default = "Some(PythonFormatter::Black)"
     )]
     pub(super) formatter: Option<PythonFormatter>,
+
+    #[preset_field(
+        feature = "services",
+        feature_display = "Services",
+        display = "Database Service",
+        description = "Start a database container for the test job (None, PostgreSQL, MySQL, or Redis)",
+        default = "None"
+    )]
+    pub(super) database: Option<ServiceKind>,
+
+    #[preset_field(
+        feature = "caching",
+        feature_display = "Caching",
+        display = "Cache Strategy",
+        description = "Action-based uses the toolchain action's built-in cache; manual emits explicit actions/cache keys",
+        default = "CacheMode::Action"
+    )]
+    pub(super) cache_strategy: CacheMode,
+
+    #[preset_field(flatten, feature = "github", feature_display = "GitHub Actions")]
+    pub(super) github: GitHubSettings,
+
+    #[preset_field(flatten, feature = "runner", feature_display = "Runner")]
+    pub(super) runner: RunnerSettings,
+
+    #[preset_field(flatten, feature = "job_control", feature_display = "Job Control")]
+    pub(super) job_control: JobControl,
+
+    #[preset_field(flatten, feature = "triggers", feature_display = "Triggers")]
+    pub(super) triggers: Triggers,
 }
 
 impl PythonAppPreset {
-    /// Constant default instance for registry initialization
-    pub const DEFAULT: Self = Self {
-        python_version: String::new(),
-        linter: None,
-        enable_type_check: false,
-        formatter: None,
-    };
+    /// Test job matrix over the configured Python versions, if any
+    pub(super) fn version_matrix(&self) -> Option<Matrix> {
+        Matrix::from_list("python_version", &self.python_versions)
+    }
+
+    /// Command that runs the test suite, writing coverage.xml if coverage is enabled
+    pub(super) fn test_command(&self) -> &'static str {
+        if self.enable_coverage {
+            "pytest --cov --cov-report=term --cov-report=xml"
+        } else {
+            "pytest"
+        }
+    }
+
+    /// Upload of the pytest-cov report, if coverage is enabled
+    pub(super) fn coverage(&self) -> Option<CoverageUpload> {
+        self.enable_coverage.then(|| {
+            CoverageUpload::new(
+                self.coverage_provider,
+                "coverage.xml",
+                CoverageFormat::Cobertura,
+            )
+        })
+    }
+
+    /// Service containers for the test job
+    pub(super) fn services(&self) -> Services {
+        Services::from_options(self.database)
+    }
+
+    /// Dependency cache shared by all platform backends
+    pub(super) fn cache(&self) -> CacheStrategy {
+        CacheStrategy::python(self.cache_strategy)
+    }
+
+    /// Pipeline-wide variables shared by all platform backends
+    pub(super) fn env(&self) -> PipelineEnv {
+        PipelineEnv::python()
+    }
+}
+
+impl RequiresSecrets for PythonAppPreset {
+    fn required_secrets(&self, _platform: Platform) -> Vec<Secret> {
+        // Every backend uploads coverage with the provider's token
+        self.coverage()
+            .map(|coverage| coverage.required_secrets())
+            .unwrap_or_default()
+    }
+}
+
+impl SupportFiles for PythonAppPreset {}
+
+impl DecoratesPipelines for PythonAppPreset {}
+
+impl DetectedDefaults for PythonAppPreset {
+    fn tune_defaults(&self, metadata: &HashMap<String, String>, config: &mut PresetConfig) {
+        tune_database(metadata, config);
+        // FastAPI is built on type hints, so they're worth checking
+        if metadata.get("framework").map(String::as_str) == Some("fastapi") {
+            config.set("enable_type_check".to_string(), OptionValue::Bool(true));
+        }
+    }
+
+    fn extract_github(&self, workflow: &GitHubWorkflow, config: &mut PresetConfig) {
+        if let Some(versions) = Matrix::list_from_github(workflow, "python_version") {
+            config.set("python_versions".to_string(), OptionValue::String(versions));
+        }
+    }
+}
+
+impl LocalChecks for PythonAppPreset {
+    fn local_checks(&self) -> Vec<LocalCheck> {
+        let mut checks = Vec::new();
+        if let Some(formatter) = &self.formatter {
+            checks.push(LocalCheck::new(
+                "python/format",
+                "Check formatting",
+                CheckKind::Format,
+                formatter.check_command(),
+                &["py"],
+            ));
+        }
+        if let Some(linter) = &self.linter {
+            checks.push(LocalCheck::new(
+                "python/lint",
+                &format!("Run {}", linter.name()),
+                CheckKind::Lint,
+                linter.check_command(),
+                &["py"],
+            ));
+        }
+        if self.enable_type_check {
+            checks.push(LocalCheck::new(
+                "python/type-check",
+                "Run mypy",
+                CheckKind::Lint,
+                "mypy .",
+                &["py"],
+            ));
+        }
+        checks.push(LocalCheck::new(
+            "python/test",
+            "Run tests",
+            CheckKind::Test,
+            "pytest",
+            &["py"],
+        ));
+        checks
+    }
 }
 
 impl PresetInfo for PythonAppPreset {
@@ -0,0 +1,127 @@
+use crate::editor::state::Platform;
+use crate::error::Result;
+use crate::models::steps::{self, Toolchain};
+use crate::models::{Job, Pipeline, Step};
+use crate::platforms::matrix::Matrix;
+use crate::traits::ToPipeline;
+
+use super::PythonAppPreset;
+
+impl PythonAppPreset {
+    /// Checkout, then `version` of Python where the job doesn't run in a
+    /// Python image
+    fn setup(&self, platform: Platform, version: &str, cache: bool) -> Vec<Step> {
+        let mut setup = vec![steps::checkout()];
+        if platform.runs_actions() {
+            let inputs = match cache {
+                true => self.cache().github_setup_inputs(),
+                false => Vec::new(),
+            };
+            setup.push(Toolchain::Python.setup(version, inputs));
+        }
+        setup
+    }
+
+    /// Install `tool` with pip, then run `command`
+    fn tool_steps(&self, platform: Platform, tool: &str, command: &str) -> Vec<Step> {
+        let mut tool_steps = self.setup(platform, &self.python_version, false);
+        tool_steps.extend([
+            steps::run(format!("Install {}", tool), format!("pip install {}", tool)),
+            steps::run(format!("Run {}", tool), command),
+        ]);
+        tool_steps
+    }
+}
+
+impl ToPipeline for PythonAppPreset {
+    fn to_pipeline(&self, platform: Platform) -> Result<Pipeline> {
+        let job = |id: &str, name: &str, timeout: u32, steps: Vec<Step>| Job {
+            id: id.to_string(),
+            name: Some(name.to_string()),
+            stage: Some("test".to_string()),
+            image: platform
+                .runs_jobs_in_images()
+                .then(|| format!("python:{}", self.python_version)),
+            runner: None,
+            needs: Vec::new(),
+            env: Default::default(),
+            timeout_minutes: Some(timeout),
+            matrix: None,
+            services: Vec::new(),
+            cache: None,
+            artifacts: Vec::new(),
+            steps,
+        };
+        let mut jobs = Vec::new();
+
+        // Test job (always present), optionally across several Python versions;
+        // Jenkins stages run once, on the preset's own version
+        let matrix = self
+            .version_matrix()
+            .filter(|_| platform != Platform::Jenkins);
+        let test_python_version = match &matrix {
+            Some(_) => Matrix::var(platform, "python_version"),
+            None => self.python_version.clone(),
+        };
+        let mut test_steps = self.setup(platform, &test_python_version, true);
+        test_steps.push(steps::run(
+            "Install dependencies",
+            "pip install -r requirements.txt",
+        ));
+        if self.coverage().is_some() {
+            test_steps.push(steps::run("Install pytest-cov", "pip install pytest-cov"));
+        }
+        test_steps.push(steps::run("Run tests", self.test_command()));
+        test_steps.extend(
+            self.coverage()
+                .map(|coverage| steps::coverage_upload(&coverage)),
+        );
+        jobs.push(Job {
+            image: platform
+                .runs_jobs_in_images()
+                .then(|| format!("python:{}", test_python_version)),
+            matrix,
+            services: self.services().services,
+            cache: Some(self.cache()),
+            ..job("python/test", "Test", 30, test_steps)
+        });
+
+        // Lint job (optional)
+        if let Some(linter) = &self.linter {
+            jobs.push(job(
+                "python/lint",
+                "Lint",
+                15,
+                self.tool_steps(platform, linter.name(), linter.check_command()),
+            ));
+        }
+
+        // Type check job (optional)
+        if self.enable_type_check {
+            jobs.push(job(
+                "python/type-check",
+                "Type Check",
+                15,
+                self.tool_steps(platform, "mypy", "mypy ."),
+            ));
+        }
+
+        // Format check job (optional)
+        if let Some(formatter) = &self.formatter {
+            jobs.push(job(
+                "python/format",
+                "Format Check",
+                10,
+                self.tool_steps(platform, formatter.name(), formatter.check_command()),
+            ));
+        }
+
+        let mut pipeline = Pipeline {
+            triggers: self.triggers.config(),
+            env: Default::default(),
+            jobs,
+        };
+        self.env().apply(&mut pipeline);
+        Ok(pipeline)
+    }
+}
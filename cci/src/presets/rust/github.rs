@@ -1,240 +1,51 @@
+use crate::editor::state::Platform;
 use crate::error::Result;
-use crate::platforms::github::models::{
-    GitHubJob, GitHubStep, GitHubTriggerConfig, GitHubTriggers, GitHubWorkflow,
-};
-use crate::traits::ToGitHub;
+use crate::models::adapters::{GitHubAdapter, PlatformAdapter};
+use crate::models::Step;
+use crate::platforms::github::models::GitHubWorkflow;
+use crate::platforms::os::OsMatrix;
+use crate::traits::{ToGitHub, ToPipeline};
 use std::collections::BTreeMap;
 
 use super::RustPreset;
 
 impl ToGitHub for RustPreset {
     fn to_github(&self) -> Result<GitHubWorkflow> {
-        let mut jobs = BTreeMap::new();
-
-        // Test job (always present)
-        let mut test_steps = vec![
-            GitHubStep {
-                name: Some("Checkout code".to_string()),
-                uses: Some("actions/checkout@v4".to_string()),
-                run: None,
-                with: None,
-                env: None,
-            },
-            GitHubStep {
-                name: Some("Setup Rust toolchain".to_string()),
-                uses: Some("dtolnay/rust-toolchain@stable".to_string()),
-                run: None,
-                with: Some(BTreeMap::from([(
-                    "toolchain".to_string(),
-                    serde_yaml::Value::String(self.rust_version.clone()),
-                )])),
-                env: None,
-            },
-            GitHubStep {
-                name: Some("Cache dependencies".to_string()),
-                uses: Some("Swatinem/rust-cache@v2".to_string()),
-                run: None,
-                with: None,
-                env: None,
-            },
-            GitHubStep {
-                name: Some("Run tests".to_string()),
-                uses: None,
-                run: Some("cargo test --all-features".to_string()),
-                with: None,
-                env: None,
-            },
-        ];
-
-        if self.enable_coverage {
-            test_steps.push(GitHubStep {
-                name: Some("Install tarpaulin".to_string()),
-                uses: None,
-                run: Some("cargo install cargo-tarpaulin".to_string()),
-                with: None,
-                env: None,
-            });
-            test_steps.push(GitHubStep {
-                name: Some("Generate coverage".to_string()),
-                uses: None,
-                run: Some("cargo tarpaulin --out Xml --all-features".to_string()),
-                with: None,
-                env: None,
-            });
-            test_steps.push(GitHubStep {
-                name: Some("Upload coverage to Codecov".to_string()),
-                uses: Some("codecov/codecov-action@v3".to_string()),
-                run: None,
-                with: None,
-                env: None,
-            });
+        let pipeline = self.to_pipeline(Platform::GitHub)?;
+        let mut workflow = GitHubAdapter::lower(&pipeline, &mut Vec::new());
+        if let (Some(coverage), Some(test)) = (self.coverage(), workflow.jobs.get_mut("rust/test"))
+        {
+            coverage.apply_github(test);
         }
-
-        if self.build_release {
-            test_steps.push(GitHubStep {
-                name: Some("Build release binary".to_string()),
-                uses: None,
-                run: Some("cargo build --release".to_string()),
-                with: None,
-                env: None,
+        // audit-check reports findings as check runs and issues
+        if let Some(security) = workflow.jobs.get_mut("rust/security") {
+            security.permissions = self.github.read_only_permissions.then(|| {
+                BTreeMap::from([
+                    ("contents".to_string(), "read".to_string()),
+                    ("checks".to_string(), "write".to_string()),
+                    ("issues".to_string(), "write".to_string()),
+                ])
             });
         }
 
-        jobs.insert(
-            "rust/test".to_string(),
-            GitHubJob {
-                runs_on: "ubuntu-latest".to_string(),
-                steps: test_steps,
-                needs: None,
-                timeout_minutes: Some(30),
-                continue_on_error: None,
-            },
-        );
-
-        // Lint job (optional)
-        if self.enable_linter {
-            jobs.insert(
-                "rust/lint".to_string(),
-                GitHubJob {
-                    runs_on: "ubuntu-latest".to_string(),
-                    steps: vec![
-                        GitHubStep {
-                            name: Some("Checkout code".to_string()),
-                            uses: Some("actions/checkout@v4".to_string()),
-                            run: None,
-                            with: None,
-                            env: None,
-                        },
-                        GitHubStep {
-                            name: Some("Setup Rust toolchain".to_string()),
-                            uses: Some("dtolnay/rust-toolchain@stable".to_string()),
-                            run: None,
-                            with: Some(BTreeMap::from([
-                                (
-                                    "toolchain".to_string(),
-                                    serde_yaml::Value::String(self.rust_version.clone()),
-                                ),
-                                (
-                                    "components".to_string(),
-                                    serde_yaml::Value::String("clippy".to_string()),
-                                ),
-                            ])),
-                            env: None,
-                        },
-                        GitHubStep {
-                            name: Some("Run clippy".to_string()),
-                            uses: None,
-                            run: Some("cargo clippy --all-features -- -D warnings".to_string()),
-                            with: None,
-                            env: None,
-                        },
-                    ],
-                    needs: None,
-                    timeout_minutes: Some(15),
-                    continue_on_error: None,
-                },
-            );
-        }
-
-        // Format check job (optional)
-        if self.enable_format_check {
-            jobs.insert(
-                "rust/format".to_string(),
-                GitHubJob {
-                    runs_on: "ubuntu-latest".to_string(),
-                    steps: vec![
-                        GitHubStep {
-                            name: Some("Checkout code".to_string()),
-                            uses: Some("actions/checkout@v4".to_string()),
-                            run: None,
-                            with: None,
-                            env: None,
-                        },
-                        GitHubStep {
-                            name: Some("Setup Rust toolchain".to_string()),
-                            uses: Some("dtolnay/rust-toolchain@stable".to_string()),
-                            run: None,
-                            with: Some(BTreeMap::from([
-                                (
-                                    "toolchain".to_string(),
-                                    serde_yaml::Value::String(self.rust_version.clone()),
-                                ),
-                                (
-                                    "components".to_string(),
-                                    serde_yaml::Value::String("rustfmt".to_string()),
-                                ),
-                            ])),
-                            env: None,
-                        },
-                        GitHubStep {
-                            name: Some("Check formatting".to_string()),
-                            uses: None,
-                            run: Some("cargo fmt -- --check".to_string()),
-                            with: None,
-                            env: None,
-                        },
-                    ],
-                    needs: None,
-                    timeout_minutes: Some(10),
-                    continue_on_error: None,
-                },
-            );
+        self.runner.config().apply_github(&mut workflow);
+        if let Some(os_matrix) = self.os_matrix() {
+            // tarpaulin only runs on Linux, which also keeps to one upload
+            let coverage: Vec<String> = self.coverage_steps().iter().map(Step::label).collect();
+            if let Some(test) = workflow.jobs.get_mut("rust/test") {
+                test.steps
+                    .iter_mut()
+                    .filter(|step| {
+                        step.name
+                            .as_ref()
+                            .is_some_and(|name| coverage.contains(name))
+                    })
+                    .for_each(OsMatrix::linux_only);
+            }
+            os_matrix.apply_github(&mut workflow, "rust/test");
         }
-
-        // Security scan job (optional)
-        if self.enable_security_scan {
-            jobs.insert(
-                "rust/security".to_string(),
-                GitHubJob {
-                    runs_on: "ubuntu-latest".to_string(),
-                    steps: vec![
-                        GitHubStep {
-                            name: Some("Checkout code".to_string()),
-                            uses: Some("actions/checkout@v4".to_string()),
-                            run: None,
-                            with: None,
-                            env: None,
-                        },
-                        GitHubStep {
-                            name: Some("Run cargo audit".to_string()),
-                            uses: Some("rustsec/audit-check@v1".to_string()),
-                            run: None,
-                            with: Some(BTreeMap::from([(
-                                "token".to_string(),
-                                serde_yaml::Value::String(
-                                    "${{ secrets.GITHUB_TOKEN }}".to_string(),
-                                ),
-                            )])),
-                            env: None,
-                        },
-                    ],
-                    needs: None,
-                    timeout_minutes: Some(10),
-                    continue_on_error: None,
-                },
-            );
-        }
-
-        Ok(GitHubWorkflow {
-            name: "CI".to_string(),
-            on: GitHubTriggers::Detailed(BTreeMap::from([
-                (
-                    "push".to_string(),
-                    GitHubTriggerConfig {
-                        branches: Some(vec!["main".to_string(), "master".to_string()]),
-                        tags: None,
-                    },
-                ),
-                (
-                    "pull_request".to_string(),
-                    GitHubTriggerConfig {
-                        branches: Some(vec!["main".to_string(), "master".to_string()]),
-                        tags: None,
-                    },
-                ),
-            ])),
-            env: None,
-            jobs,
-        })
+        self.job_control.apply_github(&mut workflow);
+        self.github.apply_github(&mut workflow)?;
+        Ok(workflow)
     }
 }
@@ -1,12 +1,31 @@
-use crate::traits::PresetInfo;
+use crate::editor::config::{OptionValue, PresetConfig};
+use crate::editor::state::Platform;
+use crate::platforms::cache::{CacheMode, CacheStrategy};
+use crate::platforms::checks::{CheckKind, LocalCheck};
+use crate::platforms::coverage::{CoverageFormat, CoverageProvider, CoverageUpload};
+use crate::platforms::env::PipelineEnv;
+use crate::platforms::github::models::GitHubWorkflow;
+use crate::platforms::matrix::Matrix;
+use crate::platforms::os::OsMatrix;
+use crate::platforms::secrets::Secret;
+use crate::platforms::services::{tune_database, ServiceKind, Services};
+use crate::presets::job_control::{JobControl, JobControlConfig};
+use crate::presets::workflow::{
+    GitHubSettings, GitHubSettingsConfig, RunnerSettings, RunnerSettingsConfig, Triggers,
+    TriggersConfig,
+};
+use crate::traits::{
+    DecoratesPipelines, DetectedDefaults, LocalChecks, PresetInfo, RequiresSecrets, SupportFiles,
+};
 use cci_macros::Preset;
+use std::collections::HashMap;
 
 mod circleci;
-mod detectable;
 mod gitea;
 mod github;
 mod gitlab;
 mod jenkins;
+mod pipeline;
 
 /// Unified preset for Rust projects (binaries, libraries, and workspaces)
 #[derive(Debug, Clone, Preset)]
@@ -14,7 +33,9 @@ mod jenkins;
     id = "rust",
     name = "Rust",
     description = "CI pipeline for Rust projects (binaries, libraries, and workspaces)",
-    matches = "RustBinary | RustLibrary | RustWorkspace"
+    matches = "RustBinary | RustLibrary | RustWorkspace",
+    detect_uses = "rust-toolchain",
+    detect_run = "cargo test"
 )]
 pub struct RustPreset {
     #[preset_field(default = "\"stable\".to_string()", hidden = true)]
@@ -25,16 +46,48 @@ pub struct RustPreset {
         feature_display = "Testing",
         display = "Code Coverage",
         description = "Enable code coverage reporting with tarpaulin",
-        default = "false"
+        default = "true",
+        github_step = "cargo tarpaulin"
     )]
     pub(super) enable_coverage: bool,
 
+    #[preset_field(
+        feature = "testing",
+        feature_display = "Testing",
+        display = "Coverage Provider",
+        description = "Service coverage reports are uploaded to",
+        default = "CoverageProvider::Codecov",
+        depends_on = "enable_coverage"
+    )]
+    pub(super) coverage_provider: CoverageProvider,
+
+    #[preset_field(
+        feature = "testing",
+        feature_display = "Testing",
+        display = "Toolchain Matrix",
+        description = "Comma-separated toolchains to test against (e.g., stable, beta, nightly)",
+        default = "String::new()",
+        validation = "Validation::version_list(\"stable, beta, 1.75\")"
+    )]
+    pub(super) toolchain_matrix: String,
+
+    #[preset_field(
+        feature = "testing",
+        feature_display = "Testing",
+        display = "Operating Systems",
+        description = "Comma-separated systems the test job runs on in GitHub and Gitea workflows (linux, macos, windows); database services need Linux",
+        default = "String::new()",
+        validation = "Validation::os_list()"
+    )]
+    pub(super) os_matrix: String,
+
     #[preset_field(
         feature = "linting",
         feature_display = "Linting",
         display = "Clippy Linter",
         description = "Run Clippy linter for code quality",
-        default = "false"
+        default = "true",
+        github_step = "cargo clippy"
     )]
     pub(super) enable_linter: bool,
 
@@ -43,7 +96,8 @@ pub struct RustPreset {
         feature_display = "Security",
         display = "Security Scan",
         description = "Run cargo-audit for dependency vulnerabilities",
-        default = "false"
+        default = "true",
+        github_step = "rustsec/audit-check"
     )]
     pub(super) enable_security_scan: bool,
 
@@ -52,7 +106,8 @@ pub struct RustPreset {
         feature_display = "Formatting",
         display = "Rustfmt Check",
         description = "Check code formatting with rustfmt",
-        default = "false"
+        default = "true",
+        github_step = "cargo fmt"
     )]
     pub(super) enable_format_check: bool,
 
@@ -61,21 +116,160 @@ pub struct RustPreset {
         feature_display = "Building",
         display = "Build Release",
         description = "Build optimized release binary in CI",
-        default = "false"
+        default = "true",
+        github_step = "cargo build --release"
     )]
     pub(super) build_release: bool,
+
+    #[preset_field(
+        feature = "services",
+        feature_display = "Services",
+        display = "Database Service",
+        description = "Start a database container for the test job (None, PostgreSQL, MySQL, or Redis)",
+        default = "None"
+    )]
+    pub(super) database: Option<ServiceKind>,
+
+    #[preset_field(
+        feature = "caching",
+        feature_display = "Caching",
+        display = "Cache Strategy",
+        description = "Action-based uses the toolchain action's built-in cache; manual emits explicit actions/cache keys",
+        default = "CacheMode::Action"
+    )]
+    pub(super) cache_strategy: CacheMode,
+
+    #[preset_field(flatten, feature = "github", feature_display = "GitHub Actions")]
+    pub(super) github: GitHubSettings,
+
+    #[preset_field(flatten, feature = "runner", feature_display = "Runner")]
+    pub(super) runner: RunnerSettings,
+
+    #[preset_field(flatten, feature = "job_control", feature_display = "Job Control")]
+    pub(super) job_control: JobControl,
+
+    #[preset_field(flatten, feature = "triggers", feature_display = "Triggers")]
+    pub(super) triggers: Triggers,
 }
 
-impl Default for RustPreset {
-    fn default() -> Self {
-        Self {
-            rust_version: "stable".to_string(),
-            enable_coverage: false,
-            enable_linter: false,
-            enable_security_scan: false,
-            enable_format_check: false,
-            build_release: false,
+impl RustPreset {
+    /// Test job matrix over the configured toolchains, if any
+    pub(super) fn toolchain_matrix(&self) -> Option<Matrix> {
+        Matrix::from_list("toolchain", &self.toolchain_matrix)
+    }
+
+    /// Systems besides Linux the test job runs on, if any
+    pub(super) fn os_matrix(&self) -> Option<OsMatrix> {
+        OsMatrix::from_list(&self.os_matrix)
+    }
+
+    /// Upload of the tarpaulin report, if coverage is enabled
+    pub(super) fn coverage(&self) -> Option<CoverageUpload> {
+        self.enable_coverage.then(|| {
+            CoverageUpload::new(
+                self.coverage_provider,
+                "cobertura.xml",
+                CoverageFormat::Cobertura,
+            )
+        })
+    }
+
+    /// Service containers for the test job
+    pub(super) fn services(&self) -> Services {
+        Services::from_options(self.database)
+    }
+
+    /// Dependency cache shared by all platform backends
+    pub(super) fn cache(&self) -> CacheStrategy {
+        CacheStrategy::rust(self.cache_strategy)
+    }
+
+    /// Pipeline-wide variables shared by all platform backends
+    pub(super) fn env(&self) -> PipelineEnv {
+        PipelineEnv::rust()
+    }
+}
+
+impl RequiresSecrets for RustPreset {
+    fn required_secrets(&self, _platform: Platform) -> Vec<Secret> {
+        // Every backend uploads coverage with the provider's token
+        self.coverage()
+            .map(|coverage| coverage.required_secrets())
+            .unwrap_or_default()
+    }
+}
+
+impl SupportFiles for RustPreset {}
+
+impl DecoratesPipelines for RustPreset {}
+
+impl DetectedDefaults for RustPreset {
+    fn tune_defaults(&self, metadata: &HashMap<String, String>, config: &mut PresetConfig) {
+        tune_database(metadata, config);
+    }
+
+    fn extract_github(&self, workflow: &GitHubWorkflow, config: &mut PresetConfig) {
+        if let Some(toolchains) = Matrix::list_from_github(workflow, "toolchain") {
+            config.set(
+                "toolchain_matrix".to_string(),
+                OptionValue::String(toolchains),
+            );
+        }
+        if let Some(systems) = OsMatrix::list_from_github(workflow) {
+            config.set("os_matrix".to_string(), OptionValue::String(systems));
+        }
+    }
+}
+
+impl LocalChecks for RustPreset {
+    fn local_checks(&self) -> Vec<LocalCheck> {
+        let mut checks = Vec::new();
+        if self.enable_format_check {
+            let command = "cargo fmt -- --check";
+            checks.push(LocalCheck::new(
+                "rust/format",
+                "Check formatting",
+                CheckKind::Format,
+                command,
+                &["rs"],
+            ));
+        }
+        if self.enable_linter {
+            let command = "cargo clippy --all-features -- -D warnings";
+            checks.push(LocalCheck::new(
+                "rust/lint",
+                "Run clippy",
+                CheckKind::Lint,
+                command,
+                &["rs"],
+            ));
+        }
+        checks.push(LocalCheck::new(
+            "rust/test",
+            "Run tests",
+            CheckKind::Test,
+            "cargo test --all-features",
+            &["rs"],
+        ));
+        if self.enable_security_scan {
+            checks.push(LocalCheck::new(
+                "rust/security",
+                "Audit dependencies",
+                CheckKind::Audit,
+                "cargo audit",
+                &["lock", "toml"],
+            ));
+        }
+        if self.build_release {
+            checks.push(LocalCheck::new(
+                "rust/build",
+                "Build release",
+                CheckKind::Build,
+                "cargo build --release",
+                &["rs"],
+            ));
         }
+        checks
     }
 }
 
@@ -93,6 +287,7 @@ impl PresetInfo for RustPreset {
 mod tests {
     use super::*;
     use crate::traits::{PresetInfo, ToGitHub};
+    use std::collections::BTreeMap;
 
     #[test]
     fn test_default() {
@@ -104,6 +299,12 @@ mod tests {
         assert!(preset.enable_security_scan);
         assert!(preset.enable_format_check);
         assert!(preset.build_release);
+        // The workflow settings take the defaults their groups declare
+        assert_eq!(preset.triggers.workflow_name, "CI");
+        assert_eq!(preset.triggers.push_branches, "main, master");
+        assert_eq!(preset.triggers.pr_branches, "main, master");
+        assert!(preset.github.read_only_permissions);
+        assert!(preset.github.cancel_in_progress);
     }
 
     #[test]
@@ -115,6 +316,7 @@ mod tests {
             enable_security_scan: false,
             enable_format_check: false,
             build_release: true,
+            ..RustPreset::default()
         };
 
         assert_eq!(preset.rust_version, "1.75.0");
@@ -132,6 +334,7 @@ mod tests {
             enable_security_scan: false,
             enable_format_check: false,
             build_release: false,
+            ..RustPreset::default()
         };
         let workflow = preset.to_github().unwrap();
 
@@ -149,6 +352,7 @@ mod tests {
             enable_security_scan: false,
             enable_format_check: false,
             build_release: false,
+            ..RustPreset::default()
         };
         let workflow = preset.to_github().unwrap();
 
@@ -156,6 +360,224 @@ mod tests {
         assert!(workflow.jobs.contains_key("rust/lint"));
     }
 
+    #[test]
+    fn test_to_github_permissions_and_concurrency() {
+        let workflow = RustPreset::default().to_github().unwrap();
+        assert_eq!(
+            workflow.permissions,
+            Some(BTreeMap::from([(
+                "contents".to_string(),
+                "read".to_string()
+            )]))
+        );
+        assert!(workflow.concurrency.unwrap().cancel_in_progress);
+
+        let preset = RustPreset {
+            github: GitHubSettings {
+                read_only_permissions: false,
+                cancel_in_progress: false,
+                pin_actions: false,
+            },
+            ..RustPreset::default()
+        };
+        let workflow = preset.to_github().unwrap();
+        assert!(workflow.permissions.is_none());
+        assert!(workflow.concurrency.is_none());
+        assert!(workflow.jobs["rust/security"].permissions.is_none());
+    }
+
+    #[test]
+    fn test_os_matrix() {
+        let preset = RustPreset {
+            os_matrix: "linux, macos, windows".to_string(),
+            toolchain_matrix: "stable, beta".to_string(),
+            ..RustPreset::default()
+        };
+        let workflow = preset.to_github().unwrap();
+        let test = &workflow.jobs["rust/test"];
+        let axes = &test.strategy.as_ref().unwrap().matrix.axes;
+        assert_eq!(
+            axes["os"],
+            ["ubuntu-latest", "macos-latest", "windows-latest"]
+        );
+        assert_eq!(axes["toolchain"], ["stable", "beta"]);
+        assert_eq!(test.runs_on, "${{ matrix.os }}".into());
+        assert_eq!(
+            workflow.defaults.as_ref().unwrap().run.shell.as_deref(),
+            Some("bash")
+        );
+        let coverage = test
+            .steps
+            .iter()
+            .find(|step| step.name.as_deref() == Some("Generate coverage"))
+            .unwrap();
+        assert_eq!(
+            coverage.if_condition.as_deref(),
+            Some("runner.os == 'Linux'")
+        );
+        // Other jobs keep the selected runner
+        assert_eq!(workflow.jobs["rust/lint"].runs_on, "ubuntu-latest".into());
+
+        let mut config = PresetConfig::new("rust".to_string());
+        preset.extract_github(&workflow, &mut config);
+        assert_eq!(
+            config.get_string("os_matrix").as_deref(),
+            Some("linux, macos, windows")
+        );
+    }
+
+    #[test]
+    fn test_job_control() {
+        use crate::traits::{ToCircleCI, ToGitLab, ToJenkins};
+
+        let preset = RustPreset {
+            job_control: JobControl {
+                timeout_minutes: 45,
+                retries: 1,
+                allow_failure: true,
+            },
+            ..RustPreset::default()
+        };
+        let workflow = preset.to_github().unwrap();
+        for job in workflow.jobs.values() {
+            assert_eq!(job.timeout_minutes, Some(45));
+            assert_eq!(job.continue_on_error, Some(true));
+        }
+
+        let ci = preset.to_gitlab().unwrap();
+        let test = &ci.jobs["rust/test"];
+        assert_eq!(test.timeout.as_deref(), Some("45m"));
+        assert_eq!((test.retry, test.allow_failure), (Some(1), Some(true)));
+
+        let config = preset.to_circleci().unwrap();
+        let yaml = serde_yaml::to_string(&config).unwrap();
+        assert!(yaml.contains("no_output_timeout: 45m"));
+
+        let jenkins = preset.to_jenkins().unwrap();
+        assert_eq!(
+            jenkins.stages[0].steps[..3],
+            [
+                "catchError(buildResult: 'SUCCESS', stageResult: 'FAILURE') {",
+                "    timeout(time: 45, unit: 'MINUTES') {",
+                "        retry(2) {",
+            ]
+        );
+        assert_eq!(jenkins.stages[0].steps.last().unwrap(), "}");
+
+        // Left alone, jobs keep their own limits
+        let workflow = RustPreset::default().to_github().unwrap();
+        assert_eq!(workflow.jobs["rust/test"].timeout_minutes, Some(30));
+        assert!(workflow.jobs["rust/test"].continue_on_error.is_none());
+    }
+
+    #[test]
+    fn test_to_gitlab_needs_and_coverage() {
+        use crate::traits::ToGitLab;
+
+        let ci = RustPreset::default().to_gitlab().unwrap();
+        assert_eq!(
+            ci.jobs["rust/build"].needs,
+            Some(vec!["rust/test".to_string()])
+        );
+        assert_eq!(ci.jobs["rust/lint"].needs, Some(Vec::new()));
+        assert!(ci.jobs["rust/test"].coverage.is_some());
+        assert_eq!(ci.default.unwrap().interruptible, Some(true));
+    }
+
+    #[test]
+    fn test_coverage_provider() {
+        use crate::traits::ToGitLab;
+
+        let preset = RustPreset {
+            coverage_provider: CoverageProvider::Coveralls,
+            ..RustPreset::default()
+        };
+        let workflow = preset.to_github().unwrap();
+        let upload = workflow.jobs["rust/test"]
+            .steps
+            .iter()
+            .find(|step| step.name.as_deref() == Some("Upload coverage to Coveralls"))
+            .unwrap();
+        assert_eq!(
+            upload.uses.as_deref(),
+            Some("coverallsapp/github-action@v2")
+        );
+
+        let ci = preset.to_gitlab().unwrap();
+        assert!(ci.jobs["rust/test"]
+            .script
+            .iter()
+            .any(|line| line.contains("./coveralls report cobertura.xml")));
+
+        assert_eq!(
+            preset.required_secrets(Platform::GitLab),
+            vec![crate::platforms::secrets::COVERALLS_REPO_TOKEN]
+        );
+        let preset = RustPreset {
+            enable_coverage: false,
+            ..preset
+        };
+        assert!(preset.required_secrets(Platform::GitHub).is_empty());
+    }
+
+    #[test]
+    fn test_toolchain_matrix() {
+        use crate::platforms::circleci::models::CircleCIWorkflowJob;
+        use crate::traits::{ToCircleCI, ToGitLab};
+
+        let workflow = RustPreset::default().to_github().unwrap();
+        assert!(workflow.jobs["rust/test"].strategy.is_none());
+
+        let preset = RustPreset {
+            toolchain_matrix: "stable, beta, nightly".to_string(),
+            ..RustPreset::default()
+        };
+
+        let workflow = preset.to_github().unwrap();
+        let test = &workflow.jobs["rust/test"];
+        assert_eq!(
+            test.strategy.as_ref().unwrap().matrix.axes["toolchain"],
+            vec!["stable", "beta", "nightly"]
+        );
+        assert_eq!(
+            test.steps[1].with.as_ref().unwrap()["toolchain"],
+            serde_yaml::Value::String("${{ matrix.toolchain }}".to_string())
+        );
+
+        let ci = preset.to_gitlab().unwrap();
+        let test = &ci.jobs["rust/test"];
+        assert!(test.script[0].contains("--default-toolchain $TOOLCHAIN"));
+        assert!(test.parallel.as_ref().unwrap().matrix[0].contains_key("TOOLCHAIN"));
+
+        let config = preset.to_circleci().unwrap();
+        assert!(config.jobs["rust/test"]
+            .parameters
+            .as_ref()
+            .unwrap()
+            .contains_key("toolchain"));
+        assert!(matches!(
+            &config.workflows["main"].jobs[0],
+            CircleCIWorkflowJob::WithRequires { job } if job["rust/test"].matrix.is_some()
+        ));
+    }
+
+    #[test]
+    fn test_detects_own_pipelines() {
+        use crate::presets::docker::DockerPreset;
+        use crate::traits::{Detectable, ToCircleCI, ToGitLab, ToGitea, ToJenkins};
+
+        let preset = RustPreset::default();
+        assert!(preset.matches_github(&preset.to_github().unwrap()));
+        assert!(preset.matches_gitea(&preset.to_gitea().unwrap()));
+        assert!(preset.matches_gitlab(&preset.to_gitlab().unwrap()));
+        assert!(preset.matches_circleci(&preset.to_circleci().unwrap()));
+        assert!(preset.matches_jenkins(&preset.to_jenkins().unwrap()));
+
+        let docker = DockerPreset::default();
+        assert!(!preset.matches_github(&docker.to_github().unwrap()));
+        assert!(!preset.matches_gitlab(&docker.to_gitlab().unwrap()));
+    }
+
     #[test]
     fn test_preset_info() {
         let preset = RustPreset::default();
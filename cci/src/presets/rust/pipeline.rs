@@ -0,0 +1,172 @@
+use crate::editor::state::Platform;
+use crate::error::Result;
+use crate::models::steps::{self, Toolchain};
+use crate::models::{Job, Pipeline, Step};
+use crate::platforms::artifacts::Artifact;
+use crate::platforms::matrix::Matrix;
+use crate::traits::ToPipeline;
+
+use super::RustPreset;
+
+/// Image jobs run in on the platforms that run them in one
+const IMAGE: &str = "rust:latest";
+
+impl RustPreset {
+    /// Steps installing `toolchain`, with a rustup `component` such as clippy
+    ///
+    /// Actions workflows use the toolchain action; elsewhere rustup installs
+    /// it in the image.
+    fn setup(&self, platform: Platform, toolchain: &str, component: Option<&str>) -> Vec<Step> {
+        if platform.runs_actions() {
+            let components =
+                component.map(|component| ("components".to_string(), component.into()));
+            return vec![Toolchain::Rust.setup(toolchain, components)];
+        }
+        let (name, flag) = match component {
+            Some(component) => (
+                format!("Install Rust with {}", component),
+                format!(" --component {}", component),
+            ),
+            None => ("Install Rust".to_string(), String::new()),
+        };
+        let mut setup = vec![steps::run(
+            name,
+            format!(
+                "curl --proto '=https' --tlsv1.2 -sSf https://sh.rustup.rs | sh -s -- -y --default-toolchain {}{}",
+                toolchain, flag
+            ),
+        )];
+        // CircleCI steps already find cargo on the image's PATH
+        if platform != Platform::CircleCI {
+            setup.push(Step::Run {
+                name: None,
+                command: "source $HOME/.cargo/env".to_string(),
+            });
+        }
+        setup
+    }
+
+    /// Coverage report generation and upload, if coverage is enabled
+    pub(super) fn coverage_steps(&self) -> Vec<Step> {
+        let Some(coverage) = self.coverage() else {
+            return Vec::new();
+        };
+        vec![
+            steps::run("Install tarpaulin", "cargo install cargo-tarpaulin"),
+            steps::run(
+                "Generate coverage",
+                "cargo tarpaulin --out Xml --all-features",
+            ),
+            steps::coverage_upload(&coverage),
+        ]
+    }
+}
+
+impl ToPipeline for RustPreset {
+    fn to_pipeline(&self, platform: Platform) -> Result<Pipeline> {
+        let job = |id: &str, name: &str, stage: &str, timeout: u32, steps: Vec<Step>| Job {
+            id: id.to_string(),
+            name: Some(name.to_string()),
+            stage: Some(stage.to_string()),
+            image: platform.runs_jobs_in_images().then(|| IMAGE.to_string()),
+            runner: None,
+            needs: Vec::new(),
+            env: Default::default(),
+            timeout_minutes: Some(timeout),
+            matrix: None,
+            services: Vec::new(),
+            cache: None,
+            artifacts: Vec::new(),
+            steps,
+        };
+        let mut jobs = Vec::new();
+
+        // Test job (always present), optionally across several toolchains;
+        // Jenkins stages run once, on the preset's own toolchain
+        let matrix = self
+            .toolchain_matrix()
+            .filter(|_| platform != Platform::Jenkins);
+        let test_toolchain = match &matrix {
+            Some(_) => Matrix::var(platform, "toolchain"),
+            None => self.rust_version.clone(),
+        };
+        let mut test_steps = vec![steps::checkout()];
+        test_steps.extend(self.setup(platform, &test_toolchain, None));
+        test_steps.push(steps::run("Run tests", "cargo test --all-features"));
+        test_steps.extend(self.coverage_steps());
+        jobs.push(Job {
+            matrix,
+            services: self.services().services,
+            cache: Some(self.cache()),
+            ..job("rust/test", "Test", "test", 30, test_steps)
+        });
+
+        // Release build (optional), once the tests pass
+        if self.build_release {
+            let mut build_steps = vec![steps::checkout()];
+            build_steps.extend(self.setup(platform, &self.rust_version, None));
+            build_steps.push(steps::run("Build release", "cargo build --release"));
+            jobs.push(Job {
+                needs: vec!["rust/test".to_string()],
+                cache: Some(self.cache()),
+                artifacts: vec![Artifact::new("release", &["target/release/"])],
+                ..job("rust/build", "Build", "build", 30, build_steps)
+            });
+        }
+
+        // Lint job (optional)
+        if self.enable_linter {
+            let mut lint_steps = vec![steps::checkout()];
+            lint_steps.extend(self.setup(platform, &self.rust_version, Some("clippy")));
+            lint_steps.push(steps::run(
+                "Run clippy",
+                "cargo clippy --all-features -- -D warnings",
+            ));
+            jobs.push(Job {
+                cache: Some(self.cache()),
+                ..job("rust/lint", "Lint", "lint", 15, lint_steps)
+            });
+        }
+
+        // Format check job (optional)
+        if self.enable_format_check {
+            let mut format_steps = vec![steps::checkout()];
+            format_steps.extend(self.setup(platform, &self.rust_version, Some("rustfmt")));
+            format_steps.push(steps::run("Check formatting", "cargo fmt -- --check"));
+            jobs.push(job("rust/format", "Format Check", "lint", 10, format_steps));
+        }
+
+        // Security scan job (optional)
+        if self.enable_security_scan {
+            let audit = if platform.runs_actions() {
+                vec![steps::action(
+                    "Run cargo audit",
+                    "rustsec/audit-check@v1",
+                    [("token".to_string(), "${{ secrets.GITHUB_TOKEN }}".into())],
+                )]
+            } else {
+                vec![
+                    steps::run("Install cargo-audit", "cargo install cargo-audit"),
+                    steps::run("Run cargo audit", "cargo audit"),
+                ]
+            };
+            let mut security_steps = vec![steps::checkout()];
+            security_steps.extend(audit);
+            jobs.push(job(
+                "rust/security",
+                "Security Scan",
+                "security",
+                10,
+                security_steps,
+            ));
+        }
+
+        let mut pipeline = Pipeline {
+            triggers: self.triggers.config(),
+            env: Default::default(),
+            jobs,
+        };
+        self.env().apply(&mut pipeline);
+        Ok(pipeline)
+    }
+}
@@ -0,0 +1,175 @@
+//! Triggers, runner and GitHub Actions settings of a preset's workflow, for
+//! presets to flatten into their own options
+//!
+//! ```text
+//! #[preset_field(flatten, feature = "triggers", feature_display = "Triggers")]
+//! pub(super) triggers: Triggers,
+//! #[preset_field(flatten, feature = "runner", feature_display = "Runner")]
+//! pub(super) runner: RunnerSettings,
+//! #[preset_field(flatten, feature = "github", feature_display = "GitHub Actions")]
+//! pub(super) github: GitHubSettings,
+//! ```
+//!
+//! These are the workflow settings `WORKFLOW_SETTING_FEATURES` names, so
+//! their toggles keep their defaults even for presets that weren't detected.
+//! A preset whose workflow reads differently gives the group defaults of its
+//! own, e.g. `default = "Triggers { tags: ..., ..Triggers::default() }"`.
+
+use crate::error;
+use crate::platforms::github::models::GitHubWorkflow;
+use crate::platforms::github::pinning::pin_workflow;
+use crate::platforms::gitlab::models::GitLabCI;
+use crate::platforms::runner::{RunnerConfig, RunnerKind};
+use crate::platforms::triggers::{split_list, TriggerConfig};
+use cci_macros::Preset;
+
+/// What starts the workflow and what it's called
+#[derive(Debug, Clone, Preset)]
+#[preset(group)]
+pub struct Triggers {
+    #[preset_field(
+        display = "Workflow Name",
+        description = "Name shown for the generated workflow/pipeline",
+        default = "\"CI\".to_string()"
+    )]
+    pub workflow_name: String,
+
+    #[preset_field(
+        display = "Push Branches",
+        description = "Comma-separated branches that trigger a run on push",
+        default = "\"main, master\".to_string()"
+    )]
+    pub push_branches: String,
+
+    #[preset_field(
+        display = "PR Branches",
+        description = "Comma-separated target branches that trigger a run on pull requests",
+        default = "\"main, master\".to_string()"
+    )]
+    pub pr_branches: String,
+
+    #[preset_field(
+        display = "Tags",
+        description = "Comma-separated tag patterns that trigger a run (e.g., v*)",
+        default = "String::new()"
+    )]
+    pub tags: String,
+
+    #[preset_field(
+        display = "Schedule (cron)",
+        description = "Cron expression for scheduled runs (empty to disable)",
+        default = "String::new()",
+        validation = "Validation::cron()"
+    )]
+    pub schedule_cron: String,
+
+    #[preset_field(
+        display = "Manual Dispatch",
+        description = "Allow starting the pipeline manually",
+        default = "false"
+    )]
+    pub manual_dispatch: bool,
+
+    #[preset_field(
+        display = "Paths",
+        description = "Comma-separated globs; pushes and pull requests only run when a changed file matches one (GitHub, Gitea and GitLab)",
+        default = "String::new()",
+        placeholder = "src/**"
+    )]
+    pub paths: String,
+}
+
+impl Triggers {
+    /// The triggers every platform backend writes
+    pub fn config(&self) -> TriggerConfig {
+        let mut triggers = TriggerConfig::from_options(
+            &self.workflow_name,
+            &self.push_branches,
+            &self.pr_branches,
+            &self.tags,
+            &self.schedule_cron,
+            self.manual_dispatch,
+        );
+        triggers.paths = split_list(&self.paths);
+        triggers
+    }
+}
+
+/// The machine jobs run on
+#[derive(Debug, Clone, Preset)]
+#[preset(group)]
+pub struct RunnerSettings {
+    #[preset_field(
+        display = "Runner",
+        description = "Machine type jobs run on",
+        default = "RunnerKind::UbuntuLatest"
+    )]
+    pub runner: RunnerKind,
+
+    #[preset_field(
+        display = "Runner Labels / Image",
+        description = "Comma-separated self-hosted runner labels, or the container image",
+        default = "String::new()",
+        placeholder = "self-hosted, linux"
+    )]
+    pub runner_label: String,
+}
+
+impl RunnerSettings {
+    /// The runner every platform backend picks
+    pub fn config(&self) -> RunnerConfig {
+        RunnerConfig::from_options(self.runner, &self.runner_label)
+    }
+}
+
+/// Hardening and concurrency of GitHub and Gitea workflows
+#[derive(Debug, Clone, Preset)]
+#[preset(group)]
+pub struct GitHubSettings {
+    #[preset_field(
+        display = "Least-privilege Permissions",
+        description = "Limit GITHUB_TOKEN to read-only access (contents: read)",
+        default = "true"
+    )]
+    pub read_only_permissions: bool,
+
+    #[preset_field(
+        display = "Cancel Superseded Runs",
+        description = "Cancel in-progress runs when a newer commit is pushed to the same ref",
+        default = "true"
+    )]
+    pub cancel_in_progress: bool,
+
+    #[preset_field(
+        display = "Pin Actions to SHAs",
+        description = "Reference actions by commit SHA instead of mutable tags",
+        default = "false"
+    )]
+    pub pin_actions: bool,
+}
+
+impl GitHubSettings {
+    /// Limit the token, cancel superseded runs and pin actions
+    ///
+    /// Pinning only covers the steps already there, so this comes last.
+    pub fn apply_github(&self, workflow: &mut GitHubWorkflow) -> error::Result<()> {
+        if self.read_only_permissions {
+            workflow.set_read_only_permissions();
+        }
+        if self.cancel_in_progress {
+            workflow.set_cancel_in_progress();
+        }
+        if self.pin_actions {
+            pin_workflow(workflow)?;
+        }
+        Ok(())
+    }
+
+    /// Let newer pipelines cancel superseded jobs, GitLab's take on
+    /// cancelling in-progress runs
+    pub fn apply_gitlab(&self, ci: &mut GitLabCI) {
+        if self.cancel_in_progress {
+            ci.set_interruptible();
+        }
+    }
+}
@@ -1,9 +1,17 @@
+use crate::editor::config::PresetConfig;
+use crate::editor::state::Platform;
 use crate::error::Result;
+use crate::models::Pipeline;
+use crate::platforms::checks::LocalCheck;
 use crate::platforms::circleci::models::CircleCIConfig;
 use crate::platforms::gitea::models::GiteaWorkflow;
 use crate::platforms::github::models::GitHubWorkflow;
 use crate::platforms::gitlab::models::GitLabCI;
+use crate::platforms::helpers::PlatformConfig;
 use crate::platforms::jenkins::models::JenkinsConfig;
+use crate::platforms::secrets::Secret;
+use std::collections::HashMap;
+use std::path::PathBuf;
 
 /// Trait for converting a preset to GitHub Actions workflow
 pub trait ToGitHub {
@@ -30,7 +38,74 @@ pub trait ToJenkins {
     fn to_jenkins(&self) -> Result<JenkinsConfig>;
 }
 
+/// Trait for presets that describe their pipeline once, in the
+/// platform-neutral [`Pipeline`] model
+///
+/// Their `To*` implementations lower the pipeline with the platform's
+/// [`PlatformAdapter`](crate::models::adapters::PlatformAdapter), then only
+/// add what the model doesn't describe, such as GitHub token permissions.
+pub trait ToPipeline {
+    /// The pipeline as `platform` runs it; `platform` only picks between
+    /// steps and images written for particular platforms
+    fn to_pipeline(&self, platform: Platform) -> Result<Pipeline>;
+}
+
+/// Trait for declaring the secrets a preset's generated config references
+pub trait RequiresSecrets {
+    /// Secrets that must be configured on `platform` before the pipeline can pass
+    ///
+    /// Presets that only use credentials the platform provides itself can
+    /// rely on the default.
+    fn required_secrets(&self, _platform: Platform) -> Vec<Secret> {
+        Vec::new()
+    }
+}
+
+/// Trait for presets whose pipelines read files besides the CI config itself
+pub trait SupportFiles {
+    /// Files to write next to the config for `platform`, relative to the project root
+    fn support_files(&self, _platform: Platform) -> Result<Vec<(PathBuf, String)>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Trait for presets whose jobs run commands developers can run locally
+pub trait LocalChecks {
+    /// Checks mirroring the generated jobs, in the order CI runs them
+    fn local_checks(&self) -> Vec<LocalCheck> {
+        Vec::new()
+    }
+}
+
+/// Trait for addons that extend the pipelines generated by other presets
+pub trait DecoratesPipelines {
+    /// Add this preset's jobs or steps to another preset's platform config
+    fn decorate(&self, _pipeline: &mut PlatformConfig) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Trait for presets whose defaults depend on what detection found besides
+/// the project type, e.g. the framework in its `metadata`
+pub trait DetectedDefaults {
+    /// Whether the project calls for this preset though its type doesn't
+    /// match, e.g. a CLI that ships binaries calling for releases
+    fn matches_metadata(&self, _metadata: &HashMap<String, String>) -> bool {
+        false
+    }
+
+    /// Adjust the detected defaults in `config` to the project
+    fn tune_defaults(&self, _metadata: &HashMap<String, String>, _config: &mut PresetConfig) {}
+
+    /// Read the options of a matching GitHub workflow that the generated
+    /// `extract_config` doesn't, e.g. a test matrix, into `config`
+    fn extract_github(&self, _workflow: &GitHubWorkflow, _config: &mut PresetConfig) {}
+}
+
 /// Trait for detecting if a preset matches an existing config
+///
+/// `#[preset(detect_uses = "...", detect_run = "...")]` derives it from the
+/// actions and commands the preset's pipelines run.
 pub trait Detectable {
     /// Check if this preset matches a GitHub Actions workflow
     fn matches_github(&self, workflow: &GitHubWorkflow) -> bool;
@@ -0,0 +1,413 @@
+//! Running the `cci` binary against scratch repositories
+
+use assert_cmd::cargo::cargo_bin_cmd;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+/// `cci` run in `dir`, without colors so output can be matched
+fn cci(dir: &TempDir) -> Command {
+    let mut command = cargo_bin_cmd!("cci");
+    command.current_dir(dir.path()).env("NO_COLOR", "1");
+    command
+}
+
+#[test]
+fn test_lint_prints_paths_relative_to_the_repository() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join(".gitlab-ci.yml"),
+        "test:\n  script:\n    - cargo test\n",
+    )
+    .unwrap();
+
+    cci(&dir)
+        .arg("lint")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("✓ .gitlab-ci.yml\n"))
+        .stdout(predicate::str::contains("./").not());
+}
+
+/// A Rust project with a cci.ron using the Rust preset
+fn rust_project() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join("Cargo.toml"),
+        "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )
+    .unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/main.rs"), "fn main() {}\n").unwrap();
+    fs::write(
+        dir.path().join("cci.ron"),
+        "(version: 3, presets: [Rust((rust_version: \"stable\"))])\n",
+    )
+    .unwrap();
+    dir
+}
+
+/// Stdout of a finished command, parsed as JSON
+fn json(output: &std::process::Output) -> serde_json::Value {
+    serde_json::from_slice(&output.stdout).expect("stdout is JSON")
+}
+
+#[test]
+fn test_detect_json() {
+    let dir = rust_project();
+    let output = cci(&dir)
+        .args(["detect", "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let report = json(&output);
+    assert!(report["project_type"].as_str().unwrap().contains("Rust"));
+    assert!(report["confidence"].as_u64().unwrap() <= 100);
+    assert!(report["matching_presets"]
+        .as_array()
+        .unwrap()
+        .contains(&"rust".into()));
+    for list in ["other_project_types", "members", "facets", "workflows"] {
+        assert!(report[list].is_array(), "{} is a list", list);
+    }
+    assert!(report["metadata"].is_object());
+}
+
+#[test]
+fn test_validate_json() {
+    let dir = rust_project();
+    let output = cci(&dir)
+        .args(["validate", "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let report = json(&output);
+    assert_eq!(report["valid"], true);
+    assert_eq!(report["presets"], serde_json::json!(["rust"]));
+    assert!(report.get("error").is_none());
+
+    fs::write(dir.path().join("cci.ron"), "(presets: [").unwrap();
+    let output = cci(&dir)
+        .args(["validate", "--format", "json"])
+        .output()
+        .unwrap();
+    let report = json(&output);
+    assert_eq!(report["valid"], false);
+    assert!(report["error"].is_string());
+}
+
+#[test]
+fn test_check_json() {
+    let dir = rust_project();
+    let output = cci(&dir)
+        .args(["check", "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+
+    let report = json(&output);
+    assert_eq!(report["up_to_date"], false);
+    let files = report["files"].as_array().unwrap();
+    assert!(!files.is_empty());
+    for file in files {
+        assert_eq!(file["status"], "missing");
+        assert!(file["path"].is_string());
+        assert!(file["platform"].is_string());
+    }
+
+    cci(&dir).args(["generate", "--no-lint"]).assert().success();
+    let output = cci(&dir)
+        .args(["check", "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let report = json(&output);
+    assert_eq!(report["up_to_date"], true);
+    assert!(report["files"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|file| file["status"] == "ok" && file.get("diff").is_none()));
+}
+
+#[test]
+fn test_presets_list_json() {
+    let dir = TempDir::new().unwrap();
+    let output = cci(&dir)
+        .args(["presets", "list", "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let presets = json(&output);
+    let rust = presets
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|preset| preset["id"] == "rust")
+        .expect("the Rust preset is listed");
+    assert_eq!(rust["kind"], "preset");
+    assert!(rust["name"].is_string());
+    assert!(rust["features"].as_array().unwrap().iter().all(|feature| {
+        feature["options"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|option| option["id"].is_string() && option["type"].is_string())
+    }));
+
+    // Features are listed in the order the preset declares them
+    let features: Vec<&str> = rust["features"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|feature| feature["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        features,
+        [
+            "testing",
+            "linting",
+            "security",
+            "formatting",
+            "building",
+            "services",
+            "caching",
+            "github",
+            "runner",
+            "job_control",
+            "triggers",
+        ]
+    );
+}
+
+#[test]
+fn test_format_is_only_accepted_where_it_is_used() {
+    let dir = TempDir::new().unwrap();
+    cci(&dir)
+        .args(["presets", "list", "--json"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unexpected argument '--json'"));
+    cci(&dir)
+        .args(["lint", "--format", "json"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unexpected argument '--format'"));
+    cci(&dir)
+        .args(["lint", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--format").not());
+}
+
+/// Whether `generate` wrote any of the GitHub or GitLab files
+fn wrote_pipelines(dir: &TempDir) -> bool {
+    dir.path().join(".github").exists() || dir.path().join(".gitlab-ci.yml").exists()
+}
+
+#[test]
+fn test_generate_stdout_prints_instead_of_writing() {
+    let dir = rust_project();
+    cci(&dir)
+        .args(["generate", "--stdout"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("name: CI\n"))
+        .stdout(predicate::str::contains("cargo test --all-features"))
+        .stdout(predicate::str::contains("==>").not());
+    assert!(!wrote_pipelines(&dir));
+
+    // Several files are each headed by their path
+    cci(&dir)
+        .args(["generate", "--stdout", "-p", "github,gitlab"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "==> .github/workflows/ci.yml <==\nname: CI\n",
+        ))
+        .stdout(predicate::str::contains("\n==> .gitlab-ci.yml <==\n"));
+    assert!(!wrote_pipelines(&dir));
+}
+
+#[test]
+fn test_generate_stdout_reads_the_config_from_stdin() {
+    let dir = rust_project();
+    let config = fs::read_to_string(dir.path().join("cci.ron")).unwrap();
+    fs::remove_file(dir.path().join("cci.ron")).unwrap();
+
+    cci(&dir)
+        .args(["generate", "-", "--stdout", "-p", "gitlab"])
+        .write_stdin(config)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("cargo test"));
+    assert!(!wrote_pipelines(&dir));
+}
+
+#[test]
+fn test_generate_dry_run_lists_files_without_writing() {
+    let dir = rust_project();
+    cci(&dir)
+        .args(["generate", "--dry-run", "--no-lint", "-p", "github,gitlab"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            ".github/workflows/ci.yml (create)",
+        ))
+        .stdout(predicate::str::contains(".gitlab-ci.yml (create)"))
+        .stdout(predicate::str::contains(
+            "2 file(s) would be created, 0 overwritten",
+        ));
+    assert!(!wrote_pipelines(&dir));
+
+    // Files that would change are listed with their diff and left as they are
+    let config = |coverage: bool| {
+        format!(
+            "(version: 3, presets: [Rust((rust_version: \"stable\", enable_coverage: {}))])\n",
+            coverage
+        )
+    };
+    fs::write(dir.path().join("cci.ron"), config(true)).unwrap();
+    cci(&dir)
+        .args(["generate", "--no-lint", "-p", "gitlab"])
+        .assert()
+        .success();
+    let gitlab = dir.path().join(".gitlab-ci.yml");
+    let generated = fs::read_to_string(&gitlab).unwrap();
+    fs::write(dir.path().join("cci.ron"), config(false)).unwrap();
+    cci(&dir)
+        .args(["generate", "--dry-run", "--no-lint", "-p", "gitlab"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(".gitlab-ci.yml (overwrite)"))
+        .stdout(predicate::str::contains("- cargo tarpaulin"))
+        .stdout(predicate::str::contains(
+            "0 file(s) would be created, 1 overwritten",
+        ))
+        .stdout(predicate::str::contains("--force"));
+    assert_eq!(fs::read_to_string(&gitlab).unwrap(), generated);
+}
+
+#[test]
+fn test_import_reports_workflows_it_cannot_parse() {
+    let dir = TempDir::new().unwrap();
+    let workflows = dir.path().join(".github/workflows");
+    fs::create_dir_all(&workflows).unwrap();
+    fs::write(workflows.join("ci.yml"), "name: CI\non: push\n").unwrap();
+
+    cci(&dir)
+        .arg("import")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Failed to parse"))
+        .stderr(predicate::str::contains("missing field `jobs`"))
+        .stderr(predicate::str::contains("matches a preset").not());
+}
+
+#[test]
+fn test_generate_combines_presets_into_one_gitlab_file() {
+    let dir = rust_project();
+    fs::write(
+        dir.path().join("cci.ron"),
+        "(version: 3, presets: [Rust((rust_version: \"stable\")), Docker((image_name: \"demo\"))])\n",
+    )
+    .unwrap();
+
+    cci(&dir)
+        .args(["generate", "--platform", "gitlab", "--no-lint", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(".gitlab-ci.yml (create)"))
+        .stdout(predicate::str::contains(".gitlab-ci-").not());
+    cci(&dir)
+        .args(["generate", "--platform", "gitlab", "--no-lint"])
+        .assert()
+        .success();
+
+    let ci_files: Vec<String> = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|name| name.starts_with(".gitlab-ci"))
+        .collect();
+    assert_eq!(ci_files, vec![".gitlab-ci.yml"]);
+    let content = fs::read_to_string(dir.path().join(".gitlab-ci.yml")).unwrap();
+    assert!(content.contains("rust/test:"));
+    assert!(content.contains("docker/build:"));
+
+    cci(&dir)
+        .args(["check", "--platform", "gitlab"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_generate_follows_the_configured_file_naming() {
+    let dir = rust_project();
+    fs::write(
+        dir.path().join("cci.ron"),
+        "(version: 3, presets: [Rust((rust_version: \"stable\"))], file_naming: per_preset)\n",
+    )
+    .unwrap();
+
+    cci(&dir)
+        .args(["generate", "--dry-run", "--no-lint", "-p", "github,gitlab"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            ".github/workflows/rust.yml (create)",
+        ))
+        .stdout(predicate::str::contains(".gitlab-ci.yml (create)"));
+}
+
+#[test]
+fn test_check_passes_after_the_editor_writes() {
+    use cci::editor::config::OptionValue;
+    use cci::editor::state::{EditorState, Platform};
+
+    let dir = rust_project();
+    fs::write(
+        dir.path().join("cci.ron"),
+        "(version: 3, presets: [Rust((rust_version: \"stable\")), Python((python_version: \"3.12\"))])\n",
+    )
+    .unwrap();
+    let mut state = EditorState::from_config_file(&dir.path().join("cci.ron")).unwrap();
+    state.set_option_value("rust", "enable_coverage", OptionValue::Bool(true));
+    state.cycle_file_naming();
+    state.extra_platforms.insert(Platform::GitLab);
+    for file in state.planned_writes().unwrap() {
+        let path = state.write_root().join(&file.path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, file.content).unwrap();
+    }
+    state
+        .save_to_config_file(&dir.path().join("cci.ron"))
+        .unwrap();
+
+    cci(&dir)
+        .args(["check", "-p", "github,gitlab"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("✓ .github/workflows/rust.yml"))
+        .stdout(predicate::str::contains(
+            "✓ .github/workflows/python-app.yml",
+        ))
+        .stdout(predicate::str::contains("✓ .gitlab-ci.yml"));
+}
+
+#[test]
+fn test_native_runs_need_a_place_to_run() {
+    let dir = rust_project();
+    cci(&dir)
+        .args(["run", "--native", "rust/test"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("pass --host"));
+    cci(&dir)
+        .args(["run", "--native", "--all-steps", "--dry-run"])
+        .assert()
+        .success();
+}